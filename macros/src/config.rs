@@ -848,6 +848,14 @@ impl<'a> FieldConfig<'a> {
                             #encode
                         }
                     }
+                } else if let Some(default_fn) = &default_fn {
+                    // Values equal to the default are omitted, as with
+                    // implicitly tagged fields.
+                    quote! {
+                        if #this #field != (#default_fn)() {
+                            #encode
+                        }
+                    }
                 } else {
                     encode
                 }
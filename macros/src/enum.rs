@@ -151,6 +151,36 @@ impl Enum {
             |id| quote!(const IDENTIFIER: Option<&'static str> = Some(#id);),
         );
 
+        // An untagged CHOICE is identified by the tag of whichever
+        // alternative is selected, which for alternatives that are
+        // themselves untagged CHOICEs is only known at runtime.
+        let selected_tag = (self.config.choice && self.config.tag.is_none()).then(|| {
+            let tags = self.variants.iter().enumerate().map(|(i, v)| {
+                let ident = &v.ident;
+                let variant_config = VariantConfig::new(v, &self.generics, &self.config);
+                let tag_tokens = variant_config.tag(i).to_tokens(crate_root);
+
+                match &v.fields {
+                    syn::Fields::Unnamed(_)
+                        if variant_config.tag.is_none() && !self.config.automatic_tags =>
+                    {
+                        quote!(Self::#ident(value) => #crate_root::AsnType::selected_tag(value))
+                    }
+                    syn::Fields::Named(_) => quote!(Self::#ident { .. } => #tag_tokens),
+                    syn::Fields::Unnamed(_) => quote!(Self::#ident(_) => #tag_tokens),
+                    syn::Fields::Unit => quote!(Self::#ident => #tag_tokens),
+                }
+            });
+
+            quote! {
+                fn selected_tag(&self) -> #crate_root::Tag {
+                    match self {
+                        #(#tags),*
+                    }
+                }
+            }
+        });
+
         quote! {
             impl #impl_generics #crate_root::AsnType for #name #ty_generics #where_clause {
                 const TAG: #crate_root::Tag = {
@@ -164,6 +194,7 @@ impl Enum {
                 };
                 #alt_identifier
                 #constraints_def
+                #selected_tag
             }
 
             #choice_impl
@@ -331,20 +362,6 @@ impl Enum {
     fn encode_choice(&self, generics: &syn::Generics) -> proc_macro2::TokenStream {
         let crate_root = &self.config.crate_root;
 
-        let tags = self.variants.iter().enumerate().map(|(i, v)| {
-            let ident = &v.ident;
-            let name = &self.name;
-            let variant_config = VariantConfig::new(v, generics, &self.config);
-            let variant_tag = variant_config.tag(i);
-            let tag_tokens = variant_tag.to_tokens(crate_root);
-
-            match &v.fields {
-                syn::Fields::Named(_) => quote!(#name::#ident { .. } => #tag_tokens),
-                syn::Fields::Unnamed(_) => quote!(#name::#ident (_) => #tag_tokens),
-                syn::Fields::Unit => quote!(#name::#ident => #tag_tokens),
-            }
-        });
-
         let variants = self.variants.iter().enumerate().map(|(i, v)| {
             let ident = &v.ident;
             let name = &self.name;
@@ -417,9 +434,7 @@ impl Enum {
         let encode_variants = quote! {
             encoder.encode_choice::<Self>(
                 Self::CONSTRAINTS,
-                match self {
-                    #(#tags),*
-                },
+                #crate_root::AsnType::selected_tag(self),
                 |encoder| match self {
                     #(#variants),*
                 },
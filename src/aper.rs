@@ -492,4 +492,26 @@ mod tests {
         let encoded = rasn::aper::encode(&connect_data).expect("failed to encode");
         let _: ConnectData = rasn::aper::decode(&encoded).expect("failed to decode");
     }
+
+    #[test]
+    fn fixed_octet_string() {
+        #[derive(Debug, AsnType, Decode, Encode, PartialEq)]
+        #[rasn(crate_root = "crate")]
+        struct A {
+            a: bool,
+            b: FixedOctetString<2>,
+            c: FixedOctetString<4>,
+        }
+
+        round_trip!(
+            aper,
+            A,
+            A {
+                a: true,
+                b: [0xff, 0xff].into(),
+                c: [192, 168, 1, 10].into(),
+            },
+            &[0xff, 0xff, 0x80, 0xc0, 0xa8, 0x01, 0x0a]
+        );
+    }
 }
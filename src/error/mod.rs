@@ -5,6 +5,7 @@
 #![allow(clippy::module_name_repetitions)]
 mod decode;
 mod encode;
+mod pem;
 mod string;
 
 pub mod strings {
@@ -24,3 +25,4 @@ pub use encode::EncodeErrorKind;
 pub use encode::{
     BerEncodeErrorKind, CodecEncodeError, EncodeError, JerEncodeErrorKind, XerEncodeErrorKind,
 };
+pub use pem::PemError;
@@ -0,0 +1,27 @@
+use alloc::string::String;
+
+use super::DecodeError;
+
+/// An error that occurred when reading PEM encoded data.
+#[derive(Debug, snafu::Snafu)]
+#[snafu(visibility(pub))]
+#[non_exhaustive]
+pub enum PemError {
+    #[snafu(display("no pre-encapsulation boundary (`-----BEGIN <label>-----`) found"))]
+    MissingBegin,
+    #[snafu(display("no post-encapsulation boundary found for label `{}`", label))]
+    MissingEnd { label: String },
+    #[snafu(display("invalid label `{}`", label))]
+    InvalidLabel { label: String },
+    #[snafu(display("label `{}` doesn't match the closing label `{}`", begin, end))]
+    MismatchedLabels { begin: String, end: String },
+    #[snafu(display("invalid base64 at line {} of the `{}` block", line, label))]
+    InvalidBase64 { label: String, line: usize },
+    #[snafu(display("expected label `{}`, found `{}`", expected, found))]
+    UnexpectedLabel {
+        expected: &'static str,
+        found: String,
+    },
+    #[snafu(display("invalid contents: {}", error))]
+    Decode { error: DecodeError },
+}
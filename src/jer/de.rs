@@ -564,14 +564,13 @@ impl Decoder {
                     .map(|(i, _)| (i, v))
             })
             .map_or(Tag::EOC, |(i, v)| {
-                match variants::Variants::from_slice(
-                    &[D::VARIANTS, D::EXTENDED_VARIANTS.unwrap_or(&[])].concat(),
-                )
-                .get(i)
+                match [D::VARIANTS, D::EXTENDED_VARIANTS.unwrap_or(&[])]
+                    .concat()
+                    .get(i)
                 {
                     Some(t) => {
                         self.stack.push(v.clone());
-                        *t
+                        t.smallest_tag()
                     }
                     None => Tag::EOC,
                 }
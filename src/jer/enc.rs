@@ -6,7 +6,7 @@ use crate::{
     bits::to_vec,
     enc::Error,
     error::{EncodeError, JerEncodeErrorKind},
-    types::{fields::Fields, variants},
+    types::fields::Fields,
 };
 
 pub struct Encoder {
@@ -432,18 +432,10 @@ impl crate::Encoder for Encoder {
         encode_fn: impl FnOnce(&mut Self) -> Result<crate::Tag, Self::Error>,
         _identifier: Option<&'static str>,
     ) -> Result<Self::Ok, Self::Error> {
-        let variants = variants::Variants::from_slice(
-            &[E::VARIANTS, E::EXTENDED_VARIANTS.unwrap_or(&[])].concat(),
-        );
+        let variants = [E::VARIANTS, E::EXTENDED_VARIANTS.unwrap_or(&[])].concat();
 
-        let identifier = variants
-            .iter()
-            .enumerate()
-            .find_map(|(i, &variant_tag)| {
-                (tag == variant_tag)
-                    .then_some(E::IDENTIFIERS.get(i))
-                    .flatten()
-            })
+        let identifier = crate::types::TagTree::tag_index(&tag, &variants)
+            .and_then(|i| E::IDENTIFIERS.get(i))
             .ok_or_else(|| crate::error::EncodeError::variant_not_in_choice(self.codec()))?;
 
         if variants.is_empty() {
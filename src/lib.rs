@@ -50,6 +50,7 @@ pub mod der;
 pub mod error;
 pub mod jer;
 mod num;
+pub mod pem;
 pub mod uper;
 pub mod xer;
 
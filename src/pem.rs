@@ -0,0 +1,416 @@
+//! # PEM
+//!
+//! Textual encoding of DER structures as defined in [RFC 7468], commonly
+//! known as PEM. A PEM document contains one or more blocks, each wrapping
+//! base64 encoded DER between `-----BEGIN <label>-----` and
+//! `-----END <label>-----` lines. Text outside of the blocks is ignored.
+//!
+//! Types that have a registered label implement [`PemLabel`], which allows
+//! them to be used with [`encode`] and [`decode`].
+//!
+//! [RFC 7468]: https://datatracker.ietf.org/doc/html/rfc7468
+
+use alloc::{string::String, vec::Vec};
+
+use crate::error::PemError;
+
+const BEGIN: &str = "-----BEGIN ";
+const END: &str = "-----END ";
+const BOUNDARY_SUFFIX: &str = "-----";
+const LINE_LENGTH: usize = 64;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A type with a PEM label, such as `CERTIFICATE`.
+pub trait PemLabel {
+    /// The label written when encoding the type.
+    const LABEL: &'static str;
+    /// Other labels accepted when decoding the type, as found in the wild.
+    const ALTERNATIVE_LABELS: &'static [&'static str] = &[];
+
+    /// Returns whether `label` identifies this type.
+    fn matches_label(label: &str) -> bool {
+        label == Self::LABEL || Self::ALTERNATIVE_LABELS.contains(&label)
+    }
+}
+
+/// A single PEM block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Block {
+    /// The label identifying the type of the contents.
+    pub label: String,
+    /// The decoded contents, usually DER.
+    pub contents: Vec<u8>,
+}
+
+impl Block {
+    /// Creates a new block.
+    pub fn new(label: impl Into<String>, contents: impl Into<Vec<u8>>) -> Self {
+        Self {
+            label: label.into(),
+            contents: contents.into(),
+        }
+    }
+
+    /// Decodes the contents of the block as DER, checking that the label
+    /// matches `T`.
+    pub fn decode<T: crate::Decode + PemLabel>(&self) -> Result<T, PemError> {
+        if !T::matches_label(&self.label) {
+            return Err(PemError::UnexpectedLabel {
+                expected: T::LABEL,
+                found: self.label.clone(),
+            });
+        }
+
+        crate::der::decode(&self.contents).map_err(|error| PemError::Decode { error })
+    }
+
+    /// Encodes the block as text, wrapping the base64 contents at 64
+    /// characters with `\n` line endings.
+    pub fn to_pem(&self) -> String {
+        let mut output = String::new();
+        output.push_str(BEGIN);
+        output.push_str(&self.label);
+        output.push_str(BOUNDARY_SUFFIX);
+        output.push('\n');
+
+        let encoded = base64_encode(&self.contents);
+        for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+            // Base64 is always ASCII.
+            output.push_str(core::str::from_utf8(line).unwrap());
+            output.push('\n');
+        }
+
+        output.push_str(END);
+        output.push_str(&self.label);
+        output.push_str(BOUNDARY_SUFFIX);
+        output.push('\n');
+        output
+    }
+}
+
+impl core::fmt::Display for Block {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_pem())
+    }
+}
+
+/// Parses the first block in `input`.
+pub fn parse(input: &str) -> Result<Block, PemError> {
+    parse_many(input)
+        .next()
+        .unwrap_or(Err(PemError::MissingBegin))
+}
+
+/// Returns an iterator over every block in `input`, such as a certificate
+/// bundle. Iteration stops after the first error.
+pub fn parse_many(input: &str) -> Blocks<'_> {
+    Blocks { input, done: false }
+}
+
+/// Iterator over the blocks in a PEM document, created by [`parse_many`].
+#[derive(Clone, Debug)]
+pub struct Blocks<'input> {
+    input: &'input str,
+    done: bool,
+}
+
+impl Iterator for Blocks<'_> {
+    type Item = Result<Block, PemError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let Some(start) = find_boundary(self.input, BEGIN) else {
+            self.done = true;
+            return None;
+        };
+
+        let result = parse_block(&self.input[start..]);
+        match &result {
+            Ok((_, rest)) => self.input = rest,
+            Err(_) => self.done = true,
+        }
+        Some(result.map(|(block, _)| block))
+    }
+}
+
+/// Finds `boundary` at the start of a line in `input`.
+fn find_boundary(input: &str, boundary: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.starts_with(boundary) {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Reads the label from a boundary line such as `-----BEGIN CERTIFICATE-----`.
+fn boundary_label<'a>(line: &'a str, prefix: &str) -> Result<&'a str, PemError> {
+    let line = line.trim_end_matches(['\r', '\n', ' ', '\t']);
+    let label = line
+        .strip_prefix(prefix)
+        .and_then(|line| line.strip_suffix(BOUNDARY_SUFFIX))
+        .unwrap_or(line);
+
+    if is_valid_label(label) {
+        Ok(label)
+    } else {
+        Err(PemError::InvalidLabel {
+            label: label.into(),
+        })
+    }
+}
+
+/// Checks a label against the `label` rule of RFC 7468 Section 3, which
+/// allows printable characters other than `-`, separated by single spaces or
+/// hyphens. The empty label is allowed.
+fn is_valid_label(label: &str) -> bool {
+    let mut previous_separator = true;
+    for c in label.chars() {
+        let is_separator = c == ' ' || c == '-';
+        if is_separator && previous_separator || !(c == ' ' || c.is_ascii_graphic()) {
+            return false;
+        }
+        previous_separator = is_separator;
+    }
+    label.is_empty() || !previous_separator
+}
+
+fn parse_block(input: &str) -> Result<(Block, &str), PemError> {
+    let (begin_line, body) = input.split_once('\n').unwrap_or((input, ""));
+    let label = boundary_label(begin_line, BEGIN)?;
+
+    let end = find_boundary(body, END).ok_or_else(|| PemError::MissingEnd {
+        label: label.into(),
+    })?;
+    let (end_line, rest) = body[end..].split_once('\n').unwrap_or((&body[end..], ""));
+    let end_label = boundary_label(end_line, END)?;
+    if end_label != label {
+        return Err(PemError::MismatchedLabels {
+            begin: label.into(),
+            end: end_label.into(),
+        });
+    }
+
+    let mut decoder = Base64Decoder::default();
+    for (index, line) in body[..end].lines().enumerate() {
+        decoder.push(line).ok_or_else(|| PemError::InvalidBase64 {
+            label: label.into(),
+            // Line 1 is the pre-encapsulation boundary.
+            line: index + 2,
+        })?;
+    }
+    let contents = decoder.finish().ok_or_else(|| PemError::InvalidBase64 {
+        label: label.into(),
+        line: body[..end].lines().count() + 1,
+    })?;
+
+    Ok((Block::new(label, contents), rest))
+}
+
+/// Attempts to decode the first block in `input` as `T`.
+pub fn decode<T: crate::Decode + PemLabel>(input: &str) -> Result<T, PemError> {
+    parse(input)?.decode()
+}
+
+/// Attempts to decode every block in `input` as `T`.
+pub fn decode_all<T: crate::Decode + PemLabel>(input: &str) -> Result<Vec<T>, PemError> {
+    parse_many(input).map(|block| block?.decode()).collect()
+}
+
+/// Attempts to encode `value` as a DER PEM block.
+pub fn encode<T: crate::Encode + PemLabel>(value: &T) -> Result<String, crate::error::EncodeError> {
+    Ok(Block::new(T::LABEL, crate::der::encode(value)?).to_pem())
+}
+
+fn base64_encode(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (value >> (18 - index * 6)) & 0x3f;
+                output.push(ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Incremental base64 decoder that skips whitespace, returning `None` on
+/// invalid input.
+#[derive(Default)]
+struct Base64Decoder {
+    output: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+    padding: u8,
+}
+
+impl Base64Decoder {
+    fn push(&mut self, line: &str) -> Option<()> {
+        for byte in line.bytes() {
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+
+            if byte == b'=' {
+                self.padding += 1;
+                if self.padding > 2 {
+                    return None;
+                }
+                continue;
+            } else if self.padding > 0 {
+                return None;
+            }
+
+            let value = ALPHABET.iter().position(|c| *c == byte)? as u32;
+            self.buffer = (self.buffer << 6) | value;
+            self.bits += 6;
+            if self.bits >= 8 {
+                self.bits -= 8;
+                self.output.push((self.buffer >> self.bits) as u8);
+                self.buffer &= (1 << self.bits) - 1;
+            }
+        }
+
+        Some(())
+    }
+
+    fn finish(self) -> Option<Vec<u8>> {
+        // Each padding character stands for two bits that were left over.
+        let expected_padding = match self.bits {
+            0 => 0,
+            4 => 2,
+            2 => 1,
+            _ => return None,
+        };
+
+        (self.buffer == 0 && self.padding == expected_padding).then_some(self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        for (decoded, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encoded, base64_encode(decoded));
+            let mut decoder = Base64Decoder::default();
+            decoder.push(encoded).unwrap();
+            assert_eq!(decoded, decoder.finish().unwrap());
+        }
+
+        for invalid in ["Zg", "Zg=", "Zh==", "Z===", "Zg==Zg==", "Zm9v!"] {
+            let mut decoder = Base64Decoder::default();
+            assert!(
+                decoder
+                    .push(invalid)
+                    .and_then(|_| decoder.finish())
+                    .is_none(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn bundle() {
+        let input = "\
+Explanatory text is ignored.
+-----BEGIN INTEGER-----
+AgEF
+-----END INTEGER-----
+-----BEGIN OCTET STRING-----\r
+BAJo\r
+aQ==\r
+-----END OCTET STRING-----\r
+";
+        let blocks = parse_many(input).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            alloc::vec![
+                Block::new("INTEGER", [2, 1, 5]),
+                Block::new("OCTET STRING", [4, 2, b'h', b'i']),
+            ],
+            blocks
+        );
+        assert_eq!(
+            "-----BEGIN INTEGER-----\nAgEF\n-----END INTEGER-----\n",
+            blocks[0].to_pem()
+        );
+        assert_eq!(blocks[0], parse(&blocks[0].to_pem()).unwrap());
+    }
+
+    #[test]
+    fn long_lines() {
+        let block = Block::new("DATA", [0xff; 100]);
+        let pem = block.to_pem();
+        assert_eq!(
+            [20, 64, 64, 8, 18],
+            pem.lines().map(str::len).collect::<Vec<_>>()[..]
+        );
+        assert_eq!(block, parse(&pem).unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(parse("nothing here"), Err(PemError::MissingBegin)));
+        assert!(matches!(
+            parse("-----BEGIN A-----\nAgEF\n"),
+            Err(PemError::MissingEnd { .. })
+        ));
+        assert!(matches!(
+            parse("-----BEGIN A-----\nAgEF\n-----END B-----\n"),
+            Err(PemError::MismatchedLabels { .. })
+        ));
+        assert!(matches!(
+            parse("-----BEGIN A  B-----\nAgEF\n-----END A  B-----\n"),
+            Err(PemError::InvalidLabel { .. })
+        ));
+        assert!(matches!(
+            parse("-----BEGIN A-----\nAgEF\nAg!F\n-----END A-----\n"),
+            Err(PemError::InvalidBase64 { line: 3, .. })
+        ));
+    }
+
+    struct Label;
+
+    impl PemLabel for Label {
+        const LABEL: &'static str = "X509 CRL";
+        const ALTERNATIVE_LABELS: &'static [&'static str] = &["CRL"];
+    }
+
+    #[test]
+    fn labels() {
+        assert!(Label::matches_label("X509 CRL"));
+        assert!(Label::matches_label("CRL"));
+        assert!(!Label::matches_label("CERTIFICATE"));
+        assert!(is_valid_label(""));
+        assert!(is_valid_label("PKCS7"));
+        assert!(is_valid_label("X.509 CERTIFICATE"));
+        assert!(is_valid_label("ENCRYPTED PRIVATE KEY"));
+        assert!(!is_valid_label(" LEADING"));
+        assert!(!is_valid_label("TRAILING-"));
+        assert!(!is_valid_label("DOUBLE--HYPHEN"));
+    }
+}
@@ -667,7 +667,18 @@ impl<'input> crate::Decoder for Decoder<'input> {
         let mut octet_string = types::BitString::default();
         let codec = self.codec();
 
-        self.decode_extensible_container(constraints, |input, length| {
+        let extensible_is_present = self.parse_extensible_bit(&constraints)?;
+        let size = constraints.size().filter(|_| !extensible_is_present);
+        let mut input = self.input;
+        // ITU-T X.691 (02/2021) §17 NOTE: Octet strings of fixed length less than or equal to two
+        // octets are not octet-aligned, all other fixed length octet strings are.
+        if size
+            .is_some_and(|size| size.constraint.range() == Some(1) && size.constraint.minimum() > 2)
+        {
+            input = self.parse_padding(input)?;
+        }
+
+        self.input = self.decode_length(input, size, &mut |input, length| {
             let (input, part) = nom::bytes::streaming::take(length * 8)(input)
                 .map_err(|e| DecodeError::map_nom_err(e, codec))?;
 
@@ -969,11 +980,11 @@ impl<'input> crate::Decoder for Decoder<'input> {
         D: crate::types::DecodeChoice,
     {
         let is_extensible = self.parse_extensible_bit(&constraints)?;
-        let variants = crate::types::variants::Variants::from_static(if is_extensible {
+        let variants = if is_extensible {
             D::EXTENDED_VARIANTS.unwrap_or(&[])
         } else {
             D::VARIANTS
-        });
+        };
 
         let index = if variants.len() != 1 || is_extensible {
             usize::try_from(if is_extensible {
@@ -999,16 +1010,23 @@ impl<'input> crate::Decoder for Decoder<'input> {
             0
         };
 
-        let tag = variants.get(index).ok_or_else(|| {
-            DecodeError::choice_index_not_found(index, variants.clone(), self.codec())
-        })?;
+        let tag = variants
+            .get(index)
+            .map(crate::TagTree::smallest_tag)
+            .ok_or_else(|| {
+                DecodeError::choice_index_not_found(
+                    index,
+                    crate::types::variants::Variants::from_static(variants),
+                    self.codec(),
+                )
+            })?;
 
         if is_extensible {
             let bytes = self.decode_octets()?;
             let mut decoder = Decoder::new(&bytes, self.options);
-            D::from_tag(&mut decoder, *tag)
+            D::from_tag(&mut decoder, tag)
         } else {
-            D::from_tag(self, *tag)
+            D::from_tag(self, tag)
         }
     }
 
@@ -1150,16 +1150,13 @@ impl crate::Encoder for Encoder {
 
         let is_root_extension = crate::TagTree::tag_contains(&tag, E::VARIANTS);
         self.encode_extensible_bit(&constraints, &mut buffer, || is_root_extension);
-        let variants = crate::types::variants::Variants::from_static(if is_root_extension {
+        let variants = if is_root_extension {
             E::VARIANTS
         } else {
             E::EXTENDED_VARIANTS.unwrap_or(&[])
-        });
+        };
 
-        let index = variants
-            .iter()
-            .enumerate()
-            .find_map(|(i, &variant_tag)| (tag == variant_tag).then_some(i))
+        let index = crate::TagTree::tag_index(&tag, variants)
             .ok_or_else(|| Error::variant_not_in_choice(self.codec()))?;
 
         let bounds = if is_root_extension {
@@ -1186,7 +1183,7 @@ impl crate::Encoder for Encoder {
             0
         };
 
-        choice_encoder.parent_output_length = Some(choice_bits_len);
+        choice_encoder.parent_output_length = Some(self.output_length() + choice_bits_len);
         let _tag = (encode_fn)(&mut choice_encoder)?;
 
         match (index, bounds) {
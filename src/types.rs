@@ -88,6 +88,13 @@ pub trait AsnType {
     /// Identifier of an ASN.1 type as specified in the original specification
     /// if not identical with the identifier of `Self`
     const IDENTIFIER: Option<&'static str> = None;
+
+    /// The tag of this value. For CHOICE types this is the tag of the
+    /// currently selected alternative, for all other types it is
+    /// [`Self::TAG`].
+    fn selected_tag(&self) -> Tag {
+        Self::TAG
+    }
 }
 
 /// A `SET` or `SEQUENCE` value.
@@ -324,11 +331,19 @@ impl AsnType for str {
 impl<T: AsnType> AsnType for &'_ T {
     const TAG: Tag = T::TAG;
     const TAG_TREE: TagTree = T::TAG_TREE;
+
+    fn selected_tag(&self) -> Tag {
+        (**self).selected_tag()
+    }
 }
 
 impl<T: AsnType> AsnType for Box<T> {
     const TAG: Tag = T::TAG;
     const TAG_TREE: TagTree = T::TAG_TREE;
+
+    fn selected_tag(&self) -> Tag {
+        (**self).selected_tag()
+    }
 }
 
 impl<T: AsnType> AsnType for alloc::vec::Vec<T> {
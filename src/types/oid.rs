@@ -244,6 +244,7 @@ oids! {
     ITU_T => 0;
     ITU_T_DATA_PSS_UCL_PILOT => 0, 9, 2342, 19200300, 100;
     ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE => 0, 9, 2342, 19200300, 100, 1;
+    ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE_USER_ID => 0, 9, 2342, 19200300, 100, 1, 1;
     ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE_DOMAIN_COMPONENT => 0, 9, 2342, 19200300, 100, 1, 25;
 }
 
@@ -259,12 +260,31 @@ oids! {
 
     ISO_MEMBER_BODY_US_ANSI_X942_NUMBER_TYPE_PUBLIC => 1, 2, 840, 10046, 2, 1;
 
+    ISO_MEMBER_BODY_US_MICROSOFT_KERBEROS_V5 => 1, 2, 840, 48018, 1, 2, 2;
+    ISO_MEMBER_BODY_US_MIT_KERBEROS_V5 => 1, 2, 840, 113554, 1, 2, 2;
+    ISO_MEMBER_BODY_US_MIT_KERBEROS_V5_USER_TO_USER => 1, 2, 840, 113554, 1, 2, 2, 3;
+
+    ISO_MEMBER_BODY_US_ANSI_X962_KEY_TYPE_EC_PUBLIC_KEY => 1, 2, 840, 10045, 2, 1;
+    ISO_MEMBER_BODY_US_ANSI_X962_CURVES_PRIME256V1 => 1, 2, 840, 10045, 3, 1, 7;
+    ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA1 => 1, 2, 840, 10045, 4, 1;
+    ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA224 => 1, 2, 840, 10045, 4, 3, 1;
+    ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA256 => 1, 2, 840, 10045, 4, 3, 2;
+    ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA384 => 1, 2, 840, 10045, 4, 3, 3;
+    ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA512 => 1, 2, 840, 10045, 4, 3, 4;
+
     ISO_MEMBER_BODY_US_RSADSI_PKCS => 1, 2, 840, 113549, 1;
     ISO_MEMBER_BODY_US_RSADSI_PKCS1 => 1, 2, 840, 113549, 1, 1;
     ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSA => 1, 2, 840, 113549, 1, 1, 1;
     ISO_MEMBER_BODY_US_RSADSI_PKCS1_MD5_RSA => 1, 2, 840, 113549, 1, 1, 4;
     ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA1_RSA => 1, 2, 840, 113549, 1, 1, 5;
     ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSAES_OAEP => 1, 2, 840, 113549, 1, 1, 7;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_MGF1 => 1, 2, 840, 113549, 1, 1, 8;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_P_SPECIFIED => 1, 2, 840, 113549, 1, 1, 9;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSASSA_PSS => 1, 2, 840, 113549, 1, 1, 10;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA256_RSA => 1, 2, 840, 113549, 1, 1, 11;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA384_RSA => 1, 2, 840, 113549, 1, 1, 12;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA512_RSA => 1, 2, 840, 113549, 1, 1, 13;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA224_RSA => 1, 2, 840, 113549, 1, 1, 14;
 
     ISO_MEMBER_BODY_US_RSADSI_DIGEST_ALGORITHM => 1, 2, 840, 113549, 1, 2;
     ISO_MEMBER_BODY_US_RSADSI_DIGEST_ALGORITHM_MD5 => 1, 2, 840, 113549, 1, 2, 5;
@@ -274,6 +294,13 @@ oids! {
     ISO_MEMBER_BODY_US_RSADSI_ENCRYPTION_ALGORITHM_DES_EDE3_CBC => 1, 2, 840, 113549, 1, 3, 7;
 
     ISO_MEMBER_BODY_US_RSADSI_PKCS5_PBKDF2 => 1, 2, 840, 113549, 1, 5, 12;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS5_PBES2 => 1, 2, 840, 113549, 1, 5, 13;
+
+    ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA1 => 1, 2, 840, 113549, 2, 7;
+    ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA224 => 1, 2, 840, 113549, 2, 8;
+    ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA256 => 1, 2, 840, 113549, 2, 9;
+    ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA384 => 1, 2, 840, 113549, 2, 10;
+    ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA512 => 1, 2, 840, 113549, 2, 11;
 
     ISO_MEMBER_BODY_US_RSADSI_PKCS7_DATA => 1, 2, 840, 113549, 1, 7, 1;
     ISO_MEMBER_BODY_US_RSADSI_PKCS7_SIGNED_DATA => 1, 2, 840, 113549, 1, 7, 2;
@@ -324,6 +351,7 @@ oids! {
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_ESDH => 1, 2, 840, 113549, 1, 9, 16, 3, 5;
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_CMS3DESWRAP => 1, 2, 840, 113549, 1, 9, 16, 3, 6;
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_CMS3RC2WRAP => 1, 2, 840, 113549, 1, 9, 16, 3, 7;
+    ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_PWRI_KEK => 1, 2, 840, 113549, 1, 9, 16, 3, 9;
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_SSDH => 1, 2, 840, 113549, 1, 9, 16, 3, 10;
 
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_CAPABILITY => 1, 2, 840, 113549, 1, 9, 16, 3, 11;
@@ -346,6 +374,18 @@ oids! {
     ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_SKD_GLKEY => 1, 2, 840, 113549, 1, 9, 16, 8, 15;
 
     ISO_IDENTIFIED_ORGANISATION => 1, 3;
+    ISO_IDENTIFIED_ORGANISATION_THAWTE_X25519 => 1, 3, 101, 110;
+    ISO_IDENTIFIED_ORGANISATION_THAWTE_X448 => 1, 3, 101, 111;
+    ISO_IDENTIFIED_ORGANISATION_THAWTE_ED25519 => 1, 3, 101, 112;
+    ISO_IDENTIFIED_ORGANISATION_THAWTE_ED448 => 1, 3, 101, 113;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP256K1 => 1, 3, 132, 0, 10;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP224R1 => 1, 3, 132, 0, 33;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP384R1 => 1, 3, 132, 0, 34;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP521R1 => 1, 3, 132, 0, 35;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA224KDF => 1, 3, 132, 1, 11, 0;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA256KDF => 1, 3, 132, 1, 11, 1;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA384KDF => 1, 3, 132, 1, 11, 2;
+    ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA512KDF => 1, 3, 132, 1, 11, 3;
     ISO_IDENTIFIED_ORGANISATION_DOD => 1, 3, 6;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET => 1, 3, 6, 1;
 
@@ -380,16 +420,31 @@ oids! {
 
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY => 1, 3, 6, 1, 5;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5 => 1, 3, 6, 1, 5, 2;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_SAN => 1, 3, 6, 1, 5, 2, 2;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT => 1, 3, 6, 1, 5, 2, 3;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_AUTH_DATA => 1, 3, 6, 1, 5, 2, 3, 1;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_DH_KEY_DATA => 1, 3, 6, 1, 5, 2, 3, 2;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_RKEY_DATA => 1, 3, 6, 1, 5, 2, 3, 3;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KP_CLIENT_AUTH => 1, 3, 6, 1, 5, 2, 3, 4;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KP_KDC => 1, 3, 6, 1, 5, 2, 3, 5;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF => 1, 3, 6, 1, 5, 2, 3, 6;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA1 => 1, 3, 6, 1, 5, 2, 3, 6, 1;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA256 => 1, 3, 6, 1, 5, 2, 3, 6, 2;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA512 => 1, 3, 6, 1, 5, 2, 3, 6, 3;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA384 => 1, 3, 6, 1, 5, 2, 3, 6, 4;
 
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS => 1, 3, 6, 1, 5, 5;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_SPNEGO => 1, 3, 6, 1, 5, 5, 2;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX => 1, 3, 6, 1, 5, 5, 7;
 
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE => 1, 3, 6, 1, 5, 5, 7, 1;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AUTHORITY_INFO_ACCESS => 1, 3, 6, 1, 5, 5, 7, 1, 1;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AUDIT_IDENTIFY => 1, 3, 6, 1, 5, 5, 7, 1, 4;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AA_CONTROLS => 1, 3, 6, 1, 5, 5, 7, 1, 6;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_IP_ADDR_BLOCKS => 1, 3, 6, 1, 5, 5, 7, 1, 7;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AS_SYS_IDS => 1, 3, 6, 1, 5, 5, 7, 1, 8;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AC_PROXYING => 1, 3, 6, 1, 5, 5, 7, 1, 10;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_SUBJECT_INFO_ACCESS => 1, 3, 6, 1, 5, 5, 7, 1, 11;
 
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_QT => 1, 3, 6, 1, 5, 5, 7, 2;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_QT_CPS => 1, 3, 6, 1, 5, 5, 7, 2, 1;
@@ -423,6 +478,8 @@ oids! {
 
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD => 1, 3, 6, 1, 5, 5, 7, 48;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_OCSP => 1, 3, 6, 1, 5, 5, 7, 48, 1;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_OCSP_BASIC => 1, 3, 6, 1, 5, 5, 7, 48, 1, 1;
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_OCSP_NONCE => 1, 3, 6, 1, 5, 5, 7, 48, 1, 2;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_CA_ISSUERS => 1, 3, 6, 1, 5, 5, 7, 48, 2;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_TIME_STAMPING => 1, 3, 6, 1, 5, 5, 7, 48, 3;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_CA_REPOSITORY => 1, 3, 6, 1, 5, 5, 7, 48, 5;
@@ -436,6 +493,7 @@ oids! {
 
     ISO_IDENTIFIED_ORGANISATION_OIW_SECSIG_ALGORITHM_SHA1 => 1, 3, 14, 3, 2, 26;
 
+    ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_PRIVATE_ENTERPRISES_MICROSOFT_NTLM_SSP => 1, 3, 6, 1, 4, 1, 311, 2, 2, 10;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_PRIVATE_ENTERPRISES_MICROSOFT_SPC_INDIRECT_DATA_OBJID => 1, 3, 6, 1, 4, 1, 311, 2, 1, 4;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_PRIVATE_ENTERPRISES_MICROSOFT_SPC_PE_IMAGE_DATA_OBJID => 1, 3, 6, 1, 4, 1, 311, 2, 1, 15;
     ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_PRIVATE_ENTERPRISES_MICROSOFT_SPC_SP_OPUS_INFO_OBJID => 1, 3, 6, 1, 4, 1, 311, 2, 1, 12;
@@ -519,6 +577,7 @@ oids! {
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COUNTRY_NAME => 2, 5, 4, 6;
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_LOCALITY_NAME => 2, 5, 4, 7;
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_STATE_OR_PROVINCE_NAME => 2, 5, 4, 8;
+    JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_STREET_ADDRESS => 2, 5, 4, 9;
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_ORGANISATION_NAME => 2, 5, 4, 10;
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_ORGANISATIONAL_UNIT_NAME => 2, 5, 4, 11;
     JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_TITLE => 2, 5, 4, 12;
@@ -578,6 +637,7 @@ oids! {
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CERTIFICATE_POLICIES_ANY_POLICY => 2, 5, 29, 32, 0;
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_POLICY_MAPPINGS => 2, 5, 29, 33;
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_KEY_IDENTIFIER => 2, 5, 29, 35;
+    JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_POLICY_CONSTRAINTS => 2, 5, 29, 36;
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_EXT_KEY_USAGE => 2, 5, 29, 37;
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_EXT_KEY_USAGE_ANY_EXTENDED_KEY_USAGE => 2, 5, 29, 37, 0;
     JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_FRESHEST_CRL => 2, 5, 29, 46;
@@ -607,10 +667,16 @@ oids! {
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES => 2, 16, 840, 1, 101, 3, 4, 1;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_CBC => 2, 16, 840, 1, 101, 3, 4, 1, 2;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_WRAP => 2, 16, 840, 1, 101, 3, 4, 1, 5;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_GCM => 2, 16, 840, 1, 101, 3, 4, 1, 6;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_CCM => 2, 16, 840, 1, 101, 3, 4, 1, 7;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_CBC => 2, 16, 840, 1, 101, 3, 4, 1, 22;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_WRAP => 2, 16, 840, 1, 101, 3, 4, 1, 25;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_GCM => 2, 16, 840, 1, 101, 3, 4, 1, 26;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_CCM => 2, 16, 840, 1, 101, 3, 4, 1, 27;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_CBC => 2, 16, 840, 1, 101, 3, 4, 1, 42;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_WRAP => 2, 16, 840, 1, 101, 3, 4, 1, 45;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_GCM => 2, 16, 840, 1, 101, 3, 4, 1, 46;
+    JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_CCM => 2, 16, 840, 1, 101, 3, 4, 1, 47;
 
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA224 => 2, 16, 840, 1, 101, 3, 4, 2, 4;
     JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA256 => 2, 16, 840, 1, 101, 3, 4, 2, 1;
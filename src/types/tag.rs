@@ -291,6 +291,14 @@ impl TagTree {
         }
     }
 
+    /// Returns the position of the node in `nodes` that contains `needle`,
+    /// looking through nested `Choice`s but only counting top-level nodes.
+    pub fn tag_index(needle: &Tag, nodes: &[TagTree]) -> Option<usize> {
+        nodes
+            .iter()
+            .position(|node| Self::tag_contains(needle, core::slice::from_ref(node)))
+    }
+
     /// Whether `needle` matches any `Leaf`s in `nodes`.
     pub const fn tag_contains(needle: &Tag, nodes: &[TagTree]) -> bool {
         let mut index = 0;
@@ -1006,4 +1006,27 @@ mod tests {
             &[0x80, 0x95, 0x00]
         );
     }
+
+    #[test]
+    fn fixed_octet_string() {
+        #[derive(AsnType, Debug, Decode, Encode, PartialEq)]
+        #[rasn(crate_root = "crate")]
+        struct A {
+            a: bool,
+            b: FixedOctetString<2>,
+            c: FixedOctetString<4>,
+        }
+
+        // Unlike APER, UPER never aligns fixed length octet strings.
+        round_trip!(
+            uper,
+            A,
+            A {
+                a: true,
+                b: [0xff, 0xff].into(),
+                c: [192, 168, 1, 10].into(),
+            },
+            &[0xff, 0xff, 0xe0, 0x54, 0x00, 0x85, 0x00]
+        );
+    }
 }
@@ -6,9 +6,7 @@ use crate::{
         string::{String, ToString},
         vec::Vec,
     },
-    types::{
-        fields::Fields, variants::Variants, Any, BitStr, Enumerated, GeneralizedTime, UtcTime,
-    },
+    types::{fields::Fields, Any, BitStr, Enumerated, GeneralizedTime, UtcTime},
     xer::{
         BIT_STRING_TYPE_TAG, BMP_STRING_TYPE_TAG, GENERALIZED_TIME_TYPE_TAG,
         GENERAL_STRING_TYPE_TAG, IA5_STRING_TYPE_TAG, INTEGER_TYPE_TAG, NULL_TYPE_TAG,
@@ -518,17 +516,10 @@ impl crate::Encoder for Encoder {
         ));
         self.write_start_element(&xml_tag)?;
 
-        let variants =
-            Variants::from_slice(&[E::VARIANTS, E::EXTENDED_VARIANTS.unwrap_or(&[])].concat());
+        let variants = [E::VARIANTS, E::EXTENDED_VARIANTS.unwrap_or(&[])].concat();
 
-        let identifier = variants
-            .iter()
-            .enumerate()
-            .find_map(|(i, &variant_tag)| {
-                (tag == variant_tag)
-                    .then_some(E::IDENTIFIERS.get(i))
-                    .flatten()
-            })
+        let identifier = crate::types::TagTree::tag_index(&tag, &variants)
+            .and_then(|i| E::IDENTIFIERS.get(i))
             .ok_or_else(|| crate::error::EncodeError::variant_not_in_choice(self.codec()))?;
 
         self.write_start_element(identifier)?;
@@ -1,17 +1,54 @@
 //! # Algorithms used with CMS.
 //! Algorithms OIDs and parameter data types.
 
-use rasn::prelude::*;
+use rasn::{
+    de::Error as _,
+    error::{DecodeError, EncodeError},
+    prelude::*,
+};
 
 use rasn_pkix::AlgorithmIdentifier;
 
 pub const SHA1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_OIW_SECSIG_ALGORITHM_SHA1;
 pub const MD5: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_DIGEST_ALGORITHM_MD5;
+pub const SHA224: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA224;
+pub const SHA256: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA256;
+pub const SHA384: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA384;
+pub const SHA512: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA512;
 pub const DSA: &Oid = Oid::ISO_MEMBER_BODY_US_X957_X9CM_DSA;
 pub const DSA_WITH_SHA1: &Oid = Oid::ISO_MEMBER_BODY_US_X957_X9CM_DSA_SHA1;
 pub const RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSA;
 pub const MD5_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_MD5_RSA;
 pub const SHA1_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA1_RSA;
+pub const SHA224_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA224_RSA;
+pub const SHA256_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA256_RSA;
+pub const SHA384_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA384_RSA;
+pub const SHA512_WITH_RSA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA512_RSA;
+pub const RSASSA_PSS: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSASSA_PSS;
+pub const MGF1: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_MGF1;
+pub const P_SPECIFIED: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_P_SPECIFIED;
+
+pub const EC_PUBLIC_KEY: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_KEY_TYPE_EC_PUBLIC_KEY;
+pub const ECDSA_WITH_SHA1: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA1;
+pub const ECDSA_WITH_SHA224: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA224;
+pub const ECDSA_WITH_SHA256: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA256;
+pub const ECDSA_WITH_SHA384: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA384;
+pub const ECDSA_WITH_SHA512: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA512;
+pub const SECP224R1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP224R1;
+pub const SECP256R1: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X962_CURVES_PRIME256V1;
+pub const SECP384R1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP384R1;
+pub const SECP521R1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP521R1;
+pub const SECP256K1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_CURVE_SECP256K1;
+
+pub const X25519: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_THAWTE_X25519;
+pub const X448: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_THAWTE_X448;
+pub const ED25519: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_THAWTE_ED25519;
+pub const ED448: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_THAWTE_ED448;
+
 pub const PUBLIC_NUMBER: &Oid = Oid::ISO_MEMBER_BODY_US_ANSI_X942_NUMBER_TYPE_PUBLIC;
 
 pub const ESDH: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_ESDH;
@@ -21,10 +58,27 @@ pub const CMS3RC2WRAP: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORIT
 
 pub const DES_EDE3_CBC: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_ENCRYPTION_ALGORITHM_DES_EDE3_CBC;
 pub const RC2_CBC: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_ENCRYPTION_ALGORITHM_RC2_CBC;
+pub const PWRI_KEK: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_ALGORITHM_PWRI_KEK;
+pub const RSAES_OAEP: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSAES_OAEP;
+
+pub const DH_SINGLE_PASS_STD_DH_SHA224KDF: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA224KDF;
+pub const DH_SINGLE_PASS_STD_DH_SHA256KDF: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA256KDF;
+pub const DH_SINGLE_PASS_STD_DH_SHA384KDF: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA384KDF;
+pub const DH_SINGLE_PASS_STD_DH_SHA512KDF: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_CERTICOM_SCHEMES_DH_SINGLE_PASS_STD_DH_SHA512KDF;
 
 pub const HMAC_SHA1: &Oid =
     Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_HMAC_SHA1;
+pub const HMAC_WITH_SHA1: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA1;
+pub const HMAC_WITH_SHA224: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA224;
+pub const HMAC_WITH_SHA256: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA256;
+pub const HMAC_WITH_SHA384: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA384;
+pub const HMAC_WITH_SHA512: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_HMAC_WITH_SHA512;
 pub const PBKDF2: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS5_PBKDF2;
+pub const PBES2: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS5_PBES2;
 
 pub const AES: &Oid = Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES;
 pub const AES128_CBC: &Oid =
@@ -39,6 +93,42 @@ pub const AES256_CBC: &Oid =
     Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_CBC;
 pub const AES256_WRAP: &Oid =
     Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_WRAP;
+pub const AES128_GCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_GCM;
+pub const AES192_GCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_GCM;
+pub const AES256_GCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_GCM;
+pub const AES128_CCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES128_CCM;
+pub const AES192_CCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES192_CCM;
+pub const AES256_CCM: &Oid =
+    Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_AES256_CCM;
+
+/// Returns the length in bytes of the key used by the symmetric algorithm
+/// `oid`, or `None` if it isn't known.
+pub fn key_length(oid: &Oid) -> Option<usize> {
+    [
+        (AES128_CBC, 16),
+        (AES128_WRAP, 16),
+        (AES128_GCM, 16),
+        (AES128_CCM, 16),
+        (AES192_CBC, 24),
+        (AES192_WRAP, 24),
+        (AES192_GCM, 24),
+        (AES192_CCM, 24),
+        (AES256_CBC, 32),
+        (AES256_WRAP, 32),
+        (AES256_GCM, 32),
+        (AES256_CCM, 32),
+        (DES_EDE3_CBC, 24),
+        (CMS3DESWRAP, 24),
+    ]
+    .into_iter()
+    .find(|(known, _)| *known == oid)
+    .map(|(_, length)| length)
+}
 
 pub type DssPubKey = Integer;
 pub type AesIv = OctetString;
@@ -113,3 +203,318 @@ pub enum Pbkdf2Salt {
     Specified(OctetString),
     OtherSource(AlgorithmIdentifier),
 }
+
+/// Parameters of the AES-GCM content-authenticated encryption algorithms,
+/// defined in [RFC 5084](https://datatracker.ietf.org/doc/html/rfc5084).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct GcmParameters {
+    pub aes_nonce: OctetString,
+    #[rasn(default = "default_aes_icv_len")]
+    pub aes_icv_len: Integer,
+}
+
+/// Parameters of the AES-CCM content-authenticated encryption algorithms,
+/// defined in [RFC 5084](https://datatracker.ietf.org/doc/html/rfc5084).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct CcmParameters {
+    pub aes_nonce: OctetString,
+    #[rasn(default = "default_aes_icv_len")]
+    pub aes_icv_len: Integer,
+}
+
+/// The default length of the AES-GCM and AES-CCM integrity check value.
+pub fn default_aes_icv_len() -> Integer {
+    12.into()
+}
+
+/// The input to the key derivation function of elliptic curve key
+/// agreement, defined in
+/// [RFC 5753](https://datatracker.ietf.org/doc/html/rfc5753#section-7.2).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct EccCmsSharedInfo {
+    pub key_info: AlgorithmIdentifier,
+    #[rasn(tag(explicit(0)))]
+    pub entity_u_info: Option<OctetString>,
+    #[rasn(tag(explicit(2)))]
+    pub supp_pub_info: OctetString,
+}
+
+/// Parameters of the RSASSA-PSS signature algorithm, defined in
+/// [RFC 4055](https://datatracker.ietf.org/doc/html/rfc4055#section-3.1).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct RsassaPssParameters {
+    #[rasn(tag(explicit(0)), default = "default_sha1_identifier")]
+    pub hash_algorithm: AlgorithmIdentifier,
+    #[rasn(tag(explicit(1)), default = "default_mgf1_sha1_identifier")]
+    pub mask_gen_algorithm: AlgorithmIdentifier,
+    #[rasn(tag(explicit(2)), default = "default_salt_length")]
+    pub salt_length: Integer,
+    #[rasn(tag(explicit(3)), default = "default_trailer_field")]
+    pub trailer_field: Integer,
+}
+
+/// Parameters of the RSAES-OAEP key transport algorithm, defined in
+/// [RFC 4055](https://datatracker.ietf.org/doc/html/rfc4055#section-4.1).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct RsaesOaepParameters {
+    #[rasn(tag(explicit(0)), default = "default_sha1_identifier")]
+    pub hash_func: AlgorithmIdentifier,
+    #[rasn(tag(explicit(1)), default = "default_mgf1_sha1_identifier")]
+    pub mask_gen_func: AlgorithmIdentifier,
+    #[rasn(tag(explicit(2)), default = "default_p_source_func")]
+    pub p_source_func: AlgorithmIdentifier,
+}
+
+/// `sha1Identifier`, the default hash algorithm of RSASSA-PSS and RSAES-OAEP.
+pub fn default_sha1_identifier() -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: SHA1.into(),
+        parameters: Some(Any::new(rasn::der::encode(&()).unwrap())),
+    }
+}
+
+/// `mgf1SHA1Identifier`, the default mask generation function of RSASSA-PSS
+/// and RSAES-OAEP.
+pub fn default_mgf1_sha1_identifier() -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: MGF1.into(),
+        parameters: Some(Any::new(
+            rasn::der::encode(&default_sha1_identifier()).unwrap(),
+        )),
+    }
+}
+
+/// The default salt length of RSASSA-PSS.
+pub fn default_salt_length() -> Integer {
+    20.into()
+}
+
+/// The default trailer field of RSASSA-PSS.
+pub fn default_trailer_field() -> Integer {
+    1.into()
+}
+
+/// `pSpecifiedEmptyIdentifier`, the default encoding parameters source of
+/// RSAES-OAEP.
+pub fn default_p_source_func() -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: P_SPECIFIED.into(),
+        parameters: Some(Any::new(rasn::der::encode(&OctetString::new()).unwrap())),
+    }
+}
+
+/// Parameters of elliptic curve public keys, defined in
+/// [RFC 5480](https://datatracker.ietf.org/doc/html/rfc5480#section-2.1.1).
+/// Specified curves are not supported.
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[rasn(choice)]
+pub enum EcParameters {
+    NamedCurve(ObjectIdentifier),
+    ImplicitCurve(()),
+}
+
+/// Parameters of the PBES2 encryption scheme, defined in
+/// [RFC 8018](https://datatracker.ietf.org/doc/html/rfc8018#appendix-A.4).
+#[derive(AsnType, Debug, Clone, Decode, Encode, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Pbes2Parameters {
+    pub key_derivation_func: AlgorithmIdentifier,
+    pub encryption_scheme: AlgorithmIdentifier,
+}
+
+/// Parameters that are either `NULL` or absent, both of which are found in
+/// practice. `Some(())` is an explicit `NULL`.
+pub type NullParameters = Option<()>;
+
+/// Conversion of parameter types to and from the `parameters` field of an
+/// [`AlgorithmIdentifier`].
+trait Parameters: Sized {
+    fn from_parameters(parameters: Option<&Any>) -> Result<Self, DecodeError>;
+    fn to_parameters(&self) -> Result<Option<Any>, EncodeError>;
+}
+
+macro_rules! parameters {
+    ($($parameters:ty),* $(,)?) => {
+        $(
+            impl Parameters for $parameters {
+                fn from_parameters(parameters: Option<&Any>) -> Result<Self, DecodeError> {
+                    let parameters = parameters.ok_or_else(|| {
+                        DecodeError::missing_field("parameters", rasn::Codec::Der)
+                    })?;
+                    rasn::der::decode(parameters.as_bytes())
+                }
+
+                fn to_parameters(&self) -> Result<Option<Any>, EncodeError> {
+                    Ok(Some(Any::new(rasn::der::encode(self)?)))
+                }
+            }
+
+            impl Parameters for Option<$parameters> {
+                fn from_parameters(parameters: Option<&Any>) -> Result<Self, DecodeError> {
+                    parameters
+                        .map(|parameters| rasn::der::decode(parameters.as_bytes()))
+                        .transpose()
+                }
+
+                fn to_parameters(&self) -> Result<Option<Any>, EncodeError> {
+                    self.as_ref().map_or(Ok(None), Parameters::to_parameters)
+                }
+            }
+        )*
+    };
+}
+
+parameters!(
+    (),
+    OctetString,
+    AlgorithmIdentifier,
+    RsassaPssParameters,
+    RsaesOaepParameters,
+    EcParameters,
+    Pbkdf2Parameters,
+    Pbes2Parameters,
+    GcmParameters,
+    CcmParameters,
+);
+
+macro_rules! algorithms {
+    (
+        absent {
+            $($(#[$absent_meta:meta])* $absent:ident => $absent_oid:expr,)*
+        }
+        parameters {
+            $($(#[$meta:meta])* $variant:ident($parameters:ty) => $oid:expr,)*
+        }
+    ) => {
+        /// An algorithm and its typed parameters.
+        ///
+        /// Converting an [`AlgorithmIdentifier`] to an `Algorithm` decodes the
+        /// parameters of known algorithms, and fails if they're invalid.
+        /// Unknown algorithms are kept as [`Algorithm::Other`]. Converting
+        /// back gives the original identifier, as long as its parameters were
+        /// DER encoded.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Algorithm {
+            $($(#[$absent_meta])* $absent,)*
+            $($(#[$meta])* $variant($parameters),)*
+            /// An algorithm not known to the registry.
+            Other(AlgorithmIdentifier),
+        }
+
+        impl Algorithm {
+            /// Returns the algorithm's object identifier.
+            pub fn oid(&self) -> &Oid {
+                match self {
+                    $(Self::$absent => $absent_oid,)*
+                    $(Self::$variant(_) => $oid,)*
+                    Self::Other(identifier) => &identifier.algorithm,
+                }
+            }
+        }
+
+        impl TryFrom<&AlgorithmIdentifier> for Algorithm {
+            type Error = DecodeError;
+
+            fn try_from(identifier: &AlgorithmIdentifier) -> Result<Self, Self::Error> {
+                let oid = &identifier.algorithm;
+                let parameters = identifier.parameters.as_ref();
+                $(
+                    if oid == $absent_oid {
+                        return match parameters {
+                            None => Ok(Self::$absent),
+                            Some(_) => Err(DecodeError::custom(
+                                "parameters must be absent",
+                                rasn::Codec::Der,
+                            )),
+                        };
+                    }
+                )*
+                $(
+                    if oid == $oid {
+                        return Ok(Self::$variant(Parameters::from_parameters(parameters)?));
+                    }
+                )*
+                Ok(Self::Other(identifier.clone()))
+            }
+        }
+
+        impl TryFrom<&Algorithm> for AlgorithmIdentifier {
+            type Error = EncodeError;
+
+            fn try_from(algorithm: &Algorithm) -> Result<Self, Self::Error> {
+                let parameters = match algorithm {
+                    $(Algorithm::$absent => None,)*
+                    $(Algorithm::$variant(parameters) => parameters.to_parameters()?,)*
+                    Algorithm::Other(identifier) => return Ok(identifier.clone()),
+                };
+
+                Ok(Self {
+                    algorithm: algorithm.oid().into(),
+                    parameters,
+                })
+            }
+        }
+    };
+}
+
+algorithms! {
+    absent {
+        Aes128Wrap => AES128_WRAP,
+        Aes192Wrap => AES192_WRAP,
+        Aes256Wrap => AES256_WRAP,
+        EcdsaWithSha1 => ECDSA_WITH_SHA1,
+        EcdsaWithSha224 => ECDSA_WITH_SHA224,
+        EcdsaWithSha256 => ECDSA_WITH_SHA256,
+        EcdsaWithSha384 => ECDSA_WITH_SHA384,
+        EcdsaWithSha512 => ECDSA_WITH_SHA512,
+        X25519 => X25519,
+        X448 => X448,
+        Ed25519 => ED25519,
+        Ed448 => ED448,
+    }
+    parameters {
+        Sha1(NullParameters) => SHA1,
+        Sha224(NullParameters) => SHA224,
+        Sha256(NullParameters) => SHA256,
+        Sha384(NullParameters) => SHA384,
+        Sha512(NullParameters) => SHA512,
+        Rsa(NullParameters) => RSA,
+        Sha1WithRsa(NullParameters) => SHA1_WITH_RSA,
+        Sha224WithRsa(NullParameters) => SHA224_WITH_RSA,
+        Sha256WithRsa(NullParameters) => SHA256_WITH_RSA,
+        Sha384WithRsa(NullParameters) => SHA384_WITH_RSA,
+        Sha512WithRsa(NullParameters) => SHA512_WITH_RSA,
+        /// Parameters are absent in public keys that aren't restricted to
+        /// particular PSS parameters.
+        RsassaPss(Option<RsassaPssParameters>) => RSASSA_PSS,
+        RsaesOaep(RsaesOaepParameters) => RSAES_OAEP,
+        /// The mask generation function of RSASSA-PSS and RSAES-OAEP, with
+        /// its hash algorithm.
+        Mgf1(AlgorithmIdentifier) => MGF1,
+        EcPublicKey(EcParameters) => EC_PUBLIC_KEY,
+        /// Elliptic curve key agreement, with its key wrap algorithm.
+        DhSinglePassStdDhSha224Kdf(KeyWrapAlgorithm) => DH_SINGLE_PASS_STD_DH_SHA224KDF,
+        DhSinglePassStdDhSha256Kdf(KeyWrapAlgorithm) => DH_SINGLE_PASS_STD_DH_SHA256KDF,
+        DhSinglePassStdDhSha384Kdf(KeyWrapAlgorithm) => DH_SINGLE_PASS_STD_DH_SHA384KDF,
+        DhSinglePassStdDhSha512Kdf(KeyWrapAlgorithm) => DH_SINGLE_PASS_STD_DH_SHA512KDF,
+        HmacWithSha1(NullParameters) => HMAC_WITH_SHA1,
+        HmacWithSha224(NullParameters) => HMAC_WITH_SHA224,
+        HmacWithSha256(NullParameters) => HMAC_WITH_SHA256,
+        HmacWithSha384(NullParameters) => HMAC_WITH_SHA384,
+        HmacWithSha512(NullParameters) => HMAC_WITH_SHA512,
+        Pbkdf2(Pbkdf2Parameters) => PBKDF2,
+        Pbes2(Pbes2Parameters) => PBES2,
+        /// Password-based key wrapping, with its underlying cipher.
+        PwriKek(AlgorithmIdentifier) => PWRI_KEK,
+        DesEde3Cbc(Iv) => DES_EDE3_CBC,
+        Aes128Cbc(AesIv) => AES128_CBC,
+        Aes192Cbc(AesIv) => AES192_CBC,
+        Aes256Cbc(AesIv) => AES256_CBC,
+        Aes128Gcm(GcmParameters) => AES128_GCM,
+        Aes192Gcm(GcmParameters) => AES192_GCM,
+        Aes256Gcm(GcmParameters) => AES256_GCM,
+        Aes128Ccm(CcmParameters) => AES128_CCM,
+        Aes192Ccm(CcmParameters) => AES192_CCM,
+        Aes256Ccm(CcmParameters) => AES256_CCM,
+    }
+}
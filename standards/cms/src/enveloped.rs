@@ -0,0 +1,735 @@
+//! # Enveloped Data
+//!
+//! Creating and opening [`EnvelopedData`] as described in [RFC 5652 Section 6],
+//! and [`AuthEnvelopedData`] as described in [RFC 5083].
+//!
+//! Content is encrypted with a content-encryption key (CEK), which is in turn
+//! encrypted for each recipient by one of the [`RecipientInfo`] kinds:
+//!
+//! - [`KeyTransRecipientInfo`] encrypts the CEK with the recipient's public
+//!   key, through a [`KeyTransportEncryptor`] and [`KeyTransportDecryptor`].
+//! - [`KeyAgreeRecipientInfo`] wraps the CEK with a key-encryption key (KEK)
+//!   agreed with the recipient through a [`KeyAgreement`], using the
+//!   `ECC-CMS-SharedInfo` key derivation input of [RFC 5753].
+//! - [`KekRecipientInfo`] wraps the CEK with a previously distributed KEK.
+//! - [`PasswordRecipientInfo`] wraps the CEK with a KEK derived from a
+//!   password through a [`KeyDerivation`], as described in [RFC 3211].
+//!
+//! Each kind has a `new` constructor for the sender and a `decrypt_key`
+//! method for the recipient, which returns the CEK to pass to
+//! [`EnvelopedData::decrypt`] or [`AuthEnvelopedData::decrypt`]. Key wrapping
+//! goes through a [`KeyWrap`], and content encryption through a
+//! [`ContentCipher`] or [`AuthenticatedCipher`]. Generating the CEK, IVs and
+//! nonces is left to the caller, who includes them in the algorithm
+//! parameters.
+//!
+//! [RFC 3211]: https://datatracker.ietf.org/doc/html/rfc3211
+//! [RFC 5083]: https://datatracker.ietf.org/doc/html/rfc5083
+//! [RFC 5652 Section 6]: https://datatracker.ietf.org/doc/html/rfc5652#section-6
+//! [RFC 5753]: https://datatracker.ietf.org/doc/html/rfc5753
+
+use alloc::vec::Vec;
+
+use rasn::{error::EncodeError, prelude::*};
+use rasn_pkix::SubjectPublicKeyInfo;
+
+use crate::{
+    algorithms::{EccCmsSharedInfo, KeyWrapAlgorithm},
+    *,
+};
+
+/// Encrypts and decrypts content.
+pub trait ContentCipher {
+    /// Encrypts `plaintext` with `key`, or returns `None` if the algorithm
+    /// isn't supported.
+    fn encrypt(
+        &self,
+        algorithm: &ContentEncryptionAlgorithmIdentifier,
+        key: &[u8],
+        plaintext: &[u8],
+    ) -> Option<Vec<u8>>;
+
+    /// Decrypts `ciphertext` with `key`, or returns `None` if the algorithm
+    /// isn't supported or decryption fails.
+    fn decrypt(
+        &self,
+        algorithm: &ContentEncryptionAlgorithmIdentifier,
+        key: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// Encrypts and decrypts content with an authenticated encryption algorithm,
+/// such as AES-GCM.
+pub trait AuthenticatedCipher {
+    /// Encrypts `plaintext` with `key`, authenticating it along with `aad`,
+    /// and returns the ciphertext and message authentication code, or `None`
+    /// if the algorithm isn't supported.
+    fn encrypt(
+        &self,
+        algorithm: &ContentEncryptionAlgorithmIdentifier,
+        key: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Option<(Vec<u8>, Vec<u8>)>;
+
+    /// Decrypts `ciphertext` with `key`, or returns `None` if the algorithm
+    /// isn't supported or `mac` doesn't authenticate the ciphertext and
+    /// `aad`.
+    fn decrypt(
+        &self,
+        algorithm: &ContentEncryptionAlgorithmIdentifier,
+        key: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        mac: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// Encrypts keys for a recipient's public key.
+pub trait KeyTransportEncryptor {
+    /// Encrypts `key` for `public_key`, or returns `None` if the algorithm
+    /// isn't supported.
+    fn encrypt_key(
+        &self,
+        algorithm: &KeyEncryptionAlgorithmIdentifier,
+        public_key: &SubjectPublicKeyInfo,
+        key: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// Decrypts keys encrypted for the recipient's public key.
+pub trait KeyTransportDecryptor {
+    /// Decrypts `encrypted_key` with the recipient's private key, or returns
+    /// `None` if the algorithm isn't supported or decryption fails.
+    fn decrypt_key(
+        &self,
+        algorithm: &KeyEncryptionAlgorithmIdentifier,
+        encrypted_key: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// Agrees key-encryption keys with another party's public key.
+pub trait KeyAgreement {
+    /// Agrees a shared secret between the private key and `public_key`, and
+    /// derives a `length` byte key-encryption key from it and `shared_info`
+    /// with the key derivation function of `algorithm`. Returns `None` if the
+    /// algorithm isn't supported or the public key is invalid.
+    fn derive_kek(
+        &self,
+        algorithm: &KeyEncryptionAlgorithmIdentifier,
+        public_key: &BitString,
+        shared_info: &[u8],
+        length: usize,
+    ) -> Option<Vec<u8>>;
+}
+
+/// Derives key-encryption keys from passwords.
+pub trait KeyDerivation {
+    /// Derives a `length` byte key from `password`, or returns `None` if the
+    /// algorithm isn't supported.
+    fn derive_key(
+        &self,
+        algorithm: &KeyDerivationAlgorithmIdentifier,
+        password: &[u8],
+        length: usize,
+    ) -> Option<Vec<u8>>;
+}
+
+/// Wraps keys with a key-encryption key.
+pub trait KeyWrap {
+    /// Wraps `key` with `kek`, or returns `None` if the algorithm isn't
+    /// supported.
+    fn wrap(
+        &self,
+        algorithm: &KeyEncryptionAlgorithmIdentifier,
+        kek: &[u8],
+        key: &[u8],
+    ) -> Option<Vec<u8>>;
+
+    /// Unwraps `wrapped_key` with `kek`, or returns `None` if the algorithm
+    /// isn't supported or the integrity check fails.
+    fn unwrap(
+        &self,
+        algorithm: &KeyEncryptionAlgorithmIdentifier,
+        kek: &[u8],
+        wrapped_key: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// An error returned when creating or opening an envelope.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnvelopeError {
+    /// The key length of an algorithm isn't known.
+    UnsupportedAlgorithm(ObjectIdentifier),
+    /// The parameters of an algorithm are missing or invalid.
+    InvalidParameters(ObjectIdentifier),
+    /// A key couldn't be encrypted, wrapped or agreed.
+    KeyEncryption,
+    /// A key couldn't be decrypted, unwrapped or agreed.
+    KeyDecryption,
+    /// The content couldn't be encrypted.
+    ContentEncryption,
+    /// The content couldn't be decrypted or authenticated.
+    ContentDecryption,
+    /// The encrypted content is detached.
+    MissingContent,
+    /// The originator of a key agreement isn't identified by its public key.
+    MissingOriginatorKey,
+    /// A password recipient has no key derivation algorithm.
+    MissingKeyDerivation,
+    /// No encrypted key was found for the recipient.
+    UnknownRecipient,
+    /// A structure couldn't be encoded.
+    Encode(EncodeError),
+}
+
+impl core::fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedAlgorithm(oid) => write!(f, "unsupported algorithm {oid:?}"),
+            Self::InvalidParameters(oid) => write!(f, "invalid parameters for {oid:?}"),
+            Self::KeyEncryption => write!(f, "key encryption failed"),
+            Self::KeyDecryption => write!(f, "key decryption failed"),
+            Self::ContentEncryption => write!(f, "content encryption failed"),
+            Self::ContentDecryption => write!(f, "content decryption failed"),
+            Self::MissingContent => write!(f, "encrypted content is detached"),
+            Self::MissingOriginatorKey => write!(f, "originator public key not present"),
+            Self::MissingKeyDerivation => write!(f, "key derivation algorithm not present"),
+            Self::UnknownRecipient => write!(f, "no encrypted key for the recipient"),
+            Self::Encode(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<EncodeError> for EnvelopeError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+/// Decodes the parameters of `algorithm` as `T`.
+fn parameters<T: Decode>(algorithm: &AlgorithmIdentifier) -> Result<T, EnvelopeError> {
+    algorithm
+        .parameters
+        .as_ref()
+        .and_then(|parameters| rasn::der::decode(parameters.as_bytes()).ok())
+        .ok_or_else(|| EnvelopeError::InvalidParameters(algorithm.algorithm.clone()))
+}
+
+/// Returns the length of the key used by `algorithm`, looking through the
+/// parameters of `id-alg-PWRI-KEK` to the underlying cipher.
+fn key_length(algorithm: &AlgorithmIdentifier) -> Result<usize, EnvelopeError> {
+    if algorithm.algorithm == *algorithms::PWRI_KEK {
+        return key_length(&parameters(algorithm)?);
+    }
+
+    algorithms::key_length(&algorithm.algorithm)
+        .ok_or_else(|| EnvelopeError::UnsupportedAlgorithm(algorithm.algorithm.clone()))
+}
+
+impl KeyTransRecipientInfo {
+    /// Encrypts `cek` for the recipient identified by `rid`, whose public key
+    /// is `public_key`.
+    pub fn new<E: KeyTransportEncryptor>(
+        rid: RecipientIdentifier,
+        key_encryption_algorithm: KeyEncryptionAlgorithmIdentifier,
+        public_key: &SubjectPublicKeyInfo,
+        cek: &[u8],
+        encryptor: &E,
+    ) -> Result<Self, EnvelopeError> {
+        let encrypted_key = encryptor
+            .encrypt_key(&key_encryption_algorithm, public_key, cek)
+            .ok_or(EnvelopeError::KeyEncryption)?;
+
+        Ok(Self {
+            version: match rid {
+                RecipientIdentifier::IssuerAndSerialNumber(_) => 0.into(),
+                RecipientIdentifier::SubjectKeyIdentifier(_) => 2.into(),
+            },
+            rid,
+            key_encryption_algorithm,
+            encrypted_key: encrypted_key.into(),
+        })
+    }
+
+    /// Decrypts the content-encryption key.
+    pub fn decrypt_key<D: KeyTransportDecryptor>(
+        &self,
+        decryptor: &D,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        decryptor
+            .decrypt_key(&self.key_encryption_algorithm, &self.encrypted_key)
+            .ok_or(EnvelopeError::KeyDecryption)
+    }
+}
+
+impl KeyAgreeRecipientInfo {
+    /// Wraps `cek` for each of `recipients`, identified along with their
+    /// public keys, with key-encryption keys agreed with `agreement`.
+    /// `key_encryption_algorithm` is the key agreement algorithm, whose
+    /// parameters are the key wrap algorithm, and `originator` identifies the
+    /// public key matching `agreement`'s private key.
+    pub fn new<A: KeyAgreement, W: KeyWrap>(
+        originator: OriginatorIdentifierOrKey,
+        user_keying_material: Option<UserKeyingMaterial>,
+        key_encryption_algorithm: KeyEncryptionAlgorithmIdentifier,
+        recipients: &[(KeyAgreeRecipientIdentifier, &BitString)],
+        cek: &[u8],
+        agreement: &A,
+        wrap: &W,
+    ) -> Result<Self, EnvelopeError> {
+        let mut info = Self {
+            version: 3.into(),
+            originator,
+            user_keying_material,
+            key_encryption_algorithm,
+            recipient_encrypted_keys: Vec::with_capacity(recipients.len()),
+        };
+
+        for (rid, public_key) in recipients {
+            let (wrap_algorithm, kek) = info.derive_kek(public_key, agreement)?;
+            let encrypted_key = wrap
+                .wrap(&wrap_algorithm, &kek, cek)
+                .ok_or(EnvelopeError::KeyEncryption)?;
+            info.recipient_encrypted_keys.push(RecipientEncryptedKey {
+                key_agree_recipient_identifier: rid.clone(),
+                encrypted_key: encrypted_key.into(),
+            });
+        }
+
+        Ok(info)
+    }
+
+    /// Returns the key wrap algorithm from the parameters of the key
+    /// agreement algorithm.
+    pub fn key_wrap_algorithm(&self) -> Result<KeyWrapAlgorithm, EnvelopeError> {
+        parameters(&self.key_encryption_algorithm)
+    }
+
+    /// Unwraps the content-encryption key for the recipient identified by
+    /// `rid`, with a key-encryption key agreed with the originator's public
+    /// key.
+    pub fn decrypt_key<A: KeyAgreement, W: KeyWrap>(
+        &self,
+        rid: &KeyAgreeRecipientIdentifier,
+        agreement: &A,
+        wrap: &W,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        let encrypted_key = self
+            .recipient_encrypted_keys
+            .iter()
+            .find(|key| key.key_agree_recipient_identifier == *rid)
+            .ok_or(EnvelopeError::UnknownRecipient)?;
+        let OriginatorIdentifierOrKey::OriginatorPublicKey(originator) = &self.originator else {
+            return Err(EnvelopeError::MissingOriginatorKey);
+        };
+
+        let (wrap_algorithm, kek) =
+            self.derive_kek(&originator.public_key, agreement)
+                .map_err(|error| match error {
+                    EnvelopeError::KeyEncryption => EnvelopeError::KeyDecryption,
+                    error => error,
+                })?;
+        wrap.unwrap(&wrap_algorithm, &kek, &encrypted_key.encrypted_key)
+            .ok_or(EnvelopeError::KeyDecryption)
+    }
+
+    /// Derives the key-encryption key agreed with `public_key`, returning it
+    /// along with the key wrap algorithm it's used with.
+    fn derive_kek<A: KeyAgreement>(
+        &self,
+        public_key: &BitString,
+        agreement: &A,
+    ) -> Result<(KeyWrapAlgorithm, Vec<u8>), EnvelopeError> {
+        let wrap_algorithm = self.key_wrap_algorithm()?;
+        let length = key_length(&wrap_algorithm)?;
+        let shared_info = rasn::der::encode(&EccCmsSharedInfo {
+            key_info: wrap_algorithm.clone(),
+            entity_u_info: self.user_keying_material.clone(),
+            supp_pub_info: ((length * 8) as u32).to_be_bytes().to_vec().into(),
+        })?;
+
+        let kek = agreement
+            .derive_kek(
+                &self.key_encryption_algorithm,
+                public_key,
+                &shared_info,
+                length,
+            )
+            .ok_or(EnvelopeError::KeyEncryption)?;
+        Ok((wrap_algorithm, kek))
+    }
+}
+
+impl KekRecipientInfo {
+    /// Wraps `cek` with the previously distributed `kek`, identified by
+    /// `kek_id`.
+    pub fn new<W: KeyWrap>(
+        kek_id: KekIdentifier,
+        key_encryption_algorithm: KeyEncryptionAlgorithmIdentifier,
+        kek: &[u8],
+        cek: &[u8],
+        wrap: &W,
+    ) -> Result<Self, EnvelopeError> {
+        let encrypted_key = wrap
+            .wrap(&key_encryption_algorithm, kek, cek)
+            .ok_or(EnvelopeError::KeyEncryption)?;
+
+        Ok(Self {
+            version: 4.into(),
+            kek_id,
+            key_encryption_algorithm,
+            encrypted_key: encrypted_key.into(),
+        })
+    }
+
+    /// Unwraps the content-encryption key with `kek`.
+    pub fn decrypt_key<W: KeyWrap>(&self, kek: &[u8], wrap: &W) -> Result<Vec<u8>, EnvelopeError> {
+        wrap.unwrap(&self.key_encryption_algorithm, kek, &self.encrypted_key)
+            .ok_or(EnvelopeError::KeyDecryption)
+    }
+}
+
+impl PasswordRecipientInfo {
+    /// Wraps `cek` with a key-encryption key derived from `password`.
+    pub fn new<K: KeyDerivation, W: KeyWrap>(
+        key_derivation_algorithm: KeyDerivationAlgorithmIdentifier,
+        key_encryption_algorithm: KeyEncryptionAlgorithmIdentifier,
+        password: &[u8],
+        cek: &[u8],
+        derivation: &K,
+        wrap: &W,
+    ) -> Result<Self, EnvelopeError> {
+        let kek = derivation
+            .derive_key(
+                &key_derivation_algorithm,
+                password,
+                key_length(&key_encryption_algorithm)?,
+            )
+            .ok_or(EnvelopeError::KeyEncryption)?;
+        let encrypted_key = wrap
+            .wrap(&key_encryption_algorithm, &kek, cek)
+            .ok_or(EnvelopeError::KeyEncryption)?;
+
+        Ok(Self {
+            version: 0.into(),
+            key_derivation_algorithm: Some(key_derivation_algorithm),
+            key_encryption_algorithm,
+            encrypted_eey: encrypted_key.into(),
+        })
+    }
+
+    /// Unwraps the content-encryption key with a key-encryption key derived
+    /// from `password`.
+    pub fn decrypt_key<K: KeyDerivation, W: KeyWrap>(
+        &self,
+        password: &[u8],
+        derivation: &K,
+        wrap: &W,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        let key_derivation_algorithm = self
+            .key_derivation_algorithm
+            .as_ref()
+            .ok_or(EnvelopeError::MissingKeyDerivation)?;
+        let kek = derivation
+            .derive_key(
+                key_derivation_algorithm,
+                password,
+                key_length(&self.key_encryption_algorithm)?,
+            )
+            .ok_or(EnvelopeError::KeyDecryption)?;
+
+        wrap.unwrap(&self.key_encryption_algorithm, &kek, &self.encrypted_eey)
+            .ok_or(EnvelopeError::KeyDecryption)
+    }
+}
+
+impl From<KeyTransRecipientInfo> for RecipientInfo {
+    fn from(info: KeyTransRecipientInfo) -> Self {
+        Self::KeyTransRecipientInfo(info)
+    }
+}
+
+impl From<KeyAgreeRecipientInfo> for RecipientInfo {
+    fn from(info: KeyAgreeRecipientInfo) -> Self {
+        Self::KeyAgreeRecipientInfo(info)
+    }
+}
+
+impl From<KekRecipientInfo> for RecipientInfo {
+    fn from(info: KekRecipientInfo) -> Self {
+        Self::KekRecipientInfo(info)
+    }
+}
+
+impl From<PasswordRecipientInfo> for RecipientInfo {
+    fn from(info: PasswordRecipientInfo) -> Self {
+        Self::PasswordRecipientInfo(info)
+    }
+}
+
+impl From<OtherRecipientInfo> for RecipientInfo {
+    fn from(info: OtherRecipientInfo) -> Self {
+        Self::OtherRecipientInfo(info)
+    }
+}
+
+/// A builder for [`EnvelopedData`].
+#[derive(Clone, Debug)]
+pub struct EnvelopedDataBuilder {
+    content_type: ContentType,
+    content_encryption_algorithm: ContentEncryptionAlgorithmIdentifier,
+    originator_info: Option<OriginatorInfo>,
+    recipient_infos: RecipientInfos,
+    unprotected_attributes: Vec<Attribute>,
+}
+
+impl EnvelopedDataBuilder {
+    /// Creates a builder for content of `content_type`, encrypted with
+    /// `content_encryption_algorithm`.
+    pub fn new(
+        content_type: ContentType,
+        content_encryption_algorithm: ContentEncryptionAlgorithmIdentifier,
+    ) -> Self {
+        Self {
+            content_type,
+            content_encryption_algorithm,
+            originator_info: None,
+            recipient_infos: RecipientInfos::new(),
+            unprotected_attributes: Vec::new(),
+        }
+    }
+
+    /// Sets the originator certificates and CRLs.
+    pub fn originator_info(mut self, originator_info: OriginatorInfo) -> Self {
+        self.originator_info = Some(originator_info);
+        self
+    }
+
+    /// Adds a recipient of the content-encryption key.
+    pub fn recipient(mut self, recipient_info: impl Into<RecipientInfo>) -> Self {
+        self.recipient_infos.insert(recipient_info.into());
+        self
+    }
+
+    /// Adds an unprotected attribute.
+    pub fn unprotected_attribute(mut self, attribute: Attribute) -> Self {
+        self.unprotected_attributes.push(attribute);
+        self
+    }
+
+    /// Encrypts `content` with `cek`, the key given to the recipients.
+    pub fn build<C: ContentCipher>(
+        self,
+        content: &[u8],
+        cek: &[u8],
+        cipher: &C,
+    ) -> Result<EnvelopedData, EnvelopeError> {
+        let encrypted_content = cipher
+            .encrypt(&self.content_encryption_algorithm, cek, content)
+            .ok_or(EnvelopeError::ContentEncryption)?;
+
+        Ok(EnvelopedData {
+            version: self.version().into(),
+            originator_info: self.originator_info,
+            recipient_infos: self.recipient_infos,
+            encrypted_content_info: EncryptedContentInfo {
+                content_type: self.content_type,
+                content_encryption_algorithm: self.content_encryption_algorithm,
+                encrypted_content: Some(encrypted_content.into()),
+            },
+            unprotected_attrs: (!self.unprotected_attributes.is_empty())
+                .then(|| self.unprotected_attributes.into_iter().collect()),
+        })
+    }
+
+    /// Returns the version, following RFC 5652 Section 6.1.
+    fn version(&self) -> u8 {
+        let certificates = self
+            .originator_info
+            .iter()
+            .flat_map(|info| info.certs.iter().flatten());
+        let crls = self
+            .originator_info
+            .iter()
+            .flat_map(|info| info.crls.iter().flatten());
+
+        let mut other_certificates = false;
+        let mut v2_attribute_certificates = false;
+        for certificate in certificates {
+            match certificate {
+                CertificateChoices::Other(_) => other_certificates = true,
+                CertificateChoices::V2AttributeCertificate(_) => v2_attribute_certificates = true,
+                _ => {}
+            }
+        }
+        let other_crls = crls
+            .clone()
+            .any(|crl| matches!(crl, RevocationInfoChoice::Other(_)));
+        let password_or_other_recipients = self.recipient_infos.iter().any(|info| {
+            matches!(
+                info,
+                RecipientInfo::PasswordRecipientInfo(_) | RecipientInfo::OtherRecipientInfo(_)
+            )
+        });
+        let v0_recipients = self.recipient_infos.iter().all(|info| {
+            matches!(info, RecipientInfo::KeyTransRecipientInfo(info) if info.version == 0.into())
+        });
+
+        if other_certificates || other_crls {
+            4
+        } else if v2_attribute_certificates || password_or_other_recipients {
+            3
+        } else if self.originator_info.is_none()
+            && self.unprotected_attributes.is_empty()
+            && v0_recipients
+        {
+            0
+        } else {
+            2
+        }
+    }
+}
+
+impl EnvelopedData {
+    /// Decrypts the content with `cek`, the content-encryption key recovered
+    /// from one of the recipient infos.
+    pub fn decrypt<C: ContentCipher>(
+        &self,
+        cek: &[u8],
+        cipher: &C,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        let info = &self.encrypted_content_info;
+        let encrypted_content = info
+            .encrypted_content
+            .as_ref()
+            .ok_or(EnvelopeError::MissingContent)?;
+
+        cipher
+            .decrypt(&info.content_encryption_algorithm, cek, encrypted_content)
+            .ok_or(EnvelopeError::ContentDecryption)
+    }
+}
+
+/// A builder for [`AuthEnvelopedData`].
+#[derive(Clone, Debug)]
+pub struct AuthEnvelopedDataBuilder {
+    content_type: ContentType,
+    content_encryption_algorithm: ContentEncryptionAlgorithmIdentifier,
+    originator_info: Option<OriginatorInfo>,
+    recipient_infos: RecipientInfos,
+    auth_attributes: Vec<Attribute>,
+    unauth_attributes: Vec<Attribute>,
+}
+
+impl AuthEnvelopedDataBuilder {
+    /// Creates a builder for content of `content_type`, encrypted with the
+    /// authenticated encryption algorithm `content_encryption_algorithm`.
+    pub fn new(
+        content_type: ContentType,
+        content_encryption_algorithm: ContentEncryptionAlgorithmIdentifier,
+    ) -> Self {
+        Self {
+            content_type,
+            content_encryption_algorithm,
+            originator_info: None,
+            recipient_infos: RecipientInfos::new(),
+            auth_attributes: Vec::new(),
+            unauth_attributes: Vec::new(),
+        }
+    }
+
+    /// Sets the originator certificates and CRLs.
+    pub fn originator_info(mut self, originator_info: OriginatorInfo) -> Self {
+        self.originator_info = Some(originator_info);
+        self
+    }
+
+    /// Adds a recipient of the content-encryption key.
+    pub fn recipient(mut self, recipient_info: impl Into<RecipientInfo>) -> Self {
+        self.recipient_infos.insert(recipient_info.into());
+        self
+    }
+
+    /// Adds an attribute authenticated along with the content.
+    pub fn auth_attribute(mut self, attribute: Attribute) -> Self {
+        self.auth_attributes.push(attribute);
+        self
+    }
+
+    /// Adds an unauthenticated attribute.
+    pub fn unauth_attribute(mut self, attribute: Attribute) -> Self {
+        self.unauth_attributes.push(attribute);
+        self
+    }
+
+    /// Encrypts and authenticates `content` with `cek`, the key given to the
+    /// recipients.
+    pub fn build<C: AuthenticatedCipher>(
+        self,
+        content: &[u8],
+        cek: &[u8],
+        cipher: &C,
+    ) -> Result<AuthEnvelopedData, EnvelopeError> {
+        let auth_attrs: Option<AuthAttributes> =
+            (!self.auth_attributes.is_empty()).then(|| self.auth_attributes.into_iter().collect());
+        let aad = auth_attrs
+            .as_ref()
+            .map(rasn::der::encode)
+            .transpose()?
+            .unwrap_or_default();
+        let (encrypted_content, mac) = cipher
+            .encrypt(&self.content_encryption_algorithm, cek, &aad, content)
+            .ok_or(EnvelopeError::ContentEncryption)?;
+
+        Ok(AuthEnvelopedData {
+            version: 0.into(),
+            originator_info: self.originator_info,
+            recipient_infos: self.recipient_infos,
+            auth_encrypted_content_info: EncryptedContentInfo {
+                content_type: self.content_type,
+                content_encryption_algorithm: self.content_encryption_algorithm,
+                encrypted_content: Some(encrypted_content.into()),
+            },
+            auth_attrs,
+            mac: mac.into(),
+            unauth_attrs: (!self.unauth_attributes.is_empty())
+                .then(|| self.unauth_attributes.into_iter().collect()),
+        })
+    }
+}
+
+impl AuthEnvelopedData {
+    /// Decrypts and authenticates the content with `cek`, the
+    /// content-encryption key recovered from one of the recipient infos.
+    pub fn decrypt<C: AuthenticatedCipher>(
+        &self,
+        cek: &[u8],
+        cipher: &C,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        let info = &self.auth_encrypted_content_info;
+        let encrypted_content = info
+            .encrypted_content
+            .as_ref()
+            .ok_or(EnvelopeError::MissingContent)?;
+        // The authenticated attributes are DER encoded with a SET OF tag.
+        let aad = self
+            .auth_attrs
+            .as_ref()
+            .map(rasn::der::encode)
+            .transpose()?
+            .unwrap_or_default();
+
+        cipher
+            .decrypt(
+                &info.content_encryption_algorithm,
+                cek,
+                &aad,
+                encrypted_content,
+                &self.mac,
+            )
+            .ok_or(EnvelopeError::ContentDecryption)
+    }
+}
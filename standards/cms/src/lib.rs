@@ -5,8 +5,10 @@ extern crate alloc;
 
 pub mod algorithms;
 pub mod authenticode;
+pub mod enveloped;
 pub mod firmware_wrapper;
 pub mod pkcs7_compat;
+pub mod signed;
 
 use alloc::boxed::Box;
 
@@ -51,6 +53,10 @@ pub const CONTENT_ENCRYPTED_DATA: &Oid = Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS7_EN
 pub const CONTENT_AUTHENTICATED_DATA: &Oid =
     Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_CT_AUTHENTICATED_DATA;
 
+/// OID of AuthEnvelopedData content type
+pub const CONTENT_AUTH_ENVELOPED_DATA: &Oid =
+    Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS9_SMIME_CT_AUTH_ENVELOPED_DATA;
+
 pub type CmsVersion = Integer;
 pub type ContentType = ObjectIdentifier;
 pub type DigestAlgorithmIdentifier = AlgorithmIdentifier;
@@ -101,6 +107,11 @@ pub struct ContentInfo {
     pub content: Any,
 }
 
+impl rasn::pem::PemLabel for ContentInfo {
+    const LABEL: &'static str = "CMS";
+    const ALTERNATIVE_LABELS: &'static [&'static str] = &["PKCS7"];
+}
+
 /// SignedData represents a signed-data content type
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SignedData {
@@ -0,0 +1,689 @@
+//! # Signed Data
+//!
+//! Creating and verifying [`SignedData`] as described in [RFC 5652 Section 5].
+//!
+//! When a [`SignerInfo`] has signed attributes, the signature is computed
+//! over the DER encoding of the attributes as a `SET OF Attribute`, with the
+//! universal `SET` tag in place of the `[0] IMPLICIT` tag used in the
+//! `SignerInfo`. The builders and verifiers in this module take care of that,
+//! as well as the mandatory content type and message digest attributes.
+//!
+//! Cryptography is supplied by the caller: digests through a [`Digester`],
+//! signatures through a [`Signer`], and signature verification through a
+//! [`SignatureVerifier`].
+//!
+//! Verification computes the signature over the signed attributes as they
+//! were received, only replacing their tag, since signers don't always sort
+//! them as DER requires. [`SetOf`] doesn't keep that order, so the verifiers
+//! take the received encodings of [`SignedData`] and [`SignerInfo`] rather
+//! than decoded values.
+//!
+//! [RFC 5652 Section 5]: https://datatracker.ietf.org/doc/html/rfc5652#section-5
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    prelude::*,
+};
+pub use rasn_pkix::{builder::Signer, validation::SignatureVerifier};
+use rasn_pkix::{SubjectPublicKeyInfo, Time};
+
+use crate::*;
+
+/// Computes message digests.
+pub trait Digester {
+    /// Returns the digest of `data` using `algorithm`, or `None` if the
+    /// algorithm isn't supported.
+    fn digest(&self, algorithm: &DigestAlgorithmIdentifier, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// An error returned when signing content.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SignError<E> {
+    /// The digester doesn't support the digest algorithm.
+    UnsupportedDigest(ObjectIdentifier),
+    /// Signed attributes are required because the content type isn't
+    /// `id-data`.
+    SignedAttributesRequired,
+    /// No signer with the given identifier was found to countersign.
+    UnknownSigner,
+    /// A structure couldn't be encoded.
+    Encode(EncodeError),
+    /// The signer failed.
+    Sign(E),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for SignError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedDigest(oid) => write!(f, "unsupported digest algorithm {oid:?}"),
+            Self::SignedAttributesRequired => write!(f, "signed attributes are required"),
+            Self::UnknownSigner => write!(f, "no signer to countersign"),
+            Self::Encode(error) => write!(f, "{error}"),
+            Self::Sign(error) => write!(f, "signing failed: {error}"),
+        }
+    }
+}
+
+impl<E> From<EncodeError> for SignError<E> {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+/// An error found when verifying a signature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The content is detached, and wasn't supplied.
+    MissingContent,
+    /// No certificate for the signer was found.
+    MissingCertificate,
+    /// The digester doesn't support the digest algorithm.
+    UnsupportedDigest(ObjectIdentifier),
+    /// A required signed attribute is missing.
+    MissingAttribute(ObjectIdentifier),
+    /// An attribute is repeated, has more or less than one value, or its
+    /// value is invalid.
+    InvalidAttribute(ObjectIdentifier),
+    /// The content type attribute doesn't match the encapsulated content.
+    ContentTypeMismatch,
+    /// The message digest attribute doesn't match the content.
+    DigestMismatch,
+    /// The signature isn't valid.
+    InvalidSignature,
+    /// A structure couldn't be encoded.
+    Encode(EncodeError),
+    /// A structure couldn't be decoded.
+    Decode(DecodeError),
+    /// A received encoding isn't made of definite length values.
+    InvalidEncoding,
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingContent => write!(f, "detached content wasn't supplied"),
+            Self::MissingCertificate => write!(f, "signer certificate not found"),
+            Self::UnsupportedDigest(oid) => write!(f, "unsupported digest algorithm {oid:?}"),
+            Self::MissingAttribute(oid) => write!(f, "missing signed attribute {oid:?}"),
+            Self::InvalidAttribute(oid) => write!(f, "invalid attribute {oid:?}"),
+            Self::ContentTypeMismatch => write!(f, "content type attribute mismatch"),
+            Self::DigestMismatch => write!(f, "message digest mismatch"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::Encode(error) => write!(f, "{error}"),
+            Self::Decode(error) => write!(f, "{error}"),
+            Self::InvalidEncoding => write!(f, "invalid encoding"),
+        }
+    }
+}
+
+/// Creates an attribute with a single DER-encoded value.
+pub fn attribute<T: Encode>(r#type: &Oid, value: &T) -> Result<Attribute, EncodeError> {
+    Ok(Attribute {
+        r#type: r#type.into(),
+        values: SetOf::from([Any::new(rasn::der::encode(value)?)]),
+    })
+}
+
+/// Returns the single value of the attribute `oid` in `attributes`, or
+/// `None` if it isn't present.
+fn single_value<'a>(
+    attributes: &'a SetOf<Attribute>,
+    oid: &Oid,
+) -> Result<Option<&'a Any>, VerifyError> {
+    let mut matching = attributes
+        .iter()
+        .filter(|attribute| attribute.r#type == *oid);
+    let Some(attribute) = matching.next() else {
+        return Ok(None);
+    };
+
+    if matching.next().is_some() || attribute.values.len() != 1 {
+        return Err(VerifyError::InvalidAttribute(oid.into()));
+    }
+
+    Ok(attribute.values.first())
+}
+
+/// Builds a [`SignerInfo`], as part of a [`SignedDataBuilder`] or as a
+/// countersignature.
+///
+/// By default the signer info includes signed attributes, which always
+/// contain the message digest and, except for countersignatures, the
+/// content type.
+#[derive(Clone, Debug)]
+pub struct SignerInfoBuilder {
+    sid: SignerIdentifier,
+    digest_algorithm: DigestAlgorithmIdentifier,
+    signed_attributes: Option<Vec<Attribute>>,
+    unsigned_attributes: Vec<Attribute>,
+}
+
+impl SignerInfoBuilder {
+    /// Creates a builder for a signer identified by `sid`, digesting content
+    /// with `digest_algorithm`.
+    pub fn new(sid: SignerIdentifier, digest_algorithm: DigestAlgorithmIdentifier) -> Self {
+        Self {
+            sid,
+            digest_algorithm,
+            signed_attributes: Some(Vec::new()),
+            unsigned_attributes: Vec::new(),
+        }
+    }
+
+    /// Signs the content directly, without signed attributes. Any signed
+    /// attributes already added are discarded.
+    pub fn without_signed_attributes(mut self) -> Self {
+        self.signed_attributes = None;
+        self
+    }
+
+    /// Adds a signing time attribute.
+    pub fn signing_time(self, time: GeneralizedTime) -> Result<Self, EncodeError> {
+        Ok(self.signed_attribute(attribute(SIGNING_TIME, &Time::new(time))?))
+    }
+
+    /// Adds a signed attribute, enabling signed attributes if they were
+    /// disabled.
+    pub fn signed_attribute(mut self, attribute: Attribute) -> Self {
+        self.signed_attributes
+            .get_or_insert_with(Vec::new)
+            .push(attribute);
+        self
+    }
+
+    /// Adds an unsigned attribute.
+    pub fn unsigned_attribute(mut self, attribute: Attribute) -> Self {
+        self.unsigned_attributes.push(attribute);
+        self
+    }
+
+    /// Signs `content`. `content_type` is `None` for countersignatures, which
+    /// have no content type attribute.
+    fn sign<D: Digester, S: Signer>(
+        &self,
+        content: &[u8],
+        content_type: Option<&ObjectIdentifier>,
+        digester: &D,
+        signer: &S,
+    ) -> Result<SignerInfo, SignError<S::Error>> {
+        let (signed_attrs, message) = match &self.signed_attributes {
+            Some(extra) => {
+                let digest = digester
+                    .digest(&self.digest_algorithm, content)
+                    .ok_or_else(|| {
+                        SignError::UnsupportedDigest(self.digest_algorithm.algorithm.clone())
+                    })?;
+
+                let mut attributes = SetOf::new();
+                if let Some(content_type) = content_type {
+                    attributes.insert(attribute(CONTENT_TYPE, content_type)?);
+                }
+                attributes.insert(attribute(MESSAGE_DIGEST, &OctetString::from(digest))?);
+                attributes.extend(extra.iter().cloned());
+
+                let message = rasn::der::encode(&attributes)?;
+                (Some(attributes), message)
+            }
+            None if content_type.is_some_and(|content_type| content_type != CONTENT_DATA) => {
+                return Err(SignError::SignedAttributesRequired)
+            }
+            None => (None, content.to_vec()),
+        };
+
+        let signature = signer.sign(&message).map_err(SignError::Sign)?;
+
+        Ok(SignerInfo {
+            version: match self.sid {
+                SignerIdentifier::IssuerAndSerialNumber(_) => 1.into(),
+                SignerIdentifier::SubjectKeyIdentifier(_) => 3.into(),
+            },
+            sid: self.sid.clone(),
+            digest_algorithm: self.digest_algorithm.clone(),
+            signed_attrs,
+            signature_algorithm: signer.algorithm(),
+            signature: signature.into(),
+            unsigned_attrs: (!self.unsigned_attributes.is_empty())
+                .then(|| self.unsigned_attributes.iter().cloned().collect()),
+        })
+    }
+
+    /// Creates a countersignature of `signer_info`, signing the contents of
+    /// its signature, and adds it to `signer_info`'s unsigned attributes.
+    pub fn countersign<D: Digester, S: Signer>(
+        &self,
+        signer_info: &mut SignerInfo,
+        digester: &D,
+        signer: &S,
+    ) -> Result<(), SignError<S::Error>> {
+        let countersignature = self.sign(&signer_info.signature, None, digester, signer)?;
+        let value = Any::new(rasn::der::encode(&countersignature)?);
+        let attributes = signer_info.unsigned_attrs.get_or_insert_with(SetOf::new);
+
+        // Countersignatures share a single attribute with one value each.
+        let existing = attributes
+            .iter()
+            .find(|attribute| attribute.r#type == *COUNTER_SIGNATURE)
+            .cloned();
+        let mut counter_attribute = match existing {
+            Some(existing) => {
+                attributes.remove(&existing);
+                existing
+            }
+            None => Attribute {
+                r#type: COUNTER_SIGNATURE.into(),
+                values: SetOf::new(),
+            },
+        };
+        counter_attribute.values.insert(value);
+        attributes.insert(counter_attribute);
+
+        Ok(())
+    }
+}
+
+/// Builds a [`SignedData`] over some content.
+#[derive(Clone, Debug)]
+pub struct SignedDataBuilder<'content> {
+    content_type: ContentType,
+    content: &'content [u8],
+    detached: bool,
+    digest_algorithms: DigestAlgorithmIdentifiers,
+    certificates: CertificateSet,
+    crls: RevocationInfoChoices,
+    signer_infos: Vec<SignerInfo>,
+}
+
+impl<'content> SignedDataBuilder<'content> {
+    /// Creates a builder signing `content` of type `content_type`.
+    pub fn new(content_type: ContentType, content: &'content [u8]) -> Self {
+        Self {
+            content_type,
+            content,
+            detached: false,
+            digest_algorithms: DigestAlgorithmIdentifiers::new(),
+            certificates: CertificateSet::new(),
+            crls: RevocationInfoChoices::new(),
+            signer_infos: Vec::new(),
+        }
+    }
+
+    /// Omits the content from the signed data, producing a detached
+    /// signature.
+    pub fn detached(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    /// Includes a certificate, such as a signer's certificate or one needed
+    /// to build a path to it.
+    pub fn certificate(mut self, certificate: Certificate) -> Self {
+        self.certificates
+            .insert(CertificateChoices::Certificate(Box::new(certificate)));
+        self
+    }
+
+    /// Includes a certificate revocation list.
+    pub fn crl(mut self, crl: CertificateList) -> Self {
+        self.crls.insert(RevocationInfoChoice::Crl(crl));
+        self
+    }
+
+    /// Signs the content with `signer`, as described by `signer_info`.
+    pub fn signer<D: Digester, S: Signer>(
+        mut self,
+        signer_info: &SignerInfoBuilder,
+        digester: &D,
+        signer: &S,
+    ) -> Result<Self, SignError<S::Error>> {
+        let info = signer_info.sign(self.content, Some(&self.content_type), digester, signer)?;
+        self.digest_algorithms.insert(info.digest_algorithm.clone());
+        self.signer_infos.push(info);
+        Ok(self)
+    }
+
+    /// Countersigns the signature of the signer identified by `sid`.
+    pub fn countersign<D: Digester, S: Signer>(
+        mut self,
+        sid: &SignerIdentifier,
+        countersigner_info: &SignerInfoBuilder,
+        digester: &D,
+        signer: &S,
+    ) -> Result<Self, SignError<S::Error>> {
+        let signer_info = self
+            .signer_infos
+            .iter_mut()
+            .find(|info| info.sid == *sid)
+            .ok_or(SignError::UnknownSigner)?;
+        countersigner_info.countersign(signer_info, digester, signer)?;
+        Ok(self)
+    }
+
+    /// Builds the signed data.
+    pub fn build(self) -> SignedData {
+        let other_certificates = self
+            .certificates
+            .iter()
+            .any(|certificate| matches!(certificate, CertificateChoices::Other(_)));
+        let other_crls = self
+            .crls
+            .iter()
+            .any(|crl| matches!(crl, RevocationInfoChoice::Other(_)));
+        let v2_attribute_certificates = self.certificates.iter().any(|certificate| {
+            matches!(certificate, CertificateChoices::V2AttributeCertificate(_))
+        });
+        let v3_signers = self
+            .signer_infos
+            .iter()
+            .any(|info| info.version == 3.into());
+
+        // RFC 5652 Section 5.1.
+        let version = if other_certificates || other_crls {
+            5
+        } else if v2_attribute_certificates {
+            4
+        } else if v3_signers || self.content_type != *CONTENT_DATA {
+            3
+        } else {
+            1
+        };
+
+        SignedData {
+            version: version.into(),
+            digest_algorithms: self.digest_algorithms,
+            encap_content_info: EncapsulatedContentInfo {
+                content_type: self.content_type,
+                content: (!self.detached).then(|| OctetString::copy_from_slice(self.content)),
+            },
+            certificates: (!self.certificates.is_empty()).then_some(self.certificates),
+            crls: (!self.crls.is_empty()).then_some(self.crls),
+            signer_infos: self.signer_infos.into_iter().collect(),
+        }
+    }
+}
+
+impl SignedData {
+    /// Returns the certificate identified by `sid`, if it is included.
+    pub fn find_certificate(&self, sid: &SignerIdentifier) -> Option<&Certificate> {
+        self.certificates
+            .iter()
+            .flatten()
+            .filter_map(|choice| match choice {
+                CertificateChoices::Certificate(certificate) => Some(&**certificate),
+                _ => None,
+            })
+            .find(|certificate| sid.matches(certificate))
+    }
+
+    /// Returns the received encodings of the signer infos in `encoded`, a
+    /// BER encoded `SignedData`.
+    pub fn encoded_signer_infos(encoded: &[u8]) -> Result<Vec<&[u8]>, VerifyError> {
+        signer_infos(encoded).ok_or(VerifyError::InvalidEncoding)
+    }
+
+    /// Verifies every signer of `encoded`, a BER encoded `SignedData`, using
+    /// the signer certificates included in it. `detached_content` must be
+    /// given if the content isn't encapsulated.
+    ///
+    /// This doesn't validate the signer certificates themselves.
+    pub fn verify<D: Digester, V: SignatureVerifier>(
+        encoded: &[u8],
+        detached_content: Option<&[u8]>,
+        digester: &D,
+        verifier: &V,
+    ) -> Result<(), VerifyError> {
+        let data: Self = rasn::ber::decode(encoded).map_err(VerifyError::Decode)?;
+        for encoded_signer_info in Self::encoded_signer_infos(encoded)? {
+            let signer_info: SignerInfo =
+                rasn::ber::decode(encoded_signer_info).map_err(VerifyError::Decode)?;
+            let certificate = data
+                .find_certificate(&signer_info.sid)
+                .ok_or(VerifyError::MissingCertificate)?;
+            data.verify_signer(
+                encoded_signer_info,
+                &certificate.tbs_certificate.subject_public_key_info,
+                detached_content,
+                digester,
+                verifier,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the signature of `signer_info`, the received encoding of one
+    /// of the signer infos, with `public_key`.
+    pub fn verify_signer<D: Digester, V: SignatureVerifier>(
+        &self,
+        signer_info: &[u8],
+        public_key: &SubjectPublicKeyInfo,
+        detached_content: Option<&[u8]>,
+        digester: &D,
+        verifier: &V,
+    ) -> Result<(), VerifyError> {
+        let content = self
+            .encap_content_info
+            .content
+            .as_deref()
+            .or(detached_content)
+            .ok_or(VerifyError::MissingContent)?;
+
+        rasn::ber::decode::<SignerInfo>(signer_info)
+            .map_err(VerifyError::Decode)?
+            .verify(
+                signer_info,
+                content,
+                Some(&self.encap_content_info.content_type),
+                public_key,
+                digester,
+                verifier,
+            )
+    }
+}
+
+impl SignerIdentifier {
+    /// Returns whether this identifies `certificate`.
+    pub fn matches(&self, certificate: &Certificate) -> bool {
+        let tbs = &certificate.tbs_certificate;
+        match self {
+            Self::IssuerAndSerialNumber(id) => {
+                id.issuer == tbs.issuer && id.serial_number == tbs.serial_number
+            }
+            Self::SubjectKeyIdentifier(id) => tbs
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.subject_key_identifier())
+                .is_some_and(|key_id| key_id.is_ok_and(|key_id| key_id == *id)),
+        }
+    }
+}
+
+impl SignerInfo {
+    /// Returns the received encodings of the countersignatures in the
+    /// unsigned attributes.
+    pub fn encoded_countersignatures(&self) -> impl Iterator<Item = &[u8]> {
+        self.unsigned_attrs
+            .iter()
+            .flatten()
+            .filter(|attribute| attribute.r#type == *COUNTER_SIGNATURE)
+            .flat_map(|attribute| attribute.values.iter())
+            .map(|value| value.as_bytes())
+    }
+
+    /// Decodes the countersignatures in the unsigned attributes.
+    pub fn countersignatures(&self) -> Result<Vec<SignerInfo>, DecodeError> {
+        self.encoded_countersignatures()
+            .map(rasn::der::decode)
+            .collect()
+    }
+
+    /// Verifies that `countersignature`, the received encoding of one of the
+    /// countersignatures, is a valid countersignature of this signer info,
+    /// made with `public_key`.
+    pub fn verify_countersignature<D: Digester, V: SignatureVerifier>(
+        &self,
+        countersignature: &[u8],
+        public_key: &SubjectPublicKeyInfo,
+        digester: &D,
+        verifier: &V,
+    ) -> Result<(), VerifyError> {
+        rasn::ber::decode::<SignerInfo>(countersignature)
+            .map_err(VerifyError::Decode)?
+            .verify(
+                countersignature,
+                &self.signature,
+                None,
+                public_key,
+                digester,
+                verifier,
+            )
+    }
+
+    /// Verifies this signer info, whose received encoding is `encoded`.
+    fn verify<D: Digester, V: SignatureVerifier>(
+        &self,
+        encoded: &[u8],
+        content: &[u8],
+        content_type: Option<&ObjectIdentifier>,
+        public_key: &SubjectPublicKeyInfo,
+        digester: &D,
+        verifier: &V,
+    ) -> Result<(), VerifyError> {
+        let message = match &self.signed_attrs {
+            Some(attributes) => {
+                let digest = single_value(attributes, MESSAGE_DIGEST)?
+                    .ok_or_else(|| VerifyError::MissingAttribute(MESSAGE_DIGEST.into()))?;
+                let digest = rasn::der::decode::<OctetString>(digest.as_bytes())
+                    .map_err(|_| VerifyError::InvalidAttribute(MESSAGE_DIGEST.into()))?;
+                let expected = digester
+                    .digest(&self.digest_algorithm, content)
+                    .ok_or_else(|| {
+                        VerifyError::UnsupportedDigest(self.digest_algorithm.algorithm.clone())
+                    })?;
+                if *digest != *expected {
+                    return Err(VerifyError::DigestMismatch);
+                }
+
+                let attribute_content_type = single_value(attributes, CONTENT_TYPE)?
+                    .map(|value| rasn::der::decode::<ObjectIdentifier>(value.as_bytes()))
+                    .transpose()
+                    .map_err(|_| VerifyError::InvalidAttribute(CONTENT_TYPE.into()))?;
+                match (content_type, attribute_content_type) {
+                    (Some(expected), Some(actual)) if *expected != actual => {
+                        return Err(VerifyError::ContentTypeMismatch)
+                    }
+                    (Some(_), None) => {
+                        return Err(VerifyError::MissingAttribute(CONTENT_TYPE.into()))
+                    }
+                    // Countersignatures must not have a content type.
+                    (None, Some(_)) => {
+                        return Err(VerifyError::InvalidAttribute(CONTENT_TYPE.into()))
+                    }
+                    _ => {}
+                }
+
+                signed_attributes(encoded).ok_or(VerifyError::InvalidEncoding)?
+            }
+            None => content.to_vec(),
+        };
+
+        if verifier.verify(
+            public_key,
+            &self.signature_algorithm,
+            &message,
+            &self.signature,
+        ) {
+            Ok(())
+        } else {
+            Err(VerifyError::InvalidSignature)
+        }
+    }
+}
+
+/// Returns the signer infos in an encoded `SignedData`, by walking its
+/// definite length BER framing.
+fn signer_infos(encoded: &[u8]) -> Option<Vec<&[u8]>> {
+    let data = contents(encoded, 0, 0x30)?;
+    // version, digestAlgorithms and encapContentInfo.
+    let mut position = data.start;
+    for tag in [0x02, 0x31, 0x30] {
+        position = contents(encoded, position, tag)?.end;
+    }
+    // certificates and crls.
+    for tag in [0xa0, 0xa1] {
+        if let Some(range) = contents(encoded, position, tag) {
+            position = range.end;
+        }
+    }
+    let signer_infos = contents(encoded, position, 0x31)?;
+    if signer_infos.end != data.end {
+        return None;
+    }
+
+    let mut encodings = Vec::new();
+    let mut position = signer_infos.start;
+    while position < signer_infos.end {
+        let end = contents(encoded, position, 0x30)?.end;
+        encodings.push(encoded.get(position..end)?);
+        position = end;
+    }
+    (position == signer_infos.end).then_some(encodings)
+}
+
+/// Returns the signed attributes of an encoded `SignerInfo` as they were
+/// received, with the universal `SET` tag in place of `[0] IMPLICIT`.
+fn signed_attributes(encoded: &[u8]) -> Option<Vec<u8>> {
+    let info = contents(encoded, 0, 0x30)?;
+    let version = contents(encoded, info.start, 0x02)?;
+    // sid is either a SEQUENCE or a [0] IMPLICIT OCTET STRING.
+    let sid = element(encoded, version.end)?.1;
+    let digest_algorithm = contents(encoded, sid.end, 0x30)?;
+    let attributes = contents(encoded, digest_algorithm.end, 0xa0)?;
+    if attributes.end > info.end {
+        return None;
+    }
+
+    let mut message = encoded[digest_algorithm.end..attributes.end].to_vec();
+    message[0] = 0x31;
+    Some(message)
+}
+
+/// Reads the header of the value at `position`, which must have the single
+/// octet `tag` and a definite length, returning where its contents are.
+fn contents(encoded: &[u8], position: usize, tag: u8) -> Option<Range<usize>> {
+    element(encoded, position)
+        .filter(|(found, _)| *found == tag)
+        .map(|(_, range)| range)
+}
+
+/// Reads the header of the value at `position`, which must have a single
+/// octet tag and a definite length, returning its tag and where its contents
+/// are.
+fn element(encoded: &[u8], position: usize) -> Option<(u8, Range<usize>)> {
+    let (&tag, rest) = encoded.get(position..)?.split_first()?;
+    if tag & 0x1f == 0x1f {
+        return None;
+    }
+    let (header, length) = match *rest.first()? {
+        length @ 0..=0x7f => (1, usize::from(length)),
+        0x80 => return None,
+        first => {
+            let header = 1 + usize::from(first & 0x7f);
+            let length = rest
+                .get(1..header)?
+                .iter()
+                .try_fold(0usize, |length, byte| {
+                    length.checked_mul(256)?.checked_add(usize::from(*byte))
+                })?;
+            (header, length)
+        }
+    };
+
+    let start = position + 1 + header;
+    let end = start.checked_add(length)?;
+    (end <= encoded.len()).then_some((tag, start..end))
+}
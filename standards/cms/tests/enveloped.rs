@@ -0,0 +1,846 @@
+//! Envelopes produced by OpenSSL 3 (`openssl cms -encrypt`) for each kind of
+//! recipient, along with the recipients' keys, and round trips through the
+//! builders.
+
+use aes::cipher::{
+    block_padding::{NoPadding, Pkcs7},
+    generic_array::GenericArray,
+    BlockDecrypt, BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit,
+};
+use aes_gcm::{aead::Aead, Aes128Gcm, Aes256Gcm, Nonce};
+use p256::{ecdh::diffie_hellman, elliptic_curve::sec1::ToEncodedPoint, PublicKey, SecretKey};
+use rasn::types::*;
+use rasn_cms::{
+    algorithms::{self, GcmParameters, Pbkdf2Parameters, Pbkdf2Salt},
+    enveloped::*,
+    *,
+};
+use rasn_pkix::SubjectPublicKeyInfo;
+use rsa::{
+    pkcs1::DecodeRsaPublicKey, pkcs8::DecodePrivateKey, Pkcs1v15Encrypt, RsaPrivateKey,
+    RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+
+const KEY_TRANS: &[u8] = include_bytes!("data/enveloped/keytrans.cms");
+const KEY_AGREE: &[u8] = include_bytes!("data/enveloped/keyagree.cms");
+const KEK: &[u8] = include_bytes!("data/enveloped/kek.cms");
+const PASSWORD: &[u8] = include_bytes!("data/enveloped/pwri.cms");
+const AUTH_ENVELOPED: &[u8] = include_bytes!("data/enveloped/authenveloped.cms");
+const RSA_CERTIFICATE: &[u8] = include_bytes!("data/enveloped/rsa.crt.der");
+const RSA_KEY: &[u8] = include_bytes!("data/enveloped/rsa.key.der");
+const EC_CERTIFICATE: &[u8] = include_bytes!("data/enveloped/ec.crt.der");
+const EC_KEY: &[u8] = include_bytes!("data/enveloped/ec.key.der");
+
+const CONTENT: &[u8] = b"hello, envelope\n";
+const SECRET_KEY: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+const SECRET_PASSWORD: &[u8] = b"correct horse";
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+type TdesCbcEnc = cbc::Encryptor<des::TdesEde3>;
+type TdesCbcDec = cbc::Decryptor<des::TdesEde3>;
+
+/// The IV of the second encryption in the Triple-DES key wrap.
+const TDES_WRAP_IV: [u8; 8] = [0x4a, 0xdd, 0xa2, 0x2c, 0x79, 0xe8, 0x21, 0x05];
+
+fn iv(algorithm: &AlgorithmIdentifier) -> Option<Vec<u8>> {
+    let iv: OctetString = rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+    Some(iv.to_vec())
+}
+
+fn cbc_encrypt(
+    algorithm: &AlgorithmIdentifier,
+    key: &[u8],
+    data: &[u8],
+    pad: bool,
+) -> Option<Vec<u8>> {
+    let iv = iv(algorithm)?;
+    Some(match &algorithm.algorithm {
+        oid if oid == algorithms::AES128_CBC => {
+            let cipher = Aes128CbcEnc::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.encrypt_padded_vec_mut::<Pkcs7>(data)
+            } else {
+                cipher.encrypt_padded_vec_mut::<NoPadding>(data)
+            }
+        }
+        oid if oid == algorithms::AES256_CBC => {
+            let cipher = Aes256CbcEnc::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.encrypt_padded_vec_mut::<Pkcs7>(data)
+            } else {
+                cipher.encrypt_padded_vec_mut::<NoPadding>(data)
+            }
+        }
+        oid if oid == algorithms::DES_EDE3_CBC => {
+            let cipher = TdesCbcEnc::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.encrypt_padded_vec_mut::<Pkcs7>(data)
+            } else {
+                cipher.encrypt_padded_vec_mut::<NoPadding>(data)
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn cbc_decrypt(
+    algorithm: &AlgorithmIdentifier,
+    key: &[u8],
+    data: &[u8],
+    pad: bool,
+) -> Option<Vec<u8>> {
+    let iv = iv(algorithm)?;
+    match &algorithm.algorithm {
+        oid if oid == algorithms::AES128_CBC => {
+            let cipher = Aes128CbcDec::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.decrypt_padded_vec_mut::<Pkcs7>(data).ok()
+            } else {
+                cipher.decrypt_padded_vec_mut::<NoPadding>(data).ok()
+            }
+        }
+        oid if oid == algorithms::AES256_CBC => {
+            let cipher = Aes256CbcDec::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.decrypt_padded_vec_mut::<Pkcs7>(data).ok()
+            } else {
+                cipher.decrypt_padded_vec_mut::<NoPadding>(data).ok()
+            }
+        }
+        oid if oid == algorithms::DES_EDE3_CBC => {
+            let cipher = TdesCbcDec::new_from_slices(key, &iv).ok()?;
+            if pad {
+                cipher.decrypt_padded_vec_mut::<Pkcs7>(data).ok()
+            } else {
+                cipher.decrypt_padded_vec_mut::<NoPadding>(data).ok()
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Decrypts a single block with AES-ECB.
+fn aes_decrypt_block(key: &[u8], block: &[u8]) -> Option<Vec<u8>> {
+    let mut block = GenericArray::clone_from_slice(block);
+    match key.len() {
+        16 => aes::Aes128::new_from_slice(key)
+            .ok()?
+            .decrypt_block(&mut block),
+        32 => aes::Aes256::new_from_slice(key)
+            .ok()?
+            .decrypt_block(&mut block),
+        _ => return None,
+    }
+    Some(block.to_vec())
+}
+
+fn with_iv(algorithm: &AlgorithmIdentifier, iv: &[u8]) -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: algorithm.algorithm.clone(),
+        parameters: Some(Any::new(
+            rasn::der::encode(&OctetString::copy_from_slice(iv)).unwrap(),
+        )),
+    }
+}
+
+fn des_ede3_cbc(iv: &[u8]) -> AlgorithmIdentifier {
+    with_iv(&algorithm(algorithms::DES_EDE3_CBC, None::<&()>), iv)
+}
+
+/// The first eight octets of the SHA-1 digest of `key`.
+fn tdes_wrap_icv(key: &[u8]) -> Vec<u8> {
+    sha1::Sha1::digest(key)[..8].to_vec()
+}
+
+/// RFC 3217 Section 3.1, with a fixed IV.
+fn tdes_wrap(kek: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+    let key: Vec<u8> = key.iter().map(|byte| odd_parity(*byte)).collect();
+    let iv = [3; 8];
+    let temp1 = cbc_encrypt(
+        &des_ede3_cbc(&iv),
+        kek,
+        &[&key[..], &tdes_wrap_icv(&key)].concat(),
+        false,
+    )?;
+    let mut temp3 = [&iv[..], &temp1].concat();
+    temp3.reverse();
+    cbc_encrypt(&des_ede3_cbc(&TDES_WRAP_IV), kek, &temp3, false)
+}
+
+/// RFC 3217 Section 3.2.
+fn tdes_unwrap(kek: &[u8], wrapped_key: &[u8]) -> Option<Vec<u8>> {
+    if wrapped_key.len() != 40 {
+        return None;
+    }
+    let mut temp2 = cbc_decrypt(&des_ede3_cbc(&TDES_WRAP_IV), kek, wrapped_key, false)?;
+    temp2.reverse();
+    let (iv, temp1) = temp2.split_at(8);
+    let mut key = cbc_decrypt(&des_ede3_cbc(iv), kek, temp1, false)?;
+    let icv = key.split_off(24);
+    (icv == tdes_wrap_icv(&key)).then_some(key)
+}
+
+/// Sets the least significant bit of `byte` so that it has odd parity, as
+/// DES keys do.
+fn odd_parity(byte: u8) -> u8 {
+    let high = byte & 0xfe;
+    high | u8::from(high.count_ones().is_multiple_of(2))
+}
+
+struct Crypto;
+
+impl ContentCipher for Crypto {
+    fn encrypt(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        key: &[u8],
+        plaintext: &[u8],
+    ) -> Option<Vec<u8>> {
+        cbc_encrypt(algorithm, key, plaintext, true)
+    }
+
+    fn decrypt(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        key: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>> {
+        cbc_decrypt(algorithm, key, ciphertext, true)
+    }
+}
+
+impl AuthenticatedCipher for Crypto {
+    fn encrypt(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        key: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        let parameters: GcmParameters =
+            rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+        if parameters.aes_icv_len != 16.into() {
+            return None;
+        }
+        let nonce = Nonce::from_slice(&parameters.aes_nonce);
+        let payload = aes_gcm::aead::Payload {
+            msg: plaintext,
+            aad,
+        };
+        let mut ciphertext = match &algorithm.algorithm {
+            oid if oid == algorithms::AES128_GCM => Aes128Gcm::new_from_slice(key)
+                .ok()?
+                .encrypt(nonce, payload)
+                .ok()?,
+            oid if oid == algorithms::AES256_GCM => Aes256Gcm::new_from_slice(key)
+                .ok()?
+                .encrypt(nonce, payload)
+                .ok()?,
+            _ => return None,
+        };
+        let mac = ciphertext.split_off(ciphertext.len() - 16);
+        Some((ciphertext, mac))
+    }
+
+    fn decrypt(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        key: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        mac: &[u8],
+    ) -> Option<Vec<u8>> {
+        let parameters: GcmParameters =
+            rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+        if parameters.aes_icv_len != 16.into() || mac.len() != 16 {
+            return None;
+        }
+        let nonce = Nonce::from_slice(&parameters.aes_nonce);
+        let message = [ciphertext, mac].concat();
+        let payload = aes_gcm::aead::Payload { msg: &message, aad };
+        match &algorithm.algorithm {
+            oid if oid == algorithms::AES128_GCM => Aes128Gcm::new_from_slice(key)
+                .ok()?
+                .decrypt(nonce, payload)
+                .ok(),
+            oid if oid == algorithms::AES256_GCM => Aes256Gcm::new_from_slice(key)
+                .ok()?
+                .decrypt(nonce, payload)
+                .ok(),
+            _ => None,
+        }
+    }
+}
+
+impl KeyWrap for Crypto {
+    fn wrap(&self, algorithm: &AlgorithmIdentifier, kek: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        match &algorithm.algorithm {
+            oid if oid == algorithms::AES128_WRAP => {
+                aes_kw::KekAes128::new(GenericArray::from_slice(kek))
+                    .wrap_vec(key)
+                    .ok()
+            }
+            oid if oid == algorithms::CMS3DESWRAP => tdes_wrap(kek, key),
+            // RFC 3211 Section 2.3.1, with a fixed padding.
+            oid if oid == algorithms::PWRI_KEK => {
+                let cipher: AlgorithmIdentifier =
+                    rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+                let mut block = vec![key.len() as u8, !key[0], !key[1], !key[2]];
+                block.extend(key);
+                while block.len() < 32 || !block.len().is_multiple_of(16) {
+                    block.push(0x5a);
+                }
+                let inner = cbc_encrypt(&cipher, kek, &block, false)?;
+                let outer_iv = &inner[inner.len() - 16..];
+                cbc_encrypt(&with_iv(&cipher, outer_iv), kek, &inner, false)
+            }
+            _ => None,
+        }
+    }
+
+    fn unwrap(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        kek: &[u8],
+        wrapped_key: &[u8],
+    ) -> Option<Vec<u8>> {
+        match &algorithm.algorithm {
+            oid if oid == algorithms::AES128_WRAP => {
+                aes_kw::KekAes128::new(GenericArray::from_slice(kek))
+                    .unwrap_vec(wrapped_key)
+                    .ok()
+            }
+            oid if oid == algorithms::CMS3DESWRAP => tdes_unwrap(kek, wrapped_key),
+            // RFC 3211 Section 2.3.2.
+            oid if oid == algorithms::PWRI_KEK => {
+                let cipher: AlgorithmIdentifier =
+                    rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+                let n = wrapped_key.len();
+                if n < 32 || !n.is_multiple_of(16) {
+                    return None;
+                }
+                // The last block of the inner layer is the IV of the outer.
+                let outer_iv: Vec<u8> = aes_decrypt_block(kek, &wrapped_key[n - 16..])?
+                    .iter()
+                    .zip(&wrapped_key[n - 32..n - 16])
+                    .map(|(a, b)| a ^ b)
+                    .collect();
+                let inner = cbc_decrypt(&with_iv(&cipher, &outer_iv), kek, wrapped_key, false)?;
+                let block = cbc_decrypt(&cipher, kek, &inner, false)?;
+                let length = usize::from(block[0]);
+                let key = block.get(4..4 + length)?;
+                (block[1..4]
+                    .iter()
+                    .zip(key)
+                    .all(|(check, byte)| *check == !byte))
+                .then(|| key.to_vec())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl KeyDerivation for Crypto {
+    fn derive_key(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        password: &[u8],
+        length: usize,
+    ) -> Option<Vec<u8>> {
+        if algorithm.algorithm != *algorithms::PBKDF2 {
+            return None;
+        }
+        let parameters: Pbkdf2Parameters =
+            rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+        let Pbkdf2Salt::Specified(salt) = parameters.salt else {
+            return None;
+        };
+        if parameters.prf != algorithms::default_pbkdf2_algorithm() {
+            return None;
+        }
+        let rounds = u32::try_from(parameters.iteration_count).ok()?;
+        let mut key = vec![0; length];
+        pbkdf2::pbkdf2_hmac::<sha1::Sha1>(password, &salt, rounds, &mut key);
+        Some(key)
+    }
+}
+
+/// A recipient's RSA private key, or a sender encrypting for RSA public keys.
+struct Rsa(Option<RsaPrivateKey>);
+
+impl KeyTransportEncryptor for Rsa {
+    fn encrypt_key(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        public_key: &SubjectPublicKeyInfo,
+        key: &[u8],
+    ) -> Option<Vec<u8>> {
+        if algorithm.algorithm != *algorithms::RSA {
+            return None;
+        }
+        RsaPublicKey::from_pkcs1_der(public_key.subject_public_key.as_raw_slice())
+            .ok()?
+            .encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, key)
+            .ok()
+    }
+}
+
+impl KeyTransportDecryptor for Rsa {
+    fn decrypt_key(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        encrypted_key: &[u8],
+    ) -> Option<Vec<u8>> {
+        if algorithm.algorithm != *algorithms::RSA {
+            return None;
+        }
+        self.0
+            .as_ref()?
+            .decrypt(Pkcs1v15Encrypt, encrypted_key)
+            .ok()
+    }
+}
+
+/// Elliptic curve Diffie-Hellman on P-256, with the ANSI X9.63 KDF.
+struct Ecdh(SecretKey);
+
+impl KeyAgreement for Ecdh {
+    fn derive_kek(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        public_key: &BitString,
+        shared_info: &[u8],
+        length: usize,
+    ) -> Option<Vec<u8>> {
+        if algorithm.algorithm != *algorithms::DH_SINGLE_PASS_STD_DH_SHA256KDF {
+            return None;
+        }
+        let public_key = PublicKey::from_sec1_bytes(public_key.as_raw_slice()).ok()?;
+        let secret = diffie_hellman(self.0.to_nonzero_scalar(), public_key.as_affine());
+
+        let mut kek = Vec::new();
+        for counter in 1u32.. {
+            if kek.len() >= length {
+                break;
+            }
+            let mut hasher = Sha256::new();
+            hasher.update(secret.raw_secret_bytes());
+            hasher.update(counter.to_be_bytes());
+            hasher.update(shared_info);
+            kek.extend(hasher.finalize());
+        }
+        kek.truncate(length);
+        Some(kek)
+    }
+}
+
+fn certificate(der: &[u8]) -> Certificate {
+    rasn::der::decode(der).unwrap()
+}
+
+fn issuer_and_serial_number(certificate: &Certificate) -> IssuerAndSerialNumber {
+    IssuerAndSerialNumber {
+        issuer: certificate.tbs_certificate.issuer.clone(),
+        serial_number: certificate.tbs_certificate.serial_number.clone(),
+    }
+}
+
+fn enveloped_data(der: &[u8]) -> EnvelopedData {
+    let info = rasn::der::decode::<ContentInfo>(der).unwrap();
+    assert_eq!(*CONTENT_ENVELOPED_DATA, info.content_type);
+    rasn::der::decode(info.content.as_bytes()).unwrap()
+}
+
+fn only_recipient(data: &EnvelopedData) -> &RecipientInfo {
+    assert_eq!(1, data.recipient_infos.len());
+    data.recipient_infos.first().unwrap()
+}
+
+fn rsa_key() -> Rsa {
+    Rsa(Some(RsaPrivateKey::from_pkcs8_der(RSA_KEY).unwrap()))
+}
+
+fn ec_key() -> Ecdh {
+    Ecdh(SecretKey::from_pkcs8_der(EC_KEY).unwrap())
+}
+
+fn algorithm(oid: &Oid, parameters: Option<&impl rasn::Encode>) -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: oid.into(),
+        parameters: parameters.map(|parameters| Any::new(rasn::der::encode(parameters).unwrap())),
+    }
+}
+
+fn aes128_wrap() -> AlgorithmIdentifier {
+    algorithm(algorithms::AES128_WRAP, None::<&()>)
+}
+
+#[test]
+fn openssl_key_trans() {
+    let data = enveloped_data(KEY_TRANS);
+    assert_eq!(Integer::from(0), data.version);
+    let RecipientInfo::KeyTransRecipientInfo(info) = only_recipient(&data) else {
+        panic!("expected a key transport recipient");
+    };
+    assert_eq!(
+        RecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(&certificate(
+            RSA_CERTIFICATE
+        ))),
+        info.rid
+    );
+
+    let cek = info.decrypt_key(&rsa_key()).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+    assert!(matches!(
+        data.decrypt(&[0; 16], &Crypto),
+        Err(EnvelopeError::ContentDecryption)
+    ));
+}
+
+#[test]
+fn openssl_key_agree() {
+    let data = enveloped_data(KEY_AGREE);
+    assert_eq!(Integer::from(2), data.version);
+    let RecipientInfo::KeyAgreeRecipientInfo(info) = only_recipient(&data) else {
+        panic!("expected a key agreement recipient");
+    };
+    assert_eq!(aes128_wrap(), info.key_wrap_algorithm().unwrap());
+
+    let rid = KeyAgreeRecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(
+        &certificate(EC_CERTIFICATE),
+    ));
+    let cek = info.decrypt_key(&rid, &ec_key(), &Crypto).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+
+    let other = KeyAgreeRecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(
+        &certificate(RSA_CERTIFICATE),
+    ));
+    assert!(matches!(
+        info.decrypt_key(&other, &ec_key(), &Crypto),
+        Err(EnvelopeError::UnknownRecipient)
+    ));
+}
+
+#[test]
+fn openssl_kek() {
+    let data = enveloped_data(KEK);
+    let RecipientInfo::KekRecipientInfo(info) = only_recipient(&data) else {
+        panic!("expected a KEK recipient");
+    };
+    assert_eq!(&[1, 2], &*info.kek_id.key_identifier);
+
+    let cek = info.decrypt_key(&SECRET_KEY, &Crypto).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+    assert!(matches!(
+        info.decrypt_key(&[0; 16], &Crypto),
+        Err(EnvelopeError::KeyDecryption)
+    ));
+}
+
+#[test]
+fn openssl_password() {
+    let data = enveloped_data(PASSWORD);
+    assert_eq!(Integer::from(3), data.version);
+    let RecipientInfo::PasswordRecipientInfo(info) = only_recipient(&data) else {
+        panic!("expected a password recipient");
+    };
+
+    let cek = info.decrypt_key(SECRET_PASSWORD, &Crypto, &Crypto).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+    assert!(matches!(
+        info.decrypt_key(b"wrong horse", &Crypto, &Crypto),
+        Err(EnvelopeError::KeyDecryption)
+    ));
+}
+
+#[test]
+fn openssl_auth_enveloped() {
+    let info = rasn::der::decode::<ContentInfo>(AUTH_ENVELOPED).unwrap();
+    assert_eq!(*CONTENT_AUTH_ENVELOPED_DATA, info.content_type);
+    let data = rasn::der::decode::<AuthEnvelopedData>(info.content.as_bytes()).unwrap();
+    let parameters: GcmParameters = rasn::der::decode(
+        data.auth_encrypted_content_info
+            .content_encryption_algorithm
+            .parameters
+            .as_ref()
+            .unwrap()
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(12, parameters.aes_nonce.len());
+    assert_eq!(Integer::from(16), parameters.aes_icv_len);
+
+    let Some(RecipientInfo::KeyTransRecipientInfo(recipient)) = data.recipient_infos.first() else {
+        panic!("expected a key transport recipient");
+    };
+    let cek = recipient.decrypt_key(&rsa_key()).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+
+    let mut tampered = data.clone();
+    tampered.mac = OctetString::from(vec![0; 16]);
+    assert!(matches!(
+        tampered.decrypt(&cek, &Crypto),
+        Err(EnvelopeError::ContentDecryption)
+    ));
+}
+
+#[test]
+fn round_trip() {
+    let cek = [0x42; 16];
+    let rsa_certificate = certificate(RSA_CERTIFICATE);
+    let ec_certificate = certificate(EC_CERTIFICATE);
+
+    let key_trans = KeyTransRecipientInfo::new(
+        RecipientIdentifier::SubjectKeyIdentifier(OctetString::from_static(b"rsa")),
+        algorithm(algorithms::RSA, Some(&())),
+        &rsa_certificate.tbs_certificate.subject_public_key_info,
+        &cek,
+        &Rsa(None),
+    )
+    .unwrap();
+    assert_eq!(Integer::from(2), key_trans.version);
+
+    let ephemeral = SecretKey::random(&mut rand::thread_rng());
+    let ec_public_key = &ec_certificate.tbs_certificate.subject_public_key_info;
+    let rid = KeyAgreeRecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(
+        &ec_certificate,
+    ));
+    let key_agree = KeyAgreeRecipientInfo::new(
+        OriginatorIdentifierOrKey::OriginatorPublicKey(OriginatorPublicKey {
+            algorithm: ec_public_key.algorithm.clone(),
+            public_key: BitString::from_slice(
+                ephemeral.public_key().to_encoded_point(false).as_bytes(),
+            ),
+        }),
+        Some(OctetString::from_static(b"user keying material")),
+        algorithm(
+            algorithms::DH_SINGLE_PASS_STD_DH_SHA256KDF,
+            Some(&aes128_wrap()),
+        ),
+        &[(rid.clone(), &ec_public_key.subject_public_key)],
+        &cek,
+        &Ecdh(ephemeral),
+        &Crypto,
+    )
+    .unwrap();
+
+    let kek = KekRecipientInfo::new(
+        KekIdentifier {
+            key_identifier: OctetString::from_static(&[1, 2]),
+            date: None,
+            other: None,
+        },
+        aes128_wrap(),
+        &SECRET_KEY,
+        &cek,
+        &Crypto,
+    )
+    .unwrap();
+
+    let password = PasswordRecipientInfo::new(
+        algorithm(
+            algorithms::PBKDF2,
+            Some(&Pbkdf2Parameters {
+                salt: Pbkdf2Salt::Specified(OctetString::from_static(b"salt")),
+                iteration_count: 1000.into(),
+                key_length: None,
+                prf: algorithms::default_pbkdf2_algorithm(),
+            }),
+        ),
+        algorithm(
+            algorithms::PWRI_KEK,
+            Some(&with_iv(
+                &algorithm(algorithms::AES128_CBC, None::<&()>),
+                &[7; 16],
+            )),
+        ),
+        SECRET_PASSWORD,
+        &cek,
+        &Crypto,
+        &Crypto,
+    )
+    .unwrap();
+
+    let content_encryption = with_iv(&algorithm(algorithms::AES128_CBC, None::<&()>), &[9; 16]);
+    let data = EnvelopedDataBuilder::new(CONTENT_DATA.into(), content_encryption.clone())
+        .recipient(key_trans)
+        .recipient(key_agree)
+        .recipient(kek)
+        .recipient(password)
+        .build(CONTENT, &cek, &Crypto)
+        .unwrap();
+    assert_eq!(Integer::from(3), data.version);
+
+    let data: EnvelopedData = rasn::der::decode(&rasn::der::encode(&data).unwrap()).unwrap();
+    assert_eq!(4, data.recipient_infos.len());
+    for recipient in &data.recipient_infos {
+        let decrypted = match recipient {
+            RecipientInfo::KeyTransRecipientInfo(info) => info.decrypt_key(&rsa_key()),
+            RecipientInfo::KeyAgreeRecipientInfo(info) => {
+                info.decrypt_key(&rid, &ec_key(), &Crypto)
+            }
+            RecipientInfo::KekRecipientInfo(info) => info.decrypt_key(&SECRET_KEY, &Crypto),
+            RecipientInfo::PasswordRecipientInfo(info) => {
+                info.decrypt_key(SECRET_PASSWORD, &Crypto, &Crypto)
+            }
+            RecipientInfo::OtherRecipientInfo(_) => unreachable!(),
+        };
+        assert_eq!(cek.as_slice(), decrypted.unwrap());
+    }
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+
+    // Without password recipients or attributes, key transport recipients
+    // identified by issuer and serial number give version 0.
+    let data = EnvelopedDataBuilder::new(CONTENT_DATA.into(), content_encryption)
+        .recipient(
+            KeyTransRecipientInfo::new(
+                RecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(
+                    &rsa_certificate,
+                )),
+                algorithm(algorithms::RSA, Some(&())),
+                &rsa_certificate.tbs_certificate.subject_public_key_info,
+                &cek,
+                &Rsa(None),
+            )
+            .unwrap(),
+        )
+        .build(CONTENT, &cek, &Crypto)
+        .unwrap();
+    assert_eq!(Integer::from(0), data.version);
+}
+
+/// Triple-DES content encryption with key transport and CMS3DESwrap KEK
+/// recipients, the combinations used by the RFC 4134 Section 5.1 and 5.2
+/// examples.
+#[test]
+fn triple_des_round_trip() {
+    // The key wrap sets odd parity, which Triple-DES ignores.
+    let cek: Vec<u8> = (0..24).collect();
+    let parity_cek: Vec<u8> = cek.iter().map(|byte| odd_parity(*byte)).collect();
+    let kek = [0x5a; 24];
+    let rsa_certificate = certificate(RSA_CERTIFICATE);
+
+    // The same key wrapped by OpenSSL's EVP_des_ede3_wrap, which picks a
+    // random IV.
+    let openssl_wrapped = [
+        0x51, 0x6b, 0xbf, 0x01, 0xb3, 0x46, 0xcb, 0xb6, 0xbe, 0x10, 0x53, 0x37, 0x79, 0x6f, 0x1c,
+        0x08, 0xcb, 0x4a, 0x44, 0xbf, 0xf0, 0xb3, 0x23, 0x6e, 0x6e, 0x3b, 0xe3, 0x29, 0x71, 0x00,
+        0x2f, 0x13, 0x8c, 0x78, 0x29, 0xbd, 0xa9, 0x11, 0x8e, 0x09,
+    ];
+    assert_eq!(parity_cek, tdes_unwrap(&kek, &openssl_wrapped).unwrap());
+
+    let data = EnvelopedDataBuilder::new(CONTENT_DATA.into(), des_ede3_cbc(&[9; 8]))
+        .recipient(
+            KeyTransRecipientInfo::new(
+                RecipientIdentifier::IssuerAndSerialNumber(issuer_and_serial_number(
+                    &rsa_certificate,
+                )),
+                algorithm(algorithms::RSA, Some(&())),
+                &rsa_certificate.tbs_certificate.subject_public_key_info,
+                &cek,
+                &Rsa(None),
+            )
+            .unwrap(),
+        )
+        .recipient(
+            KekRecipientInfo::new(
+                KekIdentifier {
+                    key_identifier: OctetString::from_static(&[1, 2, 3, 4]),
+                    date: None,
+                    other: None,
+                },
+                algorithm(algorithms::CMS3DESWRAP, Some(&())),
+                &kek,
+                &cek,
+                &Crypto,
+            )
+            .unwrap(),
+        )
+        .build(CONTENT, &cek, &Crypto)
+        .unwrap();
+    assert_eq!(Integer::from(2), data.version);
+    // The content and a whole eight octet block of padding.
+    assert_eq!(
+        CONTENT.len() + 8,
+        data.encrypted_content_info
+            .encrypted_content
+            .as_ref()
+            .unwrap()
+            .len()
+    );
+
+    let data: EnvelopedData = rasn::der::decode(&rasn::der::encode(&data).unwrap()).unwrap();
+    let recipients: Vec<_> = data.recipient_infos.iter().collect();
+    let [RecipientInfo::KeyTransRecipientInfo(key_trans), RecipientInfo::KekRecipientInfo(kek_info)] =
+        recipients[..]
+    else {
+        panic!("expected a key transport and a KEK recipient");
+    };
+    let decrypted = key_trans.decrypt_key(&rsa_key()).unwrap();
+    assert_eq!(cek, decrypted);
+    assert_eq!(CONTENT, data.decrypt(&decrypted, &Crypto).unwrap());
+
+    assert_eq!(40, kek_info.encrypted_key.len());
+    let decrypted = kek_info.decrypt_key(&kek, &Crypto).unwrap();
+    assert_eq!(parity_cek, decrypted);
+    assert_eq!(CONTENT, data.decrypt(&decrypted, &Crypto).unwrap());
+
+    // The integrity check catches a wrong key encryption key.
+    assert!(matches!(
+        kek_info.decrypt_key(&[0xa5; 24], &Crypto),
+        Err(EnvelopeError::KeyDecryption)
+    ));
+}
+
+#[test]
+fn auth_enveloped_round_trip() {
+    let cek = [0x24; 16];
+    let gcm = algorithm(
+        algorithms::AES128_GCM,
+        Some(&GcmParameters {
+            aes_nonce: OctetString::from_static(&[3; 12]),
+            aes_icv_len: 16.into(),
+        }),
+    );
+    let content_type = rasn_cms::signed::attribute(CONTENT_TYPE, &CONTENT_DATA.to_owned()).unwrap();
+
+    let data = AuthEnvelopedDataBuilder::new(CONTENT_DATA.into(), gcm)
+        .recipient(
+            KekRecipientInfo::new(
+                KekIdentifier {
+                    key_identifier: OctetString::from_static(&[1, 2]),
+                    date: None,
+                    other: None,
+                },
+                aes128_wrap(),
+                &SECRET_KEY,
+                &cek,
+                &Crypto,
+            )
+            .unwrap(),
+        )
+        .auth_attribute(content_type)
+        .build(CONTENT, &cek, &Crypto)
+        .unwrap();
+    assert_eq!(Integer::from(0), data.version);
+    assert_eq!(16, data.mac.len());
+
+    let data: AuthEnvelopedData = rasn::der::decode(&rasn::der::encode(&data).unwrap()).unwrap();
+    let Some(RecipientInfo::KekRecipientInfo(recipient)) = data.recipient_infos.first() else {
+        panic!("expected a KEK recipient");
+    };
+    let cek = recipient.decrypt_key(&SECRET_KEY, &Crypto).unwrap();
+    assert_eq!(CONTENT, data.decrypt(&cek, &Crypto).unwrap());
+
+    // The attributes are authenticated along with the content.
+    let mut tampered = data;
+    tampered.auth_attrs = None;
+    assert!(matches!(
+        tampered.decrypt(&cek, &Crypto),
+        Err(EnvelopeError::ContentDecryption)
+    ));
+}
@@ -62,3 +62,528 @@ fn test_authenticode() {
         _ => panic!("Unexpected SpcUuid value"),
     }
 }
+
+mod signed {
+    use rasn::types::*;
+    use rasn_cms::{signed::*, *};
+    use rasn_pkix::{AlgorithmIdentifier, SubjectPublicKeyInfo};
+    use rsa::{pkcs1::DecodeRsaPublicKey, Pkcs1v15Sign, RsaPublicKey};
+    use sha2::{Digest, Sha256};
+
+    struct Sha256Digester;
+
+    impl Digester for Sha256Digester {
+        fn digest(&self, algorithm: &AlgorithmIdentifier, data: &[u8]) -> Option<Vec<u8>> {
+            // OpenSSL writes the signature algorithm as the digest algorithm.
+            (algorithm.algorithm == *algorithms::SHA256
+                || algorithm.algorithm == *algorithms::SHA256_WITH_RSA)
+                .then(|| Sha256::digest(data).to_vec())
+        }
+    }
+
+    struct RsaVerifier;
+
+    impl SignatureVerifier for RsaVerifier {
+        fn verify(
+            &self,
+            public_key: &SubjectPublicKeyInfo,
+            _: &AlgorithmIdentifier,
+            message: &[u8],
+            signature: &[u8],
+        ) -> bool {
+            let key =
+                RsaPublicKey::from_pkcs1_der(public_key.subject_public_key.as_raw_slice()).unwrap();
+            key.verify(
+                Pkcs1v15Sign::new::<Sha256>(),
+                &Sha256::digest(message),
+                signature,
+            )
+            .is_ok()
+        }
+    }
+
+    /// A stand-in for a real signature scheme, where the "signature" is the
+    /// key followed by the SHA-256 digest of the message.
+    struct TestSigner(u8);
+
+    impl Signer for TestSigner {
+        type Error = core::convert::Infallible;
+
+        fn algorithm(&self) -> AlgorithmIdentifier {
+            AlgorithmIdentifier {
+                algorithm: algorithms::SHA256_WITH_RSA.into(),
+                parameters: None,
+            }
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let mut signature = vec![self.0];
+            signature.extend(Sha256::digest(message));
+            Ok(signature)
+        }
+    }
+
+    impl SignatureVerifier for TestSigner {
+        fn verify(
+            &self,
+            public_key: &SubjectPublicKeyInfo,
+            algorithm: &AlgorithmIdentifier,
+            message: &[u8],
+            signature: &[u8],
+        ) -> bool {
+            public_key.subject_public_key.as_raw_slice() == [self.0]
+                && *algorithm == self.algorithm()
+                && signature == self.sign(message).unwrap()
+        }
+    }
+
+    fn public_key(key: u8) -> SubjectPublicKeyInfo {
+        SubjectPublicKeyInfo {
+            algorithm: AlgorithmIdentifier {
+                algorithm: algorithms::RSA.into(),
+                parameters: None,
+            },
+            subject_public_key: BitString::from_slice(&[key]),
+        }
+    }
+
+    fn sha256() -> AlgorithmIdentifier {
+        AlgorithmIdentifier {
+            algorithm: algorithms::SHA256.into(),
+            parameters: None,
+        }
+    }
+
+    fn sid(serial: u32) -> SignerIdentifier {
+        SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+            issuer: Name::RdnSequence(Default::default()),
+            serial_number: serial.into(),
+        })
+    }
+
+    #[test]
+    fn verify_openssl() {
+        let info = rasn::der::decode::<ContentInfo>(super::SIGNED_DATA).unwrap();
+        let encoded = info.content.as_bytes();
+        SignedData::verify(encoded, None, &Sha256Digester, &RsaVerifier).unwrap();
+
+        let mut tampered = rasn::der::decode::<SignedData>(encoded).unwrap();
+        tampered.encap_content_info.content = Some(OctetString::from_static(b"goodbye"));
+        assert!(matches!(
+            SignedData::verify(
+                &rasn::der::encode(&tampered).unwrap(),
+                None,
+                &Sha256Digester,
+                &RsaVerifier
+            ),
+            Err(VerifyError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn signed_attributes() {
+        let content = b"hello, world";
+        let data = SignedDataBuilder::new(CONTENT_DATA.into(), content)
+            .signer(
+                &SignerInfoBuilder::new(sid(1), sha256()),
+                &Sha256Digester,
+                &TestSigner(1),
+            )
+            .unwrap()
+            .build();
+
+        assert_eq!(Integer::from(1), data.version);
+        assert_eq!(1, data.digest_algorithms.len());
+        let signer_info = data.signer_infos.first().unwrap();
+        let attributes = signer_info.signed_attrs.as_ref().unwrap();
+        assert!(attributes.iter().any(|a| a.r#type == *CONTENT_TYPE));
+        assert!(attributes.iter().any(|a| a.r#type == *MESSAGE_DIGEST));
+
+        // The signature covers the attributes encoded with a SET tag.
+        let signed = rasn::der::encode(attributes).unwrap();
+        assert_eq!(0x31, signed[0]);
+        assert_eq!(
+            TestSigner(1).sign(&signed).unwrap(),
+            &*signer_info.signature
+        );
+        let encoded = rasn::der::encode(signer_info).unwrap();
+        assert!(encoded
+            .windows(signed.len() - 1)
+            .any(|window| window == &signed[1..]));
+        let signer_info = &encoded[..];
+
+        let decoded = rasn::der::decode::<SignedData>(&rasn::der::encode(&data).unwrap()).unwrap();
+        decoded
+            .verify_signer(
+                signer_info,
+                &public_key(1),
+                None,
+                &Sha256Digester,
+                &TestSigner(1),
+            )
+            .unwrap();
+        assert!(matches!(
+            decoded.verify_signer(
+                signer_info,
+                &public_key(2),
+                None,
+                &Sha256Digester,
+                &TestSigner(1)
+            ),
+            Err(VerifyError::InvalidSignature)
+        ));
+
+        let mut tampered = decoded.clone();
+        tampered.encap_content_info.content = Some(OctetString::from_static(b"goodbye"));
+        assert!(matches!(
+            tampered.verify_signer(
+                signer_info,
+                &public_key(1),
+                None,
+                &Sha256Digester,
+                &TestSigner(1)
+            ),
+            Err(VerifyError::DigestMismatch)
+        ));
+
+        let mut tampered = decoded;
+        tampered.encap_content_info.content_type = CONTENT_SIGNED_DATA.into();
+        assert!(matches!(
+            tampered.verify_signer(
+                signer_info,
+                &public_key(1),
+                None,
+                &Sha256Digester,
+                &TestSigner(1)
+            ),
+            Err(VerifyError::ContentTypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn detached_and_countersigned() {
+        let content = b"detached content";
+        let countersigner = SignerInfoBuilder::new(sid(2), sha256());
+        let data = SignedDataBuilder::new(CONTENT_DATA.into(), content)
+            .detached()
+            .signer(
+                &SignerInfoBuilder::new(sid(1), sha256()).without_signed_attributes(),
+                &Sha256Digester,
+                &TestSigner(1),
+            )
+            .unwrap()
+            .countersign(&sid(1), &countersigner, &Sha256Digester, &TestSigner(2))
+            .unwrap()
+            .countersign(&sid(1), &countersigner, &Sha256Digester, &TestSigner(2))
+            .unwrap()
+            .build();
+
+        assert!(data.encap_content_info.content.is_none());
+        let signer_info = data.signer_infos.first().unwrap();
+        assert!(signer_info.signed_attrs.is_none());
+        let encoded = rasn::der::encode(signer_info).unwrap();
+        assert!(matches!(
+            data.verify_signer(
+                &encoded,
+                &public_key(1),
+                None,
+                &Sha256Digester,
+                &TestSigner(1)
+            ),
+            Err(VerifyError::MissingContent)
+        ));
+        data.verify_signer(
+            &encoded,
+            &public_key(1),
+            Some(content),
+            &Sha256Digester,
+            &TestSigner(1),
+        )
+        .unwrap();
+
+        let countersignatures = signer_info.countersignatures().unwrap();
+        assert_eq!(1, countersignatures.len());
+        let countersignature = &countersignatures[0];
+        assert!(!countersignature
+            .signed_attrs
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|a| a.r#type == *CONTENT_TYPE));
+        let encoded_countersignatures: Vec<_> = signer_info.encoded_countersignatures().collect();
+        assert_eq!(
+            rasn::der::encode(countersignature).unwrap(),
+            encoded_countersignatures[0]
+        );
+        signer_info
+            .verify_countersignature(
+                encoded_countersignatures[0],
+                &public_key(2),
+                &Sha256Digester,
+                &TestSigner(2),
+            )
+            .unwrap();
+
+        let error = SignedDataBuilder::new(CONTENT_SIGNED_DATA.into(), content)
+            .signer(
+                &SignerInfoBuilder::new(sid(1), sha256()).without_signed_attributes(),
+                &Sha256Digester,
+                &TestSigner(1),
+            )
+            .unwrap_err();
+        assert!(matches!(error, SignError::SignedAttributesRequired));
+    }
+
+    #[test]
+    fn unsorted_signed_attributes() {
+        let content = b"hello, world";
+        let data = SignedDataBuilder::new(CONTENT_DATA.into(), content)
+            .signer(
+                &SignerInfoBuilder::new(sid(1), sha256()),
+                &Sha256Digester,
+                &TestSigner(1),
+            )
+            .unwrap()
+            .build();
+        let mut signer_info = data.signer_infos.first().unwrap().clone();
+
+        // Encode the attributes in the reverse of DER order, and sign them
+        // that way, as some signers do.
+        let sorted = rasn::der::encode(signer_info.signed_attrs.as_ref().unwrap()).unwrap();
+        let mut attributes: Vec<_> = signer_info
+            .signed_attrs
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|attribute| rasn::der::encode(attribute).unwrap())
+            .collect();
+        attributes.sort();
+        attributes.reverse();
+        let header = &sorted[..sorted.len() - attributes.concat().len()];
+        let unsorted = [header, &attributes.concat()].concat();
+        assert_ne!(sorted, unsorted);
+        signer_info.signature = TestSigner(1).sign(&unsorted).unwrap().into();
+
+        let encoded = rasn::der::encode(&signer_info).unwrap();
+        let position = encoded
+            .windows(sorted.len() - 1)
+            .position(|window| window == &sorted[1..])
+            .unwrap();
+        let mut received = encoded.clone();
+        received[position..position + sorted.len() - 1].copy_from_slice(&unsorted[1..]);
+
+        // Decoding sorts the attributes, so only the received encoding has
+        // the signed bytes.
+        let decoded = rasn::ber::decode::<SignerInfo>(&received).unwrap();
+        assert_eq!(signer_info, decoded);
+        assert_ne!(received, rasn::der::encode(&decoded).unwrap());
+
+        data.verify_signer(
+            &received,
+            &public_key(1),
+            None,
+            &Sha256Digester,
+            &TestSigner(1),
+        )
+        .unwrap();
+        assert!(matches!(
+            data.verify_signer(
+                &encoded,
+                &public_key(1),
+                None,
+                &Sha256Digester,
+                &TestSigner(1)
+            ),
+            Err(VerifyError::InvalidSignature)
+        ));
+
+        // The signer infos of an encoded SignedData are found as received.
+        let data = SignedData {
+            signer_infos: SetOf::from([signer_info]),
+            ..data
+        };
+        let mut encoded_data = rasn::der::encode(&data).unwrap();
+        let position = encoded_data
+            .windows(encoded.len())
+            .position(|window| window == encoded)
+            .unwrap();
+        encoded_data[position..position + encoded.len()].copy_from_slice(&received);
+        assert_eq!(
+            vec![&received[..]],
+            SignedData::encoded_signer_infos(&encoded_data).unwrap()
+        );
+    }
+}
+
+mod algorithms {
+    use rasn::types::*;
+    use rasn_cms::algorithms::*;
+    use rasn_pkix::AlgorithmIdentifier;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Decodes a DER encoded algorithm identifier to an [`Algorithm`], checks
+    /// that it converts back to the same encoding, and returns it.
+    fn round_trip(der: &str) -> Algorithm {
+        let der = hex(der);
+        let identifier: AlgorithmIdentifier = rasn::der::decode(&der).unwrap();
+        let algorithm = Algorithm::try_from(&identifier).unwrap();
+        assert_eq!(identifier.algorithm, *algorithm.oid());
+        let converted = AlgorithmIdentifier::try_from(&algorithm).unwrap();
+        assert_eq!(identifier, converted);
+        assert_eq!(der, rasn::der::encode(&converted).unwrap());
+        algorithm
+    }
+
+    fn sha256(null: bool) -> AlgorithmIdentifier {
+        AlgorithmIdentifier::try_from(&Algorithm::Sha256(null.then_some(()))).unwrap()
+    }
+
+    #[test]
+    fn null_or_absent() {
+        assert_eq!(
+            Algorithm::Sha256(Some(())),
+            round_trip("300d06096086480165030402010500")
+        );
+        assert_eq!(
+            Algorithm::Sha256(None),
+            round_trip("300b0609608648016503040201")
+        );
+    }
+
+    #[test]
+    fn rsa() {
+        // RSASSA-PSS with SHA-256, as written by OpenSSL.
+        let Algorithm::RsassaPss(Some(parameters)) = round_trip(
+            "304106092a864886f70d01010a3034a00f300d06096086480165030402010500a11c301a06092a864886f7\
+             0d010108300d06096086480165030402010500a203020120",
+        ) else {
+            panic!("expected RSASSA-PSS parameters");
+        };
+        assert_eq!(sha256(true), parameters.hash_algorithm);
+        assert_eq!(
+            Algorithm::Mgf1(sha256(true)),
+            Algorithm::try_from(&parameters.mask_gen_algorithm).unwrap()
+        );
+        assert_eq!(Integer::from(32), parameters.salt_length);
+        assert_eq!(default_trailer_field(), parameters.trailer_field);
+
+        // RSASSA-PSS public keys may have absent parameters.
+        assert_eq!(
+            Algorithm::RsassaPss(None),
+            round_trip("300b06092a864886f70d01010a")
+        );
+
+        // RSAES-OAEP with SHA-256, whose hash parameters are absent.
+        let Algorithm::RsaesOaep(parameters) = round_trip(
+            "303806092a864886f70d010107302ba00d300b0609608648016503040201a11a301806092a864886f70d01\
+             0108300b0609608648016503040201",
+        ) else {
+            panic!("expected RSAES-OAEP parameters");
+        };
+        assert_eq!(sha256(false), parameters.hash_func);
+        assert_eq!(default_p_source_func(), parameters.p_source_func);
+
+        // Default parameters are omitted.
+        let defaults = Algorithm::RsaesOaep(RsaesOaepParameters {
+            hash_func: default_sha1_identifier(),
+            mask_gen_func: default_mgf1_sha1_identifier(),
+            p_source_func: default_p_source_func(),
+        });
+        assert_eq!(
+            hex("300d06092a864886f70d0101073000"),
+            rasn::der::encode(&AlgorithmIdentifier::try_from(&defaults).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn elliptic_curves() {
+        assert_eq!(
+            Algorithm::EcPublicKey(EcParameters::NamedCurve(SECP256R1.into())),
+            round_trip("301306072a8648ce3d020106082a8648ce3d030107")
+        );
+        assert_eq!(
+            Algorithm::DhSinglePassStdDhSha256Kdf(AlgorithmIdentifier {
+                algorithm: AES128_WRAP.into(),
+                parameters: None,
+            }),
+            round_trip("301506062b8104010b01300b0609608648016503040105")
+        );
+        assert_eq!(Algorithm::Ed25519, round_trip("300506032b6570"));
+
+        // EdDSA parameters must be absent.
+        let identifier: AlgorithmIdentifier =
+            rasn::der::decode(&hex("300706032b65700500")).unwrap();
+        assert!(Algorithm::try_from(&identifier).is_err());
+    }
+
+    #[test]
+    fn password_based_encryption() {
+        let Algorithm::Pbes2(parameters) = round_trip(
+            "305f06092a864886f70d01050d3052303106092a864886f70d01050c30240410031f6c82a1960d0484427e\
+             0079b04b3802020800300c06082a864886f70d02090500301d060960864801650304012a04106b62c58c10\
+             ed98a9080066c66c26c634",
+        ) else {
+            panic!("expected PBES2 parameters");
+        };
+
+        let Algorithm::Pbkdf2(kdf) = Algorithm::try_from(&parameters.key_derivation_func).unwrap()
+        else {
+            panic!("expected PBKDF2 parameters");
+        };
+        assert_eq!(Integer::from(2048), kdf.iteration_count);
+        assert_eq!(
+            Algorithm::HmacWithSha256(Some(())),
+            Algorithm::try_from(&kdf.prf).unwrap()
+        );
+        assert!(matches!(
+            Algorithm::try_from(&parameters.encryption_scheme).unwrap(),
+            Algorithm::Aes256Cbc(iv) if iv.len() == 16
+        ));
+    }
+
+    #[test]
+    fn authenticated_encryption() {
+        let Algorithm::Aes128Gcm(parameters) =
+            round_trip("301e06096086480165030401063011040cae003411cbd4e80906a7a3da020110")
+        else {
+            panic!("expected AES-GCM parameters");
+        };
+        assert_eq!(Integer::from(16), parameters.aes_icv_len);
+
+        // The default ICV length is omitted.
+        let ccm = Algorithm::Aes256Ccm(CcmParameters {
+            aes_nonce: OctetString::from_static(&[1; 7]),
+            aes_icv_len: default_aes_icv_len(),
+        });
+        let identifier = AlgorithmIdentifier::try_from(&ccm).unwrap();
+        assert_eq!(
+            hex("3009040701010101010101"),
+            identifier.parameters.as_ref().unwrap().as_bytes()
+        );
+        assert_eq!(ccm, Algorithm::try_from(&identifier).unwrap());
+    }
+
+    #[test]
+    fn unknown_and_invalid() {
+        let unknown = AlgorithmIdentifier {
+            algorithm: ObjectIdentifier::new(vec![1, 2, 3, 4]).unwrap(),
+            parameters: Some(Any::new(vec![0x02, 0x01, 0x05])),
+        };
+        let algorithm = Algorithm::try_from(&unknown).unwrap();
+        assert_eq!(Algorithm::Other(unknown.clone()), algorithm);
+        assert_eq!(unknown, AlgorithmIdentifier::try_from(&algorithm).unwrap());
+
+        // GCM parameters are required.
+        let missing = AlgorithmIdentifier {
+            algorithm: AES128_GCM.into(),
+            parameters: None,
+        };
+        assert!(Algorithm::try_from(&missing).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+//! The MIT `FILE:` credential cache format, version 4, which stores a
+//! principal's tickets and their session keys.
+//!
+//! ```
+//! use rasn_kerberos::ccache::Ccache;
+//!
+//! let ccache = Ccache::parse(include_bytes!("../tests/data/krb5cc")).unwrap();
+//! println!("Default principal: {}", ccache.principal);
+//! for credential in ccache.credentials() {
+//!     println!("{} valid until {}", credential.server, credential.end_time.0);
+//! }
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::file::{put_data16, put_data32, put_i16, put_time, put_u32, string, Reader};
+use crate::{
+    AuthorizationData, AuthorizationDataValue, EncryptionKey, HostAddress, HostAddresses,
+    KerberosFlags, KerberosTime, PrincipalName, Ticket, TicketFlags,
+};
+
+pub use crate::file::{FileError, Principal};
+
+/// The header tag of the KDC time offset.
+const KDC_OFFSET_TAG: u16 = 1;
+
+/// A credential cache file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ccache {
+    /// The difference between the KDC's clock and the local clock. Unknown
+    /// header fields are skipped when parsing.
+    pub kdc_offset: Option<KdcOffset>,
+    /// The default client principal of the cache.
+    pub principal: Principal,
+    /// The credentials and configuration entries, in the order they were
+    /// stored.
+    pub entries: Vec<CcacheEntry>,
+}
+
+/// The difference between the KDC's clock and the local clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KdcOffset {
+    pub seconds: i32,
+    pub microseconds: i32,
+}
+
+/// An entry in a [Ccache].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CcacheEntry {
+    Credential(Box<Credential>),
+    Config(ConfigEntry),
+}
+
+/// A ticket and the information the client needs to use it, corresponding to
+/// the contents of an [crate::EncKdcRepPart].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credential {
+    pub client: Principal,
+    pub server: Principal,
+    /// The session key.
+    pub key: EncryptionKey,
+    pub auth_time: KerberosTime,
+    pub start_time: Option<KerberosTime>,
+    pub end_time: KerberosTime,
+    pub renew_till: Option<KerberosTime>,
+    /// Whether the ticket is encrypted in another ticket's session key, rather
+    /// than the server's long-term key.
+    pub is_skey: bool,
+    pub flags: TicketFlags,
+    pub addresses: HostAddresses,
+    pub authorization_data: AuthorizationData,
+    pub ticket: Ticket,
+    /// The additional ticket used for user-to-user authentication.
+    pub second_ticket: Option<Ticket>,
+}
+
+/// A configuration value stored in the cache by the Kerberos library, such as
+/// whether the KDC supports FAST. These are stored as credentials for a server
+/// principal in the [ConfigEntry::REALM] realm.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigEntry {
+    pub client: Principal,
+    /// The principal `krb5_ccache_conf_data/<key>[/<principal>]`.
+    pub server: Principal,
+    pub value: Vec<u8>,
+}
+
+impl Ccache {
+    /// The file format version supported by this module.
+    pub const VERSION: u16 = 0x0504;
+
+    /// Creates an empty cache for `principal`.
+    pub fn new(principal: Principal) -> Self {
+        Self {
+            kdc_offset: None,
+            principal,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Parses a credential cache file, decoding each embedded ticket.
+    pub fn parse(input: &[u8]) -> Result<Self, FileError> {
+        let mut reader = Reader::new(input);
+        let version = reader.u16()?;
+        if version != Self::VERSION {
+            return Err(FileError::UnsupportedVersion(version));
+        }
+
+        let mut header = Reader::new(reader.data16()?);
+        let mut kdc_offset = None;
+        while !header.is_empty() {
+            let tag = header.u16()?;
+            let mut value = Reader::new(header.data16()?);
+            if tag == KDC_OFFSET_TAG {
+                kdc_offset = Some(KdcOffset {
+                    seconds: value.i32()?,
+                    microseconds: value.i32()?,
+                });
+            }
+        }
+
+        let principal = read_principal(&mut reader)?;
+        let mut entries = Vec::new();
+        while !reader.is_empty() {
+            entries.push(CcacheEntry::parse(&mut reader)?);
+        }
+
+        Ok(Self {
+            kdc_offset,
+            principal,
+            entries,
+        })
+    }
+
+    /// Serialises the cache, encoding each embedded ticket with DER.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, FileError> {
+        let mut header = Vec::new();
+        if let Some(offset) = &self.kdc_offset {
+            header.extend_from_slice(&KDC_OFFSET_TAG.to_be_bytes());
+            put_data16(
+                &mut header,
+                &[
+                    offset.seconds.to_be_bytes(),
+                    offset.microseconds.to_be_bytes(),
+                ]
+                .concat(),
+            )?;
+        }
+
+        let mut output = Self::VERSION.to_be_bytes().to_vec();
+        put_data16(&mut output, &header)?;
+        write_principal(&mut output, &self.principal)?;
+        for entry in &self.entries {
+            entry.write(&mut output)?;
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the credentials in the cache, skipping configuration entries.
+    pub fn credentials(&self) -> impl Iterator<Item = &Credential> {
+        self.entries.iter().filter_map(|entry| match entry {
+            CcacheEntry::Credential(credential) => Some(&**credential),
+            CcacheEntry::Config(_) => None,
+        })
+    }
+
+    /// Returns the most recently stored credential for `server`.
+    pub fn find(&self, server: &Principal) -> Option<&Credential> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            CcacheEntry::Credential(credential) if credential.server.matches(server) => {
+                Some(&**credential)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the most recently stored configuration value for `key`,
+    /// optionally scoped to `principal`.
+    pub fn config(&self, key: &str, principal: Option<&Principal>) -> Option<&[u8]> {
+        let expected = ConfigEntry::server(key, principal).ok()?;
+        self.entries.iter().rev().find_map(|entry| match entry {
+            CcacheEntry::Config(config) if config.server.matches(&expected) => Some(&*config.value),
+            _ => None,
+        })
+    }
+}
+
+impl CcacheEntry {
+    fn parse(reader: &mut Reader<'_>) -> Result<Self, FileError> {
+        let client = read_principal(reader)?;
+        let server = read_principal(reader)?;
+        let key = EncryptionKey {
+            r#type: reader.i16()?,
+            value: reader.data32()?.to_vec().into(),
+        };
+        let auth_time = reader.time()?;
+        let start_time = reader.time()?;
+        let end_time = reader.time()?;
+        let renew_till = reader.time()?;
+        let is_skey = reader.u8()? != 0;
+        let flags = TicketFlags(KerberosFlags::from_slice(&reader.u32()?.to_be_bytes()));
+
+        let addresses = (0..reader.u32()?)
+            .map(|_| {
+                Ok(HostAddress {
+                    addr_type: reader.i16()?,
+                    address: reader.data32()?.to_vec().into(),
+                })
+            })
+            .collect::<Result<_, FileError>>()?;
+        let authorization_data = (0..reader.u32()?)
+            .map(|_| {
+                Ok(AuthorizationDataValue {
+                    r#type: reader.i16()?,
+                    data: reader.data32()?.to_vec().into(),
+                })
+            })
+            .collect::<Result<_, FileError>>()?;
+
+        let ticket = reader.data32()?;
+        let second_ticket = reader.data32()?;
+
+        if server.realm.as_slice() == ConfigEntry::REALM.as_bytes() {
+            return Ok(Self::Config(ConfigEntry {
+                client,
+                server,
+                value: ticket.to_vec(),
+            }));
+        }
+
+        let unset = |time: KerberosTime| (time.0.timestamp() != 0).then_some(time);
+        Ok(Self::Credential(Box::new(Credential {
+            client,
+            server,
+            key,
+            auth_time,
+            start_time: unset(start_time),
+            end_time,
+            renew_till: unset(renew_till),
+            is_skey,
+            flags,
+            addresses,
+            authorization_data,
+            ticket: rasn::der::decode(ticket)?,
+            second_ticket: match second_ticket {
+                [] => None,
+                ticket => Some(rasn::der::decode(ticket)?),
+            },
+        })))
+    }
+
+    fn write(&self, output: &mut Vec<u8>) -> Result<(), FileError> {
+        let credential = match self {
+            Self::Credential(credential) => credential,
+            Self::Config(config) => {
+                // Everything besides the principals and the value is zero.
+                write_principal(output, &config.client)?;
+                write_principal(output, &config.server)?;
+                output.extend_from_slice(&[0; 2 + 4 + 4 * 4 + 1 + 4 + 4 + 4]);
+                put_data32(output, &config.value)?;
+                return put_data32(output, &[]);
+            }
+        };
+
+        let epoch = KerberosTime(chrono::DateTime::UNIX_EPOCH.into());
+        write_principal(output, &credential.client)?;
+        write_principal(output, &credential.server)?;
+        put_i16(output, credential.key.r#type)?;
+        put_data32(output, &credential.key.value)?;
+        put_time(output, &credential.auth_time)?;
+        put_time(output, credential.start_time.as_ref().unwrap_or(&epoch))?;
+        put_time(output, &credential.end_time)?;
+        put_time(output, credential.renew_till.as_ref().unwrap_or(&epoch))?;
+        output.push(credential.is_skey.into());
+
+        let mut flags = 0u32;
+        for (i, bit) in credential.flags.0.iter().take(32).enumerate() {
+            if *bit {
+                flags |= 1 << (31 - i);
+            }
+        }
+        output.extend_from_slice(&flags.to_be_bytes());
+
+        put_u32(output, credential.addresses.len())?;
+        for address in &credential.addresses {
+            put_i16(output, address.addr_type)?;
+            put_data32(output, &address.address)?;
+        }
+        put_u32(output, credential.authorization_data.len())?;
+        for value in &credential.authorization_data {
+            put_i16(output, value.r#type)?;
+            put_data32(output, &value.data)?;
+        }
+
+        put_data32(output, &rasn::der::encode(&credential.ticket)?)?;
+        match &credential.second_ticket {
+            Some(ticket) => put_data32(output, &rasn::der::encode(ticket)?),
+            None => put_data32(output, &[]),
+        }
+    }
+}
+
+impl ConfigEntry {
+    /// The realm of configuration entries' server principals.
+    pub const REALM: &'static str = "X-CACHECONF:";
+
+    /// The first component of configuration entries' server principals.
+    pub const COMPONENT: &'static str = "krb5_ccache_conf_data";
+
+    /// Creates a configuration entry for `client` setting `key` to `value`,
+    /// optionally scoped to `principal`.
+    pub fn new(
+        client: Principal,
+        key: &str,
+        principal: Option<&Principal>,
+        value: Vec<u8>,
+    ) -> Result<Self, FileError> {
+        Ok(Self {
+            client,
+            server: Self::server(key, principal)?,
+            value,
+        })
+    }
+
+    /// Returns the configuration key.
+    pub fn key(&self) -> Option<&[u8]> {
+        self.server.name.string.get(1).map(|key| key.as_slice())
+    }
+
+    /// Returns the principal the entry is scoped to, as the string form of the
+    /// principal.
+    pub fn principal(&self) -> Option<&[u8]> {
+        self.server
+            .name
+            .string
+            .get(2)
+            .map(|principal| principal.as_slice())
+    }
+
+    fn server(key: &str, principal: Option<&Principal>) -> Result<Principal, FileError> {
+        let mut components =
+            alloc::vec![string(Self::COMPONENT.as_bytes())?, string(key.as_bytes())?];
+        if let Some(principal) = principal {
+            components.push(string(alloc::format!("{principal}").as_bytes())?);
+        }
+
+        Ok(Principal::new(
+            string(Self::REALM.as_bytes())?,
+            PrincipalName {
+                r#type: 1,
+                string: components,
+            },
+        ))
+    }
+}
+
+fn read_principal(reader: &mut Reader<'_>) -> Result<Principal, FileError> {
+    let r#type = reader.u32()? as i32;
+    let count = reader.u32()?;
+    let realm = string(reader.data32()?)?;
+    let components = (0..count)
+        .map(|_| string(reader.data32()?))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Principal::new(
+        realm,
+        PrincipalName {
+            r#type,
+            string: components,
+        },
+    ))
+}
+
+fn write_principal(output: &mut Vec<u8>, principal: &Principal) -> Result<(), FileError> {
+    output.extend_from_slice(&principal.name.r#type.to_be_bytes());
+    put_u32(output, principal.name.string.len())?;
+    put_data32(output, &principal.realm)?;
+    for component in &principal.name.string {
+        put_data32(output, component)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,557 @@
+//! # Encryption and Checksum Profiles
+//!
+//! The encryption types of [RFC 3962] (`aes128-cts-hmac-sha1-96` and
+//! `aes256-cts-hmac-sha1-96`) and [RFC 8009] (`aes128-cts-hmac-sha256-128`
+//! and `aes256-cts-hmac-sha384-192`), built on the simplified profile of
+//! [RFC 3961]. With the `rc4-hmac` feature, the legacy `rc4-hmac` encryption
+//! type from [RFC 4757] is also available.
+//!
+//! Each [`Enctype`] provides string-to-key, key derivation, encryption and
+//! checksums. [`EncryptedData`], [`EncryptionKey`] and [`Checksum`] have
+//! helpers that pick the encryption type from the key, and encode or decode
+//! the plaintext with DER.
+//!
+//! Encryption requires a random confounder of
+//! [`Enctype::confounder_length`] bytes, which the caller must generate with a
+//! cryptographically secure random number generator.
+//!
+//! ```
+//! use rasn_kerberos::{crypto::key_usage, EncryptedData, EncryptionKey, KerberosTime, PaEncTsEnc};
+//!
+//! let key = EncryptionKey::from_password(18, b"password", b"EXAMPLE.COMuser", None).unwrap();
+//! let timestamp = PaEncTsEnc {
+//!     patimestamp: KerberosTime(chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().into()),
+//!     pausec: None,
+//! };
+//!
+//! let encrypted = EncryptedData::encrypt(
+//!     &timestamp,
+//!     &key,
+//!     key_usage::AS_REQ_PA_ENC_TIMESTAMP,
+//!     None,
+//!     &[0x42; 16],
+//! )
+//! .unwrap();
+//! let decrypted: PaEncTsEnc = encrypted
+//!     .decrypt(&key, key_usage::AS_REQ_PA_ENC_TIMESTAMP)
+//!     .unwrap();
+//! assert_eq!(timestamp, decrypted);
+//! ```
+//!
+//! [RFC 3961]: https://datatracker.ietf.org/doc/html/rfc3961
+//! [RFC 3962]: https://datatracker.ietf.org/doc/html/rfc3962
+//! [RFC 4757]: https://datatracker.ietf.org/doc/html/rfc4757
+//! [RFC 8009]: https://datatracker.ietf.org/doc/html/rfc8009
+
+mod aes_sha1;
+mod aes_sha2;
+mod cts;
+#[cfg(feature = "rc4-hmac")]
+mod rc4;
+
+use alloc::vec::Vec;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+
+use crate::{Checksum, EncryptedData, EncryptionKey, EtypeInfo2Entry, PrincipalName, Realm};
+
+/// Key usage numbers from [RFC 4120 Section 7.5.1] and [RFC 4121 Section 2],
+/// which separate the keys derived for each use of a base key.
+///
+/// [RFC 4120 Section 7.5.1]: https://datatracker.ietf.org/doc/html/rfc4120#section-7.5.1
+/// [RFC 4121 Section 2]: https://datatracker.ietf.org/doc/html/rfc4121#section-2
+pub mod key_usage {
+    /// AS-REQ PA-ENC-TIMESTAMP padata timestamp, encrypted with the client
+    /// key.
+    pub const AS_REQ_PA_ENC_TIMESTAMP: i32 = 1;
+    /// AS-REP or TGS-REP ticket, encrypted with the service key.
+    pub const TICKET: i32 = 2;
+    /// AS-REP encrypted part, encrypted with the client key.
+    pub const AS_REP_ENC_PART: i32 = 3;
+    /// TGS-REQ authorization data, encrypted with the TGS session key.
+    pub const TGS_REQ_AUTH_DATA_SESSION_KEY: i32 = 4;
+    /// TGS-REQ authorization data, encrypted with the TGS authenticator
+    /// subkey.
+    pub const TGS_REQ_AUTH_DATA_SUBKEY: i32 = 5;
+    /// TGS-REQ PA-TGS-REQ authenticator checksum, keyed with the TGS session
+    /// key.
+    pub const TGS_REQ_AUTHENTICATOR_CHECKSUM: i32 = 6;
+    /// TGS-REQ PA-TGS-REQ authenticator, encrypted with the TGS session key.
+    pub const TGS_REQ_AUTHENTICATOR: i32 = 7;
+    /// TGS-REP encrypted part, encrypted with the TGS session key.
+    pub const TGS_REP_ENC_PART_SESSION_KEY: i32 = 8;
+    /// TGS-REP encrypted part, encrypted with the TGS authenticator subkey.
+    pub const TGS_REP_ENC_PART_SUBKEY: i32 = 9;
+    /// AP-REQ authenticator checksum, keyed with the application session
+    /// key.
+    pub const AP_REQ_AUTHENTICATOR_CHECKSUM: i32 = 10;
+    /// AP-REQ authenticator, encrypted with the application session key.
+    pub const AP_REQ_AUTHENTICATOR: i32 = 11;
+    /// AP-REP encrypted part, encrypted with the application session key.
+    pub const AP_REP_ENC_PART: i32 = 12;
+    /// KRB-PRIV encrypted part, encrypted with a key chosen by the
+    /// application.
+    pub const KRB_PRIV_ENC_PART: i32 = 13;
+    /// KRB-CRED encrypted part, encrypted with a key chosen by the
+    /// application.
+    pub const KRB_CRED_ENC_PART: i32 = 14;
+    /// KRB-SAFE checksum, keyed with a key chosen by the application.
+    pub const KRB_SAFE_CHECKSUM: i32 = 15;
+    /// PAC server and KDC signatures, from [MS-PAC].
+    ///
+    /// [MS-PAC]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-pac/
+    pub const PAC_CHECKSUM: i32 = 17;
+    /// AD-KDC-ISSUED checksum.
+    pub const AD_KDC_ISSUED_CHECKSUM: i32 = 19;
+    /// GSS-API acceptor sealing (wrap) tokens.
+    pub const ACCEPTOR_SEAL: i32 = 22;
+    /// GSS-API acceptor signing (MIC) tokens.
+    pub const ACCEPTOR_SIGN: i32 = 23;
+    /// GSS-API initiator sealing (wrap) tokens.
+    pub const INITIATOR_SEAL: i32 = 24;
+    /// GSS-API initiator signing (MIC) tokens.
+    pub const INITIATOR_SIGN: i32 = 25;
+}
+
+/// An error returned by cryptographic operations.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CryptoError {
+    /// The encryption type isn't supported.
+    UnsupportedEtype(i32),
+    /// The checksum type isn't supported.
+    UnsupportedChecksumType(i32),
+    /// The data was encrypted with a different encryption type than the
+    /// key's.
+    EtypeMismatch { key: i32, data: i32 },
+    /// The key isn't the right length for the encryption type.
+    InvalidKeyLength,
+    /// The confounder isn't the right length for the encryption type.
+    InvalidConfounderLength,
+    /// The string-to-key parameters are invalid.
+    InvalidParameters,
+    /// The password can't be used with the encryption type.
+    InvalidPassword,
+    /// The encryption type doesn't derive keys from key usages.
+    NoKeyDerivation,
+    /// The ciphertext is too short to have been produced by the encryption
+    /// type.
+    InvalidCiphertext,
+    /// The ciphertext or checksum failed its integrity check.
+    IntegrityCheckFailed,
+    /// The plaintext couldn't be encoded.
+    Encode(EncodeError),
+    /// The decrypted plaintext couldn't be decoded.
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedEtype(etype) => write!(f, "unsupported encryption type {etype}"),
+            Self::UnsupportedChecksumType(r#type) => {
+                write!(f, "unsupported checksum type {type}")
+            }
+            Self::EtypeMismatch { key, data } => write!(
+                f,
+                "data encrypted with encryption type {data}, but key has type {key}"
+            ),
+            Self::InvalidKeyLength => write!(f, "invalid key length"),
+            Self::InvalidConfounderLength => write!(f, "invalid confounder length"),
+            Self::InvalidParameters => write!(f, "invalid string-to-key parameters"),
+            Self::InvalidPassword => write!(f, "invalid password"),
+            Self::NoKeyDerivation => write!(f, "encryption type doesn't derive keys"),
+            Self::InvalidCiphertext => write!(f, "ciphertext too short"),
+            Self::IntegrityCheckFailed => write!(f, "integrity check failed"),
+            Self::Encode(error) => write!(f, "failed to encode plaintext: {error}"),
+            Self::Decode(error) => write!(f, "failed to decode plaintext: {error}"),
+        }
+    }
+}
+
+impl From<EncodeError> for CryptoError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+impl From<DecodeError> for CryptoError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+/// The keys derived from a base key for each key usage, as described in
+/// [RFC 3961 Section 5.3].
+///
+/// [RFC 3961 Section 5.3]: https://datatracker.ietf.org/doc/html/rfc3961#section-5.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DerivedKey {
+    /// The checksum key, `Kc`.
+    Checksum = 0x99,
+    /// The encryption key, `Ke`.
+    Encryption = 0xAA,
+    /// The integrity key, `Ki`.
+    Integrity = 0x55,
+}
+
+impl DerivedKey {
+    /// Returns the well-known constant `usage | 0xXX` used to derive the key.
+    fn constant(self, usage: i32) -> [u8; 5] {
+        let [a, b, c, d] = usage.to_be_bytes();
+        [a, b, c, d, self as u8]
+    }
+}
+
+/// A supported encryption type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Enctype {
+    /// `aes128-cts-hmac-sha1-96` from RFC 3962.
+    Aes128CtsHmacSha196,
+    /// `aes256-cts-hmac-sha1-96` from RFC 3962.
+    Aes256CtsHmacSha196,
+    /// `aes128-cts-hmac-sha256-128` from RFC 8009.
+    Aes128CtsHmacSha256128,
+    /// `aes256-cts-hmac-sha384-192` from RFC 8009.
+    Aes256CtsHmacSha384192,
+    /// `rc4-hmac` from RFC 4757.
+    #[cfg(feature = "rc4-hmac")]
+    Rc4Hmac,
+}
+
+impl Enctype {
+    pub const AES128_CTS_HMAC_SHA1_96: i32 = 17;
+    pub const AES256_CTS_HMAC_SHA1_96: i32 = 18;
+    pub const AES128_CTS_HMAC_SHA256_128: i32 = 19;
+    pub const AES256_CTS_HMAC_SHA384_192: i32 = 20;
+    pub const RC4_HMAC: i32 = 23;
+
+    /// Returns the encryption type with the number `etype`.
+    pub fn from_etype(etype: i32) -> Result<Self, CryptoError> {
+        match etype {
+            Self::AES128_CTS_HMAC_SHA1_96 => Ok(Self::Aes128CtsHmacSha196),
+            Self::AES256_CTS_HMAC_SHA1_96 => Ok(Self::Aes256CtsHmacSha196),
+            Self::AES128_CTS_HMAC_SHA256_128 => Ok(Self::Aes128CtsHmacSha256128),
+            Self::AES256_CTS_HMAC_SHA384_192 => Ok(Self::Aes256CtsHmacSha384192),
+            #[cfg(feature = "rc4-hmac")]
+            Self::RC4_HMAC => Ok(Self::Rc4Hmac),
+            _ => Err(CryptoError::UnsupportedEtype(etype)),
+        }
+    }
+
+    /// Returns the encryption type's number.
+    pub const fn etype(self) -> i32 {
+        match self {
+            Self::Aes128CtsHmacSha196 => Self::AES128_CTS_HMAC_SHA1_96,
+            Self::Aes256CtsHmacSha196 => Self::AES256_CTS_HMAC_SHA1_96,
+            Self::Aes128CtsHmacSha256128 => Self::AES128_CTS_HMAC_SHA256_128,
+            Self::Aes256CtsHmacSha384192 => Self::AES256_CTS_HMAC_SHA384_192,
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => Self::RC4_HMAC,
+        }
+    }
+
+    /// Returns the number of the encryption type's mandatory checksum type.
+    pub const fn checksum_type(self) -> i32 {
+        match self {
+            Self::Aes128CtsHmacSha196 => 15,
+            Self::Aes256CtsHmacSha196 => 16,
+            Self::Aes128CtsHmacSha256128 => 19,
+            Self::Aes256CtsHmacSha384192 => 20,
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => -138,
+        }
+    }
+
+    /// Returns the length of the encryption type's keys in bytes.
+    pub const fn key_length(self) -> usize {
+        match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes128CtsHmacSha256128 => 16,
+            Self::Aes256CtsHmacSha196 | Self::Aes256CtsHmacSha384192 => 32,
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => 16,
+        }
+    }
+
+    /// Returns the length of the random confounder prepended to the
+    /// plaintext in bytes.
+    pub const fn confounder_length(self) -> usize {
+        match self {
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => 8,
+            _ => 16,
+        }
+    }
+
+    /// Converts `password` into a key, using `salt` and the encryption
+    /// type's string-to-key parameters, `params`, or the default parameters
+    /// if they're absent.
+    pub fn string_to_key(
+        self,
+        password: &[u8],
+        salt: &[u8],
+        params: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes256CtsHmacSha196 => {
+                aes_sha1::string_to_key(self.key_length(), password, salt, params)
+            }
+            Self::Aes128CtsHmacSha256128 => {
+                aes_sha2::AES128_CTS_HMAC_SHA256_128.string_to_key(password, salt, params)
+            }
+            Self::Aes256CtsHmacSha384192 => {
+                aes_sha2::AES256_CTS_HMAC_SHA384_192.string_to_key(password, salt, params)
+            }
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => rc4::string_to_key(password, params),
+        }
+    }
+
+    /// Derives the `derived` key for `usage` from the base key `key`.
+    pub fn derive_key(
+        self,
+        key: &[u8],
+        usage: i32,
+        derived: DerivedKey,
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.check_key(key)?;
+        match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes256CtsHmacSha196 => {
+                aes_sha1::derive_key(key, &derived.constant(usage))
+            }
+            Self::Aes128CtsHmacSha256128 => {
+                aes_sha2::AES128_CTS_HMAC_SHA256_128.derive_key(key, usage, derived)
+            }
+            Self::Aes256CtsHmacSha384192 => {
+                aes_sha2::AES256_CTS_HMAC_SHA384_192.derive_key(key, usage, derived)
+            }
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => Err(CryptoError::NoKeyDerivation),
+        }
+    }
+
+    /// Encrypts `plaintext` with `key` for `usage`, prepending the random
+    /// `confounder`.
+    pub fn encrypt(
+        self,
+        key: &[u8],
+        usage: i32,
+        plaintext: &[u8],
+        confounder: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.check_key(key)?;
+        if confounder.len() != self.confounder_length() {
+            return Err(CryptoError::InvalidConfounderLength);
+        }
+
+        let mut data = Vec::with_capacity(confounder.len() + plaintext.len());
+        data.extend_from_slice(confounder);
+        data.extend_from_slice(plaintext);
+
+        match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes256CtsHmacSha196 => {
+                aes_sha1::encrypt(key, usage, &data)
+            }
+            Self::Aes128CtsHmacSha256128 => {
+                aes_sha2::AES128_CTS_HMAC_SHA256_128.encrypt(key, usage, &data)
+            }
+            Self::Aes256CtsHmacSha384192 => {
+                aes_sha2::AES256_CTS_HMAC_SHA384_192.encrypt(key, usage, &data)
+            }
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => Ok(rc4::encrypt(key, usage, data)),
+        }
+    }
+
+    /// Decrypts `ciphertext` with `key` for `usage`, checking its integrity
+    /// and removing the confounder.
+    pub fn decrypt(
+        self,
+        key: &[u8],
+        usage: i32,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.check_key(key)?;
+        let mut data = match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes256CtsHmacSha196 => {
+                aes_sha1::decrypt(key, usage, ciphertext)
+            }
+            Self::Aes128CtsHmacSha256128 => {
+                aes_sha2::AES128_CTS_HMAC_SHA256_128.decrypt(key, usage, ciphertext)
+            }
+            Self::Aes256CtsHmacSha384192 => {
+                aes_sha2::AES256_CTS_HMAC_SHA384_192.decrypt(key, usage, ciphertext)
+            }
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => rc4::decrypt(key, usage, ciphertext),
+        }?;
+
+        data.drain(..self.confounder_length());
+        Ok(data)
+    }
+
+    /// Computes the encryption type's mandatory checksum of `data` with `key`
+    /// for `usage`.
+    pub fn checksum(self, key: &[u8], usage: i32, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        self.check_key(key)?;
+        match self {
+            Self::Aes128CtsHmacSha196 | Self::Aes256CtsHmacSha196 => {
+                aes_sha1::checksum(key, usage, data)
+            }
+            Self::Aes128CtsHmacSha256128 => {
+                aes_sha2::AES128_CTS_HMAC_SHA256_128.checksum(key, usage, data)
+            }
+            Self::Aes256CtsHmacSha384192 => {
+                aes_sha2::AES256_CTS_HMAC_SHA384_192.checksum(key, usage, data)
+            }
+            #[cfg(feature = "rc4-hmac")]
+            Self::Rc4Hmac => Ok(rc4::checksum(key, usage, data)),
+        }
+    }
+
+    fn check_key(self, key: &[u8]) -> Result<(), CryptoError> {
+        if key.len() == self.key_length() {
+            Ok(())
+        } else {
+            Err(CryptoError::InvalidKeyLength)
+        }
+    }
+}
+
+/// Compares two MACs in constant time.
+pub(crate) fn verify_mac(expected: &[u8], actual: &[u8]) -> Result<(), CryptoError> {
+    let difference = expected
+        .iter()
+        .zip(actual)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+
+    if expected.len() == actual.len() && difference == 0 {
+        Ok(())
+    } else {
+        Err(CryptoError::IntegrityCheckFailed)
+    }
+}
+
+impl EncryptionKey {
+    /// Converts `password` into a key of encryption type `etype`.
+    pub fn from_password(
+        etype: i32,
+        password: &[u8],
+        salt: &[u8],
+        params: Option<&[u8]>,
+    ) -> Result<Self, CryptoError> {
+        let value = Enctype::from_etype(etype)?.string_to_key(password, salt, params)?;
+
+        Ok(Self {
+            r#type: etype,
+            value: value.into(),
+        })
+    }
+
+    /// Returns the key's encryption type.
+    pub fn enctype(&self) -> Result<Enctype, CryptoError> {
+        Enctype::from_etype(self.r#type)
+    }
+}
+
+impl EtypeInfo2Entry {
+    /// Converts `password` into a key with the entry's encryption type, salt
+    /// and parameters. `default_salt` is used when the entry has no salt,
+    /// usually [`PrincipalName::salt`].
+    pub fn string_to_key(
+        &self,
+        password: &[u8],
+        default_salt: &[u8],
+    ) -> Result<EncryptionKey, CryptoError> {
+        let salt = self.salt.as_ref().map_or(default_salt, |salt| &salt[..]);
+        EncryptionKey::from_password(self.etype, password, salt, self.s2kparams.as_deref())
+    }
+}
+
+impl PrincipalName {
+    /// Returns the default salt for the principal in `realm`, the realm
+    /// followed by each component of the name.
+    pub fn salt(&self, realm: &Realm) -> Vec<u8> {
+        let mut salt = realm.to_vec();
+        for component in &self.string {
+            salt.extend_from_slice(component);
+        }
+        salt
+    }
+}
+
+impl EncryptedData {
+    /// Encrypts the DER encoding of `value` with `key` for `usage`.
+    pub fn encrypt<T: Encode>(
+        value: &T,
+        key: &EncryptionKey,
+        usage: i32,
+        kvno: Option<u32>,
+        confounder: &[u8],
+    ) -> Result<Self, CryptoError> {
+        Self::encrypt_bytes(&rasn::der::encode(value)?, key, usage, kvno, confounder)
+    }
+
+    /// Encrypts `plaintext` with `key` for `usage`.
+    pub fn encrypt_bytes(
+        plaintext: &[u8],
+        key: &EncryptionKey,
+        usage: i32,
+        kvno: Option<u32>,
+        confounder: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let cipher = key
+            .enctype()?
+            .encrypt(&key.value, usage, plaintext, confounder)?;
+
+        Ok(Self {
+            etype: key.r#type,
+            kvno,
+            cipher: cipher.into(),
+        })
+    }
+
+    /// Decrypts the data with `key` for `usage`, and decodes it from DER.
+    pub fn decrypt<T: Decode>(&self, key: &EncryptionKey, usage: i32) -> Result<T, CryptoError> {
+        Ok(rasn::der::decode(&self.decrypt_bytes(key, usage)?)?)
+    }
+
+    /// Decrypts the data with `key` for `usage`.
+    pub fn decrypt_bytes(&self, key: &EncryptionKey, usage: i32) -> Result<Vec<u8>, CryptoError> {
+        if self.etype != key.r#type {
+            return Err(CryptoError::EtypeMismatch {
+                key: key.r#type,
+                data: self.etype,
+            });
+        }
+
+        key.enctype()?.decrypt(&key.value, usage, &self.cipher)
+    }
+}
+
+impl Checksum {
+    /// Computes the mandatory checksum for `key`'s encryption type over
+    /// `data` for `usage`.
+    pub fn new(key: &EncryptionKey, usage: i32, data: &[u8]) -> Result<Self, CryptoError> {
+        let enctype = key.enctype()?;
+
+        Ok(Self {
+            r#type: enctype.checksum_type(),
+            checksum: enctype.checksum(&key.value, usage, data)?.into(),
+        })
+    }
+
+    /// Checks the checksum over `data` for `usage` with `key`.
+    pub fn verify(&self, key: &EncryptionKey, usage: i32, data: &[u8]) -> Result<(), CryptoError> {
+        let enctype = key.enctype()?;
+        if self.r#type != enctype.checksum_type() {
+            return Err(CryptoError::UnsupportedChecksumType(self.r#type));
+        }
+
+        verify_mac(&enctype.checksum(&key.value, usage, data)?, &self.checksum)
+    }
+}
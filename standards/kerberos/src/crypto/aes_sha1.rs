@@ -0,0 +1,195 @@
+//! `aes128-cts-hmac-sha1-96` and `aes256-cts-hmac-sha1-96` from [RFC 3962],
+//! using the simplified profile of [RFC 3961 Section 5.3].
+//!
+//! [RFC 3962]: https://datatracker.ietf.org/doc/html/rfc3962
+//! [RFC 3961 Section 5.3]: https://datatracker.ietf.org/doc/html/rfc3961#section-5.3
+
+use alloc::{vec, vec::Vec};
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use super::{
+    cts::{Aes, BLOCK_SIZE},
+    verify_mac, CryptoError, DerivedKey,
+};
+
+/// The length of the truncated HMAC-SHA1 in bytes.
+const MAC_LENGTH: usize = 12;
+
+/// The iteration count used when no string-to-key parameters are given.
+const DEFAULT_ITERATIONS: u32 = 4096;
+
+pub(super) fn string_to_key(
+    key_length: usize,
+    password: &[u8],
+    salt: &[u8],
+    params: Option<&[u8]>,
+) -> Result<Vec<u8>, CryptoError> {
+    let iterations = match params {
+        Some(params) => iteration_count(params)?,
+        None => DEFAULT_ITERATIONS,
+    };
+
+    let mut key = vec![0; key_length];
+    pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, iterations, &mut key);
+    derive_key(&key, b"kerberos")
+}
+
+/// Parses the iteration count from string-to-key parameters, rejecting the
+/// value zero, which RFC 3962 allows implementations to reject.
+pub(super) fn iteration_count(params: &[u8]) -> Result<u32, CryptoError> {
+    let iterations = u32::from_be_bytes(
+        params
+            .try_into()
+            .map_err(|_| CryptoError::InvalidParameters)?,
+    );
+
+    match iterations {
+        0 => Err(CryptoError::InvalidParameters),
+        iterations => Ok(iterations),
+    }
+}
+
+/// The key derivation function `DK(key, constant)`, from RFC 3961
+/// Section 5.1.
+pub(super) fn derive_key(key: &[u8], constant: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = Aes::new(key)?;
+    let mut block = [0; BLOCK_SIZE];
+    block.copy_from_slice(&n_fold(constant, BLOCK_SIZE));
+
+    let mut derived = Vec::with_capacity(key.len() + BLOCK_SIZE);
+    while derived.len() < key.len() {
+        cipher.encrypt_block(&mut block);
+        derived.extend_from_slice(&block);
+    }
+
+    derived.truncate(key.len());
+    Ok(derived)
+}
+
+pub(super) fn encrypt(key: &[u8], usage: i32, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let ke = derive_key(key, &DerivedKey::Encryption.constant(usage))?;
+    let ki = derive_key(key, &DerivedKey::Integrity.constant(usage))?;
+
+    let mut ciphertext = Aes::new(&ke)?.encrypt_cts(data);
+    ciphertext.extend_from_slice(&hmac(&ki, data));
+    Ok(ciphertext)
+}
+
+pub(super) fn decrypt(key: &[u8], usage: i32, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let Some(length) = ciphertext.len().checked_sub(MAC_LENGTH) else {
+        return Err(CryptoError::InvalidCiphertext);
+    };
+    if length < BLOCK_SIZE {
+        return Err(CryptoError::InvalidCiphertext);
+    }
+
+    let ke = derive_key(key, &DerivedKey::Encryption.constant(usage))?;
+    let ki = derive_key(key, &DerivedKey::Integrity.constant(usage))?;
+
+    let (ciphertext, mac) = ciphertext.split_at(length);
+    let data = Aes::new(&ke)?.decrypt_cts(ciphertext)?;
+    verify_mac(&hmac(&ki, &data), mac)?;
+    Ok(data)
+}
+
+pub(super) fn checksum(key: &[u8], usage: i32, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let kc = derive_key(key, &DerivedKey::Checksum.constant(usage))?;
+    Ok(hmac(&kc, data))
+}
+
+/// HMAC-SHA1 truncated to 96 bits.
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <Hmac<Sha1>>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes()[..MAC_LENGTH].to_vec()
+}
+
+/// The `n-fold` operation from RFC 3961 Section 5.1, stretching or shrinking
+/// `input` to `length` bytes.
+pub(super) fn n_fold(input: &[u8], length: usize) -> Vec<u8> {
+    let input_length = input.len();
+    let input_bits = input_length * 8;
+    let lcm = input_length / gcd(input_length, length) * length;
+
+    // Adds each copy of the input, rotated right by 13 bits more than the
+    // last, into the output with ones' complement addition, working from the
+    // least significant byte.
+    let mut output = vec![0u8; length];
+    let mut carry = 0u32;
+    for i in (0..lcm).rev() {
+        let msbit = (input_bits - 1
+            + (input_bits + 13) * (i / input_length)
+            + (input_length - i % input_length) * 8)
+            % input_bits;
+        let high = u32::from(input[(input_length - 1 - msbit / 8) % input_length]);
+        let low = u32::from(input[(input_length - msbit / 8) % input_length]);
+
+        carry += ((high << 8 | low) >> (msbit % 8 + 1)) & 0xff;
+        carry += u32::from(output[i % length]);
+        output[i % length] = carry as u8;
+        carry >>= 8;
+    }
+
+    // Wraps the remaining carry around.
+    if carry != 0 {
+        for byte in output.iter_mut().rev() {
+            carry += u32::from(*byte);
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+    }
+
+    output
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3961_n_fold() {
+        // RFC 3961 Appendix A.1.
+        let vectors: [(&[u8], usize, &str); 10] = [
+            (b"012345", 64, "be072631276b1955"),
+            (b"password", 56, "78a07b6caf85fa"),
+            (b"Rough Consensus, and Running Code", 64, "bb6ed30870b7f0e0"),
+            (
+                b"password",
+                168,
+                "59e4a8ca7c0385c3c37b3f6d2000247cb6e6bd5b3e",
+            ),
+            (
+                b"MASSACHVSETTS INSTITVTE OF TECHNOLOGY",
+                192,
+                "db3b0d8f0b061e603282b308a50841229ad798fab9540c1b",
+            ),
+            (b"Q", 168, "518a54a215a8452a518a54a215a8452a518a54a215"),
+            (b"ba", 168, "fb25d531ae8974499f52fd92ea9857c4ba24cf297e"),
+            (b"kerberos", 64, "6b65726265726f73"),
+            (b"kerberos", 128, "6b65726265726f737b9b5b2b93132b93"),
+            (
+                b"kerberos",
+                256,
+                "6b65726265726f737b9b5b2b93132b935c9bdcdad95c9899c4cae4dee6d6cae4",
+            ),
+        ];
+
+        for (input, bits, expected) in vectors {
+            let expected: Vec<u8> = (0..expected.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&expected[i..i + 2], 16).unwrap())
+                .collect();
+            assert_eq!(expected, n_fold(input, bits / 8), "{bits}-fold");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! `aes128-cts-hmac-sha256-128` and `aes256-cts-hmac-sha384-192` from
+//! [RFC 8009].
+//!
+//! [RFC 8009]: https://datatracker.ietf.org/doc/html/rfc8009
+
+use alloc::{vec, vec::Vec};
+
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha384};
+
+use super::{
+    aes_sha1::iteration_count,
+    cts::{Aes, BLOCK_SIZE},
+    verify_mac, CryptoError, DerivedKey,
+};
+
+/// The iteration count used when no string-to-key parameters are given.
+const DEFAULT_ITERATIONS: u32 = 32768;
+
+/// The parameters of one of the RFC 8009 encryption types.
+pub(super) struct Profile {
+    /// The encryption type name, which prefixes the salt.
+    name: &'static [u8],
+    /// The length of the protocol key and `Ke` in bytes.
+    key_length: usize,
+    /// The length of `Ki`, `Kc`, and the truncated HMAC in bytes.
+    mac_length: usize,
+}
+
+pub(super) const AES128_CTS_HMAC_SHA256_128: Profile = Profile {
+    name: b"aes128-cts-hmac-sha256-128",
+    key_length: 16,
+    mac_length: 16,
+};
+
+pub(super) const AES256_CTS_HMAC_SHA384_192: Profile = Profile {
+    name: b"aes256-cts-hmac-sha384-192",
+    key_length: 32,
+    mac_length: 24,
+};
+
+impl Profile {
+    pub(super) fn string_to_key(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        params: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoError> {
+        let iterations = match params {
+            Some(params) => iteration_count(params)?,
+            None => DEFAULT_ITERATIONS,
+        };
+
+        let mut prefixed_salt = Vec::with_capacity(self.name.len() + 1 + salt.len());
+        prefixed_salt.extend_from_slice(self.name);
+        prefixed_salt.push(0);
+        prefixed_salt.extend_from_slice(salt);
+
+        let mut key = vec![0; self.key_length];
+        if self.is_sha384() {
+            pbkdf2::pbkdf2_hmac::<Sha384>(password, &prefixed_salt, iterations, &mut key);
+        } else {
+            pbkdf2::pbkdf2_hmac::<Sha256>(password, &prefixed_salt, iterations, &mut key);
+        }
+
+        Ok(self.kdf(&key, b"kerberos", self.key_length))
+    }
+
+    pub(super) fn derive_key(
+        &self,
+        key: &[u8],
+        usage: i32,
+        derived: DerivedKey,
+    ) -> Result<Vec<u8>, CryptoError> {
+        let length = match derived {
+            DerivedKey::Encryption => self.key_length,
+            DerivedKey::Checksum | DerivedKey::Integrity => self.mac_length,
+        };
+
+        Ok(self.kdf(key, &derived.constant(usage), length))
+    }
+
+    pub(super) fn encrypt(
+        &self,
+        key: &[u8],
+        usage: i32,
+        data: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let ke = self.derive_key(key, usage, DerivedKey::Encryption)?;
+        let ki = self.derive_key(key, usage, DerivedKey::Integrity)?;
+
+        let mut ciphertext = Aes::new(&ke)?.encrypt_cts(data);
+        let mac = self.hmac(&ki, &[&[0; BLOCK_SIZE], &ciphertext]);
+        ciphertext.extend_from_slice(&mac[..self.mac_length]);
+        Ok(ciphertext)
+    }
+
+    pub(super) fn decrypt(
+        &self,
+        key: &[u8],
+        usage: i32,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let Some(length) = ciphertext.len().checked_sub(self.mac_length) else {
+            return Err(CryptoError::InvalidCiphertext);
+        };
+        if length < BLOCK_SIZE {
+            return Err(CryptoError::InvalidCiphertext);
+        }
+
+        let ke = self.derive_key(key, usage, DerivedKey::Encryption)?;
+        let ki = self.derive_key(key, usage, DerivedKey::Integrity)?;
+
+        // The MAC covers the initial vector and ciphertext, so it's checked
+        // before decrypting.
+        let (ciphertext, mac) = ciphertext.split_at(length);
+        let expected = self.hmac(&ki, &[&[0; BLOCK_SIZE], ciphertext]);
+        verify_mac(&expected[..self.mac_length], mac)?;
+        Aes::new(&ke)?.decrypt_cts(ciphertext)
+    }
+
+    pub(super) fn checksum(
+        &self,
+        key: &[u8],
+        usage: i32,
+        data: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let kc = self.derive_key(key, usage, DerivedKey::Checksum)?;
+        let mut checksum = self.hmac(&kc, &[data]);
+        checksum.truncate(self.mac_length);
+        Ok(checksum)
+    }
+
+    /// `KDF-HMAC-SHA2(key, label, k)` from RFC 8009 Section 3, with an empty
+    /// context, producing `length` bytes.
+    fn kdf(&self, key: &[u8], label: &[u8], length: usize) -> Vec<u8> {
+        let bits = (length as u32 * 8).to_be_bytes();
+        let mut output = self.hmac(key, &[&1u32.to_be_bytes(), label, &[0], &bits]);
+        output.truncate(length);
+        output
+    }
+
+    /// HMAC-SHA-256 or HMAC-SHA-384 over `parts`.
+    fn hmac(&self, key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+        if self.is_sha384() {
+            let mut mac = <Hmac<Sha384>>::new_from_slice(key).expect("HMAC accepts any key length");
+            parts.iter().for_each(|part| mac.update(part));
+            mac.finalize().into_bytes().to_vec()
+        } else {
+            let mut mac = <Hmac<Sha256>>::new_from_slice(key).expect("HMAC accepts any key length");
+            parts.iter().for_each(|part| mac.update(part));
+            mac.finalize().into_bytes().to_vec()
+        }
+    }
+
+    fn is_sha384(&self) -> bool {
+        self.key_length == 32
+    }
+}
@@ -0,0 +1,182 @@
+//! AES in CBC mode with ciphertext stealing, as described in
+//! [RFC 3962 Section 5].
+//!
+//! [RFC 3962 Section 5]: https://datatracker.ietf.org/doc/html/rfc3962#section-5
+
+use alloc::vec::Vec;
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+
+use super::CryptoError;
+
+pub(super) const BLOCK_SIZE: usize = 16;
+
+type Block = [u8; BLOCK_SIZE];
+
+/// An AES-128 or AES-256 block cipher. It only lives for a single
+/// encryption or decryption, so the size difference doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub(super) enum Aes {
+    Aes128(aes::Aes128),
+    Aes256(aes::Aes256),
+}
+
+impl Aes {
+    pub(super) fn new(key: &[u8]) -> Result<Self, CryptoError> {
+        match key.len() {
+            16 => Ok(Self::Aes128(aes::Aes128::new(GenericArray::from_slice(
+                key,
+            )))),
+            32 => Ok(Self::Aes256(aes::Aes256::new(GenericArray::from_slice(
+                key,
+            )))),
+            _ => Err(CryptoError::InvalidKeyLength),
+        }
+    }
+
+    pub(super) fn encrypt_block(&self, block: &mut Block) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Self::Aes128(cipher) => cipher.encrypt_block(block),
+            Self::Aes256(cipher) => cipher.encrypt_block(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut Block) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Self::Aes128(cipher) => cipher.decrypt_block(block),
+            Self::Aes256(cipher) => cipher.decrypt_block(block),
+        }
+    }
+
+    /// Encrypts `data`, which must be at least one block long, with a zero
+    /// initial vector. The last two blocks are always swapped, and the
+    /// final block truncated to the length of the last block of `data`.
+    pub(super) fn encrypt_cts(&self, data: &[u8]) -> Vec<u8> {
+        let mut blocks: Vec<Block> = data.chunks(BLOCK_SIZE).map(to_block).collect();
+        let mut previous = [0; BLOCK_SIZE];
+        for block in &mut blocks {
+            xor(block, &previous);
+            self.encrypt_block(block);
+            previous = *block;
+        }
+
+        if let [.., second_last, last] = &mut blocks[..] {
+            core::mem::swap(second_last, last);
+        }
+
+        let mut ciphertext = blocks.concat();
+        ciphertext.truncate(data.len());
+        ciphertext
+    }
+
+    /// Decrypts `data` encrypted by [`Aes::encrypt_cts`].
+    pub(super) fn decrypt_cts(&self, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if data.len() < BLOCK_SIZE {
+            return Err(CryptoError::InvalidCiphertext);
+        }
+
+        let count = data.len().div_ceil(BLOCK_SIZE);
+        let mut plaintext = Vec::with_capacity(data.len());
+        if count == 1 {
+            let mut block = to_block(data);
+            self.decrypt_block(&mut block);
+            plaintext.extend_from_slice(&block);
+            return Ok(plaintext);
+        }
+
+        let (full, stolen) = data.split_at(BLOCK_SIZE * (count - 1));
+        let (chained, last_full) = full.split_at(BLOCK_SIZE * (count - 2));
+
+        let mut previous = [0; BLOCK_SIZE];
+        for chunk in chained.chunks(BLOCK_SIZE) {
+            let ciphertext = to_block(chunk);
+            let mut block = ciphertext;
+            self.decrypt_block(&mut block);
+            xor(&mut block, &previous);
+            plaintext.extend_from_slice(&block);
+            previous = ciphertext;
+        }
+
+        // The last full ciphertext block is the encryption of the padded final
+        // plaintext block, chained with the stolen block.
+        let mut last = to_block(last_full);
+        self.decrypt_block(&mut last);
+        let mut second_last = last;
+        second_last[..stolen.len()].copy_from_slice(stolen);
+        for (byte, stolen) in last.iter_mut().zip(stolen) {
+            *byte ^= stolen;
+        }
+
+        self.decrypt_block(&mut second_last);
+        xor(&mut second_last, &previous);
+        plaintext.extend_from_slice(&second_last);
+        plaintext.extend_from_slice(&last[..stolen.len()]);
+        Ok(plaintext)
+    }
+}
+
+/// Copies `chunk` into a block, padding it with zeros.
+fn to_block(chunk: &[u8]) -> Block {
+    let mut block = [0; BLOCK_SIZE];
+    block[..chunk.len()].copy_from_slice(chunk);
+    block
+}
+
+fn xor(block: &mut Block, other: &Block) {
+    for (byte, other) in block.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3962_vectors() {
+        // RFC 3962 Appendix B, with the key "chicken teriyaki".
+        let cipher = Aes::new(b"chicken teriyaki").unwrap();
+        let plaintext = b"I would like the General Gau's Chicken, please, and wonton soup.";
+        let vectors: [(usize, &str); 6] = [
+            (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (
+                31,
+                "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5",
+            ),
+            (
+                32,
+                "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584",
+            ),
+            (
+                47,
+                "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e\
+                 39312523a78662d5be7fcbcc98ebf5",
+            ),
+            (
+                48,
+                "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd8\
+                 39312523a78662d5be7fcbcc98ebf5a8",
+            ),
+            (
+                64,
+                "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a8\
+                 4807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8",
+            ),
+        ];
+
+        for (length, expected) in vectors {
+            let expected: Vec<u8> = (0..expected.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&expected[i..i + 2], 16).unwrap())
+                .collect();
+            let ciphertext = cipher.encrypt_cts(&plaintext[..length]);
+            assert_eq!(expected, ciphertext, "length {length}");
+            assert_eq!(
+                &plaintext[..length],
+                &cipher.decrypt_cts(&ciphertext).unwrap()[..]
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! `rc4-hmac` from [RFC 4757].
+//!
+//! This encryption type is deprecated by [RFC 8429], and is only provided for
+//! interoperability with older Active Directory deployments. MD4 and MD5 come
+//! from the `md4` and `md-5` crates, which are only pulled in by the
+//! `rc4-hmac` feature. The RC4 stream cipher is implemented here, and mustn't
+//! be used for anything else.
+//!
+//! [RFC 4757]: https://datatracker.ietf.org/doc/html/rfc4757
+//! [RFC 8429]: https://datatracker.ietf.org/doc/html/rfc8429
+
+use alloc::vec::Vec;
+
+use hmac::{Hmac, Mac};
+use md4::{Digest, Md4};
+use md5::Md5;
+
+use super::{verify_mac, CryptoError};
+
+/// The length of the HMAC-MD5 checksum in bytes.
+const CHECKSUM_LENGTH: usize = 16;
+
+/// The length of the confounder in bytes.
+const CONFOUNDER_LENGTH: usize = 8;
+
+/// Converts `password` into a key, the MD4 hash of its UTF-16LE encoding.
+/// The salt is unused.
+pub(super) fn string_to_key(
+    password: &[u8],
+    params: Option<&[u8]>,
+) -> Result<Vec<u8>, CryptoError> {
+    if params.is_some_and(|params| !params.is_empty()) {
+        return Err(CryptoError::InvalidParameters);
+    }
+
+    let password = core::str::from_utf8(password).map_err(|_| CryptoError::InvalidPassword)?;
+    let encoded: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    Ok(Md4::digest(&encoded).to_vec())
+}
+
+pub(super) fn encrypt(key: &[u8], usage: i32, mut data: Vec<u8>) -> Vec<u8> {
+    let k1 = hmac_md5(key, &[&translate_usage(usage).to_le_bytes()]);
+    let checksum = hmac_md5(&k1, &[&data]);
+    let k3 = hmac_md5(&k1, &[&checksum]);
+    rc4(&k3, &mut data);
+
+    let mut ciphertext = checksum.to_vec();
+    ciphertext.append(&mut data);
+    ciphertext
+}
+
+pub(super) fn decrypt(key: &[u8], usage: i32, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if ciphertext.len() < CHECKSUM_LENGTH + CONFOUNDER_LENGTH {
+        return Err(CryptoError::InvalidCiphertext);
+    }
+
+    let (checksum, ciphertext) = ciphertext.split_at(CHECKSUM_LENGTH);
+    let k1 = hmac_md5(key, &[&translate_usage(usage).to_le_bytes()]);
+    let k3 = hmac_md5(&k1, &[checksum]);
+    let mut data = ciphertext.to_vec();
+    rc4(&k3, &mut data);
+
+    verify_mac(&hmac_md5(&k1, &[&data]), checksum)?;
+    Ok(data)
+}
+
+/// The `HMAC-MD5` checksum type, from RFC 4757 Section 4.
+pub(super) fn checksum(key: &[u8], usage: i32, data: &[u8]) -> Vec<u8> {
+    let signing_key = hmac_md5(key, &[b"signaturekey\0"]);
+    let digest = Md5::new()
+        .chain_update(translate_usage(usage).to_le_bytes())
+        .chain_update(data)
+        .finalize();
+    hmac_md5(&signing_key, &[&digest]).to_vec()
+}
+
+/// Maps RFC 4120 key usages to the message types used by RFC 4757.
+fn translate_usage(usage: i32) -> i32 {
+    match usage {
+        3 => 8,
+        23 => 13,
+        usage => usage,
+    }
+}
+
+fn hmac_md5(key: &[u8], parts: &[&[u8]]) -> [u8; 16] {
+    let mut mac = <Hmac<Md5>>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Encrypts or decrypts `data` in place with the RC4 stream cipher.
+fn rc4(key: &[u8], data: &mut [u8]) {
+    let mut state: [u8; 256] = core::array::from_fn(|i| i as u8);
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, usize::from(j));
+    }
+
+    let (mut i, mut j) = (0u8, 0u8);
+    for byte in data {
+        i = i.wrapping_add(1);
+        j = j.wrapping_add(state[usize::from(i)]);
+        state.swap(usize::from(i), usize::from(j));
+        let index = state[usize::from(i)].wrapping_add(state[usize::from(j)]);
+        *byte ^= state[usize::from(index)];
+    }
+}
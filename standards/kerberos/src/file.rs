@@ -0,0 +1,213 @@
+//! Shared support for the binary (non-ASN.1) keytab and credential cache
+//! file formats.
+
+use alloc::{string::String, vec::Vec};
+
+use rasn::error::{strings::InvalidGeneralString, DecodeError, EncodeError};
+
+use crate::{KerberosString, KerberosTime, PrincipalName, Realm};
+
+/// An error returned when reading or writing a keytab or credential cache.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FileError {
+    /// The file has a format version that isn't supported.
+    UnsupportedVersion(u16),
+    /// The file ended in the middle of a field.
+    UnexpectedEof,
+    /// A realm or name component isn't a valid [KerberosString].
+    InvalidString(InvalidGeneralString),
+    /// A length, count, time or encryption type is too large for the field
+    /// that stores it.
+    OutOfRange,
+    /// An embedded ticket couldn't be decoded.
+    Decode(DecodeError),
+    /// An embedded ticket couldn't be encoded.
+    Encode(EncodeError),
+}
+
+impl core::fmt::Display for FileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported file format version {version:#06x}")
+            }
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::InvalidString(error) => write!(f, "invalid string: {error}"),
+            Self::OutOfRange => write!(f, "value too large for its field"),
+            Self::Decode(error) => write!(f, "failed to decode ticket: {error}"),
+            Self::Encode(error) => write!(f, "failed to encode ticket: {error}"),
+        }
+    }
+}
+
+impl From<InvalidGeneralString> for FileError {
+    fn from(error: InvalidGeneralString) -> Self {
+        Self::InvalidString(error)
+    }
+}
+
+impl From<DecodeError> for FileError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl From<EncodeError> for FileError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+/// A principal identifier, a [PrincipalName] together with its [Realm].
+///
+/// Keytabs and credential caches store both in one structure, rather than
+/// separately as the protocol messages do.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Principal {
+    pub realm: Realm,
+    pub name: PrincipalName,
+}
+
+impl Principal {
+    pub fn new(realm: Realm, name: PrincipalName) -> Self {
+        Self { realm, name }
+    }
+
+    /// Returns whether `self` and `other` name the same principal. The name
+    /// type is only a hint, so it isn't compared.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.realm == other.realm && self.name.string == other.name.string
+    }
+}
+
+/// Formats the principal as `component/component@REALM`, escaping any `/`,
+/// `@` and `\` in the components and realm with a backslash.
+impl core::fmt::Display for Principal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, component) in self.name.string.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            write_escaped(f, component)?;
+        }
+        f.write_str("@")?;
+        write_escaped(f, &self.realm)
+    }
+}
+
+fn write_escaped(f: &mut core::fmt::Formatter<'_>, string: &KerberosString) -> core::fmt::Result {
+    for character in String::from_utf8_lossy(string).chars() {
+        if matches!(character, '/' | '@' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{character}")?;
+    }
+    Ok(())
+}
+
+/// A cursor over big-endian fields.
+pub(crate) struct Reader<'input> {
+    input: &'input [u8],
+}
+
+impl<'input> Reader<'input> {
+    pub(crate) fn new(input: &'input [u8]) -> Self {
+        Self { input }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.input.len()
+    }
+
+    pub(crate) fn bytes(&mut self, length: usize) -> Result<&'input [u8], FileError> {
+        if self.input.len() < length {
+            return Err(FileError::UnexpectedEof);
+        }
+
+        let (bytes, rest) = self.input.split_at(length);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, FileError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, FileError> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, FileError> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, FileError> {
+        Ok(i32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Reads bytes prefixed with a 16 bit length.
+    pub(crate) fn data16(&mut self) -> Result<&'input [u8], FileError> {
+        let length = self.u16()?;
+        self.bytes(length.into())
+    }
+
+    /// Reads bytes prefixed with a 32 bit length.
+    pub(crate) fn data32(&mut self) -> Result<&'input [u8], FileError> {
+        let length = self.u32()?;
+        self.bytes(usize::try_from(length).map_err(|_| FileError::OutOfRange)?)
+    }
+
+    /// Reads a time in seconds since the Unix epoch.
+    pub(crate) fn time(&mut self) -> Result<KerberosTime, FileError> {
+        let seconds = self.u32()?;
+        let time = chrono::DateTime::from_timestamp(seconds.into(), 0)
+            .expect("32 bit timestamps are in range");
+        Ok(KerberosTime(time.into()))
+    }
+
+    /// Reads a signed 16 bit type, such as an encryption or address type.
+    pub(crate) fn i16(&mut self) -> Result<i32, FileError> {
+        Ok((self.u16()? as i16).into())
+    }
+}
+
+pub(crate) fn put_data16(output: &mut Vec<u8>, data: &[u8]) -> Result<(), FileError> {
+    let length = u16::try_from(data.len()).map_err(|_| FileError::OutOfRange)?;
+    output.extend_from_slice(&length.to_be_bytes());
+    output.extend_from_slice(data);
+    Ok(())
+}
+
+pub(crate) fn put_data32(output: &mut Vec<u8>, data: &[u8]) -> Result<(), FileError> {
+    put_u32(output, data.len())?;
+    output.extend_from_slice(data);
+    Ok(())
+}
+
+/// Writes a count or length as 32 bits.
+pub(crate) fn put_u32(output: &mut Vec<u8>, value: usize) -> Result<(), FileError> {
+    let value = u32::try_from(value).map_err(|_| FileError::OutOfRange)?;
+    output.extend_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+pub(crate) fn put_time(output: &mut Vec<u8>, time: &KerberosTime) -> Result<(), FileError> {
+    let seconds = u32::try_from(time.0.timestamp()).map_err(|_| FileError::OutOfRange)?;
+    output.extend_from_slice(&seconds.to_be_bytes());
+    Ok(())
+}
+
+pub(crate) fn put_i16(output: &mut Vec<u8>, value: i32) -> Result<(), FileError> {
+    let value = i16::try_from(value).map_err(|_| FileError::OutOfRange)?;
+    output.extend_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+pub(crate) fn string(bytes: &[u8]) -> Result<KerberosString, FileError> {
+    Ok(KerberosString::from_bytes(bytes)?)
+}
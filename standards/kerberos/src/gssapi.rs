@@ -0,0 +1,467 @@
+//! The tokens of the Kerberos V5 GSS-API mechanism, from [RFC 4121].
+//!
+//! Context establishment tokens carry an [ApReq], [ApRep] or [KrbError]
+//! behind a two byte [TokenId], inside the GSS-API [InitialContextToken]
+//! framing. Once the context is established, [MicToken]s and [WrapToken]s
+//! protect application messages with the session key or a subkey.
+//!
+//! [RFC 4121]: https://datatracker.ietf.org/doc/html/rfc4121
+
+use alloc::vec::Vec;
+
+use rasn::error::{DecodeError, EncodeError};
+use rasn_spnego::{InitialContextToken, TokenError, KERBEROS_V5, MS_KERBEROS_V5};
+
+use crate::crypto::{key_usage, CryptoError};
+use crate::{ApRep, ApReq, EncryptionKey, KrbCred, KrbError};
+
+/// The length of the header of MIC and Wrap tokens in bytes.
+const HEADER_LENGTH: usize = 16;
+
+/// An error returned when reading or checking a mechanism token.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GssError {
+    /// The token's initial context token framing is invalid, or is for
+    /// another mechanism.
+    Framing(TokenError),
+    /// The token has an unexpected token identifier.
+    UnexpectedTokenId(TokenId),
+    /// The token is too short, or its filler bytes are invalid.
+    Malformed,
+    /// The header inside an encrypted Wrap token doesn't match the outer
+    /// header.
+    HeaderMismatch,
+    /// The Kerberos message in the token couldn't be decoded.
+    Decode(DecodeError),
+    /// The token failed its integrity check, or couldn't be decrypted.
+    Crypto(CryptoError),
+}
+
+impl core::fmt::Display for GssError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Framing(error) => write!(f, "{error}"),
+            Self::UnexpectedTokenId(id) => write!(f, "unexpected token ID {:02x?}", id.0),
+            Self::Malformed => write!(f, "malformed token"),
+            Self::HeaderMismatch => write!(f, "wrap token header mismatch"),
+            Self::Decode(error) => write!(f, "failed to decode token: {error}"),
+            Self::Crypto(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<TokenError> for GssError {
+    fn from(error: TokenError) -> Self {
+        Self::Framing(error)
+    }
+}
+
+impl From<DecodeError> for GssError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl From<CryptoError> for GssError {
+    fn from(error: CryptoError) -> Self {
+        Self::Crypto(error)
+    }
+}
+
+/// The identifier at the start of each mechanism token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TokenId(pub [u8; 2]);
+
+impl TokenId {
+    pub const AP_REQ: Self = Self([0x01, 0x00]);
+    pub const AP_REP: Self = Self([0x02, 0x00]);
+    pub const KRB_ERROR: Self = Self([0x03, 0x00]);
+    pub const MIC: Self = Self([0x04, 0x04]);
+    pub const WRAP: Self = Self([0x05, 0x04]);
+}
+
+/// A context establishment token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContextToken {
+    ApReq(ApReq),
+    ApRep(ApRep),
+    KrbError(KrbError),
+}
+
+impl ContextToken {
+    /// Parses a framed token for either the Kerberos V5 mechanism OID, or the
+    /// one used by older versions of Windows.
+    pub fn parse(input: &[u8]) -> Result<Self, GssError> {
+        let token = InitialContextToken::parse(input)?;
+        if token.this_mech != KERBEROS_V5 && token.this_mech != MS_KERBEROS_V5 {
+            return Err(TokenError::UnexpectedMech(token.this_mech).into());
+        }
+
+        let Some((id, message)) = token.inner_context_token.split_first_chunk() else {
+            return Err(GssError::Malformed);
+        };
+        Ok(match TokenId(*id) {
+            TokenId::AP_REQ => Self::ApReq(rasn::der::decode(message)?),
+            TokenId::AP_REP => Self::ApRep(rasn::der::decode(message)?),
+            TokenId::KRB_ERROR => Self::KrbError(rasn::der::decode(message)?),
+            id => return Err(GssError::UnexpectedTokenId(id)),
+        })
+    }
+
+    /// Returns the framed token, for the Kerberos V5 mechanism OID.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let (id, message) = match self {
+            Self::ApReq(message) => (TokenId::AP_REQ, rasn::der::encode(message)?),
+            Self::ApRep(message) => (TokenId::AP_REP, rasn::der::encode(message)?),
+            Self::KrbError(message) => (TokenId::KRB_ERROR, rasn::der::encode(message)?),
+        };
+
+        let inner = [&id.0[..], &message].concat();
+        Ok(InitialContextToken::new(KERBEROS_V5.into(), inner).to_bytes())
+    }
+}
+
+/// The checksum in the [crate::Authenticator] of an [ApReq] token, which
+/// carries the GSS-API context flags, from [RFC 4121 Section 4.1.1].
+///
+/// [RFC 4121 Section 4.1.1]: https://datatracker.ietf.org/doc/html/rfc4121#section-4.1.1
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuthenticatorChecksum {
+    /// The MD5 hash of the channel bindings, or zeros when there are none.
+    pub channel_binding: [u8; 16],
+    /// The requested context flags, such as [AuthenticatorChecksum::MUTUAL].
+    pub flags: u32,
+    /// The delegated credentials, sent when [AuthenticatorChecksum::DELEG] is
+    /// set.
+    pub delegation: Option<KrbCred>,
+    /// Any extension data following the delegated credentials.
+    pub extensions: Vec<u8>,
+}
+
+impl AuthenticatorChecksum {
+    /// The checksum type of the authenticator checksum.
+    pub const TYPE: i32 = 0x8003;
+
+    pub const DELEG: u32 = 1;
+    pub const MUTUAL: u32 = 2;
+    pub const REPLAY: u32 = 4;
+    pub const SEQUENCE: u32 = 8;
+    pub const CONF: u32 = 16;
+    pub const INTEG: u32 = 32;
+    pub const DCE_STYLE: u32 = 0x1000;
+
+    /// Parses the `checksum` field of a checksum of type
+    /// [AuthenticatorChecksum::TYPE].
+    pub fn parse(input: &[u8]) -> Result<Self, GssError> {
+        let (length, rest) = input.split_first_chunk::<4>().ok_or(GssError::Malformed)?;
+        let (channel_binding, rest) = rest.split_first_chunk::<16>().ok_or(GssError::Malformed)?;
+        let (flags, mut rest) = rest.split_first_chunk::<4>().ok_or(GssError::Malformed)?;
+        if u32::from_le_bytes(*length) != 16 {
+            return Err(GssError::Malformed);
+        }
+
+        let flags = u32::from_le_bytes(*flags);
+        let mut delegation = None;
+        if flags & Self::DELEG != 0 {
+            let (option, tail) = rest.split_first_chunk::<2>().ok_or(GssError::Malformed)?;
+            let (length, tail) = tail.split_first_chunk::<2>().ok_or(GssError::Malformed)?;
+            let (credential, tail) = tail
+                .split_at_checked(u16::from_le_bytes(*length).into())
+                .ok_or(GssError::Malformed)?;
+            if u16::from_le_bytes(*option) != 1 {
+                return Err(GssError::Malformed);
+            }
+
+            delegation = Some(rasn::der::decode(credential)?);
+            rest = tail;
+        }
+
+        Ok(Self {
+            channel_binding: *channel_binding,
+            flags,
+            delegation,
+            extensions: rest.to_vec(),
+        })
+    }
+
+    /// Returns the `checksum` field of the checksum.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut output = 16u32.to_le_bytes().to_vec();
+        output.extend_from_slice(&self.channel_binding);
+        output.extend_from_slice(&self.flags.to_le_bytes());
+        if let Some(credential) = &self.delegation {
+            let credential = rasn::der::encode(credential)?;
+            let length = u16::try_from(credential.len()).map_err(|_| {
+                EncodeError::from_kind(
+                    rasn::error::EncodeErrorKind::Custom {
+                        msg: "delegated credentials are longer than 65535 bytes".into(),
+                    },
+                    rasn::Codec::Der,
+                )
+            })?;
+            output.extend_from_slice(&1u16.to_le_bytes());
+            output.extend_from_slice(&length.to_le_bytes());
+            output.extend_from_slice(&credential);
+        }
+        output.extend_from_slice(&self.extensions);
+        Ok(output)
+    }
+}
+
+/// The flags of MIC and Wrap tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TokenFlags(pub u8);
+
+impl TokenFlags {
+    /// The token was sent by the context acceptor.
+    pub const SENT_BY_ACCEPTOR: Self = Self(0x01);
+    /// The Wrap token's data is encrypted.
+    pub const SEALED: Self = Self(0x02);
+    /// The token is protected with the acceptor's subkey.
+    pub const ACCEPTOR_SUBKEY: Self = Self(0x04);
+
+    /// Returns whether all of the flags in `other` are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for TokenFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// A token carrying a checksum over an application message, from
+/// [RFC 4121 Section 4.2.6.1].
+///
+/// [RFC 4121 Section 4.2.6.1]: https://datatracker.ietf.org/doc/html/rfc4121#section-4.2.6.1
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MicToken {
+    pub flags: TokenFlags,
+    pub sequence_number: u64,
+    pub checksum: Vec<u8>,
+}
+
+impl MicToken {
+    /// Computes the token for `message` with `key`.
+    pub fn new(
+        key: &EncryptionKey,
+        flags: TokenFlags,
+        sequence_number: u64,
+        message: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let mut token = Self {
+            flags,
+            sequence_number,
+            checksum: Vec::new(),
+        };
+        token.checksum = key.enctype()?.checksum(
+            &key.value,
+            token.usage(),
+            &[message, &token.header()].concat(),
+        )?;
+        Ok(token)
+    }
+
+    /// Checks the token's checksum over `message` with `key`.
+    pub fn verify(&self, key: &EncryptionKey, message: &[u8]) -> Result<(), CryptoError> {
+        let expected = key.enctype()?.checksum(
+            &key.value,
+            self.usage(),
+            &[message, &self.header()].concat(),
+        )?;
+        crate::crypto::verify_mac(&expected, &self.checksum)
+    }
+
+    pub fn parse(input: &[u8]) -> Result<Self, GssError> {
+        let (header, checksum) = input
+            .split_first_chunk::<HEADER_LENGTH>()
+            .ok_or(GssError::Malformed)?;
+        let id = TokenId([header[0], header[1]]);
+        if id != TokenId::MIC {
+            return Err(GssError::UnexpectedTokenId(id));
+        }
+        if header[3..8] != [0xff; 5] {
+            return Err(GssError::Malformed);
+        }
+
+        Ok(Self {
+            flags: TokenFlags(header[2]),
+            sequence_number: u64::from_be_bytes(header[8..].try_into().unwrap()),
+            checksum: checksum.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.header()[..], &self.checksum].concat()
+    }
+
+    fn header(&self) -> [u8; HEADER_LENGTH] {
+        let mut header = [0xff; HEADER_LENGTH];
+        header[..2].copy_from_slice(&TokenId::MIC.0);
+        header[2] = self.flags.0;
+        header[8..].copy_from_slice(&self.sequence_number.to_be_bytes());
+        header
+    }
+
+    fn usage(&self) -> i32 {
+        match self.flags.contains(TokenFlags::SENT_BY_ACCEPTOR) {
+            true => key_usage::ACCEPTOR_SIGN,
+            false => key_usage::INITIATOR_SIGN,
+        }
+    }
+}
+
+/// A token carrying an application message protected for integrity, and
+/// optionally confidentiality, from [RFC 4121 Section 4.2.6.2].
+///
+/// [RFC 4121 Section 4.2.6.2]: https://datatracker.ietf.org/doc/html/rfc4121#section-4.2.6.2
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WrapToken {
+    pub flags: TokenFlags,
+    /// The "extra count": the number of filler bytes in sealed tokens, or the
+    /// length of the checksum in unsealed ones.
+    pub ec: u16,
+    /// The "right rotation count": the number of bytes `data` has been
+    /// rotated right by.
+    pub rrc: u16,
+    pub sequence_number: u64,
+    pub data: Vec<u8>,
+}
+
+impl WrapToken {
+    /// Encrypts `message` with `key`, prepending the random `confounder`. The
+    /// [TokenFlags::SEALED] flag is added to `flags`.
+    pub fn seal(
+        key: &EncryptionKey,
+        flags: TokenFlags,
+        sequence_number: u64,
+        message: &[u8],
+        confounder: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let mut token = Self {
+            flags: flags | TokenFlags::SEALED,
+            ec: 0,
+            rrc: 0,
+            sequence_number,
+            data: Vec::new(),
+        };
+        token.data = key.enctype()?.encrypt(
+            &key.value,
+            token.usage(),
+            &[message, &token.header(0, 0)].concat(),
+            confounder,
+        )?;
+        Ok(token)
+    }
+
+    /// Appends a checksum over `message` with `key`, without encrypting it.
+    pub fn sign(
+        key: &EncryptionKey,
+        flags: TokenFlags,
+        sequence_number: u64,
+        message: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let mut token = Self {
+            flags: TokenFlags(flags.0 & !TokenFlags::SEALED.0),
+            ec: 0,
+            rrc: 0,
+            sequence_number,
+            data: Vec::new(),
+        };
+        let checksum = key.enctype()?.checksum(
+            &key.value,
+            token.usage(),
+            &[message, &token.header(0, 0)].concat(),
+        )?;
+        token.ec = checksum.len() as u16;
+        token.data = [message, &checksum].concat();
+        Ok(token)
+    }
+
+    /// Checks the token with `key`, and returns the application message,
+    /// decrypting it if the token is sealed.
+    pub fn open(&self, key: &EncryptionKey) -> Result<Vec<u8>, GssError> {
+        let mut data = self.data.clone();
+        if !data.is_empty() {
+            let rrc = usize::from(self.rrc) % data.len();
+            data.rotate_left(rrc);
+        }
+
+        let enctype = key.enctype()?;
+        let ec = usize::from(self.ec);
+        if self.flags.contains(TokenFlags::SEALED) {
+            let mut plaintext = enctype.decrypt(&key.value, self.usage(), &data)?;
+            let Some(length) = plaintext.len().checked_sub(ec + HEADER_LENGTH) else {
+                return Err(GssError::Malformed);
+            };
+            if plaintext[length + ec..] != self.header(self.ec, 0) {
+                return Err(GssError::HeaderMismatch);
+            }
+
+            plaintext.truncate(length);
+            Ok(plaintext)
+        } else {
+            let Some(length) = data.len().checked_sub(ec) else {
+                return Err(GssError::Malformed);
+            };
+            let (message, checksum) = data.split_at(length);
+            let expected = enctype.checksum(
+                &key.value,
+                self.usage(),
+                &[message, &self.header(0, 0)].concat(),
+            )?;
+            crate::crypto::verify_mac(&expected, checksum)?;
+            Ok(message.to_vec())
+        }
+    }
+
+    pub fn parse(input: &[u8]) -> Result<Self, GssError> {
+        let (header, data) = input
+            .split_first_chunk::<HEADER_LENGTH>()
+            .ok_or(GssError::Malformed)?;
+        let id = TokenId([header[0], header[1]]);
+        if id != TokenId::WRAP {
+            return Err(GssError::UnexpectedTokenId(id));
+        }
+        if header[3] != 0xff {
+            return Err(GssError::Malformed);
+        }
+
+        Ok(Self {
+            flags: TokenFlags(header[2]),
+            ec: u16::from_be_bytes([header[4], header[5]]),
+            rrc: u16::from_be_bytes([header[6], header[7]]),
+            sequence_number: u64::from_be_bytes(header[8..].try_into().unwrap()),
+            data: data.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.header(self.ec, self.rrc)[..], &self.data].concat()
+    }
+
+    /// Returns the header with the given EC and RRC, which are zeroed in the
+    /// copies protected by the checksum or encryption.
+    fn header(&self, ec: u16, rrc: u16) -> [u8; HEADER_LENGTH] {
+        let mut header = [0; HEADER_LENGTH];
+        header[..2].copy_from_slice(&TokenId::WRAP.0);
+        header[2] = self.flags.0;
+        header[3] = 0xff;
+        header[4..6].copy_from_slice(&ec.to_be_bytes());
+        header[6..8].copy_from_slice(&rrc.to_be_bytes());
+        header[8..].copy_from_slice(&self.sequence_number.to_be_bytes());
+        header
+    }
+
+    fn usage(&self) -> i32 {
+        match self.flags.contains(TokenFlags::SENT_BY_ACCEPTOR) {
+            true => key_usage::ACCEPTOR_SEAL,
+            false => key_usage::INITIATOR_SEAL,
+        }
+    }
+}
@@ -0,0 +1,582 @@
+//! A minimal in-process KDC, for testing clients and services without a
+//! real realm.
+//!
+//! [Kdc] answers AS and TGS requests for the principals added to it, with
+//! optional encrypted timestamp pre-authentication. Its keys and confounders
+//! come from a deterministic generator rather than a random number generator,
+//! and it keeps no replay cache, so it must never be used outside of tests.
+
+use alloc::{vec, vec::Vec};
+
+use chrono::TimeDelta;
+use rasn::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{key_usage, CryptoError, Enctype};
+use crate::keytab::{Keytab, KeytabEntry, Principal};
+use crate::transport::{FramingError, UDP_PREFERENCE_LIMIT};
+use crate::*;
+
+/// The maximum difference between a client's clock and [Kdc::now].
+const MAX_SKEW: TimeDelta = TimeDelta::minutes(5);
+
+/// The encryption types the KDC supports, most preferred first.
+const ETYPES: [i32; 4] = [
+    Enctype::AES256_CTS_HMAC_SHA1_96,
+    Enctype::AES128_CTS_HMAC_SHA1_96,
+    Enctype::AES256_CTS_HMAC_SHA384_192,
+    Enctype::AES128_CTS_HMAC_SHA256_128,
+];
+
+const INITIAL: usize = 9;
+const PRE_AUTHENT: usize = 10;
+
+/// A KDC for a single realm, holding its principal database in memory.
+#[derive(Clone, Debug)]
+pub struct Kdc {
+    /// The realm of the KDC and all of its principals.
+    pub realm: Realm,
+    /// The current time, used for issuing tickets and checking clients'
+    /// clocks.
+    pub now: KerberosTime,
+    /// The longest lifetime of an issued ticket.
+    pub max_lifetime: TimeDelta,
+    /// Whether AS requests must be pre-authenticated with an encrypted
+    /// timestamp.
+    pub require_preauth: bool,
+    /// The encryption types of the keys [Kdc::add_principal] derives, most
+    /// preferred first.
+    pub etypes: Vec<i32>,
+    /// The largest reply [Kdc::handle_udp] sends, above which it replies
+    /// with [KrbError::KRB_ERR_RESPONSE_TOO_BIG].
+    pub max_udp_length: usize,
+    principals: Vec<Entry>,
+    counter: u64,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    name: PrincipalName,
+    kvno: u32,
+    keys: Vec<EncryptionKey>,
+}
+
+/// The reason a request failed, turned into a [KrbError] by [Kdc::handle].
+struct Failure {
+    code: i32,
+    e_data: Option<Vec<u8>>,
+}
+
+impl From<i32> for Failure {
+    fn from(code: i32) -> Self {
+        Self { code, e_data: None }
+    }
+}
+
+impl Kdc {
+    /// Creates a KDC with a `krbtgt` principal for `realm`.
+    pub fn new(realm: Realm, now: KerberosTime) -> Self {
+        let mut kdc = Self {
+            realm,
+            now,
+            max_lifetime: TimeDelta::hours(10),
+            require_preauth: true,
+            etypes: ETYPES.to_vec(),
+            max_udp_length: UDP_PREFERENCE_LIMIT,
+            principals: Vec::new(),
+            counter: 0,
+        };
+
+        let krbtgt = kdc.krbtgt();
+        let keys = ETYPES.map(|etype| kdc.random_key(etype)).to_vec();
+        kdc.principals.push(Entry {
+            name: krbtgt,
+            kvno: 1,
+            keys,
+        });
+        kdc
+    }
+
+    /// Adds a principal with keys derived from `password` and the default
+    /// salt, or replaces its keys with the next key version number if it
+    /// already exists.
+    pub fn add_principal(
+        &mut self,
+        name: PrincipalName,
+        password: &[u8],
+    ) -> Result<(), CryptoError> {
+        let salt = name.salt(&self.realm);
+        let keys = self
+            .etypes
+            .iter()
+            .map(|etype| EncryptionKey::from_password(*etype, password, &salt, None))
+            .collect::<Result<_, _>>()?;
+
+        match self.find_mut(&name) {
+            Some(entry) => {
+                entry.kvno += 1;
+                entry.keys = keys;
+            }
+            None => self.principals.push(Entry {
+                name,
+                kvno: 1,
+                keys,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Returns a keytab holding the keys of the principal `name`, for a
+    /// service accepting tickets issued by the KDC.
+    pub fn keytab(&self, name: &PrincipalName) -> Option<Keytab> {
+        let entry = self.find(name)?;
+        let principal = Principal::new(self.realm.clone(), entry.name.clone());
+        let entries = entry
+            .keys
+            .iter()
+            .map(|key| KeytabEntry {
+                principal: principal.clone(),
+                timestamp: self.now(),
+                kvno: entry.kvno,
+                key: key.clone(),
+            })
+            .collect();
+
+        Some(Keytab { entries })
+    }
+
+    /// Answers a request, with either an [AsRep], a [TgsRep] or a
+    /// [KrbError].
+    pub fn handle(&mut self, request: &KdcMessage) -> KdcMessage {
+        let (request, result) = match request {
+            KdcMessage::AsReq(AsReq(request)) => (Some(request), self.as_exchange(request)),
+            KdcMessage::TgsReq(TgsReq(request)) => (Some(request), self.tgs_exchange(request)),
+            _ => (None, Err(KrbError::KRB_AP_ERR_MSG_TYPE.into())),
+        };
+
+        result.unwrap_or_else(|failure| {
+            KdcMessage::KrbError(self.error(failure, request.map(|request| &request.req_body)))
+        })
+    }
+
+    /// Answers a request received as a UDP datagram, returning the datagram
+    /// to reply with.
+    pub fn handle_udp(&mut self, datagram: &[u8]) -> Vec<u8> {
+        let reply = match KdcMessage::from_udp(datagram) {
+            Ok(request) => self.handle(&request),
+            Err(_) => self.error_message(KrbError::KRB_ERR_GENERIC),
+        };
+
+        let reply = reply.to_udp().expect("replies always encode");
+        if reply.len() > self.max_udp_length {
+            self.error_message(KrbError::KRB_ERR_RESPONSE_TOO_BIG)
+                .to_udp()
+                .expect("replies always encode")
+        } else {
+            reply
+        }
+    }
+
+    /// Answers the first request in the data received over a TCP connection
+    /// so far, returning the framed reply and the number of bytes the
+    /// request took up, or `None` if more data is needed.
+    pub fn handle_tcp(&mut self, input: &[u8]) -> Option<(Vec<u8>, usize)> {
+        let (reply, length) = match KdcMessage::from_tcp(input, u32::MAX as usize) {
+            Ok(Some((request, length))) => (self.handle(&request), length),
+            Ok(None) => return None,
+            Err(FramingError::ReservedBit) => (
+                self.error_message(KrbError::KRB_ERR_FIELD_TOOLONG),
+                input.len(),
+            ),
+            Err(_) => (self.error_message(KrbError::KRB_ERR_GENERIC), input.len()),
+        };
+
+        Some((reply.to_tcp().expect("replies always encode"), length))
+    }
+
+    fn as_exchange(&mut self, request: &KdcReq) -> Result<KdcMessage, Failure> {
+        let body = &request.req_body;
+        let client = body
+            .cname
+            .as_ref()
+            .filter(|_| body.realm == self.realm)
+            .and_then(|name| self.find(name))
+            .ok_or(KrbError::KDC_ERR_C_PRINCIPAL_UNKNOWN)?;
+        let server = self.server(body)?;
+        let client_key = body
+            .etype
+            .iter()
+            .find_map(|etype| client.keys.iter().find(|key| key.r#type == *etype))
+            .ok_or(KrbError::KDC_ERR_ETYPE_NOSUPP)?;
+
+        let etype_info = PaData {
+            r#type: PaData::ETYPE_INFO2,
+            value: rasn::der::encode(&vec![EtypeInfo2Entry {
+                etype: client_key.r#type,
+                salt: KerberosString::from_bytes(&client.name.salt(&self.realm)).ok(),
+                s2kparams: None,
+            }])
+            .expect("padata always encodes")
+            .into(),
+        };
+
+        let timestamp = request
+            .padata
+            .iter()
+            .flatten()
+            .find(|padata| padata.r#type == PaData::ENC_TIMESTAMP);
+        let pre_authenticated = match timestamp {
+            Some(padata) => {
+                let timestamp = rasn::der::decode::<PaEncTimestamp>(&padata.value)
+                    .ok()
+                    .and_then(|data| {
+                        let key = client.keys.iter().find(|key| key.r#type == data.etype)?;
+                        data.decrypt::<PaEncTsEnc>(key, key_usage::AS_REQ_PA_ENC_TIMESTAMP)
+                            .ok()
+                    })
+                    .ok_or(KrbError::KDC_ERR_PREAUTH_FAILED)?;
+                self.check_skew(&timestamp.patimestamp)?;
+                true
+            }
+            None if self.require_preauth => {
+                let method_data = vec![
+                    PaData {
+                        r#type: PaData::ENC_TIMESTAMP,
+                        value: OctetString::new(),
+                    },
+                    etype_info,
+                ];
+                return Err(Failure {
+                    code: KrbError::KDC_ERR_PREAUTH_REQUIRED,
+                    e_data: Some(rasn::der::encode(&method_data).expect("padata always encodes")),
+                });
+            }
+            None => false,
+        };
+
+        let mut flags = vec![INITIAL];
+        if pre_authenticated {
+            flags.push(PRE_AUTHENT);
+        }
+        let (client_key, client_kvno) = (client_key.clone(), client.kvno);
+        let (server_name, server_key, server_kvno) =
+            (server.name.clone(), server.keys[0].clone(), server.kvno);
+        let client_name = client.name.clone();
+        let end_time = self.end_time(&body.till, None)?;
+
+        let (ticket, enc_part) = self.issue(
+            body,
+            client_name.clone(),
+            &flags,
+            self.now(),
+            end_time,
+            (server_name, server_key, server_kvno),
+        )?;
+        let enc_part = EncryptedData::encrypt(
+            &EncAsRepPart(enc_part),
+            &client_key,
+            key_usage::AS_REP_ENC_PART,
+            Some(client_kvno),
+            &self.confounder(&client_key),
+        )
+        .map_err(|_| KrbError::KRB_ERR_GENERIC)?;
+
+        Ok(KdcMessage::AsRep(AsRep(KdcRep {
+            pvno: 5.into(),
+            msg_type: 11.into(),
+            padata: Some(vec![etype_info]),
+            crealm: self.realm.clone(),
+            cname: client_name,
+            ticket,
+            enc_part,
+        })))
+    }
+
+    fn tgs_exchange(&mut self, request: &KdcReq) -> Result<KdcMessage, Failure> {
+        let body = &request.req_body;
+        let ap_req = request
+            .padata
+            .iter()
+            .flatten()
+            .find(|padata| padata.r#type == PaData::TGS_REQ)
+            .and_then(|padata| rasn::der::decode::<ApReq>(&padata.value).ok())
+            .ok_or(KrbError::KDC_ERR_PADATA_TYPE_NOSUPP)?;
+
+        let krbtgt = self.find(&self.krbtgt()).expect("krbtgt always exists");
+        if ap_req.ticket.realm != self.realm || ap_req.ticket.sname.string != krbtgt.name.string {
+            return Err(KrbError::KRB_AP_ERR_NOT_US.into());
+        }
+        let krbtgt_key = krbtgt
+            .keys
+            .iter()
+            .find(|key| key.r#type == ap_req.ticket.enc_part.etype)
+            .ok_or(KrbError::KRB_AP_ERR_NOKEY)?;
+        let tgt: EncTicketPart = ap_req
+            .ticket
+            .enc_part
+            .decrypt(krbtgt_key, key_usage::TICKET)
+            .map_err(|_| KrbError::KRB_AP_ERR_BAD_INTEGRITY)?;
+        let authenticator: Authenticator = ap_req
+            .authenticator
+            .decrypt(&tgt.key, key_usage::TGS_REQ_AUTHENTICATOR)
+            .map_err(|_| KrbError::KRB_AP_ERR_BAD_INTEGRITY)?;
+
+        if authenticator.crealm != tgt.crealm || authenticator.cname.string != tgt.cname.string {
+            return Err(KrbError::KRB_AP_ERR_BADMATCH.into());
+        }
+        self.check_skew(&authenticator.ctime)?;
+        if tgt.end_time <= self.now {
+            return Err(KrbError::KRB_AP_ERR_TKT_EXPIRED.into());
+        }
+
+        let checksum = authenticator
+            .cksum
+            .as_ref()
+            .ok_or(KrbError::KRB_AP_ERR_INAPP_CKSUM)?;
+        let encoded_body = rasn::der::encode(body).map_err(|_| KrbError::KRB_ERR_GENERIC)?;
+        checksum
+            .verify(
+                &tgt.key,
+                key_usage::TGS_REQ_AUTHENTICATOR_CHECKSUM,
+                &encoded_body,
+            )
+            .map_err(|_| KrbError::KRB_AP_ERR_MODIFIED)?;
+
+        let server = self.server(body)?;
+        let server = (server.name.clone(), server.keys[0].clone(), server.kvno);
+        let mut flags = Vec::new();
+        if tgt.flags.0.get(PRE_AUTHENT).is_some_and(|bit| *bit) {
+            flags.push(PRE_AUTHENT);
+        }
+        let end_time = self.end_time(&body.till, Some(&tgt.end_time))?;
+
+        let (ticket, enc_part) = self.issue(
+            body,
+            tgt.cname.clone(),
+            &flags,
+            tgt.auth_time.clone(),
+            end_time,
+            server,
+        )?;
+        let (reply_key, usage) = match &authenticator.subkey {
+            Some(subkey) => (subkey, key_usage::TGS_REP_ENC_PART_SUBKEY),
+            None => (&tgt.key, key_usage::TGS_REP_ENC_PART_SESSION_KEY),
+        };
+        let enc_part = EncryptedData::encrypt(
+            &EncTgsRepPart(enc_part),
+            reply_key,
+            usage,
+            None,
+            &self.confounder(reply_key),
+        )
+        .map_err(|_| KrbError::KRB_ERR_GENERIC)?;
+
+        Ok(KdcMessage::TgsRep(TgsRep(KdcRep {
+            pvno: 5.into(),
+            msg_type: 13.into(),
+            padata: None,
+            crealm: tgt.crealm,
+            cname: tgt.cname,
+            ticket,
+            enc_part,
+        })))
+    }
+
+    /// Issues a ticket for the server to the client, returning it with the
+    /// encrypted part of the reply.
+    fn issue(
+        &mut self,
+        body: &KdcReqBody,
+        cname: PrincipalName,
+        flags: &[usize],
+        auth_time: KerberosTime,
+        end_time: KerberosTime,
+        (sname, server_key, server_kvno): (PrincipalName, EncryptionKey, u32),
+    ) -> Result<(Ticket, EncKdcRepPart), Failure> {
+        let etype = body
+            .etype
+            .iter()
+            .copied()
+            .find(|etype| ETYPES.contains(etype))
+            .ok_or(KrbError::KDC_ERR_ETYPE_NOSUPP)?;
+        let session_key = self.random_key(etype);
+
+        let mut bits = KerberosFlags::repeat(false, 32);
+        for flag in flags {
+            bits.set(*flag, true);
+        }
+        let flags = TicketFlags(bits);
+
+        let ticket_part = EncTicketPart {
+            flags: flags.clone(),
+            key: session_key.clone(),
+            crealm: self.realm.clone(),
+            cname,
+            transited: TransitedEncoding {
+                r#type: 1,
+                contents: OctetString::new(),
+            },
+            auth_time: auth_time.clone(),
+            start_time: Some(self.now()),
+            end_time: end_time.clone(),
+            renew_till: None,
+            caddr: body.addresses.clone(),
+            authorization_data: None,
+        };
+        let ticket = Ticket {
+            tkt_vno: 5.into(),
+            realm: self.realm.clone(),
+            sname: sname.clone(),
+            enc_part: EncryptedData::encrypt(
+                &ticket_part,
+                &server_key,
+                key_usage::TICKET,
+                Some(server_kvno),
+                &self.confounder(&server_key),
+            )
+            .map_err(|_| KrbError::KRB_ERR_GENERIC)?,
+        };
+
+        let enc_part = EncKdcRepPart {
+            key: session_key,
+            last_req: Vec::new(),
+            nonce: body.nonce,
+            key_expiration: None,
+            flags,
+            auth_time,
+            start_time: Some(self.now()),
+            end_time,
+            renew_till: None,
+            srealm: self.realm.clone(),
+            sname,
+            caddr: body.addresses.clone(),
+            encrypted_pa_data: None,
+        };
+
+        Ok((ticket, enc_part))
+    }
+
+    fn server(&self, body: &KdcReqBody) -> Result<&Entry, Failure> {
+        body.sname
+            .as_ref()
+            .and_then(|name| self.find(name))
+            .ok_or_else(|| KrbError::KDC_ERR_S_PRINCIPAL_UNKNOWN.into())
+    }
+
+    /// Returns the end time of a ticket requested until `till`, limited by
+    /// the KDC's maximum lifetime and the end time of the TGT, if any.
+    fn end_time(
+        &self,
+        till: &KerberosTime,
+        limit: Option<&KerberosTime>,
+    ) -> Result<KerberosTime, Failure> {
+        let mut end_time = self.now().0 + self.max_lifetime;
+        if till.0.timestamp() != 0 {
+            end_time = end_time.min(till.0);
+        }
+        if let Some(limit) = limit {
+            end_time = end_time.min(limit.0);
+        }
+
+        if end_time <= self.now.0 {
+            return Err(KrbError::KDC_ERR_NEVER_VALID.into());
+        }
+        Ok(KerberosTime(end_time))
+    }
+
+    fn check_skew(&self, time: &KerberosTime) -> Result<(), Failure> {
+        if (time.0 - self.now.0).abs() > MAX_SKEW {
+            Err(KrbError::KRB_AP_ERR_SKEW.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn error(&self, failure: Failure, body: Option<&KdcReqBody>) -> KrbError {
+        KrbError {
+            pvno: 5.into(),
+            msg_type: 30.into(),
+            ctime: None,
+            cusec: None,
+            stime: self.now(),
+            susec: self.now.0.timestamp_subsec_micros().into(),
+            error_code: failure.code,
+            crealm: body
+                .filter(|body| body.cname.is_some())
+                .map(|body| body.realm.clone()),
+            cname: body.and_then(|body| body.cname.clone()),
+            realm: self.realm.clone(),
+            sname: body
+                .and_then(|body| body.sname.clone())
+                .unwrap_or_else(|| self.krbtgt()),
+            e_text: None,
+            e_data: failure.e_data.map(Into::into),
+        }
+    }
+
+    /// Returns [Kdc::now] without its fractional seconds, which
+    /// [KerberosTime]s can't have.
+    fn now(&self) -> KerberosTime {
+        let now = self.now.0;
+        KerberosTime(now - TimeDelta::nanoseconds(now.timestamp_subsec_nanos().into()))
+    }
+
+    fn error_message(&self, code: i32) -> KdcMessage {
+        KdcMessage::KrbError(self.error(code.into(), None))
+    }
+
+    fn krbtgt(&self) -> PrincipalName {
+        PrincipalName {
+            r#type: 2,
+            string: vec![
+                KerberosString::from_bytes(b"krbtgt").expect("valid string"),
+                self.realm.clone(),
+            ],
+        }
+    }
+
+    fn find(&self, name: &PrincipalName) -> Option<&Entry> {
+        self.principals
+            .iter()
+            .find(|entry| entry.name.string == name.string)
+    }
+
+    fn find_mut(&mut self, name: &PrincipalName) -> Option<&mut Entry> {
+        self.principals
+            .iter_mut()
+            .find(|entry| entry.name.string == name.string)
+    }
+
+    fn random_key(&mut self, etype: i32) -> EncryptionKey {
+        let enctype = Enctype::from_etype(etype).expect("supported encryption type");
+        let mut value = vec![0; enctype.key_length()];
+        self.random(&mut value);
+        EncryptionKey {
+            r#type: etype,
+            value: value.into(),
+        }
+    }
+
+    fn confounder(&mut self, key: &EncryptionKey) -> Vec<u8> {
+        let length = key
+            .enctype()
+            .map(Enctype::confounder_length)
+            .unwrap_or_default();
+        let mut confounder = vec![0; length];
+        self.random(&mut confounder);
+        confounder
+    }
+
+    /// Fills `output` with bytes from a hash of a counter, so the KDC's
+    /// replies only depend on its requests.
+    fn random(&mut self, output: &mut [u8]) {
+        for chunk in output.chunks_mut(32) {
+            self.counter += 1;
+            let block = Sha256::new()
+                .chain_update(b"rasn-kerberos test KDC")
+                .chain_update(self.counter.to_be_bytes())
+                .finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! The MIT keytab file format, version `0x502`, which stores the long-term
+//! keys of one or more principals.
+//!
+//! ```
+//! use rasn_kerberos::keytab::Keytab;
+//!
+//! let keytab = Keytab::parse(include_bytes!("../tests/data/krb5.keytab")).unwrap();
+//! for entry in &keytab.entries {
+//!     println!("{} kvno {} etype {}", entry.principal, entry.kvno, entry.key.r#type);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::file::{put_data16, put_i16, put_time, string, Reader};
+use crate::{EncryptionKey, KerberosTime, PrincipalName};
+
+pub use crate::file::{FileError, Principal};
+
+/// A keytab file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keytab {
+    pub entries: Vec<KeytabEntry>,
+}
+
+/// A single key in a [Keytab].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeytabEntry {
+    /// The principal the key belongs to.
+    pub principal: Principal,
+    /// When the key was added to the keytab.
+    pub timestamp: KerberosTime,
+    /// The key version number.
+    pub kvno: u32,
+    /// The key itself.
+    pub key: EncryptionKey,
+}
+
+impl Keytab {
+    /// The file format version supported by this module.
+    pub const VERSION: u16 = 0x0502;
+
+    /// Parses a keytab file. Deleted entries, which are left in the file as
+    /// holes, are skipped.
+    pub fn parse(input: &[u8]) -> Result<Self, FileError> {
+        let mut reader = Reader::new(input);
+        let version = reader.u16()?;
+        if version != Self::VERSION {
+            return Err(FileError::UnsupportedVersion(version));
+        }
+
+        let mut entries = Vec::new();
+        while !reader.is_empty() {
+            let size = reader.i32()?;
+            let record = reader.bytes(size.unsigned_abs() as usize)?;
+            if size > 0 {
+                entries.push(KeytabEntry::parse(record)?);
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Serialises the keytab, without any holes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, FileError> {
+        let mut output = Self::VERSION.to_be_bytes().to_vec();
+        for entry in &self.entries {
+            let record = entry.to_bytes()?;
+            let size = i32::try_from(record.len()).map_err(|_| FileError::OutOfRange)?;
+            output.extend_from_slice(&size.to_be_bytes());
+            output.extend_from_slice(&record);
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the entry for `principal` with the encryption type `etype`,
+    /// either with the key version number `kvno` or, when `kvno` is `None`,
+    /// with the highest one.
+    pub fn find(
+        &self,
+        principal: &Principal,
+        kvno: Option<u32>,
+        etype: i32,
+    ) -> Option<&KeytabEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.principal.matches(principal) && entry.key.r#type == etype)
+            .filter(|entry| kvno.is_none_or(|kvno| entry.kvno == kvno))
+            .max_by_key(|entry| entry.kvno)
+    }
+}
+
+impl KeytabEntry {
+    fn parse(record: &[u8]) -> Result<Self, FileError> {
+        let mut reader = Reader::new(record);
+        let count = reader.u16()?;
+        let realm = string(reader.data16()?)?;
+        let components = (0..count)
+            .map(|_| string(reader.data16()?))
+            .collect::<Result<_, _>>()?;
+        let r#type = reader.u32()? as i32;
+        let timestamp = reader.time()?;
+        let kvno = reader.u8()?;
+        let key = EncryptionKey {
+            r#type: reader.i16()?,
+            value: reader.data16()?.to_vec().into(),
+        };
+
+        // The 32 bit key version number is a later addition, and takes
+        // precedence over the 8 bit one when present and non-zero.
+        let kvno = match reader.remaining() >= 4 {
+            true => Some(reader.u32()?).filter(|kvno| *kvno != 0),
+            false => None,
+        }
+        .unwrap_or(kvno.into());
+
+        Ok(Self {
+            principal: Principal::new(
+                realm,
+                PrincipalName {
+                    r#type,
+                    string: components,
+                },
+            ),
+            timestamp,
+            kvno,
+            key,
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, FileError> {
+        let mut output = Vec::new();
+        let count =
+            u16::try_from(self.principal.name.string.len()).map_err(|_| FileError::OutOfRange)?;
+        output.extend_from_slice(&count.to_be_bytes());
+        put_data16(&mut output, &self.principal.realm)?;
+        for component in &self.principal.name.string {
+            put_data16(&mut output, component)?;
+        }
+        output.extend_from_slice(&self.principal.name.r#type.to_be_bytes());
+        put_time(&mut output, &self.timestamp)?;
+        output.push(self.kvno as u8);
+        put_i16(&mut output, self.key.r#type)?;
+        put_data16(&mut output, &self.key.value)?;
+        output.extend_from_slice(&self.kvno.to_be_bytes());
+        Ok(output)
+    }
+}
@@ -1,8 +1,24 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+extern crate alloc;
+
+pub mod ccache;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+mod file;
+#[cfg(feature = "gssapi")]
+pub mod gssapi;
+#[cfg(feature = "kdc")]
+pub mod kdc;
+pub mod keytab;
 #[cfg(feature = "otp")]
 pub mod otp;
+pub mod pac;
+#[cfg(feature = "pkinit")]
+pub mod pkinit;
+pub mod transport;
+pub mod typed;
 
 use rasn::prelude::*;
 
@@ -19,6 +35,7 @@ pub type AdIfRelevant = AuthorizationData;
 pub type EtypeInfo2 = SequenceOf<EtypeInfo2Entry>;
 pub type EtypeInfo = SequenceOf<EtypeInfoEntry>;
 pub type PaEncTimestamp = EncryptedData;
+pub type PaEncryptedChallenge = EncryptedData;
 pub type LastReq = SequenceOf<LastReqValue>;
 pub type MethodData = SequenceOf<PaData>;
 pub type KerberosString = GeneralString;
@@ -113,6 +130,16 @@ impl AuthorizationDataValue {
     pub const AND_OR: i32 = 5;
     /// DER encoding of [AdMandatoryForKdc].
     pub const MANDATORY_FOR_KDC: i32 = 8;
+    /// DER encoding of a `pkinit::AdInitialVerifiedCas`.
+    pub const INITIAL_VERIFIED_CAS: i32 = 9;
+    /// DER encoding of [AdLoginAlias].
+    pub const LOGIN_ALIAS: i32 = 80;
+    /// DER encoding of [AdCammac].
+    pub const CAMMAC: i32 = 96;
+    /// DER encoding of an `otp::AdAuthenticationIndicator`.
+    pub const AUTHENTICATION_INDICATOR: i32 = 97;
+    /// A Microsoft [pac::Pac], which isn't DER encoded.
+    pub const WIN2K_PAC: i32 = 128;
 }
 
 /// Pre-Authenication data.
@@ -124,6 +151,31 @@ pub struct PaData {
     pub value: OctetString,
 }
 
+impl PaData {
+    /// DER encoding of the [ApReq] authenticating a [TgsReq].
+    pub const TGS_REQ: i32 = 1;
+    /// DER encoding of a [PaEncTimestamp].
+    pub const ENC_TIMESTAMP: i32 = 2;
+    /// DER encoding of [EtypeInfo].
+    pub const ETYPE_INFO: i32 = 11;
+    /// DER encoding of a `pkinit::PaPkAsReq`.
+    pub const PK_AS_REQ: i32 = 16;
+    /// DER encoding of a `pkinit::PaPkAsRep`.
+    pub const PK_AS_REP: i32 = 17;
+    /// DER encoding of [EtypeInfo2].
+    pub const ETYPE_INFO2: i32 = 19;
+    /// DER encoding of a [PaPacRequest].
+    pub const PAC_REQUEST: i32 = 128;
+    /// DER encoding of a [PaEncryptedChallenge].
+    pub const ENCRYPTED_CHALLENGE: i32 = 138;
+    /// DER encoding of an `otp::PaOtpChallenge`.
+    pub const OTP_CHALLENGE: i32 = 141;
+    /// DER encoding of an `otp::PaOtpRequest`.
+    pub const OTP_REQUEST: i32 = 142;
+    /// DER encoding of an `otp::PaOtpPinChange`.
+    pub const OTP_PIN_CHANGE: i32 = 144;
+}
+
 // KerberosFlags   ::= BIT STRING (SIZE (32..MAX))
 pub type KerberosFlags = BitString;
 
@@ -1153,6 +1205,82 @@ pub struct KrbError {
     pub e_data: Option<OctetString>,
 }
 
+impl KrbError {
+    pub const KDC_ERR_NONE: i32 = 0;
+    pub const KDC_ERR_NAME_EXP: i32 = 1;
+    pub const KDC_ERR_SERVICE_EXP: i32 = 2;
+    pub const KDC_ERR_BAD_PVNO: i32 = 3;
+    pub const KDC_ERR_C_OLD_MAST_KVNO: i32 = 4;
+    pub const KDC_ERR_S_OLD_MAST_KVNO: i32 = 5;
+    pub const KDC_ERR_C_PRINCIPAL_UNKNOWN: i32 = 6;
+    pub const KDC_ERR_S_PRINCIPAL_UNKNOWN: i32 = 7;
+    pub const KDC_ERR_PRINCIPAL_NOT_UNIQUE: i32 = 8;
+    pub const KDC_ERR_NULL_KEY: i32 = 9;
+    pub const KDC_ERR_CANNOT_POSTDATE: i32 = 10;
+    pub const KDC_ERR_NEVER_VALID: i32 = 11;
+    pub const KDC_ERR_POLICY: i32 = 12;
+    pub const KDC_ERR_BADOPTION: i32 = 13;
+    pub const KDC_ERR_ETYPE_NOSUPP: i32 = 14;
+    pub const KDC_ERR_SUMTYPE_NOSUPP: i32 = 15;
+    pub const KDC_ERR_PADATA_TYPE_NOSUPP: i32 = 16;
+    pub const KDC_ERR_TRTYPE_NOSUPP: i32 = 17;
+    pub const KDC_ERR_CLIENT_REVOKED: i32 = 18;
+    pub const KDC_ERR_SERVICE_REVOKED: i32 = 19;
+    pub const KDC_ERR_TGT_REVOKED: i32 = 20;
+    pub const KDC_ERR_CLIENT_NOTYET: i32 = 21;
+    pub const KDC_ERR_SERVICE_NOTYET: i32 = 22;
+    pub const KDC_ERR_KEY_EXPIRED: i32 = 23;
+    pub const KDC_ERR_PREAUTH_FAILED: i32 = 24;
+    pub const KDC_ERR_PREAUTH_REQUIRED: i32 = 25;
+    pub const KDC_ERR_SERVER_NOMATCH: i32 = 26;
+    pub const KDC_ERR_MUST_USE_USER2USER: i32 = 27;
+    pub const KDC_ERR_PATH_NOT_ACCEPTED: i32 = 28;
+    pub const KDC_ERR_SVC_UNAVAILABLE: i32 = 29;
+    pub const KRB_AP_ERR_BAD_INTEGRITY: i32 = 31;
+    pub const KRB_AP_ERR_TKT_EXPIRED: i32 = 32;
+    pub const KRB_AP_ERR_TKT_NYV: i32 = 33;
+    pub const KRB_AP_ERR_REPEAT: i32 = 34;
+    pub const KRB_AP_ERR_NOT_US: i32 = 35;
+    pub const KRB_AP_ERR_BADMATCH: i32 = 36;
+    pub const KRB_AP_ERR_SKEW: i32 = 37;
+    pub const KRB_AP_ERR_BADADDR: i32 = 38;
+    pub const KRB_AP_ERR_BADVERSION: i32 = 39;
+    pub const KRB_AP_ERR_MSG_TYPE: i32 = 40;
+    pub const KRB_AP_ERR_MODIFIED: i32 = 41;
+    pub const KRB_AP_ERR_BADORDER: i32 = 42;
+    pub const KRB_AP_ERR_BADKEYVER: i32 = 44;
+    pub const KRB_AP_ERR_NOKEY: i32 = 45;
+    pub const KRB_AP_ERR_MUT_FAIL: i32 = 46;
+    pub const KRB_AP_ERR_BADDIRECTION: i32 = 47;
+    pub const KRB_AP_ERR_METHOD: i32 = 48;
+    pub const KRB_AP_ERR_BADSEQ: i32 = 49;
+    pub const KRB_AP_ERR_INAPP_CKSUM: i32 = 50;
+    pub const KRB_AP_PATH_NOT_ACCEPTED: i32 = 51;
+    /// The reply was too big for UDP, and the request should be retried over
+    /// TCP.
+    pub const KRB_ERR_RESPONSE_TOO_BIG: i32 = 52;
+    pub const KRB_ERR_GENERIC: i32 = 60;
+    /// The length of a TCP request had its reserved high bit set.
+    pub const KRB_ERR_FIELD_TOOLONG: i32 = 61;
+}
+
+/// Any top-level message exchanged with a KDC or application server,
+/// distinguished by its application tag.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[rasn(choice)]
+pub enum KdcMessage {
+    AsReq(AsReq),
+    AsRep(AsRep),
+    TgsReq(TgsReq),
+    TgsRep(TgsRep),
+    ApReq(ApReq),
+    ApRep(ApRep),
+    KrbSafe(KrbSafe),
+    KrbPriv(KrbPriv),
+    KrbCred(KrbCred),
+    KrbError(KrbError),
+}
+
 pub type TypedData = SequenceOf<TypedDataItem>;
 
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1171,6 +1299,16 @@ pub struct PaEncTsEnc {
     pub pausec: Option<Microseconds>,
 }
 
+/// Asks the KDC to include a [pac::Pac] in the ticket or leave it out, from
+/// [MS-KILE].
+///
+/// [MS-KILE]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-kile/
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PaPacRequest {
+    #[rasn(tag(explicit(0)))]
+    pub include_pac: bool,
+}
+
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct EtypeInfoEntry {
     #[rasn(tag(explicit(0)))]
@@ -0,0 +1,585 @@
+//! # Privilege Attribute Certificate
+//!
+//! The authorization data Microsoft's KDCs put in tickets, from [MS-PAC]. A
+//! PAC is carried as [`AuthorizationDataValue::WIN2K_PAC`], usually inside
+//! [`AuthorizationDataValue::IF_RELEVANT`], and is a little-endian container
+//! of typed buffers rather than ASN.1.
+//!
+//! [Pac] reads and writes the container, and with the `crypto` feature can
+//! sign and verify it with the server and KDC keys. A few of the simpler buffers can be decoded; the
+//! others, such as the NDR encoded [`PacBuffer::LOGON_INFO`], are left as
+//! bytes.
+//!
+//! [MS-PAC]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-pac/
+
+use alloc::{string::String, vec, vec::Vec};
+
+use chrono::{DateTime, Utc};
+use rasn::error::DecodeError;
+
+#[cfg(feature = "crypto")]
+use crate::{
+    crypto::{key_usage, CryptoError},
+    Checksum, EncryptionKey,
+};
+use crate::{AdIfRelevant, AuthorizationData, AuthorizationDataValue};
+
+/// The number of seconds from the start of 1601, the FILETIME epoch, to the
+/// Unix epoch.
+const FILETIME_UNIX_EPOCH: i64 = 11_644_473_600;
+
+/// An error returned when reading, signing or verifying a [Pac].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PacError {
+    /// The PAC or one of its buffers is truncated or inconsistent.
+    Malformed,
+    /// The PAC has no buffer of the type.
+    MissingBuffer(u32),
+    /// The authorization data holding the PAC couldn't be decoded.
+    Decode(DecodeError),
+    /// A signature couldn't be computed or didn't match.
+    #[cfg(feature = "crypto")]
+    Crypto(CryptoError),
+}
+
+impl core::fmt::Display for PacError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed PAC"),
+            Self::MissingBuffer(r#type) => write!(f, "PAC has no buffer of type {type}"),
+            Self::Decode(error) => write!(f, "failed to decode authorization data: {error}"),
+            #[cfg(feature = "crypto")]
+            Self::Crypto(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<DecodeError> for PacError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl From<CryptoError> for PacError {
+    fn from(error: CryptoError) -> Self {
+        Self::Crypto(error)
+    }
+}
+
+/// A cursor over little-endian fields.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], PacError> {
+        if length > self.0.len() {
+            return Err(PacError::Malformed);
+        }
+        let (bytes, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, PacError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, PacError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, PacError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a 16 bit length and offset, returning the bytes they point to
+    /// in `base`.
+    fn pointer(&mut self, base: &'a [u8]) -> Result<&'a [u8], PacError> {
+        let length = self.u16()? as usize;
+        let offset = self.u16()? as usize;
+        slice(base, offset, length)
+    }
+}
+
+fn align8(offset: usize) -> usize {
+    offset.next_multiple_of(8)
+}
+
+/// Returns the `length` bytes at `offset` in `input`.
+fn slice(input: &[u8], offset: usize, length: usize) -> Result<&[u8], PacError> {
+    offset
+        .checked_add(length)
+        .and_then(|end| input.get(offset..end))
+        .ok_or(PacError::Malformed)
+}
+
+fn utf16(input: &[u8]) -> Result<String, PacError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(PacError::Malformed);
+    }
+    char::decode_utf16(
+        input
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|unit| u16::from_le_bytes(*unit)),
+    )
+    .collect::<Result<_, _>>()
+    .map_err(|_| PacError::Malformed)
+}
+
+fn to_utf16(value: &str) -> Vec<u8> {
+    value.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// A Privilege Attribute Certificate: a version and a list of typed buffers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pac {
+    pub version: u32,
+    pub buffers: Vec<PacBuffer>,
+}
+
+/// A single buffer in a [Pac].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PacBuffer {
+    pub r#type: u32,
+    pub data: Vec<u8>,
+}
+
+impl PacBuffer {
+    /// NDR encoded `KERB_VALIDATION_INFO`.
+    pub const LOGON_INFO: u32 = 1;
+    /// Encrypted supplemental credentials, after PKINIT.
+    pub const CREDENTIALS_INFO: u32 = 2;
+    /// The server signature, a [PacSignature].
+    pub const SERVER_CHECKSUM: u32 = 6;
+    /// The KDC signature, a [PacSignature].
+    pub const PRIVSVR_CHECKSUM: u32 = 7;
+    /// A [PacClientInfo].
+    pub const CLIENT_INFO: u32 = 10;
+    /// NDR encoded `S4U_DELEGATION_INFO`.
+    pub const CONSTRAINED_DELEGATION: u32 = 11;
+    /// An [UpnDnsInfo].
+    pub const UPN_DNS_INFO: u32 = 12;
+    /// Client claims.
+    pub const CLIENT_CLAIMS: u32 = 13;
+    /// NDR encoded `PAC_DEVICE_INFO`.
+    pub const DEVICE_INFO: u32 = 14;
+    /// Device claims.
+    pub const DEVICE_CLAIMS: u32 = 15;
+    /// The ticket signature, a [PacSignature].
+    pub const TICKET_CHECKSUM: u32 = 16;
+    /// `PAC_ATTRIBUTES_INFO` flags.
+    pub const ATTRIBUTES_INFO: u32 = 17;
+    /// The [Sid] of the client the ticket was requested for.
+    pub const REQUESTOR: u32 = 18;
+    /// The full PAC signature, a [PacSignature].
+    pub const FULL_CHECKSUM: u32 = 19;
+}
+
+impl Pac {
+    pub fn new(buffers: Vec<PacBuffer>) -> Self {
+        Self {
+            version: 0,
+            buffers,
+        }
+    }
+
+    pub fn parse(input: &[u8]) -> Result<Self, PacError> {
+        let mut reader = Reader(input);
+        let count = reader.u32()?;
+        let version = reader.u32()?;
+
+        let buffers = (0..count)
+            .map(|_| {
+                let r#type = reader.u32()?;
+                let length = reader.u32()? as usize;
+                let offset = usize::try_from(reader.u64()?).map_err(|_| PacError::Malformed)?;
+
+                Ok(PacBuffer {
+                    r#type,
+                    data: slice(input, offset, length)?.to_vec(),
+                })
+            })
+            .collect::<Result<_, PacError>>()?;
+
+        Ok(Self { version, buffers })
+    }
+
+    /// Encodes the PAC, placing each buffer at the next multiple of eight
+    /// bytes after the buffer before it, as Windows and MIT krb5 do.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend((self.buffers.len() as u32).to_le_bytes());
+        header.extend(self.version.to_le_bytes());
+
+        let mut data = Vec::new();
+        let start = 8 + 16 * self.buffers.len();
+        for buffer in &self.buffers {
+            let offset = align8(start + data.len());
+            data.resize(offset - start, 0);
+            header.extend(buffer.r#type.to_le_bytes());
+            header.extend((buffer.data.len() as u32).to_le_bytes());
+            header.extend((offset as u64).to_le_bytes());
+            data.extend(&buffer.data);
+        }
+
+        [header, data].concat()
+    }
+
+    /// Finds the PAC in `authorization_data`, either at the top level or
+    /// inside [`AuthorizationDataValue::IF_RELEVANT`] elements.
+    pub fn find(authorization_data: &AuthorizationData) -> Option<Result<Self, PacError>> {
+        for element in authorization_data {
+            match element.r#type {
+                AuthorizationDataValue::WIN2K_PAC => return Some(Self::parse(&element.data)),
+                AuthorizationDataValue::IF_RELEVANT => {
+                    let inner = match rasn::der::decode::<AdIfRelevant>(&element.data) {
+                        Ok(inner) => inner,
+                        Err(error) => return Some(Err(error.into())),
+                    };
+                    if let Some(pac) = Self::find(&inner) {
+                        return Some(pac);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Returns the data of the first buffer of `type`.
+    pub fn buffer(&self, r#type: u32) -> Option<&[u8]> {
+        self.buffers
+            .iter()
+            .find(|buffer| buffer.r#type == r#type)
+            .map(|buffer| &buffer.data[..])
+    }
+
+    fn required(&self, r#type: u32) -> Result<&[u8], PacError> {
+        self.buffer(r#type).ok_or(PacError::MissingBuffer(r#type))
+    }
+
+    pub fn server_signature(&self) -> Result<PacSignature, PacError> {
+        PacSignature::parse(self.required(PacBuffer::SERVER_CHECKSUM)?)
+    }
+
+    pub fn kdc_signature(&self) -> Result<PacSignature, PacError> {
+        PacSignature::parse(self.required(PacBuffer::PRIVSVR_CHECKSUM)?)
+    }
+
+    pub fn client_info(&self) -> Result<PacClientInfo, PacError> {
+        PacClientInfo::parse(self.required(PacBuffer::CLIENT_INFO)?)
+    }
+
+    pub fn upn_dns_info(&self) -> Result<UpnDnsInfo, PacError> {
+        UpnDnsInfo::parse(self.required(PacBuffer::UPN_DNS_INFO)?)
+    }
+
+    pub fn requestor(&self) -> Result<Sid, PacError> {
+        Sid::parse(self.required(PacBuffer::REQUESTOR)?)
+    }
+
+    /// Signs the PAC with the service's key and the KDC's key, adding the
+    /// signature buffers if they're missing.
+    #[cfg(feature = "crypto")]
+    pub fn sign(
+        &mut self,
+        server_key: &EncryptionKey,
+        kdc_key: &EncryptionKey,
+    ) -> Result<(), PacError> {
+        for (r#type, key) in [
+            (PacBuffer::SERVER_CHECKSUM, server_key),
+            (PacBuffer::PRIVSVR_CHECKSUM, kdc_key),
+        ] {
+            let checksum_type = key.enctype()?.checksum_type();
+            let length = PacSignature::length(checksum_type)
+                .ok_or(CryptoError::UnsupportedChecksumType(checksum_type))?;
+            let data = PacSignature::new(checksum_type, vec![0; length]).to_bytes();
+            match self
+                .buffers
+                .iter_mut()
+                .find(|buffer| buffer.r#type == r#type)
+            {
+                Some(buffer) => buffer.data = data,
+                None => self.buffers.push(PacBuffer { r#type, data }),
+            }
+        }
+
+        let server = Checksum::new(server_key, key_usage::PAC_CHECKSUM, &self.zeroed()?)?;
+        self.set_signature(PacBuffer::SERVER_CHECKSUM, &server.checksum)?;
+        let kdc = Checksum::new(kdc_key, key_usage::PAC_CHECKSUM, &server.checksum)?;
+        self.set_signature(PacBuffer::PRIVSVR_CHECKSUM, &kdc.checksum)
+    }
+
+    /// Checks the server signature, which covers the whole PAC, with the
+    /// service's key.
+    #[cfg(feature = "crypto")]
+    pub fn verify(&self, server_key: &EncryptionKey) -> Result<(), PacError> {
+        self.server_signature()?.verify(server_key, &self.zeroed()?)
+    }
+
+    /// Checks the KDC signature, which covers the server signature, with the
+    /// KDC's key.
+    #[cfg(feature = "crypto")]
+    pub fn verify_kdc(&self, kdc_key: &EncryptionKey) -> Result<(), PacError> {
+        self.kdc_signature()?
+            .verify(kdc_key, &self.server_signature()?.signature)
+    }
+
+    /// Encodes the PAC with the server and KDC signatures zeroed, as they
+    /// are when the server signature is computed.
+    #[cfg(feature = "crypto")]
+    fn zeroed(&self) -> Result<Vec<u8>, PacError> {
+        let mut pac = self.clone();
+        for r#type in [PacBuffer::SERVER_CHECKSUM, PacBuffer::PRIVSVR_CHECKSUM] {
+            pac.set_signature(r#type, &vec![0; self.signature_length(r#type)?])?;
+        }
+
+        Ok(pac.to_bytes())
+    }
+
+    #[cfg(feature = "crypto")]
+    fn signature_length(&self, r#type: u32) -> Result<usize, PacError> {
+        Ok(PacSignature::parse(self.required(r#type)?)?.signature.len())
+    }
+
+    #[cfg(feature = "crypto")]
+    fn set_signature(&mut self, r#type: u32, signature: &[u8]) -> Result<(), PacError> {
+        let buffer = self
+            .buffers
+            .iter_mut()
+            .find(|buffer| buffer.r#type == r#type)
+            .ok_or(PacError::MissingBuffer(r#type))?;
+        let mut value = PacSignature::parse(&buffer.data)?;
+        value.signature = signature.to_vec();
+        buffer.data = value.to_bytes();
+
+        Ok(())
+    }
+}
+
+/// The content of the signature buffers: a checksum and, for signatures
+/// made by read-only domain controllers, the key's identifier.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PacSignature {
+    pub r#type: i32,
+    pub signature: Vec<u8>,
+    pub rodc_identifier: Option<u16>,
+}
+
+impl PacSignature {
+    pub fn new(r#type: i32, signature: Vec<u8>) -> Self {
+        Self {
+            r#type,
+            signature,
+            rodc_identifier: None,
+        }
+    }
+
+    /// The length of the signature made with checksum `type`.
+    fn length(r#type: i32) -> Option<usize> {
+        match r#type {
+            // hmac-sha1-96-aes128 and hmac-sha1-96-aes256
+            15 | 16 => Some(12),
+            // hmac-sha256-128-aes128 and hmac-md5
+            19 | -138 => Some(16),
+            // hmac-sha384-192-aes256
+            20 => Some(24),
+            _ => None,
+        }
+    }
+
+    pub fn parse(input: &[u8]) -> Result<Self, PacError> {
+        let mut reader = Reader(input);
+        let r#type = reader.u32()? as i32;
+        let length = Self::length(r#type).unwrap_or(reader.0.len());
+        let signature = reader.bytes(length)?.to_vec();
+        let rodc_identifier = match reader.0.len() {
+            0 => None,
+            _ => Some(reader.u16()?),
+        };
+
+        Ok(Self {
+            r#type,
+            signature,
+            rodc_identifier,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.r#type.to_le_bytes().to_vec();
+        bytes.extend(&self.signature);
+        if let Some(rodc_identifier) = self.rodc_identifier {
+            bytes.extend(rodc_identifier.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Checks the signature over `data` with `key`.
+    #[cfg(feature = "crypto")]
+    pub fn verify(&self, key: &EncryptionKey, data: &[u8]) -> Result<(), PacError> {
+        let checksum = Checksum {
+            r#type: self.r#type,
+            checksum: self.signature.clone().into(),
+        };
+
+        Ok(checksum.verify(key, key_usage::PAC_CHECKSUM, data)?)
+    }
+}
+
+/// The client's name and the ticket's authentication time, which a server
+/// compares with the ticket's.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PacClientInfo {
+    pub client_id: DateTime<Utc>,
+    pub name: String,
+}
+
+impl PacClientInfo {
+    pub fn parse(input: &[u8]) -> Result<Self, PacError> {
+        let mut reader = Reader(input);
+        let ticks = i64::try_from(reader.u64()?).map_err(|_| PacError::Malformed)?;
+        let length = reader.u16()? as usize;
+        let name = utf16(reader.bytes(length)?)?;
+        let client_id = DateTime::from_timestamp(
+            ticks.div_euclid(10_000_000) - FILETIME_UNIX_EPOCH,
+            (ticks.rem_euclid(10_000_000) * 100) as u32,
+        )
+        .ok_or(PacError::Malformed)?;
+
+        Ok(Self { client_id, name })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let ticks = (self.client_id.timestamp() + FILETIME_UNIX_EPOCH) * 10_000_000
+            + i64::from(self.client_id.timestamp_subsec_nanos() / 100);
+        let name = to_utf16(&self.name);
+
+        let mut bytes = ticks.to_le_bytes().to_vec();
+        bytes.extend((name.len() as u16).to_le_bytes());
+        bytes.extend(name);
+        bytes
+    }
+}
+
+/// The client's user principal name and DNS domain, and with
+/// [`UpnDnsInfo::EXTENDED`], its SAM account name and SID.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UpnDnsInfo {
+    pub upn: String,
+    pub dns_domain_name: String,
+    pub flags: u32,
+    pub sam_name: Option<String>,
+    pub sid: Option<Sid>,
+}
+
+impl UpnDnsInfo {
+    /// The client has no UPN, and `upn` was constructed from its name.
+    pub const UPN_CONSTRUCTED: u32 = 1;
+    /// The SAM account name and SID are present.
+    pub const EXTENDED: u32 = 2;
+
+    pub fn parse(input: &[u8]) -> Result<Self, PacError> {
+        let mut reader = Reader(input);
+        let upn = utf16(reader.pointer(input)?)?;
+        let dns_domain_name = utf16(reader.pointer(input)?)?;
+        let flags = reader.u32()?;
+
+        let (sam_name, sid) = match flags & Self::EXTENDED {
+            0 => (None, None),
+            _ => (
+                Some(utf16(reader.pointer(input)?)?),
+                Some(Sid::parse(reader.pointer(input)?)?),
+            ),
+        };
+
+        Ok(Self {
+            upn,
+            dns_domain_name,
+            flags,
+            sam_name,
+            sid,
+        })
+    }
+
+    /// Encodes the info, placing each field at a multiple of eight bytes as
+    /// Windows does.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut fields = vec![to_utf16(&self.upn), to_utf16(&self.dns_domain_name)];
+        let extended = self.flags & Self::EXTENDED != 0;
+        if extended {
+            fields.push(to_utf16(self.sam_name.as_deref().unwrap_or_default()));
+            fields.push(self.sid.as_ref().map(Sid::to_bytes).unwrap_or_default());
+        }
+
+        let header_length = if extended { 20 } else { 12 };
+        let mut header = Vec::new();
+        let mut data = Vec::new();
+        let start = align8(header_length);
+        for (i, field) in fields.iter().enumerate() {
+            if i == 2 {
+                header.extend(self.flags.to_le_bytes());
+            }
+            let offset = align8(start + data.len());
+            data.resize(offset - start, 0);
+            header.extend((field.len() as u16).to_le_bytes());
+            header.extend((offset as u16).to_le_bytes());
+            data.extend(field);
+        }
+        if !extended {
+            header.extend(self.flags.to_le_bytes());
+        }
+        header.resize(start, 0);
+
+        [header, data].concat()
+    }
+}
+
+/// A Windows security identifier, such as `S-1-5-21-1-2-3-1104`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sid {
+    pub revision: u8,
+    /// The 48 bit identifier authority.
+    pub identifier_authority: u64,
+    pub sub_authorities: Vec<u32>,
+}
+
+impl Sid {
+    pub fn parse(input: &[u8]) -> Result<Self, PacError> {
+        let mut reader = Reader(input);
+        let header = reader.bytes(8)?;
+        let mut authority = [0; 8];
+        authority[2..].copy_from_slice(&header[2..]);
+        let sub_authorities = (0..header[1])
+            .map(|_| reader.u32())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            revision: header[0],
+            identifier_authority: u64::from_be_bytes(authority),
+            sub_authorities,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.revision, self.sub_authorities.len() as u8];
+        bytes.extend(&self.identifier_authority.to_be_bytes()[2..]);
+        for sub_authority in &self.sub_authorities {
+            bytes.extend(sub_authority.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl core::fmt::Display for Sid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "S-{}-{}", self.revision, self.identifier_authority)?;
+        for sub_authority in &self.sub_authorities {
+            write!(f, "-{sub_authority}")?;
+        }
+        Ok(())
+    }
+}
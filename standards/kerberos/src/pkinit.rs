@@ -0,0 +1,453 @@
+//! # Public Key Cryptography for Initial Authentication
+//!
+//! The pre-authentication data from [RFC 4556], with the key derivation
+//! function negotiation from [RFC 8636]. The signed and enveloped parts are
+//! DER encoded CMS structures from [`rasn_cms`], which can be built with its
+//! [`SignedDataBuilder`](rasn_cms::signed::SignedDataBuilder) and
+//! [`EnvelopedDataBuilder`](rasn_cms::enveloped::EnvelopedDataBuilder) from
+//! the DER encoding of an [AuthPack], [KdcDhKeyInfo] or [ReplyKeyPack], with
+//! the matching content type below.
+//!
+//! [RFC 4556]: https://datatracker.ietf.org/doc/html/rfc4556
+//! [RFC 8636]: https://datatracker.ietf.org/doc/html/rfc8636
+use alloc::vec::Vec;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    prelude::*,
+};
+use rasn_cms::{ContentInfo, EnvelopedData, SignedData};
+use rasn_pkix::{AlgorithmIdentifier, SubjectPublicKeyInfo};
+
+use super::{
+    crypto::{key_usage, CryptoError},
+    Checksum, EncryptionKey, KdcReqBody, KerberosTime, Microseconds, PrincipalName, Realm,
+};
+
+/// The `otherName` type of a subject alternative name holding a
+/// [Krb5PrincipalName].
+pub const SAN: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_SAN;
+/// The content type of a signed [AuthPack].
+pub const AUTH_DATA: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_AUTH_DATA;
+/// The content type of a signed [KdcDhKeyInfo].
+pub const DH_KEY_DATA: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_DH_KEY_DATA;
+/// The content type of a signed [ReplyKeyPack].
+pub const RKEY_DATA: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_RKEY_DATA;
+/// The extended key usage of client certificates.
+pub const KP_CLIENT_AUTH: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KP_CLIENT_AUTH;
+/// The extended key usage of KDC certificates.
+pub const KP_KDC: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KP_KDC;
+/// The key derivation function based on SHA-1.
+pub const KDF_SHA1: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA1;
+/// The key derivation function based on SHA-256.
+pub const KDF_SHA256: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA256;
+/// The key derivation function based on SHA-384.
+pub const KDF_SHA384: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA384;
+/// The key derivation function based on SHA-512.
+pub const KDF_SHA512: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_KERBEROS_V5_PKINIT_KDF_AH_SHA512;
+
+/// The typed data type of [TdTrustedCertifiers].
+pub const TD_TRUSTED_CERTIFIERS: i32 = 104;
+/// The typed data type of [TdInvalidCertificates].
+pub const TD_INVALID_CERTIFICATES: i32 = 105;
+/// The typed data type of [TdDhParameters].
+pub const TD_DH_PARAMETERS: i32 = 109;
+
+pub type DhNonce = OctetString;
+pub type TdTrustedCertifiers = SequenceOf<ExternalPrincipalIdentifier>;
+pub type TdInvalidCertificates = SequenceOf<ExternalPrincipalIdentifier>;
+pub type TdDhParameters = SequenceOf<AlgorithmIdentifier>;
+pub type AdInitialVerifiedCas = SequenceOf<ExternalPrincipalIdentifier>;
+
+/// An error returned when reading the CMS structures in PKINIT messages.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PkinitError {
+    /// The CMS structure has a different content type than expected.
+    UnexpectedContentType(ObjectIdentifier),
+    /// The signed data doesn't encapsulate its content.
+    MissingContent,
+    /// The CMS structure or its content couldn't be decoded.
+    Decode(DecodeError),
+    /// A checksum couldn't be computed or didn't match.
+    Crypto(CryptoError),
+}
+
+impl core::fmt::Display for PkinitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedContentType(oid) => write!(f, "unexpected content type {oid:?}"),
+            Self::MissingContent => write!(f, "signed data has no encapsulated content"),
+            Self::Decode(error) => write!(f, "failed to decode: {error}"),
+            Self::Crypto(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<DecodeError> for PkinitError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl From<CryptoError> for PkinitError {
+    fn from(error: CryptoError) -> Self {
+        Self::Crypto(error)
+    }
+}
+
+/// Wraps `content` in a DER encoded [ContentInfo].
+fn wrap<T: Encode>(content_type: &Oid, content: &T) -> Result<OctetString, EncodeError> {
+    let content_info = ContentInfo {
+        content_type: content_type.into(),
+        content: Any::new(rasn::der::encode(content)?),
+    };
+
+    Ok(rasn::der::encode(&content_info)?.into())
+}
+
+/// Reads the content of a DER encoded [ContentInfo].
+fn unwrap<T: Decode>(content_type: &Oid, input: &[u8]) -> Result<T, PkinitError> {
+    let content_info: ContentInfo = rasn::der::decode(input)?;
+    if content_info.content_type != *content_type {
+        return Err(PkinitError::UnexpectedContentType(
+            content_info.content_type,
+        ));
+    }
+
+    Ok(rasn::der::decode(content_info.content.as_bytes())?)
+}
+
+/// Reads the content of `signed_data`, which must have `content_type`.
+///
+/// This doesn't verify the signature, which should be done first with
+/// [SignedData::verify] on the received encoding.
+fn signed_content<T: Decode>(
+    signed_data: &SignedData,
+    content_type: &Oid,
+) -> Result<T, PkinitError> {
+    let info = &signed_data.encap_content_info;
+    if info.content_type != *content_type {
+        return Err(PkinitError::UnexpectedContentType(
+            info.content_type.clone(),
+        ));
+    }
+    let content = info.content.as_ref().ok_or(PkinitError::MissingContent)?;
+
+    Ok(rasn::der::decode(content)?)
+}
+
+/// The `PA-PK-AS-REQ` pre-authentication data sent by the client.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct PaPkAsReq {
+    /// A DER encoded [ContentInfo] holding the [SignedData] of an [AuthPack].
+    #[rasn(tag(0))]
+    pub signed_auth_pack: OctetString,
+    /// The certification authorities trusted by the client.
+    #[rasn(tag(explicit(1)))]
+    pub trusted_certifiers: Option<SequenceOf<ExternalPrincipalIdentifier>>,
+    /// A DER encoded `SignerIdentifier` of the KDC's key.
+    #[rasn(tag(2))]
+    pub kdc_pk_id: Option<OctetString>,
+}
+
+impl PaPkAsReq {
+    /// Creates a request from the signed data of an [AuthPack].
+    pub fn new(signed_auth_pack: &SignedData) -> Result<Self, EncodeError> {
+        Ok(Self {
+            signed_auth_pack: wrap(rasn_cms::CONTENT_SIGNED_DATA, signed_auth_pack)?,
+            trusted_certifiers: None,
+            kdc_pk_id: None,
+        })
+    }
+
+    /// Decodes the signed data of the [AuthPack].
+    pub fn signed_data(&self) -> Result<SignedData, PkinitError> {
+        unwrap(rasn_cms::CONTENT_SIGNED_DATA, &self.signed_auth_pack)
+    }
+}
+
+/// Identifies a certificate or certification authority.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct ExternalPrincipalIdentifier {
+    /// A DER encoded PKIX `Name`.
+    #[rasn(tag(0))]
+    pub subject_name: Option<OctetString>,
+    /// A DER encoded CMS `IssuerAndSerialNumber`.
+    #[rasn(tag(1))]
+    pub issuer_and_serial_number: Option<OctetString>,
+    /// A certificate's subject key identifier.
+    #[rasn(tag(2))]
+    pub subject_key_identifier: Option<OctetString>,
+}
+
+impl ExternalPrincipalIdentifier {
+    /// Creates an identifier from a certificate's issuer and serial number.
+    pub fn from_issuer_and_serial_number(
+        value: &rasn_cms::IssuerAndSerialNumber,
+    ) -> Result<Self, EncodeError> {
+        Ok(Self {
+            subject_name: None,
+            issuer_and_serial_number: Some(rasn::der::encode(value)?.into()),
+            subject_key_identifier: None,
+        })
+    }
+}
+
+/// The content signed by the client, with the type [AUTH_DATA].
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct AuthPack {
+    #[rasn(tag(explicit(0)))]
+    pub pk_authenticator: PkAuthenticator,
+    /// The client's Diffie-Hellman public key, absent when the reply key is
+    /// encrypted with the client's certificate instead.
+    #[rasn(tag(explicit(1)))]
+    pub client_public_value: Option<SubjectPublicKeyInfo>,
+    /// The CMS algorithms supported by the client, in order of preference.
+    #[rasn(tag(explicit(2)))]
+    pub supported_cms_types: Option<SequenceOf<AlgorithmIdentifier>>,
+    #[rasn(tag(explicit(3)))]
+    pub client_dh_nonce: Option<DhNonce>,
+    /// The key derivation functions supported by the client, in order of
+    /// preference.
+    #[rasn(tag(explicit(4)))]
+    pub supported_kdfs: Option<SequenceOf<KdfAlgorithmId>>,
+}
+
+impl AuthPack {
+    pub fn new(pk_authenticator: PkAuthenticator) -> Self {
+        Self {
+            pk_authenticator,
+            client_public_value: None,
+            supported_cms_types: None,
+            client_dh_nonce: None,
+            supported_kdfs: None,
+        }
+    }
+
+    /// Reads the auth pack signed in `signed_data`.
+    pub fn from_signed_data(signed_data: &SignedData) -> Result<Self, PkinitError> {
+        signed_content(signed_data, AUTH_DATA)
+    }
+}
+
+/// Binds an [AuthPack] to the request it's sent in.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct PkAuthenticator {
+    #[rasn(tag(explicit(0)))]
+    pub cusec: Microseconds,
+    #[rasn(tag(explicit(1)))]
+    pub ctime: KerberosTime,
+    /// The nonce of the request body.
+    #[rasn(tag(explicit(2)))]
+    pub nonce: u32,
+    /// The SHA-1 checksum of the DER encoded request body.
+    #[rasn(tag(explicit(3)))]
+    pub pa_checksum: Option<OctetString>,
+    /// A token from a previous `PA_AS_FRESHNESS`, from RFC 8070.
+    #[rasn(tag(explicit(4)))]
+    pub freshness_token: Option<OctetString>,
+}
+
+impl PkAuthenticator {
+    /// Creates an authenticator for `req_body`, with its checksum.
+    pub fn new(
+        cusec: Microseconds,
+        ctime: KerberosTime,
+        req_body: &KdcReqBody,
+    ) -> Result<Self, EncodeError> {
+        Ok(Self {
+            cusec,
+            ctime,
+            nonce: req_body.nonce,
+            pa_checksum: Some(Self::checksum(req_body)?),
+            freshness_token: None,
+        })
+    }
+
+    /// Computes the `paChecksum` of `req_body`.
+    pub fn checksum(req_body: &KdcReqBody) -> Result<OctetString, EncodeError> {
+        use sha1::Digest;
+
+        let encoded = rasn::der::encode(req_body)?;
+        Ok(Vec::from(&sha1::Sha1::digest(encoded)[..]).into())
+    }
+}
+
+/// The `PA-PK-AS-REP` pre-authentication data sent by the KDC.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[rasn(choice)]
+pub enum PaPkAsRep {
+    /// The reply key is derived from a Diffie-Hellman exchange.
+    #[rasn(tag(explicit(0)))]
+    DhInfo(DhRepInfo),
+    /// A DER encoded [ContentInfo] holding the [EnvelopedData] of the signed
+    /// data of a [ReplyKeyPack], encrypted for the client's certificate.
+    #[rasn(tag(1))]
+    EncKeyPack(OctetString),
+}
+
+impl PaPkAsRep {
+    /// Creates a reply from the enveloped data of a [ReplyKeyPack].
+    pub fn enc_key_pack(enveloped_data: &EnvelopedData) -> Result<Self, EncodeError> {
+        wrap(rasn_cms::CONTENT_ENVELOPED_DATA, enveloped_data).map(Self::EncKeyPack)
+    }
+
+    /// Decodes the enveloped data of an [PaPkAsRep::EncKeyPack] reply.
+    ///
+    /// Its decrypted content is the DER encoded [SignedData] of a
+    /// [ReplyKeyPack].
+    pub fn enveloped_data(&self) -> Option<Result<EnvelopedData, PkinitError>> {
+        match self {
+            Self::DhInfo(_) => None,
+            Self::EncKeyPack(content_info) => {
+                Some(unwrap(rasn_cms::CONTENT_ENVELOPED_DATA, content_info))
+            }
+        }
+    }
+}
+
+/// The KDC's half of a Diffie-Hellman exchange.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct DhRepInfo {
+    /// A DER encoded [ContentInfo] holding the [SignedData] of a
+    /// [KdcDhKeyInfo].
+    #[rasn(tag(0))]
+    pub dh_signed_data: OctetString,
+    #[rasn(tag(explicit(1)))]
+    pub server_dh_nonce: Option<DhNonce>,
+    /// The key derivation function chosen by the KDC, absent when it uses
+    /// `octetstring2key` from RFC 4556.
+    #[rasn(tag(explicit(2)))]
+    pub kdf: Option<KdfAlgorithmId>,
+}
+
+impl DhRepInfo {
+    /// Creates a reply from the signed data of a [KdcDhKeyInfo].
+    pub fn new(dh_signed_data: &SignedData) -> Result<Self, EncodeError> {
+        Ok(Self {
+            dh_signed_data: wrap(rasn_cms::CONTENT_SIGNED_DATA, dh_signed_data)?,
+            server_dh_nonce: None,
+            kdf: None,
+        })
+    }
+
+    /// Decodes the signed data of the [KdcDhKeyInfo].
+    pub fn signed_data(&self) -> Result<SignedData, PkinitError> {
+        unwrap(rasn_cms::CONTENT_SIGNED_DATA, &self.dh_signed_data)
+    }
+}
+
+/// The content signed by the KDC in a Diffie-Hellman exchange, with the type
+/// [DH_KEY_DATA].
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct KdcDhKeyInfo {
+    /// The KDC's public key, encoded the way it is in a
+    /// [SubjectPublicKeyInfo].
+    #[rasn(tag(explicit(0)))]
+    pub subject_public_key: BitString,
+    /// The nonce of the client's [PkAuthenticator].
+    #[rasn(tag(explicit(1)))]
+    pub nonce: u32,
+    /// When the KDC's key will no longer be reused.
+    #[rasn(tag(explicit(2)))]
+    pub dh_key_expiration: Option<KerberosTime>,
+}
+
+impl KdcDhKeyInfo {
+    pub fn new(subject_public_key: BitString, nonce: u32) -> Self {
+        Self {
+            subject_public_key,
+            nonce,
+            dh_key_expiration: None,
+        }
+    }
+
+    /// Reads the key info signed in `signed_data`.
+    pub fn from_signed_data(signed_data: &SignedData) -> Result<Self, PkinitError> {
+        signed_content(signed_data, DH_KEY_DATA)
+    }
+}
+
+/// The content signed by the KDC when it encrypts the reply key for the
+/// client's certificate, with the type [RKEY_DATA].
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct ReplyKeyPack {
+    /// The key that encrypts the enc-part of the AS-REP.
+    #[rasn(tag(explicit(0)))]
+    pub reply_key: EncryptionKey,
+    /// A checksum of the DER encoded AS-REQ, keyed with the reply key.
+    #[rasn(tag(explicit(1)))]
+    pub as_checksum: Checksum,
+}
+
+impl ReplyKeyPack {
+    /// Creates a key pack for `reply_key`, checksumming `as_req`, the AS-REQ
+    /// as it was received.
+    pub fn new(reply_key: EncryptionKey, as_req: &[u8]) -> Result<Self, CryptoError> {
+        // RFC 4556 reuses the key usage of TGS-REQ authenticator checksums.
+        let as_checksum = Checksum::new(
+            &reply_key,
+            key_usage::TGS_REQ_AUTHENTICATOR_CHECKSUM,
+            as_req,
+        )?;
+
+        Ok(Self {
+            reply_key,
+            as_checksum,
+        })
+    }
+
+    /// Reads the key pack signed in `signed_data`.
+    pub fn from_signed_data(signed_data: &SignedData) -> Result<Self, PkinitError> {
+        signed_content(signed_data, RKEY_DATA)
+    }
+
+    /// Verifies the checksum of `as_req`, the AS-REQ as it was sent.
+    pub fn verify(&self, as_req: &[u8]) -> Result<(), CryptoError> {
+        self.as_checksum.verify(
+            &self.reply_key,
+            key_usage::TGS_REQ_AUTHENTICATOR_CHECKSUM,
+            as_req,
+        )
+    }
+}
+
+/// Identifies a key derivation function, from RFC 8636.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct KdfAlgorithmId {
+    #[rasn(tag(explicit(0)))]
+    pub kdf_id: ObjectIdentifier,
+}
+
+impl KdfAlgorithmId {
+    pub fn new(kdf_id: ObjectIdentifier) -> Self {
+        Self { kdf_id }
+    }
+}
+
+/// The value of a [SAN] subject alternative name.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Krb5PrincipalName {
+    #[rasn(tag(explicit(0)))]
+    pub realm: Realm,
+    #[rasn(tag(explicit(1)))]
+    pub principal_name: PrincipalName,
+}
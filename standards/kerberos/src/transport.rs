@@ -0,0 +1,112 @@
+//! Framing of [KdcMessage]s sent over TCP and UDP, from [RFC 4120 Section
+//! 7.2].
+//!
+//! Over UDP, each datagram holds exactly one DER encoded message. Over TCP,
+//! each message is preceded by its length as a four byte big-endian integer,
+//! whose high bit is reserved and must be zero.
+//!
+//! [RFC 4120 Section 7.2]: https://datatracker.ietf.org/doc/html/rfc4120#section-7.2
+
+use alloc::vec::Vec;
+
+use rasn::error::{DecodeError, EncodeError};
+
+use crate::KdcMessage;
+
+/// The length of the prefix of each message sent over TCP.
+pub const TCP_LENGTH_PREFIX: usize = 4;
+
+/// The size above which MIT clients send requests over TCP rather than UDP.
+pub const UDP_PREFERENCE_LIMIT: usize = 1465;
+
+/// An error returned when reading a framed message.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FramingError {
+    /// The reserved high bit of a TCP length prefix is set. A KDC should
+    /// reply with [crate::KrbError::KRB_ERR_FIELD_TOOLONG].
+    ReservedBit,
+    /// The message is longer than the reader's maximum length.
+    TooLong(usize),
+    /// The message couldn't be decoded.
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ReservedBit => write!(f, "reserved bit set in length prefix"),
+            Self::TooLong(length) => write!(f, "message of {length} bytes is too long"),
+            Self::Decode(error) => write!(f, "failed to decode message: {error}"),
+        }
+    }
+}
+
+impl From<DecodeError> for FramingError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl KdcMessage {
+    /// Encodes the message with its TCP length prefix.
+    pub fn to_tcp(&self) -> Result<Vec<u8>, EncodeError> {
+        let message = rasn::der::encode(self)?;
+        let length = u32::try_from(message.len())
+            .ok()
+            .filter(|length| length & 0x8000_0000 == 0)
+            .ok_or_else(|| {
+                EncodeError::from_kind(
+                    rasn::error::EncodeErrorKind::Custom {
+                        msg: "message too long for a TCP length prefix".into(),
+                    },
+                    rasn::Codec::Der,
+                )
+            })?;
+
+        Ok([&length.to_be_bytes()[..], &message].concat())
+    }
+
+    /// Reads the first message from the data received over a TCP connection
+    /// so far, returning it with the number of bytes it took up, or `None` if
+    /// more data is needed.
+    ///
+    /// Messages longer than `max_length` are rejected as soon as their length
+    /// prefix has been read, so a peer can't make the reader buffer more than
+    /// that.
+    pub fn from_tcp(
+        input: &[u8],
+        max_length: usize,
+    ) -> Result<Option<(Self, usize)>, FramingError> {
+        let Some((prefix, rest)) = input.split_first_chunk::<TCP_LENGTH_PREFIX>() else {
+            return Ok(None);
+        };
+
+        let length = u32::from_be_bytes(*prefix);
+        if length & 0x8000_0000 != 0 {
+            return Err(FramingError::ReservedBit);
+        }
+        let length = length as usize;
+        if length > max_length {
+            return Err(FramingError::TooLong(length));
+        }
+
+        match rest.get(..length) {
+            Some(message) => Ok(Some((
+                rasn::der::decode(message)?,
+                TCP_LENGTH_PREFIX + length,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Encodes the message as a UDP datagram.
+    pub fn to_udp(&self) -> Result<Vec<u8>, EncodeError> {
+        rasn::der::encode(self)
+    }
+
+    /// Reads the message in a UDP datagram.
+    pub fn from_udp(datagram: &[u8]) -> Result<Self, FramingError> {
+        Ok(rasn::der::decode(datagram)?)
+    }
+}
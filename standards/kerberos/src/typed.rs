@@ -0,0 +1,256 @@
+//! # Typed Pre-authentication and Authorization Data
+//!
+//! [`PaData::value`] and [`AuthorizationDataValue::data`] hold encodings
+//! whose type is determined by their registered type number. [`TypedPaData`]
+//! and [`TypedAuthorizationData`] decode and encode the types known to this
+//! crate by those numbers, and keep any others as they were received, so
+//! messages can be read and rewritten without losing elements.
+//!
+//! ```
+//! use rasn_kerberos::{typed::TypedPaData, PaData, PaPacRequest};
+//!
+//! let pa_data = TypedPaData::PacRequest(PaPacRequest { include_pac: true })
+//!     .encode()
+//!     .unwrap();
+//! assert_eq!(PaData::PAC_REQUEST, pa_data.r#type);
+//! assert_eq!(
+//!     TypedPaData::PacRequest(PaPacRequest { include_pac: true }),
+//!     pa_data.decode_typed().unwrap(),
+//! );
+//! ```
+use alloc::{format, vec::Vec};
+
+use rasn::error::{DecodeError, DecodeErrorKind, EncodeError};
+
+use crate::{
+    pac::Pac, AdAndOr, AdCammac, AdIfRelevant, AdKdcIssued, AdLoginAlias, AdMandatoryForKdc, ApReq,
+    AuthorizationData, AuthorizationDataValue, EtypeInfo, EtypeInfo2, PaData, PaEncTimestamp,
+    PaEncryptedChallenge, PaPacRequest,
+};
+
+/// A pre-authentication element, decoded by its type.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum TypedPaData {
+    TgsReq(ApReq),
+    EncTimestamp(PaEncTimestamp),
+    EtypeInfo(EtypeInfo),
+    #[cfg(feature = "pkinit")]
+    PkAsReq(crate::pkinit::PaPkAsReq),
+    #[cfg(feature = "pkinit")]
+    PkAsRep(crate::pkinit::PaPkAsRep),
+    EtypeInfo2(EtypeInfo2),
+    PacRequest(PaPacRequest),
+    EncryptedChallenge(PaEncryptedChallenge),
+    #[cfg(feature = "otp")]
+    OtpChallenge(crate::otp::PaOtpChallenge),
+    #[cfg(feature = "otp")]
+    OtpRequest(crate::otp::PaOtpRequest),
+    #[cfg(feature = "otp")]
+    OtpPinChange(crate::otp::PaOtpPinChange),
+    /// An element whose type isn't known to this crate, or an empty hint.
+    Opaque(PaData),
+}
+
+impl TypedPaData {
+    /// The registered number of the element's type.
+    pub fn r#type(&self) -> i32 {
+        match self {
+            Self::TgsReq(_) => PaData::TGS_REQ,
+            Self::EncTimestamp(_) => PaData::ENC_TIMESTAMP,
+            Self::EtypeInfo(_) => PaData::ETYPE_INFO,
+            #[cfg(feature = "pkinit")]
+            Self::PkAsReq(_) => PaData::PK_AS_REQ,
+            #[cfg(feature = "pkinit")]
+            Self::PkAsRep(_) => PaData::PK_AS_REP,
+            Self::EtypeInfo2(_) => PaData::ETYPE_INFO2,
+            Self::PacRequest(_) => PaData::PAC_REQUEST,
+            Self::EncryptedChallenge(_) => PaData::ENCRYPTED_CHALLENGE,
+            #[cfg(feature = "otp")]
+            Self::OtpChallenge(_) => PaData::OTP_CHALLENGE,
+            #[cfg(feature = "otp")]
+            Self::OtpRequest(_) => PaData::OTP_REQUEST,
+            #[cfg(feature = "otp")]
+            Self::OtpPinChange(_) => PaData::OTP_PIN_CHANGE,
+            Self::Opaque(pa_data) => pa_data.r#type,
+        }
+    }
+
+    /// Decodes the value of `pa_data` by its type, keeping it opaque if the
+    /// type isn't known or the value is empty. An empty value is a hint, as
+    /// a KDC sends in the METHOD-DATA of `KDC_ERR_PREAUTH_REQUIRED` to list
+    /// the pre-authentication types it accepts.
+    pub fn decode(pa_data: &PaData) -> Result<Self, DecodeError> {
+        let value = &pa_data.value[..];
+        if value.is_empty() {
+            return Ok(Self::Opaque(pa_data.clone()));
+        }
+
+        Ok(match pa_data.r#type {
+            PaData::TGS_REQ => Self::TgsReq(rasn::der::decode(value)?),
+            PaData::ENC_TIMESTAMP => Self::EncTimestamp(rasn::der::decode(value)?),
+            PaData::ETYPE_INFO => Self::EtypeInfo(rasn::der::decode(value)?),
+            #[cfg(feature = "pkinit")]
+            PaData::PK_AS_REQ => Self::PkAsReq(rasn::der::decode(value)?),
+            #[cfg(feature = "pkinit")]
+            PaData::PK_AS_REP => Self::PkAsRep(rasn::der::decode(value)?),
+            PaData::ETYPE_INFO2 => Self::EtypeInfo2(rasn::der::decode(value)?),
+            PaData::PAC_REQUEST => Self::PacRequest(rasn::der::decode(value)?),
+            PaData::ENCRYPTED_CHALLENGE => Self::EncryptedChallenge(rasn::der::decode(value)?),
+            #[cfg(feature = "otp")]
+            PaData::OTP_CHALLENGE => Self::OtpChallenge(rasn::der::decode(value)?),
+            #[cfg(feature = "otp")]
+            PaData::OTP_REQUEST => Self::OtpRequest(rasn::der::decode(value)?),
+            #[cfg(feature = "otp")]
+            PaData::OTP_PIN_CHANGE => Self::OtpPinChange(rasn::der::decode(value)?),
+            _ => Self::Opaque(pa_data.clone()),
+        })
+    }
+
+    /// Encodes the element as [PaData].
+    pub fn encode(&self) -> Result<PaData, EncodeError> {
+        let value = match self {
+            Self::TgsReq(value) => rasn::der::encode(value),
+            Self::EncTimestamp(value) => rasn::der::encode(value),
+            Self::EtypeInfo(value) => rasn::der::encode(value),
+            #[cfg(feature = "pkinit")]
+            Self::PkAsReq(value) => rasn::der::encode(value),
+            #[cfg(feature = "pkinit")]
+            Self::PkAsRep(value) => rasn::der::encode(value),
+            Self::EtypeInfo2(value) => rasn::der::encode(value),
+            Self::PacRequest(value) => rasn::der::encode(value),
+            Self::EncryptedChallenge(value) => rasn::der::encode(value),
+            #[cfg(feature = "otp")]
+            Self::OtpChallenge(value) => rasn::der::encode(value),
+            #[cfg(feature = "otp")]
+            Self::OtpRequest(value) => rasn::der::encode(value),
+            #[cfg(feature = "otp")]
+            Self::OtpPinChange(value) => rasn::der::encode(value),
+            Self::Opaque(pa_data) => return Ok(pa_data.clone()),
+        }?;
+
+        Ok(PaData {
+            r#type: self.r#type(),
+            value: value.into(),
+        })
+    }
+}
+
+impl PaData {
+    /// Decodes the value by its type, keeping it opaque if the type isn't
+    /// known or the value is an empty hint.
+    pub fn decode_typed(&self) -> Result<TypedPaData, DecodeError> {
+        TypedPaData::decode(self)
+    }
+}
+
+/// An authorization data element, decoded by its type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TypedAuthorizationData {
+    IfRelevant(AdIfRelevant),
+    KdcIssued(AdKdcIssued),
+    AndOr(AdAndOr),
+    MandatoryForKdc(AdMandatoryForKdc),
+    #[cfg(feature = "pkinit")]
+    InitialVerifiedCas(crate::pkinit::AdInitialVerifiedCas),
+    LoginAlias(AdLoginAlias),
+    Cammac(AdCammac),
+    #[cfg(feature = "otp")]
+    AuthenticationIndicator(crate::otp::AdAuthenticationIndicator),
+    Win2kPac(Pac),
+    /// An element whose type isn't known to this crate.
+    Opaque(AuthorizationDataValue),
+}
+
+impl TypedAuthorizationData {
+    /// The registered number of the element's type.
+    pub fn r#type(&self) -> i32 {
+        match self {
+            Self::IfRelevant(_) => AuthorizationDataValue::IF_RELEVANT,
+            Self::KdcIssued(_) => AuthorizationDataValue::KDC_ISSUED,
+            Self::AndOr(_) => AuthorizationDataValue::AND_OR,
+            Self::MandatoryForKdc(_) => AuthorizationDataValue::MANDATORY_FOR_KDC,
+            #[cfg(feature = "pkinit")]
+            Self::InitialVerifiedCas(_) => AuthorizationDataValue::INITIAL_VERIFIED_CAS,
+            Self::LoginAlias(_) => AuthorizationDataValue::LOGIN_ALIAS,
+            Self::Cammac(_) => AuthorizationDataValue::CAMMAC,
+            #[cfg(feature = "otp")]
+            Self::AuthenticationIndicator(_) => AuthorizationDataValue::AUTHENTICATION_INDICATOR,
+            Self::Win2kPac(_) => AuthorizationDataValue::WIN2K_PAC,
+            Self::Opaque(element) => element.r#type,
+        }
+    }
+
+    /// Decodes the data of `element` by its type, keeping it opaque if the
+    /// type isn't known.
+    pub fn decode(element: &AuthorizationDataValue) -> Result<Self, DecodeError> {
+        let data = &element.data[..];
+        Ok(match element.r#type {
+            AuthorizationDataValue::IF_RELEVANT => Self::IfRelevant(rasn::der::decode(data)?),
+            AuthorizationDataValue::KDC_ISSUED => Self::KdcIssued(rasn::der::decode(data)?),
+            AuthorizationDataValue::AND_OR => Self::AndOr(rasn::der::decode(data)?),
+            AuthorizationDataValue::MANDATORY_FOR_KDC => {
+                Self::MandatoryForKdc(rasn::der::decode(data)?)
+            }
+            #[cfg(feature = "pkinit")]
+            AuthorizationDataValue::INITIAL_VERIFIED_CAS => {
+                Self::InitialVerifiedCas(rasn::der::decode(data)?)
+            }
+            AuthorizationDataValue::LOGIN_ALIAS => Self::LoginAlias(rasn::der::decode(data)?),
+            AuthorizationDataValue::CAMMAC => Self::Cammac(rasn::der::decode(data)?),
+            #[cfg(feature = "otp")]
+            AuthorizationDataValue::AUTHENTICATION_INDICATOR => {
+                Self::AuthenticationIndicator(rasn::der::decode(data)?)
+            }
+            AuthorizationDataValue::WIN2K_PAC => {
+                Self::Win2kPac(Pac::parse(data).map_err(|error| {
+                    DecodeError::from_kind(
+                        DecodeErrorKind::Custom {
+                            msg: format!("invalid PAC: {error}"),
+                        },
+                        rasn::Codec::Der,
+                    )
+                })?)
+            }
+            _ => Self::Opaque(element.clone()),
+        })
+    }
+
+    /// Encodes the element as an [AuthorizationDataValue].
+    pub fn encode(&self) -> Result<AuthorizationDataValue, EncodeError> {
+        let data = match self {
+            Self::IfRelevant(value) => rasn::der::encode(value),
+            Self::KdcIssued(value) => rasn::der::encode(value),
+            Self::AndOr(value) => rasn::der::encode(value),
+            Self::MandatoryForKdc(value) => rasn::der::encode(value),
+            #[cfg(feature = "pkinit")]
+            Self::InitialVerifiedCas(value) => rasn::der::encode(value),
+            Self::LoginAlias(value) => rasn::der::encode(value),
+            Self::Cammac(value) => rasn::der::encode(value),
+            #[cfg(feature = "otp")]
+            Self::AuthenticationIndicator(value) => rasn::der::encode(value),
+            Self::Win2kPac(pac) => Ok(pac.to_bytes()),
+            Self::Opaque(element) => return Ok(element.clone()),
+        }?;
+
+        Ok(AuthorizationDataValue {
+            r#type: self.r#type(),
+            data: data.into(),
+        })
+    }
+
+    /// Decodes every element of `authorization_data`.
+    pub fn decode_all(authorization_data: &AuthorizationData) -> Result<Vec<Self>, DecodeError> {
+        authorization_data.iter().map(Self::decode).collect()
+    }
+}
+
+impl AuthorizationDataValue {
+    /// Decodes the data by its type, keeping it opaque if the type isn't
+    /// known.
+    pub fn decode_typed(&self) -> Result<TypedAuthorizationData, DecodeError> {
+        TypedAuthorizationData::decode(self)
+    }
+}
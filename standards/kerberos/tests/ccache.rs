@@ -0,0 +1,144 @@
+use rasn::prelude::*;
+use rasn_kerberos::{
+    ccache::{Ccache, CcacheEntry, ConfigEntry, FileError, KdcOffset, Principal},
+    *,
+};
+
+use pretty_assertions::assert_eq;
+
+// Written by MIT krb5's `krb5_cc_store_cred` and `krb5_cc_set_config`: a TGT,
+// a `fast_avail` entry, a `pa_type` entry for the TGT, and a service ticket.
+const CCACHE: &[u8] = include_bytes!("data/krb5cc");
+
+fn principal(r#type: i32, components: &[&str]) -> Principal {
+    Principal::new(
+        KerberosString::try_from("EXAMPLE.COM".to_string()).unwrap(),
+        PrincipalName {
+            r#type,
+            string: components
+                .iter()
+                .map(|component| KerberosString::try_from(component.to_string()).unwrap())
+                .collect(),
+        },
+    )
+}
+
+#[test]
+fn mit_ccache() {
+    let ccache = Ccache::parse(CCACHE).unwrap();
+    let user = principal(1, &["user"]);
+    let krbtgt = principal(2, &["krbtgt", "EXAMPLE.COM"]);
+    let http = principal(3, &["HTTP", "www.example.com"]);
+
+    assert_eq!(None, ccache.kdc_offset);
+    assert_eq!(user, ccache.principal);
+    assert_eq!(4, ccache.entries.len());
+    assert_eq!(2, ccache.credentials().count());
+
+    let tgt = ccache.find(&krbtgt).unwrap();
+    assert_eq!(user, tgt.client);
+    assert_eq!(krbtgt, tgt.server);
+    assert_eq!(18, tgt.key.r#type);
+    assert_eq!(&(0..32).collect::<Vec<u8>>()[..], &tgt.key.value[..]);
+    assert_eq!(1_700_000_000, tgt.auth_time.0.timestamp());
+    assert_eq!(
+        Some(1_700_000_000),
+        tgt.start_time.as_ref().map(|time| time.0.timestamp())
+    );
+    assert_eq!(1_700_036_000, tgt.end_time.0.timestamp());
+    assert_eq!(
+        Some(1_700_604_800),
+        tgt.renew_till.as_ref().map(|time| time.0.timestamp())
+    );
+    assert!(!tgt.is_skey);
+    // forwardable, renewable, initial, pre-authent and enc-pa-rep.
+    let set: Vec<usize> = tgt.flags.0.iter_ones().collect();
+    assert_eq!(vec![1, 8, 9, 10, 15], set);
+    assert_eq!(
+        vec![HostAddress {
+            addr_type: HostAddress::IPV4,
+            address: OctetString::from_static(&[192, 0, 2, 1]),
+        }],
+        tgt.addresses
+    );
+    assert_eq!(
+        AuthorizationDataValue::IF_RELEVANT,
+        tgt.authorization_data[0].r#type
+    );
+    assert_eq!(krbtgt.name, tgt.ticket.sname);
+    assert_eq!(18, tgt.ticket.enc_part.etype);
+    assert_eq!(Some(1), tgt.ticket.enc_part.kvno);
+    assert_eq!(None, tgt.second_ticket);
+
+    let service = ccache.find(&http).unwrap();
+    assert_eq!(
+        1_700_000_100,
+        service.start_time.as_ref().unwrap().0.timestamp()
+    );
+    assert_eq!(None, service.renew_till);
+    assert!(service.addresses.is_empty());
+    assert_eq!(http.name.string, service.ticket.sname.string);
+
+    assert_eq!(Some(&b"yes"[..]), ccache.config("fast_avail", None));
+    assert_eq!(Some(&b"2"[..]), ccache.config("pa_type", Some(&krbtgt)));
+    assert_eq!(None, ccache.config("pa_type", None));
+    let CcacheEntry::Config(config) = &ccache.entries[2] else {
+        panic!("expected a configuration entry");
+    };
+    assert_eq!(Some(&b"pa_type"[..]), config.key());
+    assert_eq!(
+        Some(&b"krbtgt/EXAMPLE.COM@EXAMPLE.COM"[..]),
+        config.principal()
+    );
+
+    assert_eq!(CCACHE, &ccache.to_bytes().unwrap()[..]);
+}
+
+#[test]
+fn round_trip() {
+    let template = Ccache::parse(CCACHE).unwrap();
+    let user = principal(1, &["user"]);
+    let mut ccache = Ccache::new(user.clone());
+    ccache.kdc_offset = Some(KdcOffset {
+        seconds: -30,
+        microseconds: 500,
+    });
+
+    let mut credential = template.credentials().next().unwrap().clone();
+    credential.second_ticket = Some(credential.ticket.clone());
+    credential.is_skey = true;
+    ccache
+        .entries
+        .push(CcacheEntry::Credential(Box::new(credential)));
+    ccache.entries.push(CcacheEntry::Config(
+        ConfigEntry::new(user, "refresh_time", None, b"1700030000".to_vec()).unwrap(),
+    ));
+
+    let bytes = ccache.to_bytes().unwrap();
+    assert_eq!(
+        &[0x05, 0x04, 0x00, 0x0c, 0x00, 0x01, 0x00, 0x08],
+        &bytes[..8]
+    );
+    assert_eq!(ccache, Ccache::parse(&bytes).unwrap());
+    assert_eq!(
+        Some(&b"1700030000"[..]),
+        Ccache::parse(&bytes).unwrap().config("refresh_time", None)
+    );
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        Ccache::parse(&[0x05, 0x03, 0x00, 0x00]),
+        Err(FileError::UnsupportedVersion(0x0503))
+    ));
+    assert!(matches!(
+        Ccache::parse(&CCACHE[..CCACHE.len() - 1]),
+        Err(FileError::UnexpectedEof)
+    ));
+
+    // The ticket of the TGT starts at offset 0xce, after its length.
+    let mut corrupt = CCACHE.to_vec();
+    corrupt[0xce] = 0x62;
+    assert!(matches!(Ccache::parse(&corrupt), Err(FileError::Decode(_))));
+}
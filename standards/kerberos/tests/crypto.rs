@@ -0,0 +1,445 @@
+#![cfg(feature = "crypto")]
+
+use rasn::prelude::*;
+use rasn_kerberos::{
+    crypto::{key_usage, CryptoError, DerivedKey, Enctype},
+    *,
+};
+
+use pretty_assertions::assert_eq;
+
+fn hex(string: &str) -> Vec<u8> {
+    let string: String = string.split_whitespace().collect();
+    (0..string.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&string[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The first `length` bytes of 00 01 02 ...
+fn counting(length: u8) -> Vec<u8> {
+    (0..length).collect()
+}
+
+#[test]
+fn rfc3962_string_to_key() {
+    // RFC 3962 Appendix B.
+    let salt = b"ATHENA.MIT.EDUraeburn";
+    let vectors = [
+        (
+            1,
+            "42263c6e89f4fc28b8df68ee09799f15",
+            "fe697b52bc0d3ce14432ba036a92e65bbb52280990a2fa27883998d72af30161",
+        ),
+        (
+            1200,
+            "4c01cd46d632d01e6dbe230a01ed642a",
+            "55a6ac740ad17b4846941051e1e8b0a7548d93b0ab30a8bc3ff16280382b8c2a",
+        ),
+    ];
+
+    for (iterations, aes128, aes256) in vectors {
+        let params = u32::to_be_bytes(iterations);
+        assert_eq!(
+            hex(aes128),
+            Enctype::Aes128CtsHmacSha196
+                .string_to_key(b"password", salt, Some(&params))
+                .unwrap()
+        );
+        assert_eq!(
+            hex(aes256),
+            Enctype::Aes256CtsHmacSha196
+                .string_to_key(b"password", salt, Some(&params))
+                .unwrap()
+        );
+    }
+
+    // The default iteration count is 4096.
+    assert_eq!(
+        Enctype::Aes128CtsHmacSha196
+            .string_to_key(b"password", salt, Some(&[0, 0, 0x10, 0]))
+            .unwrap(),
+        Enctype::Aes128CtsHmacSha196
+            .string_to_key(b"password", salt, None)
+            .unwrap()
+    );
+    assert!(matches!(
+        Enctype::Aes128CtsHmacSha196.string_to_key(b"password", salt, Some(&[0; 4])),
+        Err(CryptoError::InvalidParameters)
+    ));
+    assert!(matches!(
+        Enctype::Aes128CtsHmacSha196.string_to_key(b"password", salt, Some(&[1])),
+        Err(CryptoError::InvalidParameters)
+    ));
+}
+
+#[test]
+fn rfc8009_string_to_key() {
+    // RFC 8009 Appendix A, with an iteration count of 32768.
+    let mut salt = hex("10df9dd783e5bc8acea1730e74355f61");
+    salt.extend_from_slice(b"ATHENA.MIT.EDUraeburn");
+
+    assert_eq!(
+        hex("089bca48b105ea6ea77ca5d2f39dc5e7"),
+        Enctype::Aes128CtsHmacSha256128
+            .string_to_key(b"password", &salt, None)
+            .unwrap()
+    );
+    assert_eq!(
+        hex("45bd806dbf6a833a9cffc1c94589a222367a79bc21c413718906e9f578a78467"),
+        Enctype::Aes256CtsHmacSha384192
+            .string_to_key(b"password", &salt, Some(&[0, 0, 0x80, 0]))
+            .unwrap()
+    );
+}
+
+#[test]
+fn rfc8009_key_derivation() {
+    // RFC 8009 Appendix A, with key usage 2.
+    let key = hex("3705d96080c17728a0e800eab6e0d23c");
+    let enctype = Enctype::Aes128CtsHmacSha256128;
+    assert_eq!(
+        hex("b31a018a48f54776f403e9a396325dc3"),
+        enctype.derive_key(&key, 2, DerivedKey::Checksum).unwrap()
+    );
+    assert_eq!(
+        hex("9b197dd1e8c5609d6e67c3e37c62c72e"),
+        enctype.derive_key(&key, 2, DerivedKey::Encryption).unwrap()
+    );
+    assert_eq!(
+        hex("9fda0e56ab2d85e1569a688696c26a6c"),
+        enctype.derive_key(&key, 2, DerivedKey::Integrity).unwrap()
+    );
+
+    let key = hex("6d404d37faf79f9df0d33568d320669800eb4836472ea8a026d16b7182460c52");
+    let enctype = Enctype::Aes256CtsHmacSha384192;
+    assert_eq!(
+        hex("ef5718be86cc84963d8bbb5031e9f5c4ba41f28faf69e73d"),
+        enctype.derive_key(&key, 2, DerivedKey::Checksum).unwrap()
+    );
+    assert_eq!(
+        hex("56ab22bee63d82d7bc5227f6773f8ea7a5eb1c825160c38312980c442e5c7e49"),
+        enctype.derive_key(&key, 2, DerivedKey::Encryption).unwrap()
+    );
+    assert_eq!(
+        hex("69b16514e3cd8e56b82010d5c73012b622c4d00ffc23ed1f"),
+        enctype.derive_key(&key, 2, DerivedKey::Integrity).unwrap()
+    );
+}
+
+#[test]
+fn rfc8009_encryption() {
+    // RFC 8009 Appendix A, with key usage 2.
+    let vectors = [
+        (
+            Enctype::Aes128CtsHmacSha256128,
+            "3705d96080c17728a0e800eab6e0d23c",
+            [
+                (
+                    0,
+                    "7e5895eaf2672435bad817f545a37148",
+                    "ef85fb890bb8472f4dab20394dca781dad877eda39d50c870c0d5a0a8e48c718",
+                ),
+                (
+                    6,
+                    "7bca285e2fd4130fb55b1a5c83bc5b24",
+                    "84d7f30754ed987bab0bf3506beb09cfb55402cef7e6877ce99e247e52d16ed4
+                     421dfdf8976c",
+                ),
+                (
+                    16,
+                    "56ab21713ff62c0a1457200f6fa9948f",
+                    "3517d640f50ddc8ad3628722b3569d2ae07493fa8263254080ea65c1008e8fc2
+                     95fb4852e7d83e1e7c48c37eebe6b0d3",
+                ),
+                (
+                    21,
+                    "a7a4e29a4728ce10664fb64e49ad3fac",
+                    "720f73b18d9859cd6ccb4346115cd336c70f58edc0c4437c5573544c31c813bc
+                     e1e6d072c186b39a413c2f92ca9b8334a287ffcbfc",
+                ),
+            ],
+        ),
+        (
+            Enctype::Aes256CtsHmacSha384192,
+            "6d404d37faf79f9df0d33568d320669800eb4836472ea8a026d16b7182460c52",
+            [
+                (
+                    0,
+                    "f764e9fa15c276478b2c7d0c4e5f58e4",
+                    "41f53fa5bfe7026d91faf9be959195a058707273a96a40f0a01960621ac61274
+                     8b9bbfbe7eb4ce3c",
+                ),
+                (
+                    6,
+                    "b80d3251c1f6471494256ffe712d0b9a",
+                    "4ed7b37c2bcac8f74f23c1cf07e62bc7b75fb3f637b9f559c7f664f69eab7b60
+                     92237526ea0d1f61cb20d69d10f2",
+                ),
+                (
+                    16,
+                    "53bf8a0d105265d4e276428624ce5e63",
+                    "bc47ffec7998eb91e8115cf8d19dac4bbbe2e163e87dd37f49beca92027764f6
+                     8cf51f14d798c2273f35df574d1f932e40c4ff255b36a266",
+                ),
+                (
+                    21,
+                    "763e65367e864f02f55153c7e3b58af1",
+                    "40013e2df58e8751957d2878bcd2d6fe101ccfd556cb1eae79db3c3ee86429f2
+                     b2a602ac86fef6ecb647d6295fae077a1feb517508d2c16b4192e01f62",
+                ),
+            ],
+        ),
+    ];
+
+    for (enctype, key, cases) in vectors {
+        let key = hex(key);
+        for (length, confounder, expected) in cases {
+            let plaintext = counting(length);
+            let ciphertext = enctype
+                .encrypt(&key, 2, &plaintext, &hex(confounder))
+                .unwrap();
+            assert_eq!(hex(expected), ciphertext, "{enctype:?} {length}");
+            assert_eq!(plaintext, enctype.decrypt(&key, 2, &ciphertext).unwrap());
+        }
+    }
+}
+
+#[test]
+fn rfc8009_checksum() {
+    // RFC 8009 Appendix A, "Checksum generation".
+    let plaintext = counting(21);
+    assert_eq!(
+        hex("d78367186643d67b411cba9139fc1dee"),
+        Enctype::Aes128CtsHmacSha256128
+            .checksum(&hex("3705d96080c17728a0e800eab6e0d23c"), 2, &plaintext)
+            .unwrap()
+    );
+    assert_eq!(
+        hex("45ee791567eefca37f4ac1e0222de80d43c3bfa06699672a"),
+        Enctype::Aes256CtsHmacSha384192
+            .checksum(
+                &hex("6d404d37faf79f9df0d33568d320669800eb4836472ea8a026d16b7182460c52"),
+                2,
+                &plaintext,
+            )
+            .unwrap()
+    );
+}
+
+/// Keys, ciphertexts and checksums produced by MIT Kerberos for the password
+/// "password" and salt "EXAMPLE.COMuser".
+#[test]
+fn mit_interop() {
+    let plaintext = b"rasn kerberos interop";
+    let vectors = [
+        (
+            Enctype::AES128_CTS_HMAC_SHA1_96,
+            "3892facdf64b383e0f9e8607b8de96e3",
+            "f858b95110a474ada831fd1c4d3b8bf47171ea713070f3db76094979493d317f
+             04b4aaef89e8f92638dcd330d3c8bd5af1",
+            "40a8a3a5865286baa67dd0b7",
+        ),
+        (
+            Enctype::AES256_CTS_HMAC_SHA1_96,
+            "511ccb9e22d7d454919b07228c4cee55c6f8137d6c221534ed446aa2d7a4e864",
+            "3dbf294f06199a87adaa0b24f4e132721bb5a7879fef6c9f861bed48a74e17e3
+             9ce565886722a047e6ea91683c030c68e9",
+            "2d82605512c271e2a5e845c2",
+        ),
+        (
+            Enctype::AES128_CTS_HMAC_SHA256_128,
+            "e0206569b1378654a12a9d837340eb48",
+            "7e4c983a68b9e876ad91912f1650cb17075c650e6c3e12bd899a4f036056abda
+             b6db8dfe3697c78d8b57190a023fcfa62a5d99ba89",
+            "ebb79ec168540547db19cccf1ecb7d67",
+        ),
+        (
+            Enctype::AES256_CTS_HMAC_SHA384_192,
+            "d7b6c3c24c21fb5c0eb5c3a15c8e4294278b5794d5cf3fb64ae8a39b399b802a",
+            "a7ea266d5731922b921cb2d71d32220bcea6f42dc2f9947b4fe8da96af170900
+             94680f7cc6bbf8467300fe3cbcc62f05648d0f0e88924580c199b24a32",
+            "59ad303112b4b34d567613d0264476ae6d470b27a5cb118e",
+        ),
+    ];
+
+    for (etype, expected_key, ciphertext, checksum) in vectors {
+        let key =
+            EncryptionKey::from_password(etype, b"password", b"EXAMPLE.COMuser", None).unwrap();
+        assert_eq!(hex(expected_key), key.value.to_vec());
+        let encrypted = EncryptedData {
+            etype,
+            kvno: None,
+            cipher: hex(ciphertext).into(),
+        };
+        assert_eq!(
+            plaintext.to_vec(),
+            encrypted
+                .decrypt_bytes(&key, key_usage::AS_REP_ENC_PART)
+                .unwrap()
+        );
+
+        let expected =
+            Checksum::new(&key, key_usage::AP_REQ_AUTHENTICATOR_CHECKSUM, plaintext).unwrap();
+        assert_eq!(hex(checksum), expected.checksum.to_vec());
+        expected
+            .verify(&key, key_usage::AP_REQ_AUTHENTICATOR_CHECKSUM, plaintext)
+            .unwrap();
+    }
+}
+
+#[cfg(feature = "rc4-hmac")]
+#[test]
+fn rc4_hmac() {
+    let key = EncryptionKey::from_password(Enctype::RC4_HMAC, b"password", b"", None).unwrap();
+    assert_eq!(hex("8846f7eaee8fb117ad06bdd830b7586c"), key.value.to_vec());
+
+    // Produced by MIT Kerberos. The AS-REP key usage is translated to 8.
+    let plaintext = b"rasn kerberos interop";
+    let encrypted = EncryptedData {
+        etype: Enctype::RC4_HMAC,
+        kvno: None,
+        cipher: hex(
+            "e87de6b8cb96452d9e54cbd91a589999c1df4641d8c2c267a17e91961919f74d
+             f3648e46f407227db5c184f9b4",
+        )
+        .into(),
+    };
+    assert_eq!(
+        plaintext.to_vec(),
+        encrypted
+            .decrypt_bytes(&key, key_usage::AS_REP_ENC_PART)
+            .unwrap()
+    );
+    assert_eq!(
+        plaintext.to_vec(),
+        encrypted
+            .decrypt_bytes(&key, key_usage::TGS_REP_ENC_PART_SESSION_KEY)
+            .unwrap()
+    );
+
+    let checksum =
+        Checksum::new(&key, key_usage::AP_REQ_AUTHENTICATOR_CHECKSUM, plaintext).unwrap();
+    assert_eq!(-138, checksum.r#type);
+    assert_eq!(
+        hex("c67220f3e340d6450b58a7d061ca1861"),
+        checksum.checksum.to_vec()
+    );
+
+    let encrypted =
+        EncryptedData::encrypt_bytes(plaintext, &key, key_usage::TICKET, None, &[7; 8]).unwrap();
+    assert_eq!(
+        plaintext.to_vec(),
+        encrypted.decrypt_bytes(&key, key_usage::TICKET).unwrap()
+    );
+    assert!(matches!(
+        Enctype::Rc4Hmac.derive_key(&key.value, 2, DerivedKey::Checksum),
+        Err(CryptoError::NoKeyDerivation)
+    ));
+}
+
+#[test]
+fn typed_encryption() {
+    let realm = KerberosString::from_bytes(b"EXAMPLE.COM").unwrap();
+    let cname = PrincipalName {
+        r#type: 1,
+        string: vec![KerberosString::from_bytes(b"user").unwrap()],
+    };
+    assert_eq!(b"EXAMPLE.COMuser".to_vec(), cname.salt(&realm));
+
+    // Without a salt, the principal's default salt is used.
+    let entry = EtypeInfo2Entry {
+        etype: Enctype::AES256_CTS_HMAC_SHA1_96,
+        salt: None,
+        s2kparams: None,
+    };
+    let key = entry
+        .string_to_key(b"password", &cname.salt(&realm))
+        .unwrap();
+    assert_eq!(
+        hex("511ccb9e22d7d454919b07228c4cee55c6f8137d6c221534ed446aa2d7a4e864"),
+        key.value.to_vec()
+    );
+    let salted = EtypeInfo2Entry {
+        salt: Some(KerberosString::from_bytes(b"EXAMPLE.COMuser").unwrap()),
+        ..entry
+    };
+    assert_eq!(key, salted.string_to_key(b"password", b"ignored").unwrap());
+
+    let value = EncryptionKey {
+        r#type: Enctype::AES128_CTS_HMAC_SHA1_96,
+        value: OctetString::from_static(&[0x11; 16]),
+    };
+    let encrypted = EncryptedData::encrypt(
+        &value,
+        &key,
+        key_usage::KRB_CRED_ENC_PART,
+        Some(2),
+        &[0; 16],
+    )
+    .unwrap();
+    assert_eq!(Enctype::AES256_CTS_HMAC_SHA1_96, encrypted.etype);
+    assert_eq!(Some(2), encrypted.kvno);
+    assert_eq!(
+        value,
+        encrypted
+            .decrypt::<EncryptionKey>(&key, key_usage::KRB_CRED_ENC_PART)
+            .unwrap()
+    );
+
+    // The wrong key usage fails the integrity check.
+    assert!(matches!(
+        encrypted.decrypt::<EncryptionKey>(&key, key_usage::AS_REP_ENC_PART),
+        Err(CryptoError::IntegrityCheckFailed)
+    ));
+    // As does tampering with the ciphertext.
+    let mut tampered = encrypted.clone();
+    let mut cipher = tampered.cipher.to_vec();
+    cipher[20] ^= 1;
+    tampered.cipher = cipher.into();
+    assert!(matches!(
+        tampered.decrypt::<EncryptionKey>(&key, key_usage::KRB_CRED_ENC_PART),
+        Err(CryptoError::IntegrityCheckFailed)
+    ));
+    // A key of another encryption type is rejected.
+    assert!(matches!(
+        encrypted.decrypt::<EncryptionKey>(&value, key_usage::KRB_CRED_ENC_PART),
+        Err(CryptoError::EtypeMismatch { key: 17, data: 18 })
+    ));
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        Enctype::from_etype(1),
+        Err(CryptoError::UnsupportedEtype(1))
+    ));
+    assert!(matches!(
+        Enctype::Aes128CtsHmacSha196.encrypt(&[0; 32], 1, b"", &[0; 16]),
+        Err(CryptoError::InvalidKeyLength)
+    ));
+    assert!(matches!(
+        Enctype::Aes128CtsHmacSha196.encrypt(&[0; 16], 1, b"", &[0; 8]),
+        Err(CryptoError::InvalidConfounderLength)
+    ));
+    assert!(matches!(
+        Enctype::Aes256CtsHmacSha384192.decrypt(&[0; 32], 1, &[0; 39]),
+        Err(CryptoError::InvalidCiphertext)
+    ));
+
+    let key = EncryptionKey {
+        r#type: Enctype::AES128_CTS_HMAC_SHA256_128,
+        value: OctetString::from_static(&[0x22; 16]),
+    };
+    let mut checksum = Checksum::new(&key, key_usage::KRB_SAFE_CHECKSUM, b"data").unwrap();
+    assert_eq!(19, checksum.r#type);
+    assert!(matches!(
+        checksum.verify(&key, key_usage::KRB_SAFE_CHECKSUM, b"date"),
+        Err(CryptoError::IntegrityCheckFailed)
+    ));
+    checksum.r#type = 16;
+    assert!(matches!(
+        checksum.verify(&key, key_usage::KRB_SAFE_CHECKSUM, b"data"),
+        Err(CryptoError::UnsupportedChecksumType(16))
+    ));
+}
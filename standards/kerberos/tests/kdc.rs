@@ -0,0 +1,334 @@
+#![cfg(feature = "kdc")]
+
+use chrono::{DateTime, TimeDelta};
+use rasn::prelude::*;
+use rasn_kerberos::{crypto::key_usage, kdc::Kdc, *};
+
+use pretty_assertions::assert_eq;
+
+const NONCE: u32 = 0x1234_5678;
+
+fn string(value: &str) -> KerberosString {
+    KerberosString::try_from(value.to_string()).unwrap()
+}
+
+fn name(r#type: i32, components: &[&str]) -> PrincipalName {
+    PrincipalName {
+        r#type,
+        string: components
+            .iter()
+            .map(|component| string(component))
+            .collect(),
+    }
+}
+
+fn time(seconds: i64) -> KerberosTime {
+    KerberosTime(DateTime::from_timestamp(seconds, 0).unwrap().into())
+}
+
+fn kdc() -> Kdc {
+    let mut kdc = Kdc::new(string("EXAMPLE.COM"), time(1_700_000_000));
+    kdc.etypes = vec![18, 17];
+    kdc.add_principal(name(1, &["user"]), b"password").unwrap();
+    kdc.add_principal(name(3, &["HTTP", "www.example.com"]), b"secret")
+        .unwrap();
+    kdc
+}
+
+fn body(cname: Option<PrincipalName>, sname: PrincipalName) -> KdcReqBody {
+    KdcReqBody {
+        kdc_options: KdcOptions(KerberosFlags::repeat(false, 32)),
+        cname,
+        realm: string("EXAMPLE.COM"),
+        sname: Some(sname),
+        from: None,
+        till: time(0),
+        rtime: None,
+        nonce: NONCE,
+        etype: vec![20, 18, 17],
+        addresses: None,
+        enc_authorization_data: None,
+        additional_tickets: None,
+    }
+}
+
+fn as_req(password: &[u8], timestamp: Option<KerberosTime>) -> KdcMessage {
+    let user = name(1, &["user"]);
+    let padata = timestamp.map(|timestamp| {
+        let key = EncryptionKey::from_password(18, password, b"EXAMPLE.COMuser", None).unwrap();
+        let timestamp = PaEncTsEnc {
+            patimestamp: timestamp,
+            pausec: Some(0.into()),
+        };
+        let data = EncryptedData::encrypt(
+            &timestamp,
+            &key,
+            key_usage::AS_REQ_PA_ENC_TIMESTAMP,
+            None,
+            &[0; 16],
+        )
+        .unwrap();
+        vec![PaData {
+            r#type: PaData::ENC_TIMESTAMP,
+            value: rasn::der::encode(&data).unwrap().into(),
+        }]
+    });
+
+    KdcMessage::AsReq(AsReq(KdcReq {
+        pvno: 5.into(),
+        msg_type: 10.into(),
+        padata,
+        req_body: body(Some(user), name(2, &["krbtgt", "EXAMPLE.COM"])),
+    }))
+}
+
+fn error_code(reply: KdcMessage) -> i32 {
+    match reply {
+        KdcMessage::KrbError(error) => error.error_code,
+        reply => panic!("expected an error, got {reply:?}"),
+    }
+}
+
+/// Authenticates as user@EXAMPLE.COM, returning the TGT and its session key.
+fn login(kdc: &mut Kdc) -> (Ticket, EncryptionKey) {
+    let KdcMessage::AsRep(AsRep(reply)) = kdc.handle(&as_req(b"password", Some(kdc.now.clone())))
+    else {
+        panic!("expected an AS-REP");
+    };
+    let key = EncryptionKey::from_password(18, b"password", b"EXAMPLE.COMuser", None).unwrap();
+    let EncAsRepPart(enc_part) = reply
+        .enc_part
+        .decrypt(&key, key_usage::AS_REP_ENC_PART)
+        .unwrap();
+    (reply.ticket, enc_part.key)
+}
+
+fn tgs_req(
+    ticket: Ticket,
+    session_key: &EncryptionKey,
+    subkey: Option<EncryptionKey>,
+    ctime: KerberosTime,
+) -> KdcMessage {
+    let body = body(None, name(3, &["HTTP", "www.example.com"]));
+    let checksum = Checksum::new(
+        session_key,
+        key_usage::TGS_REQ_AUTHENTICATOR_CHECKSUM,
+        &rasn::der::encode(&body).unwrap(),
+    )
+    .unwrap();
+    let authenticator = Authenticator {
+        authenticator_vno: 5.into(),
+        crealm: string("EXAMPLE.COM"),
+        cname: name(1, &["user"]),
+        cksum: Some(checksum),
+        cusec: 0.into(),
+        ctime,
+        subkey,
+        seq_number: None,
+        authorization_data: None,
+    };
+    let ap_req = ApReq {
+        pvno: 5.into(),
+        msg_type: 14.into(),
+        ap_options: ApOptions(KerberosFlags::repeat(false, 32)),
+        ticket,
+        authenticator: EncryptedData::encrypt(
+            &authenticator,
+            session_key,
+            key_usage::TGS_REQ_AUTHENTICATOR,
+            None,
+            &[0; 16],
+        )
+        .unwrap(),
+    };
+
+    KdcMessage::TgsReq(TgsReq(KdcReq {
+        pvno: 5.into(),
+        msg_type: 12.into(),
+        padata: Some(vec![PaData {
+            r#type: PaData::TGS_REQ,
+            value: rasn::der::encode(&ap_req).unwrap().into(),
+        }]),
+        req_body: body,
+    }))
+}
+
+#[test]
+fn as_exchange() {
+    let mut kdc = kdc();
+    kdc.now = KerberosTime(kdc.now.0 + TimeDelta::microseconds(250));
+
+    let KdcMessage::KrbError(error) = kdc.handle(&as_req(b"password", None)) else {
+        panic!("expected pre-authentication to be required");
+    };
+    assert_eq!(KrbError::KDC_ERR_PREAUTH_REQUIRED, error.error_code);
+    // KerberosTime has no fractional seconds, so they're sent separately.
+    assert_eq!(time(1_700_000_000), error.stime);
+    assert_eq!(Integer::from(250), error.susec);
+    let method_data: MethodData = rasn::der::decode(&error.e_data.unwrap()).unwrap();
+    // Every element decodes, including the empty ENC-TIMESTAMP hint.
+    for padata in &method_data {
+        assert_eq!(*padata, padata.decode_typed().unwrap().encode().unwrap());
+    }
+    let etype_info = method_data
+        .iter()
+        .find(|padata| padata.r#type == PaData::ETYPE_INFO2)
+        .unwrap();
+    let etype_info: EtypeInfo2 = rasn::der::decode(&etype_info.value).unwrap();
+    // The client has no aes256-cts-hmac-sha384-192 key.
+    assert_eq!(18, etype_info[0].etype);
+    assert_eq!(Some(string("EXAMPLE.COMuser")), etype_info[0].salt);
+
+    let KdcMessage::AsRep(AsRep(reply)) = kdc.handle(&as_req(b"password", Some(kdc.now.clone())))
+    else {
+        panic!("expected an AS-REP");
+    };
+    assert_eq!(name(1, &["user"]), reply.cname);
+    assert_eq!(Some(1), reply.enc_part.kvno);
+
+    let key = EncryptionKey::from_password(18, b"password", b"EXAMPLE.COMuser", None).unwrap();
+    let EncAsRepPart(enc_part) = reply
+        .enc_part
+        .decrypt(&key, key_usage::AS_REP_ENC_PART)
+        .unwrap();
+    assert_eq!(NONCE, enc_part.nonce);
+    // Session keys use the first supported encryption type.
+    assert_eq!(20, enc_part.key.r#type);
+    assert_eq!(
+        time(1_700_000_000).0 + TimeDelta::hours(10),
+        enc_part.end_time.0
+    );
+
+    let krbtgt = kdc.keytab(&name(2, &["krbtgt", "EXAMPLE.COM"])).unwrap();
+    let ticket_key = &krbtgt.entries[0].key;
+    let ticket: EncTicketPart = reply
+        .ticket
+        .enc_part
+        .decrypt(ticket_key, key_usage::TICKET)
+        .unwrap();
+    assert_eq!(enc_part.key, ticket.key);
+    assert_eq!(name(1, &["user"]), ticket.cname);
+    for (bit, set) in [(9, true), (10, true), (1, false)] {
+        assert_eq!(set, ticket.flags.0[bit], "flag {bit}");
+    }
+}
+
+#[test]
+fn tgs_exchange() {
+    let mut kdc = kdc();
+    let (tgt, session_key) = login(&mut kdc);
+    let subkey = EncryptionKey {
+        r#type: 17,
+        value: OctetString::from(vec![0x42; 16]),
+    };
+
+    let request = tgs_req(tgt, &session_key, Some(subkey.clone()), kdc.now.clone());
+    let KdcMessage::TgsRep(TgsRep(reply)) = kdc.handle(&request) else {
+        panic!("expected a TGS-REP");
+    };
+    let EncTgsRepPart(enc_part) = reply
+        .enc_part
+        .decrypt(&subkey, key_usage::TGS_REP_ENC_PART_SUBKEY)
+        .unwrap();
+    assert_eq!(NONCE, enc_part.nonce);
+    assert_eq!(name(3, &["HTTP", "www.example.com"]), enc_part.sname);
+
+    // The service decrypts the ticket with its keytab.
+    let keytab = kdc.keytab(&name(3, &["HTTP", "www.example.com"])).unwrap();
+    let entry = keytab
+        .entries
+        .iter()
+        .find(|entry| entry.key.r#type == reply.ticket.enc_part.etype)
+        .unwrap();
+    assert_eq!(reply.ticket.enc_part.kvno, Some(entry.kvno));
+    let ticket: EncTicketPart = reply
+        .ticket
+        .enc_part
+        .decrypt(&entry.key, key_usage::TICKET)
+        .unwrap();
+    assert_eq!(enc_part.key, ticket.key);
+    assert_eq!(name(1, &["user"]), ticket.cname);
+    assert!(!ticket.flags.0[9]);
+}
+
+#[test]
+fn errors() {
+    let mut kdc = kdc();
+    assert_eq!(
+        KrbError::KDC_ERR_PREAUTH_FAILED,
+        error_code(kdc.handle(&as_req(b"wrong", Some(kdc.now.clone()))))
+    );
+    assert_eq!(
+        KrbError::KRB_AP_ERR_SKEW,
+        error_code(kdc.handle(&as_req(b"password", Some(time(1_600_000_000)))))
+    );
+
+    let KdcMessage::AsReq(AsReq(mut request)) = as_req(b"password", None) else {
+        unreachable!()
+    };
+    request.req_body.cname = Some(name(1, &["nobody"]));
+    assert_eq!(
+        KrbError::KDC_ERR_C_PRINCIPAL_UNKNOWN,
+        error_code(kdc.handle(&KdcMessage::AsReq(AsReq(request.clone()))))
+    );
+    request.req_body.cname = Some(name(1, &["user"]));
+    request.req_body.sname = Some(name(1, &["nobody"]));
+    assert_eq!(
+        KrbError::KDC_ERR_S_PRINCIPAL_UNKNOWN,
+        error_code(kdc.handle(&KdcMessage::AsReq(AsReq(request.clone()))))
+    );
+    request.req_body.etype = vec![1, 3];
+    request.req_body.sname = Some(name(2, &["krbtgt", "EXAMPLE.COM"]));
+    assert_eq!(
+        KrbError::KDC_ERR_ETYPE_NOSUPP,
+        error_code(kdc.handle(&KdcMessage::AsReq(AsReq(request))))
+    );
+
+    let (tgt, session_key) = login(&mut kdc);
+    let wrong_key = EncryptionKey {
+        r#type: session_key.r#type,
+        value: OctetString::from(vec![0; 32]),
+    };
+    assert_eq!(
+        KrbError::KRB_AP_ERR_BAD_INTEGRITY,
+        error_code(kdc.handle(&tgs_req(tgt.clone(), &wrong_key, None, kdc.now.clone())))
+    );
+
+    kdc.now = KerberosTime(kdc.now.0 + TimeDelta::hours(11));
+    assert_eq!(
+        KrbError::KRB_AP_ERR_TKT_EXPIRED,
+        error_code(kdc.handle(&tgs_req(tgt, &session_key, None, kdc.now.clone())))
+    );
+}
+
+#[test]
+fn transports() {
+    let mut kdc = kdc();
+    let request = as_req(b"password", Some(kdc.now.clone()));
+
+    // A request split across reads is only answered once it's complete.
+    let framed = request.to_tcp().unwrap();
+    assert_eq!(None, kdc.handle_tcp(&framed[..10]));
+    let (reply, length) = kdc.handle_tcp(&framed).unwrap();
+    assert_eq!(framed.len(), length);
+    let (reply, _) = KdcMessage::from_tcp(&reply, usize::MAX).unwrap().unwrap();
+    assert!(matches!(reply, KdcMessage::AsRep(_)));
+
+    let (reply, _) = kdc.handle_tcp(&[0x80, 0, 0, 1, 0]).unwrap();
+    let (reply, _) = KdcMessage::from_tcp(&reply, usize::MAX).unwrap().unwrap();
+    assert_eq!(KrbError::KRB_ERR_FIELD_TOOLONG, error_code(reply));
+
+    let reply = kdc.handle_udp(&request.to_udp().unwrap());
+    assert!(matches!(
+        KdcMessage::from_udp(&reply).unwrap(),
+        KdcMessage::AsRep(_)
+    ));
+
+    // Replies that don't fit in a datagram tell the client to use TCP.
+    kdc.max_udp_length = 100;
+    let reply = kdc.handle_udp(&request.to_udp().unwrap());
+    assert_eq!(
+        KrbError::KRB_ERR_RESPONSE_TOO_BIG,
+        error_code(KdcMessage::from_udp(&reply).unwrap())
+    );
+}
@@ -0,0 +1,88 @@
+use rasn_kerberos::{
+    keytab::{FileError, Keytab, Principal},
+    *,
+};
+
+use pretty_assertions::assert_eq;
+
+// Written by MIT krb5's `krb5_kt_add_entry`, with a deleted entry for
+// user@EXAMPLE.COM kvno 2 left as a hole.
+const KEYTAB: &[u8] = include_bytes!("data/krb5.keytab");
+
+fn principal(realm: &str, r#type: i32, components: &[&str]) -> Principal {
+    Principal::new(
+        KerberosString::try_from(realm.to_string()).unwrap(),
+        PrincipalName {
+            r#type,
+            string: components
+                .iter()
+                .map(|component| KerberosString::try_from(component.to_string()).unwrap())
+                .collect(),
+        },
+    )
+}
+
+#[test]
+fn mit_keytab() {
+    let keytab = Keytab::parse(KEYTAB).unwrap();
+    let http = principal("EXAMPLE.COM", 3, &["HTTP", "www.example.com"]);
+    let user = principal("EXAMPLE.COM", 1, &["user"]);
+
+    assert_eq!(3, keytab.entries.len());
+    assert_eq!(http, keytab.entries[0].principal);
+    assert_eq!("HTTP/www.example.com@EXAMPLE.COM", http.to_string());
+    // MIT stamps entries with the time they were added.
+    assert_eq!(1_792_364_745, keytab.entries[0].timestamp.0.timestamp());
+
+    for (entry, etype) in keytab.entries[..2].iter().zip([18, 17]) {
+        assert_eq!(3, entry.kvno);
+        assert_eq!(etype, entry.key.r#type);
+    }
+
+    // The 32 bit key version number overrides the truncated 8 bit one.
+    let entry = keytab.find(&user, None, 18).unwrap();
+    assert_eq!(300, entry.kvno);
+    assert!(keytab.find(&user, Some(2), 17).is_none());
+    assert!(keytab.find(&http, Some(3), 17).is_some());
+
+    // Entries are written as MIT writes them, without the hole.
+    let bytes = keytab.to_bytes().unwrap();
+    assert_eq!(
+        &KEYTAB[..2 + 4 + 0x57 + 4 + 0x47],
+        &bytes[..2 + 4 + 0x57 + 4 + 0x47]
+    );
+    assert_eq!(keytab, Keytab::parse(&bytes).unwrap());
+}
+
+#[cfg(feature = "crypto")]
+#[test]
+fn mit_keytab_keys() {
+    let keytab = Keytab::parse(KEYTAB).unwrap();
+    let http = principal("EXAMPLE.COM", 3, &["HTTP", "www.example.com"]);
+    let user = principal("EXAMPLE.COM", 1, &["user"]);
+
+    let salt = http.name.salt(&http.realm);
+    for (entry, etype) in keytab.entries[..2].iter().zip([18, 17]) {
+        assert_eq!(
+            EncryptionKey::from_password(etype, b"secret", &salt, None).unwrap(),
+            entry.key
+        );
+    }
+    assert_eq!(
+        EncryptionKey::from_password(18, b"password", b"EXAMPLE.COMuser", None).unwrap(),
+        keytab.find(&user, None, 18).unwrap().key
+    );
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        Keytab::parse(&[0x05, 0x01]),
+        Err(FileError::UnsupportedVersion(0x0501))
+    ));
+    assert!(matches!(
+        Keytab::parse(&KEYTAB[..KEYTAB.len() - 1]),
+        Err(FileError::UnexpectedEof)
+    ));
+    assert_eq!(Keytab::default(), Keytab::parse(&[0x05, 0x02]).unwrap());
+}
@@ -0,0 +1,167 @@
+#[cfg(feature = "crypto")]
+use rasn_kerberos::crypto::CryptoError;
+use rasn_kerberos::{
+    pac::{Pac, PacBuffer, PacClientInfo, PacError, PacSignature, Sid, UpnDnsInfo},
+    *,
+};
+
+use pretty_assertions::assert_eq;
+
+// A PAC for user@EXAMPLE.COM built and signed by MIT krb5's `krb5_pac_sign`,
+// with an opaque logon info buffer and an extended UPN_DNS_INFO buffer laid
+// out as Windows does. MIT adds the client info and both signatures.
+const PAC: &[u8] = include_bytes!("data/pac/pac");
+// The PAC inside AD-IF-RELEVANT, followed by an AD-LOGIN-ALIAS and a local
+// (negative) type.
+const AUTHORIZATION_DATA: &[u8] = include_bytes!("data/pac/authorization-data");
+
+#[cfg(feature = "crypto")]
+fn key(first: u8) -> EncryptionKey {
+    EncryptionKey {
+        r#type: 18,
+        value: (first..first + 32).collect::<Vec<u8>>().into(),
+    }
+}
+
+#[test]
+fn mit_pac() {
+    let pac = Pac::parse(PAC).unwrap();
+    assert_eq!(0, pac.version);
+    assert_eq!(
+        vec![
+            PacBuffer::LOGON_INFO,
+            PacBuffer::UPN_DNS_INFO,
+            PacBuffer::CLIENT_INFO,
+            PacBuffer::SERVER_CHECKSUM,
+            PacBuffer::PRIVSVR_CHECKSUM,
+        ],
+        pac.buffers
+            .iter()
+            .map(|buffer| buffer.r#type)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(PAC, pac.to_bytes());
+    assert_eq!(
+        (1..41).collect::<Vec<u8>>(),
+        pac.buffer(PacBuffer::LOGON_INFO).unwrap()
+    );
+
+    assert_eq!(
+        PacClientInfo {
+            client_id: chrono::DateTime::from_timestamp(1_760_788_800, 0).unwrap(),
+            name: "user".into(),
+        },
+        pac.client_info().unwrap()
+    );
+    assert_eq!(
+        pac.buffer(PacBuffer::CLIENT_INFO).unwrap(),
+        pac.client_info().unwrap().to_bytes()
+    );
+
+    let upn_dns_info = pac.upn_dns_info().unwrap();
+    assert_eq!("user@example.com", upn_dns_info.upn);
+    assert_eq!("EXAMPLE.COM", upn_dns_info.dns_domain_name);
+    assert_eq!(UpnDnsInfo::EXTENDED, upn_dns_info.flags);
+    assert_eq!(Some("user"), upn_dns_info.sam_name.as_deref());
+    assert_eq!(
+        "S-1-5-21-1-2-3-1104",
+        upn_dns_info.sid.as_ref().unwrap().to_string()
+    );
+    assert_eq!(
+        pac.buffer(PacBuffer::UPN_DNS_INFO).unwrap(),
+        upn_dns_info.to_bytes()
+    );
+
+    let signature = pac.server_signature().unwrap();
+    assert_eq!(16, signature.r#type);
+    assert_eq!(12, signature.signature.len());
+    assert_eq!(None, signature.rodc_identifier);
+    assert!(matches!(
+        pac.requestor(),
+        Err(PacError::MissingBuffer(PacBuffer::REQUESTOR))
+    ));
+}
+
+#[cfg(feature = "crypto")]
+#[test]
+fn signatures() {
+    let (server_key, kdc_key) = (key(0), key(32));
+    let pac = Pac::parse(PAC).unwrap();
+    pac.verify(&server_key).unwrap();
+    pac.verify_kdc(&kdc_key).unwrap();
+    assert!(matches!(
+        pac.verify(&kdc_key),
+        Err(PacError::Crypto(CryptoError::IntegrityCheckFailed))
+    ));
+
+    let mut tampered = pac.clone();
+    tampered.buffers[0].data[0] ^= 1;
+    assert!(tampered.verify(&server_key).is_err());
+    // The KDC signature only covers the server signature.
+    tampered.verify_kdc(&kdc_key).unwrap();
+
+    // Re-signing reproduces MIT's signatures.
+    let mut resigned = pac.clone();
+    resigned.sign(&server_key, &kdc_key).unwrap();
+    assert_eq!(pac, resigned);
+
+    let mut unsigned = Pac::new(vec![pac.buffers[0].clone(), pac.buffers[2].clone()]);
+    unsigned.sign(&server_key, &kdc_key).unwrap();
+    assert_eq!(pac.buffers.len() - 1, unsigned.buffers.len());
+    Pac::parse(&unsigned.to_bytes())
+        .unwrap()
+        .verify(&server_key)
+        .unwrap();
+    unsigned.verify_kdc(&kdc_key).unwrap();
+}
+
+#[test]
+fn find_in_authorization_data() {
+    let authorization_data: AuthorizationData = rasn::der::decode(AUTHORIZATION_DATA).unwrap();
+    assert_eq!(
+        Pac::parse(PAC).unwrap(),
+        Pac::find(&authorization_data).unwrap().unwrap()
+    );
+    assert!(Pac::find(&authorization_data[1..].to_vec()).is_none());
+}
+
+#[test]
+fn buffers() {
+    let sid = Sid {
+        revision: 1,
+        identifier_authority: 5,
+        sub_authorities: vec![18],
+    };
+    assert_eq!("S-1-5-18", sid.to_string());
+    assert_eq!(vec![1, 1, 0, 0, 0, 0, 0, 5, 18, 0, 0, 0], sid.to_bytes());
+    assert_eq!(sid, Sid::parse(&sid.to_bytes()).unwrap());
+
+    let info = UpnDnsInfo {
+        upn: "user@example.com".into(),
+        dns_domain_name: "EXAMPLE.COM".into(),
+        flags: 0,
+        sam_name: None,
+        sid: None,
+    };
+    let encoded = info.to_bytes();
+    // Without the extended fields, the strings start after 12 bytes of
+    // header and 4 of padding.
+    assert_eq!([32, 0, 16, 0], encoded[..4]);
+    assert_eq!(info, UpnDnsInfo::parse(&encoded).unwrap());
+
+    let signature = PacSignature {
+        rodc_identifier: Some(7),
+        ..PacSignature::new(16, vec![0xaa; 12])
+    };
+    assert_eq!(18, signature.to_bytes().len());
+    assert_eq!(
+        signature,
+        PacSignature::parse(&signature.to_bytes()).unwrap()
+    );
+
+    assert!(matches!(Pac::parse(&PAC[..100]), Err(PacError::Malformed)));
+    assert!(matches!(
+        UpnDnsInfo::parse(&encoded[..8]),
+        Err(PacError::Malformed)
+    ));
+}
@@ -0,0 +1,279 @@
+#![cfg(feature = "pkinit")]
+
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use rasn::prelude::*;
+use rasn_cms::{
+    algorithms,
+    enveloped::{ContentCipher, KeyTransportDecryptor},
+    signed::Digester,
+    AlgorithmIdentifier, Certificate, ContentInfo, IssuerAndSerialNumber, RecipientInfo,
+    SignedData,
+};
+use rasn_kerberos::{pkinit::*, *};
+use rasn_pkix::{validation::SignatureVerifier, SubjectPublicKeyInfo};
+use rsa::{
+    pkcs1::DecodeRsaPublicKey, pkcs8::DecodePrivateKey, Pkcs1v15Encrypt, Pkcs1v15Sign,
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+
+use pretty_assertions::assert_eq;
+
+// MIT krb5's PKINIT plugin builds its CMS structures with OpenSSL, so these
+// were produced the same way: the PKINIT structures were DER encoded
+// independently of this crate, then signed with `openssl cms -sign -nodetach
+// -econtent_type`, or encrypted for the client with `openssl cms -encrypt`.
+// OpenSSL always labels enveloped content as id-data, so the fixture has been
+// patched to the id-signedData required by RFC 4556. The client and KDC
+// certificates carry the PKINIT extended key usages and principal names.
+const AS_REQ: &[u8] = include_bytes!("data/pkinit/as-req");
+const PA_PK_AS_REP_DH: &[u8] = include_bytes!("data/pkinit/pa-pk-as-rep-dh");
+const PA_PK_AS_REP_ENC_KEY_PACK: &[u8] = include_bytes!("data/pkinit/pa-pk-as-rep-enc-key-pack");
+const CLIENT_KEY: &[u8] = include_bytes!("data/pkinit/client-key");
+
+struct Crypto;
+
+impl Digester for Crypto {
+    fn digest(&self, algorithm: &AlgorithmIdentifier, data: &[u8]) -> Option<Vec<u8>> {
+        (algorithm.algorithm == *algorithms::SHA256).then(|| Sha256::digest(data).to_vec())
+    }
+}
+
+impl SignatureVerifier for Crypto {
+    fn verify(
+        &self,
+        public_key: &SubjectPublicKeyInfo,
+        _: &AlgorithmIdentifier,
+        message: &[u8],
+        signature: &[u8],
+    ) -> bool {
+        let key =
+            RsaPublicKey::from_pkcs1_der(public_key.subject_public_key.as_raw_slice()).unwrap();
+        key.verify(
+            Pkcs1v15Sign::new::<Sha256>(),
+            &Sha256::digest(message),
+            signature,
+        )
+        .is_ok()
+    }
+}
+
+impl KeyTransportDecryptor for Crypto {
+    fn decrypt_key(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        encrypted_key: &[u8],
+    ) -> Option<Vec<u8>> {
+        (algorithm.algorithm == *algorithms::RSA).then_some(())?;
+        RsaPrivateKey::from_pkcs8_der(CLIENT_KEY)
+            .unwrap()
+            .decrypt(Pkcs1v15Encrypt, encrypted_key)
+            .ok()
+    }
+}
+
+impl ContentCipher for Crypto {
+    fn encrypt(&self, _: &AlgorithmIdentifier, _: &[u8], _: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn decrypt(
+        &self,
+        algorithm: &AlgorithmIdentifier,
+        key: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>> {
+        (algorithm.algorithm == *algorithms::AES256_CBC).then_some(())?;
+        let iv: OctetString = rasn::der::decode(algorithm.parameters.as_ref()?.as_bytes()).ok()?;
+        cbc::Decryptor::<aes::Aes256>::new_from_slices(key, &iv)
+            .ok()?
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .ok()
+    }
+}
+
+fn string(value: &str) -> KerberosString {
+    KerberosString::try_from(value.to_string()).unwrap()
+}
+
+/// Verifies the signed data in `content_info`, a DER encoded `ContentInfo`.
+fn verify(content_info: &[u8]) {
+    let content_info: ContentInfo = rasn::der::decode(content_info).unwrap();
+    SignedData::verify(content_info.content.as_bytes(), None, &Crypto, &Crypto).unwrap();
+}
+
+fn signer(signed_data: &SignedData) -> &Certificate {
+    signed_data
+        .find_certificate(&signed_data.signer_infos.first().unwrap().sid)
+        .unwrap()
+}
+
+/// Checks that the subject alternative name of `certificate` holds `name`.
+fn assert_principal(certificate: &Certificate, name: &Krb5PrincipalName) {
+    let extensions = certificate.tbs_certificate.extensions.as_ref().unwrap();
+    let san = extensions
+        .iter()
+        .find(|extension| extension.extn_id == *rasn_pkix::extensions::SUBJECT_ALT_NAME)
+        .unwrap();
+    let encoded = rasn::der::encode(name).unwrap();
+    assert!(san
+        .extn_value
+        .windows(encoded.len())
+        .any(|window| window == encoded));
+}
+
+#[test]
+fn pa_pk_as_req() {
+    let as_req: AsReq = rasn::der::decode(AS_REQ).unwrap();
+    assert_eq!(AS_REQ, rasn::der::encode(&as_req).unwrap());
+    let padata = &as_req.0.padata.as_ref().unwrap()[0];
+    assert_eq!(PaData::PK_AS_REQ, padata.r#type);
+    let pa_pk_as_req: PaPkAsReq = rasn::der::decode(&padata.value).unwrap();
+    assert_eq!(&padata.value[..], rasn::der::encode(&pa_pk_as_req).unwrap());
+
+    let kdc_pk_id: IssuerAndSerialNumber =
+        rasn::der::decode(pa_pk_as_req.kdc_pk_id.as_ref().unwrap()).unwrap();
+    assert_eq!(Integer::from(0x12), kdc_pk_id.serial_number);
+    let certifier = &pa_pk_as_req.trusted_certifiers.as_ref().unwrap()[0];
+    let ca: IssuerAndSerialNumber =
+        rasn::der::decode(certifier.issuer_and_serial_number.as_ref().unwrap()).unwrap();
+    assert_eq!(kdc_pk_id.issuer, ca.issuer);
+    assert_eq!(
+        *certifier,
+        ExternalPrincipalIdentifier::from_issuer_and_serial_number(&ca).unwrap()
+    );
+
+    verify(&pa_pk_as_req.signed_auth_pack);
+    let signed_data = pa_pk_as_req.signed_data().unwrap();
+    assert_eq!(
+        pa_pk_as_req.signed_auth_pack,
+        PaPkAsReq::new(&signed_data).unwrap().signed_auth_pack
+    );
+    assert_principal(
+        signer(&signed_data),
+        &Krb5PrincipalName {
+            realm: string("EXAMPLE.COM"),
+            principal_name: PrincipalName {
+                r#type: 1,
+                string: vec![string("user")],
+            },
+        },
+    );
+
+    let auth_pack = AuthPack::from_signed_data(&signed_data).unwrap();
+    let body = &as_req.0.req_body;
+    let authenticator = &auth_pack.pk_authenticator;
+    assert_eq!(body.nonce, authenticator.nonce);
+    assert_eq!(
+        PkAuthenticator::checksum(body).unwrap(),
+        authenticator.pa_checksum.clone().unwrap()
+    );
+    assert_eq!(
+        *authenticator,
+        PkAuthenticator::new(
+            authenticator.cusec.clone(),
+            authenticator.ctime.clone(),
+            body
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Oid::ISO_MEMBER_BODY_US_ANSI_X942_NUMBER_TYPE_PUBLIC,
+        &auth_pack.client_public_value.unwrap().algorithm.algorithm
+    );
+    assert_eq!(
+        (0..32).collect::<Vec<u8>>(),
+        auth_pack.client_dh_nonce.unwrap().to_vec()
+    );
+    assert_eq!(
+        vec![
+            KdfAlgorithmId::new(KDF_SHA256.into()),
+            KdfAlgorithmId::new(KDF_SHA1.into()),
+            KdfAlgorithmId::new(KDF_SHA512.into()),
+        ],
+        auth_pack.supported_kdfs.unwrap()
+    );
+}
+
+#[test]
+fn pa_pk_as_rep_dh_info() {
+    let reply: PaPkAsRep = rasn::der::decode(PA_PK_AS_REP_DH).unwrap();
+    assert_eq!(PA_PK_AS_REP_DH, rasn::der::encode(&reply).unwrap());
+    assert!(reply.enveloped_data().is_none());
+    let PaPkAsRep::DhInfo(dh_info) = reply else {
+        panic!("expected DH info");
+    };
+    assert_eq!(Some(KdfAlgorithmId::new(KDF_SHA256.into())), dh_info.kdf);
+
+    verify(&dh_info.dh_signed_data);
+    let signed_data = dh_info.signed_data().unwrap();
+    assert_principal(
+        signer(&signed_data),
+        &Krb5PrincipalName {
+            realm: string("EXAMPLE.COM"),
+            principal_name: PrincipalName {
+                r#type: 2,
+                string: vec![string("krbtgt"), string("EXAMPLE.COM")],
+            },
+        },
+    );
+
+    let key_info = KdcDhKeyInfo::from_signed_data(&signed_data).unwrap();
+    let as_req: AsReq = rasn::der::decode(AS_REQ).unwrap();
+    assert_eq!(as_req.0.req_body.nonce, key_info.nonce);
+    // The public value is the DER encoded integer y.
+    assert_eq!(0x02, key_info.subject_public_key.as_raw_slice()[0]);
+
+    assert!(matches!(
+        AuthPack::from_signed_data(&signed_data),
+        Err(PkinitError::UnexpectedContentType(oid)) if oid == *DH_KEY_DATA
+    ));
+}
+
+#[test]
+fn pa_pk_as_rep_enc_key_pack() {
+    let reply: PaPkAsRep = rasn::der::decode(PA_PK_AS_REP_ENC_KEY_PACK).unwrap();
+    assert_eq!(
+        PA_PK_AS_REP_ENC_KEY_PACK,
+        rasn::der::encode(&reply).unwrap()
+    );
+    let enveloped_data = reply.enveloped_data().unwrap().unwrap();
+    assert_eq!(reply, PaPkAsRep::enc_key_pack(&enveloped_data).unwrap());
+    assert_eq!(
+        *rasn_cms::CONTENT_SIGNED_DATA,
+        enveloped_data.encrypted_content_info.content_type
+    );
+
+    let Some(RecipientInfo::KeyTransRecipientInfo(recipient)) =
+        enveloped_data.recipient_infos.first().cloned()
+    else {
+        panic!("expected a key transport recipient");
+    };
+    let cek = recipient.decrypt_key(&Crypto).unwrap();
+    let encoded = enveloped_data.decrypt(&cek, &Crypto).unwrap();
+    SignedData::verify(&encoded, None, &Crypto, &Crypto).unwrap();
+    let signed_data: SignedData = rasn::der::decode(&encoded).unwrap();
+
+    let key_pack = ReplyKeyPack::from_signed_data(&signed_data).unwrap();
+    assert_eq!(18, key_pack.reply_key.r#type);
+    assert_eq!(
+        (0x40..0x60).collect::<Vec<u8>>(),
+        key_pack.reply_key.value.to_vec()
+    );
+    assert_eq!(16, key_pack.as_checksum.r#type);
+}
+
+#[test]
+fn reply_key_pack_checksum() {
+    let reply_key = EncryptionKey {
+        r#type: 18,
+        value: OctetString::from((0x40..0x60).collect::<Vec<u8>>()),
+    };
+    let key_pack = ReplyKeyPack::new(reply_key, AS_REQ).unwrap();
+    assert_eq!(16, key_pack.as_checksum.r#type);
+    key_pack.verify(AS_REQ).unwrap();
+    assert!(key_pack.verify(&AS_REQ[1..]).is_err());
+
+    let encoded = rasn::der::encode(&key_pack).unwrap();
+    assert_eq!(key_pack, rasn::der::decode(&encoded).unwrap());
+}
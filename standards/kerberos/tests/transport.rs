@@ -0,0 +1,106 @@
+use rasn::prelude::*;
+use rasn_kerberos::{transport::FramingError, *};
+
+use pretty_assertions::assert_eq;
+
+fn string(value: &str) -> KerberosString {
+    KerberosString::try_from(value.to_string()).unwrap()
+}
+
+fn krb_error(error_code: i32) -> KdcMessage {
+    KdcMessage::KrbError(KrbError {
+        pvno: 5.into(),
+        msg_type: 30.into(),
+        ctime: None,
+        cusec: None,
+        stime: KerberosTime(
+            chrono::DateTime::from_timestamp(1_700_000_000, 0)
+                .unwrap()
+                .into(),
+        ),
+        susec: 0.into(),
+        error_code,
+        crealm: None,
+        cname: None,
+        realm: string("EXAMPLE.COM"),
+        sname: PrincipalName {
+            r#type: 2,
+            string: vec![string("krbtgt"), string("EXAMPLE.COM")],
+        },
+        e_text: None,
+        e_data: None,
+    })
+}
+
+#[test]
+fn dispatch() {
+    let message = krb_error(KrbError::KDC_ERR_PREAUTH_REQUIRED);
+    let encoded = message.to_udp().unwrap();
+    // [APPLICATION 30]
+    assert_eq!(0x7e, encoded[0]);
+    assert_eq!(encoded, rasn::der::encode(&message).unwrap());
+    assert_eq!(message, KdcMessage::from_udp(&encoded).unwrap());
+
+    let ap_rep = ApRep {
+        pvno: 5.into(),
+        msg_type: 15.into(),
+        enc_part: EncryptedData {
+            etype: 18,
+            kvno: None,
+            cipher: OctetString::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+        },
+    };
+    let encoded = rasn::der::encode(&ap_rep).unwrap();
+    assert_eq!(
+        KdcMessage::ApRep(ap_rep),
+        KdcMessage::from_udp(&encoded).unwrap()
+    );
+
+    // An [APPLICATION 3] EncTicketPart isn't a top-level message.
+    assert!(matches!(
+        KdcMessage::from_udp(&[0x63, 0x02, 0x30, 0x00]),
+        Err(FramingError::Decode(_))
+    ));
+}
+
+#[test]
+fn tcp_framing() {
+    let first = krb_error(KrbError::KDC_ERR_C_PRINCIPAL_UNKNOWN);
+    let second = krb_error(KrbError::KDC_ERR_S_PRINCIPAL_UNKNOWN);
+    let first_framed = first.to_tcp().unwrap();
+    let length = first_framed.len() - 4;
+    assert_eq!(&(length as u32).to_be_bytes(), &first_framed[..4]);
+
+    let mut stream = first_framed.clone();
+    stream.extend(second.to_tcp().unwrap());
+    for end in 0..first_framed.len() {
+        assert!(KdcMessage::from_tcp(&stream[..end], usize::MAX)
+            .unwrap()
+            .is_none());
+    }
+
+    let (message, read) = KdcMessage::from_tcp(&stream, usize::MAX).unwrap().unwrap();
+    assert_eq!(first, message);
+    assert_eq!(first_framed.len(), read);
+    let (message, _) = KdcMessage::from_tcp(&stream[read..], usize::MAX)
+        .unwrap()
+        .unwrap();
+    assert_eq!(second, message);
+}
+
+#[test]
+fn tcp_errors() {
+    assert!(matches!(
+        KdcMessage::from_tcp(&[0x80, 0, 0, 0], usize::MAX),
+        Err(FramingError::ReservedBit)
+    ));
+    // Too long messages are rejected before they've been received.
+    assert!(matches!(
+        KdcMessage::from_tcp(&[0, 1, 0, 0], 0xffff),
+        Err(FramingError::TooLong(0x10000))
+    ));
+    assert!(matches!(
+        KdcMessage::from_tcp(&[0, 0, 0, 2, 0x30, 0x00], usize::MAX),
+        Err(FramingError::Decode(_))
+    ));
+}
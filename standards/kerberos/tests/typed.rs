@@ -0,0 +1,113 @@
+use rasn::prelude::*;
+use rasn_kerberos::{
+    pac::Pac,
+    typed::{TypedAuthorizationData, TypedPaData},
+    *,
+};
+
+use pretty_assertions::assert_eq;
+
+// An ETYPE-INFO2, an empty ENC-TIMESTAMP hint, a PA-PAC-REQUEST and an empty
+// PA-FX-FAST hint, as a KDC might send in METHOD-DATA.
+const METHOD_DATA: &[u8] = include_bytes!("data/pac/method-data");
+const AUTHORIZATION_DATA: &[u8] = include_bytes!("data/pac/authorization-data");
+const PAC: &[u8] = include_bytes!("data/pac/pac");
+
+fn string(value: &str) -> KerberosString {
+    KerberosString::try_from(value.to_string()).unwrap()
+}
+
+#[test]
+fn pa_data() {
+    let method_data: MethodData = rasn::der::decode(METHOD_DATA).unwrap();
+    let etype_info2 = method_data[0].decode_typed().unwrap();
+    assert_eq!(
+        TypedPaData::EtypeInfo2(vec![
+            EtypeInfo2Entry {
+                etype: 18,
+                salt: Some(string("EXAMPLE.COMuser")),
+                s2kparams: None,
+            },
+            EtypeInfo2Entry {
+                etype: 17,
+                salt: None,
+                s2kparams: None,
+            },
+        ]),
+        etype_info2
+    );
+    assert_eq!(PaData::ETYPE_INFO2, etype_info2.r#type());
+
+    // An empty ENC-TIMESTAMP is only a hint, and isn't an EncryptedData.
+    assert_eq!(
+        TypedPaData::Opaque(method_data[1].clone()),
+        method_data[1].decode_typed().unwrap()
+    );
+    assert_eq!(
+        TypedPaData::PacRequest(PaPacRequest { include_pac: true }),
+        method_data[2].decode_typed().unwrap()
+    );
+    assert_eq!(
+        TypedPaData::Opaque(method_data[3].clone()),
+        method_data[3].decode_typed().unwrap()
+    );
+
+    for pa_data in &method_data {
+        assert_eq!(*pa_data, pa_data.decode_typed().unwrap().encode().unwrap());
+    }
+}
+
+#[cfg(feature = "pkinit")]
+#[test]
+fn pkinit_pa_data() {
+    let as_req: AsReq = rasn::der::decode(include_bytes!("data/pkinit/as-req")).unwrap();
+    let pa_data = &as_req.0.padata.unwrap()[0];
+    let typed = pa_data.decode_typed().unwrap();
+    assert!(matches!(typed, TypedPaData::PkAsReq(_)));
+    assert_eq!(*pa_data, typed.encode().unwrap());
+}
+
+#[test]
+fn authorization_data() {
+    let authorization_data: AuthorizationData = rasn::der::decode(AUTHORIZATION_DATA).unwrap();
+    let typed = TypedAuthorizationData::decode_all(&authorization_data).unwrap();
+
+    let TypedAuthorizationData::IfRelevant(if_relevant) = &typed[0] else {
+        panic!("expected AD-IF-RELEVANT");
+    };
+    assert_eq!(
+        TypedAuthorizationData::Win2kPac(Pac::parse(PAC).unwrap()),
+        if_relevant[0].decode_typed().unwrap()
+    );
+    assert_eq!(
+        TypedAuthorizationData::LoginAlias(AdLoginAlias::new(vec![PrincipalName {
+            r#type: 1,
+            string: vec![string("alias")],
+        }])),
+        typed[1]
+    );
+    assert_eq!(
+        TypedAuthorizationData::Opaque(AuthorizationDataValue {
+            r#type: -1,
+            data: OctetString::from_static(b"local"),
+        }),
+        typed[2]
+    );
+    assert_eq!(-1, typed[2].r#type());
+
+    let encoded = typed
+        .iter()
+        .map(|element| element.encode().unwrap())
+        .collect::<AuthorizationData>();
+    assert_eq!(authorization_data, encoded);
+    assert_eq!(
+        if_relevant[0],
+        if_relevant[0].decode_typed().unwrap().encode().unwrap()
+    );
+
+    let truncated = AuthorizationDataValue {
+        r#type: AuthorizationDataValue::WIN2K_PAC,
+        data: OctetString::from_static(&[1, 0, 0, 0]),
+    };
+    assert!(truncated.decode_typed().is_err());
+}
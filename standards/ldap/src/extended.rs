@@ -0,0 +1,423 @@
+//! # Typed Extended Operations
+//!
+//! [`ExtendedRequest`], [`ExtendedResponse`] and [`IntermediateResponse`]
+//! carry values whose type is determined by the operation's name. This module
+//! defines the StartTLS ([RFC 4511]), Password Modify ([RFC 3062]) and
+//! Who am I? ([RFC 4532]) operations, and the Content Synchronization
+//! ([RFC 4533]) intermediate response, and converts them to and from the
+//! generic messages.
+//!
+//! Password Modify and Who am I? responses don't carry a name, so decoding a
+//! response needs the name of the request it answers.
+//!
+//! ```
+//! use rasn_ldap::{extended::KnownExtendedRequest, ExtendedRequest};
+//!
+//! let request = ExtendedRequest::from_known(&KnownExtendedRequest::WhoAmI).unwrap();
+//! assert_eq!(None, request.request_value);
+//! assert_eq!(Some(KnownExtendedRequest::WhoAmI), request.decode_known().unwrap());
+//! ```
+//!
+//! [RFC 4511]: https://datatracker.ietf.org/doc/html/rfc4511#section-4.14
+//! [RFC 3062]: https://datatracker.ietf.org/doc/html/rfc3062
+//! [RFC 4532]: https://datatracker.ietf.org/doc/html/rfc4532
+//! [RFC 4533]: https://datatracker.ietf.org/doc/html/rfc4533
+
+use rasn::{
+    de::Error as _,
+    error::{DecodeError, EncodeError},
+    types::*,
+    Decode, Encode,
+};
+
+use crate::{
+    controls::SyncInfoValue, ExtendedRequest, ExtendedResponse, IntermediateResponse, LdapResult,
+    LdapString,
+};
+
+pub const START_TLS: &str = "1.3.6.1.4.1.1466.20037";
+pub const PASSWORD_MODIFY: &str = "1.3.6.1.4.1.4203.1.11.1";
+pub const WHO_AM_I: &str = "1.3.6.1.4.1.4203.1.11.3";
+/// The name of the unsolicited notification a server sends before closing a
+/// connection.
+pub const NOTICE_OF_DISCONNECTION: &str = "1.3.6.1.4.1.1466.20036";
+/// The name of the Content Synchronization intermediate response.
+pub const SYNC_INFO: &str = "1.3.6.1.4.1.4203.1.9.1.4";
+
+/// An extended request known to this crate.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KnownExtendedRequest {
+    StartTls,
+    PasswordModify(PasswordModifyRequest),
+    WhoAmI,
+}
+
+impl KnownExtendedRequest {
+    /// The name of the operation.
+    pub fn request_name(&self) -> &'static str {
+        match self {
+            Self::StartTls => START_TLS,
+            Self::PasswordModify(_) => PASSWORD_MODIFY,
+            Self::WhoAmI => WHO_AM_I,
+        }
+    }
+
+    /// Decodes the `value` of the request named `request_name`. Returns
+    /// `Ok(None)` if the operation isn't known.
+    pub fn decode(request_name: &[u8], value: Option<&[u8]>) -> Result<Option<Self>, DecodeError> {
+        Ok(Some(match request_name {
+            name if name == START_TLS.as_bytes() => {
+                no_value(value)?;
+                Self::StartTls
+            }
+            name if name == PASSWORD_MODIFY.as_bytes() => Self::PasswordModify(
+                value
+                    .map(rasn::ber::decode)
+                    .transpose()?
+                    .unwrap_or_default(),
+            ),
+            name if name == WHO_AM_I.as_bytes() => {
+                no_value(value)?;
+                Self::WhoAmI
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Encodes the value of the request with BER, returning `None` if the
+    /// request has no value.
+    pub fn encode(&self) -> Result<Option<OctetString>, EncodeError> {
+        Ok(match self {
+            Self::StartTls | Self::WhoAmI => None,
+            // The value is absent if none of its fields are present.
+            Self::PasswordModify(value) if *value == PasswordModifyRequest::default() => None,
+            Self::PasswordModify(value) => Some(rasn::ber::encode(value)?.into()),
+        })
+    }
+}
+
+impl ExtendedRequest {
+    /// Creates a new request from a typed value.
+    pub fn from_known(value: &KnownExtendedRequest) -> Result<Self, EncodeError> {
+        Ok(Self {
+            request_name: value.request_name().into(),
+            request_value: value.encode()?,
+        })
+    }
+
+    /// Decodes the request's value, returning `Ok(None)` if the operation
+    /// isn't known.
+    pub fn decode_known(&self) -> Result<Option<KnownExtendedRequest>, DecodeError> {
+        KnownExtendedRequest::decode(&self.request_name, self.request_value.as_deref())
+    }
+}
+
+/// An extended response known to this crate.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KnownExtendedResponse {
+    StartTls,
+    PasswordModify(PasswordModifyResponse),
+    /// The client's authorization identity, such as `dn:uid=jdoe,dc=org`, or
+    /// empty if the client is anonymous.
+    WhoAmI(LdapString),
+}
+
+impl KnownExtendedResponse {
+    /// The name of the operation the response answers.
+    pub fn request_name(&self) -> &'static str {
+        match self {
+            Self::StartTls => START_TLS,
+            Self::PasswordModify(_) => PASSWORD_MODIFY,
+            Self::WhoAmI(_) => WHO_AM_I,
+        }
+    }
+
+    /// The name carried by the response, which only StartTLS has.
+    pub fn response_name(&self) -> Option<&'static str> {
+        match self {
+            Self::StartTls => Some(START_TLS),
+            Self::PasswordModify(_) | Self::WhoAmI(_) => None,
+        }
+    }
+
+    /// Decodes the `value` of the response to the request named
+    /// `request_name`. Returns `Ok(None)` if the operation isn't known.
+    pub fn decode(request_name: &[u8], value: Option<&[u8]>) -> Result<Option<Self>, DecodeError> {
+        Ok(Some(match request_name {
+            name if name == START_TLS.as_bytes() => {
+                no_value(value)?;
+                Self::StartTls
+            }
+            name if name == PASSWORD_MODIFY.as_bytes() => Self::PasswordModify(
+                value
+                    .map(rasn::ber::decode)
+                    .transpose()?
+                    .unwrap_or_default(),
+            ),
+            // The authorization identity isn't BER encoded.
+            name if name == WHO_AM_I.as_bytes() => {
+                Self::WhoAmI(value.map(OctetString::copy_from_slice).unwrap_or_default())
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Encodes the value of the response, returning `None` if the response
+    /// has no value.
+    pub fn encode(&self) -> Result<Option<OctetString>, EncodeError> {
+        Ok(match self {
+            Self::StartTls => None,
+            Self::PasswordModify(value) if *value == PasswordModifyResponse::default() => None,
+            Self::PasswordModify(value) => Some(rasn::ber::encode(value)?.into()),
+            Self::WhoAmI(authz_id) => Some(authz_id.clone()),
+        })
+    }
+}
+
+impl ExtendedResponse {
+    /// Creates a new response with `result` from a typed value.
+    pub fn from_known(
+        result: LdapResult,
+        value: &KnownExtendedResponse,
+    ) -> Result<Self, EncodeError> {
+        Ok(Self {
+            result_code: result.result_code,
+            matched_dn: result.matched_dn,
+            diagnostic_message: result.diagnostic_message,
+            referral: result.referral,
+            response_name: value.response_name().map(Into::into),
+            response_value: value.encode()?,
+        })
+    }
+
+    /// Decodes the value of the response to the request named
+    /// `request_name`, returning `Ok(None)` if the operation isn't known. The
+    /// response's own name, if present, takes precedence.
+    pub fn decode_known(
+        &self,
+        request_name: &[u8],
+    ) -> Result<Option<KnownExtendedResponse>, DecodeError> {
+        KnownExtendedResponse::decode(
+            self.response_name.as_deref().unwrap_or(request_name),
+            self.response_value.as_deref(),
+        )
+    }
+}
+
+/// An intermediate response known to this crate.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KnownIntermediateResponse {
+    SyncInfo(SyncInfoValue),
+}
+
+impl KnownIntermediateResponse {
+    /// The name of the response.
+    pub fn response_name(&self) -> &'static str {
+        match self {
+            Self::SyncInfo(_) => SYNC_INFO,
+        }
+    }
+
+    /// Decodes the BER encoded `value` of the response named
+    /// `response_name`. Returns `Ok(None)` if the response isn't known.
+    pub fn decode(response_name: &[u8], value: &[u8]) -> Result<Option<Self>, DecodeError> {
+        if response_name == SYNC_INFO.as_bytes() {
+            return rasn::ber::decode(value).map(|v| Some(Self::SyncInfo(v)));
+        }
+
+        Ok(None)
+    }
+
+    /// Encodes the value of the response with BER.
+    pub fn encode(&self) -> Result<OctetString, EncodeError> {
+        match self {
+            Self::SyncInfo(value) => rasn::ber::encode(value).map(Into::into),
+        }
+    }
+}
+
+impl IntermediateResponse {
+    /// Creates a new response from a typed value.
+    pub fn from_known(value: &KnownIntermediateResponse) -> Result<Self, EncodeError> {
+        Ok(Self {
+            response_name: Some(value.response_name().into()),
+            response_value: Some(value.encode()?),
+        })
+    }
+
+    /// Decodes the response's value, returning `Ok(None)` if the response is
+    /// unnamed or isn't known.
+    pub fn decode_known(&self) -> Result<Option<KnownIntermediateResponse>, DecodeError> {
+        match &self.response_name {
+            Some(name) => KnownIntermediateResponse::decode(
+                name,
+                self.response_value.as_deref().unwrap_or_default(),
+            ),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The value of a Password Modify request. An absent `user_identity` changes
+/// the password of the bound user, and an absent `new_password` asks the
+/// server to generate one.
+#[derive(AsnType, Encode, Decode, Debug, Default, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[non_exhaustive]
+pub struct PasswordModifyRequest {
+    #[rasn(tag(0))]
+    pub user_identity: Option<OctetString>,
+    #[rasn(tag(1))]
+    pub old_password: Option<OctetString>,
+    #[rasn(tag(2))]
+    pub new_password: Option<OctetString>,
+}
+
+impl PasswordModifyRequest {
+    /// PasswordModifyRequest constructor
+    pub fn new(
+        user_identity: Option<OctetString>,
+        old_password: Option<OctetString>,
+        new_password: Option<OctetString>,
+    ) -> Self {
+        Self {
+            user_identity,
+            old_password,
+            new_password,
+        }
+    }
+}
+
+/// The value of a Password Modify response.
+#[derive(AsnType, Encode, Decode, Debug, Default, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[non_exhaustive]
+pub struct PasswordModifyResponse {
+    /// The password generated by the server.
+    #[rasn(tag(0))]
+    pub generated_password: Option<OctetString>,
+}
+
+impl PasswordModifyResponse {
+    /// PasswordModifyResponse constructor
+    pub fn new(generated_password: Option<OctetString>) -> Self {
+        Self { generated_password }
+    }
+}
+
+fn no_value(value: Option<&[u8]>) -> Result<(), DecodeError> {
+    match value {
+        Some(_) => Err(DecodeError::custom(
+            "unexpected value for operation",
+            rasn::Codec::Ber,
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{controls::SyncRefresh, ResultCode};
+    use pretty_assertions::assert_eq;
+
+    fn success() -> LdapResult {
+        LdapResult::new(ResultCode::Success, <_>::default(), <_>::default())
+    }
+
+    #[test]
+    fn password_modify() {
+        let value = PasswordModifyRequest::new(
+            Some("uid=jdoe,dc=example,dc=org".into()),
+            Some("old".into()),
+            Some("new".into()),
+        );
+        let request =
+            ExtendedRequest::from_known(&KnownExtendedRequest::PasswordModify(value.clone()))
+                .unwrap();
+        assert_eq!(PASSWORD_MODIFY.as_bytes(), &*request.request_name);
+        // Encoded by pyasn1.
+        assert_eq!(
+            &b"\x30\x26\x80\x1auid=jdoe,dc=example,dc=org\x81\x03old\x82\x03new"[..],
+            &*request.request_value.clone().unwrap()
+        );
+        assert_eq!(
+            Some(KnownExtendedRequest::PasswordModify(value)),
+            request.decode_known().unwrap()
+        );
+
+        let empty = ExtendedRequest::from_known(&KnownExtendedRequest::PasswordModify(
+            PasswordModifyRequest::default(),
+        ))
+        .unwrap();
+        assert_eq!(None, empty.request_value);
+        assert_eq!(
+            Some(KnownExtendedRequest::PasswordModify(<_>::default())),
+            empty.decode_known().unwrap()
+        );
+
+        let generated = KnownExtendedResponse::PasswordModify(PasswordModifyResponse::new(Some(
+            "secret".into(),
+        )));
+        let response = ExtendedResponse::from_known(success(), &generated).unwrap();
+        assert_eq!(None, response.response_name);
+        assert_eq!(
+            Some(generated),
+            response.decode_known(PASSWORD_MODIFY.as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn start_tls_and_who_am_i() {
+        let request = ExtendedRequest::from_known(&KnownExtendedRequest::StartTls).unwrap();
+        assert_eq!(START_TLS.as_bytes(), &*request.request_name);
+        assert_eq!(None, request.request_value);
+        let response =
+            ExtendedResponse::from_known(success(), &KnownExtendedResponse::StartTls).unwrap();
+        assert_eq!(Some(START_TLS.into()), response.response_name);
+        // The response carries its own name.
+        assert_eq!(
+            Some(KnownExtendedResponse::StartTls),
+            response.decode_known(b"").unwrap()
+        );
+
+        let who_am_i = KnownExtendedResponse::WhoAmI("dn:uid=jdoe,dc=example,dc=org".into());
+        let response = ExtendedResponse::from_known(success(), &who_am_i).unwrap();
+        assert_eq!(
+            Some("dn:uid=jdoe,dc=example,dc=org".into()),
+            response.response_value
+        );
+        assert_eq!(
+            Some(who_am_i),
+            response.decode_known(WHO_AM_I.as_bytes()).unwrap()
+        );
+
+        let invalid = ExtendedRequest {
+            request_name: WHO_AM_I.into(),
+            request_value: Some("value".into()),
+        };
+        assert!(invalid.decode_known().is_err());
+        let unknown = ExtendedRequest {
+            request_name: "1.2.3".into(),
+            request_value: None,
+        };
+        assert_eq!(None, unknown.decode_known().unwrap());
+    }
+
+    #[test]
+    fn sync_info() {
+        let value = KnownIntermediateResponse::SyncInfo(SyncInfoValue::RefreshDelete(
+            SyncRefresh::new(Some("cookie".into()), true),
+        ));
+        let response = IntermediateResponse::from_known(&value).unwrap();
+        assert_eq!(Some(SYNC_INFO.into()), response.response_name);
+        assert_eq!(Some(value), response.decode_known().unwrap());
+
+        let unnamed = IntermediateResponse {
+            response_name: None,
+            response_value: None,
+        };
+        assert_eq!(None, unnamed.decode_known().unwrap());
+    }
+}
@@ -0,0 +1,840 @@
+//! # Search Filter Strings
+//!
+//! This module implements the string representation of search filters
+//! defined in [RFC 4515], allowing [`Filter`] to be converted to and from
+//! strings such as `(&(objectClass=person)(|(cn=Jo*)(mail=*@x.org)))`.
+//!
+//! Values are written as UTF-8, with `*`, `(`, `)`, `\`, control characters
+//! and any bytes that aren't valid UTF-8 escaped as `\` followed by two hex
+//! digits. The
+//! absolute true and false filters of [RFC 4526], `(&)` and `(|)`, are
+//! written and parsed as an empty `And` and `Or`.
+//!
+//! Parsing a formatted filter returns the original filter, provided its
+//! attribute descriptions and matching rules are valid [RFC 4512]
+//! identifiers, its substrings are non-empty, and its extensible matches
+//! have a type or a matching rule; filters that break these rules can't be
+//! represented as strings. As `And` and `Or` are sets, formatting a parsed
+//! string returns it with their elements in order, and without duplicates.
+//!
+//! [RFC 4515]: https://datatracker.ietf.org/doc/html/rfc4515
+//! [RFC 4526]: https://datatracker.ietf.org/doc/html/rfc4526
+//! [RFC 4512]: https://datatracker.ietf.org/doc/html/rfc4512
+
+use alloc::{boxed::Box, collections::BTreeSet, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::{
+    AttributeValueAssertion, Filter, MatchingRuleAssertion, SubstringChoice, SubstringFilter,
+};
+
+/// An error produced when parsing a search filter string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseFilterError {
+    /// A filter didn't start with `(`, or didn't end with `)`.
+    MissingParenthesis { position: usize },
+    /// An attribute description was empty, or was not valid.
+    InvalidAttributeDescription { position: usize },
+    /// An extensible match's matching rule was not a valid OID, or was
+    /// preceded by something other than `:dn`.
+    InvalidMatchingRule { position: usize },
+    /// An attribute description was not followed by `=`, `~=`, `>=`, `<=`
+    /// or `:=`.
+    InvalidOperator { position: usize },
+    /// A `\` escape was not followed by a hex pair.
+    InvalidEscape { position: usize },
+    /// A value contained an unescaped `(`, or an unescaped `*` where
+    /// substrings aren't allowed.
+    InvalidValue { position: usize },
+    /// The filter was followed by more characters.
+    TrailingCharacters { position: usize },
+    /// The filter starting at `position` was nested more deeply than the
+    /// parser allows.
+    TooDeep { position: usize },
+    /// The string ended where a filter was expected.
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingParenthesis { position } => {
+                write!(f, "expected parenthesis at {position}")
+            }
+            Self::InvalidAttributeDescription { position } => {
+                write!(f, "invalid attribute description at {position}")
+            }
+            Self::InvalidMatchingRule { position } => {
+                write!(f, "invalid matching rule at {position}")
+            }
+            Self::InvalidOperator { position } => {
+                write!(f, "expected a filter operator at {position}")
+            }
+            Self::InvalidEscape { position } => write!(f, "invalid escape sequence at {position}"),
+            Self::InvalidValue { position } => {
+                write!(f, "invalid character in value at {position}")
+            }
+            Self::TrailingCharacters { position } => {
+                write!(f, "unexpected characters after filter at {position}")
+            }
+            Self::TooDeep { position } => write!(f, "filter nested too deeply at {position}"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of filter"),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('(')?;
+        match self {
+            Self::And(filters) => {
+                f.write_char('&')?;
+                for filter in filters {
+                    filter.fmt(f)?;
+                }
+            }
+            Self::Or(filters) => {
+                f.write_char('|')?;
+                for filter in filters {
+                    filter.fmt(f)?;
+                }
+            }
+            Self::Not(filter) => {
+                f.write_char('!')?;
+                filter.fmt(f)?;
+            }
+            Self::EqualityMatch(ava) => write_assertion(f, ava, "=")?,
+            Self::Substrings(substrings) => {
+                write_identifier(f, &substrings.r#type)?;
+                f.write_char('=')?;
+                for choice in &substrings.substrings {
+                    match choice {
+                        SubstringChoice::Initial(value) => write_value(f, value)?,
+                        SubstringChoice::Any(value) | SubstringChoice::Final(value) => {
+                            f.write_char('*')?;
+                            write_value(f, value)?;
+                        }
+                    }
+                }
+                if !matches!(
+                    substrings.substrings.last(),
+                    Some(SubstringChoice::Final(_))
+                ) {
+                    f.write_char('*')?;
+                }
+            }
+            Self::GreaterOrEqual(ava) => write_assertion(f, ava, ">=")?,
+            Self::LessOrEqual(ava) => write_assertion(f, ava, "<=")?,
+            Self::Present(description) => {
+                write_identifier(f, description)?;
+                f.write_str("=*")?;
+            }
+            Self::ApproxMatch(ava) => write_assertion(f, ava, "~=")?,
+            Self::ExtensibleMatch(assertion) => {
+                if let Some(r#type) = &assertion.r#type {
+                    write_identifier(f, r#type)?;
+                }
+                if assertion.dn_attributes {
+                    f.write_str(":dn")?;
+                }
+                if let Some(rule) = &assertion.matching_rule {
+                    f.write_char(':')?;
+                    write_identifier(f, rule)?;
+                }
+                f.write_str(":=")?;
+                write_value(f, &assertion.match_value)?;
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+/// The deepest nesting of filters that parsing with [`str::parse`] accepts.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+impl Filter {
+    /// Parses a filter string, allowing filters to be nested at most
+    /// `max_depth` deep. A filter string such as `(!(!(a=1)))` is three deep.
+    ///
+    /// Parsing recurses into nested filters, so the limit stops untrusted
+    /// strings from overflowing the stack.
+    pub fn parse_with_max_depth(s: &str, max_depth: usize) -> Result<Self, ParseFilterError> {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            position: 0,
+            depth: max_depth,
+        };
+        let filter = parser.parse_filter()?;
+        if !parser.is_empty() {
+            return Err(ParseFilterError::TrailingCharacters {
+                position: parser.position,
+            });
+        }
+
+        Ok(filter)
+    }
+}
+
+impl core::str::FromStr for Filter {
+    type Err = ParseFilterError;
+
+    /// Parses a filter string nested at most [`DEFAULT_MAX_DEPTH`] deep.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_max_depth(s, DEFAULT_MAX_DEPTH)
+    }
+}
+
+fn write_assertion(
+    f: &mut fmt::Formatter<'_>,
+    ava: &AttributeValueAssertion,
+    operator: &str,
+) -> fmt::Result {
+    write_identifier(f, &ava.attribute_desc)?;
+    f.write_str(operator)?;
+    write_value(f, &ava.assertion_value)
+}
+
+/// Writes an attribute description or matching rule, which are valid ASCII
+/// unless the filter can't be represented as a string.
+fn write_identifier(f: &mut fmt::Formatter<'_>, identifier: &[u8]) -> fmt::Result {
+    for chunk in identifier.utf8_chunks() {
+        f.write_str(chunk.valid())?;
+        if !chunk.invalid().is_empty() {
+            f.write_char(char::REPLACEMENT_CHARACTER)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_value(f: &mut fmt::Formatter<'_>, value: &[u8]) -> fmt::Result {
+    for chunk in value.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '*' | '(' | ')' | '\\' => write!(f, "\\{:02x}", c as u8)?,
+                _ if c.is_ascii_control() => write!(f, "\\{:02x}", c as u8)?,
+                _ => f.write_char(c)?,
+            }
+        }
+        for byte in chunk.invalid() {
+            write!(f, "\\{byte:02x}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `byte` can appear in an attribute description or matching rule.
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b';')
+}
+
+/// Whether `oid` is a descriptor (`cn`) or a numeric OID (`2.5.4.3`).
+pub(crate) fn is_oid(oid: &[u8]) -> bool {
+    match oid.first() {
+        Some(first) if first.is_ascii_alphabetic() => oid
+            .iter()
+            .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-'),
+        Some(_) => {
+            let mut numbers = oid.split(|byte| *byte == b'.');
+            let valid = |number: &[u8]| {
+                !number.is_empty()
+                    && number.iter().all(u8::is_ascii_digit)
+                    && (number.len() == 1 || number[0] != b'0')
+            };
+            numbers.clone().count() > 1 && numbers.all(valid)
+        }
+        None => false,
+    }
+}
+
+/// Whether `description` is an attribute type followed by options, such as
+/// `cn;lang-en`.
+pub(crate) fn is_attribute_description(description: &[u8]) -> bool {
+    let mut parts = description.split(|byte| *byte == b';');
+    parts.next().is_some_and(is_oid)
+        && parts.all(|option| {
+            !option.is_empty()
+                && option
+                    .iter()
+                    .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+        })
+}
+
+struct Parser<'input> {
+    input: &'input [u8],
+    position: usize,
+    /// How many more levels of nesting are allowed.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseFilterError> {
+        if self.eat(byte) {
+            Ok(())
+        } else if self.is_empty() {
+            Err(ParseFilterError::UnexpectedEnd)
+        } else {
+            Err(ParseFilterError::MissingParenthesis {
+                position: self.position,
+            })
+        }
+    }
+
+    fn take_identifier(&mut self) -> &[u8] {
+        let start = self.position;
+        while self.peek().is_some_and(is_identifier_byte) {
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter, ParseFilterError> {
+        let position = self.position;
+        self.depth = self
+            .depth
+            .checked_sub(1)
+            .ok_or(ParseFilterError::TooDeep { position })?;
+
+        self.expect(b'(')?;
+        let filter = if self.eat(b'&') {
+            Filter::And(self.parse_filter_list()?)
+        } else if self.eat(b'|') {
+            Filter::Or(self.parse_filter_list()?)
+        } else if self.eat(b'!') {
+            Filter::Not(Box::new(self.parse_filter()?))
+        } else {
+            self.parse_item()?
+        };
+        self.expect(b')')?;
+
+        self.depth += 1;
+        Ok(filter)
+    }
+
+    fn parse_filter_list(&mut self) -> Result<BTreeSet<Filter>, ParseFilterError> {
+        let mut filters = BTreeSet::new();
+        while self.peek() == Some(b'(') {
+            filters.insert(self.parse_filter()?);
+        }
+
+        Ok(filters)
+    }
+
+    fn parse_item(&mut self) -> Result<Filter, ParseFilterError> {
+        let start = self.position;
+        let description = self.take_identifier().to_vec();
+        let invalid_description = ParseFilterError::InvalidAttributeDescription { position: start };
+
+        if self.peek() == Some(b':') {
+            if !description.is_empty() && !is_attribute_description(&description) {
+                return Err(invalid_description);
+            }
+            return self.parse_extensible_match(description, start);
+        }
+
+        if !is_attribute_description(&description) {
+            return Err(if self.is_empty() {
+                ParseFilterError::UnexpectedEnd
+            } else {
+                invalid_description
+            });
+        }
+        let description = description.into();
+
+        let operator = self.position;
+        let filter = match self.peek() {
+            Some(b'=') => {
+                self.position += 1;
+                return self.parse_equality_or_substrings(description);
+            }
+            Some(b'~') => Filter::ApproxMatch,
+            Some(b'>') => Filter::GreaterOrEqual,
+            Some(b'<') => Filter::LessOrEqual,
+            None => return Err(ParseFilterError::UnexpectedEnd),
+            _ => return Err(ParseFilterError::InvalidOperator { position: operator }),
+        };
+        self.position += 1;
+        if !self.eat(b'=') {
+            return Err(ParseFilterError::InvalidOperator { position: operator });
+        }
+        let value = self.parse_value(false)?;
+
+        Ok(filter(AttributeValueAssertion::new(
+            description,
+            value.into(),
+        )))
+    }
+
+    fn parse_equality_or_substrings(
+        &mut self,
+        description: crate::AttributeDescription,
+    ) -> Result<Filter, ParseFilterError> {
+        let mut pieces = alloc::vec![self.parse_value(true)?];
+        while self.eat(b'*') {
+            pieces.push(self.parse_value(true)?);
+        }
+
+        if let [value] = &mut pieces[..] {
+            return Ok(Filter::EqualityMatch(AttributeValueAssertion::new(
+                description,
+                core::mem::take(value).into(),
+            )));
+        }
+
+        let last = pieces.len() - 1;
+        let substrings = pieces
+            .into_iter()
+            .enumerate()
+            .filter(|(_, piece)| !piece.is_empty())
+            .map(|(i, piece)| match i {
+                0 => SubstringChoice::Initial(piece.into()),
+                i if i == last => SubstringChoice::Final(piece.into()),
+                _ => SubstringChoice::Any(piece.into()),
+            })
+            .collect::<Vec<_>>();
+
+        Ok(if substrings.is_empty() {
+            Filter::Present(description)
+        } else {
+            Filter::Substrings(SubstringFilter::new(description, substrings))
+        })
+    }
+
+    fn parse_extensible_match(
+        &mut self,
+        description: Vec<u8>,
+        start: usize,
+    ) -> Result<Filter, ParseFilterError> {
+        // The `:dn` and matching rule that follow the attribute description.
+        let mut parts = Vec::new();
+        loop {
+            if !self.eat(b':') {
+                return Err(if self.is_empty() {
+                    ParseFilterError::UnexpectedEnd
+                } else {
+                    ParseFilterError::InvalidOperator {
+                        position: self.position,
+                    }
+                });
+            }
+            if self.eat(b'=') {
+                break;
+            }
+            let position = self.position;
+            parts.push((position, self.take_identifier().to_vec()));
+        }
+
+        let is_dn = |part: &[u8]| part.eq_ignore_ascii_case(b"dn");
+        let (dn_attributes, matching_rule) = match &mut parts[..] {
+            [] => (false, None),
+            [(_, part)] if !description.is_empty() && is_dn(part) => (true, None),
+            [rule] => (false, Some(core::mem::take(rule))),
+            [(_, dn), rule] if is_dn(dn) => (true, Some(core::mem::take(rule))),
+            [(position, _), ..] => {
+                return Err(ParseFilterError::InvalidMatchingRule {
+                    position: *position,
+                })
+            }
+        };
+        if let Some((position, rule)) = &matching_rule {
+            if !is_oid(rule) {
+                return Err(ParseFilterError::InvalidMatchingRule {
+                    position: *position,
+                });
+            }
+        } else if description.is_empty() {
+            return Err(ParseFilterError::InvalidAttributeDescription { position: start });
+        }
+
+        let value = self.parse_value(false)?;
+        Ok(Filter::ExtensibleMatch(MatchingRuleAssertion::new(
+            matching_rule.map(|(_, rule)| rule.into()),
+            (!description.is_empty()).then(|| description.into()),
+            value.into(),
+            dn_attributes,
+        )))
+    }
+
+    /// Parses an assertion value up to the closing `)`, or up to a `*` if
+    /// `substrings` is set.
+    fn parse_value(&mut self, substrings: bool) -> Result<Vec<u8>, ParseFilterError> {
+        let mut value = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(ParseFilterError::UnexpectedEnd),
+                Some(b')') => break,
+                Some(b'*') if substrings => break,
+                Some(b'*' | b'(' | b'\0') => {
+                    return Err(ParseFilterError::InvalidValue {
+                        position: self.position,
+                    })
+                }
+                Some(b'\\') => {
+                    let position = self.position;
+                    let digits = self
+                        .input
+                        .get(position + 1..position + 3)
+                        .filter(|digits| digits.iter().all(u8::is_ascii_hexdigit))
+                        .ok_or(ParseFilterError::InvalidEscape { position })?;
+                    value.push((hex_digit(digits[0]) << 4) | hex_digit(digits[1]));
+                    self.position += 3;
+                }
+                Some(byte) => {
+                    value.push(byte);
+                    self.position += 1;
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    fn ava(description: &str, value: &[u8]) -> AttributeValueAssertion {
+        AttributeValueAssertion::new(
+            description.as_bytes().to_vec().into(),
+            value.to_vec().into(),
+        )
+    }
+
+    fn parse(filter: &str) -> Filter {
+        filter.parse().unwrap()
+    }
+
+    #[test]
+    fn nested() {
+        let filter = Filter::And(BTreeSet::from([
+            Filter::EqualityMatch(ava("objectClass", b"person")),
+            Filter::Or(BTreeSet::from([
+                Filter::Substrings(SubstringFilter::new(
+                    "cn".into(),
+                    vec![SubstringChoice::Initial("Jo".into())],
+                )),
+                Filter::Substrings(SubstringFilter::new(
+                    "mail".into(),
+                    vec![SubstringChoice::Final("@x.org".into())],
+                )),
+            ])),
+        ]));
+
+        assert_eq!(
+            filter,
+            parse("(&(objectClass=person)(|(cn=Jo*)(mail=*@x.org)))")
+        );
+        // `And` and `Or` are sets, so are written in order.
+        let string = "(&(|(cn=Jo*)(mail=*@x.org))(objectClass=person))";
+        assert_eq!(string, filter.to_string());
+        assert_eq!(filter, parse(string));
+    }
+
+    #[test]
+    fn rfc_4515_examples() {
+        for string in [
+            "(cn=Babs Jensen)",
+            "(!(cn=Tim Howes))",
+            "(&(|(sn=Jensen)(cn=Babs J*))(objectClass=Person))",
+            "(o=univ*of*mich*)",
+            "(seeAlso=)",
+            "(cn:caseExactMatch:=Fred Flintstone)",
+            "(cn:=Betty Rubble)",
+            "(sn:dn:2.4.6.8.10:=Barney Rubble)",
+            "(o:dn:=Ace Industry)",
+            "(:1.2.3:=Wilma Flintstone)",
+            "(:dn:2.4.6.8.10:=Dino)",
+            "(o=Parens R Us \\28for all your parenthetical needs\\29)",
+            "(cn=*\\2a*)",
+            "(filename=C:\\5cMyFile)",
+            "(bin=\\00\\00\\00\\04)",
+            "(sn=Lu\u{010D}i\u{0107})",
+        ] {
+            assert_eq!(string, parse(string).to_string());
+        }
+
+        assert_eq!(
+            Filter::ExtensibleMatch(MatchingRuleAssertion::new(
+                Some("2.4.6.8.10".into()),
+                Some("sn".into()),
+                "Barney Rubble".into(),
+                true,
+            )),
+            parse("(sn:dn:2.4.6.8.10:=Barney Rubble)")
+        );
+        assert_eq!(
+            Filter::Substrings(SubstringFilter::new(
+                "o".into(),
+                vec![
+                    SubstringChoice::Initial("univ".into()),
+                    SubstringChoice::Any("of".into()),
+                    SubstringChoice::Any("mich".into()),
+                ],
+            )),
+            parse("(o=univ*of*mich*)")
+        );
+        assert_eq!(
+            Filter::EqualityMatch(ava("bin", &[0, 0, 0, 4])),
+            parse("(bin=\\00\\00\\00\\04)")
+        );
+        // Escaped UTF-8 is written unescaped.
+        assert_eq!(
+            "(sn=Lu\u{010D}i\u{0107})",
+            parse("(sn=Lu\\c4\\8di\\c4\\87)").to_string()
+        );
+    }
+
+    #[test]
+    fn items() {
+        assert_eq!(Filter::Present("cn".into()), parse("(cn=*)"),);
+        assert_eq!(Filter::ApproxMatch(ava("cn", b"Jon")), parse("(cn~=Jon)"));
+        assert_eq!(
+            Filter::GreaterOrEqual(ava("uidNumber", b"1000")),
+            parse("(uidNumber>=1000)")
+        );
+        assert_eq!(
+            Filter::LessOrEqual(ava("cn;lang-en", b"b")),
+            parse("(cn;lang-en<=b)")
+        );
+        assert_eq!(
+            Filter::EqualityMatch(ava("2.5.4.3", b"a=b")),
+            parse("(2.5.4.3=a=b)")
+        );
+        assert_eq!(Filter::And(BTreeSet::new()), parse("(&)"));
+        assert_eq!("(|)", Filter::Or(BTreeSet::new()).to_string());
+        // Empty substrings between asterisks are ignored.
+        assert_eq!("(cn=a*b)", parse("(cn=a**b)").to_string());
+        assert_eq!("(cn=*)", parse("(cn=**)").to_string());
+    }
+
+    #[test]
+    fn errors() {
+        for (string, error) in [
+            ("", ParseFilterError::UnexpectedEnd),
+            ("(cn=a", ParseFilterError::UnexpectedEnd),
+            ("(&(cn=a)", ParseFilterError::UnexpectedEnd),
+            ("cn=a", ParseFilterError::MissingParenthesis { position: 0 }),
+            (
+                "(cn=a)(sn=b)",
+                ParseFilterError::TrailingCharacters { position: 6 },
+            ),
+            (
+                "(=a)",
+                ParseFilterError::InvalidAttributeDescription { position: 1 },
+            ),
+            (
+                "(1cn=a)",
+                ParseFilterError::InvalidAttributeDescription { position: 1 },
+            ),
+            (
+                "(cn;=a)",
+                ParseFilterError::InvalidAttributeDescription { position: 1 },
+            ),
+            (
+                "(01.2=a)",
+                ParseFilterError::InvalidAttributeDescription { position: 1 },
+            ),
+            ("(cn!=a)", ParseFilterError::InvalidOperator { position: 3 }),
+            ("(cn~a)", ParseFilterError::InvalidOperator { position: 3 }),
+            (
+                "(cn:dn=a)",
+                ParseFilterError::InvalidOperator { position: 6 },
+            ),
+            (
+                "(:=a)",
+                ParseFilterError::InvalidAttributeDescription { position: 1 },
+            ),
+            (
+                "(cn:rule:dn:=a)",
+                ParseFilterError::InvalidMatchingRule { position: 4 },
+            ),
+            (
+                "(cn:1.:=a)",
+                ParseFilterError::InvalidMatchingRule { position: 4 },
+            ),
+            ("(cn=a\\2)", ParseFilterError::InvalidEscape { position: 5 }),
+            (
+                "(cn=a\\zz)",
+                ParseFilterError::InvalidEscape { position: 5 },
+            ),
+            ("(cn=a(b)", ParseFilterError::InvalidValue { position: 5 }),
+            ("(cn>=a*)", ParseFilterError::InvalidValue { position: 6 }),
+        ] {
+            assert_eq!(Err(error), string.parse::<Filter>(), "{string}");
+        }
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = |depth: usize| "(!".repeat(depth - 1) + "(a=1)" + &")".repeat(depth - 1);
+
+        let string = nested(DEFAULT_MAX_DEPTH);
+        assert_eq!(string, parse(&string).to_string());
+        assert_eq!(
+            Err(ParseFilterError::TooDeep {
+                position: 2 * DEFAULT_MAX_DEPTH
+            }),
+            nested(DEFAULT_MAX_DEPTH + 1).parse::<Filter>()
+        );
+        // Deep enough to overflow the stack without the limit.
+        assert_eq!(
+            Err(ParseFilterError::TooDeep {
+                position: 2 * DEFAULT_MAX_DEPTH
+            }),
+            nested(200_000).parse::<Filter>()
+        );
+
+        assert_eq!(
+            Err(ParseFilterError::TooDeep { position: 4 }),
+            Filter::parse_with_max_depth("(&(|(cn=a)))", 2)
+        );
+        assert_eq!(
+            parse("(&(|(cn=a)))"),
+            Filter::parse_with_max_depth("(&(|(cn=a)))", 3).unwrap()
+        );
+    }
+
+    fn identifier() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[a-zA-Z][a-zA-Z0-9-]{0,8}",
+            "(0|[1-9][0-9]{0,3})(\\.(0|[1-9][0-9]{0,3})){1,4}",
+        ]
+        // `dn` is ambiguous as a matching rule, so isn't generated.
+        .prop_filter("`dn` is ambiguous", |id| !id.eq_ignore_ascii_case("dn"))
+    }
+
+    fn description() -> impl Strategy<Value = crate::AttributeDescription> {
+        (
+            identifier(),
+            prop::collection::vec("[a-zA-Z0-9-]{1,6}", 0..3),
+        )
+            .prop_map(|(r#type, options)| {
+                let mut description = r#type;
+                for option in options {
+                    description.push(';');
+                    description.push_str(&option);
+                }
+                description.into()
+            })
+    }
+
+    fn value() -> impl Strategy<Value = crate::AssertionValue> {
+        prop_oneof![
+            prop::collection::vec(any::<u8>(), 0..8),
+            ".{0,8}".prop_map(String::into_bytes),
+            "[*()\\\\a]{0,8}".prop_map(String::into_bytes),
+        ]
+        .prop_map(Into::into)
+    }
+
+    fn non_empty_value() -> impl Strategy<Value = crate::AssertionValue> {
+        value().prop_filter("substrings are non-empty", |value| !value.is_empty())
+    }
+
+    fn filter() -> impl Strategy<Value = Filter> {
+        let substrings = (
+            prop::option::of(non_empty_value()),
+            prop::collection::vec(non_empty_value(), 0..3),
+            prop::option::of(non_empty_value()),
+        )
+            .prop_filter("substrings are non-empty", |(initial, any, last)| {
+                initial.is_some() || !any.is_empty() || last.is_some()
+            })
+            .prop_map(|(initial, any, last)| {
+                initial
+                    .map(SubstringChoice::Initial)
+                    .into_iter()
+                    .chain(any.into_iter().map(SubstringChoice::Any))
+                    .chain(last.map(SubstringChoice::Final))
+                    .collect::<Vec<_>>()
+            });
+        let extensible = (
+            prop::option::of(identifier().prop_map(Into::into)),
+            prop::option::of(description()),
+            value(),
+            any::<bool>(),
+        )
+            .prop_filter("type or rule is present", |(rule, r#type, _, _)| {
+                rule.is_some() || r#type.is_some()
+            })
+            .prop_map(|(rule, r#type, value, dn)| {
+                Filter::ExtensibleMatch(MatchingRuleAssertion::new(rule, r#type, value, dn))
+            });
+        let ava = || {
+            (description(), value())
+                .prop_map(|(description, value)| AttributeValueAssertion::new(description, value))
+        };
+
+        let item = prop_oneof![
+            ava().prop_map(Filter::EqualityMatch),
+            (description(), substrings).prop_map(|(description, substrings)| {
+                Filter::Substrings(SubstringFilter::new(description, substrings))
+            }),
+            ava().prop_map(Filter::GreaterOrEqual),
+            ava().prop_map(Filter::LessOrEqual),
+            description().prop_map(Filter::Present),
+            ava().prop_map(Filter::ApproxMatch),
+            extensible,
+        ];
+
+        item.prop_recursive(4, 32, 4, |inner| {
+            prop_oneof![
+                prop::collection::btree_set(inner.clone(), 0..4).prop_map(Filter::And),
+                prop::collection::btree_set(inner.clone(), 0..4).prop_map(Filter::Or),
+                inner.prop_map(|filter| Filter::Not(Box::new(filter))),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(filter in filter()) {
+            let string = filter.to_string();
+            let parsed = string.parse::<Filter>().unwrap();
+            prop_assert_eq!(&filter, &parsed);
+            prop_assert_eq!(string, parsed.to_string());
+        }
+
+        #[test]
+        fn parse_arbitrary(string in "[()&|!=*~<>:a-c\\\\0-9]{0,24}") {
+            // Any string either fails to parse, or prints as a string that
+            // parses to the same filter.
+            if let Ok(filter) = string.parse::<Filter>() {
+                prop_assert_eq!(&filter, &filter.to_string().parse::<Filter>().unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,956 @@
+//! # LDIF
+//!
+//! This module reads and writes the LDAP Data Interchange Format of
+//! [RFC 2849], which describes directory entries, or changes to them, as
+//! text:
+//!
+//! ```text
+//! version: 1
+//!
+//! dn: cn=Barbara Jensen,ou=People,dc=example,dc=com
+//! objectClass: person
+//! cn: Barbara Jensen
+//! description:: V2hhdCBhIGNhcmVmdWwgcmVhZGVyIHlvdSBhcmUh
+//!
+//! dn: cn=Bjorn Jensen,ou=People,dc=example,dc=com
+//! changetype: modify
+//! replace: telephoneNumber
+//! telephoneNumber: +1 408 555 1212
+//! -
+//! ```
+//!
+//! Content records are read as [`SearchResultEntry`]s, and change records as
+//! the [`AddRequest`], [`DelRequest`], [`ModifyRequest`] or
+//! [`ModifyDnRequest`] that makes the change, with any controls to send it
+//! with.
+//!
+//! Values that aren't printable ASCII, or that start with a space, `:` or
+//! `<` or end with a space, are written in base64, and lines are folded at
+//! 76 columns. Values given by URL (`:<`) can't be read, as this crate doesn't
+//! fetch them.
+//!
+//! [RFC 2849]: https://datatracker.ietf.org/doc/html/rfc2849
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::filter::{is_attribute_description, is_oid};
+use crate::{
+    AddRequest, Attribute, ChangeOperation, Control, Controls, DelRequest, ModifyDnRequest,
+    ModifyRequest, ModifyRequestChanges, PartialAttribute, ProtocolOp, SearchResultEntry,
+};
+
+/// The longest line written, before folding.
+const LINE_LENGTH: usize = 76;
+
+/// An error produced when reading LDIF, with the line it was found on,
+/// counting from one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseLdifError {
+    /// A line wasn't an attribute description followed by `:` and a value,
+    /// or a continuation line didn't follow another line.
+    InvalidLine { line: usize },
+    /// A base64 value (`::`) wasn't valid base64.
+    InvalidBase64 { line: usize },
+    /// A value was given by URL (`:<`).
+    UnsupportedUrl { line: usize },
+    /// The `version` line wasn't `version: 1`.
+    InvalidVersion { line: usize },
+    /// A record didn't start with a `dn` line.
+    MissingDn { line: usize },
+    /// A `changetype` wasn't `add`, `delete`, `modify`, `modrdn` or `moddn`.
+    InvalidChangeType { line: usize },
+    /// A `control` line wasn't an OID, optionally followed by a criticality
+    /// and a value, or was in a content record.
+    InvalidControl { line: usize },
+    /// A line didn't belong in its record, or a line the record needs was
+    /// missing.
+    InvalidRecord { line: usize },
+}
+
+impl fmt::Display for ParseLdifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLine { line } => write!(f, "invalid line {line}"),
+            Self::InvalidBase64 { line } => write!(f, "invalid base64 value on line {line}"),
+            Self::UnsupportedUrl { line } => write!(f, "unsupported URL value on line {line}"),
+            Self::InvalidVersion { line } => write!(f, "unsupported version on line {line}"),
+            Self::MissingDn { line } => write!(f, "expected a dn on line {line}"),
+            Self::InvalidChangeType { line } => write!(f, "invalid changetype on line {line}"),
+            Self::InvalidControl { line } => write!(f, "invalid control on line {line}"),
+            Self::InvalidRecord { line } => write!(f, "unexpected line {line} in record"),
+        }
+    }
+}
+
+/// A record in an LDIF file.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LdifRecord {
+    /// A content record, describing an entry.
+    Entry(SearchResultEntry),
+    /// A change record, with the controls to send its request with.
+    Change {
+        controls: Controls,
+        change: LdifChange,
+    },
+}
+
+/// The request a change record makes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LdifChange {
+    /// `changetype: add`
+    Add(AddRequest),
+    /// `changetype: delete`
+    Delete(DelRequest),
+    /// `changetype: modify`
+    Modify(ModifyRequest),
+    /// `changetype: modrdn`, or `changetype: moddn`
+    ModifyDn(ModifyDnRequest),
+}
+
+impl From<LdifChange> for ProtocolOp {
+    fn from(change: LdifChange) -> Self {
+        match change {
+            LdifChange::Add(request) => Self::AddRequest(request),
+            LdifChange::Delete(request) => Self::DelRequest(request),
+            LdifChange::Modify(request) => Self::ModifyRequest(request),
+            LdifChange::ModifyDn(request) => Self::ModDnRequest(request),
+        }
+    }
+}
+
+/// Adds the entry described by a content record, or returned by a search.
+impl From<SearchResultEntry> for AddRequest {
+    fn from(entry: SearchResultEntry) -> Self {
+        Self {
+            entry: entry.object_name,
+            attributes: entry
+                .attributes
+                .into_iter()
+                .map(|attribute| Attribute::new(attribute.r#type, attribute.vals))
+                .collect(),
+        }
+    }
+}
+
+/// An LDIF file, which is written with a `version: 1` line.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct Ldif {
+    pub records: Vec<LdifRecord>,
+}
+
+impl Ldif {
+    /// Ldif constructor.
+    pub fn new(records: Vec<LdifRecord>) -> Self {
+        Self { records }
+    }
+}
+
+impl core::str::FromStr for Ldif {
+    type Err = ParseLdifError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LdifReader::new(s)
+            .collect::<Result<_, _>>()
+            .map(|records| Self { records })
+    }
+}
+
+impl fmt::Display for Ldif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("version: 1\n")?;
+        for record in &self.records {
+            f.write_char('\n')?;
+            record.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a record as lines ending in `\n`, without a blank line after it.
+impl fmt::Display for LdifRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Entry(entry) => {
+                write_line(f, "dn", &entry.object_name)?;
+                for attribute in &entry.attributes {
+                    write_values(f, &attribute.r#type, &attribute.vals)?;
+                }
+            }
+            Self::Change { controls, change } => {
+                let dn = match change {
+                    LdifChange::Add(request) => &request.entry,
+                    LdifChange::Delete(request) => &request.0,
+                    LdifChange::Modify(request) => &request.object,
+                    LdifChange::ModifyDn(request) => &request.entry,
+                };
+                write_line(f, "dn", dn)?;
+                for control in controls {
+                    write_control(f, control)?;
+                }
+                change.fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the `changetype` line and the lines following it.
+impl fmt::Display for LdifChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add(request) => {
+                f.write_str("changetype: add\n")?;
+                for attribute in &request.attributes {
+                    write_values(f, &attribute.r#type, &attribute.vals)?;
+                }
+            }
+            Self::Delete(_) => f.write_str("changetype: delete\n")?,
+            Self::Modify(request) => {
+                f.write_str("changetype: modify\n")?;
+                for change in &request.changes {
+                    let operation = match change.operation {
+                        ChangeOperation::Add => "add",
+                        ChangeOperation::Delete => "delete",
+                        ChangeOperation::Replace => "replace",
+                    };
+                    let r#type = &change.modification.r#type;
+                    write_line(f, operation, r#type)?;
+                    write_values(f, r#type, &change.modification.vals)?;
+                    f.write_str("-\n")?;
+                }
+            }
+            Self::ModifyDn(request) => {
+                f.write_str("changetype: modrdn\n")?;
+                write_line(f, "newrdn", &request.new_rdn)?;
+                let delete_old_rdn = if request.delete_old_rdn { "1" } else { "0" };
+                write_line(f, "deleteoldrdn", delete_old_rdn.as_bytes())?;
+                if let Some(new_superior) = &request.new_superior {
+                    write_line(f, "newsuperior", new_superior)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a value can be written as it is, rather than in base64.
+fn is_safe_string(value: &[u8]) -> bool {
+    let safe_char = |byte: &u8| matches!(byte, 0x01..=0x09 | 0x0b..=0x0c | 0x0e..=0x7f);
+    match value {
+        [] => true,
+        [b' ' | b':' | b'<', ..] | [.., b' '] => false,
+        _ => value.iter().all(safe_char),
+    }
+}
+
+fn write_values<'a>(
+    f: &mut fmt::Formatter<'_>,
+    r#type: &[u8],
+    values: impl IntoIterator<Item = &'a rasn::types::OctetString>,
+) -> fmt::Result {
+    let r#type = String::from_utf8_lossy(r#type);
+    for value in values {
+        write_line(f, &r#type, value)?;
+    }
+
+    Ok(())
+}
+
+fn write_line(f: &mut fmt::Formatter<'_>, name: &str, value: &[u8]) -> fmt::Result {
+    let mut line = String::from(name);
+    push_value(&mut line, value);
+    write_folded(f, &line)
+}
+
+/// Appends a value, with the `:`, `: ` or `:: ` separating it from its name.
+fn push_value(line: &mut String, value: &[u8]) {
+    match value {
+        [] => line.push(':'),
+        _ if is_safe_string(value) => {
+            line.push_str(": ");
+            // Safe strings are ASCII.
+            line.push_str(core::str::from_utf8(value).unwrap_or_default());
+        }
+        _ => {
+            line.push_str(":: ");
+            line.push_str(&base64::encode(value));
+        }
+    }
+}
+
+fn write_control(f: &mut fmt::Formatter<'_>, control: &Control) -> fmt::Result {
+    let mut line = String::from("control: ");
+    line.push_str(&String::from_utf8_lossy(&control.control_type));
+    line.push_str(if control.criticality {
+        " true"
+    } else {
+        " false"
+    });
+    if let Some(value) = &control.control_value {
+        push_value(&mut line, value);
+    }
+    write_folded(f, &line)
+}
+
+/// Writes a line, continuing it on lines starting with a space once it
+/// reaches [`LINE_LENGTH`] columns.
+fn write_folded(f: &mut fmt::Formatter<'_>, line: &str) -> fmt::Result {
+    let mut column = 0;
+    for c in line.chars() {
+        if column == LINE_LENGTH {
+            f.write_str("\n ")?;
+            column = 1;
+        }
+        f.write_char(c)?;
+        column += 1;
+    }
+    f.write_char('\n')
+}
+
+/// A logical line of a record, after unfolding, with the line number it
+/// started on.
+struct Line {
+    number: usize,
+    text: String,
+}
+
+impl Line {
+    /// Splits the line into its attribute description and value.
+    fn parse(&self) -> Result<(&str, Vec<u8>), ParseLdifError> {
+        let line = self.number;
+        let (name, value) = self
+            .text
+            .split_once(':')
+            .filter(|(name, _)| is_attribute_description(name.as_bytes()))
+            .ok_or(ParseLdifError::InvalidLine { line })?;
+
+        let value = if let Some(value) = value.strip_prefix(':') {
+            base64::decode(value.trim_start_matches(' '))
+                .map_err(|_| ParseLdifError::InvalidBase64 { line })?
+        } else if value.starts_with('<') {
+            return Err(ParseLdifError::UnsupportedUrl { line });
+        } else {
+            value.trim_start_matches(' ').as_bytes().to_vec()
+        };
+
+        Ok((name, value))
+    }
+
+    /// Parses a line named `name`, returning its value.
+    fn value(&self, name: &str) -> Result<Vec<u8>, ParseLdifError> {
+        match self.parse()? {
+            (actual, value) if actual.eq_ignore_ascii_case(name) => Ok(value),
+            _ => Err(ParseLdifError::InvalidRecord { line: self.number }),
+        }
+    }
+}
+
+/// Reads the records of an LDIF file one at a time.
+///
+/// After an error, reading continues from the next record.
+#[derive(Clone, Debug)]
+pub struct LdifReader<'a> {
+    lines: core::iter::Enumerate<core::str::Split<'a, char>>,
+    first: bool,
+}
+
+impl<'a> LdifReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            lines: input.split('\n').enumerate(),
+            first: true,
+        }
+    }
+
+    /// Reads the lines of the next record, unfolding them and leaving out
+    /// comments, or returns `None` at the end of the input.
+    fn next_lines(&mut self) -> Option<Result<Vec<Line>, ParseLdifError>> {
+        let mut lines: Vec<Line> = Vec::new();
+        let mut comment = false;
+        let mut error = None;
+        for (index, line) in self.lines.by_ref() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if let Some(continuation) = line.strip_prefix(' ') {
+                match lines.last_mut() {
+                    _ if comment => {}
+                    Some(last) => last.text.push_str(continuation),
+                    None => {
+                        error.get_or_insert(ParseLdifError::InvalidLine { line: index + 1 });
+                    }
+                }
+                continue;
+            }
+
+            comment = line.starts_with('#');
+            if comment {
+                continue;
+            }
+            if line.is_empty() {
+                if lines.is_empty() && error.is_none() {
+                    continue;
+                }
+                break;
+            }
+            lines.push(Line {
+                number: index + 1,
+                text: line.into(),
+            });
+        }
+
+        match error {
+            Some(error) => Some(Err(error)),
+            None if lines.is_empty() => None,
+            None => Some(Ok(lines)),
+        }
+    }
+}
+
+impl Iterator for LdifReader<'_> {
+    type Item = Result<LdifRecord, ParseLdifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines = match self.next_lines()? {
+            Ok(lines) => lines,
+            Err(error) => return Some(Err(error)),
+        };
+
+        if core::mem::take(&mut self.first)
+            && lines[0]
+                .text
+                .get(..8)
+                .is_some_and(|name| name.eq_ignore_ascii_case("version:"))
+        {
+            let version = lines.remove(0);
+            if version.value("version").as_deref() != Ok(b"1") {
+                return Some(Err(ParseLdifError::InvalidVersion {
+                    line: version.number,
+                }));
+            }
+            // The version may be followed by a blank line, or by a record.
+            if lines.is_empty() {
+                return self.next();
+            }
+        }
+
+        Some(parse_record(&lines))
+    }
+}
+
+fn parse_record(lines: &[Line]) -> Result<LdifRecord, ParseLdifError> {
+    let dn = lines[0].value("dn").map_err(|error| match error {
+        ParseLdifError::InvalidRecord { line } => ParseLdifError::MissingDn { line },
+        error => error,
+    })?;
+    let mut lines = lines[1..].iter().peekable();
+
+    let mut controls = Controls::new();
+    let mut first_control = None;
+    while let Some(line) = lines.next_if(|line| line.value("control").is_ok()) {
+        first_control.get_or_insert(line.number);
+        controls.push(parse_control(line)?);
+    }
+
+    let Some(change_type) = lines.next_if(|line| line.value("changetype").is_ok()) else {
+        // Only change records have controls.
+        if let Some(line) = first_control {
+            return Err(ParseLdifError::InvalidControl { line });
+        }
+        return Ok(LdifRecord::Entry(SearchResultEntry::new(
+            dn.into(),
+            parse_attributes(lines)?
+                .into_iter()
+                .map(|attribute| PartialAttribute::new(attribute.r#type, attribute.vals))
+                .collect(),
+        )));
+    };
+
+    let change = match &*change_type.value("changetype")? {
+        b"add" => LdifChange::Add(AddRequest {
+            entry: dn.into(),
+            attributes: parse_attributes(lines)?,
+        }),
+        b"delete" => match lines.next() {
+            Some(line) => return Err(ParseLdifError::InvalidRecord { line: line.number }),
+            None => LdifChange::Delete(DelRequest(dn.into())),
+        },
+        b"modify" => LdifChange::Modify(ModifyRequest {
+            object: dn.into(),
+            changes: parse_changes(lines)?,
+        }),
+        b"modrdn" | b"moddn" => {
+            let mut next = |name| {
+                let line = lines.next().ok_or(ParseLdifError::InvalidRecord {
+                    line: change_type.number,
+                })?;
+                Ok((line.number, line.value(name)?))
+            };
+            let (_, new_rdn) = next("newrdn")?;
+            let delete_old_rdn = match next("deleteoldrdn")? {
+                (_, value) if value == b"0" => false,
+                (_, value) if value == b"1" => true,
+                (line, _) => return Err(ParseLdifError::InvalidRecord { line }),
+            };
+            let new_superior = match lines.next() {
+                Some(line) => Some(line.value("newsuperior")?.into()),
+                None => None,
+            };
+            if let Some(line) = lines.next() {
+                return Err(ParseLdifError::InvalidRecord { line: line.number });
+            }
+            LdifChange::ModifyDn(ModifyDnRequest {
+                entry: dn.into(),
+                new_rdn: new_rdn.into(),
+                delete_old_rdn,
+                new_superior,
+            })
+        }
+        _ => {
+            return Err(ParseLdifError::InvalidChangeType {
+                line: change_type.number,
+            })
+        }
+    };
+
+    Ok(LdifRecord::Change { controls, change })
+}
+
+/// Parses a `control` line's value: an OID, optionally followed by `true` or
+/// `false` and a value.
+fn parse_control(line: &Line) -> Result<Control, ParseLdifError> {
+    let invalid = ParseLdifError::InvalidControl { line: line.number };
+    let (_, text) = line.text.split_once(':').ok_or(invalid.clone())?;
+    let text = text.trim_start_matches(' ');
+    let (spec, value) = match text.split_once(':') {
+        Some((spec, value)) => (spec, Some(value)),
+        None => (text, None),
+    };
+
+    let mut words = spec.split(' ').filter(|word| !word.is_empty());
+    let oid = words
+        .next()
+        .filter(|oid| is_oid(oid.as_bytes()) && oid.starts_with(|c: char| c.is_ascii_digit()))
+        .ok_or(invalid.clone())?;
+    let criticality = match words.next() {
+        Some("true") => true,
+        Some("false") | None => false,
+        Some(_) => return Err(invalid),
+    };
+    if words.next().is_some() {
+        return Err(invalid);
+    }
+
+    // Reuse the parsing of ordinary lines for the value.
+    let value = match value {
+        Some(value) => {
+            let line = Line {
+                number: line.number,
+                text: ["control:", value].concat(),
+            };
+            Some(line.parse()?.1.into())
+        }
+        None => None,
+    };
+
+    Ok(Control::new(String::from(oid).into(), criticality, value))
+}
+
+/// Parses attribute lines, grouping the values of each attribute in the
+/// order the attributes first appear.
+fn parse_attributes<'a>(
+    lines: impl Iterator<Item = &'a Line>,
+) -> Result<Vec<Attribute>, ParseLdifError> {
+    let mut attributes: Vec<Attribute> = Vec::new();
+    for line in lines {
+        let (name, value) = line.parse()?;
+        match attributes
+            .iter_mut()
+            .find(|attribute| attribute.r#type.eq_ignore_ascii_case(name.as_bytes()))
+        {
+            Some(attribute) => {
+                attribute.vals.insert(value.into());
+            }
+            None => attributes.push(Attribute::new(
+                String::from(name).into(),
+                [value.into()].into(),
+            )),
+        }
+    }
+
+    Ok(attributes)
+}
+
+/// Parses the changes of a `modify` record, each of which is an `add`,
+/// `delete` or `replace` line, followed by the values of the attribute and a
+/// `-` line.
+fn parse_changes<'a>(
+    mut lines: impl Iterator<Item = &'a Line>,
+) -> Result<Vec<ModifyRequestChanges>, ParseLdifError> {
+    let mut changes = Vec::new();
+    while let Some(line) = lines.next() {
+        let (operation, r#type) = line.parse()?;
+        let operation = match operation.to_ascii_lowercase().as_str() {
+            "add" => ChangeOperation::Add,
+            "delete" => ChangeOperation::Delete,
+            "replace" => ChangeOperation::Replace,
+            _ => return Err(ParseLdifError::InvalidRecord { line: line.number }),
+        };
+        if !is_attribute_description(&r#type) {
+            return Err(ParseLdifError::InvalidRecord { line: line.number });
+        }
+
+        let mut modification = PartialAttribute::new(r#type.into(), Default::default());
+        // The last change's `-` is often left out.
+        for line in lines.by_ref() {
+            if line.text == "-" {
+                break;
+            }
+            let (name, value) = line.parse()?;
+            if !name.as_bytes().eq_ignore_ascii_case(&modification.r#type) {
+                return Err(ParseLdifError::InvalidRecord { line: line.number });
+            }
+            modification.vals.insert(value.into());
+        }
+        changes.push(ModifyRequestChanges {
+            operation,
+            modification,
+        });
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    use pretty_assertions::assert_eq;
+
+    fn attribute(r#type: &'static str, vals: &[&'static [u8]]) -> PartialAttribute {
+        PartialAttribute::new(
+            r#type.into(),
+            vals.iter().map(|val| (*val).into()).collect(),
+        )
+    }
+
+    fn parse(ldif: &str) -> Vec<LdifRecord> {
+        ldif.parse::<Ldif>().unwrap().records
+    }
+
+    // RFC 2849 Example 2, with a folded value, and Example 3.
+    #[test]
+    fn content() {
+        let records = parse(
+            "version: 1\n\
+             dn:cn=Barbara Jensen, ou=Product Development, dc=airius, dc=com\n\
+             objectclass:top\n\
+             objectclass:person\n\
+             cn:Barbara Jensen\n\
+             description:Babs is a big sailing fan, and travels extensively in sea\n \
+             rch of perfect sailing conditions.\n\
+             \n\
+             # A comment,\n  continued.\n\
+             dn: cn=Gern Jensen, ou=Product Testing, dc=airius, dc=com\r\n\
+             cn: Gern Jensen\r\n\
+             description:: V2hhdCBhIGNhcmVmdWwgcmVhZGVyIHlvdSBhcmUhICBUaGlzIHZhbHVlIHd\n \
+             pbGwgYmUgYmFzZS02NC1lbmNvZGVkIGJlY2F1c2UgaXQgaGFzIGEgY29udHJvbCBjaGFyYW\n \
+             N0ZXIgaW4gaXQgKGEgQ1IpLg0gIEJ5IHRoZSB3YXksIHlvdSBzaG91bGQgcmVhbGx5IGdld\n \
+             CBvdXQgbW9yZS4=\n\
+             title:\n\n\n",
+        );
+
+        assert_eq!(
+            vec![
+                LdifRecord::Entry(SearchResultEntry::new(
+                    "cn=Barbara Jensen, ou=Product Development, dc=airius, dc=com".into(),
+                    vec![
+                        attribute("objectclass", &[b"top", b"person"]),
+                        attribute("cn", &[b"Barbara Jensen"]),
+                        attribute(
+                            "description",
+                            &[b"Babs is a big sailing fan, and travels extensively in search of perfect sailing conditions."]
+                        ),
+                    ],
+                )),
+                LdifRecord::Entry(SearchResultEntry::new(
+                    "cn=Gern Jensen, ou=Product Testing, dc=airius, dc=com".into(),
+                    vec![
+                        attribute("cn", &[b"Gern Jensen"]),
+                        attribute(
+                            "description",
+                            &[b"What a careful reader you are!  This value will be base-64-encoded because it has a control character in it (a CR).\r  By the way, you should really get out more."]
+                        ),
+                        attribute("title", &[b""]),
+                    ],
+                )),
+            ],
+            records
+        );
+
+        let LdifRecord::Entry(entry) = records[1].clone() else {
+            unreachable!()
+        };
+        let add = AddRequest::from(entry);
+        assert_eq!(&*add.attributes[0].r#type, b"cn");
+    }
+
+    // RFC 2849 Example 6, and a change with a control from Example 7.
+    #[test]
+    fn changes() {
+        let records = parse(
+            "version: 1\n\
+             \n\
+             dn: cn=Fiona Jensen, ou=Marketing, dc=airius, dc=com\n\
+             changetype: add\n\
+             objectclass: top\n\
+             objectclass: person\n\
+             cn: Fiona Jensen\n\
+             \n\
+             dn: cn=Robert Jensen, ou=Marketing, dc=airius, dc=com\n\
+             changetype: delete\n\
+             \n\
+             dn: cn=Paul Jensen, ou=Product Development, dc=airius, dc=com\n\
+             changetype: modrdn\n\
+             newrdn: cn=Paula Jensen\n\
+             deleteoldrdn: 1\n\
+             \n\
+             dn: ou=PD Accountants, ou=Product Development, dc=airius, dc=com\n\
+             changetype: modrdn\n\
+             newrdn: ou=Product Development Accountants\n\
+             deleteoldrdn: 0\n\
+             newsuperior: ou=Accounting, dc=airius, dc=com\n\
+             \n\
+             dn: cn=Paula Jensen, ou=Product Development, dc=airius, dc=com\n\
+             changetype: modify\n\
+             add: postaladdress\n\
+             postaladdress: 123 Anystreet $ Sunnyvale, CA $ 94086\n\
+             -\n\
+             delete: description\n\
+             -\n\
+             replace: telephonenumber\n\
+             telephonenumber: +1 408 555 1234\n\
+             telephonenumber: +1 408 555 5678\n\
+             -\n\
+             delete: facsimiletelephonenumber\n\
+             facsimiletelephonenumber: +1 408 555 9876\n\
+             -\n\
+             \n\
+             dn: ou=Product Development, dc=airius, dc=com\n\
+             control: 1.2.840.113556.1.4.805 true\n\
+             changetype: delete\n",
+        );
+
+        let change = |change| LdifRecord::Change {
+            controls: vec![],
+            change,
+        };
+        let modification = |operation, r#type, vals| ModifyRequestChanges {
+            operation,
+            modification: attribute(r#type, vals),
+        };
+        assert_eq!(
+            vec![
+                change(LdifChange::Add(AddRequest {
+                    entry: "cn=Fiona Jensen, ou=Marketing, dc=airius, dc=com".into(),
+                    attributes: vec![
+                        Attribute::new(
+                            "objectclass".into(),
+                            ["top".into(), "person".into()].into()
+                        ),
+                        Attribute::new("cn".into(), ["Fiona Jensen".into()].into()),
+                    ],
+                })),
+                change(LdifChange::Delete(DelRequest(
+                    "cn=Robert Jensen, ou=Marketing, dc=airius, dc=com".into()
+                ))),
+                change(LdifChange::ModifyDn(ModifyDnRequest {
+                    entry: "cn=Paul Jensen, ou=Product Development, dc=airius, dc=com".into(),
+                    new_rdn: "cn=Paula Jensen".into(),
+                    delete_old_rdn: true,
+                    new_superior: None,
+                })),
+                change(LdifChange::ModifyDn(ModifyDnRequest {
+                    entry: "ou=PD Accountants, ou=Product Development, dc=airius, dc=com".into(),
+                    new_rdn: "ou=Product Development Accountants".into(),
+                    delete_old_rdn: false,
+                    new_superior: Some("ou=Accounting, dc=airius, dc=com".into()),
+                })),
+                change(LdifChange::Modify(ModifyRequest {
+                    object: "cn=Paula Jensen, ou=Product Development, dc=airius, dc=com".into(),
+                    changes: vec![
+                        modification(
+                            ChangeOperation::Add,
+                            "postaladdress",
+                            &[b"123 Anystreet $ Sunnyvale, CA $ 94086"]
+                        ),
+                        modification(ChangeOperation::Delete, "description", &[]),
+                        modification(
+                            ChangeOperation::Replace,
+                            "telephonenumber",
+                            &[b"+1 408 555 1234", b"+1 408 555 5678"]
+                        ),
+                        modification(
+                            ChangeOperation::Delete,
+                            "facsimiletelephonenumber",
+                            &[b"+1 408 555 9876"]
+                        ),
+                    ],
+                })),
+                LdifRecord::Change {
+                    controls: vec![Control::new("1.2.840.113556.1.4.805".into(), true, None)],
+                    change: LdifChange::Delete(DelRequest(
+                        "ou=Product Development, dc=airius, dc=com".into()
+                    )),
+                },
+            ],
+            records
+        );
+        assert!(matches!(
+            ProtocolOp::from(LdifChange::Delete(DelRequest("cn=a".into()))),
+            ProtocolOp::DelRequest(_)
+        ));
+    }
+
+    #[test]
+    fn write() {
+        let ldif = Ldif::new(vec![
+            LdifRecord::Entry(SearchResultEntry::new(
+                "cn=Émile,dc=example,dc=com".into(),
+                vec![
+                    attribute("cn", &["Émile".as_bytes()]),
+                    attribute(
+                        "description",
+                        &[b" leading space", b"plain", b":colon", b""],
+                    ),
+                    attribute("jpegPhoto", &[&[0xff, 0xd8, 0x00, 0x0a]]),
+                    attribute("note", &[&[b'x'; 80]]),
+                ],
+            )),
+            LdifRecord::Change {
+                controls: vec![Control::new("1.2.3.4".into(), false, Some("a\nb".into()))],
+                change: LdifChange::Modify(ModifyRequest {
+                    object: "dc=example,dc=com".into(),
+                    changes: vec![ModifyRequestChanges {
+                        operation: ChangeOperation::Replace,
+                        modification: attribute("description", &[b"new"]),
+                    }],
+                }),
+            },
+            LdifRecord::Change {
+                controls: vec![],
+                change: LdifChange::ModifyDn(ModifyDnRequest {
+                    entry: "cn=a,dc=example,dc=com".into(),
+                    new_rdn: "cn=b".into(),
+                    delete_old_rdn: true,
+                    new_superior: None,
+                }),
+            },
+        ]);
+
+        let written = ldif.to_string();
+        assert_eq!(
+            "version: 1\n\
+             \n\
+             dn:: Y249w4ltaWxlLGRjPWV4YW1wbGUsZGM9Y29t\n\
+             cn:: w4ltaWxl\n\
+             description:\n\
+             description:: IGxlYWRpbmcgc3BhY2U=\n\
+             description:: OmNvbG9u\n\
+             description: plain\n\
+             jpegPhoto:: /9gACg==\n\
+             note: xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx\n \
+             xxxxxxxxxx\n\
+             \n\
+             dn: dc=example,dc=com\n\
+             control: 1.2.3.4 false:: YQpi\n\
+             changetype: modify\n\
+             replace: description\n\
+             description: new\n\
+             -\n\
+             \n\
+             dn: cn=a,dc=example,dc=com\n\
+             changetype: modrdn\n\
+             newrdn: cn=b\n\
+             deleteoldrdn: 1\n",
+            written
+        );
+        assert_eq!(ldif, written.parse().unwrap());
+    }
+
+    #[test]
+    fn reader() {
+        let mut reader = LdifReader::new(
+            "dn: cn=a\ncn: a\n\ndn: cn=b\ncn:: !!\n\n\n# Last.\ndn: cn=c\nchangetype: delete\n",
+        );
+        assert!(matches!(reader.next(), Some(Ok(LdifRecord::Entry(_)))));
+        assert_eq!(
+            Some(Err(ParseLdifError::InvalidBase64 { line: 5 })),
+            reader.next()
+        );
+        assert!(matches!(reader.next(), Some(Ok(LdifRecord::Change { .. }))));
+        assert_eq!(None, reader.next());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |ldif: &str| ldif.parse::<Ldif>().unwrap_err();
+        assert_eq!(
+            ParseLdifError::InvalidVersion { line: 1 },
+            error("version: 2\n\ndn: cn=a\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidLine { line: 1 },
+            error(" dn: cn=a\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidLine { line: 2 },
+            error("dn: cn=a\nno colon\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidLine { line: 2 },
+            error("dn: cn=a\nc n: a\n")
+        );
+        assert_eq!(ParseLdifError::MissingDn { line: 1 }, error("cn: a\n"));
+        assert_eq!(
+            ParseLdifError::UnsupportedUrl { line: 2 },
+            error("dn: cn=a\njpegPhoto:< file:///photo.jpg\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidChangeType { line: 2 },
+            error("dn: cn=a\nchangetype: rename\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidControl { line: 2 },
+            error("dn: cn=a\ncontrol: 1.2.3 maybe\nchangetype: delete\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidControl { line: 2 },
+            error("dn: cn=a\ncontrol: 1.2.3\ncn: a\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidRecord { line: 3 },
+            error("dn: cn=a\nchangetype: delete\ncn: a\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidRecord { line: 2 },
+            error("dn: cn=a\nchangetype: modrdn\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidRecord { line: 4 },
+            error("dn: cn=a\nchangetype: modrdn\nnewrdn: cn=b\ndeleteoldrdn: yes\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidRecord { line: 4 },
+            error("dn: cn=a\nchangetype: modify\nadd: cn\nsn: b\n-\n")
+        );
+        assert_eq!(
+            ParseLdifError::InvalidRecord { line: 3 },
+            error("dn: cn=a\nchangetype: modify\nincrement: uidNumber\n")
+        );
+    }
+}
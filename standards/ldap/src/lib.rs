@@ -3,6 +3,15 @@
 
 extern crate alloc;
 
+pub mod controls;
+pub mod extended;
+pub mod filter;
+pub mod ldif;
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod transport;
+
 use rasn::{types::*, Decode, Encode};
 
 /// ID value of a corresponding request [`LdapMessage`].
@@ -0,0 +1,1040 @@
+//! # Schema Descriptions
+//!
+//! This module parses and writes the attribute type and object class
+//! descriptions of [RFC 4512], which a server publishes in the
+//! `attributeTypes` and `objectClasses` attributes of its subschema entry,
+//! such as:
+//!
+//! ```text
+//! ( 2.5.4.3 NAME ( 'cn' 'commonName' ) SUP name )
+//! ( 2.5.6.6 NAME 'person' SUP top STRUCTURAL MUST ( sn $ cn ) MAY ( userPassword $ telephoneNumber ) )
+//! ```
+//!
+//! [`Schema`] collects the descriptions from a subschema entry, and checks
+//! attribute values against the syntaxes of [RFC 4517] that can be checked
+//! without further context. Values of other syntaxes are accepted as they
+//! are.
+//!
+//! [RFC 4512]: https://datatracker.ietf.org/doc/html/rfc4512#section-4.1
+//! [RFC 4517]: https://datatracker.ietf.org/doc/html/rfc4517#section-3.3
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use crate::filter::is_oid;
+use crate::{AttributeValue, SearchResultEntry};
+
+/// The syntaxes of [RFC 4517] that [`Schema::check_attribute`] checks
+/// values against.
+///
+/// [RFC 4517]: https://datatracker.ietf.org/doc/html/rfc4517#section-3.3
+pub mod syntax {
+    pub const BIT_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.6";
+    pub const BOOLEAN: &str = "1.3.6.1.4.1.1466.115.121.1.7";
+    pub const COUNTRY_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.11";
+    pub const DN: &str = "1.3.6.1.4.1.1466.115.121.1.12";
+    pub const DIRECTORY_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.15";
+    pub const GENERALIZED_TIME: &str = "1.3.6.1.4.1.1466.115.121.1.24";
+    pub const IA5_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.26";
+    pub const INTEGER: &str = "1.3.6.1.4.1.1466.115.121.1.27";
+    pub const NUMERIC_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.36";
+    pub const OID: &str = "1.3.6.1.4.1.1466.115.121.1.38";
+    pub const OCTET_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.40";
+    pub const PRINTABLE_STRING: &str = "1.3.6.1.4.1.1466.115.121.1.44";
+    pub const TELEPHONE_NUMBER: &str = "1.3.6.1.4.1.1466.115.121.1.50";
+}
+
+/// An error produced when parsing a schema description.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseSchemaError {
+    /// A description or list didn't start with `(`, or didn't end with `)`.
+    MissingParenthesis { position: usize },
+    /// An OID, or a name, wasn't valid.
+    InvalidOid { position: usize },
+    /// A field name wasn't one defined for the description.
+    InvalidKeyword { position: usize },
+    /// A quoted string wasn't terminated, or had an escape other than `\27`
+    /// or `\5C`.
+    InvalidQuotedString { position: usize },
+    /// A field's value wasn't valid, such as a `USAGE` that isn't defined.
+    InvalidValue { position: usize },
+    /// The description was followed by more characters.
+    TrailingCharacters { position: usize },
+    /// A subschema entry's description wasn't valid UTF-8.
+    InvalidUtf8 { position: usize },
+    /// The string ended where more of the description was expected.
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ParseSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingParenthesis { position } => {
+                write!(f, "expected parenthesis at {position}")
+            }
+            Self::InvalidOid { position } => write!(f, "invalid OID at {position}"),
+            Self::InvalidKeyword { position } => write!(f, "unknown field at {position}"),
+            Self::InvalidQuotedString { position } => {
+                write!(f, "invalid quoted string at {position}")
+            }
+            Self::InvalidValue { position } => write!(f, "invalid value at {position}"),
+            Self::TrailingCharacters { position } => {
+                write!(f, "unexpected characters after description at {position}")
+            }
+            Self::InvalidUtf8 { position } => write!(f, "invalid UTF-8 at {position}"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of description"),
+        }
+    }
+}
+
+/// An error returned when an attribute doesn't conform to a [`Schema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaViolation {
+    /// The schema doesn't define the attribute type.
+    UndefinedAttributeType,
+    /// A single valued attribute had more than one value.
+    MultipleValues,
+    /// A value didn't conform to the attribute's syntax.
+    InvalidSyntax(AttributeValue),
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedAttributeType => write!(f, "undefined attribute type"),
+            Self::MultipleValues => write!(f, "multiple values for a single valued attribute"),
+            Self::InvalidSyntax(value) => {
+                write!(f, "invalid value {:?}", String::from_utf8_lossy(value))
+            }
+        }
+    }
+}
+
+/// What an attribute type is used for, from its `USAGE`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AttributeUsage {
+    #[default]
+    UserApplications,
+    DirectoryOperation,
+    DistributedOperation,
+    DsaOperation,
+}
+
+impl AttributeUsage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::UserApplications => "userApplications",
+            Self::DirectoryOperation => "directoryOperation",
+            Self::DistributedOperation => "distributedOperation",
+            Self::DsaOperation => "dSAOperation",
+        }
+    }
+}
+
+/// The kind of an object class.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ObjectClassKind {
+    Abstract,
+    #[default]
+    Structural,
+    Auxiliary,
+}
+
+impl ObjectClassKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Abstract => "ABSTRACT",
+            Self::Structural => "STRUCTURAL",
+            Self::Auxiliary => "AUXILIARY",
+        }
+    }
+}
+
+/// An attribute type description, from a subschema entry's `attributeTypes`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct AttributeTypeDescription {
+    pub oid: String,
+    pub names: Vec<String>,
+    pub description: Option<String>,
+    pub obsolete: bool,
+    /// The attribute type this one is a subtype of.
+    pub superior: Option<String>,
+    pub equality: Option<String>,
+    pub ordering: Option<String>,
+    pub substring: Option<String>,
+    pub syntax: Option<String>,
+    /// The suggested maximum length of values, from `SYNTAX oid{length}`.
+    pub syntax_length: Option<u32>,
+    pub single_value: bool,
+    pub collective: bool,
+    pub no_user_modification: bool,
+    pub usage: AttributeUsage,
+    /// Fields whose names start with `X-`, with their values.
+    pub extensions: Vec<(String, Vec<String>)>,
+}
+
+impl AttributeTypeDescription {
+    /// AttributeTypeDescription constructor.
+    pub fn new(oid: String) -> Self {
+        Self {
+            oid,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `name` is the attribute type's OID or one of its names,
+    /// ignoring case.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.oid.eq_ignore_ascii_case(name)
+            || self.names.iter().any(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+/// An object class description, from a subschema entry's `objectClasses`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct ObjectClassDescription {
+    pub oid: String,
+    pub names: Vec<String>,
+    pub description: Option<String>,
+    pub obsolete: bool,
+    pub superiors: Vec<String>,
+    pub kind: ObjectClassKind,
+    pub must: Vec<String>,
+    pub may: Vec<String>,
+    /// Fields whose names start with `X-`, with their values.
+    pub extensions: Vec<(String, Vec<String>)>,
+}
+
+impl ObjectClassDescription {
+    /// ObjectClassDescription constructor.
+    pub fn new(oid: String) -> Self {
+        Self {
+            oid,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `name` is the object class's OID or one of its names,
+    /// ignoring case.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.oid.eq_ignore_ascii_case(name)
+            || self.names.iter().any(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The attribute types and object classes of a subschema entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Schema {
+    pub attribute_types: Vec<AttributeTypeDescription>,
+    pub object_classes: Vec<ObjectClassDescription>,
+}
+
+impl Schema {
+    /// Schema constructor.
+    pub fn new(
+        attribute_types: Vec<AttributeTypeDescription>,
+        object_classes: Vec<ObjectClassDescription>,
+    ) -> Self {
+        Self {
+            attribute_types,
+            object_classes,
+        }
+    }
+
+    /// Parses the `attributeTypes` and `objectClasses` of a subschema entry,
+    /// as returned by a search of its DN with the filter
+    /// `(objectClass=subschema)`.
+    pub fn from_subschema(entry: &SearchResultEntry) -> Result<Self, ParseSchemaError> {
+        fn parse<T: core::str::FromStr<Err = ParseSchemaError>>(
+            value: &[u8],
+        ) -> Result<T, ParseSchemaError> {
+            core::str::from_utf8(value)
+                .map_err(|error| ParseSchemaError::InvalidUtf8 {
+                    position: error.valid_up_to(),
+                })?
+                .parse()
+        }
+
+        let mut schema = Self::default();
+        for attribute in &entry.attributes {
+            if attribute.r#type.eq_ignore_ascii_case(b"attributeTypes") {
+                for value in &attribute.vals {
+                    schema.attribute_types.push(parse(value)?);
+                }
+            } else if attribute.r#type.eq_ignore_ascii_case(b"objectClasses") {
+                for value in &attribute.vals {
+                    schema.object_classes.push(parse(value)?);
+                }
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Returns the attribute type named `name`, ignoring any options such as
+    /// `;binary`.
+    pub fn attribute_type(&self, name: &str) -> Option<&AttributeTypeDescription> {
+        let name = name.split(';').next().unwrap_or_default();
+        self.attribute_types
+            .iter()
+            .find(|attribute_type| attribute_type.is_named(name))
+    }
+
+    /// Returns the object class named `name`.
+    pub fn object_class(&self, name: &str) -> Option<&ObjectClassDescription> {
+        self.object_classes
+            .iter()
+            .find(|object_class| object_class.is_named(name))
+    }
+
+    /// Returns the syntax of the attribute type named `name`, which it may
+    /// inherit from its superior.
+    pub fn syntax(&self, name: &str) -> Option<&str> {
+        let mut attribute_type = self.attribute_type(name)?;
+        // Bounded, in case the superiors form a cycle.
+        for _ in 0..=self.attribute_types.len() {
+            if let Some(syntax) = &attribute_type.syntax {
+                return Some(syntax);
+            }
+            attribute_type = self.attribute_type(attribute_type.superior.as_deref()?)?;
+        }
+
+        None
+    }
+
+    /// Checks an attribute's values against its attribute type.
+    pub fn check_attribute<'a>(
+        &self,
+        r#type: &[u8],
+        values: impl IntoIterator<Item = &'a AttributeValue>,
+    ) -> Result<(), SchemaViolation> {
+        let name = core::str::from_utf8(r#type).unwrap_or_default();
+        let attribute_type = self
+            .attribute_type(name)
+            .ok_or(SchemaViolation::UndefinedAttributeType)?;
+        let syntax = self.syntax(name).unwrap_or_default();
+
+        let mut count = 0;
+        for value in values {
+            count += 1;
+            if attribute_type.single_value && count > 1 {
+                return Err(SchemaViolation::MultipleValues);
+            }
+            if !matches_syntax(syntax, value) {
+                return Err(SchemaViolation::InvalidSyntax(value.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `value` conforms to the syntax with the OID `syntax`, for the
+/// syntaxes in [`syntax`]. Values of other syntaxes always conform.
+pub fn matches_syntax(syntax: &str, value: &[u8]) -> bool {
+    let printable = |byte: &u8| byte.is_ascii_alphanumeric() || b"'()+,-./:? =".contains(byte);
+    match syntax {
+        syntax::BIT_STRING => value
+            .strip_prefix(b"'")
+            .and_then(|value| value.strip_suffix(b"'B"))
+            .is_some_and(|bits| bits.iter().all(|bit| matches!(bit, b'0' | b'1'))),
+        syntax::BOOLEAN => matches!(value, b"TRUE" | b"FALSE"),
+        syntax::COUNTRY_STRING => value.len() == 2 && value.iter().all(printable),
+        syntax::DN => core::str::from_utf8(value).is_ok(),
+        syntax::DIRECTORY_STRING => !value.is_empty() && core::str::from_utf8(value).is_ok(),
+        syntax::GENERALIZED_TIME => is_generalized_time(value),
+        syntax::IA5_STRING => value.is_ascii(),
+        syntax::INTEGER => match value.strip_prefix(b"-").unwrap_or(value) {
+            b"0" => value == b"0",
+            [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+            _ => false,
+        },
+        syntax::NUMERIC_STRING => {
+            !value.is_empty()
+                && value
+                    .iter()
+                    .all(|byte| byte.is_ascii_digit() || *byte == b' ')
+        }
+        syntax::OID => is_oid(value),
+        syntax::PRINTABLE_STRING | syntax::TELEPHONE_NUMBER => {
+            !value.is_empty() && value.iter().all(printable)
+        }
+        _ => true,
+    }
+}
+
+/// Whether `value` is a `YYYYMMDDHH[MM[SS]][(.|,)fraction](Z|(+|-)HH[MM])`
+/// time.
+fn is_generalized_time(value: &[u8]) -> bool {
+    let digits = value
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let (time, mut rest) = value.split_at(digits);
+    let number = |digits: &[u8]| {
+        digits
+            .iter()
+            .fold(0u32, |number, digit| number * 10 + u32::from(digit - b'0'))
+    };
+    let valid_time = matches!(time.len(), 10 | 12 | 14)
+        && (1..=12).contains(&number(&time[4..6]))
+        && (1..=31).contains(&number(&time[6..8]))
+        && number(&time[8..10]) < 24
+        && time.get(10..12).is_none_or(|minutes| number(minutes) < 60)
+        // Allowing for leap seconds.
+        && time.get(12..14).is_none_or(|seconds| number(seconds) < 61);
+    if !valid_time {
+        return false;
+    }
+
+    if let [b'.' | b',', fraction @ ..] = rest {
+        let digits = fraction
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+    match rest {
+        b"Z" => true,
+        [b'+' | b'-', offset @ ..] => {
+            matches!(offset.len(), 2 | 4)
+                && offset.iter().all(u8::is_ascii_digit)
+                && number(&offset[..2]) < 24
+                && offset.get(2..).is_none_or(|minutes| number(minutes) < 60)
+        }
+        _ => false,
+    }
+}
+
+impl core::str::FromStr for AttributeTypeDescription {
+    type Err = ParseSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let mut description = Self::new(parser.start()?);
+        while let Some((position, keyword)) = parser.keyword()? {
+            match &*keyword.to_ascii_uppercase() {
+                "NAME" => description.names = parser.qdescrs()?,
+                "DESC" => description.description = Some(parser.qdstring()?),
+                "OBSOLETE" => description.obsolete = true,
+                "SUP" => description.superior = Some(parser.oid()?),
+                "EQUALITY" => description.equality = Some(parser.oid()?),
+                "ORDERING" => description.ordering = Some(parser.oid()?),
+                "SUBSTR" => description.substring = Some(parser.oid()?),
+                "SYNTAX" => {
+                    let (syntax, length) = parser.noidlen()?;
+                    description.syntax = Some(syntax);
+                    description.syntax_length = length;
+                }
+                "SINGLE-VALUE" => description.single_value = true,
+                "COLLECTIVE" => description.collective = true,
+                "NO-USER-MODIFICATION" => description.no_user_modification = true,
+                "USAGE" => {
+                    let (position, usage) = parser.word()?;
+                    description.usage = [
+                        AttributeUsage::UserApplications,
+                        AttributeUsage::DirectoryOperation,
+                        AttributeUsage::DistributedOperation,
+                        AttributeUsage::DsaOperation,
+                    ]
+                    .into_iter()
+                    .find(|known| known.as_str().eq_ignore_ascii_case(usage))
+                    .ok_or(ParseSchemaError::InvalidValue { position })?;
+                }
+                _ if is_extension(keyword) => description
+                    .extensions
+                    .push((keyword.into(), parser.qdstrings()?)),
+                _ => return Err(ParseSchemaError::InvalidKeyword { position }),
+            }
+        }
+
+        Ok(description)
+    }
+}
+
+impl fmt::Display for AttributeTypeDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "( {}", self.oid)?;
+        write_names(f, &self.names)?;
+        if let Some(description) = &self.description {
+            f.write_str(" DESC ")?;
+            write_qdstring(f, description)?;
+        }
+        if self.obsolete {
+            f.write_str(" OBSOLETE")?;
+        }
+        for (keyword, oid) in [
+            ("SUP", &self.superior),
+            ("EQUALITY", &self.equality),
+            ("ORDERING", &self.ordering),
+            ("SUBSTR", &self.substring),
+            ("SYNTAX", &self.syntax),
+        ] {
+            if let Some(oid) = oid {
+                write!(f, " {keyword} {oid}")?;
+            }
+        }
+        if let (Some(_), Some(length)) = (&self.syntax, self.syntax_length) {
+            write!(f, "{{{length}}}")?;
+        }
+        for (keyword, flag) in [
+            ("SINGLE-VALUE", self.single_value),
+            ("COLLECTIVE", self.collective),
+            ("NO-USER-MODIFICATION", self.no_user_modification),
+        ] {
+            if flag {
+                write!(f, " {keyword}")?;
+            }
+        }
+        if self.usage != AttributeUsage::UserApplications {
+            write!(f, " USAGE {}", self.usage.as_str())?;
+        }
+        write_extensions(f, &self.extensions)?;
+        f.write_str(" )")
+    }
+}
+
+impl core::str::FromStr for ObjectClassDescription {
+    type Err = ParseSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let mut description = Self::new(parser.start()?);
+        while let Some((position, keyword)) = parser.keyword()? {
+            match &*keyword.to_ascii_uppercase() {
+                "NAME" => description.names = parser.qdescrs()?,
+                "DESC" => description.description = Some(parser.qdstring()?),
+                "OBSOLETE" => description.obsolete = true,
+                "SUP" => description.superiors = parser.oids()?,
+                "ABSTRACT" => description.kind = ObjectClassKind::Abstract,
+                "STRUCTURAL" => description.kind = ObjectClassKind::Structural,
+                "AUXILIARY" => description.kind = ObjectClassKind::Auxiliary,
+                "MUST" => description.must = parser.oids()?,
+                "MAY" => description.may = parser.oids()?,
+                _ if is_extension(keyword) => description
+                    .extensions
+                    .push((keyword.into(), parser.qdstrings()?)),
+                _ => return Err(ParseSchemaError::InvalidKeyword { position }),
+            }
+        }
+
+        Ok(description)
+    }
+}
+
+impl fmt::Display for ObjectClassDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "( {}", self.oid)?;
+        write_names(f, &self.names)?;
+        if let Some(description) = &self.description {
+            f.write_str(" DESC ")?;
+            write_qdstring(f, description)?;
+        }
+        if self.obsolete {
+            f.write_str(" OBSOLETE")?;
+        }
+        write_oids(f, "SUP", &self.superiors)?;
+        write!(f, " {}", self.kind.as_str())?;
+        write_oids(f, "MUST", &self.must)?;
+        write_oids(f, "MAY", &self.may)?;
+        write_extensions(f, &self.extensions)?;
+        f.write_str(" )")
+    }
+}
+
+fn is_extension(keyword: &str) -> bool {
+    keyword.len() > 2
+        && keyword[..2].eq_ignore_ascii_case("X-")
+        && keyword[2..]
+            .bytes()
+            .all(|byte| byte.is_ascii_alphabetic() || matches!(byte, b'-' | b'_'))
+}
+
+fn write_qdstring(f: &mut fmt::Formatter<'_>, string: &str) -> fmt::Result {
+    f.write_char('\'')?;
+    for c in string.chars() {
+        match c {
+            '\'' => f.write_str("\\27")?,
+            '\\' => f.write_str("\\5C")?,
+            _ => f.write_char(c)?,
+        }
+    }
+    f.write_char('\'')
+}
+
+fn write_qdstrings(f: &mut fmt::Formatter<'_>, strings: &[String]) -> fmt::Result {
+    match strings {
+        [string] => write_qdstring(f, string),
+        _ => {
+            f.write_char('(')?;
+            for string in strings {
+                f.write_char(' ')?;
+                write_qdstring(f, string)?;
+            }
+            f.write_str(" )")
+        }
+    }
+}
+
+fn write_names(f: &mut fmt::Formatter<'_>, names: &[String]) -> fmt::Result {
+    if names.is_empty() {
+        return Ok(());
+    }
+    f.write_str(" NAME ")?;
+    write_qdstrings(f, names)
+}
+
+fn write_oids(f: &mut fmt::Formatter<'_>, keyword: &str, oids: &[String]) -> fmt::Result {
+    match oids {
+        [] => Ok(()),
+        [oid] => write!(f, " {keyword} {oid}"),
+        [first, rest @ ..] => {
+            write!(f, " {keyword} ( {first}")?;
+            for oid in rest {
+                write!(f, " $ {oid}")?;
+            }
+            f.write_str(" )")
+        }
+    }
+}
+
+fn write_extensions(
+    f: &mut fmt::Formatter<'_>,
+    extensions: &[(String, Vec<String>)],
+) -> fmt::Result {
+    for (keyword, values) in extensions {
+        write!(f, " {keyword} ")?;
+        write_qdstrings(f, values)?;
+    }
+
+    Ok(())
+}
+
+struct Parser<'input> {
+    input: &'input str,
+    position: usize,
+}
+
+impl<'input> Parser<'input> {
+    fn new(input: &'input str) -> Self {
+        Self { input, position: 0 }
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start_matches(' ').len();
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_spaces();
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_parenthesis(&mut self, parenthesis: u8) -> Result<(), ParseSchemaError> {
+        match self.peek() {
+            Some(_) if self.eat(parenthesis) => Ok(()),
+            Some(_) => Err(ParseSchemaError::MissingParenthesis {
+                position: self.position,
+            }),
+            None => Err(ParseSchemaError::UnexpectedEnd),
+        }
+    }
+
+    /// Reads a run of characters other than spaces, parentheses, quotes and
+    /// `$`, returning its position.
+    fn word(&mut self) -> Result<(usize, &'input str), ParseSchemaError> {
+        self.skip_spaces();
+        let start = self.position;
+        let rest = &self.input[start..];
+        let length = rest.find([' ', '(', ')', '\'', '$']).unwrap_or(rest.len());
+        if length == 0 {
+            return Err(match rest.is_empty() {
+                true => ParseSchemaError::UnexpectedEnd,
+                false => ParseSchemaError::InvalidValue { position: start },
+            });
+        }
+        self.position += length;
+        Ok((start, &rest[..length]))
+    }
+
+    /// Reads the opening parenthesis and the OID of a description.
+    fn start(&mut self) -> Result<String, ParseSchemaError> {
+        self.expect_parenthesis(b'(')?;
+        self.oid()
+    }
+
+    /// Reads the next field name, or `None` after the closing parenthesis
+    /// of the description.
+    fn keyword(&mut self) -> Result<Option<(usize, &'input str)>, ParseSchemaError> {
+        if self.eat(b')') {
+            self.skip_spaces();
+            return match self.position == self.input.len() {
+                true => Ok(None),
+                false => Err(ParseSchemaError::TrailingCharacters {
+                    position: self.position,
+                }),
+            };
+        }
+        let (position, keyword) = self.word()?;
+        Ok(Some((position, keyword)))
+    }
+
+    fn oid(&mut self) -> Result<String, ParseSchemaError> {
+        let (position, oid) = self.word()?;
+        match is_oid(oid.as_bytes()) {
+            true => Ok(oid.into()),
+            false => Err(ParseSchemaError::InvalidOid { position }),
+        }
+    }
+
+    /// Reads an OID, or a list of OIDs separated by `$` in parentheses.
+    fn oids(&mut self) -> Result<Vec<String>, ParseSchemaError> {
+        if !self.eat(b'(') {
+            return Ok(alloc::vec![self.oid()?]);
+        }
+        let mut oids = alloc::vec![self.oid()?];
+        while self.eat(b'$') {
+            oids.push(self.oid()?);
+        }
+        self.expect_parenthesis(b')')?;
+        Ok(oids)
+    }
+
+    /// Reads a syntax OID, with an optional `{length}`.
+    fn noidlen(&mut self) -> Result<(String, Option<u32>), ParseSchemaError> {
+        // Some servers quote the OID.
+        let (position, word) = match self.peek() {
+            Some(b'\'') => (self.position, self.qdstring()?),
+            _ => self
+                .word()
+                .map(|(position, word)| (position, word.into()))?,
+        };
+        let (oid, length) = match word.split_once('{') {
+            Some((oid, length)) => {
+                let length = length
+                    .strip_suffix('}')
+                    .and_then(|length| length.parse().ok())
+                    .ok_or(ParseSchemaError::InvalidValue {
+                        position: position + oid.len(),
+                    })?;
+                (oid, Some(length))
+            }
+            None => (&*word, None),
+        };
+        match is_oid(oid.as_bytes()) {
+            true => Ok((oid.into(), length)),
+            false => Err(ParseSchemaError::InvalidOid { position }),
+        }
+    }
+
+    fn qdstring(&mut self) -> Result<String, ParseSchemaError> {
+        self.skip_spaces();
+        let start = self.position;
+        let invalid = ParseSchemaError::InvalidQuotedString { position: start };
+        let rest = self.input[start..]
+            .strip_prefix('\'')
+            .ok_or(invalid.clone())?;
+        let end = rest.find('\'').ok_or(invalid.clone())?;
+        self.position += end + 2;
+
+        let mut string = String::new();
+        let mut parts = rest[..end].split('\\');
+        string.push_str(parts.next().unwrap_or_default());
+        for part in parts {
+            match part.get(..2) {
+                Some("27") => string.push('\''),
+                Some("5C" | "5c") => string.push('\\'),
+                _ => return Err(invalid),
+            }
+            string.push_str(&part[2..]);
+        }
+        Ok(string)
+    }
+
+    /// Reads a quoted string, or a list of them in parentheses.
+    fn qdstrings(&mut self) -> Result<Vec<String>, ParseSchemaError> {
+        if !self.eat(b'(') {
+            return Ok(alloc::vec![self.qdstring()?]);
+        }
+        let mut strings = Vec::new();
+        while self.peek() == Some(b'\'') {
+            strings.push(self.qdstring()?);
+        }
+        self.expect_parenthesis(b')')?;
+        Ok(strings)
+    }
+
+    /// Reads names, which are quoted descriptors.
+    fn qdescrs(&mut self) -> Result<Vec<String>, ParseSchemaError> {
+        let position = self.position;
+        let names = self.qdstrings()?;
+        match names.iter().all(|name| is_oid(name.as_bytes())) {
+            true => Ok(names),
+            false => Err(ParseSchemaError::InvalidOid { position }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    use pretty_assertions::assert_eq;
+
+    // From RFC 4519 and RFC 4512, as published by OpenLDAP.
+    const CN: &str = "( 2.5.4.3 NAME ( 'cn' 'commonName' ) DESC 'RFC4519: common name(s) for which the entity is known by' SUP name )";
+    const NAME: &str = "( 2.5.4.41 NAME 'name' EQUALITY caseIgnoreMatch SUBSTR caseIgnoreSubstringsMatch SYNTAX 1.3.6.1.4.1.1466.115.121.1.15{32768} )";
+    const C: &str = "( 2.5.4.6 NAME ( 'c' 'countryName' ) SUP name SYNTAX 1.3.6.1.4.1.1466.115.121.1.11 SINGLE-VALUE )";
+    const CREATE_TIMESTAMP: &str = "( 2.5.18.1 NAME 'createTimestamp' EQUALITY generalizedTimeMatch ORDERING generalizedTimeOrderingMatch SYNTAX 1.3.6.1.4.1.1466.115.121.1.24 SINGLE-VALUE NO-USER-MODIFICATION USAGE directoryOperation )";
+    const PERSON: &str = "( 2.5.6.6 NAME 'person' DESC 'RFC2256: a person' SUP top STRUCTURAL MUST ( sn $ cn ) MAY ( userPassword $ telephoneNumber $ seeAlso $ description ) )";
+
+    #[test]
+    fn attribute_types() {
+        let cn: AttributeTypeDescription = CN.parse().unwrap();
+        assert_eq!(
+            AttributeTypeDescription {
+                names: vec!["cn".into(), "commonName".into()],
+                description: Some(
+                    "RFC4519: common name(s) for which the entity is known by".into()
+                ),
+                superior: Some("name".into()),
+                ..AttributeTypeDescription::new("2.5.4.3".into())
+            },
+            cn
+        );
+        assert_eq!(CN, cn.to_string());
+
+        let name: AttributeTypeDescription = NAME.parse().unwrap();
+        assert_eq!(Some(syntax::DIRECTORY_STRING), name.syntax.as_deref());
+        assert_eq!(Some(32768), name.syntax_length);
+        assert_eq!(NAME, name.to_string());
+
+        let create_timestamp: AttributeTypeDescription = CREATE_TIMESTAMP.parse().unwrap();
+        assert!(create_timestamp.single_value && create_timestamp.no_user_modification);
+        assert_eq!(AttributeUsage::DirectoryOperation, create_timestamp.usage);
+        assert_eq!(CREATE_TIMESTAMP, create_timestamp.to_string());
+
+        // Servers differ in their spacing, case and quoting.
+        let loose: AttributeTypeDescription =
+            "(1.2.3 NAME 'a' syntax '1.3.6.1.4.1.1466.115.121.1.27' X-ORIGIN ( 'x' 'it\\27s' ))"
+                .parse()
+                .unwrap();
+        assert_eq!(Some(syntax::INTEGER), loose.syntax.as_deref());
+        assert_eq!(
+            vec![("X-ORIGIN".into(), vec!["x".into(), "it's".into()])],
+            loose.extensions
+        );
+        assert_eq!(
+            "( 1.2.3 NAME 'a' SYNTAX 1.3.6.1.4.1.1466.115.121.1.27 X-ORIGIN ( 'x' 'it\\27s' ) )",
+            loose.to_string()
+        );
+    }
+
+    #[test]
+    fn object_classes() {
+        let person: ObjectClassDescription = PERSON.parse().unwrap();
+        assert_eq!(
+            ObjectClassDescription {
+                names: vec!["person".into()],
+                description: Some("RFC2256: a person".into()),
+                superiors: vec!["top".into()],
+                must: vec!["sn".into(), "cn".into()],
+                may: vec![
+                    "userPassword".into(),
+                    "telephoneNumber".into(),
+                    "seeAlso".into(),
+                    "description".into()
+                ],
+                ..ObjectClassDescription::new("2.5.6.6".into())
+            },
+            person
+        );
+        assert_eq!(PERSON, person.to_string());
+
+        let top: ObjectClassDescription = "( 2.5.6.0 NAME 'top' ABSTRACT MUST objectClass )"
+            .parse()
+            .unwrap();
+        assert_eq!(ObjectClassKind::Abstract, top.kind);
+        assert_eq!(vec!["objectClass".to_string()], top.must);
+    }
+
+    #[test]
+    fn errors() {
+        let error =
+            |description: &str| description.parse::<AttributeTypeDescription>().unwrap_err();
+        assert_eq!(
+            ParseSchemaError::MissingParenthesis { position: 0 },
+            error("2.5.4.3")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidOid { position: 2 },
+            error("( 2..3 )")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidKeyword { position: 10 },
+            error("( 2.5.4.3 MUST a )")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidQuotedString { position: 15 },
+            error("( 2.5.4.3 NAME 'cn )")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidQuotedString { position: 15 },
+            error("( 2.5.4.3 DESC 'a\\b' )")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidValue { position: 16 },
+            error("( 2.5.4.3 USAGE everyone )")
+        );
+        assert_eq!(
+            ParseSchemaError::InvalidValue { position: 20 },
+            error("( 2.5.4.3 SYNTAX 1.2{x} )")
+        );
+        assert_eq!(
+            ParseSchemaError::TrailingCharacters { position: 12 },
+            error("( 2.5.4.3 ) x")
+        );
+        assert_eq!(ParseSchemaError::UnexpectedEnd, error("( 2.5.4.3 SUP"));
+        assert_eq!(
+            ParseSchemaError::MissingParenthesis { position: 20 },
+            "( 2.5.6.6 MUST ( sn cn ) )"
+                .parse::<ObjectClassDescription>()
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn check_attributes() {
+        let subschema = SearchResultEntry::new(
+            "cn=Subschema".into(),
+            vec![
+                crate::PartialAttribute::new(
+                    "attributeTypes".into(),
+                    [CN, NAME, C, CREATE_TIMESTAMP]
+                        .into_iter()
+                        .map(|value| value.into())
+                        .collect(),
+                ),
+                crate::PartialAttribute::new("objectClasses".into(), [PERSON.into()].into()),
+            ],
+        );
+        let schema = Schema::from_subschema(&subschema).unwrap();
+        assert_eq!(4, schema.attribute_types.len());
+        assert!(schema.object_class("PERSON").is_some());
+        assert_eq!(
+            Some(syntax::DIRECTORY_STRING),
+            schema.syntax("commonName;lang-en")
+        );
+        assert_eq!(Some(syntax::COUNTRY_STRING), schema.syntax("2.5.4.6"));
+
+        let values = |values: &[&'static str]| -> Vec<AttributeValue> {
+            values.iter().map(|value| (*value).into()).collect()
+        };
+        assert_eq!(
+            Ok(()),
+            schema.check_attribute(b"cn", &values(&["Émile", "E"]))
+        );
+        assert_eq!(
+            Err(SchemaViolation::InvalidSyntax("".into())),
+            schema.check_attribute(b"cn", &values(&[""]))
+        );
+        assert_eq!(Ok(()), schema.check_attribute(b"c", &values(&["GB"])));
+        assert_eq!(
+            Err(SchemaViolation::MultipleValues),
+            schema.check_attribute(b"c", &values(&["GB", "US"]))
+        );
+        assert_eq!(
+            Err(SchemaViolation::InvalidSyntax("GBR".into())),
+            schema.check_attribute(b"countryName", &values(&["GBR"]))
+        );
+        assert_eq!(
+            Ok(()),
+            schema.check_attribute(b"createTimestamp", &values(&["20240229123456.5Z"]))
+        );
+        assert_eq!(
+            Err(SchemaViolation::UndefinedAttributeType),
+            schema.check_attribute(b"mail", &values(&["a@example.com"]))
+        );
+
+        let invalid = SearchResultEntry::new(
+            "cn=Subschema".into(),
+            vec![crate::PartialAttribute::new(
+                "objectClasses".into(),
+                [b"( 2.5.6.6 NAME '\xff' )"[..].into()].into(),
+            )],
+        );
+        assert_eq!(
+            Err(ParseSchemaError::InvalidUtf8 { position: 16 }),
+            Schema::from_subschema(&invalid)
+        );
+    }
+
+    #[test]
+    fn syntaxes() {
+        let valid = [
+            (syntax::BIT_STRING, &["'0101'B", "''B"][..]),
+            (syntax::BOOLEAN, &["TRUE", "FALSE"]),
+            (syntax::INTEGER, &["0", "-12", "4294967296"]),
+            (syntax::NUMERIC_STRING, &["15 079 672 281"]),
+            (syntax::OID, &["2.5.4.3", "cn"]),
+            (syntax::PRINTABLE_STRING, &["Hello (world)"]),
+            (syntax::TELEPHONE_NUMBER, &["+1 512 315 0280"]),
+            (
+                syntax::GENERALIZED_TIME,
+                &[
+                    "199412161032Z",
+                    "1994121610+0200",
+                    "199412160532-0500",
+                    "20240229235960,25Z",
+                ],
+            ),
+            (syntax::OCTET_STRING, &["\u{0}"]),
+            ("1.2.3.4", &[""]),
+        ];
+        let invalid = [
+            (syntax::BIT_STRING, &["'012'B", "0101"][..]),
+            (syntax::BOOLEAN, &["true", "1"]),
+            (syntax::INTEGER, &["", "-0", "012", "1e3"]),
+            (syntax::NUMERIC_STRING, &["", "12a"]),
+            (syntax::OID, &["2.", "-cn"]),
+            (syntax::PRINTABLE_STRING, &["", "a@b"]),
+            (syntax::IA5_STRING, &["é"]),
+            (
+                syntax::GENERALIZED_TIME,
+                &[
+                    "1994121610",
+                    "19941316103200Z",
+                    "199412161032.Z",
+                    "1994121610+2400",
+                ],
+            ),
+        ];
+
+        for (syntax, values) in valid {
+            for value in values {
+                assert!(matches_syntax(syntax, value.as_bytes()), "{value}");
+            }
+        }
+        for (syntax, values) in invalid {
+            for value in values {
+                assert!(!matches_syntax(syntax, value.as_bytes()), "{value}");
+            }
+        }
+    }
+}
@@ -0,0 +1,1022 @@
+//! A minimal in-memory directory server, for testing clients without a real
+//! directory.
+//!
+//! [`Server`] answers Bind, Search, Add, Modify and Delete requests, and the
+//! Who am I? extended operation, for a single connection. It supports simple
+//! binds checked against `userPassword`, and the Simple Paged Results
+//! control on searches.
+//!
+//! It is a stand-in rather than a directory: it has no schema or access
+//! control, compares DNs ignoring ASCII case and spaces around RDNs, and
+//! matches filters by comparing values ignoring ASCII case, ignoring any
+//! extensible match's matching rule. Values are otherwise stored and
+//! modified exactly as given.
+
+use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::controls::{self, KnownControl, PagedResults};
+use crate::extended::{self, KnownExtendedRequest, KnownExtendedResponse};
+use crate::transport::FramingError;
+use crate::*;
+
+/// The attribute simple binds are checked against.
+const USER_PASSWORD: &[u8] = b"userPassword";
+
+/// A directory server holding its entries in memory.
+#[derive(Clone, Debug)]
+pub struct Server {
+    /// The largest message [`Server::handle_stream`] accepts.
+    pub max_length: usize,
+    /// The DN the client is bound as, which is empty for anonymous.
+    pub bound_dn: LdapDn,
+    /// Entries by normalised DN.
+    entries: BTreeMap<Vec<u8>, Entry>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    dn: LdapDn,
+    attributes: AttributeList,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server {
+    /// Creates a server with no entries.
+    pub fn new() -> Self {
+        Self {
+            max_length: 1 << 20,
+            bound_dn: LdapDn::new(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an entry, replacing any existing entry with the same DN, without
+    /// checking that its parent exists. Used to seed the directory.
+    pub fn insert(&mut self, dn: LdapDn, attributes: AttributeList) {
+        self.entries
+            .insert(normalise(&dn), Entry { dn, attributes });
+    }
+
+    /// Returns the attributes of the entry named `dn`.
+    pub fn get(&self, dn: &[u8]) -> Option<&AttributeList> {
+        self.entries
+            .get(&normalise(dn))
+            .map(|entry| &entry.attributes)
+    }
+
+    /// Answers a request, returning the messages to reply with, which are
+    /// empty for requests that have no response.
+    pub fn handle(&mut self, request: &LdapMessage) -> Vec<LdapMessage> {
+        let id = request.message_id;
+        let reply = |op| vec![LdapMessage::new(id, op)];
+
+        if let Some(control) = request
+            .controls
+            .iter()
+            .flatten()
+            .find(|control| control.criticality && !self.supports(request, control))
+        {
+            let result = result(
+                ResultCode::UnavailableCriticalExtension,
+                format!(
+                    "unsupported critical control {}",
+                    String::from_utf8_lossy(&control.control_type)
+                ),
+            );
+            return match error_response(&request.protocol_op, result) {
+                Some(op) => reply(op),
+                None => Vec::new(),
+            };
+        }
+
+        match &request.protocol_op {
+            ProtocolOp::BindRequest(bind) => {
+                let LdapResult {
+                    result_code,
+                    matched_dn,
+                    diagnostic_message,
+                    referral,
+                } = self.bind(bind);
+                reply(ProtocolOp::BindResponse(BindResponse::new(
+                    result_code,
+                    matched_dn,
+                    diagnostic_message,
+                    referral,
+                    None,
+                )))
+            }
+            ProtocolOp::SearchRequest(search) => self.search(id, search, request.controls.as_ref()),
+            ProtocolOp::AddRequest(add) => {
+                reply(ProtocolOp::AddResponse(AddResponse(self.add(add))))
+            }
+            ProtocolOp::ModifyRequest(modify) => reply(ProtocolOp::ModifyResponse(ModifyResponse(
+                self.modify(modify),
+            ))),
+            ProtocolOp::DelRequest(DelRequest(dn)) => {
+                reply(ProtocolOp::DelResponse(DelResponse(self.delete(dn))))
+            }
+            ProtocolOp::ExtendedReq(request) => reply(self.extended(request)),
+            ProtocolOp::UnbindRequest(_) => {
+                self.bound_dn = LdapDn::new();
+                Vec::new()
+            }
+            // Requests are answered as they arrive, so there is nothing left
+            // to abandon.
+            ProtocolOp::AbandonRequest(_) => Vec::new(),
+            op => match error_response(
+                op,
+                result(ResultCode::UnwillingToPerform, "unsupported operation"),
+            ) {
+                Some(op) => reply(op),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    /// Answers the first request in the data received on a connection so
+    /// far, returning the encoded replies and the number of bytes the request
+    /// took up, or `None` if more data is needed.
+    ///
+    /// If the data can't be read as a message, the reply is a notice of
+    /// disconnection and all of the data is consumed; the connection should
+    /// then be closed.
+    pub fn handle_stream(&mut self, input: &[u8]) -> Option<(Vec<u8>, usize)> {
+        let (replies, length) = match LdapMessage::from_stream(input, self.max_length) {
+            Ok(Some((request, length))) => (self.handle(&request), length),
+            Ok(None) => return None,
+            Err(error) => (vec![notice_of_disconnection(&error)], input.len()),
+        };
+
+        let replies = replies
+            .iter()
+            .flat_map(|reply| reply.to_bytes().expect("replies always encode"))
+            .collect();
+        Some((replies, length))
+    }
+
+    fn supports(&self, request: &LdapMessage, control: &Control) -> bool {
+        matches!(request.protocol_op, ProtocolOp::SearchRequest(_))
+            && *control.control_type == *controls::PAGED_RESULTS.as_bytes()
+    }
+
+    fn bind(&mut self, bind: &BindRequest) -> LdapResult {
+        if bind.version != 3 {
+            return result(ResultCode::ProtocolError, "only LDAPv3 is supported");
+        }
+        let AuthenticationChoice::Simple(password) = &bind.authentication else {
+            return result(
+                ResultCode::AuthMethodNotSupported,
+                "only simple binds are supported",
+            );
+        };
+
+        self.bound_dn = LdapDn::new();
+        if bind.name.is_empty() && password.is_empty() {
+            return result(ResultCode::Success, "");
+        }
+        if password.is_empty() {
+            return result(
+                ResultCode::UnwillingToPerform,
+                "unauthenticated binds are not allowed",
+            );
+        }
+
+        let valid = self
+            .get(&bind.name)
+            .and_then(|attributes| values(attributes, USER_PASSWORD))
+            .is_some_and(|passwords| passwords.contains(password));
+        if !valid {
+            return result(ResultCode::InvalidCredentials, "");
+        }
+
+        self.bound_dn = bind.name.clone();
+        result(ResultCode::Success, "")
+    }
+
+    fn search(
+        &self,
+        id: MessageId,
+        search: &SearchRequest,
+        controls: Option<&Controls>,
+    ) -> Vec<LdapMessage> {
+        let done = |result, controls| LdapMessage {
+            controls,
+            ..LdapMessage::new(id, ProtocolOp::SearchResDone(SearchResultDone(result)))
+        };
+
+        let base = normalise(&search.base_object);
+        if !base.is_empty() && !self.entries.contains_key(&base) {
+            return vec![done(self.no_such_object(&base), None)];
+        }
+
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|(dn, _)| in_scope(dn, &base, search.scope))
+            .map(|(_, entry)| entry)
+            .filter(|entry| matches(&search.filter, &entry.attributes))
+            .map(|entry| select(entry, search))
+            .collect::<Vec<_>>();
+
+        let paged =
+            controls
+                .into_iter()
+                .flatten()
+                .find_map(|control| match control.decode_known() {
+                    Ok(Some(KnownControl::PagedResults(paged))) => Some(paged),
+                    _ => None,
+                });
+        let mut result = result(ResultCode::Success, "");
+        let mut response_controls = None;
+        if let Some(paged) = paged {
+            let total = entries.len();
+            let offset = match <[u8; 4]>::try_from(&*paged.cookie) {
+                Ok(offset) => u32::from_be_bytes(offset) as usize,
+                Err(_) if paged.cookie.is_empty() => 0,
+                Err(_) => {
+                    let error = self::result(ResultCode::UnwillingToPerform, "invalid cookie");
+                    return vec![done(error, None)];
+                }
+            };
+            // A size of zero abandons the paged search.
+            let end = match paged.size {
+                0 => total,
+                size => total.min(offset.saturating_add(size as usize)),
+            };
+            entries = entries
+                .drain(..)
+                .skip(offset)
+                .take(end.saturating_sub(offset))
+                .collect();
+            let cookie = match end < total {
+                true => (end as u32).to_be_bytes().to_vec(),
+                false => Vec::new(),
+            };
+            let response =
+                KnownControl::PagedResults(PagedResults::new(total as u32, cookie.into()));
+            response_controls = Some(vec![
+                Control::from_known(&response, false).expect("controls always encode")
+            ]);
+        } else if search.size_limit != 0 && entries.len() > search.size_limit as usize {
+            entries.truncate(search.size_limit as usize);
+            result.result_code = ResultCode::SizeLimitExceeded;
+        }
+
+        entries
+            .into_iter()
+            .map(|entry| LdapMessage::new(id, ProtocolOp::SearchResEntry(entry)))
+            .chain([done(result, response_controls)])
+            .collect()
+    }
+
+    fn add(&mut self, add: &AddRequest) -> LdapResult {
+        let dn = normalise(&add.entry);
+        if self.entries.contains_key(&dn) {
+            return result(ResultCode::EntryAlreadyExists, "");
+        }
+        if let Some(parent) = parent(&dn) {
+            if !self.entries.contains_key(parent) {
+                return self.no_such_object(&dn);
+            }
+        }
+
+        self.insert(add.entry.clone(), add.attributes.clone());
+        result(ResultCode::Success, "")
+    }
+
+    fn modify(&mut self, modify: &ModifyRequest) -> LdapResult {
+        let dn = normalise(&modify.object);
+        let Some(entry) = self.entries.get(&dn) else {
+            return self.no_such_object(&dn);
+        };
+
+        // Changes are applied to a copy, so a failure leaves the entry as it
+        // was.
+        let mut attributes = entry.attributes.clone();
+        for change in &modify.changes {
+            let modification = &change.modification;
+            let position = attributes
+                .iter()
+                .position(|attribute| eq_ignore_case(&attribute.r#type, &modification.r#type));
+            let code = match (change.operation, position) {
+                (ChangeOperation::Add, Some(position)) => {
+                    let vals = &mut attributes[position].vals;
+                    match modification
+                        .vals
+                        .iter()
+                        .all(|value| vals.insert(value.clone()))
+                    {
+                        true => ResultCode::Success,
+                        false => ResultCode::AttributeOrValueExists,
+                    }
+                }
+                (ChangeOperation::Add, None) => {
+                    attributes.push(Attribute::new(
+                        modification.r#type.clone(),
+                        modification.vals.clone(),
+                    ));
+                    ResultCode::Success
+                }
+                (ChangeOperation::Delete, None) => ResultCode::NoSuchAttribute,
+                (ChangeOperation::Delete, Some(position)) if modification.vals.is_empty() => {
+                    attributes.remove(position);
+                    ResultCode::Success
+                }
+                (ChangeOperation::Delete, Some(position)) => {
+                    let vals = &mut attributes[position].vals;
+                    let removed = modification.vals.iter().all(|value| vals.remove(value));
+                    if vals.is_empty() {
+                        attributes.remove(position);
+                    }
+                    match removed {
+                        true => ResultCode::Success,
+                        false => ResultCode::NoSuchAttribute,
+                    }
+                }
+                (ChangeOperation::Replace, position) => {
+                    if let Some(position) = position {
+                        attributes.remove(position);
+                    }
+                    if !modification.vals.is_empty() {
+                        attributes.push(Attribute::new(
+                            modification.r#type.clone(),
+                            modification.vals.clone(),
+                        ));
+                    }
+                    ResultCode::Success
+                }
+            };
+            if code != ResultCode::Success {
+                return result(
+                    code,
+                    format!(
+                        "cannot modify {}",
+                        String::from_utf8_lossy(&modification.r#type)
+                    ),
+                );
+            }
+        }
+
+        if let Some(entry) = self.entries.get_mut(&dn) {
+            entry.attributes = attributes;
+        }
+        result(ResultCode::Success, "")
+    }
+
+    fn delete(&mut self, dn: &LdapDn) -> LdapResult {
+        let dn = normalise(dn);
+        if !self.entries.contains_key(&dn) {
+            return self.no_such_object(&dn);
+        }
+        if self.entries.keys().any(|other| parent(other) == Some(&dn)) {
+            return result(ResultCode::NotAllowedOnNonLeaf, "");
+        }
+
+        self.entries.remove(&dn);
+        result(ResultCode::Success, "")
+    }
+
+    fn extended(&self, request: &ExtendedRequest) -> ProtocolOp {
+        let (result, value) = match request.decode_known() {
+            Ok(Some(KnownExtendedRequest::WhoAmI)) => {
+                let authz_id = match self.bound_dn.is_empty() {
+                    true => LdapString::new(),
+                    false => [&b"dn:"[..], &self.bound_dn].concat().into(),
+                };
+                (
+                    result(ResultCode::Success, ""),
+                    KnownExtendedResponse::WhoAmI(authz_id),
+                )
+            }
+            Ok(Some(KnownExtendedRequest::StartTls)) => (
+                result(ResultCode::Unavailable, "TLS is not available"),
+                KnownExtendedResponse::StartTls,
+            ),
+            _ => {
+                let result = result(ResultCode::ProtocolError, "unsupported extended operation");
+                return error_response(&ProtocolOp::ExtendedReq(request.clone()), result)
+                    .expect("extended requests have a response");
+            }
+        };
+
+        ProtocolOp::ExtendedResp(
+            ExtendedResponse::from_known(result, &value).expect("responses always encode"),
+        )
+    }
+
+    /// The result for a missing entry, whose matched DN is the closest
+    /// ancestor of `dn` that exists.
+    fn no_such_object(&self, dn: &[u8]) -> LdapResult {
+        let mut matched = parent(dn);
+        while let Some(dn) = matched {
+            if let Some(entry) = self.entries.get(dn) {
+                return LdapResult::new(ResultCode::NoSuchObject, entry.dn.clone(), <_>::default());
+            }
+            matched = parent(dn);
+        }
+
+        result(ResultCode::NoSuchObject, "")
+    }
+}
+
+fn result(code: ResultCode, message: impl Into<String>) -> LdapResult {
+    LdapResult::new(code, LdapDn::new(), message.into().into())
+}
+
+/// The response to send with `result` for a request that fails before it
+/// is handled, or `None` if the request has no response.
+fn error_response(request: &ProtocolOp, result: LdapResult) -> Option<ProtocolOp> {
+    Some(match request {
+        ProtocolOp::BindRequest(_) => ProtocolOp::BindResponse(BindResponse::new(
+            result.result_code,
+            result.matched_dn,
+            result.diagnostic_message,
+            None,
+            None,
+        )),
+        ProtocolOp::SearchRequest(_) => ProtocolOp::SearchResDone(SearchResultDone(result)),
+        ProtocolOp::ModifyRequest(_) => ProtocolOp::ModifyResponse(ModifyResponse(result)),
+        ProtocolOp::AddRequest(_) => ProtocolOp::AddResponse(AddResponse(result)),
+        ProtocolOp::DelRequest(_) => ProtocolOp::DelResponse(DelResponse(result)),
+        ProtocolOp::ModDnRequest(_) => ProtocolOp::ModDnResponse(ModifyDnResponse(result)),
+        ProtocolOp::CompareRequest(_) => ProtocolOp::CompareResponse(CompareResponse(result)),
+        ProtocolOp::ExtendedReq(_) => ProtocolOp::ExtendedResp(ExtendedResponse {
+            result_code: result.result_code,
+            matched_dn: result.matched_dn,
+            diagnostic_message: result.diagnostic_message,
+            referral: None,
+            response_name: None,
+            response_value: None,
+        }),
+        _ => return None,
+    })
+}
+
+fn notice_of_disconnection(error: &FramingError) -> LdapMessage {
+    LdapMessage::new(
+        0,
+        ProtocolOp::ExtendedResp(ExtendedResponse {
+            result_code: ResultCode::ProtocolError,
+            matched_dn: LdapDn::new(),
+            diagnostic_message: format!("{error}").into(),
+            referral: None,
+            response_name: Some(extended::NOTICE_OF_DISCONNECTION.into()),
+            response_value: None,
+        }),
+    )
+}
+
+/// Splits a DN into its RDNs at commas that aren't escaped.
+fn rdns(dn: &[u8]) -> Vec<&[u8]> {
+    let mut rdns = Vec::new();
+    let (mut start, mut escaped) = (0, false);
+    for (i, byte) in dn.iter().enumerate() {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b',' => {
+                rdns.push(&dn[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !dn.is_empty() {
+        rdns.push(&dn[start..]);
+    }
+    rdns
+}
+
+/// Lowercases a DN and removes spaces around its RDNs, so equal DNs compare
+/// equal.
+fn normalise(dn: &[u8]) -> Vec<u8> {
+    rdns(dn)
+        .into_iter()
+        .map(|rdn| rdn.trim_ascii().to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join(&b","[..])
+}
+
+/// The parent of a normalised DN, or `None` for an entry at the root.
+fn parent(dn: &[u8]) -> Option<&[u8]> {
+    let first = rdns(dn).first()?.len();
+    dn.get(first + 1..)
+}
+
+fn in_scope(dn: &[u8], base: &[u8], scope: SearchRequestScope) -> bool {
+    match scope {
+        SearchRequestScope::BaseObject => dn == base,
+        SearchRequestScope::SingleLevel => parent(dn).unwrap_or_default() == base,
+        _ => {
+            base.is_empty()
+                || dn == base
+                || dn
+                    .strip_suffix(base)
+                    .is_some_and(|rest| rest.ends_with(b","))
+        }
+    }
+}
+
+fn eq_ignore_case(a: &[u8], b: &[u8]) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+fn cmp_ignore_case(a: &[u8], b: &[u8]) -> Ordering {
+    a.iter()
+        .map(u8::to_ascii_lowercase)
+        .cmp(b.iter().map(u8::to_ascii_lowercase))
+}
+
+/// The values of the attribute `r#type`, if the entry has it.
+fn values<'a>(attributes: &'a AttributeList, r#type: &[u8]) -> Option<&'a SetOf<AttributeValue>> {
+    attributes
+        .iter()
+        .find(|attribute| eq_ignore_case(&attribute.r#type, r#type))
+        .map(|attribute| &attribute.vals)
+}
+
+fn any_value(attributes: &AttributeList, r#type: &[u8], predicate: impl Fn(&[u8]) -> bool) -> bool {
+    values(attributes, r#type).is_some_and(|values| values.iter().any(|value| predicate(value)))
+}
+
+fn matches(filter: &Filter, attributes: &AttributeList) -> bool {
+    match filter {
+        Filter::And(filters) => filters.iter().all(|filter| matches(filter, attributes)),
+        Filter::Or(filters) => filters.iter().any(|filter| matches(filter, attributes)),
+        Filter::Not(filter) => !matches(filter, attributes),
+        Filter::EqualityMatch(ava) | Filter::ApproxMatch(ava) => {
+            any_value(attributes, &ava.attribute_desc, |value| {
+                eq_ignore_case(value, &ava.assertion_value)
+            })
+        }
+        Filter::GreaterOrEqual(ava) => any_value(attributes, &ava.attribute_desc, |value| {
+            cmp_ignore_case(value, &ava.assertion_value).is_ge()
+        }),
+        Filter::LessOrEqual(ava) => any_value(attributes, &ava.attribute_desc, |value| {
+            cmp_ignore_case(value, &ava.assertion_value).is_le()
+        }),
+        Filter::Present(r#type) => values(attributes, r#type).is_some(),
+        Filter::Substrings(substrings) => any_value(attributes, &substrings.r#type, |value| {
+            matches_substrings(&value.to_ascii_lowercase(), &substrings.substrings)
+        }),
+        Filter::ExtensibleMatch(assertion) => {
+            let equal = |value: &[u8]| eq_ignore_case(value, &assertion.match_value);
+            match &assertion.r#type {
+                Some(r#type) => any_value(attributes, r#type, equal),
+                None => attributes
+                    .iter()
+                    .any(|attribute| attribute.vals.iter().any(|value| equal(value))),
+            }
+        }
+    }
+}
+
+fn matches_substrings(mut value: &[u8], substrings: &[SubstringChoice]) -> bool {
+    for substring in substrings {
+        match substring {
+            SubstringChoice::Initial(initial) => {
+                match value.strip_prefix(&*initial.to_ascii_lowercase()) {
+                    Some(rest) => value = rest,
+                    None => return false,
+                }
+            }
+            SubstringChoice::Any(any) => {
+                let any = any.to_ascii_lowercase();
+                match value.windows(any.len()).position(|window| window == any) {
+                    Some(position) => value = &value[position + any.len()..],
+                    None => return false,
+                }
+            }
+            SubstringChoice::Final(last) => {
+                if !value.ends_with(&last.to_ascii_lowercase()) {
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// The entry as returned by `search`, with only the requested attributes.
+fn select(entry: &Entry, search: &SearchRequest) -> SearchResultEntry {
+    let requested = &search.attributes;
+    let all = requested.is_empty() || requested.iter().any(|r#type| **r#type == *b"*");
+    let attributes = entry
+        .attributes
+        .iter()
+        .filter(|attribute| {
+            all || requested
+                .iter()
+                .any(|r#type| eq_ignore_case(r#type, &attribute.r#type))
+        })
+        .map(|attribute| {
+            let vals = match search.types_only {
+                true => SetOf::new(),
+                false => attribute.vals.clone(),
+            };
+            PartialAttribute::new(attribute.r#type.clone(), vals)
+        })
+        .collect();
+
+    SearchResultEntry::new(entry.dn.clone(), attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeSet;
+    use pretty_assertions::assert_eq;
+
+    fn attributes(attributes: &[(&'static str, &[&'static str])]) -> AttributeList {
+        attributes
+            .iter()
+            .map(|(r#type, vals)| {
+                Attribute::new(
+                    (*r#type).into(),
+                    vals.iter().map(|val| (*val).into()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn server() -> Server {
+        let mut server = Server::new();
+        server.insert(
+            "dc=example,dc=org".into(),
+            attributes(&[("objectClass", &["domain"]), ("dc", &["example"])]),
+        );
+        server.insert(
+            "ou=people,dc=example,dc=org".into(),
+            attributes(&[
+                ("objectClass", &["organizationalUnit"]),
+                ("ou", &["people"]),
+            ]),
+        );
+        for (uid, cn) in [
+            ("alice", "Alice Smith"),
+            ("bob", "Bob Jones"),
+            ("carol", "Carol Smith"),
+        ] {
+            server.insert(
+                format!("uid={uid},ou=people,dc=example,dc=org").into(),
+                attributes(&[
+                    ("objectClass", &["person"]),
+                    ("uid", &[uid]),
+                    ("cn", &[cn]),
+                    ("userPassword", &["secret"]),
+                ]),
+            );
+        }
+        server
+    }
+
+    fn code(messages: &[LdapMessage]) -> ResultCode {
+        match &messages.last().unwrap().protocol_op {
+            ProtocolOp::BindResponse(response) => response.result_code,
+            ProtocolOp::SearchResDone(SearchResultDone(result))
+            | ProtocolOp::AddResponse(AddResponse(result))
+            | ProtocolOp::ModifyResponse(ModifyResponse(result))
+            | ProtocolOp::DelResponse(DelResponse(result))
+            | ProtocolOp::CompareResponse(CompareResponse(result)) => result.result_code,
+            ProtocolOp::ExtendedResp(response) => response.result_code,
+            op => panic!("unexpected response {op:?}"),
+        }
+    }
+
+    fn request(op: ProtocolOp) -> LdapMessage {
+        LdapMessage::new(1, op)
+    }
+
+    fn bind(name: &'static str, password: &'static str) -> LdapMessage {
+        request(ProtocolOp::BindRequest(BindRequest::new(
+            3,
+            name.into(),
+            AuthenticationChoice::Simple(password.into()),
+        )))
+    }
+
+    fn search(base: &'static str, scope: SearchRequestScope, filter: &str) -> SearchRequest {
+        SearchRequest::new(
+            base.into(),
+            scope,
+            SearchRequestDerefAliases::NeverDerefAliases,
+            0,
+            0,
+            false,
+            filter.parse().unwrap(),
+            Vec::new(),
+        )
+    }
+
+    fn names(messages: &[LdapMessage]) -> Vec<&[u8]> {
+        messages
+            .iter()
+            .filter_map(|message| match &message.protocol_op {
+                ProtocolOp::SearchResEntry(entry) => Some(&*entry.object_name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn who_am_i(server: &mut Server) -> LdapString {
+        let request = ExtendedRequest::from_known(&KnownExtendedRequest::WhoAmI).unwrap();
+        let responses = server.handle(&self::request(ProtocolOp::ExtendedReq(request)));
+        let ProtocolOp::ExtendedResp(response) = &responses[0].protocol_op else {
+            panic!("unexpected response {responses:?}");
+        };
+        match response.decode_known(extended::WHO_AM_I.as_bytes()) {
+            Ok(Some(KnownExtendedResponse::WhoAmI(authz_id))) => authz_id,
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+
+    #[test]
+    fn binds() {
+        let mut server = server();
+        let alice = "uid=alice,ou=people,dc=example,dc=org";
+        assert_eq!(ResultCode::Success, code(&server.handle(&bind("", ""))));
+        assert_eq!(&b""[..], &*who_am_i(&mut server));
+
+        assert_eq!(
+            ResultCode::InvalidCredentials,
+            code(&server.handle(&bind(alice, "wrong")))
+        );
+        assert_eq!(
+            ResultCode::InvalidCredentials,
+            code(&server.handle(&bind("uid=nobody,dc=example,dc=org", "secret")))
+        );
+        assert_eq!(
+            ResultCode::UnwillingToPerform,
+            code(&server.handle(&bind(alice, "")))
+        );
+
+        // DNs are compared ignoring case and spaces between RDNs.
+        let responses = server.handle(&bind("UID=Alice, ou=People,dc=example,dc=org", "secret"));
+        assert_eq!(1, responses[0].message_id);
+        assert_eq!(ResultCode::Success, code(&responses));
+        assert_eq!(
+            &b"dn:UID=Alice, ou=People,dc=example,dc=org"[..],
+            &*who_am_i(&mut server)
+        );
+
+        let sasl = request(ProtocolOp::BindRequest(BindRequest::new(
+            3,
+            "".into(),
+            AuthenticationChoice::Sasl(SaslCredentials::new("EXTERNAL".into(), None)),
+        )));
+        assert_eq!(
+            ResultCode::AuthMethodNotSupported,
+            code(&server.handle(&sasl))
+        );
+
+        assert!(server
+            .handle(&request(ProtocolOp::UnbindRequest(UnbindRequest)))
+            .is_empty());
+        assert!(server.bound_dn.is_empty());
+    }
+
+    #[test]
+    fn searches() {
+        let mut server = server();
+        let people = "ou=people,dc=example,dc=org";
+        let mut run = |search| server.handle(&request(ProtocolOp::SearchRequest(search)));
+
+        let responses = run(self::search(
+            "dc=example,dc=org",
+            SearchRequestScope::WholeSubtree,
+            "(&(objectClass=person)(cn=*smith))",
+        ));
+        assert_eq!(ResultCode::Success, code(&responses));
+        assert_eq!(
+            vec![
+                &b"uid=alice,ou=people,dc=example,dc=org"[..],
+                b"uid=carol,ou=people,dc=example,dc=org",
+            ],
+            names(&responses)
+        );
+
+        let responses = run(self::search(
+            people,
+            SearchRequestScope::BaseObject,
+            "(objectClass=*)",
+        ));
+        assert_eq!(vec![people.as_bytes()], names(&responses));
+
+        let responses = run(self::search(
+            "dc=example,dc=org",
+            SearchRequestScope::SingleLevel,
+            "(objectClass=*)",
+        ));
+        assert_eq!(vec![people.as_bytes()], names(&responses));
+
+        let responses = run(self::search(
+            people,
+            SearchRequestScope::SingleLevel,
+            "(|(uid=BOB)(!(cn>=b)))",
+        ));
+        assert_eq!(2, names(&responses).len());
+
+        let responses = run(self::search(
+            "uid=dave,ou=people,dc=example,dc=org",
+            SearchRequestScope::BaseObject,
+            "(objectClass=*)",
+        ));
+        let ProtocolOp::SearchResDone(SearchResultDone(result)) = &responses[0].protocol_op else {
+            panic!("unexpected response {responses:?}");
+        };
+        assert_eq!(ResultCode::NoSuchObject, result.result_code);
+        assert_eq!(people.as_bytes(), &*result.matched_dn);
+
+        // Only the requested attributes are returned.
+        let mut selected = self::search(people, SearchRequestScope::SingleLevel, "(uid=alice)");
+        selected.attributes = vec!["CN".into(), "mail".into()];
+        selected.types_only = true;
+        let responses = run(selected);
+        let ProtocolOp::SearchResEntry(entry) = &responses[0].protocol_op else {
+            panic!("unexpected response {responses:?}");
+        };
+        assert_eq!(
+            vec![PartialAttribute::new("cn".into(), BTreeSet::new())],
+            entry.attributes
+        );
+
+        let mut limited = self::search(people, SearchRequestScope::SingleLevel, "(uid=*)");
+        limited.size_limit = 2;
+        let responses = run(limited);
+        assert_eq!(2, names(&responses).len());
+        assert_eq!(ResultCode::SizeLimitExceeded, code(&responses));
+    }
+
+    #[test]
+    fn paged_search() {
+        let mut server = server();
+        let mut cookie = OctetString::new();
+        let mut pages = Vec::new();
+        loop {
+            let paged = KnownControl::PagedResults(PagedResults::new(2, cookie.clone()));
+            let mut message = request(ProtocolOp::SearchRequest(search(
+                "dc=example,dc=org",
+                SearchRequestScope::WholeSubtree,
+                "(objectClass=*)",
+            )));
+            message.controls = Some(vec![Control::from_known(&paged, true).unwrap()]);
+
+            let responses = server.handle(&message);
+            pages.push(names(&responses).len());
+            let controls = responses.last().unwrap().controls.as_ref().unwrap();
+            let Ok(Some(KnownControl::PagedResults(paged))) = controls[0].decode_known() else {
+                panic!("unexpected controls {controls:?}");
+            };
+            assert_eq!(5, paged.size);
+            if paged.cookie.is_empty() {
+                break;
+            }
+            cookie = paged.cookie;
+        }
+        assert_eq!(vec![2, 2, 1], pages);
+
+        // Other critical controls aren't supported.
+        let mut message = request(ProtocolOp::DelRequest(DelRequest(
+            "dc=example,dc=org".into(),
+        )));
+        message.controls = Some(vec![Control::new("1.2.3".into(), true, None)]);
+        assert_eq!(
+            ResultCode::UnavailableCriticalExtension,
+            code(&server.handle(&message))
+        );
+    }
+
+    #[test]
+    fn updates() {
+        let mut server = server();
+        let dave = "uid=dave,ou=people,dc=example,dc=org";
+        let add = |dn: &'static str| {
+            request(ProtocolOp::AddRequest(AddRequest {
+                entry: dn.into(),
+                attributes: attributes(&[("objectClass", &["person"]), ("cn", &["Dave"])]),
+            }))
+        };
+        assert_eq!(ResultCode::Success, code(&server.handle(&add(dave))));
+        assert_eq!(
+            ResultCode::EntryAlreadyExists,
+            code(&server.handle(&add(dave)))
+        );
+        assert_eq!(
+            ResultCode::NoSuchObject,
+            code(&server.handle(&add("uid=eve,ou=staff,dc=example,dc=org")))
+        );
+
+        let modify = |changes: &[(ChangeOperation, &'static str, &[&'static str])]| {
+            request(ProtocolOp::ModifyRequest(ModifyRequest {
+                object: dave.into(),
+                changes: changes
+                    .iter()
+                    .map(|(operation, r#type, vals)| ModifyRequestChanges {
+                        operation: *operation,
+                        modification: PartialAttribute::new(
+                            (*r#type).into(),
+                            vals.iter().map(|val| (*val).into()).collect(),
+                        ),
+                    })
+                    .collect(),
+            }))
+        };
+        assert_eq!(
+            ResultCode::Success,
+            code(&server.handle(&modify(&[
+                (ChangeOperation::Add, "mail", &["dave@example.org"]),
+                (ChangeOperation::Replace, "cn", &["David"]),
+                (ChangeOperation::Delete, "objectClass", &[]),
+            ])))
+        );
+        assert_eq!(
+            &attributes(&[("mail", &["dave@example.org"]), ("cn", &["David"])]),
+            server.get(dave.as_bytes()).unwrap()
+        );
+
+        // A failed change leaves the entry as it was.
+        assert_eq!(
+            ResultCode::NoSuchAttribute,
+            code(&server.handle(&modify(&[
+                (ChangeOperation::Replace, "cn", &[]),
+                (ChangeOperation::Delete, "mail", &["dave@example.com"]),
+            ])))
+        );
+        assert_eq!(
+            ResultCode::AttributeOrValueExists,
+            code(&server.handle(&modify(&[(ChangeOperation::Add, "CN", &["David"])])))
+        );
+        assert_eq!(2, server.get(dave.as_bytes()).unwrap().len());
+
+        let delete = |dn: &'static str| request(ProtocolOp::DelRequest(DelRequest(dn.into())));
+        assert_eq!(
+            ResultCode::NotAllowedOnNonLeaf,
+            code(&server.handle(&delete("ou=people,dc=example,dc=org")))
+        );
+        assert_eq!(ResultCode::Success, code(&server.handle(&delete(dave))));
+        assert_eq!(
+            ResultCode::NoSuchObject,
+            code(&server.handle(&delete(dave)))
+        );
+        assert!(server.get(dave.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn stream() {
+        let mut server = server();
+        let mut input = bind("uid=bob,ou=people,dc=example,dc=org", "secret")
+            .to_bytes()
+            .unwrap();
+        let mut search = request(ProtocolOp::SearchRequest(self::search(
+            "dc=example,dc=org",
+            SearchRequestScope::WholeSubtree,
+            "(uid=bob)",
+        )));
+        search.message_id = 2;
+        input.extend(search.to_bytes().unwrap());
+
+        assert_eq!(None, server.handle_stream(&input[..10]));
+        let (reply, length) = server.handle_stream(&input).unwrap();
+        let (response, _) = LdapMessage::from_stream(&reply, usize::MAX)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ResultCode::Success, code(&[response]));
+
+        let (mut reply, rest) = server.handle_stream(&input[length..]).unwrap();
+        assert_eq!(input.len() - length, rest);
+        let mut pending = transport::PendingRequests::new();
+        pending.insert("bind");
+        assert_eq!(2, pending.insert("search"));
+        let mut responses = Vec::new();
+        while let Some((response, length)) = LdapMessage::from_stream(&reply, usize::MAX).unwrap() {
+            pending.match_response(&response).unwrap();
+            responses.push(response);
+            reply.drain(..length);
+        }
+        assert_eq!(2, responses.len());
+        assert_eq!(1, pending.len());
+
+        // Data that isn't a message gets a notice of disconnection.
+        let (reply, length) = server.handle_stream(b"\x04\x00\x00").unwrap();
+        assert_eq!(3, length);
+        let (notice, _) = LdapMessage::from_stream(&reply, usize::MAX)
+            .unwrap()
+            .unwrap();
+        assert_eq!(0, notice.message_id);
+        let ProtocolOp::ExtendedResp(notice) = notice.protocol_op else {
+            panic!("unexpected notice {notice:?}");
+        };
+        assert_eq!(ResultCode::ProtocolError, notice.result_code);
+        assert_eq!(
+            Some(extended::NOTICE_OF_DISCONNECTION.into()),
+            notice.response_name
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Framing of [`LdapMessage`]s sent over a byte stream, from [RFC 4511
+//! Section 5].
+//!
+//! Each message is a BER encoded SEQUENCE sent straight after the previous
+//! one, with a definite length, so a reader finds the end of a message from
+//! the length in its header. [`PendingRequests`] matches the messages a
+//! client receives to the requests it sent, by [`MessageId`].
+//!
+//! [RFC 4511 Section 5]: https://datatracker.ietf.org/doc/html/rfc4511#section-5
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use rasn::error::{DecodeError, EncodeError};
+
+use crate::{LdapMessage, MessageId, ProtocolOp};
+
+/// The largest message ID; message IDs are `INTEGER (0 .. maxInt)`.
+pub const MAX_MESSAGE_ID: MessageId = i32::MAX as MessageId;
+
+/// The identifier octet of a universal, constructed SEQUENCE.
+const SEQUENCE: u8 = 0x30;
+
+/// An error returned when reading a framed message.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FramingError {
+    /// The data didn't start with a SEQUENCE header with a definite length,
+    /// which RFC 4511 requires.
+    InvalidHeader,
+    /// The message is longer than the reader's maximum length.
+    TooLong(usize),
+    /// The message couldn't be decoded.
+    Decode(DecodeError),
+    /// A response's message ID didn't match any pending request.
+    UnknownMessageId(MessageId),
+}
+
+impl core::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid message header"),
+            Self::TooLong(length) => write!(f, "message of {length} bytes is too long"),
+            Self::Decode(error) => write!(f, "failed to decode message: {error}"),
+            Self::UnknownMessageId(id) => write!(f, "response to unknown message ID {id}"),
+        }
+    }
+}
+
+impl From<DecodeError> for FramingError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl LdapMessage {
+    /// Encodes the message for sending on a stream.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        rasn::ber::encode(self)
+    }
+
+    /// Reads the first message from the data received on a stream so far,
+    /// returning it with the number of bytes it took up, or `None` if more
+    /// data is needed.
+    ///
+    /// Messages longer than `max_length`, including their header, are
+    /// rejected as soon as their header has been read, so a peer can't make
+    /// the reader buffer more than that.
+    pub fn from_stream(
+        input: &[u8],
+        max_length: usize,
+    ) -> Result<Option<(Self, usize)>, FramingError> {
+        let Some((header, length)) = message_length(input)? else {
+            return Ok(None);
+        };
+        let total = header
+            .checked_add(length)
+            .ok_or(FramingError::TooLong(usize::MAX))?;
+        if total > max_length {
+            return Err(FramingError::TooLong(total));
+        }
+
+        match input.get(..total) {
+            Some(message) => Ok(Some((rasn::ber::decode(message)?, total))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Reads the header of the message at the start of `input`, returning the
+/// length of the header and the length of the contents, or `None` if the
+/// header is incomplete.
+fn message_length(input: &[u8]) -> Result<Option<(usize, usize)>, FramingError> {
+    match input.first() {
+        Some(&SEQUENCE) => {}
+        Some(_) => return Err(FramingError::InvalidHeader),
+        None => return Ok(None),
+    }
+    let Some(&first) = input.get(1) else {
+        return Ok(None);
+    };
+    if first & 0x80 == 0 {
+        return Ok(Some((2, usize::from(first))));
+    }
+
+    // The long form, where the low bits give the number of length octets.
+    // Zero would be the indefinite length.
+    let count = usize::from(first & 0x7f);
+    if count == 0 {
+        return Err(FramingError::InvalidHeader);
+    }
+    let Some(octets) = input.get(2..2 + count) else {
+        return Ok(None);
+    };
+    let length = octets.iter().try_fold(0usize, |length, octet| {
+        length
+            .checked_mul(256)
+            .map(|length| length | usize::from(*octet))
+            .ok_or(FramingError::TooLong(usize::MAX))
+    })?;
+
+    Ok(Some((2 + count, length)))
+}
+
+/// The requests a client is waiting on responses to, each with a value of
+/// the client's choosing, such as the channel to deliver the response on.
+#[derive(Clone, Debug)]
+pub struct PendingRequests<T> {
+    next_id: MessageId,
+    pending: BTreeMap<MessageId, T>,
+}
+
+/// A response matched to its request by [`PendingRequests::match_response`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Matched<'a, T> {
+    /// One of several responses to a request, such as a search result entry,
+    /// after which the request is still pending.
+    Partial(&'a T),
+    /// The last response to a request, which is no longer pending.
+    Final(T),
+    /// An unsolicited notification, which has a message ID of zero.
+    Unsolicited,
+}
+
+impl<T> Default for PendingRequests<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PendingRequests<T> {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Records a request, returning the message ID to send it with. IDs
+    /// start from one and wrap around after [`MAX_MESSAGE_ID`], skipping any
+    /// still in use.
+    ///
+    /// Requests that have no response, such as Abandon and Unbind, should
+    /// use [`PendingRequests::next_id`] instead.
+    pub fn insert(&mut self, request: T) -> MessageId {
+        let id = self.next_id();
+        self.pending.insert(id, request);
+        id
+    }
+
+    /// Returns a message ID that isn't in use, without recording a request.
+    pub fn next_id(&mut self) -> MessageId {
+        loop {
+            let id = self.next_id;
+            self.next_id = if id >= MAX_MESSAGE_ID { 1 } else { id + 1 };
+            if !self.pending.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    /// Returns the request sent with the message ID `id`.
+    pub fn get(&self, id: MessageId) -> Option<&T> {
+        self.pending.get(&id)
+    }
+
+    /// Stops waiting for responses to the request sent with the message ID
+    /// `id`, such as after abandoning it, and returns it.
+    pub fn remove(&mut self, id: MessageId) -> Option<T> {
+        self.pending.remove(&id)
+    }
+
+    /// The number of requests waiting on responses.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Matches a received message to its request, removing the request once
+    /// its last response has arrived.
+    pub fn match_response(
+        &mut self,
+        message: &LdapMessage,
+    ) -> Result<Matched<'_, T>, FramingError> {
+        let id = message.message_id;
+        if id == 0 {
+            return Ok(Matched::Unsolicited);
+        }
+
+        let partial = matches!(
+            message.protocol_op,
+            ProtocolOp::SearchResEntry(_)
+                | ProtocolOp::SearchResRef(_)
+                | ProtocolOp::IntermediateResponse(_)
+        );
+        if partial {
+            self.pending.get(&id).map(Matched::Partial)
+        } else {
+            self.pending.remove(&id).map(Matched::Final)
+        }
+        .ok_or(FramingError::UnknownMessageId(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+    use pretty_assertions::assert_eq;
+
+    fn bind() -> LdapMessage {
+        LdapMessage::new(
+            1,
+            ProtocolOp::BindRequest(BindRequest::new(
+                3,
+                "cn=admin".into(),
+                AuthenticationChoice::Simple("secret".into()),
+            )),
+        )
+    }
+
+    fn done(id: MessageId) -> LdapMessage {
+        LdapMessage::new(
+            id,
+            ProtocolOp::SearchResDone(SearchResultDone(LdapResult::new(
+                ResultCode::Success,
+                <_>::default(),
+                <_>::default(),
+            ))),
+        )
+    }
+
+    #[test]
+    fn stream() {
+        let message = bind();
+        let encoded = message.to_bytes().unwrap();
+        let mut input = encoded.clone();
+        input.extend_from_slice(&encoded);
+
+        for end in 0..encoded.len() {
+            assert!(LdapMessage::from_stream(&input[..end], 1024)
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(
+            Some((message, encoded.len())),
+            LdapMessage::from_stream(&input, 1024).unwrap()
+        );
+
+        assert!(matches!(
+            LdapMessage::from_stream(&input, encoded.len() - 1),
+            Err(FramingError::TooLong(length)) if length == encoded.len()
+        ));
+    }
+
+    #[test]
+    fn long_form_length() {
+        let message = LdapMessage::new(
+            7,
+            ProtocolOp::DelRequest(DelRequest(vec![b'a'; 300].into())),
+        );
+        let encoded = message.to_bytes().unwrap();
+        // Two length octets.
+        assert_eq!(&[0x30, 0x82], &encoded[..2]);
+        for end in [3, 4, 5] {
+            assert!(LdapMessage::from_stream(&encoded[..end], 1024)
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(
+            Some((message, encoded.len())),
+            LdapMessage::from_stream(&encoded, 1024).unwrap()
+        );
+
+        // The length is rejected before the contents arrive.
+        assert!(matches!(
+            LdapMessage::from_stream(&[0x30, 0x84, 0x7f, 0xff, 0xff, 0xff], 1024),
+            Err(FramingError::TooLong(0x8000_0005))
+        ));
+        assert!(matches!(
+            LdapMessage::from_stream(
+                &[0x30, 0x89, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+                1024
+            ),
+            Err(FramingError::TooLong(_))
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(LdapMessage::from_stream(&[], 1024).unwrap().is_none());
+        assert!(matches!(
+            LdapMessage::from_stream(&[0x31, 0x00], 1024),
+            Err(FramingError::InvalidHeader)
+        ));
+        // Indefinite length.
+        assert!(matches!(
+            LdapMessage::from_stream(&[0x30, 0x80, 0x02, 0x01, 0x01, 0x00, 0x00], 1024),
+            Err(FramingError::InvalidHeader)
+        ));
+        assert!(matches!(
+            LdapMessage::from_stream(&[0x30, 0x03, 0x02, 0x01, 0x01], 1024),
+            Err(FramingError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn pending_requests() {
+        let mut pending = PendingRequests::new();
+        assert_eq!(1, pending.insert("bind"));
+        assert_eq!(2, pending.insert("search"));
+        assert_eq!(3, pending.next_id());
+        assert_eq!(2, pending.len());
+
+        let entry = LdapMessage::new(
+            2,
+            ProtocolOp::SearchResEntry(SearchResultEntry::new("cn=a".into(), <_>::default())),
+        );
+        assert_eq!(
+            Matched::Partial(&"search"),
+            pending.match_response(&entry).unwrap()
+        );
+        assert_eq!(
+            Matched::Final("search"),
+            pending.match_response(&done(2)).unwrap()
+        );
+        assert!(matches!(
+            pending.match_response(&done(2)),
+            Err(FramingError::UnknownMessageId(2))
+        ));
+        assert_eq!(
+            Matched::Unsolicited,
+            pending.match_response(&done(0)).unwrap()
+        );
+        assert_eq!(Some(&"bind"), pending.get(1));
+        assert_eq!(Some("bind"), pending.remove(1));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn message_ids_wrap() {
+        let mut pending = PendingRequests::new();
+        pending.insert(());
+        pending.next_id = MAX_MESSAGE_ID;
+        assert_eq!(MAX_MESSAGE_ID, pending.insert(()));
+        // One is still pending, so it is skipped.
+        assert_eq!(2, pending.next_id());
+    }
+}
@@ -0,0 +1,292 @@
+//! The parsed form of a MIB module.
+
+/// A single `DEFINITIONS ::= BEGIN ... END` MIB module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Module {
+    pub name: String,
+    pub imports: Vec<Import>,
+    pub types: Vec<TypeAssignment>,
+    pub nodes: Vec<Node>,
+}
+
+impl Module {
+    /// Returns the `OBJECT IDENTIFIER` node named `name`, if it is defined in
+    /// this module.
+    pub fn node(&self, name: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    /// Returns the type named `name`, if it is defined in this module.
+    pub fn type_assignment(&self, name: &str) -> Option<&TypeAssignment> {
+        self.types.iter().find(|ty| ty.name == name)
+    }
+
+    /// Returns the module that `name` is imported from, if it is imported.
+    pub fn imported_from(&self, name: &str) -> Option<&str> {
+        self.imports
+            .iter()
+            .find(|import| import.names.iter().any(|n| n == name))
+            .map(|import| &*import.module)
+    }
+
+    /// Returns whether this module is written in SMIv2, i.e. it imports
+    /// from `SNMPv2-SMI`.
+    pub fn is_smi_v2(&self) -> bool {
+        self.imports
+            .iter()
+            .any(|import| import.module == "SNMPv2-SMI")
+    }
+}
+
+/// The names imported from another module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Import {
+    pub module: String,
+    pub names: Vec<String>,
+}
+
+/// A type assignment, such as a `TEXTUAL-CONVENTION` or the `SEQUENCE`
+/// describing a table's conceptual row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TypeAssignment {
+    pub name: String,
+    pub syntax: Syntax,
+    /// Present when the type is defined with a `TEXTUAL-CONVENTION` macro.
+    pub textual_convention: Option<TextualConvention>,
+}
+
+/// The clauses of a `TEXTUAL-CONVENTION`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TextualConvention {
+    pub display_hint: Option<String>,
+    pub status: Status,
+    pub description: String,
+    pub reference: Option<String>,
+}
+
+/// The syntax of an object or type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Syntax {
+    /// A reference to a base type such as `INTEGER`, `OCTET STRING` or
+    /// `Counter32`, or to a textual convention, with an optional refinement.
+    Type {
+        name: String,
+        refinement: Refinement,
+    },
+    /// A `BITS` construct, with its named bit positions.
+    Bits(Vec<NamedNumber>),
+    /// A `SEQUENCE OF` a conceptual row type, describing a table.
+    SequenceOf(String),
+    /// A `SEQUENCE` of columns, describing a conceptual row.
+    Sequence(Vec<(String, Syntax)>),
+    /// A `CHOICE`, only found in the SMI definitions themselves.
+    Choice,
+}
+
+impl Syntax {
+    /// The enumerated values of an `INTEGER` syntax, if any.
+    pub fn enumeration(&self) -> Option<&[NamedNumber]> {
+        match self {
+            Self::Type {
+                refinement: Refinement::Enumeration(values),
+                ..
+            } => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// A sub-typing refinement of a syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Refinement {
+    None,
+    /// `{ up(1), down(2) }`
+    Enumeration(Vec<NamedNumber>),
+    /// `(0..255 | 1024)`
+    Range(Vec<Range>),
+    /// `(SIZE (0..255))`
+    Size(Vec<Range>),
+}
+
+/// A named number from an enumeration or `BITS` construct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NamedNumber {
+    pub name: String,
+    pub value: i128,
+}
+
+impl NamedNumber {
+    /// Named number constructor.
+    pub fn new(name: impl Into<String>, value: i128) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+/// An inclusive range of values, `min` equals `max` for single values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Range {
+    pub min: i128,
+    pub max: i128,
+}
+
+impl Range {
+    /// Range constructor.
+    pub fn new(min: i128, max: i128) -> Self {
+        Self { min, max }
+    }
+}
+
+/// A value assigned an `OBJECT IDENTIFIER`, either directly or by one of the
+/// SMI macros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+    pub status: Option<Status>,
+    pub description: Option<String>,
+    pub reference: Option<String>,
+    /// The value of the node, relative to the first component.
+    pub value: Vec<OidComponent>,
+}
+
+/// The macro that defined a [`Node`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NodeKind {
+    /// `name OBJECT IDENTIFIER ::= { ... }`
+    ObjectIdentifier,
+    ModuleIdentity(ModuleIdentity),
+    ObjectIdentity,
+    ObjectType(ObjectType),
+    /// A `NOTIFICATION-TYPE`, with the objects it carries.
+    Notification {
+        objects: Vec<String>,
+    },
+    /// An SMIv1 `TRAP-TYPE`, whose value is the enterprise followed by
+    /// `0` and the specific trap number, as RFC 3584 maps it.
+    Trap {
+        variables: Vec<String>,
+    },
+    /// An `OBJECT-GROUP` or `NOTIFICATION-GROUP`, with its members.
+    Group {
+        members: Vec<String>,
+    },
+    /// A `MODULE-COMPLIANCE` or `AGENT-CAPABILITIES` statement.
+    Conformance,
+}
+
+/// The clauses of a `MODULE-IDENTITY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModuleIdentity {
+    pub last_updated: String,
+    pub organization: String,
+    pub contact_info: String,
+    /// The revision dates, most recent first.
+    pub revisions: Vec<String>,
+}
+
+/// The clauses of an `OBJECT-TYPE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObjectType {
+    pub syntax: Syntax,
+    pub units: Option<String>,
+    pub access: Access,
+    pub index: Option<Index>,
+    /// The unparsed contents of the `DEFVAL` clause.
+    pub default_value: Option<String>,
+}
+
+/// The `INDEX` or `AUGMENTS` clause of a conceptual row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Index {
+    Index(Vec<IndexPart>),
+    Augments(String),
+}
+
+/// A single object in an `INDEX` clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IndexPart {
+    pub name: String,
+    /// Whether the object was marked `IMPLIED`, omitting its length.
+    pub implied: bool,
+}
+
+/// The `MAX-ACCESS` (or SMIv1 `ACCESS`) of an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Access {
+    NotAccessible,
+    AccessibleForNotify,
+    ReadOnly,
+    ReadWrite,
+    ReadCreate,
+    WriteOnly,
+}
+
+impl Access {
+    pub(crate) fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "not-accessible" => Self::NotAccessible,
+            "accessible-for-notify" => Self::AccessibleForNotify,
+            "read-only" => Self::ReadOnly,
+            "read-write" => Self::ReadWrite,
+            "read-create" => Self::ReadCreate,
+            "write-only" => Self::WriteOnly,
+            _ => return None,
+        })
+    }
+}
+
+/// The `STATUS` of a definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Status {
+    Current,
+    Deprecated,
+    Obsolete,
+    /// The SMIv1 `mandatory` status.
+    Mandatory,
+    /// The SMIv1 `optional` status.
+    Optional,
+}
+
+impl Status {
+    pub(crate) fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "current" => Self::Current,
+            "deprecated" => Self::Deprecated,
+            "obsolete" => Self::Obsolete,
+            "mandatory" => Self::Mandatory,
+            "optional" => Self::Optional,
+            _ => return None,
+        })
+    }
+}
+
+/// A component of an `OBJECT IDENTIFIER` value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OidComponent {
+    /// A descriptor such as `mib-2`, only valid as the first component.
+    Name(String),
+    /// A plain arc such as `31`.
+    Number(u32),
+    /// A named arc such as `org(3)`.
+    NamedNumber(String, u32),
+}
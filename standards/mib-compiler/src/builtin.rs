@@ -0,0 +1,263 @@
+//! The modules that define the SMI itself, and the handful of widely imported
+//! modules, so that most MIBs compile without any search paths. Only the
+//! definitions that other modules import are included, the macros are
+//! understood by the parser directly.
+
+pub(crate) const MODULES: &[&str] = &[
+    SNMPV2_SMI,
+    SNMPV2_TC,
+    SNMPV2_CONF,
+    SNMPV2_MIB,
+    RFC1155_SMI,
+    RFC_1212,
+    RFC_1215,
+    RFC1213_MIB,
+    IANA_IF_TYPE_MIB,
+];
+
+const SNMPV2_SMI: &str = r#"
+SNMPv2-SMI DEFINITIONS ::= BEGIN
+
+org            OBJECT IDENTIFIER ::= { iso 3 }
+dod            OBJECT IDENTIFIER ::= { org 6 }
+internet       OBJECT IDENTIFIER ::= { dod 1 }
+directory      OBJECT IDENTIFIER ::= { internet 1 }
+mgmt           OBJECT IDENTIFIER ::= { internet 2 }
+mib-2          OBJECT IDENTIFIER ::= { mgmt 1 }
+transmission   OBJECT IDENTIFIER ::= { mib-2 10 }
+experimental   OBJECT IDENTIFIER ::= { internet 3 }
+private        OBJECT IDENTIFIER ::= { internet 4 }
+enterprises    OBJECT IDENTIFIER ::= { private 1 }
+security       OBJECT IDENTIFIER ::= { internet 5 }
+snmpV2         OBJECT IDENTIFIER ::= { internet 6 }
+snmpDomains    OBJECT IDENTIFIER ::= { snmpV2 1 }
+snmpProxys     OBJECT IDENTIFIER ::= { snmpV2 2 }
+snmpModules    OBJECT IDENTIFIER ::= { snmpV2 3 }
+zeroDotZero    OBJECT IDENTIFIER ::= { 0 0 }
+
+Integer32 ::= INTEGER (-2147483648..2147483647)
+IpAddress ::= [APPLICATION 0] IMPLICIT OCTET STRING (SIZE (4))
+Counter32 ::= [APPLICATION 1] IMPLICIT INTEGER (0..4294967295)
+Gauge32 ::= [APPLICATION 2] IMPLICIT INTEGER (0..4294967295)
+Unsigned32 ::= [APPLICATION 2] IMPLICIT INTEGER (0..4294967295)
+TimeTicks ::= [APPLICATION 3] IMPLICIT INTEGER (0..4294967295)
+Opaque ::= [APPLICATION 4] IMPLICIT OCTET STRING
+Counter64 ::= [APPLICATION 6] IMPLICIT INTEGER (0..18446744073709551615)
+
+END
+"#;
+
+const SNMPV2_TC: &str = r#"
+SNMPv2-TC DEFINITIONS ::= BEGIN
+
+DisplayString ::= TEXTUAL-CONVENTION
+    DISPLAY-HINT "255a"
+    STATUS       current
+    DESCRIPTION  "Represents textual information taken from the NVT ASCII
+                 character set."
+    SYNTAX       OCTET STRING (SIZE (0..255))
+
+PhysAddress ::= TEXTUAL-CONVENTION
+    DISPLAY-HINT "1x:"
+    STATUS       current
+    DESCRIPTION  "Represents media- or physical-level addresses."
+    SYNTAX       OCTET STRING
+
+MacAddress ::= TEXTUAL-CONVENTION
+    DISPLAY-HINT "1x:"
+    STATUS       current
+    DESCRIPTION  "Represents an 802 MAC address in canonical order."
+    SYNTAX       OCTET STRING (SIZE (6))
+
+TruthValue ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Represents a boolean value."
+    SYNTAX       INTEGER { true(1), false(2) }
+
+TestAndIncr ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Represents integer-valued information used for atomic
+                 operations."
+    SYNTAX       INTEGER (0..2147483647)
+
+AutonomousType ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Represents an independently extensible type identification
+                 value."
+    SYNTAX       OBJECT IDENTIFIER
+
+InstancePointer ::= TEXTUAL-CONVENTION
+    STATUS       obsolete
+    DESCRIPTION  "A pointer to either a specific instance of a MIB object or
+                 a conceptual row of a MIB table."
+    SYNTAX       OBJECT IDENTIFIER
+
+VariablePointer ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "A pointer to a specific object instance."
+    SYNTAX       OBJECT IDENTIFIER
+
+RowPointer ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Represents a pointer to a conceptual row."
+    SYNTAX       OBJECT IDENTIFIER
+
+RowStatus ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "The RowStatus textual convention is used to manage the
+                 creation and deletion of conceptual rows."
+    SYNTAX       INTEGER {
+                     active(1),
+                     notInService(2),
+                     notReady(3),
+                     createAndGo(4),
+                     createAndWait(5),
+                     destroy(6)
+                 }
+
+TimeStamp ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "The value of the sysUpTime object at which a specific
+                 occurrence happened."
+    SYNTAX       TimeTicks
+
+TimeInterval ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "A period of time, measured in units of 0.01 seconds."
+    SYNTAX       INTEGER (0..2147483647)
+
+DateAndTime ::= TEXTUAL-CONVENTION
+    DISPLAY-HINT "2d-1d-1d,1d:1d:1d.1d,1a1d:1d"
+    STATUS       current
+    DESCRIPTION  "A date-time specification."
+    SYNTAX       OCTET STRING (SIZE (8 | 11))
+
+StorageType ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Describes the memory realization of a conceptual row."
+    SYNTAX       INTEGER {
+                     other(1),
+                     volatile(2),
+                     nonVolatile(3),
+                     permanent(4),
+                     readOnly(5)
+                 }
+
+TDomain ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Denotes a kind of transport service."
+    SYNTAX       OBJECT IDENTIFIER
+
+TAddress ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "Denotes a transport service address."
+    SYNTAX       OCTET STRING (SIZE (1..255))
+
+END
+"#;
+
+const SNMPV2_CONF: &str = r#"
+SNMPv2-CONF DEFINITIONS ::= BEGIN
+END
+"#;
+
+const SNMPV2_MIB: &str = r#"
+SNMPv2-MIB DEFINITIONS ::= BEGIN
+
+IMPORTS
+    mib-2, snmpModules FROM SNMPv2-SMI;
+
+system            OBJECT IDENTIFIER ::= { mib-2 1 }
+snmp              OBJECT IDENTIFIER ::= { mib-2 11 }
+snmpMIB           OBJECT IDENTIFIER ::= { snmpModules 1 }
+snmpMIBObjects    OBJECT IDENTIFIER ::= { snmpMIB 1 }
+snmpTrap          OBJECT IDENTIFIER ::= { snmpMIBObjects 4 }
+snmpTrapOID       OBJECT IDENTIFIER ::= { snmpTrap 1 }
+snmpTrapEnterprise OBJECT IDENTIFIER ::= { snmpTrap 3 }
+snmpTraps         OBJECT IDENTIFIER ::= { snmpMIBObjects 5 }
+snmpSet           OBJECT IDENTIFIER ::= { snmpMIBObjects 6 }
+snmpMIBConformance OBJECT IDENTIFIER ::= { snmpMIB 2 }
+snmpMIBCompliances OBJECT IDENTIFIER ::= { snmpMIBConformance 1 }
+snmpMIBGroups     OBJECT IDENTIFIER ::= { snmpMIBConformance 2 }
+sysDescr          OBJECT IDENTIFIER ::= { system 1 }
+sysObjectID       OBJECT IDENTIFIER ::= { system 2 }
+sysUpTime         OBJECT IDENTIFIER ::= { system 3 }
+sysContact        OBJECT IDENTIFIER ::= { system 4 }
+sysName           OBJECT IDENTIFIER ::= { system 5 }
+sysLocation       OBJECT IDENTIFIER ::= { system 6 }
+sysServices       OBJECT IDENTIFIER ::= { system 7 }
+coldStart         OBJECT IDENTIFIER ::= { snmpTraps 1 }
+warmStart         OBJECT IDENTIFIER ::= { snmpTraps 2 }
+authenticationFailure OBJECT IDENTIFIER ::= { snmpTraps 5 }
+
+END
+"#;
+
+const RFC1155_SMI: &str = r#"
+RFC1155-SMI DEFINITIONS ::= BEGIN
+
+internet      OBJECT IDENTIFIER ::= { iso org(3) dod(6) 1 }
+directory     OBJECT IDENTIFIER ::= { internet 1 }
+mgmt          OBJECT IDENTIFIER ::= { internet 2 }
+experimental  OBJECT IDENTIFIER ::= { internet 3 }
+private       OBJECT IDENTIFIER ::= { internet 4 }
+enterprises   OBJECT IDENTIFIER ::= { private 1 }
+
+NetworkAddress ::= CHOICE { internet IpAddress }
+IpAddress ::= [APPLICATION 0] IMPLICIT OCTET STRING (SIZE (4))
+Counter ::= [APPLICATION 1] IMPLICIT INTEGER (0..4294967295)
+Gauge ::= [APPLICATION 2] IMPLICIT INTEGER (0..4294967295)
+TimeTicks ::= [APPLICATION 3] IMPLICIT INTEGER (0..4294967295)
+Opaque ::= [APPLICATION 4] IMPLICIT OCTET STRING
+
+END
+"#;
+
+const RFC_1212: &str = r#"
+RFC-1212 DEFINITIONS ::= BEGIN
+END
+"#;
+
+const RFC_1215: &str = r#"
+RFC-1215 DEFINITIONS ::= BEGIN
+END
+"#;
+
+const RFC1213_MIB: &str = r#"
+RFC1213-MIB DEFINITIONS ::= BEGIN
+
+IMPORTS
+    mgmt FROM RFC1155-SMI;
+
+mib-2         OBJECT IDENTIFIER ::= { mgmt 1 }
+system        OBJECT IDENTIFIER ::= { mib-2 1 }
+interfaces    OBJECT IDENTIFIER ::= { mib-2 2 }
+at            OBJECT IDENTIFIER ::= { mib-2 3 }
+ip            OBJECT IDENTIFIER ::= { mib-2 4 }
+icmp          OBJECT IDENTIFIER ::= { mib-2 5 }
+tcp           OBJECT IDENTIFIER ::= { mib-2 6 }
+udp           OBJECT IDENTIFIER ::= { mib-2 7 }
+egp           OBJECT IDENTIFIER ::= { mib-2 8 }
+transmission  OBJECT IDENTIFIER ::= { mib-2 10 }
+snmp          OBJECT IDENTIFIER ::= { mib-2 11 }
+
+DisplayString ::= OCTET STRING
+PhysAddress ::= OCTET STRING
+
+END
+"#;
+
+/// Only the type itself is included, as the list of interface types changes
+/// too often to be kept here. Load the current module from IANA to get the
+/// named values.
+const IANA_IF_TYPE_MIB: &str = r#"
+IANAifType-MIB DEFINITIONS ::= BEGIN
+
+IANAifType ::= TEXTUAL-CONVENTION
+    STATUS       current
+    DESCRIPTION  "This data type is used as the syntax of the ifType object
+                 in the (updated) definition of MIB-II's ifTable."
+    SYNTAX       INTEGER
+
+END
+"#;
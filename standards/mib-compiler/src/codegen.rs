@@ -0,0 +1,558 @@
+use std::fmt::Write;
+
+use crate::{ast::*, Compiler, Error};
+
+/// The modules whose application types map directly onto `smi` types.
+const SMI_MODULES: &[&str] = &["SNMPv2-SMI", "RFC1155-SMI"];
+
+/// Guards against modules whose values or types refer back to themselves.
+const MAX_DEPTH: usize = 64;
+
+/// The Rust type chosen for a syntax, along with any named values that
+/// should be made available as constants on objects of that syntax.
+struct TypeInfo {
+    rust: String,
+    values: Vec<NamedNumber>,
+    bits: bool,
+    integer: bool,
+}
+
+impl TypeInfo {
+    fn new(rust: &str) -> Self {
+        Self {
+            rust: rust.into(),
+            values: Vec::new(),
+            bits: false,
+            integer: false,
+        }
+    }
+}
+
+/// Tracks which imports the generated code needs, so that it compiles
+/// without unused import warnings.
+#[derive(Default)]
+struct Uses {
+    types: bool,
+    v2: bool,
+    objects: bool,
+    tables: bool,
+}
+
+pub(crate) struct Generator<'a> {
+    compiler: &'a Compiler,
+    module: &'a Module,
+    uses: Uses,
+}
+
+impl<'a> Generator<'a> {
+    pub(crate) fn new(compiler: &'a Compiler, module: &'a Module) -> Self {
+        Self {
+            compiler,
+            module,
+            uses: Uses::default(),
+        }
+    }
+
+    pub(crate) fn generate(mut self) -> Result<String, Error> {
+        let module = self.module;
+        let mut body = String::new();
+
+        for ty in &module.types {
+            self.type_alias(&mut body, ty)?;
+        }
+
+        for node in &module.nodes {
+            let description = match node.kind {
+                NodeKind::ObjectIdentifier => None,
+                NodeKind::ModuleIdentity(_) | NodeKind::ObjectIdentity => {
+                    node.description.as_deref()
+                }
+                _ => continue,
+            };
+            self.constant(&mut body, node, description.map(String::from))?;
+        }
+
+        let mut objects = String::new();
+        for node in &module.nodes {
+            if let NodeKind::ObjectType(object) = &node.kind {
+                self.object_type(&mut objects, node, object)?;
+            }
+        }
+        if !objects.is_empty() {
+            self.uses.objects = true;
+            writeln!(body, "\nobject_type! {{{}\n}}", objects.trim_end()).unwrap();
+        }
+
+        for node in &module.nodes {
+            let objects = match &node.kind {
+                NodeKind::Notification { objects } => objects,
+                NodeKind::Trap { variables } => variables,
+                _ => continue,
+            };
+            let mut description = node.description.clone().unwrap_or_default();
+            if !objects.is_empty() {
+                let objects = objects
+                    .iter()
+                    .map(|object| format!("`{object}`"))
+                    .collect::<Vec<_>>();
+                write!(description, "\n\nObjects: {}.", objects.join(", ")).unwrap();
+            }
+            self.constant(&mut body, node, Some(description))?;
+        }
+
+        let mut output = format!(
+            "// Generated from `{}` by rasn-mib-compiler.\n",
+            module.name
+        );
+        if self.uses.tables {
+            output.push_str("\nuse alloc::{string::ToString, vec::Vec};\n");
+        } else {
+            output.push('\n');
+        }
+        match (self.uses.types, self.uses.objects) {
+            (true, true) => output.push_str("use rasn::{types::*, AsnType};\n"),
+            (true, false) => output.push_str("use rasn::types::*;\n"),
+            (false, true) => output.push_str("use rasn::AsnType;\n"),
+            (false, false) => {}
+        }
+        match (self.uses.objects, self.uses.v2) {
+            (true, true) => output.push_str("use smi::{object_type, v2::*};\n"),
+            (true, false) => output.push_str("use smi::object_type;\n"),
+            (false, true) => output.push_str("use smi::v2::*;\n"),
+            (false, false) => {}
+        }
+        output.push_str(&body);
+
+        Ok(output)
+    }
+
+    /// Emits a `pub type` for a textual convention or plain type assignment,
+    /// table rows are emitted as structs by their objects instead.
+    fn type_alias(&mut self, out: &mut String, ty: &TypeAssignment) -> Result<(), Error> {
+        if !matches!(ty.syntax, Syntax::Type { .. } | Syntax::Bits(_)) {
+            return Ok(());
+        }
+
+        let info = self.type_info(self.module, &ty.syntax, false, 0)?;
+        out.push('\n');
+        if let Some(tc) = &ty.textual_convention {
+            doc(out, "", &tc.description);
+        }
+        writeln!(out, "pub type {} = {};", upper_camel(&ty.name), info.rust).unwrap();
+        Ok(())
+    }
+
+    fn constant(
+        &mut self,
+        out: &mut String,
+        node: &Node,
+        description: Option<String>,
+    ) -> Result<(), Error> {
+        let value = self.compiler.resolve_oid(self.module, &node.name)?;
+        self.uses.types = true;
+        out.push('\n');
+        if let Some(description) = description {
+            doc(out, "", &description);
+        }
+        writeln!(
+            out,
+            "pub const {}: &Oid = Oid::const_new(&{});",
+            screaming_snake(&node.name),
+            arcs(&value)
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn object_type(
+        &mut self,
+        out: &mut String,
+        node: &Node,
+        object: &ObjectType,
+    ) -> Result<(), Error> {
+        let name = upper_camel(&node.name);
+        let value = self.compiler.resolve_oid(self.module, &node.name)?;
+
+        let mut description = node.description.clone().unwrap_or_default();
+        if let Some(units) = &object.units {
+            write!(description, "\n\nUnits: `{units}`.").unwrap();
+        }
+        if let Some(default) = &object.default_value {
+            write!(description, "\n\nDefaults to `{default}`.").unwrap();
+        }
+        match &object.index {
+            Some(Index::Index(parts)) => {
+                let parts = parts
+                    .iter()
+                    .map(|part| match part.implied {
+                        true => format!("`IMPLIED {}`", part.name),
+                        false => format!("`{}`", part.name),
+                    })
+                    .collect::<Vec<_>>();
+                write!(description, "\n\nIndexed by {}.", parts.join(", ")).unwrap();
+            }
+            Some(Index::Augments(row)) => {
+                write!(description, "\n\nAugments `{row}`.").unwrap();
+            }
+            None => {}
+        }
+
+        out.push('\n');
+        doc(out, "    ", &description);
+        out.push_str("    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]\n");
+
+        let mut info = None;
+        match &object.syntax {
+            Syntax::SequenceOf(row) => {
+                let entry = self
+                    .module
+                    .nodes
+                    .iter()
+                    .find(|node| {
+                        matches!(
+                            &node.kind,
+                            NodeKind::ObjectType(ObjectType {
+                                syntax: Syntax::Type { name, .. },
+                                ..
+                            }) if name == row
+                        )
+                    })
+                    .ok_or_else(|| self.unresolved(row))?;
+                self.uses.tables = true;
+                writeln!(
+                    out,
+                    "    pub opaque struct {name}(pub Vec<{}>);",
+                    upper_camel(&entry.name)
+                )
+                .unwrap();
+            }
+            Syntax::Type {
+                name: row,
+                refinement: Refinement::None,
+            } if matches!(
+                self.module.type_assignment(row),
+                Some(TypeAssignment {
+                    syntax: Syntax::Sequence(_),
+                    ..
+                })
+            ) =>
+            {
+                let Some(TypeAssignment {
+                    syntax: Syntax::Sequence(columns),
+                    ..
+                }) = self.module.type_assignment(row)
+                else {
+                    unreachable!()
+                };
+                self.uses.tables = true;
+                writeln!(out, "    pub struct {name} {{").unwrap();
+                for (column, _) in columns {
+                    if self.module.node(column).is_none() {
+                        return Err(self.unresolved(column));
+                    }
+                    writeln!(
+                        out,
+                        "        pub {}: {},",
+                        snake_case(column),
+                        upper_camel(column)
+                    )
+                    .unwrap();
+                }
+                out.push_str("    }\n");
+            }
+            syntax => {
+                let syntax = self.type_info(self.module, syntax, true, 0)?;
+                writeln!(out, "    pub struct {name}(pub {});", syntax.rust).unwrap();
+                info = Some(syntax);
+            }
+        }
+
+        writeln!(out, "    access: {},", access(object.access)).unwrap();
+        writeln!(out, "    status: {},", status(node.status)).unwrap();
+        writeln!(out, "    value = {};", arcs(&value)).unwrap();
+
+        if let Some(info) = info.filter(|info| !info.values.is_empty()) {
+            let ty = match info.values.iter().any(|value| value.value < 0) {
+                true => "i64",
+                false => "u64",
+            };
+            writeln!(out, "\n    impl {name} {{").unwrap();
+            if info.bits {
+                out.push_str("        // The positions of the named bits.\n");
+            }
+            for value in &info.values {
+                writeln!(
+                    out,
+                    "        pub const {}: {ty} = {};",
+                    screaming_snake(&value.name),
+                    value.value
+                )
+                .unwrap();
+            }
+            out.push_str("    }\n");
+        }
+
+        Ok(())
+    }
+
+    fn type_info(
+        &mut self,
+        module: &'a Module,
+        syntax: &Syntax,
+        local: bool,
+        depth: usize,
+    ) -> Result<TypeInfo, Error> {
+        match syntax {
+            Syntax::Bits(bits) => {
+                self.uses.types = true;
+                Ok(TypeInfo {
+                    values: bits.clone(),
+                    bits: true,
+                    ..TypeInfo::new("OctetString")
+                })
+            }
+            Syntax::Type { name, refinement } => {
+                let mut info = self.named_type(module, name, refinement, local, depth)?;
+                if let Refinement::Enumeration(values) = refinement {
+                    info.values = values.clone();
+                }
+                Ok(info)
+            }
+            Syntax::SequenceOf(_) | Syntax::Sequence(_) | Syntax::Choice => {
+                Err(Error::UnknownType {
+                    module: module.name.clone(),
+                    name: String::from("SEQUENCE"),
+                })
+            }
+        }
+    }
+
+    fn named_type(
+        &mut self,
+        module: &'a Module,
+        name: &str,
+        refinement: &Refinement,
+        local: bool,
+        depth: usize,
+    ) -> Result<TypeInfo, Error> {
+        let unknown = || Error::UnknownType {
+            module: module.name.clone(),
+            name: name.into(),
+        };
+        if depth > MAX_DEPTH {
+            return Err(unknown());
+        }
+
+        let ranges = match refinement {
+            Refinement::Range(ranges) => Some(&**ranges),
+            _ => None,
+        };
+
+        match name {
+            "INTEGER" => return Ok(self.integer(ranges.unwrap_or_default())),
+            "OCTET STRING" => {
+                self.uses.types = true;
+                return Ok(TypeInfo::new("OctetString"));
+            }
+            "OBJECT IDENTIFIER" => {
+                self.uses.types = true;
+                return Ok(TypeInfo::new("ObjectIdentifier"));
+            }
+            _ => {}
+        }
+
+        let is_smi = SMI_MODULES.contains(&&*module.name);
+        if let Some(ty) = module.type_assignment(name).filter(|_| !is_smi) {
+            let mut info = self.type_info(module, &ty.syntax, false, depth + 1)?;
+            if local {
+                info.rust = upper_camel(name);
+            } else if let (true, Some(ranges)) = (info.integer, ranges) {
+                info.rust = self.integer(ranges).rust;
+            }
+            return Ok(info);
+        }
+
+        let application = match name {
+            "Integer32" => {
+                return Ok(self.integer(ranges.unwrap_or(&[Range {
+                    min: i32::MIN.into(),
+                    max: i32::MAX.into(),
+                }])))
+            }
+            "Counter32" | "Counter" => "Counter32",
+            "Gauge32" | "Gauge" => "Gauge32",
+            "Unsigned32" => "Unsigned32",
+            "Counter64" => "Counter64",
+            "TimeTicks" => "TimeTicks",
+            "IpAddress" | "NetworkAddress" => "IpAddress",
+            "Opaque" => "Opaque",
+            _ => {
+                let from = module.imported_from(name).ok_or_else(unknown)?;
+                let from = self
+                    .compiler
+                    .module(from)
+                    .ok_or_else(|| Error::UnknownModule { name: from.into() })?;
+                return self.named_type(from, name, refinement, false, depth + 1);
+            }
+        };
+
+        self.uses.v2 = true;
+        Ok(TypeInfo::new(application))
+    }
+
+    /// Chooses the smallest primitive that holds every value in `ranges`.
+    fn integer(&mut self, ranges: &[Range]) -> TypeInfo {
+        let min = ranges.iter().map(|range| range.min).min();
+        let max = ranges.iter().map(|range| range.max).max();
+
+        let rust = match min.zip(max) {
+            Some((min, max)) if min >= 0 && max <= u32::MAX.into() => "u32",
+            Some((min, max)) if min >= i32::MIN.into() && max <= i32::MAX.into() => "i32",
+            _ => {
+                self.uses.types = true;
+                "Integer"
+            }
+        };
+
+        TypeInfo {
+            integer: true,
+            ..TypeInfo::new(rust)
+        }
+    }
+
+    fn unresolved(&self, name: &str) -> Error {
+        Error::UnresolvedName {
+            module: self.module.name.clone(),
+            name: name.into(),
+        }
+    }
+}
+
+fn access(access: Access) -> &'static str {
+    match access {
+        Access::NotAccessible => "NotAccessible",
+        Access::AccessibleForNotify => "AccessibleForNotify",
+        Access::ReadOnly => "ReadOnly",
+        Access::ReadWrite => "ReadWrite",
+        Access::ReadCreate => "ReadCreate",
+        Access::WriteOnly => "WriteOnly",
+    }
+}
+
+fn status(status: Option<Status>) -> &'static str {
+    match status {
+        Some(Status::Deprecated) => "Deprecated",
+        Some(Status::Obsolete) => "Obsolete",
+        // SMIv1's `mandatory` and `optional` both map to `current`, as
+        // RFC 3584 describes.
+        Some(Status::Current | Status::Mandatory | Status::Optional) | None => "Current",
+    }
+}
+
+fn arcs(value: &[u32]) -> String {
+    let arcs = value.iter().map(u32::to_string).collect::<Vec<_>>();
+    format!("[{}]", arcs.join(", "))
+}
+
+/// Writes `text` as a doc comment, trimming the indentation MIB authors use
+/// inside quoted strings so that it isn't taken as a code block.
+fn doc(out: &mut String, indent: &str, text: &str) {
+    let lines = text.lines().map(str::trim).collect::<Vec<_>>();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    let (Some(start), Some(end)) = (start, end) else {
+        return;
+    };
+
+    let mut blank = false;
+    for line in &lines[start..=end] {
+        if line.is_empty() {
+            blank = true;
+            continue;
+        }
+        if blank {
+            writeln!(out, "{indent}///").unwrap();
+            blank = false;
+        }
+        writeln!(out, "{indent}/// {line}").unwrap();
+    }
+}
+
+/// `ifHCInOctets` to `IfHCInOctets`.
+pub(crate) fn upper_camel(name: &str) -> String {
+    let mut output = String::with_capacity(name.len());
+    let mut capitalise = true;
+    for c in name.chars() {
+        if c == '-' || c == '_' {
+            capitalise = true;
+        } else if capitalise {
+            output.push(c.to_ascii_uppercase());
+            capitalise = false;
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// `ifHCInOctets` to `if_hc_in_octets`, escaping Rust keywords.
+pub(crate) fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '-' {
+            output.push('_');
+            continue;
+        }
+
+        if c.is_ascii_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                output.push('_');
+            }
+        }
+        output.push(c.to_ascii_lowercase());
+    }
+
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type",
+        "unsafe", "use", "where", "while", "yield",
+    ];
+
+    if KEYWORDS.contains(&&*output) {
+        output.insert_str(0, "r#");
+    }
+    output
+}
+
+/// `ifMIB` to `IF_MIB`.
+pub(crate) fn screaming_snake(name: &str) -> String {
+    snake_case(name)
+        .trim_start_matches("r#")
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(upper_camel("ifHCInOctets"), "IfHCInOctets");
+        assert_eq!(upper_camel("mib-2"), "Mib2");
+        assert_eq!(snake_case("ifHCInOctets"), "if_hc_in_octets");
+        assert_eq!(snake_case("ifType"), "if_type");
+        assert_eq!(snake_case("type"), "r#type");
+        assert_eq!(screaming_snake("ifMIB"), "IF_MIB");
+        assert_eq!(screaming_snake("mib-2"), "MIB_2");
+        assert_eq!(screaming_snake("iso88023Csmacd"), "ISO88023_CSMACD");
+        assert_eq!(screaming_snake("ethernetCsmacd"), "ETHERNET_CSMACD");
+    }
+}
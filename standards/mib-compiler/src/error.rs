@@ -0,0 +1,48 @@
+use std::{fmt, path::PathBuf};
+
+/// An error produced while loading, parsing or compiling a MIB module.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A MIB file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The source text is not a valid SMI module.
+    Syntax { line: usize, message: String },
+    /// A module was imported from, or requested for generation, but has not
+    /// been loaded and could not be found in any search path.
+    UnknownModule { name: String },
+    /// A descriptor used in an `OBJECT IDENTIFIER` value could not be
+    /// resolved.
+    UnresolvedName { module: String, name: String },
+    /// A `SYNTAX` clause refers to a type that is neither an SMI base type
+    /// nor a known textual convention.
+    UnknownType { module: String, name: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            Self::UnknownModule { name } => write!(f, "module `{name}` could not be found"),
+            Self::UnresolvedName { module, name } => {
+                write!(f, "{module}: `{name}` is not a known object identifier")
+            }
+            Self::UnknownType { module, name } => {
+                write!(f, "{module}: `{name}` is not a known type")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
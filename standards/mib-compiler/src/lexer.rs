@@ -0,0 +1,172 @@
+use crate::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// An identifier, type reference or keyword such as `OBJECT-TYPE`.
+    Ident(String),
+    Number(i128),
+    /// A quoted string, with the quotes removed.
+    Str(String),
+    /// A `'..'H` hexadecimal string, with the quotes and suffix removed.
+    Hex(String),
+    /// A `'..'B` binary string, with the quotes and suffix removed.
+    Bin(String),
+    Assign,
+    Range,
+    LeftBrace,
+    RightBrace,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Semicolon,
+    Pipe,
+}
+
+impl Token {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::Ident(ident) => format!("`{ident}`"),
+            Self::Number(number) => format!("`{number}`"),
+            Self::Str(_) => "a string".into(),
+            Self::Hex(_) | Self::Bin(_) => "a binary string".into(),
+            Self::Assign => "`::=`".into(),
+            Self::Range => "`..`".into(),
+            Self::LeftBrace => "`{`".into(),
+            Self::RightBrace => "`}`".into(),
+            Self::LeftParen => "`(`".into(),
+            Self::RightParen => "`)`".into(),
+            Self::LeftBracket => "`[`".into(),
+            Self::RightBracket => "`]`".into(),
+            Self::Comma => "`,`".into(),
+            Self::Semicolon => "`;`".into(),
+            Self::Pipe => "`|`".into(),
+        }
+    }
+}
+
+/// A token along with the line it started on.
+#[derive(Debug, Clone)]
+pub(crate) struct Spanned {
+    pub token: Token,
+    pub line: usize,
+}
+
+/// Splits MIB source text into tokens, dropping whitespace and `--` comments.
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Spanned>, Error> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    let error = |line, message: String| Error::Syntax { line, message };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = line;
+
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            // A comment runs until the end of the line or the next `--`.
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                i += 2;
+                while i < chars.len() && chars[i] != '\n' {
+                    if chars[i] == '-' && chars.get(i + 1) == Some(&'-') {
+                        i += 2;
+                        break;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .ok_or_else(|| error(start, "unterminated string".into()))?;
+                let text = chars[i + 1..i + 1 + end].iter().collect::<String>();
+                line += text.matches('\n').count();
+                tokens.push(Spanned {
+                    token: Token::Str(text),
+                    line: start,
+                });
+                i += end + 2;
+                continue;
+            }
+            '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '\'')
+                    .ok_or_else(|| error(start, "unterminated binary string".into()))?;
+                let text = chars[i + 1..i + 1 + end].iter().collect::<String>();
+                let token = match chars.get(i + end + 2) {
+                    Some('H' | 'h') => Token::Hex(text),
+                    Some('B' | 'b') => Token::Bin(text),
+                    _ => return Err(error(start, "expected `H` or `B` after string".into())),
+                };
+                tokens.push(Spanned { token, line: start });
+                i += end + 3;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (token, len) = if c.is_ascii_alphabetic() {
+            let mut end = i + 1;
+            while end < chars.len() {
+                match chars[end] {
+                    c if c.is_ascii_alphanumeric() || c == '_' => end += 1,
+                    '-' if chars
+                        .get(end + 1)
+                        .is_some_and(|c| c.is_ascii_alphanumeric()) =>
+                    {
+                        end += 1
+                    }
+                    _ => break,
+                }
+            }
+            (Token::Ident(chars[i..end].iter().collect()), end - i)
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let mut end = i + 1;
+            while end < chars.len() && chars[end].is_ascii_digit() {
+                end += 1;
+            }
+            let text = chars[i..end].iter().collect::<String>();
+            let number = text
+                .parse()
+                .map_err(|_| error(start, format!("number `{text}` is out of range")))?;
+            (Token::Number(number), end - i)
+        } else {
+            match (c, chars.get(i + 1), chars.get(i + 2)) {
+                (':', Some(':'), Some('=')) => (Token::Assign, 3),
+                ('.', Some('.'), _) => (Token::Range, 2),
+                ('{', ..) => (Token::LeftBrace, 1),
+                ('}', ..) => (Token::RightBrace, 1),
+                ('(', ..) => (Token::LeftParen, 1),
+                (')', ..) => (Token::RightParen, 1),
+                ('[', ..) => (Token::LeftBracket, 1),
+                (']', ..) => (Token::RightBracket, 1),
+                (',', ..) => (Token::Comma, 1),
+                (';', ..) => (Token::Semicolon, 1),
+                ('|', ..) => (Token::Pipe, 1),
+                _ => return Err(error(start, format!("unexpected character `{c}`"))),
+            }
+        };
+
+        tokens.push(Spanned { token, line: start });
+        i += len;
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,194 @@
+#![doc = include_str!("../README.md")]
+
+pub mod ast;
+mod builtin;
+mod codegen;
+mod error;
+mod lexer;
+mod parser;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+pub use error::Error;
+
+use ast::{Module, OidComponent};
+
+/// The file extensions tried, in order, when looking for a module in a search
+/// path.
+const EXTENSIONS: &[&str] = &["", "txt", "mib", "my"];
+
+/// Parses every MIB module in `source`, without resolving any imports.
+pub fn parse(source: &str) -> Result<Vec<Module>, Error> {
+    parser::parse(source)
+}
+
+/// Loads MIB modules and generates Rust code for them.
+///
+/// The modules defining the SMI itself, such as `SNMPv2-SMI` and
+/// `SNMPv2-TC`, are built in. Any other module that is imported is loaded
+/// from the search paths when it is needed.
+#[derive(Debug, Clone)]
+pub struct Compiler {
+    modules: BTreeMap<String, Module>,
+    search_paths: Vec<PathBuf>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    /// Compiler constructor, with only the built in modules loaded.
+    pub fn new() -> Self {
+        let mut compiler = Self {
+            modules: BTreeMap::new(),
+            search_paths: Vec::new(),
+        };
+
+        for source in builtin::MODULES {
+            compiler
+                .add_source(source)
+                .expect("built in modules are valid");
+        }
+
+        compiler
+    }
+
+    /// Adds a directory to search for imported modules. Modules are looked
+    /// for by their name, with no extension or with a `.txt`, `.mib` or
+    /// `.my` extension.
+    pub fn add_search_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Parses and loads every module in `source`, replacing any module
+    /// already loaded with the same name. Returns the names of the modules.
+    pub fn add_source(&mut self, source: &str) -> Result<Vec<String>, Error> {
+        let modules = parser::parse(source)?;
+        let names = modules.iter().map(|module| module.name.clone()).collect();
+
+        for module in modules {
+            self.modules.insert(module.name.clone(), module);
+        }
+
+        Ok(names)
+    }
+
+    /// Reads, parses and loads every module in the file at `path`. Returns
+    /// the names of the modules.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.into(),
+            source,
+        })?;
+
+        self.add_source(&source)
+    }
+
+    /// Returns the module named `name`, if it has been loaded.
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules.get(name)
+    }
+
+    /// Loads the module named `name` if it hasn't been loaded, along with
+    /// every module it imports from, from the search paths.
+    pub fn load_module(&mut self, name: &str) -> Result<&Module, Error> {
+        let mut pending = vec![name.to_owned()];
+
+        while let Some(name) = pending.pop() {
+            if !self.modules.contains_key(&name) {
+                let path = self
+                    .find(&name)
+                    .ok_or_else(|| Error::UnknownModule { name: name.clone() })?;
+                self.add_file(path)?;
+            }
+
+            let module = self
+                .modules
+                .get(&name)
+                .ok_or_else(|| Error::UnknownModule { name: name.clone() })?;
+            pending.extend(
+                module
+                    .imports
+                    .iter()
+                    .filter(|import| !self.modules.contains_key(&import.module))
+                    .map(|import| import.module.clone()),
+            );
+        }
+
+        Ok(&self.modules[name])
+    }
+
+    /// Generates Rust source code for the module named `name`, loading it
+    /// and its imports from the search paths if needed.
+    ///
+    /// The generated code refers to `rasn` and to `rasn-smi` as `smi`, and to
+    /// `alloc` when the module defines tables.
+    pub fn generate(&mut self, name: &str) -> Result<String, Error> {
+        self.load_module(name)?;
+        codegen::Generator::new(self, &self.modules[name]).generate()
+    }
+
+    /// Resolves the descriptor `name`, as seen from `module`, to its
+    /// `OBJECT IDENTIFIER` arcs.
+    pub fn resolve_oid(&self, module: &Module, name: &str) -> Result<Vec<u32>, Error> {
+        self.resolve_oid_at_depth(module, name, 0)
+    }
+
+    fn resolve_oid_at_depth(
+        &self,
+        module: &Module,
+        name: &str,
+        depth: usize,
+    ) -> Result<Vec<u32>, Error> {
+        let unresolved = || Error::UnresolvedName {
+            module: module.name.clone(),
+            name: name.into(),
+        };
+
+        match name {
+            "ccitt" => return Ok(vec![0]),
+            "iso" => return Ok(vec![1]),
+            "joint-iso-ccitt" => return Ok(vec![2]),
+            _ if depth > 64 => return Err(unresolved()),
+            _ => {}
+        }
+
+        let Some(node) = module.node(name) else {
+            let from = module.imported_from(name).ok_or_else(unresolved)?;
+            let from = self
+                .module(from)
+                .ok_or_else(|| Error::UnknownModule { name: from.into() })?;
+            return self.resolve_oid_at_depth(from, name, depth + 1);
+        };
+
+        let mut arcs = Vec::new();
+        for (i, component) in node.value.iter().enumerate() {
+            match component {
+                OidComponent::Name(parent) if i == 0 => {
+                    arcs = self.resolve_oid_at_depth(module, parent, depth + 1)?;
+                }
+                OidComponent::Number(arc) | OidComponent::NamedNumber(_, arc) => arcs.push(*arc),
+                OidComponent::Name(_) => return Err(unresolved()),
+            }
+        }
+
+        Ok(arcs)
+    }
+
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        self.search_paths.iter().find_map(|directory| {
+            EXTENSIONS
+                .iter()
+                .map(|extension| directory.join(name).with_extension(extension))
+                .find(|path| path.is_file())
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use rasn_mib_compiler::Compiler;
+
+const USAGE: &str = "\
+Usage: rasn-mib-compiler [-I DIR]... [-o DIR] FILE...
+
+Compiles the MIB modules in each FILE into Rust source code using rasn-smi.
+
+Options:
+  -I DIR    Search DIR for imported modules, may be repeated
+  -o DIR    Write each module to DIR/<module_name>.rs instead of stdout
+  -h        Print this message";
+
+fn main() -> ExitCode {
+    let mut compiler = Compiler::new();
+    let mut output = None;
+    let mut files = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            "-I" | "-o" => {
+                let Some(value) = args.next() else {
+                    eprintln!("{arg} requires a directory\n\n{USAGE}");
+                    return ExitCode::FAILURE;
+                };
+                if arg == "-I" {
+                    compiler.add_search_path(value);
+                } else {
+                    output = Some(PathBuf::from(value));
+                }
+            }
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    match run(&mut compiler, &files, output) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(
+    compiler: &mut Compiler,
+    files: &[PathBuf],
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut modules = Vec::new();
+    for file in files {
+        // Modules next to the ones being compiled are commonly imported.
+        if let Some(directory) = file.parent() {
+            compiler.add_search_path(directory);
+        }
+        modules.extend(compiler.add_file(file)?);
+    }
+
+    for module in modules {
+        let code = compiler
+            .generate(&module)
+            .map_err(|error| format!("{module}: {error}"))?;
+
+        match &output {
+            Some(directory) => {
+                let name = module.to_ascii_lowercase().replace('-', "_");
+                std::fs::write(directory.join(name).with_extension("rs"), code)?;
+            }
+            None => println!("{code}"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,782 @@
+use crate::{
+    ast::*,
+    lexer::{Spanned, Token},
+    Error,
+};
+
+/// Parses every module in `source`.
+pub(crate) fn parse(source: &str) -> Result<Vec<Module>, Error> {
+    let mut parser = Parser {
+        tokens: crate::lexer::tokenize(source)?,
+        position: 0,
+    };
+    let mut modules = Vec::new();
+
+    while parser.peek().is_some() {
+        modules.push(parser.module()?);
+    }
+
+    Ok(modules)
+}
+
+/// The clauses that may appear in any of the SMI macros, parsed in one place
+/// and then checked for each macro.
+#[derive(Default)]
+struct Clauses {
+    syntax: Option<Syntax>,
+    units: Option<String>,
+    access: Option<Access>,
+    status: Option<Status>,
+    description: Option<String>,
+    reference: Option<String>,
+    display_hint: Option<String>,
+    index: Option<Index>,
+    default_value: Option<String>,
+    last_updated: Option<String>,
+    organization: Option<String>,
+    contact_info: Option<String>,
+    revisions: Vec<String>,
+    objects: Option<Vec<String>>,
+    enterprise: Option<String>,
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |t| t.line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Syntax {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        match self.peek() {
+            Some(token) => self.error(format!("expected {expected}, found {}", token.describe())),
+            None => self.error(format!("expected {expected}, found end of input")),
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let matches = self.peek() == Some(token);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matches = self.is_keyword(keyword);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), Error> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&token.describe()))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{keyword}`")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.position += 1;
+                Ok(ident)
+            }
+            _ => Err(self.unexpected("an identifier")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Str(string)) => {
+                let string = string.clone();
+                self.position += 1;
+                Ok(string)
+            }
+            _ => Err(self.unexpected("a string")),
+        }
+    }
+
+    fn number(&mut self) -> Result<i128, Error> {
+        match self.peek() {
+            Some(Token::Number(number)) => {
+                let number = *number;
+                self.position += 1;
+                Ok(number)
+            }
+            _ => Err(self.unexpected("a number")),
+        }
+    }
+
+    fn arc(&mut self) -> Result<u32, Error> {
+        let number = self.number()?;
+        u32::try_from(number).map_err(|_| self.error(format!("`{number}` is not a valid arc")))
+    }
+
+    /// Skips a single token, or a whole `{ ... }` group, returning its text.
+    fn skip_value(&mut self) -> Result<String, Error> {
+        let mut depth = 0usize;
+        let mut text = Vec::new();
+
+        loop {
+            let token = self.next()?;
+            match token {
+                Token::LeftBrace => depth += 1,
+                Token::RightBrace => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| self.error("unbalanced `}`"))?;
+                }
+                _ => {}
+            }
+
+            text.push(match token {
+                Token::Ident(ident) => ident,
+                Token::Number(number) => number.to_string(),
+                Token::Str(string) => format!("\"{string}\""),
+                Token::Hex(hex) => format!("'{hex}'H"),
+                Token::Bin(bin) => format!("'{bin}'B"),
+                Token::Assign => "::=".into(),
+                Token::Range => "..".into(),
+                Token::LeftBrace => "{".into(),
+                Token::RightBrace => "}".into(),
+                Token::LeftParen => "(".into(),
+                Token::RightParen => ")".into(),
+                Token::LeftBracket => "[".into(),
+                Token::RightBracket => "]".into(),
+                Token::Comma => ",".into(),
+                Token::Semicolon => ";".into(),
+                Token::Pipe => "|".into(),
+            });
+
+            if depth == 0 {
+                return Ok(text.join(" "));
+            }
+        }
+    }
+
+    fn skip_until_assign(&mut self) -> Result<(), Error> {
+        while !matches!(self.peek(), Some(Token::Assign)) {
+            self.skip_value()?;
+        }
+        Ok(())
+    }
+
+    fn module(&mut self) -> Result<Module, Error> {
+        let name = self.ident()?;
+        if self.peek() == Some(&Token::LeftBrace) {
+            self.skip_value()?;
+        }
+        self.expect_keyword("DEFINITIONS")?;
+        // Skip any tagging environment, such as `IMPLICIT TAGS`.
+        self.skip_until_assign()?;
+        self.expect(&Token::Assign)?;
+        self.expect_keyword("BEGIN")?;
+
+        let mut module = Module {
+            name,
+            imports: Vec::new(),
+            types: Vec::new(),
+            nodes: Vec::new(),
+        };
+
+        if self.eat_keyword("EXPORTS") {
+            while !self.eat(&Token::Semicolon) {
+                self.next()?;
+            }
+        }
+
+        if self.eat_keyword("IMPORTS") {
+            module.imports = self.imports()?;
+        }
+
+        while !self.eat_keyword("END") {
+            self.assignment(&mut module)?;
+        }
+
+        Ok(module)
+    }
+
+    fn imports(&mut self) -> Result<Vec<Import>, Error> {
+        let mut imports = Vec::new();
+        let mut names = Vec::new();
+
+        while !self.eat(&Token::Semicolon) {
+            if self.eat_keyword("FROM") {
+                imports.push(Import {
+                    module: self.ident()?,
+                    names: core::mem::take(&mut names),
+                });
+            } else if !self.eat(&Token::Comma) {
+                names.push(self.ident()?);
+            }
+        }
+
+        if names.is_empty() {
+            Ok(imports)
+        } else {
+            Err(self.error("imported names are missing a `FROM` clause"))
+        }
+    }
+
+    fn assignment(&mut self, module: &mut Module) -> Result<(), Error> {
+        let line = self.line();
+        let name = self.ident()?;
+
+        let keyword = match self.peek() {
+            Some(Token::Assign) => {
+                self.position += 1;
+                let ty = self.type_assignment(name)?;
+                module.types.push(ty);
+                return Ok(());
+            }
+            Some(Token::Ident(keyword)) => keyword.clone(),
+            _ => return Err(self.unexpected("`::=` or a macro name")),
+        };
+        self.position += 1;
+
+        let kind = match &*keyword {
+            "MACRO" => {
+                while !self.eat_keyword("END") {
+                    self.next()?;
+                }
+                return Ok(());
+            }
+            "OBJECT" => {
+                self.expect_keyword("IDENTIFIER")?;
+                self.expect(&Token::Assign)?;
+                let value = self.oid_value()?;
+                module.nodes.push(Node {
+                    name,
+                    kind: NodeKind::ObjectIdentifier,
+                    status: None,
+                    description: None,
+                    reference: None,
+                    value,
+                });
+                return Ok(());
+            }
+            "MODULE-COMPLIANCE" | "AGENT-CAPABILITIES" => {
+                self.skip_until_assign()?;
+                self.expect(&Token::Assign)?;
+                let value = self.oid_value()?;
+                module.nodes.push(Node {
+                    name,
+                    kind: NodeKind::Conformance,
+                    status: None,
+                    description: None,
+                    reference: None,
+                    value,
+                });
+                return Ok(());
+            }
+            "OBJECT-TYPE" | "MODULE-IDENTITY" | "OBJECT-IDENTITY" | "NOTIFICATION-TYPE"
+            | "TRAP-TYPE" | "OBJECT-GROUP" | "NOTIFICATION-GROUP" => keyword,
+            _ => {
+                // A value assignment of some other type, which SMI does not
+                // give any meaning to.
+                self.skip_until_assign()?;
+                self.expect(&Token::Assign)?;
+                self.skip_value()?;
+                return Ok(());
+            }
+        };
+
+        let clauses = self.clauses(false)?;
+        self.expect(&Token::Assign)?;
+
+        let value = if kind == "TRAP-TYPE" {
+            let enterprise = clauses
+                .enterprise
+                .clone()
+                .ok_or_else(|| self.error(format!("`{name}` is missing an `ENTERPRISE`")))?;
+            vec![
+                OidComponent::Name(enterprise),
+                OidComponent::Number(0),
+                OidComponent::Number(self.arc()?),
+            ]
+        } else {
+            self.oid_value()?
+        };
+
+        let missing = |clause: &str| Error::Syntax {
+            line,
+            message: format!("`{name}` is missing a `{clause}` clause"),
+        };
+
+        let node_kind = match &*kind {
+            "OBJECT-TYPE" => NodeKind::ObjectType(ObjectType {
+                syntax: clauses.syntax.ok_or_else(|| missing("SYNTAX"))?,
+                units: clauses.units,
+                access: clauses.access.ok_or_else(|| missing("MAX-ACCESS"))?,
+                index: clauses.index,
+                default_value: clauses.default_value,
+            }),
+            "MODULE-IDENTITY" => NodeKind::ModuleIdentity(ModuleIdentity {
+                last_updated: clauses
+                    .last_updated
+                    .ok_or_else(|| missing("LAST-UPDATED"))?,
+                organization: clauses.organization.unwrap_or_default(),
+                contact_info: clauses.contact_info.unwrap_or_default(),
+                revisions: clauses.revisions,
+            }),
+            "OBJECT-IDENTITY" => NodeKind::ObjectIdentity,
+            "NOTIFICATION-TYPE" => NodeKind::Notification {
+                objects: clauses.objects.unwrap_or_default(),
+            },
+            "TRAP-TYPE" => NodeKind::Trap {
+                variables: clauses.objects.unwrap_or_default(),
+            },
+            _ => NodeKind::Group {
+                members: clauses.objects.unwrap_or_default(),
+            },
+        };
+
+        module.nodes.push(Node {
+            name,
+            kind: node_kind,
+            status: clauses.status,
+            description: clauses.description,
+            reference: clauses.reference,
+            value,
+        });
+
+        Ok(())
+    }
+
+    fn type_assignment(&mut self, name: String) -> Result<TypeAssignment, Error> {
+        if !self.eat_keyword("TEXTUAL-CONVENTION") {
+            return Ok(TypeAssignment {
+                name,
+                syntax: self.syntax()?,
+                textual_convention: None,
+            });
+        }
+
+        let clauses = self.clauses(true)?;
+
+        Ok(TypeAssignment {
+            syntax: clauses.syntax.ok_or_else(|| Error::Syntax {
+                line: self.line(),
+                message: format!("`{name}` is missing a `SYNTAX` clause"),
+            })?,
+            textual_convention: Some(TextualConvention {
+                display_hint: clauses.display_hint,
+                status: clauses.status.unwrap_or(Status::Current),
+                description: clauses.description.unwrap_or_default(),
+                reference: clauses.reference,
+            }),
+            name,
+        })
+    }
+
+    /// Parses the clauses of a macro up to its `::=`, or up to and including
+    /// the `SYNTAX` clause that ends a `TEXTUAL-CONVENTION`.
+    fn clauses(&mut self, textual_convention: bool) -> Result<Clauses, Error> {
+        let mut clauses = Clauses::default();
+
+        while textual_convention || self.peek() != Some(&Token::Assign) {
+            let keyword = self.ident()?;
+            match &*keyword {
+                "SYNTAX" => {
+                    let syntax = self.syntax()?;
+                    clauses.syntax.get_or_insert(syntax);
+                    if textual_convention {
+                        break;
+                    }
+                }
+                "WRITE-SYNTAX" => {
+                    self.syntax()?;
+                }
+                "UNITS" => clauses.units = Some(self.string()?),
+                "MAX-ACCESS" | "ACCESS" => {
+                    let access = self.ident()?;
+                    clauses.access = Some(
+                        Access::from_keyword(&access)
+                            .ok_or_else(|| self.error(format!("`{access}` is not an access")))?,
+                    );
+                }
+                "STATUS" => {
+                    let status = self.ident()?;
+                    clauses.status = Some(
+                        Status::from_keyword(&status)
+                            .ok_or_else(|| self.error(format!("`{status}` is not a status")))?,
+                    );
+                }
+                "DESCRIPTION" => {
+                    let description = self.string()?;
+                    clauses.description.get_or_insert(description);
+                }
+                "REFERENCE" => {
+                    let reference = self.string()?;
+                    clauses.reference.get_or_insert(reference);
+                }
+                "DISPLAY-HINT" => clauses.display_hint = Some(self.string()?),
+                "INDEX" => clauses.index = Some(Index::Index(self.index()?)),
+                "AUGMENTS" => {
+                    self.expect(&Token::LeftBrace)?;
+                    clauses.index = Some(Index::Augments(self.ident()?));
+                    self.expect(&Token::RightBrace)?;
+                }
+                "DEFVAL" => {
+                    let value = self.skip_value()?;
+                    let value = value
+                        .strip_prefix("{ ")
+                        .and_then(|value| value.strip_suffix(" }"))
+                        .unwrap_or(&value);
+                    clauses.default_value = Some(value.to_owned());
+                }
+                "LAST-UPDATED" => clauses.last_updated = Some(self.string()?),
+                "ORGANIZATION" => clauses.organization = Some(self.string()?),
+                "CONTACT-INFO" => clauses.contact_info = Some(self.string()?),
+                "REVISION" => clauses.revisions.push(self.string()?),
+                "OBJECTS" | "VARIABLES" | "NOTIFICATIONS" => {
+                    clauses.objects = Some(self.name_list()?);
+                }
+                "ENTERPRISE" => clauses.enterprise = Some(self.ident()?),
+                _ => return Err(self.error(format!("unknown clause `{keyword}`"))),
+            }
+        }
+
+        Ok(clauses)
+    }
+
+    fn name_list(&mut self) -> Result<Vec<String>, Error> {
+        self.expect(&Token::LeftBrace)?;
+        let mut names = Vec::new();
+        while !self.eat(&Token::RightBrace) {
+            names.push(self.ident()?);
+            if !self.eat(&Token::Comma) {
+                self.expect(&Token::RightBrace)?;
+                break;
+            }
+        }
+        Ok(names)
+    }
+
+    fn index(&mut self) -> Result<Vec<IndexPart>, Error> {
+        self.expect(&Token::LeftBrace)?;
+        let mut parts = Vec::new();
+        loop {
+            let implied = self.eat_keyword("IMPLIED");
+            // SMIv1 allows a type in place of an object, such as
+            // `OCTET STRING`, which is kept as written.
+            let mut name = self.ident()?;
+            while let Some(Token::Ident(ident)) = self.peek() {
+                name = format!("{name} {ident}");
+                self.position += 1;
+            }
+            parts.push(IndexPart { name, implied });
+
+            if !self.eat(&Token::Comma) {
+                self.expect(&Token::RightBrace)?;
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn syntax(&mut self) -> Result<Syntax, Error> {
+        // Skip the tags used by the SMI definitions themselves, such as
+        // `[APPLICATION 1] IMPLICIT INTEGER`.
+        if self.eat(&Token::LeftBracket) {
+            while !self.eat(&Token::RightBracket) {
+                self.next()?;
+            }
+            let _ = self.eat_keyword("IMPLICIT") || self.eat_keyword("EXPLICIT");
+        }
+
+        let name = match &*self.ident()? {
+            "OCTET" => {
+                self.expect_keyword("STRING")?;
+                String::from("OCTET STRING")
+            }
+            "OBJECT" => {
+                self.expect_keyword("IDENTIFIER")?;
+                String::from("OBJECT IDENTIFIER")
+            }
+            "BITS" => return Ok(Syntax::Bits(self.named_numbers()?)),
+            "CHOICE" => {
+                self.skip_value()?;
+                return Ok(Syntax::Choice);
+            }
+            "SEQUENCE" => {
+                if self.eat_keyword("OF") {
+                    return Ok(Syntax::SequenceOf(self.ident()?));
+                }
+
+                self.expect(&Token::LeftBrace)?;
+                let mut fields = Vec::new();
+                while !self.eat(&Token::RightBrace) {
+                    fields.push((self.ident()?, self.syntax()?));
+                    if !self.eat(&Token::Comma) {
+                        self.expect(&Token::RightBrace)?;
+                        break;
+                    }
+                }
+                return Ok(Syntax::Sequence(fields));
+            }
+            name => name.to_owned(),
+        };
+
+        let refinement = match self.peek() {
+            Some(Token::LeftBrace) => Refinement::Enumeration(self.named_numbers()?),
+            Some(Token::LeftParen) => {
+                self.position += 1;
+                let refinement = if self.eat_keyword("SIZE") {
+                    self.expect(&Token::LeftParen)?;
+                    let ranges = self.ranges()?;
+                    self.expect(&Token::RightParen)?;
+                    Refinement::Size(ranges)
+                } else {
+                    Refinement::Range(self.ranges()?)
+                };
+                self.expect(&Token::RightParen)?;
+                refinement
+            }
+            _ => Refinement::None,
+        };
+
+        Ok(Syntax::Type { name, refinement })
+    }
+
+    fn named_numbers(&mut self) -> Result<Vec<NamedNumber>, Error> {
+        self.expect(&Token::LeftBrace)?;
+        let mut values = Vec::new();
+        while !self.eat(&Token::RightBrace) {
+            let name = self.ident()?;
+            self.expect(&Token::LeftParen)?;
+            let value = self.number()?;
+            self.expect(&Token::RightParen)?;
+            values.push(NamedNumber { name, value });
+            if !self.eat(&Token::Comma) {
+                self.expect(&Token::RightBrace)?;
+                break;
+            }
+        }
+        Ok(values)
+    }
+
+    fn ranges(&mut self) -> Result<Vec<Range>, Error> {
+        let mut ranges = Vec::new();
+        loop {
+            let min = self.bound()?;
+            let max = if self.eat(&Token::Range) {
+                self.bound()?
+            } else {
+                min
+            };
+            ranges.push(Range { min, max });
+
+            if !self.eat(&Token::Pipe) {
+                return Ok(ranges);
+            }
+        }
+    }
+
+    fn bound(&mut self) -> Result<i128, Error> {
+        let (digits, radix) = match self.next()? {
+            Token::Number(number) => return Ok(number),
+            Token::Ident(ident) if ident == "MIN" => return Ok(i128::MIN),
+            Token::Ident(ident) if ident == "MAX" => return Ok(i128::MAX),
+            Token::Hex(digits) => (digits, 16),
+            Token::Bin(digits) => (digits, 2),
+            token => {
+                self.position -= 1;
+                return Err(self.error(format!(
+                    "expected a range bound, found {}",
+                    token.describe()
+                )));
+            }
+        };
+
+        i128::from_str_radix(&digits, radix)
+            .map_err(|_| self.error(format!("`{digits}` is not a valid bound")))
+    }
+
+    fn oid_value(&mut self) -> Result<Vec<OidComponent>, Error> {
+        self.expect(&Token::LeftBrace)?;
+        let mut components = Vec::new();
+        while !self.eat(&Token::RightBrace) {
+            match self.next()? {
+                Token::Ident(name) => {
+                    if self.eat(&Token::LeftParen) {
+                        let arc = self.arc()?;
+                        self.expect(&Token::RightParen)?;
+                        components.push(OidComponent::NamedNumber(name, arc));
+                    } else {
+                        components.push(OidComponent::Name(name));
+                    }
+                }
+                Token::Number(_) => {
+                    self.position -= 1;
+                    components.push(OidComponent::Number(self.arc()?));
+                }
+                token => {
+                    return Err(self.error(format!(
+                        "expected an object identifier component, found {}",
+                        token.describe()
+                    )))
+                }
+            }
+        }
+
+        if components.is_empty() {
+            Err(self.error("empty object identifier value"))
+        } else {
+            Ok(components)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smi_v1() {
+        let modules = parse(
+            r#"
+            EXAMPLE-MIB DEFINITIONS ::= BEGIN
+            IMPORTS
+                enterprises, Counter FROM RFC1155-SMI
+                OBJECT-TYPE FROM RFC-1212
+                TRAP-TYPE FROM RFC-1215;
+
+            example OBJECT IDENTIFIER ::= { enterprises 99999 }
+
+            exampleFlags OBJECT-TYPE
+                SYNTAX  BITS { first(0), second(1) }
+                ACCESS  read-write
+                STATUS  mandatory
+                DEFVAL  { { first } }
+                ::= { example 1 }
+
+            exampleKey OBJECT-TYPE
+                SYNTAX  INTEGER (0..'ff'h | 1024)
+                ACCESS  not-accessible
+                STATUS  optional
+                INDEX   { IMPLIED exampleName, OCTET STRING }
+                ::= { example 2 }
+
+            exampleRestart TRAP-TYPE
+                ENTERPRISE  example
+                VARIABLES   { exampleFlags }
+                DESCRIPTION "Sent when the example restarts."
+                ::= 3
+            END
+            "#,
+        )
+        .unwrap();
+
+        let module = &modules[0];
+        assert_eq!(module.name, "EXAMPLE-MIB");
+        assert_eq!(module.imported_from("Counter"), Some("RFC1155-SMI"));
+        assert!(!module.is_smi_v2());
+
+        let NodeKind::ObjectType(flags) = &module.node("exampleFlags").unwrap().kind else {
+            panic!("expected an object type");
+        };
+        assert_eq!(
+            flags.syntax,
+            Syntax::Bits(vec![
+                NamedNumber::new("first", 0),
+                NamedNumber::new("second", 1)
+            ])
+        );
+        assert_eq!(flags.default_value.as_deref(), Some("{ first }"));
+
+        let key = module.node("exampleKey").unwrap();
+        assert_eq!(key.status, Some(Status::Optional));
+        let NodeKind::ObjectType(key) = &key.kind else {
+            panic!("expected an object type");
+        };
+        assert_eq!(
+            key.syntax,
+            Syntax::Type {
+                name: "INTEGER".into(),
+                refinement: Refinement::Range(vec![Range::new(0, 255), Range::new(1024, 1024)]),
+            }
+        );
+        assert_eq!(
+            key.index,
+            Some(Index::Index(vec![
+                IndexPart {
+                    name: "exampleName".into(),
+                    implied: true
+                },
+                IndexPart {
+                    name: "OCTET STRING".into(),
+                    implied: false
+                },
+            ]))
+        );
+
+        let restart = module.node("exampleRestart").unwrap();
+        assert_eq!(
+            restart.kind,
+            NodeKind::Trap {
+                variables: vec!["exampleFlags".into()]
+            }
+        );
+        assert_eq!(
+            restart.value,
+            [
+                OidComponent::Name("example".into()),
+                OidComponent::Number(0),
+                OidComponent::Number(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse("EXAMPLE-MIB DEFINITIONS ::= BEGIN\n  foo OBJECT-TYPE\n  ::= { 1 }\nEND")
+            .unwrap_err();
+        assert!(matches!(error, Error::Syntax { line: 2, .. }), "{error}");
+
+        let error =
+            parse("EXAMPLE-MIB DEFINITIONS ::= BEGIN\n foo OBJECT IDENTIFIER ::= { }").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: empty object identifier value");
+    }
+}
@@ -0,0 +1,320 @@
+// Generated from `IF-MIB` by rasn-mib-compiler.
+
+use alloc::{string::ToString, vec::Vec};
+use rasn::{types::*, AsnType};
+use smi::{object_type, v2::*};
+
+/// This data type is used to model an administratively
+/// assigned name of the owner of a resource.
+pub type OwnerString = OctetString;
+
+/// A unique value, greater than zero, for each interface or
+/// interface sub-layer in the managed system.
+pub type InterfaceIndex = u32;
+
+/// The MIB module to describe generic objects for network
+/// interface sub-layers.  This MIB is an updated version of
+/// MIB-II's ifTable, and incorporates the extensions defined in
+/// RFC 1229.
+pub const IF_MIB: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 31]);
+
+pub const IF_MIB_OBJECTS: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 31, 1]);
+
+pub const INTERFACES: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 2]);
+
+pub const IF_CONFORMANCE: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 31, 2]);
+
+pub const IF_GROUPS: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 31, 2, 1]);
+
+pub const IF_COMPLIANCES: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 31, 2, 2]);
+
+object_type! {
+    /// The number of network interfaces (regardless of their
+    /// current state) present on this system.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfNumber(pub i32);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 1];
+
+    /// The value of sysUpTime at the time of the last creation or
+    /// deletion of an entry in the ifTable.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfTableLastChange(pub TimeTicks);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 5];
+
+    /// A list of interface entries.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub opaque struct IfTable(pub Vec<IfEntry>);
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2];
+
+    /// An entry containing management information applicable to a
+    /// particular interface.
+    ///
+    /// Indexed by `ifIndex`.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfEntry {
+        pub if_index: IfIndex,
+        pub if_descr: IfDescr,
+        pub if_type: IfType,
+        pub if_mtu: IfMtu,
+        pub if_speed: IfSpeed,
+        pub if_phys_address: IfPhysAddress,
+        pub if_admin_status: IfAdminStatus,
+        pub if_last_change: IfLastChange,
+        pub if_in_octets: IfInOctets,
+    }
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1];
+
+    /// A unique value, greater than zero, for each interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfIndex(pub InterfaceIndex);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 1];
+
+    /// A textual string containing information about the
+    /// interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfDescr(pub OctetString);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 2];
+
+    /// The type of interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfType(pub Integer);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 3];
+
+    /// The size of the largest packet which can be sent/received
+    /// on the interface, specified in octets.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfMtu(pub i32);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 4];
+
+    /// An estimate of the interface's current bandwidth in bits
+    /// per second.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfSpeed(pub Gauge32);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 5];
+
+    /// The interface's address at its protocol sub-layer.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfPhysAddress(pub OctetString);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 6];
+
+    /// The desired state of the interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfAdminStatus(pub Integer);
+    access: ReadWrite,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 7];
+
+    impl IfAdminStatus {
+        pub const UP: u64 = 1;
+        pub const DOWN: u64 = 2;
+        pub const TESTING: u64 = 3;
+    }
+
+    /// The value of sysUpTime at the time the interface entered
+    /// its current operational state.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfLastChange(pub TimeTicks);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 9];
+
+    /// The total number of octets received on the interface,
+    /// including framing characters.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfInOctets(pub Counter32);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 2, 2, 1, 10];
+
+    /// A list of interface entries.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub opaque struct IfXTable(pub Vec<IfXEntry>);
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1];
+
+    /// An entry containing additional management information
+    /// applicable to a particular interface.
+    ///
+    /// Augments `ifEntry`.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfXEntry {
+        pub if_name: IfName,
+        pub if_hc_in_octets: IfHCInOctets,
+        pub if_link_up_down_trap_enable: IfLinkUpDownTrapEnable,
+        pub if_high_speed: IfHighSpeed,
+        pub if_promiscuous_mode: IfPromiscuousMode,
+        pub if_alias: IfAlias,
+    }
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1];
+
+    /// The textual name of the interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfName(pub OctetString);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 1];
+
+    /// The total number of octets received on the interface,
+    /// including framing characters.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfHCInOctets(pub Counter64);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 6];
+
+    /// Indicates whether linkUp/linkDown traps should be generated
+    /// for this interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfLinkUpDownTrapEnable(pub Integer);
+    access: ReadWrite,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 14];
+
+    impl IfLinkUpDownTrapEnable {
+        pub const ENABLED: u64 = 1;
+        pub const DISABLED: u64 = 2;
+    }
+
+    /// An estimate of the interface's current bandwidth in units
+    /// of 1,000,000 bits per second.
+    ///
+    /// Units: `Mb/s`.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfHighSpeed(pub Gauge32);
+    access: ReadOnly,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 15];
+
+    /// This object has a value of false(2) if this interface only
+    /// accepts packets/frames that are addressed to this station.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfPromiscuousMode(pub Integer);
+    access: ReadWrite,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 16];
+
+    impl IfPromiscuousMode {
+        pub const TRUE: u64 = 1;
+        pub const FALSE: u64 = 2;
+    }
+
+    /// This object is an 'alias' name for the interface as
+    /// specified by a network manager.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfAlias(pub OctetString);
+    access: ReadWrite,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 18];
+
+    /// This table contains an entry for each address (broadcast,
+    /// multicast, or uni-cast) for which the system will receive
+    /// packets/frames on a particular interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub opaque struct IfRcvAddressTable(pub Vec<IfRcvAddressEntry>);
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 4];
+
+    /// A list of objects identifying an address for which the
+    /// system will accept packets/frames on the particular
+    /// interface identified by the index value ifIndex.
+    ///
+    /// Indexed by `ifIndex`, `ifRcvAddressAddress`.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfRcvAddressEntry {
+        pub if_rcv_address_address: IfRcvAddressAddress,
+        pub if_rcv_address_status: IfRcvAddressStatus,
+        pub if_rcv_address_type: IfRcvAddressType,
+    }
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 4, 1];
+
+    /// An address for which the system will accept packets/frames
+    /// on this entry's interface.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfRcvAddressAddress(pub OctetString);
+    access: NotAccessible,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 4, 1, 1];
+
+    /// This object is used to create and delete rows in the
+    /// ifRcvAddressTable.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfRcvAddressStatus(pub Integer);
+    access: ReadCreate,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 4, 1, 2];
+
+    impl IfRcvAddressStatus {
+        pub const ACTIVE: u64 = 1;
+        pub const NOT_IN_SERVICE: u64 = 2;
+        pub const NOT_READY: u64 = 3;
+        pub const CREATE_AND_GO: u64 = 4;
+        pub const CREATE_AND_WAIT: u64 = 5;
+        pub const DESTROY: u64 = 6;
+    }
+
+    /// This object has the value nonVolatile(3) for those entries
+    /// in the table which are valid and will not be deleted by the
+    /// next restart of the managed system.
+    ///
+    /// Defaults to `volatile`.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfRcvAddressType(pub Integer);
+    access: ReadCreate,
+    status: Current,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 4, 1, 3];
+
+    impl IfRcvAddressType {
+        pub const OTHER: u64 = 1;
+        pub const VOLATILE: u64 = 2;
+        pub const NON_VOLATILE: u64 = 3;
+    }
+
+    /// The entity which configured this interface stack.
+    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    pub struct IfStackOwner(pub OwnerString);
+    access: ReadOnly,
+    status: Deprecated,
+    value = [1, 3, 6, 1, 2, 1, 31, 1, 7];
+}
+
+/// A linkDown trap signifies that the SNMP entity, acting in
+/// an agent role, has detected that the ifOperStatus object for
+/// one of its communication links is about to enter the down
+/// state from some other state.
+///
+/// Objects: `ifIndex`, `ifAdminStatus`.
+pub const LINK_DOWN: &Oid = Oid::const_new(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 3]);
+
+/// A linkUp trap signifies that the SNMP entity, acting in an
+/// agent role, has detected that the ifOperStatus object for
+/// one of its communication links left the down state and
+/// transitioned into some other state.
+///
+/// Objects: `ifIndex`, `ifAdminStatus`.
+pub const LINK_UP: &Oid = Oid::const_new(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 4]);
@@ -0,0 +1,103 @@
+extern crate alloc;
+
+use rasn_mib_compiler::{Compiler, Error};
+
+#[allow(dead_code)]
+mod if_mib {
+    include!("data/if_mib.rs");
+}
+
+const IF_MIB: &str = include_str!("data/IF-MIB.txt");
+
+#[test]
+fn generates_if_mib() {
+    let mut compiler = Compiler::new();
+    assert_eq!(compiler.add_source(IF_MIB).unwrap(), ["IF-MIB"]);
+
+    pretty_assertions::assert_eq!(
+        compiler.generate("IF-MIB").unwrap(),
+        include_str!("data/if_mib.rs")
+    );
+}
+
+#[test]
+fn generated_object_types() {
+    use if_mib::*;
+    use rasn::types::{OctetString, Oid};
+    use smi::{
+        v2::{ApplicationSyntax, Counter64, ObjectSyntax},
+        Access, ObjectType, Status,
+    };
+
+    assert_eq!(IF_MIB, Oid::const_new(&[1, 3, 6, 1, 2, 1, 31]));
+    assert_eq!(
+        IfIndex::VALUE,
+        Oid::const_new(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1])
+    );
+    assert_eq!(IfRcvAddressStatus::ACCESS, Access::ReadCreate);
+    assert_eq!(IfStackOwner::STATUS, Status::Deprecated);
+    assert_eq!(IfAdminStatus::TESTING, 3);
+
+    let entry = IfXEntry {
+        if_name: IfName(OctetString::from_static(b"eth0")),
+        if_hc_in_octets: IfHCInOctets(Counter64(1 << 40)),
+        if_link_up_down_trap_enable: IfLinkUpDownTrapEnable(IfLinkUpDownTrapEnable::ENABLED.into()),
+        if_high_speed: IfHighSpeed(smi::v1::Gauge(1000)),
+        if_promiscuous_mode: IfPromiscuousMode(IfPromiscuousMode::FALSE.into()),
+        if_alias: IfAlias(OctetString::from_static(b"uplink")),
+    };
+
+    let syntax = entry
+        .if_hc_in_octets
+        .clone()
+        .into_object_syntax(rasn::Codec::Ber)
+        .unwrap();
+    assert_eq!(
+        syntax,
+        ObjectSyntax::ApplicationWide(ApplicationSyntax::BigCounter(Counter64(1 << 40)))
+    );
+    assert_eq!(
+        IfHCInOctets::from(Counter64::try_from(syntax).ok().unwrap()),
+        entry.if_hc_in_octets
+    );
+}
+
+#[test]
+fn loads_imports_from_search_paths() {
+    let directory = std::env::temp_dir().join("rasn-mib-compiler-search-path");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("IF-MIB.txt"), IF_MIB).unwrap();
+
+    let mut compiler = Compiler::new();
+    compiler.add_search_path(&directory);
+    compiler
+        .add_source(
+            r#"
+            EXAMPLE-MIB DEFINITIONS ::= BEGIN
+            IMPORTS
+                OBJECT-TYPE, enterprises FROM SNMPv2-SMI
+                InterfaceIndex FROM IF-MIB;
+
+            example OBJECT IDENTIFIER ::= { enterprises 99999 }
+
+            exampleIfIndex OBJECT-TYPE
+                SYNTAX      InterfaceIndex
+                MAX-ACCESS  read-only
+                STATUS      current
+                DESCRIPTION "The interface the example runs on."
+                ::= { example 1 }
+            END
+            "#,
+        )
+        .unwrap();
+
+    let code = compiler.generate("EXAMPLE-MIB").unwrap();
+    assert!(code.contains("pub const EXAMPLE: &Oid = Oid::const_new(&[1, 3, 6, 1, 4, 1, 99999]);"));
+    assert!(code.contains("pub struct ExampleIfIndex(pub u32);"));
+    assert!(compiler.module("IF-MIB").is_some());
+
+    assert!(matches!(
+        Compiler::new().generate("MISSING-MIB"),
+        Err(Error::UnknownModule { name }) if name == "MISSING-MIB"
+    ));
+}
@@ -0,0 +1,194 @@
+//! Builders for signed OCSP responses.
+//!
+//! Signing uses the same [`Signer`] trait as the certificate builders in
+//! [`rasn_pkix::builder`].
+
+use alloc::vec::Vec;
+
+use rasn::{error::EncodeError, prelude::*};
+use rasn_pkix::{
+    builder::{sign_der, BuildError, ExtensionsBuilder, Signer},
+    extensions::KnownExtension,
+    Certificate, Extension,
+};
+
+use crate::*;
+
+/// A builder for signed [BasicOcspResponse]s.
+#[derive(Clone, Debug)]
+pub struct BasicOcspResponseBuilder {
+    responder_id: ResponderId,
+    produced_at: GeneralizedTime,
+    responses: Vec<SingleResponse>,
+    extensions: ExtensionsBuilder,
+    certs: Vec<Certificate>,
+}
+
+impl BasicOcspResponseBuilder {
+    /// Creates a builder for a response from `responder_id`, produced at
+    /// `produced_at`.
+    pub fn new(responder_id: ResponderId, produced_at: GeneralizedTime) -> Self {
+        Self {
+            responder_id,
+            produced_at,
+            responses: Vec::new(),
+            extensions: ExtensionsBuilder::default(),
+            certs: Vec::new(),
+        }
+    }
+
+    /// Adds the status of the certificate identified by `cert_id`, valid
+    /// from `this_update` until `next_update`.
+    pub fn response(
+        self,
+        cert_id: CertId,
+        cert_status: CertStatus,
+        this_update: GeneralizedTime,
+        next_update: Option<GeneralizedTime>,
+    ) -> Self {
+        self.single_response(SingleResponse {
+            cert_id,
+            cert_status,
+            this_update,
+            next_update,
+            single_extensions: None,
+        })
+    }
+
+    /// Adds a single response.
+    pub fn single_response(mut self, response: SingleResponse) -> Self {
+        self.responses.push(response);
+        self
+    }
+
+    /// Adds a typed response extension, replacing any with the same OID.
+    pub fn extension(mut self, extension: KnownExtension, critical: bool) -> Self {
+        self.extensions.push(extension, critical);
+        self
+    }
+
+    /// Adds an already encoded response extension, replacing any with the
+    /// same OID.
+    pub fn custom_extension(mut self, extension: Extension) -> Self {
+        self.extensions.push_custom(extension);
+        self
+    }
+
+    /// Adds a certificate that helps the client verify the signature.
+    pub fn certificate(mut self, certificate: Certificate) -> Self {
+        self.certs.push(certificate);
+        self
+    }
+
+    /// Returns the response data to be signed.
+    pub fn tbs_response_data(&self) -> Result<ResponseData, EncodeError> {
+        Ok(ResponseData {
+            version: Version::default(),
+            responder_id: self.responder_id.clone(),
+            produced_at: self.produced_at,
+            responses: self.responses.clone(),
+            response_extensions: self.extensions.build()?,
+        })
+    }
+
+    /// Builds the response and signs it with `signer`.
+    pub fn sign<S: Signer>(&self, signer: &S) -> Result<BasicOcspResponse, BuildError<S::Error>> {
+        let tbs_response_data = self.tbs_response_data()?;
+        let signature = sign_der(&tbs_response_data, signer)?;
+
+        Ok(BasicOcspResponse {
+            tbs_response_data,
+            signature_algorithm: signer.algorithm(),
+            signature,
+            certs: (!self.certs.is_empty()).then(|| self.certs.clone()),
+        })
+    }
+}
+
+impl OcspResponse {
+    /// Creates a successful response containing `response`.
+    pub fn successful(response: &BasicOcspResponse) -> Result<Self, EncodeError> {
+        Ok(Self {
+            status: OcspResponseStatus::Successful,
+            bytes: Some(ResponseBytes {
+                r#type: BASIC_RESPONSE.into(),
+                response: rasn::der::encode(response)?.into(),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use p256::ecdsa::{
+        signature::{Signer as _, Verifier as _},
+        Signature, SigningKey,
+    };
+    use rasn_pkix::AlgorithmIdentifier;
+
+    struct EcdsaSigner(SigningKey);
+
+    impl Signer for EcdsaSigner {
+        type Error = p256::ecdsa::Error;
+
+        fn algorithm(&self) -> AlgorithmIdentifier {
+            AlgorithmIdentifier {
+                algorithm: Oid::ISO_MEMBER_BODY_US_ANSI_X962_SIGNATURES_ECDSA_WITH_SHA256.into(),
+                parameters: None,
+            }
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let signature: Signature = self.0.try_sign(message)?;
+            Ok(signature.to_der().as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn basic_response() {
+        let time: GeneralizedTime = chrono::Utc
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap()
+            .into();
+        let cert_id = CertId {
+            hash_algorithm: AlgorithmIdentifier {
+                algorithm: Oid::ISO_IDENTIFIED_ORGANISATION_OIW_SECSIG_ALGORITHM_SHA1.into(),
+                parameters: None,
+            },
+            issuer_name_hash: OctetString::from_static(&[1; 20]),
+            issuer_key_hash: OctetString::from_static(&[2; 20]),
+            serial_number: 5.into(),
+        };
+
+        let signer = EcdsaSigner(SigningKey::from_slice(&[7; 32]).unwrap());
+        let basic = BasicOcspResponseBuilder::new(
+            ResponderId::ByKey(OctetString::from_static(&[2; 20])),
+            time,
+        )
+        .response(cert_id, CertStatus::Good, time, None)
+        .sign(&signer)
+        .unwrap();
+
+        let message = rasn::der::encode(&basic.tbs_response_data).unwrap();
+        let signature = Signature::from_der(basic.signature.as_raw_slice()).unwrap();
+        signer
+            .0
+            .verifying_key()
+            .verify(&message, &signature)
+            .unwrap();
+        assert_eq!(signer.algorithm(), basic.signature_algorithm);
+        assert!(basic.certs.is_none());
+
+        let response = OcspResponse::successful(&basic).unwrap();
+        let encoded = rasn::der::encode(&response).unwrap();
+        let decoded = rasn::der::decode::<OcspResponse>(&encoded).unwrap();
+        let bytes = decoded.bytes.unwrap();
+        assert_eq!(*BASIC_RESPONSE, bytes.r#type);
+        assert_eq!(
+            basic,
+            rasn::der::decode::<BasicOcspResponse>(&bytes.response).unwrap()
+        );
+    }
+}
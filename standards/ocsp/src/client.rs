@@ -0,0 +1,342 @@
+//! Helpers for OCSP clients.
+//!
+//! These cover the steps of [RFC 6960] that a client otherwise performs by
+//! hand: building a [`CertId`] for a certificate and its issuer, attaching a
+//! nonce to a request, extracting the [`BasicOcspResponse`] from an
+//! [`OcspResponse`], finding the [`SingleResponse`] for a certificate, and
+//! checking that the response is fresh.
+//!
+//! Hashing is delegated to a [`Digester`] supplied by the caller, and the
+//! current time to a [`Clock`], so this crate doesn't depend on any particular
+//! cryptography or time library. Verifying the responder's signature is left
+//! to the caller.
+//!
+//! [RFC 6960]: https://datatracker.ietf.org/doc/html/rfc6960
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    prelude::*,
+};
+use rasn_pkix::{Certificate, Extension};
+
+use crate::*;
+
+/// Computes digests for [`CertId`]s.
+pub trait Digester {
+    /// Returns the digest of `data` using `algorithm`, or `None` if the
+    /// algorithm isn't supported.
+    fn digest(&self, algorithm: &AlgorithmIdentifier, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// A source of the current time, used to check the freshness of responses.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> GeneralizedTime;
+}
+
+/// A fixed point in time is a clock that never advances.
+impl Clock for GeneralizedTime {
+    fn now(&self) -> GeneralizedTime {
+        *self
+    }
+}
+
+/// An error returned when building a [`CertId`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CertIdError {
+    /// The digester doesn't support the hash algorithm.
+    UnsupportedDigest(ObjectIdentifier),
+    /// The certificate's issuer isn't the subject of the issuer certificate.
+    IssuerMismatch,
+    /// The issuer's name couldn't be encoded.
+    Encode(EncodeError),
+}
+
+impl core::fmt::Display for CertIdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedDigest(oid) => write!(f, "unsupported digest algorithm {:?}", &**oid),
+            Self::IssuerMismatch => write!(f, "certificate wasn't issued by the issuer"),
+            Self::Encode(error) => write!(f, "failed to encode issuer name: {error}"),
+        }
+    }
+}
+
+impl From<EncodeError> for CertIdError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+/// An error found when processing an [`OcspResponse`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ResponseError {
+    /// The responder didn't process the request successfully.
+    Unsuccessful(OcspResponseStatus),
+    /// A successful response didn't contain any response bytes.
+    MissingResponseBytes,
+    /// The response isn't a basic OCSP response.
+    UnsupportedResponseType(ObjectIdentifier),
+    /// The response, or one of its extensions, couldn't be decoded.
+    Decode(DecodeError),
+    /// The request contained a nonce, but the response didn't.
+    MissingNonce,
+    /// The response's nonce doesn't match the request's.
+    NonceMismatch,
+    /// The response's `this_update` is in the future.
+    NotYetValid,
+    /// The response's `next_update` is in the past.
+    Expired,
+}
+
+impl core::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsuccessful(status) => write!(f, "unsuccessful response: {status:?}"),
+            Self::MissingResponseBytes => write!(f, "response bytes missing"),
+            Self::UnsupportedResponseType(oid) => {
+                write!(f, "unsupported response type {:?}", &**oid)
+            }
+            Self::Decode(error) => write!(f, "failed to decode response: {error}"),
+            Self::MissingNonce => write!(f, "response nonce missing"),
+            Self::NonceMismatch => write!(f, "response nonce doesn't match request"),
+            Self::NotYetValid => write!(f, "response isn't valid yet"),
+            Self::Expired => write!(f, "response has expired"),
+        }
+    }
+}
+
+impl From<DecodeError> for ResponseError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+impl CertId {
+    /// Creates the identifier of `certificate`, issued by `issuer`, hashing
+    /// the issuer's name and key with `hash_algorithm`.
+    pub fn new<D: Digester>(
+        certificate: &Certificate,
+        issuer: &Certificate,
+        hash_algorithm: AlgorithmIdentifier,
+        digester: &D,
+    ) -> Result<Self, CertIdError> {
+        if certificate.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+            return Err(CertIdError::IssuerMismatch);
+        }
+
+        let digest = |data: &[u8]| {
+            digester
+                .digest(&hash_algorithm, data)
+                .map(OctetString::from)
+                .ok_or_else(|| CertIdError::UnsupportedDigest(hash_algorithm.algorithm.clone()))
+        };
+        let issuer_name_hash = digest(&rasn::der::encode(&certificate.tbs_certificate.issuer)?)?;
+        let issuer_key_hash = digest(
+            issuer
+                .tbs_certificate
+                .subject_public_key_info
+                .subject_public_key
+                .as_raw_slice(),
+        )?;
+
+        Ok(Self {
+            hash_algorithm,
+            issuer_name_hash,
+            issuer_key_hash,
+            serial_number: certificate.tbs_certificate.serial_number.clone(),
+        })
+    }
+
+    /// Returns whether both identifiers refer to the same certificate. Absent
+    /// and `NULL` hash algorithm parameters are treated as equal.
+    pub fn matches(&self, other: &CertId) -> bool {
+        let null_parameters = |parameters: &Option<Any>| match parameters {
+            Some(any) => any.as_bytes() == [0x05, 0x00],
+            None => true,
+        };
+        let same_parameters = self.hash_algorithm.parameters == other.hash_algorithm.parameters
+            || (null_parameters(&self.hash_algorithm.parameters)
+                && null_parameters(&other.hash_algorithm.parameters));
+
+        self.serial_number == other.serial_number
+            && self.hash_algorithm.algorithm == other.hash_algorithm.algorithm
+            && same_parameters
+            && self.issuer_name_hash == other.issuer_name_hash
+            && self.issuer_key_hash == other.issuer_key_hash
+    }
+
+    /// Returns whether this identifies `certificate`, issued by `issuer`,
+    /// using this identifier's hash algorithm.
+    pub fn matches_certificate<D: Digester>(
+        &self,
+        certificate: &Certificate,
+        issuer: &Certificate,
+        digester: &D,
+    ) -> bool {
+        self.serial_number == certificate.tbs_certificate.serial_number
+            && CertId::new(certificate, issuer, self.hash_algorithm.clone(), digester)
+                .is_ok_and(|cert_id| self.matches(&cert_id))
+    }
+}
+
+/// Creates a nonce extension containing `nonce`, for a request or for
+/// echoing in a response.
+pub fn nonce_extension(nonce: &[u8]) -> Result<Extension, EncodeError> {
+    Ok(Extension {
+        extn_id: NONCE.into(),
+        critical: false,
+        extn_value: rasn::der::encode(&Nonce::copy_from_slice(nonce))?.into(),
+    })
+}
+
+/// Returns the nonce in `extensions`, if present.
+fn find_nonce(extensions: Option<&Extensions>) -> Result<Option<Nonce>, DecodeError> {
+    extensions
+        .and_then(|extensions| extensions.get_as::<Nonce>(NONCE))
+        .transpose()
+}
+
+impl TbsRequest {
+    /// Creates an unsigned request for the status of each certificate in
+    /// `cert_ids`.
+    pub fn new(cert_ids: impl IntoIterator<Item = CertId>) -> Self {
+        Self {
+            version: Version::default(),
+            requestor_name: None,
+            request_list: cert_ids
+                .into_iter()
+                .map(|req_cert| Request {
+                    req_cert,
+                    single_request_extensions: None,
+                })
+                .collect(),
+            request_extensions: None,
+        }
+    }
+
+    /// Sets the request's nonce, replacing any existing nonce.
+    pub fn set_nonce(&mut self, nonce: &[u8]) -> Result<(), EncodeError> {
+        let extension = nonce_extension(nonce)?;
+        let extensions = self
+            .request_extensions
+            .get_or_insert_with(|| Extensions::from(Vec::new()));
+        match extensions.iter_mut().find(|e| e.extn_id == *NONCE) {
+            Some(existing) => *existing = extension,
+            None => extensions.push(extension),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the request's nonce, if present.
+    pub fn nonce(&self) -> Result<Option<Nonce>, DecodeError> {
+        find_nonce(self.request_extensions.as_ref())
+    }
+}
+
+impl OcspRequest {
+    /// Creates an unsigned request from `tbs_request`.
+    pub fn unsigned(tbs_request: TbsRequest) -> Self {
+        Self {
+            tbs_request,
+            optional_signature: None,
+        }
+    }
+}
+
+impl OcspResponse {
+    /// Returns the basic response contained in a successful response.
+    pub fn basic(&self) -> Result<BasicOcspResponse, ResponseError> {
+        if self.status != OcspResponseStatus::Successful {
+            return Err(ResponseError::Unsuccessful(self.status));
+        }
+
+        self.bytes
+            .as_ref()
+            .ok_or(ResponseError::MissingResponseBytes)?
+            .basic()
+    }
+}
+
+impl ResponseBytes {
+    /// Decodes the response as a [BasicOcspResponse].
+    pub fn basic(&self) -> Result<BasicOcspResponse, ResponseError> {
+        if self.r#type != *BASIC_RESPONSE {
+            return Err(ResponseError::UnsupportedResponseType(self.r#type.clone()));
+        }
+
+        Ok(rasn::der::decode(&self.response)?)
+    }
+}
+
+impl ResponseData {
+    /// Returns the response's nonce, if present.
+    pub fn nonce(&self) -> Result<Option<Nonce>, DecodeError> {
+        find_nonce(self.response_extensions.as_ref())
+    }
+
+    /// Checks that the response echoes the nonce of `request`. Succeeds
+    /// without checking if the request has no nonce.
+    pub fn check_nonce(&self, request: &TbsRequest) -> Result<(), ResponseError> {
+        let Some(expected) = request.nonce()? else {
+            return Ok(());
+        };
+
+        match self.nonce()? {
+            Some(nonce) if nonce == expected => Ok(()),
+            Some(_) => Err(ResponseError::NonceMismatch),
+            None => Err(ResponseError::MissingNonce),
+        }
+    }
+
+    /// Returns the response for the certificate identified by `cert_id`.
+    pub fn find(&self, cert_id: &CertId) -> Option<&SingleResponse> {
+        self.responses
+            .iter()
+            .find(|response| response.cert_id.matches(cert_id))
+    }
+
+    /// Returns the response for `certificate`, issued by `issuer`, whatever
+    /// hash algorithm the responder used to identify it.
+    pub fn find_certificate<D: Digester>(
+        &self,
+        certificate: &Certificate,
+        issuer: &Certificate,
+        digester: &D,
+    ) -> Option<&SingleResponse> {
+        self.responses.iter().find(|response| {
+            response
+                .cert_id
+                .matches_certificate(certificate, issuer, digester)
+        })
+    }
+}
+
+impl SingleResponse {
+    /// Checks that the time given by `clock` is within the response's
+    /// validity interval, allowing for `skew` between the clocks of the
+    /// client and the responder. A response without `next_update` never
+    /// expires.
+    pub fn check_freshness<C: Clock>(
+        &self,
+        clock: &C,
+        skew: Duration,
+    ) -> Result<(), ResponseError> {
+        let now = clock.now();
+
+        if self.this_update > now + skew {
+            return Err(ResponseError::NotYetValid);
+        }
+
+        match self.next_update {
+            Some(next_update) if next_update < now - skew => Err(ResponseError::Expired),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,6 +1,11 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+extern crate alloc;
+
+pub mod builder;
+pub mod client;
+
 use rasn::prelude::*;
 
 use rasn_pkix::{
@@ -15,6 +20,14 @@ pub type UnknownInfo = ();
 pub type ArchiveCutoff = GeneralizedTime;
 pub type AcceptableResponses = SequenceOf<ObjectIdentifier>;
 
+/// OID of the basic OCSP response type, [BasicOcspResponse].
+pub const BASIC_RESPONSE: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_OCSP_BASIC;
+
+/// OID of the nonce extension, whose value is a [Nonce].
+pub const NONCE: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_AD_OCSP_NONCE;
+
 /// The (optionally signed) OCSP request.
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OcspRequest {
@@ -0,0 +1,182 @@
+//! Tests of the client helpers against a request and response produced by
+//! OpenSSL's `ocsp` command, with a nonce and a SHA-1 certificate ID.
+
+use chrono::TimeZone;
+use core::time::Duration;
+use rasn::prelude::*;
+use rasn_ocsp::{client::*, *};
+use rasn_pkix::{AlgorithmIdentifier, Certificate};
+use sha1::Digest;
+
+const CA: &[u8] = include_bytes!("data/ca.crt.der");
+const LEAF: &[u8] = include_bytes!("data/leaf.crt.der");
+const REQUEST: &[u8] = include_bytes!("data/request.der");
+const RESPONSE: &[u8] = include_bytes!("data/response.der");
+
+const SHA1: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_OIW_SECSIG_ALGORITHM_SHA1;
+
+struct Sha1Digester;
+
+impl Digester for Sha1Digester {
+    fn digest(&self, algorithm: &AlgorithmIdentifier, data: &[u8]) -> Option<Vec<u8>> {
+        (algorithm.algorithm == *SHA1).then(|| sha1::Sha1::digest(data).to_vec())
+    }
+}
+
+fn sha1() -> AlgorithmIdentifier {
+    AlgorithmIdentifier {
+        algorithm: SHA1.into(),
+        parameters: None,
+    }
+}
+
+fn certificates() -> (Certificate, Certificate) {
+    (
+        rasn::der::decode(LEAF).unwrap(),
+        rasn::der::decode(CA).unwrap(),
+    )
+}
+
+fn time(day: u32, hour: u32) -> GeneralizedTime {
+    chrono::Utc
+        .with_ymd_and_hms(2026, 10, day, hour, 0, 0)
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn cert_id() {
+    let (leaf, ca) = certificates();
+    let request = rasn::der::decode::<OcspRequest>(REQUEST).unwrap();
+    let expected = &request.tbs_request.request_list[0].req_cert;
+
+    let cert_id = CertId::new(&leaf, &ca, sha1(), &Sha1Digester).unwrap();
+    // OpenSSL encodes the SHA-1 parameters as NULL.
+    assert_ne!(expected.hash_algorithm, cert_id.hash_algorithm);
+    assert!(cert_id.matches(expected));
+    assert_eq!(expected.issuer_name_hash, cert_id.issuer_name_hash);
+    assert_eq!(expected.issuer_key_hash, cert_id.issuer_key_hash);
+    assert!(expected.matches_certificate(&leaf, &ca, &Sha1Digester));
+    assert!(!expected.matches_certificate(&ca, &ca, &Sha1Digester));
+
+    assert!(matches!(
+        CertId::new(&ca, &leaf, sha1(), &Sha1Digester),
+        Err(CertIdError::IssuerMismatch)
+    ));
+    let sha256 = AlgorithmIdentifier {
+        algorithm:
+            Oid::JOINT_ISO_ITU_T_COUNTRY_US_ORGANIZATION_GOV_CSOR_NIST_ALGORITHMS_HASH_SHA256.into(),
+        parameters: None,
+    };
+    assert!(matches!(
+        CertId::new(&leaf, &ca, sha256, &Sha1Digester),
+        Err(CertIdError::UnsupportedDigest(_))
+    ));
+}
+
+#[test]
+fn response() {
+    let (leaf, ca) = certificates();
+    let request = rasn::der::decode::<OcspRequest>(REQUEST).unwrap();
+    let response = rasn::der::decode::<OcspResponse>(RESPONSE).unwrap();
+
+    let basic = response.basic().unwrap();
+    let data = &basic.tbs_response_data;
+    assert_eq!(request.tbs_request.nonce().unwrap(), data.nonce().unwrap());
+    data.check_nonce(&request.tbs_request).unwrap();
+
+    let cert_id = CertId::new(&leaf, &ca, sha1(), &Sha1Digester).unwrap();
+    let single = data.find(&cert_id).unwrap();
+    assert_eq!(CertStatus::Good, single.cert_status);
+    assert_eq!(
+        Some(single),
+        data.find_certificate(&leaf, &ca, &Sha1Digester)
+    );
+    assert_eq!(None, data.find_certificate(&ca, &ca, &Sha1Digester));
+
+    // Valid from 2026-10-18T22:49:52Z until 2026-10-25T22:49:52Z.
+    single
+        .check_freshness(&time(20, 0), Duration::ZERO)
+        .unwrap();
+    assert!(matches!(
+        single.check_freshness(&time(18, 22), Duration::ZERO),
+        Err(ResponseError::NotYetValid)
+    ));
+    single
+        .check_freshness(&time(18, 22), Duration::from_secs(3600))
+        .unwrap();
+    assert!(matches!(
+        single.check_freshness(&time(25, 23), Duration::ZERO),
+        Err(ResponseError::Expired)
+    ));
+    single
+        .check_freshness(&time(25, 23), Duration::from_secs(3600))
+        .unwrap();
+}
+
+#[test]
+fn nonce() {
+    let (leaf, ca) = certificates();
+    let cert_id = CertId::new(&leaf, &ca, sha1(), &Sha1Digester).unwrap();
+    let mut tbs_request = TbsRequest::new([cert_id]);
+    assert_eq!(None, tbs_request.nonce().unwrap());
+
+    tbs_request.set_nonce(&[1; 16]).unwrap();
+    tbs_request.set_nonce(&[2; 16]).unwrap();
+    let request = OcspRequest::unsigned(tbs_request);
+    let request: OcspRequest = rasn::der::decode(&rasn::der::encode(&request).unwrap()).unwrap();
+    let extensions = request.tbs_request.request_extensions.as_ref().unwrap();
+    assert_eq!(1, extensions.len());
+    assert_eq!(
+        Some(OctetString::from_static(&[2; 16])),
+        request.tbs_request.nonce().unwrap()
+    );
+
+    // The fixture's response echoes a different nonce.
+    let response = rasn::der::decode::<OcspResponse>(RESPONSE).unwrap();
+    let mut data = response.basic().unwrap().tbs_response_data;
+    assert!(matches!(
+        data.check_nonce(&request.tbs_request),
+        Err(ResponseError::NonceMismatch)
+    ));
+
+    data.response_extensions.as_mut().unwrap()[0] = nonce_extension(&[2; 16]).unwrap();
+    data.check_nonce(&request.tbs_request).unwrap();
+
+    data.response_extensions = None;
+    assert!(matches!(
+        data.check_nonce(&request.tbs_request),
+        Err(ResponseError::MissingNonce)
+    ));
+    data.check_nonce(&TbsRequest::new([])).unwrap();
+}
+
+#[test]
+fn unsuccessful() {
+    let response = OcspResponse {
+        status: OcspResponseStatus::TryLater,
+        bytes: None,
+    };
+    assert!(matches!(
+        response.basic(),
+        Err(ResponseError::Unsuccessful(OcspResponseStatus::TryLater))
+    ));
+
+    let response = OcspResponse {
+        status: OcspResponseStatus::Successful,
+        bytes: None,
+    };
+    assert!(matches!(
+        response.basic(),
+        Err(ResponseError::MissingResponseBytes)
+    ));
+
+    let bytes = ResponseBytes {
+        r#type: NONCE.into(),
+        response: OctetString::from_static(&[]),
+    };
+    assert!(matches!(
+        bytes.basic(),
+        Err(ResponseError::UnsupportedResponseType(_))
+    ));
+}
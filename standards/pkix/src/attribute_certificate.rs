@@ -36,6 +36,10 @@ pub struct AttributeCertificate {
     pub signature_value: BitString,
 }
 
+impl rasn::pem::PemLabel for AttributeCertificate {
+    const LABEL: &'static str = "ATTRIBUTE CERTIFICATE";
+}
+
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AttributeCertificateInfo {
     pub version: AttributeCertificateVersion,
@@ -0,0 +1,562 @@
+//! # Builders
+//!
+//! Builders for creating signed [`Certificate`]s and [`CertificateList`]s.
+//! The builders choose the encoding of times and the version number required
+//! by RFC 5280, encode typed extensions, and DER-encode the "to be signed"
+//! structure for a caller-supplied [`Signer`].
+//!
+//! ```
+//! use rasn_pkix::{builder::*, extensions::KnownExtension, *};
+//! # use rasn::types::*;
+//! # struct NullSigner;
+//! # impl Signer for NullSigner {
+//! #     type Error = core::convert::Infallible;
+//! #     fn algorithm(&self) -> AlgorithmIdentifier {
+//! #         AlgorithmIdentifier {
+//! #             algorithm: Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA1_RSA.into(),
+//! #             parameters: None,
+//! #         }
+//! #     }
+//! #     fn sign(&self, _: &[u8]) -> Result<Vec<u8>, Self::Error> {
+//! #         Ok(Vec::new())
+//! #     }
+//! # }
+//! # let (signer, public_key) = (NullSigner, SubjectPublicKeyInfo {
+//! #     algorithm: NullSigner.algorithm(),
+//! #     subject_public_key: BitString::new(),
+//! # });
+//! # use chrono::TimeZone;
+//! # let not_before = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().into();
+//! # let not_after = chrono::Utc.with_ymd_and_hms(2034, 1, 1, 0, 0, 0).unwrap().into();
+//!
+//! let certificate = CertificateBuilder::new(
+//!     1.into(),
+//!     "CN=Example CA".parse().unwrap(),
+//!     public_key,
+//!     Validity::new(not_before, not_after),
+//! )
+//! .extension(
+//!     KnownExtension::BasicConstraints(BasicConstraints {
+//!         ca: true,
+//!         path_len_constraint: None,
+//!     }),
+//!     true,
+//! )
+//! .sign(&signer)
+//! .unwrap();
+//!
+//! assert_eq!(Version::V3, certificate.tbs_certificate.version);
+//! ```
+
+use alloc::vec::Vec;
+
+use rasn::{error::EncodeError, types::*};
+
+use crate::{extensions::KnownExtension, *};
+
+/// Produces signatures for the builders in this module.
+pub trait Signer {
+    /// The error returned when signing fails.
+    type Error;
+
+    /// Returns the algorithm identifier of the signatures produced by this
+    /// signer.
+    fn algorithm(&self) -> AlgorithmIdentifier;
+
+    /// Signs `message`, the DER encoding of the structure being signed.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// An error returned when building a signed structure.
+#[derive(Debug)]
+pub enum BuildError<E> {
+    /// The structure or one of its extensions couldn't be encoded.
+    Encode(EncodeError),
+    /// The signer failed.
+    Sign(E),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for BuildError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Encode(error) => write!(f, "{error}"),
+            Self::Sign(error) => write!(f, "signing failed: {error}"),
+        }
+    }
+}
+
+impl<E> From<EncodeError> for BuildError<E> {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+/// Collects typed and custom extensions, encoding them when a structure is
+/// built. Extensions are encoded in the order they were added.
+#[derive(Clone, Debug, Default)]
+pub struct ExtensionsBuilder {
+    extensions: Vec<PendingExtension>,
+}
+
+/// An extension added to an [`ExtensionsBuilder`].
+#[derive(Clone, Debug)]
+enum PendingExtension {
+    Known(KnownExtension, bool),
+    Custom(Extension),
+}
+
+impl PendingExtension {
+    fn oid(&self) -> &Oid {
+        match self {
+            Self::Known(extension, _) => extension.oid(),
+            Self::Custom(extension) => &extension.extn_id,
+        }
+    }
+}
+
+impl ExtensionsBuilder {
+    /// Adds a typed extension, replacing any existing extension with the same
+    /// OID.
+    pub fn push(&mut self, extension: KnownExtension, critical: bool) {
+        self.insert(PendingExtension::Known(extension, critical));
+    }
+
+    /// Adds an already encoded extension, replacing any existing extension
+    /// with the same OID.
+    pub fn push_custom(&mut self, extension: Extension) {
+        self.insert(PendingExtension::Custom(extension));
+    }
+
+    fn insert(&mut self, extension: PendingExtension) {
+        self.extensions
+            .retain(|existing| existing.oid() != extension.oid());
+        self.extensions.push(extension);
+    }
+
+    /// Returns whether no extensions have been added.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    /// Encodes the extensions, returning `None` if there are none.
+    pub fn build(&self) -> Result<Option<Extensions>, EncodeError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        self.extensions
+            .iter()
+            .map(|extension| match extension {
+                PendingExtension::Known(extension, critical) => {
+                    Extension::from_known(extension, *critical)
+                }
+                PendingExtension::Custom(extension) => Ok(extension.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|extensions| Some(Extensions::from(extensions)))
+    }
+}
+
+/// Signs the DER encoding of `value` with `signer`, for building other
+/// signed structures.
+pub fn sign_der<T: Encode, S: Signer>(
+    value: &T,
+    signer: &S,
+) -> Result<BitString, BuildError<S::Error>> {
+    let message = rasn::der::encode(value)?;
+    signer
+        .sign(&message)
+        .map(BitString::from_vec)
+        .map_err(BuildError::Sign)
+}
+
+/// A builder for signed [`Certificate`]s.
+///
+/// The issuer defaults to the subject, producing a self-signed certificate.
+/// The version is chosen from the fields present: v3 when there are
+/// extensions, v2 when there are unique identifiers, and v1 otherwise.
+#[derive(Clone, Debug)]
+pub struct CertificateBuilder {
+    serial_number: CertificateSerialNumber,
+    subject: Name,
+    subject_public_key_info: SubjectPublicKeyInfo,
+    validity: Validity,
+    issuer: Option<Name>,
+    issuer_unique_id: Option<UniqueIdentifier>,
+    subject_unique_id: Option<UniqueIdentifier>,
+    extensions: ExtensionsBuilder,
+}
+
+impl CertificateBuilder {
+    /// Creates a builder for a certificate binding `subject_public_key_info`
+    /// to `subject`.
+    pub fn new(
+        serial_number: CertificateSerialNumber,
+        subject: Name,
+        subject_public_key_info: SubjectPublicKeyInfo,
+        validity: Validity,
+    ) -> Self {
+        Self {
+            serial_number,
+            subject,
+            subject_public_key_info,
+            validity,
+            issuer: None,
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: ExtensionsBuilder::default(),
+        }
+    }
+
+    /// Sets the name of the issuer.
+    pub fn issuer(mut self, issuer: Name) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    /// Sets the issuer's unique identifier.
+    pub fn issuer_unique_id(mut self, id: UniqueIdentifier) -> Self {
+        self.issuer_unique_id = Some(id);
+        self
+    }
+
+    /// Sets the subject's unique identifier.
+    pub fn subject_unique_id(mut self, id: UniqueIdentifier) -> Self {
+        self.subject_unique_id = Some(id);
+        self
+    }
+
+    /// Adds a typed extension, replacing any with the same OID.
+    pub fn extension(mut self, extension: KnownExtension, critical: bool) -> Self {
+        self.extensions.push(extension, critical);
+        self
+    }
+
+    /// Adds an already encoded extension, replacing any with the same OID.
+    pub fn custom_extension(mut self, extension: Extension) -> Self {
+        self.extensions.push_custom(extension);
+        self
+    }
+
+    /// Returns the "to be signed" certificate, declaring `signature` as its
+    /// signature algorithm.
+    pub fn tbs_certificate(
+        &self,
+        signature: AlgorithmIdentifier,
+    ) -> Result<TbsCertificate, EncodeError> {
+        let extensions = self.extensions.build()?;
+        let version = if extensions.is_some() {
+            Version::V3
+        } else if self.issuer_unique_id.is_some() || self.subject_unique_id.is_some() {
+            Version::V2
+        } else {
+            Version::V1
+        };
+
+        Ok(TbsCertificate {
+            version,
+            serial_number: self.serial_number.clone(),
+            signature,
+            issuer: self.issuer.clone().unwrap_or_else(|| self.subject.clone()),
+            validity: self.validity,
+            subject: self.subject.clone(),
+            subject_public_key_info: self.subject_public_key_info.clone(),
+            issuer_unique_id: self.issuer_unique_id.clone(),
+            subject_unique_id: self.subject_unique_id.clone(),
+            extensions,
+        })
+    }
+
+    /// Builds the certificate and signs it with `signer`.
+    pub fn sign<S: Signer>(&self, signer: &S) -> Result<Certificate, BuildError<S::Error>> {
+        let signature_algorithm = signer.algorithm();
+        let tbs_certificate = self.tbs_certificate(signature_algorithm.clone())?;
+        let signature_value = sign_der(&tbs_certificate, signer)?;
+
+        Ok(Certificate {
+            tbs_certificate,
+            signature_algorithm,
+            signature_value,
+        })
+    }
+}
+
+/// A builder for signed [`CertificateList`]s.
+#[derive(Clone, Debug)]
+pub struct CertificateListBuilder {
+    issuer: Name,
+    this_update: Time,
+    next_update: Option<Time>,
+    revoked_certificates: Vec<(RevokedCerificate, ExtensionsBuilder)>,
+    extensions: ExtensionsBuilder,
+}
+
+impl CertificateListBuilder {
+    /// Creates a builder for a list issued by `issuer` at `this_update`.
+    pub fn new(issuer: Name, this_update: GeneralizedTime) -> Self {
+        Self {
+            issuer,
+            this_update: Time::new(this_update),
+            next_update: None,
+            revoked_certificates: Vec::new(),
+            extensions: ExtensionsBuilder::default(),
+        }
+    }
+
+    /// Sets when the next list will be issued.
+    pub fn next_update(mut self, next_update: GeneralizedTime) -> Self {
+        self.next_update = Some(Time::new(next_update));
+        self
+    }
+
+    /// Adds the certificate with `serial_number` as revoked on
+    /// `revocation_date`.
+    pub fn revoke(
+        self,
+        serial_number: CertificateSerialNumber,
+        revocation_date: GeneralizedTime,
+    ) -> Self {
+        self.revoke_with_extensions(serial_number, revocation_date, [])
+    }
+
+    /// Adds the certificate with `serial_number` as revoked on
+    /// `revocation_date`, with the given typed entry extensions, such as
+    /// [`KnownExtension::CrlReason`].
+    pub fn revoke_with_extensions(
+        mut self,
+        serial_number: CertificateSerialNumber,
+        revocation_date: GeneralizedTime,
+        extensions: impl IntoIterator<Item = (KnownExtension, bool)>,
+    ) -> Self {
+        let mut entry_extensions = ExtensionsBuilder::default();
+        for (extension, critical) in extensions {
+            entry_extensions.push(extension, critical);
+        }
+
+        self.revoked_certificates.push((
+            RevokedCerificate {
+                user_certificate: serial_number,
+                revocation_date: Time::new(revocation_date),
+                crl_entry_extensions: None,
+            },
+            entry_extensions,
+        ));
+        self
+    }
+
+    /// Adds a typed extension, replacing any with the same OID.
+    pub fn extension(mut self, extension: KnownExtension, critical: bool) -> Self {
+        self.extensions.push(extension, critical);
+        self
+    }
+
+    /// Adds an already encoded extension, replacing any with the same OID.
+    pub fn custom_extension(mut self, extension: Extension) -> Self {
+        self.extensions.push_custom(extension);
+        self
+    }
+
+    /// Returns the "to be signed" list, declaring `signature` as its
+    /// signature algorithm.
+    pub fn tbs_cert_list(
+        &self,
+        signature: AlgorithmIdentifier,
+    ) -> Result<TbsCertList, EncodeError> {
+        let revoked_certificates = self
+            .revoked_certificates
+            .iter()
+            .map(|(entry, extensions)| {
+                Ok(RevokedCerificate {
+                    crl_entry_extensions: extensions.build()?,
+                    ..entry.clone()
+                })
+            })
+            .collect::<Result<_, EncodeError>>()?;
+
+        Ok(TbsCertList {
+            version: Version::V2,
+            signature,
+            issuer: self.issuer.clone(),
+            this_update: self.this_update,
+            next_update: self.next_update,
+            revoked_certificates,
+            crl_extensions: self.extensions.build()?,
+        })
+    }
+
+    /// Builds the list and signs it with `signer`.
+    pub fn sign<S: Signer>(&self, signer: &S) -> Result<CertificateList, BuildError<S::Error>> {
+        let signature_algorithm = signer.algorithm();
+        let tbs_cert_list = self.tbs_cert_list(signature_algorithm.clone())?;
+        let signature = sign_der(&tbs_cert_list, signer)?;
+
+        Ok(CertificateList {
+            tbs_cert_list,
+            signature_algorithm,
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A signer whose signature is the length of the message.
+    struct LengthSigner;
+
+    impl Signer for LengthSigner {
+        type Error = core::convert::Infallible;
+
+        fn algorithm(&self) -> AlgorithmIdentifier {
+            AlgorithmIdentifier {
+                algorithm: Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA1_RSA.into(),
+                parameters: None,
+            }
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            Ok((message.len() as u32).to_be_bytes().to_vec())
+        }
+    }
+
+    fn time(year: i32) -> GeneralizedTime {
+        chrono::Utc
+            .with_ymd_and_hms(year, 6, 1, 0, 0, 0)
+            .unwrap()
+            .into()
+    }
+
+    fn key() -> SubjectPublicKeyInfo {
+        SubjectPublicKeyInfo {
+            algorithm: AlgorithmIdentifier {
+                algorithm: Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSA.into(),
+                parameters: None,
+            },
+            subject_public_key: BitString::from_slice(&[1, 2, 3, 4]),
+        }
+    }
+
+    #[test]
+    fn time_encoding() {
+        assert!(matches!(Time::new(time(1950)), Time::Utc(_)));
+        assert!(matches!(Time::new(time(2049)), Time::Utc(_)));
+        assert!(matches!(Time::new(time(1949)), Time::General(_)));
+        assert!(matches!(Time::new(time(2050)), Time::General(_)));
+        assert_eq!(time(2050), Time::new(time(2050)).to_generalized_time());
+        assert_eq!(time(2049), Time::new(time(2049)).to_generalized_time());
+    }
+
+    #[test]
+    fn certificate() {
+        let builder = CertificateBuilder::new(
+            7.into(),
+            "CN=Leaf".parse().unwrap(),
+            key(),
+            Validity::new(time(2024), time(2054)),
+        )
+        .issuer("CN=Issuer".parse().unwrap());
+
+        let certificate = builder.sign(&LengthSigner).unwrap();
+        let tbs = &certificate.tbs_certificate;
+        assert_eq!(Version::V1, tbs.version);
+        assert!(matches!(tbs.validity.not_before, Time::Utc(_)));
+        assert!(matches!(tbs.validity.not_after, Time::General(_)));
+        assert_eq!("CN=Issuer", tbs.issuer.to_string());
+        assert_eq!(LengthSigner.algorithm(), tbs.signature);
+
+        let message = rasn::der::encode(tbs).unwrap();
+        assert_eq!(
+            (message.len() as u32).to_be_bytes(),
+            certificate.signature_value.as_raw_slice()
+        );
+
+        let encoded = rasn::der::encode(&certificate).unwrap();
+        assert_eq!(
+            certificate,
+            rasn::der::decode::<Certificate>(&encoded).unwrap()
+        );
+
+        let certificate = builder
+            .extension(
+                KnownExtension::BasicConstraints(BasicConstraints {
+                    ca: false,
+                    path_len_constraint: None,
+                }),
+                false,
+            )
+            .extension(
+                KnownExtension::BasicConstraints(BasicConstraints {
+                    ca: true,
+                    path_len_constraint: None,
+                }),
+                true,
+            )
+            .sign(&LengthSigner)
+            .unwrap();
+        let extensions = certificate.tbs_certificate.extensions.as_ref().unwrap();
+        assert_eq!(Version::V3, certificate.tbs_certificate.version);
+        assert_eq!(1, extensions.len());
+        assert!(extensions[0].critical);
+        assert!(extensions.basic_constraints().unwrap().unwrap().ca);
+    }
+
+    #[test]
+    fn extension_order() {
+        let private = Extension {
+            extn_id: ObjectIdentifier::new(alloc::vec![1, 3, 6, 1, 4, 1, 99999]).unwrap(),
+            critical: false,
+            extn_value: OctetString::from_static(&[5, 0]),
+        };
+        let mut builder = ExtensionsBuilder::default();
+        builder.push_custom(private.clone());
+        builder.push(KnownExtension::CrlNumber(1.into()), false);
+        builder.push(KnownExtension::InhibitAnyPolicy(0.into()), true);
+        builder.push(KnownExtension::CrlNumber(2.into()), false);
+
+        let extensions = builder.build().unwrap().unwrap();
+        let oids: Vec<_> = extensions.iter().map(|e| &*e.extn_id).collect();
+        assert_eq!(
+            [
+                &*private.extn_id,
+                crate::extensions::INHIBIT_ANY_POLICY,
+                crate::extensions::CRL_NUMBER,
+            ],
+            &*oids
+        );
+        assert_eq!(private, extensions[0]);
+    }
+
+    #[test]
+    fn certificate_list() {
+        let list = CertificateListBuilder::new("CN=Issuer".parse().unwrap(), time(2024))
+            .next_update(time(2025))
+            .revoke(1.into(), time(2023))
+            .revoke_with_extensions(
+                2.into(),
+                time(2023),
+                [(KnownExtension::CrlReason(CrlReason::KeyCompromise), false)],
+            )
+            .extension(KnownExtension::CrlNumber(5.into()), false)
+            .sign(&LengthSigner)
+            .unwrap();
+
+        let encoded = rasn::der::encode(&list).unwrap();
+        let decoded = rasn::der::decode::<CertificateList>(&encoded).unwrap();
+        assert_eq!(list, decoded);
+
+        let revoked = &decoded.tbs_cert_list.revoked_certificates;
+        assert_eq!(2, revoked.len());
+        assert!(revoked[0].crl_entry_extensions.is_none());
+        assert_eq!(
+            Some(Ok(KnownExtension::CrlReason(CrlReason::KeyCompromise))),
+            revoked[1]
+                .crl_entry_extensions
+                .as_ref()
+                .unwrap()
+                .get_known(crate::extensions::CRL_REASON)
+                .map(|result| result.map_err(drop))
+        );
+    }
+}
@@ -0,0 +1,615 @@
+//! # Distinguished Name Strings
+//!
+//! This module implements the string representation of distinguished names
+//! defined in [RFC 4514], allowing [`Name`] to be converted to and from
+//! strings such as `CN=Steve Kille,O=Isode Limited,C=GB`.
+//!
+//! When formatting, attribute types listed in [RFC 4514 Section 3] are
+//! written using their short names, and any other attribute type is written
+//! as a dotted-decimal OID whose value is the hex encoding (`#04024869`) of
+//! the value's BER encoding. LDAP DN strings ([`rasn_ldap::LdapDn`]) use the
+//! same syntax, and can be converted with [`Name::from_ldap_dn`].
+//!
+//! [RFC 4514]: https://datatracker.ietf.org/doc/html/rfc4514
+//! [RFC 4514 Section 3]: https://datatracker.ietf.org/doc/html/rfc4514#section-3
+//! [`rasn_ldap::LdapDn`]: https://docs.rs/rasn-ldap
+
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use rasn::types::*;
+
+use crate::{AttributeTypeAndValue, DirectoryString, Name, RelativeDistinguishedName};
+
+/// The attribute types that have a short name registered in RFC 4514.
+const SHORT_NAMES: &[(&str, &Oid)] = &[
+    ("CN", Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COMMON_NAME),
+    ("L", Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_LOCALITY_NAME),
+    (
+        "ST",
+        Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_STATE_OR_PROVINCE_NAME,
+    ),
+    (
+        "O",
+        Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_ORGANISATION_NAME,
+    ),
+    (
+        "OU",
+        Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_ORGANISATIONAL_UNIT_NAME,
+    ),
+    ("C", Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COUNTRY_NAME),
+    (
+        "STREET",
+        Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_STREET_ADDRESS,
+    ),
+    (
+        "DC",
+        Oid::ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE_DOMAIN_COMPONENT,
+    ),
+    ("UID", Oid::ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE_USER_ID),
+];
+
+/// Returns the RFC 4514 short name for `oid`, if it has one.
+pub fn short_name(oid: &Oid) -> Option<&'static str> {
+    SHORT_NAMES
+        .iter()
+        .find(|(_, known)| *known == oid)
+        .map(|(name, _)| *name)
+}
+
+/// Returns the attribute type for the RFC 4514 short name `name`, ignoring
+/// case.
+pub fn from_short_name(name: &str) -> Option<&'static Oid> {
+    SHORT_NAMES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, oid)| *oid)
+}
+
+/// An error produced when parsing a distinguished name string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseNameError {
+    /// An attribute type was not followed by `=`.
+    MissingEquals { position: usize },
+    /// An attribute type was empty, or was not a valid OID.
+    InvalidAttributeType { position: usize },
+    /// An attribute type descriptor isn't one of the known short names.
+    UnknownAttributeType(String),
+    /// A `\` escape was not followed by a special character or hex pair.
+    InvalidEscape { position: usize },
+    /// A `#` value was not valid hex, or not a single BER encoded value.
+    InvalidHexValue { position: usize },
+    /// An escaped value did not form valid UTF-8.
+    InvalidUtf8 { position: usize },
+    /// A value could not be represented in the string type required by its
+    /// attribute type (e.g. a non-printable country name).
+    InvalidValue { position: usize },
+    /// The last RDN was followed by more characters.
+    TrailingCharacters { position: usize },
+    /// The DN string ended where an attribute was expected.
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEquals { position } => {
+                write!(f, "expected `=` after attribute type at {position}")
+            }
+            Self::InvalidAttributeType { position } => {
+                write!(f, "invalid attribute type at {position}")
+            }
+            Self::UnknownAttributeType(name) => write!(f, "unknown attribute type `{name}`"),
+            Self::InvalidEscape { position } => write!(f, "invalid escape sequence at {position}"),
+            Self::InvalidHexValue { position } => write!(f, "invalid hex value at {position}"),
+            Self::InvalidUtf8 { position } => write!(f, "invalid UTF-8 in value at {position}"),
+            Self::InvalidValue { position } => {
+                write!(f, "value at {position} is invalid for its attribute type")
+            }
+            Self::TrailingCharacters { position } => {
+                write!(f, "unexpected characters after last RDN at {position}")
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of distinguished name"),
+        }
+    }
+}
+
+impl Name {
+    /// Parses an LDAP distinguished name, such as the `LdapDn` contained in
+    /// an LDAP `SearchRequest`'s base object.
+    pub fn from_ldap_dn(dn: &[u8]) -> Result<Self, ParseNameError> {
+        core::str::from_utf8(dn)
+            .map_err(|error| ParseNameError::InvalidUtf8 {
+                position: error.valid_up_to(),
+            })?
+            .parse()
+    }
+
+    /// Formats the name as an LDAP distinguished name.
+    pub fn to_ldap_dn(&self) -> OctetString {
+        OctetString::from(alloc::string::ToString::to_string(self))
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Name::RdnSequence(rdns) = self;
+
+        // RFC 4514 outputs the RDNs starting with the last element of the
+        // sequence.
+        for (i, rdn) in rdns.iter().rev().enumerate() {
+            if i != 0 {
+                f.write_char(',')?;
+            }
+            rdn.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RelativeDistinguishedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, attribute) in self.iter().enumerate() {
+            if i != 0 {
+                f.write_char('+')?;
+            }
+            attribute.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for AttributeTypeAndValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = short_name(&self.r#type)
+            .and_then(|name| value_to_string(&self.value).map(|value| (name, value)));
+
+        match string {
+            Some((name, value)) => {
+                f.write_str(name)?;
+                f.write_char('=')?;
+                write_escaped(f, &value)
+            }
+            None => {
+                write_oid(f, &self.r#type)?;
+                f.write_str("=#")?;
+                for byte in self.value.as_bytes() {
+                    write!(f, "{byte:02X}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for Name {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            position: 0,
+        };
+
+        let mut rdns = Vec::new();
+        parser.skip_spaces();
+        if parser.is_empty() {
+            return Ok(Name::RdnSequence(rdns));
+        }
+
+        loop {
+            let mut rdn = BTreeSet::new();
+            loop {
+                rdn.insert(parser.parse_attribute_type_and_value()?);
+                if !parser.eat(b'+') {
+                    break;
+                }
+            }
+            rdns.push(RelativeDistinguishedName::from(rdn));
+
+            // RFC 2253 also permitted `;` as an RDN separator.
+            if !(parser.eat(b',') || parser.eat(b';')) {
+                break;
+            }
+        }
+
+        if !parser.is_empty() {
+            return Err(ParseNameError::TrailingCharacters {
+                position: parser.position,
+            });
+        }
+
+        rdns.reverse();
+        Ok(Name::RdnSequence(rdns))
+    }
+}
+
+struct Parser<'input> {
+    input: &'input [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    fn parse_attribute_type_and_value(&mut self) -> Result<AttributeTypeAndValue, ParseNameError> {
+        self.skip_spaces();
+        let start = self.position;
+        while self.peek().is_some_and(|b| b != b'=' && b != b' ') {
+            self.position += 1;
+        }
+        let descriptor = core::str::from_utf8(&self.input[start..self.position])
+            .map_err(|_| ParseNameError::InvalidAttributeType { position: start })?;
+        self.skip_spaces();
+        if self.is_empty() && descriptor.is_empty() {
+            return Err(ParseNameError::UnexpectedEnd);
+        }
+        if !self.eat(b'=') {
+            return Err(ParseNameError::MissingEquals {
+                position: self.position,
+            });
+        }
+        self.skip_spaces();
+
+        let r#type = parse_attribute_type(descriptor, start)?;
+        let position = self.position;
+        let value = if self.eat(b'#') {
+            self.parse_hex_value(position)?
+        } else {
+            let string = self.parse_string_value()?;
+            string_to_value(&r#type, string).ok_or(ParseNameError::InvalidValue { position })?
+        };
+
+        Ok(AttributeTypeAndValue { r#type, value })
+    }
+
+    fn parse_hex_value(&mut self, position: usize) -> Result<Any, ParseNameError> {
+        let start = self.position;
+        while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+            self.position += 1;
+        }
+        let digits = &self.input[start..self.position];
+        self.skip_spaces();
+
+        if digits.is_empty() || !digits.len().is_multiple_of(2) {
+            return Err(ParseNameError::InvalidHexValue { position });
+        }
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| (hex_digit(pair[0]) << 4) | hex_digit(pair[1]))
+            .collect::<Vec<_>>();
+
+        // The value must be exactly one BER encoded element.
+        match rasn::ber::decode::<Any>(&bytes) {
+            Ok(any) if any.as_bytes().len() == bytes.len() => Ok(Any::new(bytes)),
+            _ => Err(ParseNameError::InvalidHexValue { position }),
+        }
+    }
+
+    fn parse_string_value(&mut self) -> Result<String, ParseNameError> {
+        let start = self.position;
+        let mut bytes = Vec::new();
+        // The length of `bytes` at the end of the last escape, so escaped
+        // trailing spaces are preserved.
+        let mut escaped_len = 0;
+
+        while let Some(byte) = self.peek() {
+            match byte {
+                b',' | b'+' | b';' => break,
+                b'\\' => {
+                    let position = self.position;
+                    self.position += 1;
+                    match self.peek() {
+                        Some(
+                            special @ (b' ' | b'"' | b'#' | b'+' | b',' | b';' | b'<' | b'=' | b'>'
+                            | b'\\'),
+                        ) => {
+                            bytes.push(special);
+                            self.position += 1;
+                        }
+                        Some(high) if high.is_ascii_hexdigit() => {
+                            let low = self
+                                .input
+                                .get(self.position + 1)
+                                .copied()
+                                .filter(u8::is_ascii_hexdigit)
+                                .ok_or(ParseNameError::InvalidEscape { position })?;
+                            bytes.push((hex_digit(high) << 4) | hex_digit(low));
+                            self.position += 2;
+                        }
+                        _ => return Err(ParseNameError::InvalidEscape { position }),
+                    }
+                    escaped_len = bytes.len();
+                }
+                byte => {
+                    bytes.push(byte);
+                    self.position += 1;
+                }
+            }
+        }
+
+        while bytes.len() > escaped_len && bytes.last() == Some(&b' ') {
+            bytes.pop();
+        }
+
+        String::from_utf8(bytes).map_err(|_| ParseNameError::InvalidUtf8 { position: start })
+    }
+}
+
+fn parse_attribute_type(
+    descriptor: &str,
+    position: usize,
+) -> Result<ObjectIdentifier, ParseNameError> {
+    let numeric = descriptor
+        .strip_prefix("OID.")
+        .or_else(|| descriptor.strip_prefix("oid."))
+        .unwrap_or(descriptor);
+
+    if numeric.starts_with(|c: char| c.is_ascii_digit()) {
+        numeric
+            .split('.')
+            .map(|arc| arc.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|arcs| arcs.len() >= 2)
+            .and_then(ObjectIdentifier::new)
+            .ok_or(ParseNameError::InvalidAttributeType { position })
+    } else if descriptor.is_empty() {
+        Err(ParseNameError::InvalidAttributeType { position })
+    } else {
+        from_short_name(descriptor)
+            .map(ObjectIdentifier::from)
+            .ok_or_else(|| ParseNameError::UnknownAttributeType(descriptor.into()))
+    }
+}
+
+/// Encodes `value` using the string type RFC 5280 requires or recommends for
+/// attributes of type `oid`.
+fn string_to_value(oid: &Oid, value: String) -> Option<Any> {
+    let encoded = if oid == Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COUNTRY_NAME
+        || oid == Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_SERIAL_NUMBER
+        || oid == Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_DN_QUALIFIER
+    {
+        rasn::der::encode(&PrintableString::try_from(value).ok()?)
+    } else if oid == Oid::ITU_T_DATA_PSS_UCL_PILOT_ATTRIBUTE_TYPE_DOMAIN_COMPONENT {
+        rasn::der::encode(&Ia5String::try_from(value).ok()?)
+    } else {
+        rasn::der::encode(&value)
+    };
+
+    encoded.ok().map(Any::new)
+}
+
+/// Decodes an attribute value as a string, if it's one of the string types
+/// used in distinguished names.
+fn value_to_string(value: &Any) -> Option<String> {
+    let bytes = value.as_bytes();
+    let tag = *bytes.first()?;
+
+    match tag {
+        // IA5String
+        0x16 => rasn::der::decode::<Ia5String>(bytes).ok().map(String::from),
+        // VisibleString
+        0x1A => rasn::der::decode::<VisibleString>(bytes)
+            .ok()
+            .map(String::from),
+        _ => match rasn::der::decode::<DirectoryString>(bytes).ok()? {
+            DirectoryString::Printable(string) => {
+                core::str::from_utf8(string.as_bytes()).ok().map(From::from)
+            }
+            DirectoryString::Utf8(string) => Some(string),
+            DirectoryString::Universal(string) => Some(string.value),
+            DirectoryString::Bmp(string) => {
+                let units = string
+                    .to_bytes()
+                    .chunks(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>();
+                char::decode_utf16(units).collect::<Result<_, _>>().ok()
+            }
+            // T.61 is treated as Latin-1, which matches its use in practice.
+            DirectoryString::Teletex(string) => {
+                Some(string.iter().map(|&byte| char::from(byte)).collect())
+            }
+        },
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                f.write_char('\\')?;
+                f.write_char(c)?;
+            }
+            ' ' | '#' if i == 0 => {
+                f.write_char('\\')?;
+                f.write_char(c)?;
+            }
+            ' ' if i == last => f.write_str("\\ ")?,
+            '\0' => f.write_str("\\00")?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn write_oid(f: &mut fmt::Formatter<'_>, oid: &Oid) -> fmt::Result {
+    for (i, arc) in oid.iter().enumerate() {
+        if i != 0 {
+            f.write_char('.')?;
+        }
+        write!(f, "{arc}")?;
+    }
+
+    Ok(())
+}
+
+fn hex_digit(byte: u8) -> u8 {
+    match byte {
+        b'0'..=b'9' => byte - b'0',
+        b'a'..=b'f' => byte - b'a' + 10,
+        _ => byte - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use pretty_assertions::assert_eq;
+
+    fn attribute(oid: &Oid, value: Any) -> AttributeTypeAndValue {
+        AttributeTypeAndValue {
+            r#type: oid.into(),
+            value,
+        }
+    }
+
+    fn utf8(value: &str) -> Any {
+        Any::new(rasn::der::encode(&String::from(value)).unwrap())
+    }
+
+    #[test]
+    fn display() {
+        let name = Name::RdnSequence(alloc::vec![
+            RelativeDistinguishedName::from(BTreeSet::from([attribute(
+                Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COUNTRY_NAME,
+                Any::new(rasn::der::encode(&PrintableString::try_from("GB").unwrap()).unwrap()),
+            )])),
+            RelativeDistinguishedName::from(BTreeSet::from([attribute(
+                Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_ORGANISATION_NAME,
+                utf8("Isode Limited"),
+            )])),
+            RelativeDistinguishedName::from(BTreeSet::from([attribute(
+                Oid::JOINT_ISO_ITU_T_DS_ATTRIBUTE_TYPE_COMMON_NAME,
+                utf8("Steve Kille"),
+            )])),
+        ]);
+
+        assert_eq!("CN=Steve Kille,O=Isode Limited,C=GB", name.to_string());
+        assert_eq!(
+            name,
+            "CN=Steve Kille,O=Isode Limited,C=GB"
+                .parse::<Name>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        for dn in [
+            r"CN=Sue\, Grabbit and Runn,O=Example",
+            r"CN=L. Eagle,O=Sue\, Grabbit and Runn,C=GB",
+            r"CN=Before\0dAfter,DC=example,DC=net",
+            r#"CN=\#hash\ ,O=a\+b\;c\<d\>e\\f\"g"#,
+            "CN=Lu\u{010D}i\u{0107}",
+        ] {
+            let name = dn.parse::<Name>().unwrap();
+            let reparsed = name.to_string().parse::<Name>().unwrap();
+            assert_eq!(name, reparsed, "{dn}");
+        }
+
+        let name = r"CN=\#hash\ ,O=a\+b".parse::<Name>().unwrap();
+        assert_eq!(r"CN=\#hash\ ,O=a\+b", name.to_string());
+        let name = r"CN=Lu\C4\8Di\C4\87".parse::<Name>().unwrap();
+        assert_eq!("CN=Lu\u{010D}i\u{0107}", name.to_string());
+    }
+
+    #[test]
+    fn hex_values() {
+        let name = "1.3.6.1.4.1.1466.0=#04024869,O=Test,C=GB"
+            .parse::<Name>()
+            .unwrap();
+        let Name::RdnSequence(rdns) = &name;
+        let first = rdns.last().unwrap().iter().next().unwrap();
+        assert_eq!(&[0x04, 0x02, 0x48, 0x69][..], first.value.as_bytes());
+        assert_eq!("1.3.6.1.4.1.1466.0=#04024869,O=Test,C=GB", name.to_string());
+
+        assert!(matches!(
+            "CN=#0402".parse::<Name>(),
+            Err(ParseNameError::InvalidHexValue { .. })
+        ));
+    }
+
+    #[test]
+    fn multi_valued() {
+        let name = "OU=Sales+CN=J. Smith,DC=example,DC=net"
+            .parse::<Name>()
+            .unwrap();
+        let Name::RdnSequence(rdns) = &name;
+        assert_eq!(3, rdns.len());
+        assert_eq!(2, rdns[2].len());
+        assert_eq!(name, name.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn ldap_dn() {
+        let name = Name::from_ldap_dn(b"uid=jdoe, ou=People, dc=example, dc=org").unwrap();
+        assert_eq!("UID=jdoe,OU=People,DC=example,DC=org", name.to_string());
+        assert_eq!(
+            &b"UID=jdoe,OU=People,DC=example,DC=org"[..],
+            &*name.to_ldap_dn()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Name::RdnSequence(alloc::vec![]), "".parse().unwrap());
+        assert_eq!(
+            Err(ParseNameError::UnknownAttributeType("FOO".into())),
+            "FOO=bar".parse::<Name>()
+        );
+        assert!(matches!(
+            "CN".parse::<Name>(),
+            Err(ParseNameError::MissingEquals { .. })
+        ));
+        assert!(matches!(
+            r"CN=a\zb".parse::<Name>(),
+            Err(ParseNameError::InvalidEscape { .. })
+        ));
+        assert!(matches!(
+            "C=G@".parse::<Name>(),
+            Err(ParseNameError::InvalidValue { .. })
+        ));
+        assert_eq!(Err(ParseNameError::UnexpectedEnd), "CN=a,".parse::<Name>());
+        assert_eq!(
+            Err(ParseNameError::TrailingCharacters { position: 14 }),
+            "CN=#0403616263zz".parse::<Name>()
+        );
+    }
+
+    #[test]
+    fn certificate_names() {
+        let contents = include_bytes!("../tests/data/letsencrypt-x3.crt");
+        let cert: crate::Certificate = rasn::der::decode(contents).unwrap();
+
+        assert_eq!(
+            "CN=DST Root CA X3,O=Digital Signature Trust Co.",
+            cert.tbs_certificate.issuer.to_string()
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! # Typed Certificate Extensions
+//!
+//! [`Extension::extn_value`] holds the DER encoding of the extension's value,
+//! whose type is determined by [`Extension::extn_id`]. This module connects
+//! the extension OIDs defined in [RFC 5280 Section 4.2] and
+//! [RFC 5280 Section 5.2] to their types through [`KnownExtension`], and adds
+//! lookup, validation and construction methods to [`Extensions`].
+//!
+//! ```
+//! use rasn_pkix::{extensions::{self, KnownExtension}, BasicConstraints, Extensions};
+//!
+//! let extensions = Extensions::from_known([(
+//!     KnownExtension::BasicConstraints(BasicConstraints { ca: true, path_len_constraint: None }),
+//!     true,
+//! )])
+//! .unwrap();
+//!
+//! assert!(extensions.validate(&[]).is_err());
+//! assert!(extensions.validate(&[extensions::BASIC_CONSTRAINTS]).is_ok());
+//! let constraints = extensions.basic_constraints().unwrap().unwrap();
+//! assert!(constraints.ca);
+//! assert!(extensions.get(extensions::BASIC_CONSTRAINTS).unwrap().critical);
+//! ```
+//!
+//! [RFC 5280 Section 4.2]: https://datatracker.ietf.org/doc/html/rfc5280#section-4.2
+//! [RFC 5280 Section 5.2]: https://datatracker.ietf.org/doc/html/rfc5280#section-5.2
+
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    types::*,
+    Decode,
+};
+
+use crate::*;
+
+/// The Authority Key Identifier certificate extension, RFC 5280 Section 4.2.1.1.
+pub const AUTHORITY_KEY_IDENTIFIER: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_KEY_IDENTIFIER;
+/// The Subject Key Identifier certificate extension, RFC 5280 Section 4.2.1.2.
+pub const SUBJECT_KEY_IDENTIFIER: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_SUBJECT_KEY_IDENTIFIER;
+/// The Key Usage certificate extension, RFC 5280 Section 4.2.1.3.
+pub const KEY_USAGE: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_KEY_USAGE;
+/// The Certificate Policies certificate extension, RFC 5280 Section 4.2.1.4.
+pub const CERTIFICATE_POLICIES: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CERTIFICATE_POLICIES;
+/// The `anyPolicy` certificate policy identifier, RFC 5280 Section 4.2.1.4.
+pub const ANY_POLICY: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CERTIFICATE_POLICIES_ANY_POLICY;
+/// The Policy Mappings certificate extension, RFC 5280 Section 4.2.1.5.
+pub const POLICY_MAPPINGS: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_POLICY_MAPPINGS;
+/// The Subject Alternative Name certificate extension, RFC 5280 Section 4.2.1.6.
+pub const SUBJECT_ALT_NAME: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_SUBJECT_ALT_NAME;
+/// The Issuer Alternative Name certificate extension, RFC 5280 Section 4.2.1.7.
+pub const ISSUER_ALT_NAME: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_ISSUER_ALT_NAME;
+/// The Subject Directory Attributes certificate extension, RFC 5280 Section 4.2.1.8.
+pub const SUBJECT_DIRECTORY_ATTRIBUTES: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_SUBJECT_DIRECTORY_ATTRIBUTES;
+/// The Basic Constraints certificate extension, RFC 5280 Section 4.2.1.9.
+pub const BASIC_CONSTRAINTS: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_BASIC_CONSTRAINTS;
+/// The Name Constraints certificate extension, RFC 5280 Section 4.2.1.10.
+pub const NAME_CONSTRAINTS: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_CRL_NAME_CONSTRAINTS;
+/// The Policy Constraints certificate extension, RFC 5280 Section 4.2.1.11.
+pub const POLICY_CONSTRAINTS: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_POLICY_CONSTRAINTS;
+/// The Extended Key Usage certificate extension, RFC 5280 Section 4.2.1.12.
+pub const EXT_KEY_USAGE: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_EXT_KEY_USAGE;
+/// The CRL Distribution Points certificate extension, RFC 5280 Section 4.2.1.13.
+pub const CRL_DISTRIBUTION_POINTS: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_AUTHORITY_CRL_DISTRIBUTION_POINTS;
+/// The Inhibit anyPolicy certificate extension, RFC 5280 Section 4.2.1.14.
+pub const INHIBIT_ANY_POLICY: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_INHIBIT_ANY_POLICY;
+/// The Freshest CRL certificate extension, RFC 5280 Section 4.2.1.15.
+pub const FRESHEST_CRL: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_FRESHEST_CRL;
+/// The Authority Information Access certificate extension, RFC 5280 Section 4.2.2.1.
+pub const AUTHORITY_INFO_ACCESS: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_AUTHORITY_INFO_ACCESS;
+/// The Subject Information Access certificate extension, RFC 5280 Section 4.2.2.2.
+pub const SUBJECT_INFO_ACCESS: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_PKIX_PE_SUBJECT_INFO_ACCESS;
+/// The CRL Number CRL extension, RFC 5280 Section 5.2.3.
+pub const CRL_NUMBER: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CRL_NUMBER;
+/// The Delta CRL Indicator CRL extension, RFC 5280 Section 5.2.4.
+pub const DELTA_CRL_INDICATOR: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_DELTA_CRL_INDICATOR;
+/// The Issuing Distribution Point CRL extension, RFC 5280 Section 5.2.5.
+pub const ISSUING_DISTRIBUTION_POINT: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_ISSUING_DISTRIBUTION_POINT;
+/// The Reason Code CRL entry extension, RFC 5280 Section 5.3.1.
+pub const CRL_REASON: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CRL_REASONS;
+/// The Invalidity Date CRL entry extension, RFC 5280 Section 5.3.2.
+pub const INVALIDITY_DATE: &Oid = Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_INVALIDITY_DATE;
+/// The Certificate Issuer CRL entry extension, RFC 5280 Section 5.3.3.
+pub const CERTIFICATE_ISSUER: &Oid =
+    Oid::JOINT_ISO_ITU_T_DS_CERTIFICATE_EXTENSION_CERTIFICATE_ISSUER;
+
+macro_rules! known_extensions {
+    ($($(#[$meta:meta])* $name:ident($ty:ty) => $oid:ident),+ $(,)?) => {
+        /// A certificate, CRL, or CRL entry extension with a value type known
+        /// to this crate.
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum KnownExtension {
+            $($(#[$meta])* $name($ty),)+
+        }
+
+        impl KnownExtension {
+            /// The identifier of the extension.
+            pub fn oid(&self) -> &'static Oid {
+                match self {
+                    $(Self::$name(_) => $oid,)+
+                }
+            }
+
+            /// Returns whether `oid` identifies an extension known to this
+            /// crate.
+            pub fn is_known(oid: &Oid) -> bool {
+                [$($oid),+].contains(&oid)
+            }
+
+            /// Decodes the DER encoded `value` of the extension identified by
+            /// `oid`. Returns `Ok(None)` if the extension isn't known.
+            pub fn decode(oid: &Oid, value: &[u8]) -> Result<Option<Self>, DecodeError> {
+                $(
+                    if oid == $oid {
+                        return rasn::der::decode::<$ty>(value).map(|v| Some(Self::$name(v)));
+                    }
+                )+
+
+                Ok(None)
+            }
+
+            /// Encodes the value of the extension with DER.
+            pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+                match self {
+                    $(Self::$name(value) => rasn::der::encode(value),)+
+                }
+            }
+        }
+    };
+}
+
+known_extensions! {
+    AuthorityKeyIdentifier(AuthorityKeyIdentifier) => AUTHORITY_KEY_IDENTIFIER,
+    SubjectKeyIdentifier(SubjectKeyIdentifier) => SUBJECT_KEY_IDENTIFIER,
+    KeyUsage(KeyUsage) => KEY_USAGE,
+    CertificatePolicies(CertificatePolicies) => CERTIFICATE_POLICIES,
+    PolicyMappings(PolicyMappings) => POLICY_MAPPINGS,
+    SubjectAltName(SubjectAltName) => SUBJECT_ALT_NAME,
+    IssuerAltName(GeneralNames) => ISSUER_ALT_NAME,
+    SubjectDirectoryAttributes(SubjectDirectoryAttributes) => SUBJECT_DIRECTORY_ATTRIBUTES,
+    BasicConstraints(BasicConstraints) => BASIC_CONSTRAINTS,
+    NameConstraints(NameConstraints) => NAME_CONSTRAINTS,
+    PolicyConstraints(PolicyConstraints) => POLICY_CONSTRAINTS,
+    ExtKeyUsage(ExtKeyUsageSyntax) => EXT_KEY_USAGE,
+    CrlDistributionPoints(CrlDistributionPoints) => CRL_DISTRIBUTION_POINTS,
+    InhibitAnyPolicy(InhibitAnyPolicy) => INHIBIT_ANY_POLICY,
+    FreshestCrl(FreshestCrl) => FRESHEST_CRL,
+    AuthorityInfoAccess(AuthorityInfoAccessSyntax) => AUTHORITY_INFO_ACCESS,
+    SubjectInfoAccess(SubjectInfoAccessSyntax) => SUBJECT_INFO_ACCESS,
+    CrlNumber(CrlNumber) => CRL_NUMBER,
+    DeltaCrlIndicator(BaseCrlNumber) => DELTA_CRL_INDICATOR,
+    IssuingDistributionPoint(IssuingDistributionPoint) => ISSUING_DISTRIBUTION_POINT,
+    CrlReason(CrlReason) => CRL_REASON,
+    InvalidityDate(InvalidityDate) => INVALIDITY_DATE,
+    CertificateIssuer(CertificateIssuer) => CERTIFICATE_ISSUER,
+}
+
+/// An error found when validating a set of [`Extensions`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExtensionsError {
+    /// More than one extension with the same OID was present, which
+    /// RFC 5280 forbids.
+    Duplicate(ObjectIdentifier),
+    /// An extension marked critical wasn't listed as supported by the
+    /// caller.
+    UnsupportedCritical(ObjectIdentifier),
+    /// A known extension's value couldn't be decoded.
+    Decode {
+        oid: ObjectIdentifier,
+        error: DecodeError,
+    },
+}
+
+impl core::fmt::Display for ExtensionsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Duplicate(oid) => write!(f, "duplicate extension {:?}", &**oid),
+            Self::UnsupportedCritical(oid) => {
+                write!(f, "unsupported critical extension {:?}", &**oid)
+            }
+            Self::Decode { oid, error } => {
+                write!(f, "failed to decode extension {:?}: {error}", &**oid)
+            }
+        }
+    }
+}
+
+impl Extension {
+    /// Creates a new extension from a typed value.
+    pub fn from_known(value: &KnownExtension, critical: bool) -> Result<Self, EncodeError> {
+        Ok(Self {
+            extn_id: value.oid().into(),
+            critical,
+            extn_value: value.encode()?.into(),
+        })
+    }
+
+    /// Decodes the extension's value, returning `Ok(None)` if the extension
+    /// isn't known.
+    pub fn decode_known(&self) -> Result<Option<KnownExtension>, DecodeError> {
+        KnownExtension::decode(&self.extn_id, &self.extn_value)
+    }
+
+    /// Decodes the extension's value as `T`.
+    pub fn decode_value<T: Decode>(&self) -> Result<T, DecodeError> {
+        rasn::der::decode(&self.extn_value)
+    }
+}
+
+impl Extensions {
+    /// Creates a set of extensions from typed values and their criticality.
+    pub fn from_known(
+        values: impl IntoIterator<Item = (KnownExtension, bool)>,
+    ) -> Result<Self, EncodeError> {
+        values
+            .into_iter()
+            .map(|(value, critical)| Extension::from_known(&value, critical))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    /// Adds a typed extension, replacing any existing extension with the same
+    /// OID.
+    pub fn insert(&mut self, value: &KnownExtension, critical: bool) -> Result<(), EncodeError> {
+        let extension = Extension::from_known(value, critical)?;
+        match self.0.iter_mut().find(|e| e.extn_id == extension.extn_id) {
+            Some(existing) => *existing = extension,
+            None => self.0.push(extension),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first extension identified by `oid`.
+    pub fn get(&self, oid: &Oid) -> Option<&Extension> {
+        self.0.iter().find(|extension| extension.extn_id == *oid)
+    }
+
+    /// Decodes the value of the extension identified by `oid` as `T`.
+    pub fn get_as<T: Decode>(&self, oid: &Oid) -> Option<Result<T, DecodeError>> {
+        self.get(oid).map(Extension::decode_value)
+    }
+
+    /// Decodes the extension identified by `oid`, returning `None` if it
+    /// isn't present or isn't known.
+    pub fn get_known(&self, oid: &Oid) -> Option<Result<KnownExtension, DecodeError>> {
+        self.get(oid)?.decode_known().transpose()
+    }
+
+    /// Iterates over every extension, decoding the known ones. Unknown
+    /// extensions are returned as `Ok(None)` alongside the raw extension.
+    pub fn iter_known(
+        &self,
+    ) -> impl Iterator<Item = (&Extension, Result<Option<KnownExtension>, DecodeError>)> {
+        self.0
+            .iter()
+            .map(|extension| (extension, extension.decode_known()))
+    }
+
+    /// Checks the extensions for duplicates, undecodable known extensions,
+    /// and critical extensions that aren't present in `supported`.
+    ///
+    /// Being able to decode an extension isn't the same as processing it,
+    /// so `supported` should only list the extensions the caller enforces.
+    pub fn validate(&self, supported: &[&Oid]) -> Result<(), ExtensionsError> {
+        let mut seen = BTreeSet::new();
+
+        for extension in &self.0 {
+            if !seen.insert(&extension.extn_id) {
+                return Err(ExtensionsError::Duplicate(extension.extn_id.clone()));
+            }
+
+            if let Err(error) = extension.decode_known() {
+                return Err(ExtensionsError::Decode {
+                    oid: extension.extn_id.clone(),
+                    error,
+                });
+            }
+
+            if extension.critical && !supported.iter().any(|oid| **oid == extension.extn_id) {
+                return Err(ExtensionsError::UnsupportedCritical(
+                    extension.extn_id.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the basic constraints extension.
+    pub fn basic_constraints(&self) -> Option<Result<BasicConstraints, DecodeError>> {
+        self.get_as(BASIC_CONSTRAINTS)
+    }
+
+    /// Decodes the key usage extension.
+    pub fn key_usage(&self) -> Option<Result<KeyUsage, DecodeError>> {
+        self.get_as(KEY_USAGE)
+    }
+
+    /// Decodes the subject alternative name extension.
+    pub fn subject_alt_name(&self) -> Option<Result<SubjectAltName, DecodeError>> {
+        self.get_as(SUBJECT_ALT_NAME)
+    }
+
+    /// Decodes the authority key identifier extension.
+    pub fn authority_key_identifier(&self) -> Option<Result<AuthorityKeyIdentifier, DecodeError>> {
+        self.get_as(AUTHORITY_KEY_IDENTIFIER)
+    }
+
+    /// Decodes the subject key identifier extension.
+    pub fn subject_key_identifier(&self) -> Option<Result<SubjectKeyIdentifier, DecodeError>> {
+        self.get_as(SUBJECT_KEY_IDENTIFIER)
+    }
+
+    /// Decodes the CRL distribution points extension.
+    pub fn crl_distribution_points(&self) -> Option<Result<CrlDistributionPoints, DecodeError>> {
+        self.get_as(CRL_DISTRIBUTION_POINTS)
+    }
+
+    /// Decodes the name constraints extension.
+    pub fn name_constraints(&self) -> Option<Result<NameConstraints, DecodeError>> {
+        self.get_as(NAME_CONSTRAINTS)
+    }
+}
+
+/// The bits of the [`KeyUsage`] extension.
+pub mod key_usage {
+    pub const DIGITAL_SIGNATURE: usize = 0;
+    pub const NON_REPUDIATION: usize = 1;
+    pub const KEY_ENCIPHERMENT: usize = 2;
+    pub const DATA_ENCIPHERMENT: usize = 3;
+    pub const KEY_AGREEMENT: usize = 4;
+    pub const KEY_CERT_SIGN: usize = 5;
+    pub const CRL_SIGN: usize = 6;
+    pub const ENCIPHER_ONLY: usize = 7;
+    pub const DECIPHER_ONLY: usize = 8;
+
+    /// Returns whether `bit` is asserted in `key_usage`.
+    pub fn has(key_usage: &super::KeyUsage, bit: usize) -> bool {
+        key_usage.get(bit).is_some_and(|bit| *bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn certificate() -> Certificate {
+        rasn::der::decode(include_bytes!("../tests/data/letsencrypt-x3.crt")).unwrap()
+    }
+
+    #[test]
+    fn decode_certificate_extensions() {
+        let certificate = certificate();
+        let extensions = certificate.tbs_certificate.extensions.unwrap();
+
+        extensions
+            .validate(&[BASIC_CONSTRAINTS, KEY_USAGE])
+            .unwrap();
+        let constraints = extensions.basic_constraints().unwrap().unwrap();
+        assert!(constraints.ca);
+        assert_eq!(Some(0.into()), constraints.path_len_constraint);
+
+        let key_usage = extensions.key_usage().unwrap().unwrap();
+        assert!(key_usage::has(&key_usage, key_usage::KEY_CERT_SIGN));
+        assert!(key_usage::has(&key_usage, key_usage::CRL_SIGN));
+        assert!(!key_usage::has(&key_usage, key_usage::DECIPHER_ONLY));
+
+        for (extension, known) in extensions.iter_known() {
+            let known = known.unwrap().unwrap();
+            assert_eq!(known.oid(), &extension.extn_id);
+            assert_eq!(&*extension.extn_value, &*known.encode().unwrap());
+        }
+
+        assert!(matches!(
+            extensions.get_known(AUTHORITY_INFO_ACCESS),
+            Some(Ok(KnownExtension::AuthorityInfoAccess(_)))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut extensions = Extensions::from_known([
+            (
+                KnownExtension::BasicConstraints(BasicConstraints {
+                    ca: false,
+                    path_len_constraint: None,
+                }),
+                true,
+            ),
+            (KnownExtension::InhibitAnyPolicy(2.into()), true),
+        ])
+        .unwrap();
+        extensions
+            .insert(
+                &KnownExtension::SubjectKeyIdentifier(OctetString::from_static(&[1, 2, 3])),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(3, extensions.len());
+        assert_eq!(
+            &[0x30, 0x00][..],
+            &*extensions.get(BASIC_CONSTRAINTS).unwrap().extn_value
+        );
+
+        let encoded = rasn::der::encode(&extensions).unwrap();
+        let decoded: Extensions = rasn::der::decode(&encoded).unwrap();
+        assert_eq!(extensions, decoded);
+        assert_eq!(
+            Some(KnownExtension::InhibitAnyPolicy(2.into())),
+            decoded.get_known(INHIBIT_ANY_POLICY).transpose().unwrap()
+        );
+    }
+
+    #[test]
+    fn validation() {
+        let private = ObjectIdentifier::new(alloc::vec![1, 3, 6, 1, 4, 1, 99999, 1]).unwrap();
+        let unknown = Extension {
+            extn_id: private.clone(),
+            critical: true,
+            extn_value: OctetString::from_static(&[0x05, 0x00]),
+        };
+        let extensions = Extensions::from(alloc::vec![unknown.clone()]);
+        assert!(matches!(
+            extensions.validate(&[]),
+            Err(ExtensionsError::UnsupportedCritical(oid)) if oid == private
+        ));
+        assert!(extensions.validate(&[&private]).is_ok());
+
+        // Known extensions are only accepted when critical if they're
+        // supported, as decoding them doesn't mean they're processed.
+        let extensions =
+            Extensions::from_known([(KnownExtension::FreshestCrl(alloc::vec![]), true)]).unwrap();
+        assert!(matches!(
+            extensions.validate(&[]),
+            Err(ExtensionsError::UnsupportedCritical(oid)) if oid == FRESHEST_CRL
+        ));
+        assert!(extensions.validate(&[FRESHEST_CRL]).is_ok());
+
+        let extensions = Extensions::from(alloc::vec![
+            Extension {
+                critical: false,
+                ..unknown.clone()
+            },
+            Extension {
+                critical: false,
+                ..unknown
+            },
+        ]);
+        assert!(matches!(
+            extensions.validate(&[]),
+            Err(ExtensionsError::Duplicate(oid)) if oid == private
+        ));
+
+        let extensions = Extensions::from(alloc::vec![Extension {
+            extn_id: BASIC_CONSTRAINTS.into(),
+            critical: true,
+            extn_value: OctetString::from_static(&[0x04, 0x00]),
+        }]);
+        assert!(matches!(
+            extensions.validate(&[]),
+            Err(ExtensionsError::Decode { .. })
+        ));
+    }
+}
@@ -4,7 +4,11 @@
 extern crate alloc;
 
 pub mod attribute_certificate;
+pub mod builder;
+pub mod dn;
 pub mod est;
+pub mod extensions;
+pub mod validation;
 
 use rasn::{types::*, Decode, Encode};
 
@@ -15,7 +19,7 @@ pub type BaseCrlNumber = CrlNumber;
 pub type SubjectInfoAccessSyntax = SequenceOf<AccessDescription>;
 pub type AuthorityInfoAccessSyntax = SequenceOf<AccessDescription>;
 pub type FreshestCrl = CrlDistributionPoints;
-pub type InhibitAnyPolicy = CrlDistributionPoints;
+pub type InhibitAnyPolicy = SkipCerts;
 pub type KeyPurposeId = ObjectIdentifier;
 pub type ExtKeyUsageSyntax = SequenceOf<KeyPurposeId>;
 pub type ReasonFlags = BitString;
@@ -94,6 +98,11 @@ pub struct Certificate {
     pub signature_value: BitString,
 }
 
+impl rasn::pem::PemLabel for Certificate {
+    const LABEL: &'static str = "CERTIFICATE";
+    const ALTERNATIVE_LABELS: &'static [&'static str] = &["X509 CERTIFICATE", "X.509 CERTIFICATE"];
+}
+
 /// Information associated with the subject of the certificate and the CA that
 /// issued it.
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -294,6 +303,16 @@ pub struct Validity {
     pub not_after: Time,
 }
 
+impl Validity {
+    /// Creates a validity period, encoding each time as RFC 5280 requires.
+    pub fn new(not_before: GeneralizedTime, not_after: GeneralizedTime) -> Self {
+        Self {
+            not_before: Time::new(not_before),
+            not_after: Time::new(not_after),
+        }
+    }
+}
+
 /// A general time type.
 #[derive(AsnType, Clone, Copy, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[rasn(choice)]
@@ -302,6 +321,35 @@ pub enum Time {
     General(GeneralizedTime),
 }
 
+impl Time {
+    /// Returns the time encoded as RFC 5280 requires: `UTCTime` for dates
+    /// from 1950 through 2049, and `GeneralizedTime` otherwise.
+    pub fn new(time: GeneralizedTime) -> Self {
+        // 1950-01-01T00:00:00Z and 2050-01-01T00:00:00Z as Unix timestamps.
+        const UTC_TIME_RANGE: core::ops::Range<i64> = -631_152_000..2_524_608_000;
+
+        if UTC_TIME_RANGE.contains(&time.timestamp()) {
+            Self::Utc(time.into())
+        } else {
+            Self::General(time)
+        }
+    }
+
+    /// Returns the time as a [`GeneralizedTime`].
+    pub fn to_generalized_time(&self) -> GeneralizedTime {
+        match self {
+            Self::Utc(time) => (*time).into(),
+            Self::General(time) => *time,
+        }
+    }
+}
+
+impl From<GeneralizedTime> for Time {
+    fn from(time: GeneralizedTime) -> Self {
+        Self::new(time)
+    }
+}
+
 /// The subject's public key, and the algorithm used to encode it.
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SubjectPublicKeyInfo {
@@ -309,6 +357,10 @@ pub struct SubjectPublicKeyInfo {
     pub subject_public_key: BitString,
 }
 
+impl rasn::pem::PemLabel for SubjectPublicKeyInfo {
+    const LABEL: &'static str = "PUBLIC KEY";
+}
+
 /// Identifying the public key corresponding to the private key used to sign a
 /// certificate.
 ///
@@ -343,6 +395,10 @@ pub struct CertificateList {
     pub signature: BitString,
 }
 
+impl rasn::pem::PemLabel for CertificateList {
+    const LABEL: &'static str = "X509 CRL";
+}
+
 /// The list of revoked certificates along with associated metadata.
 #[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TbsCertList {
@@ -0,0 +1,1443 @@
+//! # Certification Path Validation
+//!
+//! This module implements the certification path validation algorithm from
+//! [RFC 5280 Section 6], checking that a chain of certificates leads from a
+//! trust anchor to a target certificate. It processes validity periods,
+//! basic constraints, key usage, name constraints, certificate policies,
+//! policy mappings, policy constraints, and the inhibit any-policy
+//! extension.
+//!
+//! Signature checking is delegated to a [`SignatureVerifier`] supplied by the
+//! caller, so this crate doesn't depend on any particular cryptography
+//! library. Revocation checking is not performed, and should be done by the
+//! caller on the returned path.
+//!
+//! Names are compared by their DER encoding, rather than with the full
+//! matching rules of [RFC 5280 Section 7.1].
+//!
+//! [RFC 5280 Section 6]: https://datatracker.ietf.org/doc/html/rfc5280#section-6
+//! [RFC 5280 Section 7.1]: https://datatracker.ietf.org/doc/html/rfc5280#section-7.1
+
+use alloc::{collections::BTreeSet, string::ToString, vec::Vec};
+
+use rasn::types::*;
+
+use crate::{extensions, *};
+
+/// The PKCS #9 `emailAddress` attribute, which is subject to `rfc822Name`
+/// constraints when it appears in a subject name.
+const EMAIL_ADDRESS: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 9, 1]);
+
+/// The extensions processed by path validation, which are accepted when
+/// marked critical.
+pub const PROCESSED_EXTENSIONS: &[&Oid] = &[
+    extensions::BASIC_CONSTRAINTS,
+    extensions::KEY_USAGE,
+    extensions::NAME_CONSTRAINTS,
+    extensions::SUBJECT_ALT_NAME,
+    extensions::CERTIFICATE_POLICIES,
+    extensions::POLICY_MAPPINGS,
+    extensions::POLICY_CONSTRAINTS,
+    extensions::INHIBIT_ANY_POLICY,
+];
+
+/// Verifies the signatures of certificates in a path.
+pub trait SignatureVerifier {
+    /// Returns whether `signature` is a valid signature over `message` made
+    /// with `algorithm` by the private key corresponding to `public_key`.
+    fn verify(
+        &self,
+        public_key: &SubjectPublicKeyInfo,
+        algorithm: &AlgorithmIdentifier,
+        message: &[u8],
+        signature: &[u8],
+    ) -> bool;
+}
+
+/// The inputs to path validation described in [RFC 5280 Section 6.1.1].
+///
+/// [RFC 5280 Section 6.1.1]: https://datatracker.ietf.org/doc/html/rfc5280#section-6.1.1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// The time at which the validity of the path is determined.
+    pub time: GeneralizedTime,
+    /// The policies acceptable to the caller, `anyPolicy` by default.
+    pub initial_policy_set: BTreeSet<ObjectIdentifier>,
+    /// Whether the path must be valid for at least one policy in the
+    /// initial policy set.
+    pub initial_explicit_policy: bool,
+    /// Whether policy mapping is forbidden.
+    pub initial_policy_mapping_inhibit: bool,
+    /// Whether `anyPolicy` is ignored when it appears in a certificate.
+    pub initial_any_policy_inhibit: bool,
+    /// Names that all subject names in the path must fall within.
+    pub initial_permitted_subtrees: Option<GeneralSubtrees>,
+    /// Names that no subject name in the path may fall within.
+    pub initial_excluded_subtrees: GeneralSubtrees,
+    /// Critical extensions the caller processes itself, in addition to
+    /// [`PROCESSED_EXTENSIONS`].
+    pub supported_extensions: Vec<ObjectIdentifier>,
+}
+
+impl ValidationOptions {
+    /// Creates options with the most permissive inputs, validating at `time`.
+    pub fn new(time: GeneralizedTime) -> Self {
+        Self {
+            time,
+            initial_policy_set: BTreeSet::from([extensions::ANY_POLICY.into()]),
+            initial_explicit_policy: false,
+            initial_policy_mapping_inhibit: false,
+            initial_any_policy_inhibit: false,
+            initial_permitted_subtrees: None,
+            initial_excluded_subtrees: GeneralSubtrees::new(),
+            supported_extensions: Vec::new(),
+        }
+    }
+}
+
+/// The outputs of a successful path validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatedPath {
+    /// The public key of the target certificate.
+    pub public_key: SubjectPublicKeyInfo,
+    /// The policies the path is valid for, or `None` when the valid policy
+    /// tree is empty. Contains `anyPolicy` if the path is valid for any
+    /// policy.
+    pub policies: Option<BTreeSet<ObjectIdentifier>>,
+}
+
+/// An error found while validating a certification path.
+#[derive(Debug)]
+pub struct ValidationError {
+    /// The position in the path of the certificate that failed validation,
+    /// or `None` if the failure isn't attributable to a single certificate.
+    pub index: Option<usize>,
+    /// The reason validation failed.
+    pub kind: ValidationErrorKind,
+}
+
+/// The reason a certification path failed validation.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// The path didn't contain any certificates.
+    EmptyPath,
+    /// The trust anchor doesn't contain the information required to validate
+    /// a path.
+    InvalidTrustAnchor,
+    /// The certificate's signature wasn't valid for the working public key.
+    InvalidSignature,
+    /// The `signature` field of the TBS certificate didn't match the outer
+    /// `signature_algorithm`.
+    SignatureAlgorithmMismatch,
+    /// The certificate couldn't be encoded for signature verification.
+    Encode(rasn::error::EncodeError),
+    /// The validation time is before the certificate's `not_before`.
+    NotYetValid,
+    /// The validation time is after the certificate's `not_after`.
+    Expired,
+    /// The certificate's issuer isn't the subject of the previous
+    /// certificate.
+    IssuerMismatch,
+    /// A subject name isn't within the permitted subtrees.
+    NameNotPermitted(GeneralName),
+    /// A subject name is within the excluded subtrees.
+    NameExcluded(GeneralName),
+    /// The path isn't valid for any acceptable policy, and an explicit policy
+    /// is required.
+    NoValidPolicy,
+    /// A policy mapping maps to or from `anyPolicy`.
+    InvalidPolicyMapping,
+    /// An intermediate certificate isn't a CA certificate.
+    NotCa,
+    /// The path contains more intermediate certificates than allowed.
+    PathLengthExceeded,
+    /// An intermediate certificate's key usage doesn't allow certificate
+    /// signing.
+    KeyCertSignNotAllowed,
+    /// The certificate's extensions are invalid, duplicated, or contain an
+    /// unsupported critical extension.
+    Extensions(extensions::ExtensionsError),
+}
+
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(index) = self.index {
+            write!(f, "certificate {index}: ")?;
+        }
+
+        match &self.kind {
+            ValidationErrorKind::EmptyPath => write!(f, "empty certification path"),
+            ValidationErrorKind::InvalidTrustAnchor => write!(f, "invalid trust anchor"),
+            ValidationErrorKind::InvalidSignature => write!(f, "invalid signature"),
+            ValidationErrorKind::SignatureAlgorithmMismatch => {
+                write!(f, "signature algorithm mismatch")
+            }
+            ValidationErrorKind::Encode(error) => write!(f, "{error}"),
+            ValidationErrorKind::NotYetValid => write!(f, "certificate is not yet valid"),
+            ValidationErrorKind::Expired => write!(f, "certificate has expired"),
+            ValidationErrorKind::IssuerMismatch => {
+                write!(f, "issuer doesn't match the previous subject")
+            }
+            ValidationErrorKind::NameNotPermitted(name) => {
+                write!(f, "name {name:?} is not permitted")
+            }
+            ValidationErrorKind::NameExcluded(name) => write!(f, "name {name:?} is excluded"),
+            ValidationErrorKind::NoValidPolicy => write!(f, "no valid policy"),
+            ValidationErrorKind::InvalidPolicyMapping => {
+                write!(f, "policy mapping contains anyPolicy")
+            }
+            ValidationErrorKind::NotCa => write!(f, "certificate is not a CA"),
+            ValidationErrorKind::PathLengthExceeded => write!(f, "path length exceeded"),
+            ValidationErrorKind::KeyCertSignNotAllowed => {
+                write!(f, "key usage doesn't allow certificate signing")
+            }
+            ValidationErrorKind::Extensions(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Validates the certification path `path` from `anchor` to the target
+/// certificate.
+///
+/// `path[0]` must be issued by the trust anchor, each following certificate
+/// must be issued by the one before it, and the last certificate is the
+/// target.
+pub fn validate_path(
+    anchor: &TrustAnchorChoice,
+    path: &[Certificate],
+    options: &ValidationOptions,
+    verifier: &impl SignatureVerifier,
+) -> Result<ValidatedPath, ValidationError> {
+    if path.is_empty() {
+        return Err(ValidationError {
+            index: None,
+            kind: ValidationErrorKind::EmptyPath,
+        });
+    }
+
+    let mut state = State::new(anchor, path.len(), options)
+        .map_err(|kind| ValidationError { index: None, kind })?;
+
+    for (index, certificate) in path.iter().enumerate() {
+        let is_last = index + 1 == path.len();
+        state
+            .process(index, certificate, is_last, options, verifier)
+            .map_err(|kind| ValidationError {
+                index: Some(index),
+                kind,
+            })?;
+    }
+
+    state.wrap_up().map_err(|kind| ValidationError {
+        index: Some(path.len() - 1),
+        kind,
+    })
+}
+
+/// The state variables described in RFC 5280 Section 6.1.2.
+struct State {
+    policy_tree: Option<PolicyTree>,
+    permitted_subtrees: Vec<GeneralSubtrees>,
+    excluded_subtrees: GeneralSubtrees,
+    explicit_policy: usize,
+    inhibit_any_policy: usize,
+    policy_mapping: usize,
+    working_public_key: SubjectPublicKeyInfo,
+    working_issuer_name: Name,
+    max_path_length: usize,
+    initial_policy_set: BTreeSet<ObjectIdentifier>,
+}
+
+impl State {
+    fn new(
+        anchor: &TrustAnchorChoice,
+        length: usize,
+        options: &ValidationOptions,
+    ) -> Result<Self, ValidationErrorKind> {
+        let (working_public_key, working_issuer_name, anchor_extensions, controls) = match anchor {
+            TrustAnchorChoice::Certificate(certificate) => (
+                &certificate.tbs_certificate.subject_public_key_info,
+                &certificate.tbs_certificate.subject,
+                None,
+                None,
+            ),
+            TrustAnchorChoice::TbsCertificate(tbs) => (
+                &tbs.subject_public_key_info,
+                &tbs.subject,
+                tbs.extensions.as_ref(),
+                None,
+            ),
+            TrustAnchorChoice::TrustAnchorInfo(info) => {
+                let controls = info
+                    .cert_path
+                    .as_ref()
+                    .ok_or(ValidationErrorKind::InvalidTrustAnchor)?;
+                (&info.pub_key, &controls.ta_name, None, Some(controls))
+            }
+        };
+
+        let initial = |flag: bool| if flag { 0 } else { length + 1 };
+        let mut state = Self {
+            policy_tree: Some(PolicyTree::new()),
+            permitted_subtrees: options.initial_permitted_subtrees.iter().cloned().collect(),
+            excluded_subtrees: options.initial_excluded_subtrees.clone(),
+            explicit_policy: initial(options.initial_explicit_policy),
+            inhibit_any_policy: initial(options.initial_any_policy_inhibit),
+            policy_mapping: initial(options.initial_policy_mapping_inhibit),
+            working_public_key: working_public_key.clone(),
+            working_issuer_name: working_issuer_name.clone(),
+            max_path_length: length,
+            initial_policy_set: options.initial_policy_set.clone(),
+        };
+
+        if let Some(extensions) = anchor_extensions {
+            state.apply_anchor_extensions(extensions)?;
+        }
+        if let Some(controls) = controls {
+            if let Some(policies) = &controls.policy_set {
+                state.constrain_initial_policies(policies);
+            }
+            if let Some(flags) = &controls.policy_flags {
+                let flag = |bit: usize| flags.get(bit).is_some_and(|bit| *bit);
+                if flag(0) {
+                    state.policy_mapping = 0;
+                }
+                if flag(1) {
+                    state.explicit_policy = 0;
+                }
+                if flag(2) {
+                    state.inhibit_any_policy = 0;
+                }
+            }
+            if let Some(constraints) = &controls.name_constr {
+                state.apply_name_constraints(constraints);
+            }
+            if let Some(length) = &controls.path_len_constraint {
+                state.max_path_length = state.max_path_length.min(to_usize(length));
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Applies the constraints in a TBS certificate trust anchor's
+    /// extensions, as described in RFC 5914 Section 4.
+    fn apply_anchor_extensions(
+        &mut self,
+        extensions: &Extensions,
+    ) -> Result<(), ValidationErrorKind> {
+        let decode_error = |oid: &Oid, error| {
+            ValidationErrorKind::Extensions(extensions::ExtensionsError::Decode {
+                oid: oid.into(),
+                error,
+            })
+        };
+
+        if let Some(policies) =
+            extensions.get_as::<CertificatePolicies>(extensions::CERTIFICATE_POLICIES)
+        {
+            let policies =
+                policies.map_err(|error| decode_error(extensions::CERTIFICATE_POLICIES, error))?;
+            self.constrain_initial_policies(&policies);
+        }
+        if let Some(constraints) = extensions.name_constraints() {
+            let constraints =
+                constraints.map_err(|error| decode_error(extensions::NAME_CONSTRAINTS, error))?;
+            self.apply_name_constraints(&constraints);
+        }
+        if let Some(constraints) = extensions.basic_constraints() {
+            let constraints =
+                constraints.map_err(|error| decode_error(extensions::BASIC_CONSTRAINTS, error))?;
+            if let Some(length) = &constraints.path_len_constraint {
+                self.max_path_length = self.max_path_length.min(to_usize(length));
+            }
+        }
+        self.apply_policy_constraints(extensions)
+            .map_err(|(oid, error)| decode_error(oid, error))
+    }
+
+    /// Restricts the initial policy set to those also in `policies`.
+    fn constrain_initial_policies(&mut self, policies: &CertificatePolicies) {
+        let policies = policies
+            .iter()
+            .map(|policy| policy.policy_identifier.clone())
+            .collect::<BTreeSet<_>>();
+
+        if policies.contains(extensions::ANY_POLICY) {
+            return;
+        }
+
+        self.initial_policy_set = if self.initial_policy_set.contains(extensions::ANY_POLICY) {
+            policies
+        } else {
+            self.initial_policy_set
+                .intersection(&policies)
+                .cloned()
+                .collect()
+        };
+    }
+
+    fn apply_name_constraints(&mut self, constraints: &NameConstraints) {
+        if let Some(permitted) = &constraints.permitted_subtrees {
+            self.permitted_subtrees.push(permitted.clone());
+        }
+        if let Some(excluded) = &constraints.excluded_subtrees {
+            self.excluded_subtrees.extend(excluded.iter().cloned());
+        }
+    }
+
+    /// Applies the policy constraints and inhibit any-policy extensions,
+    /// steps (i) and (j) of RFC 5280 Section 6.1.4.
+    fn apply_policy_constraints(
+        &mut self,
+        extensions: &Extensions,
+    ) -> Result<(), (&'static Oid, rasn::error::DecodeError)> {
+        if let Some(constraints) =
+            extensions.get_as::<PolicyConstraints>(extensions::POLICY_CONSTRAINTS)
+        {
+            let constraints = constraints.map_err(|e| (extensions::POLICY_CONSTRAINTS, e))?;
+            if let Some(skip) = &constraints.require_explicit_policy {
+                self.explicit_policy = self.explicit_policy.min(to_usize(skip));
+            }
+            if let Some(skip) = &constraints.inhibit_policy_mapping {
+                self.policy_mapping = self.policy_mapping.min(to_usize(skip));
+            }
+        }
+
+        if let Some(skip) = extensions.get_as::<InhibitAnyPolicy>(extensions::INHIBIT_ANY_POLICY) {
+            let skip = skip.map_err(|e| (extensions::INHIBIT_ANY_POLICY, e))?;
+            self.inhibit_any_policy = self.inhibit_any_policy.min(to_usize(&skip));
+        }
+
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        index: usize,
+        certificate: &Certificate,
+        is_last: bool,
+        options: &ValidationOptions,
+        verifier: &impl SignatureVerifier,
+    ) -> Result<(), ValidationErrorKind> {
+        let tbs = &certificate.tbs_certificate;
+        let empty = Extensions::from(Vec::new());
+        let extensions = tbs.extensions.as_ref().unwrap_or(&empty);
+        let self_issued = tbs.issuer == tbs.subject;
+        let depth = index + 1;
+
+        extensions
+            .validate(
+                &PROCESSED_EXTENSIONS
+                    .iter()
+                    .copied()
+                    .chain(options.supported_extensions.iter().map(|oid| &**oid))
+                    .collect::<Vec<_>>(),
+            )
+            .map_err(ValidationErrorKind::Extensions)?;
+        let decode_error = |oid: &Oid, error| {
+            ValidationErrorKind::Extensions(extensions::ExtensionsError::Decode {
+                oid: oid.into(),
+                error,
+            })
+        };
+
+        // 6.1.3 (a) Basic certificate processing.
+        if tbs.signature != certificate.signature_algorithm {
+            return Err(ValidationErrorKind::SignatureAlgorithmMismatch);
+        }
+        let message = rasn::der::encode(tbs).map_err(ValidationErrorKind::Encode)?;
+        if !verifier.verify(
+            &self.working_public_key,
+            &certificate.signature_algorithm,
+            &message,
+            certificate.signature_value.as_raw_slice(),
+        ) {
+            return Err(ValidationErrorKind::InvalidSignature);
+        }
+        if options.time < tbs.validity.not_before.to_generalized_time() {
+            return Err(ValidationErrorKind::NotYetValid);
+        }
+        if options.time > tbs.validity.not_after.to_generalized_time() {
+            return Err(ValidationErrorKind::Expired);
+        }
+        if tbs.issuer != self.working_issuer_name {
+            return Err(ValidationErrorKind::IssuerMismatch);
+        }
+
+        // 6.1.3 (b) and (c) Name constraints.
+        if !self_issued || is_last {
+            let alt_names = extensions
+                .subject_alt_name()
+                .transpose()
+                .map_err(|error| decode_error(extensions::SUBJECT_ALT_NAME, error))?
+                .unwrap_or_default();
+            self.check_names(&tbs.subject, &alt_names)?;
+        }
+
+        // 6.1.3 (d), (e) and (f) Certificate policies.
+        let policies = extensions
+            .get_as::<CertificatePolicies>(extensions::CERTIFICATE_POLICIES)
+            .transpose()
+            .map_err(|error| decode_error(extensions::CERTIFICATE_POLICIES, error))?;
+        match (&mut self.policy_tree, policies) {
+            (Some(tree), Some(policies)) => {
+                let allow_any = self.inhibit_any_policy > 0 || (!is_last && self_issued);
+                tree.process_policies(depth, &policies, allow_any);
+                if tree.is_empty() {
+                    self.policy_tree = None;
+                }
+            }
+            (tree, _) => *tree = None,
+        }
+        if self.explicit_policy == 0 && self.policy_tree.is_none() {
+            return Err(ValidationErrorKind::NoValidPolicy);
+        }
+
+        if is_last {
+            self.working_public_key = tbs.subject_public_key_info.clone();
+            if let Some(constraints) =
+                extensions.get_as::<PolicyConstraints>(extensions::POLICY_CONSTRAINTS)
+            {
+                let constraints = constraints
+                    .map_err(|error| decode_error(extensions::POLICY_CONSTRAINTS, error))?;
+                if constraints
+                    .require_explicit_policy
+                    .as_ref()
+                    .is_some_and(|skip| to_usize(skip) == 0)
+                {
+                    self.explicit_policy = 0;
+                }
+            }
+            return Ok(());
+        }
+
+        // 6.1.4 Preparation for the next certificate.
+        let mappings = extensions
+            .get_as::<PolicyMappings>(extensions::POLICY_MAPPINGS)
+            .transpose()
+            .map_err(|error| decode_error(extensions::POLICY_MAPPINGS, error))?;
+        if let Some(mappings) = mappings {
+            if mappings.iter().any(|mapping| {
+                mapping.issuer_domain_policy == *extensions::ANY_POLICY
+                    || mapping.subject_domain_policy == *extensions::ANY_POLICY
+            }) {
+                return Err(ValidationErrorKind::InvalidPolicyMapping);
+            }
+
+            if let Some(tree) = &mut self.policy_tree {
+                tree.process_mappings(depth, &mappings, self.policy_mapping > 0);
+                if tree.is_empty() {
+                    self.policy_tree = None;
+                }
+            }
+        }
+
+        self.working_issuer_name = tbs.subject.clone();
+        self.working_public_key = tbs.subject_public_key_info.clone();
+
+        if let Some(constraints) = extensions.name_constraints() {
+            let constraints =
+                constraints.map_err(|error| decode_error(extensions::NAME_CONSTRAINTS, error))?;
+            self.apply_name_constraints(&constraints);
+        }
+
+        if !self_issued {
+            self.explicit_policy = self.explicit_policy.saturating_sub(1);
+            self.policy_mapping = self.policy_mapping.saturating_sub(1);
+            self.inhibit_any_policy = self.inhibit_any_policy.saturating_sub(1);
+        }
+        self.apply_policy_constraints(extensions)
+            .map_err(|(oid, error)| decode_error(oid, error))?;
+
+        let basic_constraints = extensions
+            .basic_constraints()
+            .transpose()
+            .map_err(|error| decode_error(extensions::BASIC_CONSTRAINTS, error))?;
+        let Some(basic_constraints) = basic_constraints.filter(|constraints| constraints.ca) else {
+            return Err(ValidationErrorKind::NotCa);
+        };
+
+        if !self_issued {
+            self.max_path_length = self
+                .max_path_length
+                .checked_sub(1)
+                .ok_or(ValidationErrorKind::PathLengthExceeded)?;
+        }
+        if let Some(length) = &basic_constraints.path_len_constraint {
+            self.max_path_length = self.max_path_length.min(to_usize(length));
+        }
+
+        if let Some(key_usage) = extensions.key_usage() {
+            let key_usage =
+                key_usage.map_err(|error| decode_error(extensions::KEY_USAGE, error))?;
+            if !extensions::key_usage::has(&key_usage, extensions::key_usage::KEY_CERT_SIGN) {
+                return Err(ValidationErrorKind::KeyCertSignNotAllowed);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn wrap_up(mut self) -> Result<ValidatedPath, ValidationErrorKind> {
+        self.explicit_policy = self.explicit_policy.saturating_sub(1);
+
+        let policies = self
+            .policy_tree
+            .as_ref()
+            .map(|tree| tree.intersect(&self.initial_policy_set))
+            .filter(|policies| !policies.is_empty());
+
+        if self.explicit_policy == 0 && policies.is_none() {
+            return Err(ValidationErrorKind::NoValidPolicy);
+        }
+
+        Ok(ValidatedPath {
+            public_key: self.working_public_key,
+            policies,
+        })
+    }
+
+    fn check_names(
+        &self,
+        subject: &Name,
+        alt_names: &GeneralNames,
+    ) -> Result<(), ValidationErrorKind> {
+        let Name::RdnSequence(rdns) = subject;
+        let mut names = Vec::new();
+        if !rdns.is_empty() {
+            names.push(GeneralName::DirectoryName(subject.clone()));
+        }
+        if alt_names.is_empty() {
+            for attribute in rdns.iter().flat_map(|rdn| rdn.iter()) {
+                if attribute.r#type == *EMAIL_ADDRESS {
+                    if let Ok(email) = rasn::der::decode::<Ia5String>(attribute.value.as_bytes()) {
+                        names.push(GeneralName::Rfc822Name(email));
+                    }
+                }
+            }
+        }
+        names.extend(alt_names.iter().cloned());
+
+        for name in names {
+            for permitted in &self.permitted_subtrees {
+                let mut same_kind = permitted
+                    .iter()
+                    .filter_map(|subtree| matches(&name, &subtree.base))
+                    .peekable();
+                if same_kind.peek().is_some() && !same_kind.any(|matched| matched) {
+                    return Err(ValidationErrorKind::NameNotPermitted(name));
+                }
+            }
+
+            if self
+                .excluded_subtrees
+                .iter()
+                .any(|subtree| matches(&name, &subtree.base) == Some(true))
+            {
+                return Err(ValidationErrorKind::NameExcluded(name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_usize(value: &Integer) -> usize {
+    usize::try_from(value).unwrap_or(if value > &Integer::from(0) {
+        usize::MAX
+    } else {
+        0
+    })
+}
+
+/// Returns whether `name` is within the subtree rooted at `base`, or `None`
+/// if the names are of different kinds.
+fn matches(name: &GeneralName, base: &GeneralName) -> Option<bool> {
+    match (name, base) {
+        (
+            GeneralName::DirectoryName(Name::RdnSequence(name)),
+            GeneralName::DirectoryName(Name::RdnSequence(base)),
+        ) => Some(name.len() >= base.len() && name[..base.len()] == base[..]),
+        (GeneralName::DnsName(name), GeneralName::DnsName(base)) => {
+            Some(dns_matches(&name.to_string(), &base.to_string()))
+        }
+        (GeneralName::Rfc822Name(name), GeneralName::Rfc822Name(base)) => {
+            Some(email_matches(&name.to_string(), &base.to_string()))
+        }
+        (GeneralName::Uri(name), GeneralName::Uri(base)) => {
+            Some(uri_matches(&name.to_string(), &base.to_string()))
+        }
+        (GeneralName::IpAddress(name), GeneralName::IpAddress(base)) => {
+            Some(ip_matches(name, base))
+        }
+        (GeneralName::RegisteredId(name), GeneralName::RegisteredId(base)) => Some(name == base),
+        (GeneralName::OtherName(_), GeneralName::OtherName(_))
+        | (GeneralName::X400Address(_), GeneralName::X400Address(_))
+        | (GeneralName::EdiPartyName(_), GeneralName::EdiPartyName(_)) => Some(name == base),
+        _ => None,
+    }
+}
+
+fn ends_with_ignore_case(name: &str, suffix: &str) -> bool {
+    name.len() >= suffix.len()
+        && name.is_char_boundary(name.len() - suffix.len())
+        && name[name.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+}
+
+fn domain_matches(host: &str, base: &str) -> bool {
+    match base.strip_prefix('.') {
+        Some(_) => ends_with_ignore_case(host, base),
+        None => {
+            host.eq_ignore_ascii_case(base)
+                || (ends_with_ignore_case(host, base)
+                    && host.as_bytes()[host.len() - base.len() - 1] == b'.')
+        }
+    }
+}
+
+fn dns_matches(name: &str, base: &str) -> bool {
+    base.is_empty() || domain_matches(name, base)
+}
+
+fn email_matches(name: &str, base: &str) -> bool {
+    if base.contains('@') {
+        return name.eq_ignore_ascii_case(base);
+    }
+
+    let host = name.rsplit_once('@').map_or(name, |(_, host)| host);
+    match base.strip_prefix('.') {
+        Some(_) => ends_with_ignore_case(host, base),
+        None => host.eq_ignore_ascii_case(base),
+    }
+}
+
+fn uri_matches(name: &str, base: &str) -> bool {
+    let Some((_, rest)) = name.split_once("://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if host.starts_with('[') {
+        host.split_inclusive(']').next().unwrap_or(host)
+    } else {
+        host.split(':').next().unwrap_or(host)
+    };
+
+    match base.strip_prefix('.') {
+        Some(_) => ends_with_ignore_case(host, base),
+        None => host.eq_ignore_ascii_case(base),
+    }
+}
+
+fn ip_matches(name: &[u8], base: &[u8]) -> bool {
+    if base.len() != name.len() * 2 {
+        return false;
+    }
+
+    let (address, mask) = base.split_at(name.len());
+    name.iter()
+        .zip(address)
+        .zip(mask)
+        .all(|((name, address), mask)| name & mask == address & mask)
+}
+
+/// A node in the valid policy tree described in RFC 5280 Section 6.1.2.
+#[derive(Clone, Debug)]
+struct PolicyNode {
+    valid_policy: ObjectIdentifier,
+    expected_policy_set: BTreeSet<ObjectIdentifier>,
+    parent: Option<usize>,
+}
+
+/// The valid policy tree, stored as one list of nodes per depth.
+#[derive(Clone, Debug)]
+struct PolicyTree {
+    levels: Vec<Vec<PolicyNode>>,
+}
+
+impl PolicyTree {
+    fn new() -> Self {
+        let any = ObjectIdentifier::from(extensions::ANY_POLICY);
+        Self {
+            levels: alloc::vec![alloc::vec![PolicyNode {
+                expected_policy_set: BTreeSet::from([any.clone()]),
+                valid_policy: any,
+                parent: None,
+            }]],
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.levels.first().is_none_or(Vec::is_empty)
+    }
+
+    /// Steps (d)(1) to (d)(3) of RFC 5280 Section 6.1.3.
+    fn process_policies(&mut self, depth: usize, policies: &CertificatePolicies, allow_any: bool) {
+        let any = ObjectIdentifier::from(extensions::ANY_POLICY);
+        let parents = &self.levels[depth - 1];
+        let mut level = Vec::new();
+
+        for policy in policies
+            .iter()
+            .map(|policy| &policy.policy_identifier)
+            .filter(|policy| **policy != any)
+        {
+            let mut matched = false;
+            for (index, parent) in parents.iter().enumerate() {
+                if parent.expected_policy_set.contains(policy) {
+                    matched = true;
+                    level.push(PolicyNode::child(policy, index));
+                }
+            }
+
+            if !matched {
+                for (index, parent) in parents.iter().enumerate() {
+                    if parent.valid_policy == any {
+                        level.push(PolicyNode::child(policy, index));
+                    }
+                }
+            }
+        }
+
+        if allow_any
+            && policies
+                .iter()
+                .any(|policy| policy.policy_identifier == any)
+        {
+            for (index, parent) in parents.iter().enumerate() {
+                for expected in &parent.expected_policy_set {
+                    let exists = level
+                        .iter()
+                        .any(|node| node.parent == Some(index) && node.valid_policy == *expected);
+                    if !exists {
+                        level.push(PolicyNode::child(expected, index));
+                    }
+                }
+            }
+        }
+
+        self.levels.truncate(depth);
+        self.levels.push(level);
+        self.prune();
+    }
+
+    /// Step (b) of RFC 5280 Section 6.1.4.
+    fn process_mappings(&mut self, depth: usize, mappings: &PolicyMappings, allow_mapping: bool) {
+        let any = ObjectIdentifier::from(extensions::ANY_POLICY);
+        let issuer_policies = mappings
+            .iter()
+            .map(|mapping| &mapping.issuer_domain_policy)
+            .collect::<BTreeSet<_>>();
+
+        for issuer_policy in issuer_policies {
+            if allow_mapping {
+                let mapped = mappings
+                    .iter()
+                    .filter(|mapping| mapping.issuer_domain_policy == *issuer_policy)
+                    .map(|mapping| mapping.subject_domain_policy.clone())
+                    .collect::<BTreeSet<_>>();
+                let level = &mut self.levels[depth];
+                let mut found = false;
+                for node in level.iter_mut() {
+                    if node.valid_policy == *issuer_policy {
+                        node.expected_policy_set = mapped.clone();
+                        found = true;
+                    }
+                }
+
+                if !found {
+                    if let Some(any_node) = level.iter().find(|node| node.valid_policy == any) {
+                        let node = PolicyNode {
+                            valid_policy: issuer_policy.clone(),
+                            expected_policy_set: mapped,
+                            parent: any_node.parent,
+                        };
+                        level.push(node);
+                    }
+                }
+            } else {
+                self.levels[depth].retain(|node| node.valid_policy != *issuer_policy);
+                self.prune();
+            }
+        }
+    }
+
+    /// Removes every node above the deepest level that has no children.
+    fn prune(&mut self) {
+        for depth in (0..self.levels.len().saturating_sub(1)).rev() {
+            let (upper, lower) = self.levels.split_at_mut(depth + 1);
+            let (parents, children) = (&mut upper[depth], &mut lower[0]);
+
+            let mut remap = Vec::with_capacity(parents.len());
+            let mut kept = 0;
+            for index in 0..parents.len() {
+                let has_children = children.iter().any(|node| node.parent == Some(index));
+                remap.push(has_children.then(|| {
+                    kept += 1;
+                    kept - 1
+                }));
+            }
+
+            let mut index = 0;
+            parents.retain(|_| {
+                index += 1;
+                remap[index - 1].is_some()
+            });
+            for child in children.iter_mut() {
+                child.parent = child.parent.and_then(|parent| remap[parent]);
+            }
+        }
+    }
+
+    /// Step (g) of RFC 5280 Section 6.1.5, returning the policies of the
+    /// leaves that remain after intersecting with `initial`.
+    fn intersect(&self, initial: &BTreeSet<ObjectIdentifier>) -> BTreeSet<ObjectIdentifier> {
+        let any = ObjectIdentifier::from(extensions::ANY_POLICY);
+        let Some(leaves) = self.levels.last().filter(|_| self.levels.len() > 1) else {
+            return BTreeSet::new();
+        };
+
+        if initial.contains(&any) {
+            return leaves
+                .iter()
+                .map(|node| node.valid_policy.clone())
+                .collect();
+        }
+
+        let mut policies = BTreeSet::new();
+        for leaf in leaves {
+            // Find the node where this branch left the anyPolicy trunk.
+            let mut depth = self.levels.len() - 1;
+            let mut node = leaf;
+            while let Some(parent) = node.parent.map(|index| &self.levels[depth - 1][index]) {
+                if parent.valid_policy == any {
+                    break;
+                }
+                node = parent;
+                depth -= 1;
+            }
+
+            if node.valid_policy == any {
+                policies.extend(initial.iter().cloned());
+            } else if initial.contains(&node.valid_policy) {
+                policies.insert(leaf.valid_policy.clone());
+            }
+        }
+
+        policies
+    }
+}
+
+impl PolicyNode {
+    fn child(policy: &ObjectIdentifier, parent: usize) -> Self {
+        Self {
+            valid_policy: policy.clone(),
+            expected_policy_set: BTreeSet::from([policy.clone()]),
+            parent: Some(parent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extensions::KnownExtension;
+
+    /// A verifier for test certificates, whose "signature" is the issuer's
+    /// public key followed by the length of the signed message.
+    struct TestVerifier;
+
+    impl SignatureVerifier for TestVerifier {
+        fn verify(
+            &self,
+            public_key: &SubjectPublicKeyInfo,
+            _: &AlgorithmIdentifier,
+            message: &[u8],
+            signature: &[u8],
+        ) -> bool {
+            signature == sign(public_key.subject_public_key.as_raw_slice(), message)
+        }
+    }
+
+    fn sign(key: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut signature = key.to_vec();
+        signature.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        signature
+    }
+
+    fn name(cn: &str) -> Name {
+        alloc::format!("CN={cn}").parse().unwrap()
+    }
+
+    fn time(year: i32) -> GeneralizedTime {
+        use chrono::TimeZone;
+        chrono::Utc
+            .with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+            .unwrap()
+            .into()
+    }
+
+    fn key(id: u8) -> SubjectPublicKeyInfo {
+        SubjectPublicKeyInfo {
+            algorithm: AlgorithmIdentifier {
+                algorithm: Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_RSA.into(),
+                parameters: None,
+            },
+            subject_public_key: BitString::from_slice(&[id; 4]),
+        }
+    }
+
+    fn certificate(
+        subject: &str,
+        subject_key: u8,
+        issuer: &str,
+        issuer_key: u8,
+        extensions: &[(KnownExtension, bool)],
+    ) -> Certificate {
+        let algorithm = AlgorithmIdentifier {
+            algorithm: Oid::ISO_MEMBER_BODY_US_RSADSI_PKCS1_SHA1_RSA.into(),
+            parameters: None,
+        };
+        let tbs_certificate = TbsCertificate {
+            version: Version::V3,
+            serial_number: 1.into(),
+            signature: algorithm.clone(),
+            issuer: name(issuer),
+            validity: Validity {
+                not_before: Time::Utc(time(2020).into()),
+                not_after: Time::Utc(time(2030).into()),
+            },
+            subject: name(subject),
+            subject_public_key_info: key(subject_key),
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: (!extensions.is_empty())
+                .then(|| Extensions::from_known(extensions.iter().cloned()).unwrap()),
+        };
+        let message = rasn::der::encode(&tbs_certificate).unwrap();
+
+        Certificate {
+            tbs_certificate,
+            signature_algorithm: algorithm,
+            signature_value: BitString::from_vec(sign(&[issuer_key; 4], &message)),
+        }
+    }
+
+    fn ca(path_len: Option<u32>) -> (KnownExtension, bool) {
+        (
+            KnownExtension::BasicConstraints(BasicConstraints {
+                ca: true,
+                path_len_constraint: path_len.map(Into::into),
+            }),
+            true,
+        )
+    }
+
+    fn policies(oids: &[&[u32]]) -> (KnownExtension, bool) {
+        (
+            KnownExtension::CertificatePolicies(
+                oids.iter()
+                    .map(|oid| PolicyInformation {
+                        policy_identifier: ObjectIdentifier::new(oid.to_vec()).unwrap(),
+                        policy_qualifiers: None,
+                    })
+                    .collect(),
+            ),
+            false,
+        )
+    }
+
+    fn anchor() -> TrustAnchorChoice {
+        TrustAnchorChoice::Certificate(certificate("Root", 0, "Root", 0, &[ca(None)]))
+    }
+
+    fn validate(
+        path: &[Certificate],
+        options: &ValidationOptions,
+    ) -> Result<ValidatedPath, ValidationError> {
+        validate_path(&anchor(), path, options, &TestVerifier)
+    }
+
+    #[test]
+    fn simple_path() {
+        let path = [
+            certificate("Intermediate", 1, "Root", 0, &[ca(Some(0))]),
+            certificate("Leaf", 2, "Intermediate", 1, &[]),
+        ];
+
+        let validated = validate(&path, &ValidationOptions::new(time(2025))).unwrap();
+        assert_eq!(key(2), validated.public_key);
+        assert_eq!(None, validated.policies);
+    }
+
+    #[test]
+    fn basic_failures() {
+        let options = ValidationOptions::new(time(2025));
+        let intermediate = certificate("Intermediate", 1, "Root", 0, &[ca(Some(0))]);
+
+        let error = validate(&[certificate("Leaf", 2, "Root", 9, &[])], &options).unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::InvalidSignature));
+
+        let error = validate(
+            core::slice::from_ref(&intermediate),
+            &ValidationOptions::new(time(2031)),
+        )
+        .unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::Expired));
+
+        let error = validate(
+            core::slice::from_ref(&intermediate),
+            &ValidationOptions::new(time(2019)),
+        )
+        .unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::NotYetValid));
+
+        let error = validate(
+            &[
+                intermediate.clone(),
+                certificate("Leaf", 2, "Other", 1, &[]),
+            ],
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(Some(1), error.index);
+        assert!(matches!(error.kind, ValidationErrorKind::IssuerMismatch));
+
+        let error = validate(
+            &[
+                certificate("Intermediate", 1, "Root", 0, &[]),
+                certificate("Leaf", 2, "Intermediate", 1, &[]),
+            ],
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(Some(0), error.index);
+        assert!(matches!(error.kind, ValidationErrorKind::NotCa));
+
+        let error = validate(
+            &[
+                intermediate,
+                certificate("Intermediate 2", 2, "Intermediate", 1, &[ca(None)]),
+                certificate("Leaf", 3, "Intermediate 2", 2, &[]),
+            ],
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(Some(1), error.index);
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::PathLengthExceeded
+        ));
+    }
+
+    #[test]
+    fn key_usage() {
+        let mut usage = KeyUsage::repeat(false, 7);
+        usage.set(extensions::key_usage::DIGITAL_SIGNATURE, true);
+        let path = [
+            certificate(
+                "Intermediate",
+                1,
+                "Root",
+                0,
+                &[ca(None), (KnownExtension::KeyUsage(usage), true)],
+            ),
+            certificate("Leaf", 2, "Intermediate", 1, &[]),
+        ];
+
+        let error = validate(&path, &ValidationOptions::new(time(2025))).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::KeyCertSignNotAllowed
+        ));
+    }
+
+    #[test]
+    fn unknown_critical_extension() {
+        let mut leaf = certificate("Leaf", 1, "Root", 0, &[]);
+        let private = ObjectIdentifier::new(alloc::vec![1, 3, 6, 1, 4, 1, 99999]).unwrap();
+        leaf.tbs_certificate.extensions = Some(Extensions::from(alloc::vec![Extension {
+            extn_id: private.clone(),
+            critical: true,
+            extn_value: OctetString::from_static(&[5, 0]),
+        }]));
+        leaf.signature_value = BitString::from_vec(sign(
+            &[0; 4],
+            &rasn::der::encode(&leaf.tbs_certificate).unwrap(),
+        ));
+
+        let mut options = ValidationOptions::new(time(2025));
+        let error = validate(&[leaf.clone()], &options).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::Extensions(extensions::ExtensionsError::UnsupportedCritical(_))
+        ));
+
+        options.supported_extensions.push(private);
+        assert!(validate(&[leaf], &options).is_ok());
+    }
+
+    #[test]
+    fn unprocessed_critical_extension() {
+        // Freshest CRL is decodable, but isn't processed by path validation.
+        let leaf = certificate(
+            "Leaf",
+            1,
+            "Root",
+            0,
+            &[(KnownExtension::FreshestCrl(alloc::vec![]), true)],
+        );
+
+        let mut options = ValidationOptions::new(time(2025));
+        let error = validate(core::slice::from_ref(&leaf), &options).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::Extensions(extensions::ExtensionsError::UnsupportedCritical(oid))
+                if oid == extensions::FRESHEST_CRL
+        ));
+
+        options
+            .supported_extensions
+            .push(extensions::FRESHEST_CRL.into());
+        assert!(validate(&[leaf], &options).is_ok());
+    }
+
+    #[test]
+    fn name_constraints() {
+        let constraints = KnownExtension::NameConstraints(NameConstraints {
+            permitted_subtrees: Some(alloc::vec![
+                GeneralSubtree {
+                    base: GeneralName::DnsName(Ia5String::try_from("example.com").unwrap()),
+                    minimum: 0.into(),
+                    maximum: None,
+                },
+                GeneralSubtree {
+                    base: GeneralName::DirectoryName(name("Leaf")),
+                    minimum: 0.into(),
+                    maximum: None,
+                },
+            ]),
+            excluded_subtrees: Some(alloc::vec![GeneralSubtree {
+                base: GeneralName::DnsName(Ia5String::try_from("bad.example.com").unwrap()),
+                minimum: 0.into(),
+                maximum: None,
+            }]),
+        });
+        let intermediate = certificate(
+            "Intermediate",
+            1,
+            "Root",
+            0,
+            &[ca(None), (constraints, true)],
+        );
+        let leaf = |subject: &str, dns: &str| {
+            certificate(
+                subject,
+                2,
+                "Intermediate",
+                1,
+                &[(
+                    KnownExtension::SubjectAltName(alloc::vec![GeneralName::DnsName(
+                        Ia5String::try_from(dns).unwrap()
+                    )]),
+                    false,
+                )],
+            )
+        };
+        let options = ValidationOptions::new(time(2025));
+
+        assert!(validate(
+            &[intermediate.clone(), leaf("Leaf", "www.example.com")],
+            &options
+        )
+        .is_ok());
+
+        let error = validate(
+            &[intermediate.clone(), leaf("Leaf", "example.org")],
+            &options,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::NameNotPermitted(_)
+        ));
+
+        let error = validate(
+            &[intermediate.clone(), leaf("Leaf", "www.bad.example.com")],
+            &options,
+        )
+        .unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::NameExcluded(_)));
+
+        let error = validate(&[intermediate, leaf("Other", "example.com")], &options).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::NameNotPermitted(_)
+        ));
+    }
+
+    #[test]
+    fn name_matching() {
+        assert!(dns_matches("www.example.com", "example.com"));
+        assert!(dns_matches("EXAMPLE.com", "example.com"));
+        assert!(!dns_matches("badexample.com", "example.com"));
+        assert!(!dns_matches("example.com", ".example.com"));
+        assert!(email_matches("user@mail.example.com", ".example.com"));
+        assert!(email_matches("user@example.com", "example.com"));
+        assert!(!email_matches("user@example.com", "other@example.com"));
+        assert!(uri_matches(
+            "https://user@host.example.com:8443/path",
+            ".example.com"
+        ));
+        assert!(!uri_matches("https://example.com/", ".example.com"));
+        assert!(ip_matches(&[10, 1, 2, 3], &[10, 0, 0, 0, 255, 0, 0, 0]));
+        assert!(!ip_matches(&[11, 1, 2, 3], &[10, 0, 0, 0, 255, 0, 0, 0]));
+    }
+
+    #[test]
+    fn policies_and_mappings() {
+        const POLICY_A: &[u32] = &[1, 3, 6, 1, 4, 1, 99999, 1];
+        const POLICY_B: &[u32] = &[1, 3, 6, 1, 4, 1, 99999, 2];
+        const ANY: &[u32] = &[2, 5, 29, 32, 0];
+        let policy = |oid: &[u32]| ObjectIdentifier::new(oid.to_vec()).unwrap();
+        let mut options = ValidationOptions::new(time(2025));
+        options.initial_explicit_policy = true;
+
+        let mapping = (
+            KnownExtension::PolicyMappings(alloc::vec![PolicyMapping {
+                issuer_domain_policy: policy(POLICY_A),
+                subject_domain_policy: policy(POLICY_B),
+            }]),
+            false,
+        );
+        let path = [
+            certificate(
+                "Intermediate",
+                1,
+                "Root",
+                0,
+                &[ca(None), policies(&[POLICY_A]), mapping.clone()],
+            ),
+            certificate("Leaf", 2, "Intermediate", 1, &[policies(&[POLICY_B])]),
+        ];
+        let validated = validate(&path, &options).unwrap();
+        assert_eq!(Some(BTreeSet::from([policy(POLICY_B)])), validated.policies);
+
+        options.initial_policy_set = BTreeSet::from([policy(POLICY_A)]);
+        assert!(validate(&path, &options).is_ok());
+        options.initial_policy_set = BTreeSet::from([policy(POLICY_B)]);
+        let error = validate(&path, &options).unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::NoValidPolicy));
+
+        options.initial_policy_set = BTreeSet::from([policy(ANY)]);
+        options.initial_policy_mapping_inhibit = true;
+        let error = validate(&path, &options).unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::NoValidPolicy));
+
+        options.initial_policy_mapping_inhibit = false;
+        let path = [
+            certificate("Intermediate", 1, "Root", 0, &[ca(None), policies(&[ANY])]),
+            certificate("Leaf", 2, "Intermediate", 1, &[policies(&[POLICY_A])]),
+        ];
+        let validated = validate(&path, &options).unwrap();
+        assert_eq!(Some(BTreeSet::from([policy(POLICY_A)])), validated.policies);
+
+        options.initial_any_policy_inhibit = true;
+        let error = validate(&path, &options).unwrap_err();
+        assert!(matches!(error.kind, ValidationErrorKind::NoValidPolicy));
+
+        let path = [
+            certificate(
+                "Intermediate",
+                1,
+                "Root",
+                0,
+                &[
+                    ca(None),
+                    policies(&[POLICY_A]),
+                    (
+                        KnownExtension::PolicyMappings(alloc::vec![PolicyMapping {
+                            issuer_domain_policy: policy(ANY),
+                            subject_domain_policy: policy(POLICY_B),
+                        }]),
+                        false,
+                    ),
+                ],
+            ),
+            certificate("Leaf", 2, "Intermediate", 1, &[policies(&[POLICY_B])]),
+        ];
+        let error = validate(&path, &options).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::InvalidPolicyMapping
+        ));
+    }
+
+    #[test]
+    fn policy_constraints() {
+        const POLICY_A: &[u32] = &[1, 3, 6, 1, 4, 1, 99999, 1];
+        let options = ValidationOptions::new(time(2025));
+        let path = |leaf_policies: &[&[u32]]| {
+            let extensions = [
+                ca(None),
+                policies(&[POLICY_A]),
+                (
+                    KnownExtension::PolicyConstraints(PolicyConstraints {
+                        require_explicit_policy: Some(0.into()),
+                        inhibit_policy_mapping: None,
+                    }),
+                    true,
+                ),
+            ];
+            let leaf = if leaf_policies.is_empty() {
+                certificate("Leaf", 2, "Intermediate", 1, &[])
+            } else {
+                certificate("Leaf", 2, "Intermediate", 1, &[policies(leaf_policies)])
+            };
+            [certificate("Intermediate", 1, "Root", 0, &extensions), leaf]
+        };
+
+        assert!(validate(&path(&[POLICY_A]), &options).is_ok());
+        let error = validate(&path(&[]), &options).unwrap_err();
+        assert_eq!(Some(1), error.index);
+        assert!(matches!(error.kind, ValidationErrorKind::NoValidPolicy));
+    }
+
+    #[test]
+    fn trust_anchor_info() {
+        let anchor = TrustAnchorChoice::TrustAnchorInfo(TrustAnchorInfo {
+            version: 1.into(),
+            pub_key: key(0),
+            key_id: OctetString::from_static(&[0]),
+            ta_title: None,
+            cert_path: Some(CertPathControls {
+                ta_name: name("Root"),
+                certificate: None,
+                policy_set: None,
+                policy_flags: None,
+                name_constr: None,
+                path_len_constraint: Some(0.into()),
+            }),
+            exts: None,
+            ta_title_lang_tag: None,
+        });
+        let options = ValidationOptions::new(time(2025));
+
+        let leaf = certificate("Leaf", 1, "Root", 0, &[]);
+        assert!(validate_path(&anchor, &[leaf], &options, &TestVerifier).is_ok());
+
+        let path = [
+            certificate("Intermediate", 1, "Root", 0, &[ca(None)]),
+            certificate("Leaf", 2, "Intermediate", 1, &[]),
+        ];
+        let error = validate_path(&anchor, &path, &options, &TestVerifier).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::PathLengthExceeded
+        ));
+    }
+}
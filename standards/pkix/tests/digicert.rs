@@ -5,11 +5,20 @@ use rasn_pkix::*;
 
 #[test]
 fn it_works() {
-    let contents = pem::parse(include_bytes!("data/DigiCertAssuredIDTLSCA.crt.pem")).unwrap();
+    let input = include_str!("data/DigiCertAssuredIDTLSCA.crt.pem");
+    let contents = rasn::pem::parse(input).unwrap();
 
-    let cert: rasn_pkix::Certificate = rasn::der::decode(&contents.contents).unwrap();
+    let cert: rasn_pkix::Certificate = contents.decode().unwrap();
 
     assert_eq!(contents.contents, rasn::der::encode(&cert).unwrap());
+    assert_eq!(
+        cert,
+        rasn::pem::decode(&rasn::pem::encode(&cert).unwrap()).unwrap()
+    );
+    assert!(matches!(
+        rasn::pem::decode::<CertificateList>(input),
+        Err(rasn::error::PemError::UnexpectedLabel { .. })
+    ));
 }
 
 #[test]
@@ -0,0 +1,143 @@
+//! Derive macros for `rasn-smi`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Data, DeriveInput, Lit, Meta, NestedMeta};
+
+const CRATE_NAME: &str = "smi";
+
+/// An automatic derive of the `SmiIndex` trait for structs, which encodes
+/// each field in order.
+///
+/// - `#[smi(implied)]` on the last field omits its length, as if the
+///   object were declared `IMPLIED`.
+/// - `#[smi(crate_root = "path")]` on the struct sets the path to
+///   `rasn-smi`, which defaults to `smi`.
+#[proc_macro_derive(SmiIndex, attributes(smi))]
+pub fn smi_index_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    smi_index(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn smi_index(input: DeriveInput) -> syn::Result<TokenStream> {
+    let crate_root = container_attributes(&input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) if !data.fields.is_empty() => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`SmiIndex` can only be derived for structs with fields",
+            ))
+        }
+    };
+
+    let count = fields.len();
+    let mut encodes = Vec::new();
+    let mut decodes = Vec::new();
+    let mut bindings = Vec::new();
+
+    for (i, field) in fields.iter().enumerate() {
+        let implied = field_is_implied(field)?;
+        let is_last = i + 1 == count;
+        if implied && !is_last {
+            return Err(syn::Error::new(
+                field.span(),
+                "only the last field of an index can be `implied`",
+            ));
+        }
+
+        // Only the last field is affected by the index being `IMPLIED`.
+        let implied = match (implied, is_last) {
+            (true, _) => quote!(true),
+            (false, true) => quote!(implied),
+            (false, false) => quote!(false),
+        };
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(i);
+                quote!(#index)
+            }
+        };
+        let binding = format_ident!("__field{}", i);
+        let ty = &field.ty;
+
+        encodes.push(quote! {
+            #crate_root::SmiIndex::encode_index(&self.#member, #implied, arcs)?;
+        });
+        decodes.push(quote! {
+            let (#binding, arcs) = <#ty as #crate_root::SmiIndex>::decode_index(arcs, #implied)?;
+        });
+        bindings.push(quote!(#member: #binding));
+    }
+
+    Ok(quote! {
+        impl #impl_generics #crate_root::SmiIndex for #name #ty_generics #where_clause {
+            fn encode_index(
+                &self,
+                implied: bool,
+                arcs: &mut #crate_root::__private::Vec<u32>,
+            ) -> Result<(), #crate_root::IndexError> {
+                let _ = implied;
+                #(#encodes)*
+                Ok(())
+            }
+
+            fn decode_index(
+                arcs: &[u32],
+                implied: bool,
+            ) -> Result<(Self, &[u32]), #crate_root::IndexError> {
+                let _ = implied;
+                #(#decodes)*
+                Ok((Self { #(#bindings),* }, arcs))
+            }
+        }
+    })
+}
+
+fn smi_attributes(attrs: &[syn::Attribute]) -> syn::Result<Vec<NestedMeta>> {
+    let mut items = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("smi")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => items.extend(list.nested),
+            meta => return Err(syn::Error::new(meta.span(), "expected `#[smi(...)]`")),
+        }
+    }
+    Ok(items)
+}
+
+fn container_attributes(input: &DeriveInput) -> syn::Result<syn::Path> {
+    let mut crate_root = syn::Path::from(format_ident!("{}", CRATE_NAME));
+
+    for item in smi_attributes(&input.attrs)? {
+        match item {
+            NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("crate_root") => {
+                let Lit::Str(path) = &value.lit else {
+                    return Err(syn::Error::new(value.lit.span(), "expected a string"));
+                };
+                crate_root = path.parse()?;
+            }
+            item => return Err(syn::Error::new(item.span(), "unknown `smi` attribute")),
+        }
+    }
+
+    Ok(crate_root)
+}
+
+fn field_is_implied(field: &syn::Field) -> syn::Result<bool> {
+    let mut implied = false;
+
+    for item in smi_attributes(&field.attrs)? {
+        match item {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("implied") => implied = true,
+            item => return Err(syn::Error::new(item.span(), "unknown `smi` attribute")),
+        }
+    }
+
+    Ok(implied)
+}
@@ -0,0 +1,286 @@
+//! Conceptual row indices (RFC 2578 § 7.7)
+//!
+//! The instances of a table's columns are identified by appending the values
+//! of the row's `INDEX` objects to each column's identifier. [`SmiIndex`]
+//! converts values to and from those arcs:
+//!
+//! - integer-valued objects take a single arc,
+//! - fixed length strings, including `IpAddress`, take one arc per octet,
+//! - variable length strings and object identifiers take one arc per octet or
+//!   arc, preceded by their length unless the object is `IMPLIED`.
+//!
+//! - [RFC 2578 § 7.7](https://datatracker.ietf.org/doc/html/rfc2578#section-7.7): Mapping of the INDEX clause
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use rasn::types::{FixedOctetString, Integer, ObjectIdentifier, OctetString};
+
+use crate::v1::{Gauge, IpAddress, TimeTicks};
+
+/// A value of an object that appears in a conceptual row's `INDEX` clause.
+///
+/// Structs made up of index values can derive this trait, encoding each of
+/// their fields in order. The last field may be marked `#[smi(implied)]` to
+/// omit its length, as if it were declared `IMPLIED`.
+/// ```ignore
+/// #[derive(smi::SmiIndex)]
+/// struct RcvAddressIndex {
+///     if_index: u32,
+///     #[smi(implied)]
+///     address: OctetString,
+/// }
+/// ```
+pub trait SmiIndex: Sized {
+    /// Appends the arcs of `self` to `arcs`. `implied` is set for the last
+    /// object of an `IMPLIED` index, and omits the length of variable length
+    /// values.
+    fn encode_index(&self, implied: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError>;
+
+    /// Decodes a value from the start of `arcs`, returning it along with the
+    /// arcs that follow it.
+    fn decode_index(arcs: &[u32], implied: bool) -> Result<(Self, &[u32]), IndexError>;
+
+    /// Encodes `self` as the complete index of a row.
+    fn to_index(&self) -> Result<Vec<u32>, IndexError> {
+        let mut arcs = Vec::new();
+        self.encode_index(false, &mut arcs)?;
+        Ok(arcs)
+    }
+
+    /// Decodes the complete index of a row, which must use every arc.
+    fn from_index(arcs: &[u32]) -> Result<Self, IndexError> {
+        match Self::decode_index(arcs, false)? {
+            (value, []) => Ok(value),
+            _ => Err(IndexError::TrailingArcs),
+        }
+    }
+}
+
+/// An error converting a value to or from the arcs of an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IndexError {
+    /// The arcs ended before the value did.
+    Truncated,
+    /// An arc is out of range for the value's type, or a value is out of
+    /// range for an arc.
+    OutOfRange,
+    /// Arcs remained after the whole index was decoded.
+    TrailingArcs,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "index arcs ended before the value"),
+            Self::OutOfRange => write!(f, "index value is out of range"),
+            Self::TrailingArcs => write!(f, "index has trailing arcs"),
+        }
+    }
+}
+
+/// Splits the first arc off of `arcs`.
+fn first(arcs: &[u32]) -> Result<(u32, &[u32]), IndexError> {
+    arcs.split_first()
+        .map(|(arc, rest)| (*arc, rest))
+        .ok_or(IndexError::Truncated)
+}
+
+/// Splits off the arcs of a variable length value, which are preceded by
+/// their length unless `implied`, in which case they are all the remaining
+/// arcs.
+fn variable(arcs: &[u32], implied: bool) -> Result<(&[u32], &[u32]), IndexError> {
+    if implied {
+        return Ok((arcs, &[]));
+    }
+
+    let (length, arcs) = first(arcs)?;
+    let length = usize::try_from(length).map_err(|_| IndexError::OutOfRange)?;
+    if arcs.len() < length {
+        return Err(IndexError::Truncated);
+    }
+    Ok(arcs.split_at(length))
+}
+
+fn octets(arcs: &[u32]) -> Result<Vec<u8>, IndexError> {
+    arcs.iter()
+        .map(|arc| u8::try_from(*arc).map_err(|_| IndexError::OutOfRange))
+        .collect()
+}
+
+macro_rules! integer_impls {
+    ($($integer:ty),+ $(,)?) => {
+        $(
+            impl SmiIndex for $integer {
+                fn encode_index(&self, _: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+                    arcs.push(u32::try_from(*self).map_err(|_| IndexError::OutOfRange)?);
+                    Ok(())
+                }
+
+                fn decode_index(arcs: &[u32], _: bool) -> Result<(Self, &[u32]), IndexError> {
+                    let (arc, arcs) = first(arcs)?;
+                    Ok((Self::try_from(arc).map_err(|_| IndexError::OutOfRange)?, arcs))
+                }
+            }
+        )+
+    };
+}
+
+integer_impls!(u8, u16, u32, u64, i32, i64);
+
+impl SmiIndex for Integer {
+    fn encode_index(&self, _: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+        arcs.push(u32::try_from(self).map_err(|_| IndexError::OutOfRange)?);
+        Ok(())
+    }
+
+    fn decode_index(arcs: &[u32], _: bool) -> Result<(Self, &[u32]), IndexError> {
+        let (arc, arcs) = first(arcs)?;
+        Ok((arc.into(), arcs))
+    }
+}
+
+macro_rules! application_impls {
+    ($($name:ty),+ $(,)?) => {
+        $(
+            impl SmiIndex for $name {
+                fn encode_index(&self, implied: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+                    self.0.encode_index(implied, arcs)
+                }
+
+                fn decode_index(arcs: &[u32], implied: bool) -> Result<(Self, &[u32]), IndexError> {
+                    SmiIndex::decode_index(arcs, implied).map(|(value, arcs)| (Self(value), arcs))
+                }
+            }
+        )+
+    };
+}
+
+application_impls!(Gauge, TimeTicks, IpAddress);
+
+impl SmiIndex for OctetString {
+    fn encode_index(&self, implied: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+        if !implied {
+            arcs.push(u32::try_from(self.len()).map_err(|_| IndexError::OutOfRange)?);
+        }
+        arcs.extend(self.iter().map(|octet| u32::from(*octet)));
+        Ok(())
+    }
+
+    fn decode_index(arcs: &[u32], implied: bool) -> Result<(Self, &[u32]), IndexError> {
+        let (value, arcs) = variable(arcs, implied)?;
+        Ok((octets(value)?.into(), arcs))
+    }
+}
+
+/// Fixed length strings never have a length, whether or not they're
+/// `IMPLIED`.
+impl<const N: usize> SmiIndex for FixedOctetString<N> {
+    fn encode_index(&self, _: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+        arcs.extend(self.iter().map(|octet| u32::from(*octet)));
+        Ok(())
+    }
+
+    fn decode_index(arcs: &[u32], _: bool) -> Result<(Self, &[u32]), IndexError> {
+        if arcs.len() < N {
+            return Err(IndexError::Truncated);
+        }
+        let (value, arcs) = arcs.split_at(N);
+        let value = <[u8; N]>::try_from(octets(value)?).map_err(|_| IndexError::OutOfRange)?;
+        Ok((value.into(), arcs))
+    }
+}
+
+impl SmiIndex for ObjectIdentifier {
+    fn encode_index(&self, implied: bool, arcs: &mut Vec<u32>) -> Result<(), IndexError> {
+        if !implied {
+            arcs.push(u32::try_from(self.len()).map_err(|_| IndexError::OutOfRange)?);
+        }
+        arcs.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn decode_index(arcs: &[u32], implied: bool) -> Result<(Self, &[u32]), IndexError> {
+        let (value, arcs) = variable(arcs, implied)?;
+        let value = ObjectIdentifier::new(value.to_vec()).ok_or(IndexError::OutOfRange)?;
+        Ok((value, arcs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SmiIndex;
+
+    #[derive(Debug, PartialEq, SmiIndex)]
+    struct RcvAddressIndex {
+        if_index: u32,
+        #[smi(implied)]
+        address: OctetString,
+    }
+
+    #[derive(Debug, PartialEq, SmiIndex)]
+    struct ConnIndex(IpAddress, u16, IpAddress, u16);
+
+    #[derive(Debug, PartialEq, SmiIndex)]
+    struct Name(OctetString);
+
+    #[test]
+    fn integers() {
+        assert_eq!(Ok(alloc::vec![7]), 7u32.to_index());
+        assert_eq!(Ok(7), u32::from_index(&[7]));
+        assert_eq!(Ok(Integer::from(7)), Integer::from_index(&[7]));
+        assert_eq!(Err(IndexError::OutOfRange), (-1i32).to_index());
+        assert_eq!(Err(IndexError::OutOfRange), u8::from_index(&[256]));
+        assert_eq!(Err(IndexError::Truncated), u32::from_index(&[]));
+        assert_eq!(Err(IndexError::TrailingArcs), u32::from_index(&[1, 2]));
+    }
+
+    #[test]
+    fn strings() {
+        let string = OctetString::from_static(b"ab");
+        assert_eq!(Ok(alloc::vec![2, 97, 98]), string.to_index());
+        assert_eq!(Ok(string), OctetString::from_index(&[2, 97, 98]));
+        assert_eq!(
+            Err(IndexError::Truncated),
+            OctetString::from_index(&[3, 97])
+        );
+        assert_eq!(
+            Err(IndexError::OutOfRange),
+            OctetString::from_index(&[1, 300])
+        );
+
+        let address = IpAddress(FixedOctetString::from([192, 168, 0, 1]));
+        assert_eq!(Ok(alloc::vec![192, 168, 0, 1]), address.to_index());
+        assert_eq!(Ok(address), IpAddress::from_index(&[192, 168, 0, 1]));
+
+        let oid = ObjectIdentifier::new(alloc::vec![1, 3, 6]).unwrap();
+        assert_eq!(Ok(alloc::vec![3, 1, 3, 6]), oid.to_index());
+        assert_eq!(Ok(oid), ObjectIdentifier::from_index(&[3, 1, 3, 6]));
+    }
+
+    #[test]
+    fn derived() {
+        let index = RcvAddressIndex {
+            if_index: 2,
+            address: OctetString::from_static(&[0, 1, 2]),
+        };
+        assert_eq!(Ok(alloc::vec![2, 0, 1, 2]), index.to_index());
+        assert_eq!(Ok(index), RcvAddressIndex::from_index(&[2, 0, 1, 2]));
+
+        let index = ConnIndex(
+            IpAddress(FixedOctetString::from([10, 0, 0, 1])),
+            22,
+            IpAddress(FixedOctetString::from([10, 0, 0, 2])),
+            50000,
+        );
+        let arcs = alloc::vec![10, 0, 0, 1, 22, 10, 0, 0, 2, 50000];
+        assert_eq!(Ok(arcs.clone()), index.to_index());
+        assert_eq!(Ok(index), ConnIndex::from_index(&arcs));
+
+        let name = Name(OctetString::from_static(b"a"));
+        assert_eq!(Ok(alloc::vec![1, 97]), name.to_index());
+        assert_eq!(Ok(name), Name::from_index(&[1, 97]));
+    }
+}
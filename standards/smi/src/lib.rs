@@ -2,9 +2,12 @@
 #![no_std]
 
 extern crate alloc;
+// Allows the derive macros, which refer to `smi`, to be used in this crate.
+extern crate self as smi;
 
 #[macro_use]
 mod macros;
+pub mod index;
 mod object_type;
 
 pub mod v1;
@@ -13,4 +16,11 @@ pub mod v2;
 #[doc(hidden)]
 pub use rasn;
 
+pub use index::{IndexError, SmiIndex};
 pub use object_type::{Access, ObjectType, Status};
+pub use rasn_smi_derive::SmiIndex;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+}
@@ -43,7 +43,9 @@ pub enum Access {
     ReadOnly,
     WriteOnly,
     ReadWrite,
+    ReadCreate,
     NotAccessible,
+    AccessibleForNotify,
 }
 
 /// The current status of the object's implementation.
@@ -0,0 +1,382 @@
+//! An agent's Management Information Base (RFC 3416)
+//!
+//! A [`MibTree`] holds the instances of the object types an agent
+//! implements, registered through [`smi::ObjectType`], and answers `Get`,
+//! `GetNext`, `GetBulk` and `Set` requests from them in lexicographic
+//! object identifier order, enforcing each object type's `ACCESS`.
+//!
+//! - [RFC 3416 § 4.2](https://datatracker.ietf.org/doc/html/rfc3416#section-4.2): PDU Processing
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::ops::Bound;
+
+use rasn::error::EncodeError;
+use rasn::types::{ObjectIdentifier, Oid};
+use smi::{
+    v2::{ApplicationSyntax, SimpleSyntax},
+    Access, ObjectType, SmiIndex,
+};
+
+use crate::table::{self, instance, ColumnWriter, Row, RowError};
+use crate::v2::{
+    BulkPdu, GetBulkRequest, GetNextRequest, GetRequest, ObjectSyntax, Pdu, Pdus, Response,
+    SetRequest, VarBind, VarBindList, VarBindValue,
+};
+
+/// An object type registered in a tree.
+#[derive(Clone, Copy, Debug)]
+struct Registration {
+    access: Access,
+    /// Whether a value can be converted to the object type's syntax.
+    accepts: fn(&ObjectSyntax) -> bool,
+}
+
+impl Registration {
+    fn is_readable(self) -> bool {
+        matches!(
+            self.access,
+            Access::ReadOnly | Access::ReadWrite | Access::ReadCreate
+        )
+    }
+
+    fn is_writable(self) -> bool {
+        matches!(
+            self.access,
+            Access::WriteOnly | Access::ReadWrite | Access::ReadCreate
+        )
+    }
+}
+
+/// The number of variable bindings a [`MibTree`] returns for a
+/// `GetBulkRequest` unless told otherwise.
+pub const DEFAULT_MAX_BULK_BINDINGS: usize = 1024;
+
+/// The object instances of an agent.
+#[derive(Clone, Debug)]
+pub struct MibTree {
+    types: BTreeMap<ObjectIdentifier, Registration>,
+    instances: BTreeMap<ObjectIdentifier, ObjectSyntax>,
+    max_bulk_bindings: usize,
+}
+
+impl Default for MibTree {
+    fn default() -> Self {
+        Self {
+            types: BTreeMap::new(),
+            instances: BTreeMap::new(),
+            max_bulk_bindings: DEFAULT_MAX_BULK_BINDINGS,
+        }
+    }
+}
+
+impl MibTree {
+    /// MibTree constructor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the most variable bindings a `GetBulkRequest` is answered with,
+    /// [`DEFAULT_MAX_BULK_BINDINGS`] by default.
+    pub fn set_max_bulk_bindings(&mut self, max_bulk_bindings: usize) {
+        self.max_bulk_bindings = max_bulk_bindings;
+    }
+
+    /// Registers the object type `T` without any instances, so that
+    /// requests for them get `noSuchInstance` rather than `noSuchObject`.
+    pub fn register<T>(&mut self)
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        self.types.insert(
+            T::VALUE.into(),
+            Registration {
+                access: T::ACCESS,
+                accepts: |value| T::Syntax::try_from(value.clone()).is_ok(),
+            },
+        );
+    }
+
+    /// Sets the instance of `T` identified by `index`, registering `T`.
+    ///
+    /// Scalar objects have the index `[0]`, and columns of a table the index
+    /// of their row.
+    pub fn insert<T>(
+        &mut self,
+        index: &[u32],
+        value: T,
+    ) -> Result<(), <T as TryInto<T::Syntax>>::Error>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        let value = value.into_object_syntax(rasn::Codec::Ber)?;
+        self.register::<T>();
+        self.instances.insert(instance::<T>(index), value);
+        Ok(())
+    }
+
+    /// Returns the instance of `T` identified by `index`.
+    pub fn get<T>(&self, index: &[u32]) -> Option<T>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax> + TryFrom<T::Syntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        let value = self.instances.get(&instance::<T>(index))?.clone();
+        T::try_from(T::Syntax::try_from(value).ok()?).ok()
+    }
+
+    /// Removes the instance of `T` identified by `index`, returning whether
+    /// it was present.
+    pub fn remove<T>(&mut self, index: &[u32]) -> bool
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        self.instances.remove(&instance::<T>(index)).is_some()
+    }
+
+    /// Sets the instances of each column of `row`, registering the
+    /// columns.
+    pub fn insert_row<R: Row>(&mut self, row: R) -> Result<(), RowError> {
+        let index = row.index().to_index()?;
+        row.write_columns(&mut TreeWriter { tree: self, index })?;
+        Ok(())
+    }
+
+    /// Returns the row identified by `index` from the instances of its
+    /// columns.
+    pub fn row<R: Row>(&self, index: &R::Index) -> Result<R, RowError> {
+        table::read_row(&self.instances, &index.to_index()?)
+    }
+
+    /// Answers a request PDU, or returns `None` if it isn't a request.
+    pub fn handle(&mut self, pdu: &Pdus) -> Option<Pdus> {
+        let response = match pdu {
+            Pdus::GetRequest(request) => self.get_request(request),
+            Pdus::GetNextRequest(request) => self.get_next_request(request),
+            Pdus::GetBulkRequest(request) => self.get_bulk_request(request),
+            Pdus::SetRequest(request) => self.set_request(request),
+            _ => return None,
+        };
+        Some(Pdus::Response(response))
+    }
+
+    /// Answers a `GetRequest`, with `noSuchObject` or `noSuchInstance` for
+    /// the variables that aren't readable instances.
+    pub fn get_request(&self, request: &GetRequest) -> Response {
+        let variable_bindings = request
+            .0
+            .variable_bindings
+            .iter()
+            .map(|binding| VarBind {
+                name: binding.name.clone(),
+                value: self.value(&binding.name),
+            })
+            .collect();
+        response(request.0.request_id, 0, 0, variable_bindings)
+    }
+
+    /// Answers a `GetNextRequest` with the readable instances that follow
+    /// each variable, or `endOfMibView` if there are none.
+    pub fn get_next_request(&self, request: &GetNextRequest) -> Response {
+        let variable_bindings = request
+            .0
+            .variable_bindings
+            .iter()
+            .map(|binding| self.next(&binding.name))
+            .collect();
+        response(request.0.request_id, 0, 0, variable_bindings)
+    }
+
+    /// Answers a `GetBulkRequest`: the first `non_repeaters` variables as if
+    /// by `GetNextRequest`, then up to `max_repetitions` successors of each
+    /// of the rest, interleaved, stopping early once they've all reached
+    /// `endOfMibView`.
+    ///
+    /// The response is truncated to the tree's maximum number of bindings,
+    /// as RFC 3416 Section 4.2.3 allows, however many repetitions were
+    /// asked for.
+    pub fn get_bulk_request(&self, request: &GetBulkRequest) -> Response {
+        let BulkPdu {
+            request_id,
+            non_repeaters,
+            max_repetitions,
+            variable_bindings: requested,
+        } = &request.0;
+        let non_repeaters = requested.len().min(*non_repeaters as usize);
+        let max = self.max_bulk_bindings;
+
+        let mut variable_bindings: VarBindList = requested[..non_repeaters]
+            .iter()
+            .take(max)
+            .map(|binding| self.next(&binding.name))
+            .collect();
+        let mut names: Vec<ObjectIdentifier> = requested[non_repeaters..]
+            .iter()
+            .map(|binding| binding.name.clone())
+            .collect();
+        for _ in 0..*max_repetitions {
+            if names.is_empty() {
+                break;
+            }
+            let mut ended = true;
+            for name in &mut names {
+                if variable_bindings.len() == max {
+                    return response(*request_id, 0, 0, variable_bindings);
+                }
+                let binding = self.next(name);
+                ended &= binding.value == VarBindValue::EndOfMibView;
+                name.clone_from(&binding.name);
+                variable_bindings.push(binding);
+            }
+            if ended {
+                break;
+            }
+        }
+
+        response(*request_id, 0, 0, variable_bindings)
+    }
+
+    /// Answers a `SetRequest`, setting every variable or, if any of them
+    /// can't be set, none of them.
+    ///
+    /// Only existing instances of writable object types can be set to
+    /// values of their syntax; others get `notWritable`, `noCreation`,
+    /// `wrongType` or `wrongValue` errors.
+    pub fn set_request(&mut self, request: &SetRequest) -> Response {
+        let pdu = &request.0;
+        for (index, binding) in pdu.variable_bindings.iter().enumerate() {
+            if let Err(status) = self.check_set(binding) {
+                return response(
+                    pdu.request_id,
+                    status,
+                    index as u32 + 1,
+                    pdu.variable_bindings.clone(),
+                );
+            }
+        }
+
+        for binding in &pdu.variable_bindings {
+            if let VarBindValue::Value(value) = &binding.value {
+                self.instances.insert(binding.name.clone(), value.clone());
+            }
+        }
+        response(pdu.request_id, 0, 0, pdu.variable_bindings.clone())
+    }
+
+    /// Returns the registered object type that `name` is an instance of.
+    fn registration(&self, name: &Oid) -> Option<Registration> {
+        (1..=name.len())
+            .rev()
+            .find_map(|length| self.types.get(Oid::new_unchecked(&name[..length])))
+            .copied()
+    }
+
+    fn value(&self, name: &Oid) -> VarBindValue {
+        match self.registration(name) {
+            Some(registration) if registration.is_readable() => self
+                .instances
+                .get(name)
+                .map_or(VarBindValue::NoSuchInstance, |value| {
+                    VarBindValue::Value(value.clone())
+                }),
+            _ => VarBindValue::NoSuchObject,
+        }
+    }
+
+    /// Returns the first readable instance after `name`.
+    fn next(&self, name: &ObjectIdentifier) -> VarBind {
+        self.instances
+            .range::<Oid, _>((Bound::Excluded(&**name), Bound::Unbounded))
+            .find(|(name, _)| {
+                self.registration(name)
+                    .is_some_and(Registration::is_readable)
+            })
+            .map_or_else(
+                || VarBind {
+                    name: name.clone(),
+                    value: VarBindValue::EndOfMibView,
+                },
+                |(name, value)| VarBind {
+                    name: name.clone(),
+                    value: VarBindValue::Value(value.clone()),
+                },
+            )
+    }
+
+    /// Returns the error status of setting a variable, following the order
+    /// of checks in RFC 3416 § 4.2.5.
+    fn check_set(&self, binding: &VarBind) -> Result<(), u32> {
+        let registration = self
+            .registration(&binding.name)
+            .filter(|registration| registration.is_writable())
+            .ok_or(Pdu::ERROR_STATUS_NOT_WRITABLE)?;
+        let current = self.instances.get(&binding.name);
+        let VarBindValue::Value(value) = &binding.value else {
+            return Err(Pdu::ERROR_STATUS_WRONG_TYPE);
+        };
+
+        if !(registration.accepts)(value) {
+            return Err(match current {
+                Some(current) if same_type(current, value) => Pdu::ERROR_STATUS_WRONG_VALUE,
+                _ => Pdu::ERROR_STATUS_WRONG_TYPE,
+            });
+        }
+        match current {
+            Some(_) => Ok(()),
+            None => Err(Pdu::ERROR_STATUS_NO_CREATION),
+        }
+    }
+}
+
+/// Writes the columns of the row identified by `index` to a tree.
+struct TreeWriter<'a> {
+    tree: &'a mut MibTree,
+    index: Vec<u32>,
+}
+
+impl ColumnWriter for TreeWriter<'_> {
+    fn write<T>(&mut self, value: T) -> Result<(), EncodeError>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        self.tree
+            .insert(&self.index, value)
+            .map_err(|error| <EncodeError as rasn::enc::Error>::custom(error, rasn::Codec::Ber))
+    }
+}
+
+fn response(
+    request_id: i32,
+    error_status: u32,
+    error_index: u32,
+    variable_bindings: VarBindList,
+) -> Response {
+    Response(Pdu {
+        request_id,
+        error_status,
+        error_index,
+        variable_bindings,
+    })
+}
+
+/// Whether two values have the same syntax.
+fn same_type(a: &ObjectSyntax, b: &ObjectSyntax) -> bool {
+    use core::mem::discriminant;
+
+    match (a, b) {
+        (ObjectSyntax::Simple(a), ObjectSyntax::Simple(b)) => {
+            discriminant::<SimpleSyntax>(a) == discriminant(b)
+        }
+        (ObjectSyntax::ApplicationWide(a), ObjectSyntax::ApplicationWide(b)) => {
+            discriminant::<ApplicationSyntax>(a) == discriminant(b)
+        }
+        _ => false,
+    }
+}
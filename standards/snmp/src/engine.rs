@@ -0,0 +1,874 @@
+//! SNMPv3 engine discovery and timeliness (RFC 3414)
+//!
+//! A [`LocalEngine`] is the authoritative engine of an agent: it checks
+//! incoming requests against its users, engine ID and clock, answers the
+//! ones it rejects with `Report` PDUs, and secures its responses. A
+//! [`RemoteEngine`] is a manager's view of one such agent: it discovers the
+//! agent's engine ID, keeps its notion of the agent's clock synchronised, and
+//! secures requests and checks their responses.
+//!
+//! Neither does any I/O: messages come in as the bytes received, so that
+//! their authentication is checked over what the sender signed, and go out
+//! as [`Message`]s. The current time is passed in as `now`, in seconds from
+//! any monotonic clock.
+//!
+//! - [RFC 3414 § 2.3](https://datatracker.ietf.org/doc/html/rfc3414#section-2.3): Time
+//!   Synchronization
+//! - [RFC 3414 § 3.2](https://datatracker.ietf.org/doc/html/rfc3414#section-3.2): Processing an
+//!   Incoming SNMP Message
+//! - [RFC 3414 § 4](https://datatracker.ietf.org/doc/html/rfc3414#section-4): Discovery
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use rasn::types::{Integer, OctetString, Oid};
+
+use crate::{
+    usm::{engine_boots_and_time, Digester, LocalizedKeys, PrivacyCipher, UsmError},
+    v2::{Pdu, Report, VarBind, VarBindValue},
+    v3::{HeaderData, Message, Pdus, ScopedPdu, ScopedPduData, USMSecurityParameters},
+};
+
+/// The number of seconds a message's engine time may differ from the
+/// receiver's notion of it.
+pub const TIME_WINDOW: u32 = 150;
+/// The largest value of `snmpEngineBoots` and `snmpEngineTime`. An engine
+/// whose boots reach it can't send or receive authenticated messages until
+/// it's reconfigured.
+pub const MAX_ENGINE_VALUE: u32 = 2_147_483_647;
+/// The default maximum message size, the largest UDP payload over IPv4.
+const DEFAULT_MAX_SIZE: u32 = 65_507;
+
+/// An error returned when building or receiving a message.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EngineError {
+    /// The message couldn't be secured or opened.
+    Usm(UsmError),
+    /// The message's header has reserved flags or an out of range message
+    /// ID.
+    InvalidHeader,
+    /// The message was dropped for `reason`. For a local engine, `report` is
+    /// the `Report` to send back, if the message was reportable.
+    Rejected {
+        reason: ReportReason,
+        report: Option<alloc::boxed::Box<Message>>,
+    },
+    /// The message doesn't answer an outstanding request.
+    UnknownMessageId,
+    /// The remote engine's ID hasn't been discovered yet.
+    NotDiscovered,
+    /// The user has no keys for the requested security level.
+    UnsupportedSecurityLevel,
+}
+
+impl core::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Usm(error) => write!(f, "{error}"),
+            Self::InvalidHeader => write!(f, "invalid message header"),
+            Self::Rejected { reason, .. } => write!(f, "message rejected: {reason}"),
+            Self::UnknownMessageId => write!(f, "no outstanding request with the message ID"),
+            Self::NotDiscovered => write!(f, "remote engine ID not yet discovered"),
+            Self::UnsupportedSecurityLevel => write!(f, "unsupported security level"),
+        }
+    }
+}
+
+impl From<UsmError> for EngineError {
+    fn from(error: UsmError) -> Self {
+        Self::Usm(error)
+    }
+}
+
+/// Whether a message is authenticated and encrypted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SecurityLevel {
+    NoAuthNoPriv,
+    AuthNoPriv,
+    AuthPriv,
+}
+
+impl SecurityLevel {
+    /// Returns the level of a message's `flags`, or `None` if they're
+    /// missing or reserved.
+    pub fn from_flags(flags: &[u8]) -> Option<Self> {
+        match flags.first()? & (Message::AUTH_FLAG | Message::PRIV_FLAG) {
+            0 => Some(Self::NoAuthNoPriv),
+            Message::AUTH_FLAG => Some(Self::AuthNoPriv),
+            3 => Some(Self::AuthPriv),
+            _ => None,
+        }
+    }
+
+    /// The `flags` of a message at this level.
+    pub fn flags(self, reportable: bool) -> OctetString {
+        let flags = match self {
+            Self::NoAuthNoPriv => 0,
+            Self::AuthNoPriv => Message::AUTH_FLAG,
+            Self::AuthPriv => Message::AUTH_FLAG | Message::PRIV_FLAG,
+        };
+        let reportable = match reportable {
+            true => Message::REPORTABLE_FLAG,
+            false => 0,
+        };
+        vec![flags | reportable].into()
+    }
+
+    fn is_authenticated(self) -> bool {
+        self != Self::NoAuthNoPriv
+    }
+}
+
+/// Why an incoming message was dropped, each of which has a `usmStats`
+/// counter that is reported back to the sender.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ReportReason {
+    /// `usmStatsUnsupportedSecLevels`
+    UnsupportedSecLevel,
+    /// `usmStatsNotInTimeWindows`
+    NotInTimeWindow,
+    /// `usmStatsUnknownUserNames`
+    UnknownUserName,
+    /// `usmStatsUnknownEngineIDs`
+    UnknownEngineId,
+    /// `usmStatsWrongDigests`
+    WrongDigest,
+    /// `usmStatsDecryptionErrors`
+    DecryptionError,
+}
+
+impl ReportReason {
+    const ALL: [Self; 6] = [
+        Self::UnsupportedSecLevel,
+        Self::NotInTimeWindow,
+        Self::UnknownUserName,
+        Self::UnknownEngineId,
+        Self::WrongDigest,
+        Self::DecryptionError,
+    ];
+
+    /// The instance identifier of the reason's counter, under `usmStats`.
+    pub fn oid(self) -> &'static Oid {
+        match self {
+            Self::UnsupportedSecLevel => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 1, 0]),
+            Self::NotInTimeWindow => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 2, 0]),
+            Self::UnknownUserName => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 3, 0]),
+            Self::UnknownEngineId => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 4, 0]),
+            Self::WrongDigest => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 5, 0]),
+            Self::DecryptionError => Oid::const_new(&[1, 3, 6, 1, 6, 3, 15, 1, 1, 6, 0]),
+        }
+    }
+
+    /// Returns the reason whose counter is identified by `oid`.
+    pub fn from_oid(oid: &Oid) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| reason.oid() == oid)
+    }
+
+    /// The security level of the reason's report: time window reports are
+    /// authenticated, so the sender can trust the engine's clock.
+    fn report_level(self) -> SecurityLevel {
+        match self {
+            Self::NotInTimeWindow => SecurityLevel::AuthNoPriv,
+            _ => SecurityLevel::NoAuthNoPriv,
+        }
+    }
+}
+
+impl core::fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::UnsupportedSecLevel => "unsupported security level",
+            Self::NotInTimeWindow => "not in time window",
+            Self::UnknownUserName => "unknown user name",
+            Self::UnknownEngineId => "unknown engine ID",
+            Self::WrongDigest => "wrong digest",
+            Self::DecryptionError => "decryption error",
+        })
+    }
+}
+
+/// The `usmStats` counters of a local engine.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct UsmStats {
+    pub unsupported_sec_levels: u32,
+    pub not_in_time_windows: u32,
+    pub unknown_user_names: u32,
+    pub unknown_engine_ids: u32,
+    pub wrong_digests: u32,
+    pub decryption_errors: u32,
+}
+
+impl UsmStats {
+    /// Returns the counter of `reason`.
+    pub fn get(&self, reason: ReportReason) -> u32 {
+        match reason {
+            ReportReason::UnsupportedSecLevel => self.unsupported_sec_levels,
+            ReportReason::NotInTimeWindow => self.not_in_time_windows,
+            ReportReason::UnknownUserName => self.unknown_user_names,
+            ReportReason::UnknownEngineId => self.unknown_engine_ids,
+            ReportReason::WrongDigest => self.wrong_digests,
+            ReportReason::DecryptionError => self.decryption_errors,
+        }
+    }
+
+    /// Increments the counter of `reason`, returning its new value.
+    fn increment(&mut self, reason: ReportReason) -> u32 {
+        let counter = match reason {
+            ReportReason::UnsupportedSecLevel => &mut self.unsupported_sec_levels,
+            ReportReason::NotInTimeWindow => &mut self.not_in_time_windows,
+            ReportReason::UnknownUserName => &mut self.unknown_user_names,
+            ReportReason::UnknownEngineId => &mut self.unknown_engine_ids,
+            ReportReason::WrongDigest => &mut self.wrong_digests,
+            ReportReason::DecryptionError => &mut self.decryption_errors,
+        };
+        *counter = counter.wrapping_add(1);
+        *counter
+    }
+}
+
+/// A user, with their keys localised to the authoritative engine.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct User {
+    pub name: Vec<u8>,
+    /// The user's keys, or `None` if they only use noAuthNoPriv.
+    pub keys: Option<LocalizedKeys>,
+}
+
+impl User {
+    /// User constructor.
+    pub fn new(name: impl Into<Vec<u8>>, keys: Option<LocalizedKeys>) -> Self {
+        Self {
+            name: name.into(),
+            keys,
+        }
+    }
+
+    /// Whether the user has the keys needed for `level`.
+    pub fn supports(&self, level: SecurityLevel) -> bool {
+        match level {
+            SecurityLevel::NoAuthNoPriv => true,
+            SecurityLevel::AuthNoPriv => self.keys.is_some(),
+            SecurityLevel::AuthPriv => self.keys.as_ref().is_some_and(|k| k.privacy.is_some()),
+        }
+    }
+}
+
+/// A message that passed the security checks, with its scoped PDU decrypted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Incoming {
+    pub message_id: i32,
+    /// The largest message the sender can receive.
+    pub max_size: Integer,
+    pub user_name: Vec<u8>,
+    pub security_level: SecurityLevel,
+    pub reportable: bool,
+    pub scoped_pdu: ScopedPdu,
+}
+
+impl Incoming {
+    /// Returns the reason given by a `Report` PDU.
+    pub fn report_reason(&self) -> Option<ReportReason> {
+        match &self.scoped_pdu.data {
+            Pdus::Report(Report(pdu)) => pdu
+                .variable_bindings
+                .first()
+                .and_then(|binding| ReportReason::from_oid(&binding.name)),
+            _ => None,
+        }
+    }
+}
+
+/// The authoritative engine of an agent.
+#[derive(Clone, Debug)]
+pub struct LocalEngine {
+    engine_id: Vec<u8>,
+    boots: u32,
+    started: u64,
+    users: BTreeMap<Vec<u8>, User>,
+    /// The engine's `usmStats` counters.
+    pub stats: UsmStats,
+    /// The largest message the engine can receive.
+    pub max_size: u32,
+    /// The salt of the next encrypted message, which is incremented after
+    /// each one. It should start at a random value.
+    pub salt: u64,
+}
+
+impl LocalEngine {
+    /// LocalEngine constructor, for an engine started at `now`.
+    ///
+    /// `boots` is the engine's `snmpEngineBoots`, which must be kept in
+    /// non-volatile storage and incremented each time the engine starts.
+    pub fn new(engine_id: impl Into<Vec<u8>>, boots: u32, now: u64) -> Self {
+        Self {
+            engine_id: engine_id.into(),
+            boots,
+            started: now,
+            users: BTreeMap::new(),
+            stats: UsmStats::default(),
+            max_size: DEFAULT_MAX_SIZE,
+            salt: 0,
+        }
+    }
+
+    /// The engine's `snmpEngineID`.
+    pub fn engine_id(&self) -> &[u8] {
+        &self.engine_id
+    }
+
+    /// The engine's `snmpEngineBoots` and `snmpEngineTime` at `now`.
+    ///
+    /// When the time would pass its maximum, it wraps to zero and the boots
+    /// are incremented.
+    pub fn boots_and_time(&self, now: u64) -> (u32, u32) {
+        let elapsed = now.saturating_sub(self.started);
+        let period = u64::from(MAX_ENGINE_VALUE) + 1;
+        let boots = u64::from(self.boots) + elapsed / period;
+        (
+            boots.min(MAX_ENGINE_VALUE.into()) as u32,
+            (elapsed % period) as u32,
+        )
+    }
+
+    /// Adds a user, replacing any with the same name.
+    pub fn insert_user(&mut self, user: User) {
+        self.users.insert(user.name.clone(), user);
+    }
+
+    /// Returns the user named `name`.
+    pub fn user(&self, name: &[u8]) -> Option<&User> {
+        self.users.get(name)
+    }
+
+    /// Checks and opens `encoded`, a request as it was received, following
+    /// RFC 3414 § 3.2.
+    ///
+    /// A request that fails a check is counted in [`Self::stats`] and
+    /// returned as [`EngineError::Rejected`], with the `Report` to send back
+    /// if it was reportable. A request with an empty engine ID is how a
+    /// manager discovers this engine's ID.
+    pub fn receive<C: Digester + PrivacyCipher>(
+        &mut self,
+        crypto: &C,
+        encoded: &[u8],
+        now: u64,
+    ) -> Result<Incoming, EngineError> {
+        let mut message: Message = rasn::ber::decode(encoded).map_err(UsmError::from)?;
+        let parameters = message.usm_security_parameters()?;
+        let (level, reportable) = header(&message)?;
+
+        if *parameters.authoritative_engine_id != *self.engine_id {
+            return Err(self.reject(crypto, &message, None, ReportReason::UnknownEngineId, now));
+        }
+        let Some(user) = self.users.get(&*parameters.user_name).cloned() else {
+            return Err(self.reject(crypto, &message, None, ReportReason::UnknownUserName, now));
+        };
+        if !user.supports(level) {
+            return Err(self.reject(
+                crypto,
+                &message,
+                None,
+                ReportReason::UnsupportedSecLevel,
+                now,
+            ));
+        }
+
+        if let Some(keys) = user.keys.as_ref().filter(|_| level.is_authenticated()) {
+            if Message::verify_authentication(encoded, crypto, keys.auth_protocol, &keys.auth_key)
+                .is_err()
+            {
+                return Err(self.reject(crypto, &message, None, ReportReason::WrongDigest, now));
+            }
+
+            let (boots, time) = engine_boots_and_time(&parameters);
+            let (local_boots, local_time) = self.boots_and_time(now);
+            if local_boots == MAX_ENGINE_VALUE
+                || boots != local_boots
+                || time.abs_diff(local_time) > TIME_WINDOW
+            {
+                let reason = ReportReason::NotInTimeWindow;
+                return Err(self.reject(crypto, &message, Some(keys), reason, now));
+            }
+        }
+
+        if let Some((protocol, key)) = user.keys.as_ref().and_then(|keys| keys.privacy.as_ref()) {
+            if level == SecurityLevel::AuthPriv
+                && message.decrypt_scoped_pdu(crypto, *protocol, key).is_err()
+            {
+                return Err(self.reject(
+                    crypto,
+                    &message,
+                    None,
+                    ReportReason::DecryptionError,
+                    now,
+                ));
+            }
+        }
+
+        open(message, parameters, level, reportable)
+    }
+
+    /// Builds the response to `request`, secured at the request's level.
+    pub fn respond<C: Digester + PrivacyCipher>(
+        &mut self,
+        crypto: &C,
+        request: &Incoming,
+        data: Pdus,
+        now: u64,
+    ) -> Result<Message, EngineError> {
+        let user = self
+            .users
+            .get(&request.user_name)
+            .ok_or(EngineError::UnsupportedSecurityLevel)?;
+        let (boots, time) = self.boots_and_time(now);
+        let mut message = build(
+            request.message_id,
+            self.max_size,
+            request.security_level,
+            false,
+            parameters(&self.engine_id, boots, time, &user.name),
+            ScopedPdu {
+                engine_id: request.scoped_pdu.engine_id.clone(),
+                name: request.scoped_pdu.name.clone(),
+                data,
+            },
+        );
+        secure(
+            crypto,
+            &mut message,
+            user.keys.as_ref(),
+            request.security_level,
+            &mut self.salt,
+        )?;
+        Ok(message)
+    }
+
+    /// Counts a rejected message and builds its report.
+    fn reject<C: Digester + PrivacyCipher>(
+        &mut self,
+        crypto: &C,
+        message: &Message,
+        keys: Option<&LocalizedKeys>,
+        reason: ReportReason,
+        now: u64,
+    ) -> EngineError {
+        let count = self.stats.increment(reason);
+        let Ok((_, true)) = header(message) else {
+            return EngineError::Rejected {
+                reason,
+                report: None,
+            };
+        };
+
+        let (request_id, context_name) = match &message.scoped_data {
+            ScopedPduData::CleartextPdu(pdu) => (request_id(&pdu.data), pdu.name.clone()),
+            ScopedPduData::EncryptedPdu(_) => (i32::MAX, OctetString::new()),
+        };
+        let user_name = message
+            .usm_security_parameters()
+            .map(|parameters| parameters.user_name.to_vec())
+            .unwrap_or_default();
+        let (boots, time) = self.boots_and_time(now);
+        let mut report = build(
+            message_id(message).unwrap_or_default(),
+            self.max_size,
+            reason.report_level(),
+            false,
+            parameters(&self.engine_id, boots, time, &user_name),
+            ScopedPdu {
+                engine_id: self.engine_id.clone().into(),
+                name: context_name,
+                data: Pdus::Report(Report(Pdu {
+                    request_id,
+                    error_status: Pdu::ERROR_STATUS_NO_ERROR,
+                    error_index: 0,
+                    variable_bindings: vec![VarBind {
+                        name: reason.oid().into(),
+                        value: VarBindValue::Value(smi::v1::Counter(count).into()),
+                    }],
+                })),
+            },
+        );
+
+        match secure(
+            crypto,
+            &mut report,
+            keys,
+            reason.report_level(),
+            &mut self.salt,
+        ) {
+            Ok(()) => EngineError::Rejected {
+                reason,
+                report: Some(report.into()),
+            },
+            Err(error) => error,
+        }
+    }
+}
+
+/// A manager's view of an agent's authoritative engine.
+#[derive(Clone, Debug)]
+pub struct RemoteEngine {
+    engine_id: Option<Vec<u8>>,
+    boots: u32,
+    time: u32,
+    latest_received_time: u32,
+    synchronized_at: Option<u64>,
+    pending: BTreeMap<i32, (User, SecurityLevel)>,
+    next_message_id: i32,
+    /// The largest message the manager can receive.
+    pub max_size: u32,
+    /// The salt of the next encrypted message, which is incremented after
+    /// each one. It should start at a random value.
+    pub salt: u64,
+}
+
+impl RemoteEngine {
+    /// RemoteEngine constructor, for an engine whose ID isn't known yet.
+    pub fn new() -> Self {
+        Self {
+            engine_id: None,
+            boots: 0,
+            time: 0,
+            latest_received_time: 0,
+            synchronized_at: None,
+            pending: BTreeMap::new(),
+            next_message_id: 0,
+            max_size: DEFAULT_MAX_SIZE,
+            salt: 0,
+        }
+    }
+
+    /// RemoteEngine constructor, for an engine whose ID is already known.
+    pub fn with_engine_id(engine_id: impl Into<Vec<u8>>) -> Self {
+        Self {
+            engine_id: Some(engine_id.into()),
+            ..Self::new()
+        }
+    }
+
+    /// The engine's `snmpEngineID`, once discovered.
+    pub fn engine_id(&self) -> Option<&[u8]> {
+        self.engine_id.as_deref()
+    }
+
+    /// Whether the engine's clock is known, which authenticated requests
+    /// need to be accepted.
+    pub fn is_synchronized(&self) -> bool {
+        self.synchronized_at.is_some()
+    }
+
+    /// The local notion of the engine's `snmpEngineBoots` and
+    /// `snmpEngineTime` at `now`.
+    pub fn boots_and_time(&self, now: u64) -> (u32, u32) {
+        let elapsed = self
+            .synchronized_at
+            .map_or(0, |synchronized_at| now.saturating_sub(synchronized_at));
+        let time = (u64::from(self.time) + elapsed).min(MAX_ENGINE_VALUE.into());
+        (self.boots, time as u32)
+    }
+
+    /// Builds the unauthenticated request that discovers the engine's ID,
+    /// which the engine answers with a `usmStatsUnknownEngineIDs` report.
+    pub fn discovery_request(&mut self) -> Message {
+        let user = User::new(Vec::new(), None);
+        let message_id = self.track(user, SecurityLevel::NoAuthNoPriv);
+        build(
+            message_id,
+            self.max_size,
+            SecurityLevel::NoAuthNoPriv,
+            true,
+            parameters(&[], 0, 0, &[]),
+            ScopedPdu {
+                engine_id: OctetString::new(),
+                name: OctetString::new(),
+                data: Pdus::GetRequest(crate::v2::GetRequest(Pdu {
+                    request_id: message_id,
+                    error_status: Pdu::ERROR_STATUS_NO_ERROR,
+                    error_index: 0,
+                    variable_bindings: Vec::new(),
+                })),
+            },
+        )
+    }
+
+    /// Builds a reportable request from `user`, secured at `level`.
+    ///
+    /// Until the engine's clock is synchronized, an authenticated request
+    /// is answered with a `usmStatsNotInTimeWindows` report that
+    /// synchronises it, after which the request should be sent again.
+    pub fn request<C: Digester + PrivacyCipher>(
+        &mut self,
+        crypto: &C,
+        user: &User,
+        level: SecurityLevel,
+        context_name: &[u8],
+        data: Pdus,
+        now: u64,
+    ) -> Result<Message, EngineError> {
+        let engine_id = self.engine_id.clone().ok_or(EngineError::NotDiscovered)?;
+        if !user.supports(level) {
+            return Err(EngineError::UnsupportedSecurityLevel);
+        }
+
+        let message_id = self.track(user.clone(), level);
+        let (boots, time) = self.boots_and_time(now);
+        let mut message = build(
+            message_id,
+            self.max_size,
+            level,
+            true,
+            parameters(&engine_id, boots, time, &user.name),
+            ScopedPdu {
+                engine_id: engine_id.clone().into(),
+                name: context_name.to_vec().into(),
+                data,
+            },
+        );
+        if let Err(error) = secure(
+            crypto,
+            &mut message,
+            user.keys.as_ref(),
+            level,
+            &mut self.salt,
+        ) {
+            self.cancel(message_id);
+            return Err(error);
+        }
+        Ok(message)
+    }
+
+    /// Stops waiting for the response to the request with `message_id`.
+    pub fn cancel(&mut self, message_id: i32) {
+        self.pending.remove(&message_id);
+    }
+
+    /// Checks and opens `encoded`, a response or report to an outstanding
+    /// request as it was received.
+    ///
+    /// A report of the engine's ID updates [`Self::engine_id`], and an
+    /// authenticated message updates the local notion of the engine's
+    /// clock, as RFC 3414 § 3.2 step 7b describes.
+    pub fn receive<C: Digester + PrivacyCipher>(
+        &mut self,
+        crypto: &C,
+        encoded: &[u8],
+        now: u64,
+    ) -> Result<Incoming, EngineError> {
+        let mut message: Message = rasn::ber::decode(encoded).map_err(UsmError::from)?;
+        let parameters = message.usm_security_parameters()?;
+        let (level, reportable) = header(&message)?;
+        let message_id = message_id(&message)?;
+        let (user, requested_level) = self
+            .pending
+            .remove(&message_id)
+            .ok_or(EngineError::UnknownMessageId)?;
+        let rejected = |reason| EngineError::Rejected {
+            reason,
+            report: None,
+        };
+
+        if level > requested_level || !user.supports(level) {
+            return Err(rejected(ReportReason::UnsupportedSecLevel));
+        }
+        if self
+            .engine_id
+            .as_ref()
+            .is_some_and(|engine_id| **engine_id != *parameters.authoritative_engine_id)
+        {
+            return Err(rejected(ReportReason::UnknownEngineId));
+        }
+
+        if let Some(keys) = user.keys.as_ref().filter(|_| level.is_authenticated()) {
+            if *parameters.user_name != *user.name
+                || Message::verify_authentication(
+                    encoded,
+                    crypto,
+                    keys.auth_protocol,
+                    &keys.auth_key,
+                )
+                .is_err()
+            {
+                return Err(rejected(ReportReason::WrongDigest));
+            }
+            if !self.synchronize(&parameters, now) {
+                return Err(rejected(ReportReason::NotInTimeWindow));
+            }
+        }
+
+        if let Some((protocol, key)) = user.keys.as_ref().and_then(|keys| keys.privacy.as_ref()) {
+            if level == SecurityLevel::AuthPriv
+                && message.decrypt_scoped_pdu(crypto, *protocol, key).is_err()
+            {
+                return Err(rejected(ReportReason::DecryptionError));
+            }
+        }
+
+        let engine_id = parameters.authoritative_engine_id.to_vec();
+        let incoming = open(message, parameters, level, reportable)?;
+        match incoming.report_reason() {
+            // Reports are unauthenticated unless they're about the time window.
+            Some(ReportReason::UnknownEngineId) if self.engine_id.is_none() => {
+                if !engine_id.is_empty() {
+                    self.engine_id = Some(engine_id);
+                }
+            }
+            Some(_) => {}
+            None if level != requested_level => {
+                return Err(rejected(ReportReason::UnsupportedSecLevel));
+            }
+            None => {}
+        }
+        Ok(incoming)
+    }
+
+    /// Updates the local notion of the engine's clock from an authenticated
+    /// message, returning whether the message is within the time window.
+    fn synchronize(&mut self, parameters: &USMSecurityParameters, now: u64) -> bool {
+        let (boots, time) = engine_boots_and_time(parameters);
+        if boots == MAX_ENGINE_VALUE {
+            return false;
+        }
+
+        if !self.is_synchronized()
+            || boots > self.boots
+            || (boots == self.boots && time > self.latest_received_time)
+        {
+            self.boots = boots;
+            self.time = time;
+            self.latest_received_time = time;
+            self.synchronized_at = Some(now);
+            return true;
+        }
+
+        let (local_boots, local_time) = self.boots_and_time(now);
+        boots == local_boots && time.saturating_add(TIME_WINDOW) >= local_time
+    }
+
+    /// Returns an unused message ID, remembering who it's for.
+    fn track(&mut self, user: User, level: SecurityLevel) -> i32 {
+        self.next_message_id = match self.next_message_id {
+            i32::MAX => 1,
+            id => id + 1,
+        };
+        self.pending.insert(self.next_message_id, (user, level));
+        self.next_message_id
+    }
+}
+
+impl Default for RemoteEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns a message's security level and whether it's reportable.
+fn header(message: &Message) -> Result<(SecurityLevel, bool), EngineError> {
+    let flags = &message.global_data.flags;
+    let level = SecurityLevel::from_flags(flags).ok_or(EngineError::InvalidHeader)?;
+    Ok((level, flags[0] & Message::REPORTABLE_FLAG != 0))
+}
+
+fn message_id(message: &Message) -> Result<i32, EngineError> {
+    i32::try_from(&message.global_data.message_id)
+        .ok()
+        .filter(|id| *id >= 0)
+        .ok_or(EngineError::InvalidHeader)
+}
+
+/// The request ID of a PDU.
+fn request_id(data: &Pdus) -> i32 {
+    match data {
+        Pdus::GetRequest(crate::v2::GetRequest(pdu))
+        | Pdus::GetNextRequest(crate::v2::GetNextRequest(pdu))
+        | Pdus::Response(crate::v2::Response(pdu))
+        | Pdus::SetRequest(crate::v2::SetRequest(pdu))
+        | Pdus::InformRequest(crate::v2::InformRequest(pdu))
+        | Pdus::Trap(crate::v2::Trap(pdu))
+        | Pdus::Report(Report(pdu)) => pdu.request_id,
+        Pdus::GetBulkRequest(crate::v2::GetBulkRequest(pdu)) => pdu.request_id,
+    }
+}
+
+fn parameters(engine_id: &[u8], boots: u32, time: u32, user_name: &[u8]) -> USMSecurityParameters {
+    USMSecurityParameters {
+        authoritative_engine_id: engine_id.to_vec().into(),
+        authoritative_engine_boots: boots.into(),
+        authoritative_engine_time: time.into(),
+        user_name: user_name.to_vec().into(),
+        authentication_parameters: OctetString::new(),
+        privacy_parameters: OctetString::new(),
+    }
+}
+
+fn build(
+    message_id: i32,
+    max_size: u32,
+    level: SecurityLevel,
+    reportable: bool,
+    parameters: USMSecurityParameters,
+    scoped_pdu: ScopedPdu,
+) -> Message {
+    Message {
+        version: 3.into(),
+        global_data: HeaderData {
+            message_id: message_id.into(),
+            max_size: max_size.into(),
+            flags: level.flags(reportable),
+            security_model: 3.into(),
+        },
+        security_parameters: rasn::ber::encode(&parameters)
+            .expect("security parameters always encode")
+            .into(),
+        scoped_data: ScopedPduData::CleartextPdu(scoped_pdu),
+    }
+}
+
+/// Encrypts and authenticates a message as `level` requires.
+fn secure<C: Digester + PrivacyCipher>(
+    crypto: &C,
+    message: &mut Message,
+    keys: Option<&LocalizedKeys>,
+    level: SecurityLevel,
+    salt: &mut u64,
+) -> Result<(), EngineError> {
+    if level == SecurityLevel::NoAuthNoPriv {
+        return Ok(());
+    }
+    let keys = keys.ok_or(EngineError::UnsupportedSecurityLevel)?;
+    if level == SecurityLevel::AuthPriv {
+        let (protocol, key) = keys
+            .privacy
+            .as_ref()
+            .ok_or(EngineError::UnsupportedSecurityLevel)?;
+        message.encrypt_scoped_pdu(crypto, *protocol, key, *salt)?;
+        *salt = salt.wrapping_add(1);
+    }
+    message.authenticate(crypto, keys.auth_protocol, &keys.auth_key)?;
+    Ok(())
+}
+
+/// Converts a checked message into its [`Incoming`] form.
+fn open(
+    message: Message,
+    parameters: USMSecurityParameters,
+    level: SecurityLevel,
+    reportable: bool,
+) -> Result<Incoming, EngineError> {
+    let message_id = message_id(&message)?;
+    let ScopedPduData::CleartextPdu(scoped_pdu) = message.scoped_data else {
+        return Err(UsmError::DecryptionError.into());
+    };
+    Ok(Incoming {
+        message_id,
+        max_size: message.global_data.max_size,
+        user_name: parameters.user_name.to_vec(),
+        security_level: level,
+        reportable,
+        scoped_pdu,
+    })
+}
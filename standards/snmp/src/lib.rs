@@ -3,6 +3,10 @@
 
 extern crate alloc;
 
+pub mod agent;
+pub mod engine;
+pub mod table;
+pub mod usm;
 pub mod v1;
 pub mod v2;
 pub mod v2c;
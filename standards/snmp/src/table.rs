@@ -0,0 +1,417 @@
+//! Conceptual tables (RFC 2578 § 7.1.12)
+//!
+//! A [`Row`] is a conceptual row of a table, whose columns are object types
+//! instantiated by appending the row's [`SmiIndex`] to their identifiers.
+//! Rows and [`Table`]s convert to and from the variable bindings of those
+//! instances, and a [`Walk`] collects the rows of a table from the responses
+//! to successive `GetNextRequest`s.
+//!
+//! - [RFC 2578 § 7.1.12](https://datatracker.ietf.org/doc/html/rfc2578#section-7.1.12): Conceptual Tables
+//! - [RFC 3416 § 4.2.2](https://datatracker.ietf.org/doc/html/rfc3416#section-4.2.2): The GetNextRequest-PDU
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{fmt, marker::PhantomData};
+
+use rasn::{
+    error::EncodeError,
+    types::{ObjectIdentifier, Oid},
+};
+use smi::{IndexError, ObjectType, SmiIndex};
+
+use crate::v2::{GetNextRequest, ObjectSyntax, Pdu, Response, VarBind, VarBindList, VarBindValue};
+
+/// A conceptual row of a table, whose columns are object types instantiated
+/// by the row's index.
+pub trait Row: Sized {
+    /// The objects in the row's `INDEX` clause.
+    type Index: SmiIndex;
+
+    /// The identifier of the row's entry object type, such as `ifEntry`,
+    /// whose children are the row's columns.
+    const ENTRY: &'static Oid;
+
+    /// Returns the row's index.
+    fn index(&self) -> Self::Index;
+
+    /// Writes the value of each of the row's columns to `writer`.
+    fn write_columns<W: ColumnWriter>(self, writer: &mut W) -> Result<(), EncodeError>;
+
+    /// Reads the row identified by `index` from `reader`, or returns `None`
+    /// if any of its columns are missing.
+    fn read_columns<R: ColumnReader>(index: Self::Index, reader: &R) -> Option<Self>;
+
+    /// Converts the row into a binding for each of its column instances.
+    fn to_var_binds(self) -> Result<VarBindList, RowError> {
+        let mut writer = VarBindWriter {
+            index: self.index().to_index()?,
+            variable_bindings: Vec::new(),
+        };
+        self.write_columns(&mut writer)?;
+        Ok(writer.variable_bindings)
+    }
+
+    /// Converts the bindings of column instances into rows, in index order.
+    ///
+    /// Bindings outside of the table, and those without a value, are
+    /// ignored.
+    fn from_var_binds(variable_bindings: &[VarBind]) -> Result<Vec<Self>, RowError> {
+        let mut indices = BTreeSet::new();
+        let mut instances = BTreeMap::new();
+
+        for binding in variable_bindings {
+            let VarBindValue::Value(value) = &binding.value else {
+                continue;
+            };
+            let Some(index) = column_index::<Self>(&binding.name) else {
+                continue;
+            };
+            indices.insert(index.to_vec());
+            instances.insert(binding.name.clone(), value.clone());
+        }
+
+        indices
+            .iter()
+            .map(|index| read_row(&instances, index))
+            .collect()
+    }
+}
+
+/// A table, made up of conceptual rows.
+pub trait Table: Sized {
+    /// The table's rows.
+    type Row: Row;
+
+    /// Returns the table's rows.
+    fn into_rows(self) -> Vec<Self::Row>;
+
+    /// Creates a table from its rows.
+    fn from_rows(rows: Vec<Self::Row>) -> Self;
+
+    /// Converts the table into a binding for each of its rows' column
+    /// instances.
+    fn to_var_binds(self) -> Result<VarBindList, RowError> {
+        let mut variable_bindings = Vec::new();
+        for row in self.into_rows() {
+            variable_bindings.extend(row.to_var_binds()?);
+        }
+        Ok(variable_bindings)
+    }
+
+    /// Converts the bindings of column instances into a table, as by
+    /// [`Row::from_var_binds`].
+    fn from_var_binds(variable_bindings: &[VarBind]) -> Result<Self, RowError> {
+        Self::Row::from_var_binds(variable_bindings).map(Self::from_rows)
+    }
+}
+
+impl<R: Row> Table for Vec<R> {
+    type Row = R;
+
+    fn into_rows(self) -> Vec<R> {
+        self
+    }
+
+    fn from_rows(rows: Vec<R>) -> Self {
+        rows
+    }
+}
+
+impl Table for mib::interfaces::Table {
+    type Row = mib::interfaces::Entry;
+
+    fn into_rows(self) -> Vec<Self::Row> {
+        self.0
+    }
+
+    fn from_rows(rows: Vec<Self::Row>) -> Self {
+        Self(rows)
+    }
+}
+
+macro_rules! interface_columns {
+    ($callback:ident!($($args:tt)*)) => {
+        $callback!(
+            $($args)*
+            index,
+            descr,
+            r#type,
+            mtu,
+            speed,
+            phys_address,
+            admin_status,
+            oper_status,
+            last_change,
+            in_octets,
+            in_ucast_pkts,
+            in_n_ucast_pkts,
+            in_discards,
+            in_errors,
+            in_unknown_protos,
+            out_octets,
+            out_ucast_pkts,
+            out_n_ucast_pkts,
+            out_discards,
+            out_errors,
+            out_q_len,
+            specific,
+        )
+    };
+}
+
+macro_rules! write_columns {
+    ($writer:ident, $row:ident, $($column:ident),+ $(,)?) => {
+        $($writer.write($row.$column)?;)+
+    };
+}
+
+macro_rules! read_columns {
+    ($reader:ident, $($column:ident),+ $(,)?) => {
+        Self { $($column: $reader.read()?,)+ }
+    };
+}
+
+/// An interface is indexed by its `ifIndex`, which is also its first
+/// column.
+impl Row for mib::interfaces::Entry {
+    type Index = rasn::types::Integer;
+
+    const ENTRY: &'static Oid = <Self as ObjectType>::VALUE;
+
+    fn index(&self) -> Self::Index {
+        self.index.0.clone()
+    }
+
+    fn write_columns<W: ColumnWriter>(self, writer: &mut W) -> Result<(), EncodeError> {
+        let row = self;
+        interface_columns!(write_columns!(writer, row,));
+        Ok(())
+    }
+
+    fn read_columns<R: ColumnReader>(_: Self::Index, reader: &R) -> Option<Self> {
+        Some(interface_columns!(read_columns!(reader,)))
+    }
+}
+
+/// Receives the values of a row's columns.
+pub trait ColumnWriter {
+    /// Writes the value of the column `T`.
+    fn write<T>(&mut self, value: T) -> Result<(), EncodeError>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display;
+}
+
+/// Provides the values of a row's columns.
+pub trait ColumnReader {
+    /// Reads the value of the column `T`, if it's present and of the
+    /// column's syntax.
+    fn read<T>(&self) -> Option<T>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax> + TryFrom<T::Syntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display;
+}
+
+/// Collects the column instances of a row as variable bindings.
+struct VarBindWriter {
+    index: Vec<u32>,
+    variable_bindings: VarBindList,
+}
+
+impl ColumnWriter for VarBindWriter {
+    fn write<T>(&mut self, value: T) -> Result<(), EncodeError>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        let value = value
+            .into_object_syntax(rasn::Codec::Ber)
+            .map_err(|error| <EncodeError as rasn::enc::Error>::custom(error, rasn::Codec::Ber))?;
+        self.variable_bindings.push(VarBind {
+            name: instance::<T>(&self.index),
+            value: VarBindValue::Value(value),
+        });
+        Ok(())
+    }
+}
+
+/// Reads the columns of the row identified by `index` from a map of object
+/// instances.
+struct Instances<'a> {
+    instances: &'a BTreeMap<ObjectIdentifier, ObjectSyntax>,
+    index: &'a [u32],
+}
+
+impl ColumnReader for Instances<'_> {
+    fn read<T>(&self) -> Option<T>
+    where
+        T: ObjectType<SmiSyntax = ObjectSyntax> + TryFrom<T::Syntax>,
+        T::Syntax: TryFrom<ObjectSyntax>,
+        <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+    {
+        let value = self.instances.get(&instance::<T>(self.index))?.clone();
+        T::try_from(T::Syntax::try_from(value).ok()?).ok()
+    }
+}
+
+/// Reads the row identified by the arcs `index` from `instances`.
+pub(crate) fn read_row<R: Row>(
+    instances: &BTreeMap<ObjectIdentifier, ObjectSyntax>,
+    index: &[u32],
+) -> Result<R, RowError> {
+    let reader = Instances { instances, index };
+    R::read_columns(R::Index::from_index(index)?, &reader).ok_or_else(|| RowError::Incomplete {
+        index: index.to_vec(),
+    })
+}
+
+/// Returns the instance of the column `T` identified by `index`.
+pub(crate) fn instance<T>(index: &[u32]) -> ObjectIdentifier
+where
+    T: ObjectType,
+    <T as TryInto<T::Syntax>>::Error: rasn::enc::Error + core::fmt::Display,
+{
+    ObjectIdentifier::new_unchecked([&T::VALUE[..], index].concat().into())
+}
+
+/// Returns the index of `name` if it's an instance of one of `R`'s columns.
+fn column_index<R: Row>(name: &Oid) -> Option<&[u32]> {
+    match name.strip_prefix(&R::ENTRY[..])? {
+        [_column, index @ ..] if !index.is_empty() => Some(index),
+        _ => None,
+    }
+}
+
+/// Collects the rows of a table by walking its column instances with
+/// `GetNextRequest`s, starting from the table's entry.
+///
+/// ```ignore
+/// let mut walk = Walk::<interfaces::Entry>::new();
+/// while let Some(request) = walk.request(request_id) {
+///     walk.push(&send(request)?)?;
+/// }
+/// let interfaces = walk.finish()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Walk<R> {
+    next: ObjectIdentifier,
+    variable_bindings: VarBindList,
+    finished: bool,
+    row: PhantomData<R>,
+}
+
+impl<R: Row> Default for Walk<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Row> Walk<R> {
+    /// Walk constructor.
+    pub fn new() -> Self {
+        Self {
+            next: R::ENTRY.into(),
+            variable_bindings: Vec::new(),
+            finished: false,
+            row: PhantomData,
+        }
+    }
+
+    /// Whether the walk has left the table.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the next request of the walk, or `None` once it's finished.
+    pub fn request(&self, request_id: i32) -> Option<GetNextRequest> {
+        if self.finished {
+            return None;
+        }
+
+        Some(GetNextRequest(Pdu {
+            request_id,
+            error_status: 0,
+            error_index: 0,
+            variable_bindings: alloc::vec![VarBind {
+                name: self.next.clone(),
+                value: VarBindValue::Unspecified,
+            }],
+        }))
+    }
+
+    /// Records the response to the last request. The walk finishes once a
+    /// response leaves the table or reaches `endOfMibView`.
+    pub fn push(&mut self, response: &Response) -> Result<(), RowError> {
+        let pdu = &response.0;
+        if pdu.error_status != Pdu::ERROR_STATUS_NO_ERROR {
+            return Err(RowError::ErrorStatus(pdu.error_status));
+        }
+
+        let Some(binding) = pdu.variable_bindings.first() else {
+            self.finished = true;
+            return Ok(());
+        };
+        if binding.value == VarBindValue::EndOfMibView || column_index::<R>(&binding.name).is_none()
+        {
+            self.finished = true;
+            return Ok(());
+        }
+        if binding.name <= self.next {
+            return Err(RowError::NotIncreasing);
+        }
+
+        self.next.clone_from(&binding.name);
+        self.variable_bindings.push(binding.clone());
+        Ok(())
+    }
+
+    /// Returns the rows collected by the walk.
+    pub fn finish(self) -> Result<Vec<R>, RowError> {
+        R::from_var_binds(&self.variable_bindings)
+    }
+}
+
+/// An error converting rows to or from variable bindings.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RowError {
+    /// A row's index couldn't be converted to or from its arcs.
+    Index(IndexError),
+    /// A column's value couldn't be encoded.
+    Encode(EncodeError),
+    /// The row with the index `index` is missing some of its columns.
+    Incomplete { index: Vec<u32> },
+    /// A response to a `GetNextRequest` had a non-zero error status.
+    ErrorStatus(u32),
+    /// A response to a `GetNextRequest` didn't follow the requested name.
+    NotIncreasing,
+}
+
+impl From<IndexError> for RowError {
+    fn from(error: IndexError) -> Self {
+        Self::Index(error)
+    }
+}
+
+impl From<EncodeError> for RowError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(error) => write!(f, "invalid row index: {error}"),
+            Self::Encode(error) => write!(f, "failed to encode column: {error}"),
+            Self::Incomplete { index } => write!(f, "row {index:?} is missing columns"),
+            Self::ErrorStatus(status) => write!(f, "response has error status {status}"),
+            Self::NotIncreasing => write!(f, "response name doesn't follow the request"),
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! User-based Security Model authentication and privacy (RFCs 3414, 3826, 7860)
+//!
+//! Turns passwords into keys localised to an authoritative engine, signs and
+//! verifies [`Message`]s with HMAC, and encrypts and decrypts their scoped
+//! PDUs with DES or AES-128.
+//!
+//! Hashing goes through a [`Digester`] and encryption through a
+//! [`PrivacyCipher`], which the caller implements with the cryptography
+//! library of their choice; this module builds HMAC, the key localisation
+//! algorithm and the privacy protocols' initialisation vectors on top of
+//! them.
+//!
+//! - [RFC 3414](https://datatracker.ietf.org/doc/html/rfc3414): User-based Security Model (USM)
+//!   for version 3 of the Simple Network Management Protocol (SNMPv3)
+//! - [RFC 3826](https://datatracker.ietf.org/doc/html/rfc3826): The Advanced Encryption
+//!   Standard (AES) Cipher Algorithm in the SNMP User-based Security Model
+//! - [RFC 7860](https://datatracker.ietf.org/doc/html/rfc7860): HMAC-SHA-2 Authentication
+//!   Protocols in User-Based Security Model (USM) for SNMPv3
+
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::ops::Range;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    types::{ObjectIdentifier, Oid},
+};
+
+use crate::{
+    engine::MAX_ENGINE_VALUE,
+    v3::{Message, ScopedPdu, ScopedPduData, SecurityParameters, USMSecurityParameters},
+};
+
+/// The length of the string a password is repeated to fill before hashing.
+const PASSWORD_EXPANSION: usize = 1_048_576;
+
+/// Computes message digests.
+pub trait Digester {
+    /// Returns the digest of `data` with the hash function of `protocol`, or
+    /// `None` if it isn't supported.
+    fn digest(&self, protocol: AuthProtocol, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Encrypts and decrypts with the block cipher mode of a privacy protocol:
+/// DES in CBC mode, whose data is always a multiple of eight bytes, or AES in
+/// CFB mode with 128 bit segments.
+pub trait PrivacyCipher {
+    /// Encrypts `plaintext`, or returns `None` if the protocol isn't
+    /// supported.
+    fn encrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        plaintext: &[u8],
+    ) -> Option<Vec<u8>>;
+
+    /// Decrypts `ciphertext`, or returns `None` if the protocol isn't
+    /// supported.
+    fn decrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// An error returned when securing or opening a message.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum UsmError {
+    /// The digester or cipher doesn't support the protocol.
+    UnsupportedProtocol,
+    /// The message's security model isn't the User-based Security Model.
+    WrongSecurityModel,
+    /// A password was empty.
+    EmptyPassword,
+    /// A key was shorter than its protocol requires.
+    InvalidKeyLength,
+    /// The message's authentication parameters didn't match its contents.
+    AuthenticationFailure,
+    /// The scoped PDU couldn't be decrypted, or wasn't encrypted.
+    DecryptionError,
+    /// The scoped PDU was already encrypted.
+    AlreadyEncrypted,
+    /// A structure couldn't be encoded.
+    Encode(EncodeError),
+    /// A structure couldn't be decoded.
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for UsmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedProtocol => write!(f, "unsupported protocol"),
+            Self::WrongSecurityModel => write!(f, "not a User-based Security Model message"),
+            Self::EmptyPassword => write!(f, "empty password"),
+            Self::InvalidKeyLength => write!(f, "invalid key length"),
+            Self::AuthenticationFailure => write!(f, "authentication failure"),
+            Self::DecryptionError => write!(f, "decryption error"),
+            Self::AlreadyEncrypted => write!(f, "scoped PDU is already encrypted"),
+            Self::Encode(error) => write!(f, "encoding failed: {error}"),
+            Self::Decode(error) => write!(f, "decoding failed: {error}"),
+        }
+    }
+}
+
+impl From<EncodeError> for UsmError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+impl From<DecodeError> for UsmError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+/// An authentication protocol, from RFC 3414 or RFC 7860.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum AuthProtocol {
+    /// `usmHMACMD5AuthProtocol`
+    HmacMd5,
+    /// `usmHMACSHAAuthProtocol`
+    HmacSha1,
+    /// `usmHMAC128SHA224AuthProtocol`
+    HmacSha224,
+    /// `usmHMAC192SHA256AuthProtocol`
+    HmacSha256,
+    /// `usmHMAC256SHA384AuthProtocol`
+    HmacSha384,
+    /// `usmHMAC384SHA512AuthProtocol`
+    HmacSha512,
+}
+
+impl AuthProtocol {
+    const ALL: [Self; 6] = [
+        Self::HmacMd5,
+        Self::HmacSha1,
+        Self::HmacSha224,
+        Self::HmacSha256,
+        Self::HmacSha384,
+        Self::HmacSha512,
+    ];
+
+    /// The protocol's identifier, under `snmpAuthProtocols`.
+    pub fn oid(self) -> &'static Oid {
+        match self {
+            Self::HmacMd5 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 2]),
+            Self::HmacSha1 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 3]),
+            Self::HmacSha224 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 4]),
+            Self::HmacSha256 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 5]),
+            Self::HmacSha384 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 6]),
+            Self::HmacSha512 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 1, 7]),
+        }
+    }
+
+    /// Returns the protocol identified by `oid`.
+    pub fn from_oid(oid: &Oid) -> Option<Self> {
+        Self::ALL.into_iter().find(|protocol| protocol.oid() == oid)
+    }
+
+    /// The length of the protocol's digests, and so of its keys.
+    pub fn key_length(self) -> usize {
+        match self {
+            Self::HmacMd5 => 16,
+            Self::HmacSha1 => 20,
+            Self::HmacSha224 => 28,
+            Self::HmacSha256 => 32,
+            Self::HmacSha384 => 48,
+            Self::HmacSha512 => 64,
+        }
+    }
+
+    /// The length of the truncated HMAC sent as the authentication
+    /// parameters.
+    pub fn mac_length(self) -> usize {
+        match self {
+            Self::HmacMd5 | Self::HmacSha1 => 12,
+            Self::HmacSha224 => 16,
+            Self::HmacSha256 => 24,
+            Self::HmacSha384 => 32,
+            Self::HmacSha512 => 48,
+        }
+    }
+
+    fn block_size(self) -> usize {
+        match self {
+            Self::HmacSha384 | Self::HmacSha512 => 128,
+            _ => 64,
+        }
+    }
+
+    /// Derives a key from `password`, with the password to key algorithm of
+    /// RFC 3414 Appendix A.2. The result should be passed to
+    /// [`AuthProtocol::localize_key`].
+    ///
+    /// The password is expanded to a megabyte, which is allocated for the
+    /// digester to hash.
+    pub fn password_to_key(
+        self,
+        digester: &impl Digester,
+        password: &[u8],
+    ) -> Result<Vec<u8>, UsmError> {
+        if password.is_empty() {
+            return Err(UsmError::EmptyPassword);
+        }
+        let expanded = password
+            .iter()
+            .copied()
+            .cycle()
+            .take(PASSWORD_EXPANSION)
+            .collect::<Vec<_>>();
+        self.digest(digester, &expanded)
+    }
+
+    /// Localises a key to the authoritative engine `engine_id`, as
+    /// `H(key || engine_id || key)`.
+    pub fn localize_key(
+        self,
+        digester: &impl Digester,
+        key: &[u8],
+        engine_id: &[u8],
+    ) -> Result<Vec<u8>, UsmError> {
+        self.digest(digester, &[key, engine_id, key].concat())
+    }
+
+    /// Computes the HMAC of `data` with `key`, truncated to
+    /// [`AuthProtocol::mac_length`].
+    pub fn mac(
+        self,
+        digester: &impl Digester,
+        key: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, UsmError> {
+        if key.len() < self.key_length() {
+            return Err(UsmError::InvalidKeyLength);
+        }
+        let key = match key.len() > self.block_size() {
+            true => self.digest(digester, key)?,
+            false => key.to_vec(),
+        };
+        let pad = |byte: u8| {
+            let mut pad = alloc::vec![byte; self.block_size()];
+            for (pad, key) in pad.iter_mut().zip(&key) {
+                *pad ^= key;
+            }
+            pad
+        };
+
+        let inner = self.digest(digester, &[&pad(0x36), data].concat())?;
+        let mut mac = self.digest(digester, &[pad(0x5c), inner].concat())?;
+        mac.truncate(self.mac_length());
+        Ok(mac)
+    }
+
+    fn digest(self, digester: &impl Digester, data: &[u8]) -> Result<Vec<u8>, UsmError> {
+        digester
+            .digest(self, data)
+            .filter(|digest| digest.len() == self.key_length())
+            .ok_or(UsmError::UnsupportedProtocol)
+    }
+}
+
+/// A privacy protocol, from RFC 3414 or RFC 3826.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum PrivProtocol {
+    /// `usmDESPrivProtocol`, DES in CBC mode.
+    Des,
+    /// `usmAesCfb128Protocol`, AES-128 in CFB mode.
+    Aes128,
+}
+
+impl PrivProtocol {
+    /// The protocol's identifier, under `snmpPrivProtocols`.
+    pub fn oid(self) -> &'static Oid {
+        match self {
+            Self::Des => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 2, 2]),
+            Self::Aes128 => Oid::const_new(&[1, 3, 6, 1, 6, 3, 10, 1, 2, 4]),
+        }
+    }
+
+    /// Returns the protocol identified by `oid`.
+    pub fn from_oid(oid: &Oid) -> Option<Self> {
+        [Self::Des, Self::Aes128]
+            .into_iter()
+            .find(|protocol| protocol.oid() == oid)
+    }
+
+    /// The length of the localised key the protocol needs. Privacy keys are
+    /// derived with the user's authentication protocol, and the start of the
+    /// key is used.
+    pub fn key_length(self) -> usize {
+        16
+    }
+
+    /// Returns the key and initialisation vector for `key`, the engine's
+    /// boots and time, and the privacy parameters `salt`.
+    fn key_and_iv(self, key: &[u8], boots: u32, time: u32, salt: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            // The second half of the key is XORed with the salt.
+            Self::Des => (
+                key[..8].to_vec(),
+                key[8..16].iter().zip(salt).map(|(a, b)| a ^ b).collect(),
+            ),
+            Self::Aes128 => (
+                key[..16].to_vec(),
+                [&boots.to_be_bytes()[..], &time.to_be_bytes(), salt].concat(),
+            ),
+        }
+    }
+}
+
+/// A user's keys, localised to an authoritative engine.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LocalizedKeys {
+    pub auth_protocol: AuthProtocol,
+    pub auth_key: Vec<u8>,
+    /// The privacy protocol and key, which is localised with the
+    /// authentication protocol.
+    pub privacy: Option<(PrivProtocol, Vec<u8>)>,
+}
+
+impl LocalizedKeys {
+    /// LocalizedKeys constructor.
+    pub fn new(
+        auth_protocol: AuthProtocol,
+        auth_key: Vec<u8>,
+        privacy: Option<(PrivProtocol, Vec<u8>)>,
+    ) -> Self {
+        Self {
+            auth_protocol,
+            auth_key,
+            privacy,
+        }
+    }
+
+    /// Derives and localises a user's keys from their passwords.
+    pub fn from_passwords(
+        digester: &impl Digester,
+        engine_id: &[u8],
+        auth_protocol: AuthProtocol,
+        auth_password: &[u8],
+        privacy: Option<(PrivProtocol, &[u8])>,
+    ) -> Result<Self, UsmError> {
+        let localize = |password| {
+            let key = auth_protocol.password_to_key(digester, password)?;
+            auth_protocol.localize_key(digester, &key, engine_id)
+        };
+        let privacy = match privacy {
+            Some((protocol, password)) => Some((protocol, localize(password)?)),
+            None => None,
+        };
+
+        Ok(Self::new(auth_protocol, localize(auth_password)?, privacy))
+    }
+}
+
+impl Message {
+    /// The authFlag of `global_data.flags`.
+    pub const AUTH_FLAG: u8 = 0x01;
+    /// The privFlag of `global_data.flags`.
+    pub const PRIV_FLAG: u8 = 0x02;
+    /// The reportableFlag of `global_data.flags`.
+    pub const REPORTABLE_FLAG: u8 = 0x04;
+
+    /// Decodes the message's User-based Security Model parameters.
+    pub fn usm_security_parameters(&self) -> Result<USMSecurityParameters, UsmError> {
+        if self.global_data.security_model != USMSecurityParameters::ID.into() {
+            return Err(UsmError::WrongSecurityModel);
+        }
+        Ok(rasn::ber::decode(&self.security_parameters)?)
+    }
+
+    fn set_usm_security_parameters(
+        &mut self,
+        parameters: &USMSecurityParameters,
+    ) -> Result<(), UsmError> {
+        self.security_parameters = rasn::ber::encode(parameters)?.into();
+        Ok(())
+    }
+
+    /// Signs the message with `key`, setting its authentication parameters.
+    ///
+    /// This should be done last, after encrypting the scoped PDU and setting
+    /// the flags, as it covers the whole message.
+    pub fn authenticate(
+        &mut self,
+        digester: &impl Digester,
+        protocol: AuthProtocol,
+        key: &[u8],
+    ) -> Result<(), UsmError> {
+        let mut parameters = self.usm_security_parameters()?;
+        parameters.authentication_parameters = alloc::vec![0; protocol.mac_length()].into();
+        self.set_usm_security_parameters(&parameters)?;
+
+        let mac = protocol.mac(digester, key, &rasn::ber::encode(self)?)?;
+        parameters.authentication_parameters = mac.into();
+        self.set_usm_security_parameters(&parameters)
+    }
+
+    /// Checks the authentication parameters of `encoded`, a message as it
+    /// was received, against `key`.
+    ///
+    /// The HMAC is computed over `encoded` itself with the authentication
+    /// parameters zeroed, so senders that encode lengths differently from
+    /// this crate are still accepted.
+    pub fn verify_authentication(
+        encoded: &[u8],
+        digester: &impl Digester,
+        protocol: AuthProtocol,
+        key: &[u8],
+    ) -> Result<(), UsmError> {
+        rasn::ber::decode::<Message>(encoded)?.usm_security_parameters()?;
+        let range = authentication_parameters(encoded)
+            .filter(|range| range.len() == protocol.mac_length())
+            .ok_or(UsmError::AuthenticationFailure)?;
+
+        let mut data = encoded.to_vec();
+        data[range.clone()].fill(0);
+        let mac = protocol.mac(digester, key, &data)?;
+
+        // Compared in constant time.
+        match mac
+            .iter()
+            .zip(&encoded[range])
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+        {
+            0 => Ok(()),
+            _ => Err(UsmError::AuthenticationFailure),
+        }
+    }
+
+    /// Encrypts the scoped PDU with `key`, setting the privacy parameters to
+    /// `salt`.
+    ///
+    /// The salt must not be reused with the same key: RFC 3414 suggests a
+    /// counter for DES, and RFC 3826 a counter or a random number for AES.
+    /// For DES, the engine's boots are combined with the lower 32 bits of
+    /// `salt`, as RFC 3414 describes.
+    pub fn encrypt_scoped_pdu(
+        &mut self,
+        cipher: &impl PrivacyCipher,
+        protocol: PrivProtocol,
+        key: &[u8],
+        salt: u64,
+    ) -> Result<(), UsmError> {
+        let ScopedPduData::CleartextPdu(pdu) = &self.scoped_data else {
+            return Err(UsmError::AlreadyEncrypted);
+        };
+        if key.len() < protocol.key_length() {
+            return Err(UsmError::InvalidKeyLength);
+        }
+
+        let mut parameters = self.usm_security_parameters()?;
+        let (boots, time) = engine_boots_and_time(&parameters);
+        let salt = match protocol {
+            PrivProtocol::Des => [boots.to_be_bytes(), (salt as u32).to_be_bytes()].concat(),
+            PrivProtocol::Aes128 => salt.to_be_bytes().to_vec(),
+        };
+        let (key, iv) = protocol.key_and_iv(key, boots, time, &salt);
+
+        let mut plaintext = rasn::ber::encode(pdu)?;
+        if protocol == PrivProtocol::Des {
+            // Padded to the block size; the padding is ignored on decryption.
+            plaintext.resize(plaintext.len().next_multiple_of(8), 0);
+        }
+        let ciphertext = cipher
+            .encrypt(protocol, &key, &iv, &plaintext)
+            .ok_or(UsmError::UnsupportedProtocol)?;
+
+        parameters.privacy_parameters = salt.into();
+        self.set_usm_security_parameters(&parameters)?;
+        self.scoped_data = ScopedPduData::EncryptedPdu(ciphertext.into());
+        Ok(())
+    }
+
+    /// Decrypts the scoped PDU with `key`.
+    pub fn decrypt_scoped_pdu(
+        &mut self,
+        cipher: &impl PrivacyCipher,
+        protocol: PrivProtocol,
+        key: &[u8],
+    ) -> Result<(), UsmError> {
+        let ScopedPduData::EncryptedPdu(ciphertext) = &self.scoped_data else {
+            return Err(UsmError::DecryptionError);
+        };
+        if key.len() < protocol.key_length() {
+            return Err(UsmError::InvalidKeyLength);
+        }
+
+        let parameters = self.usm_security_parameters()?;
+        let salt = &parameters.privacy_parameters;
+        if salt.len() != 8 || (protocol == PrivProtocol::Des && ciphertext.len() % 8 != 0) {
+            return Err(UsmError::DecryptionError);
+        }
+        let (boots, time) = engine_boots_and_time(&parameters);
+        let (key, iv) = protocol.key_and_iv(key, boots, time, salt);
+
+        let plaintext = cipher
+            .decrypt(protocol, &key, &iv, ciphertext)
+            .ok_or(UsmError::UnsupportedProtocol)?;
+        let pdu: ScopedPdu =
+            rasn::ber::decode(&plaintext).map_err(|_| UsmError::DecryptionError)?;
+        self.scoped_data = ScopedPduData::CleartextPdu(pdu);
+        Ok(())
+    }
+}
+
+/// The authoritative engine's boots and time, which are never negative or
+/// above [`MAX_ENGINE_VALUE`] in a valid message.
+pub(crate) fn engine_boots_and_time(parameters: &USMSecurityParameters) -> (u32, u32) {
+    (
+        u32::try_from(&parameters.authoritative_engine_boots).unwrap_or(MAX_ENGINE_VALUE),
+        u32::try_from(&parameters.authoritative_engine_time).unwrap_or(MAX_ENGINE_VALUE),
+    )
+}
+
+/// Returns where the contents of msgAuthenticationParameters are in an
+/// encoded message, by walking its definite length BER framing.
+fn authentication_parameters(encoded: &[u8]) -> Option<Range<usize>> {
+    let message = contents(encoded, 0, 0x30)?;
+    // msgVersion and msgGlobalData.
+    let version = contents(encoded, message.start, 0x02)?;
+    let global_data = contents(encoded, version.end, 0x30)?;
+    let security_parameters = contents(encoded, global_data.end, 0x04)?;
+    let usm = contents(encoded, security_parameters.start, 0x30)?;
+
+    // msgAuthoritativeEngineID, msgAuthoritativeEngineBoots,
+    // msgAuthoritativeEngineTime and msgUserName.
+    let mut position = usm.start;
+    for tag in [0x04, 0x02, 0x02, 0x04] {
+        position = contents(encoded, position, tag)?.end;
+    }
+    let range = contents(encoded, position, 0x04)?;
+
+    (range.end <= usm.end
+        && usm.end <= security_parameters.end
+        && security_parameters.end <= message.end)
+        .then_some(range)
+}
+
+/// Reads the header of the value at `position`, which must have the single
+/// octet `tag` and a definite length, returning where its contents are.
+fn contents(encoded: &[u8], position: usize, tag: u8) -> Option<Range<usize>> {
+    let (&found, rest) = encoded.get(position..)?.split_first()?;
+    if found != tag {
+        return None;
+    }
+    let (header, length) = match *rest.first()? {
+        length @ 0..=0x7f => (1, usize::from(length)),
+        0x80 => return None,
+        first => {
+            let header = 1 + usize::from(first & 0x7f);
+            let length = rest
+                .get(1..header)?
+                .iter()
+                .try_fold(0usize, |length, byte| {
+                    length.checked_mul(256)?.checked_add(usize::from(*byte))
+                })?;
+            (header, length)
+        }
+    };
+
+    let start = position + 1 + header;
+    let end = start.checked_add(length)?;
+    (end <= encoded.len()).then_some(start..end)
+}
+
+/// Returns the identifier of the protocol, for use in `usmUserAuthProtocol`.
+impl From<AuthProtocol> for ObjectIdentifier {
+    fn from(protocol: AuthProtocol) -> Self {
+        protocol.oid().to_owned()
+    }
+}
+
+/// Returns the identifier of the protocol, for use in `usmUserPrivProtocol`.
+impl From<PrivProtocol> for ObjectIdentifier {
+    fn from(protocol: PrivProtocol) -> Self {
+        protocol.oid().to_owned()
+    }
+}
@@ -85,11 +85,11 @@ pub struct VarBind {
 
 #[cfg(test)]
 mod tests {
-    use super::{Message, Trap, VarBind};
+    use super::{GetResponse, Message, Pdu, Pdus, Trap, VarBind};
     use alloc::{string::String, string::ToString, vec, vec::Vec};
     use pretty_assertions::assert_eq;
-    use rasn::types::ObjectIdentifier;
-    use smi::v1::{Gauge, IpAddress, NetworkAddress, TimeTicks};
+    use rasn::types::{Integer, ObjectIdentifier};
+    use smi::v1::{Counter, Gauge, IpAddress, NetworkAddress, TimeTicks, ToOpaque};
 
     fn string_oid(oid: impl AsRef<[u32]>) -> String {
         oid.as_ref()
@@ -160,7 +160,7 @@ mod tests {
         assert_eq!(decode_msg.version, 0.into());
         assert_eq!(decode_msg.community, "public".as_bytes());
         assert_eq!(
-            string_oid(decode_msg.data.enterprise),
+            string_oid(&decode_msg.data.enterprise),
             "1.3.6.1.4.1.11779.1.42.3.7.8"
         );
         assert_eq!(
@@ -198,19 +198,166 @@ mod tests {
             },
         };
 
-        // TODO: Currently presence of any elements in `variable_bindings` throws a choice error.
-        // Encoding succeeds and is correct with that field empty. There's a smoke-test for that
-        // below for now.
         let encode_data = rasn::ber::encode(&encode_msg).unwrap();
         assert_eq!(encode_data, decode_data);
+        assert_eq!(decode_msg, encode_msg);
+    }
 
-        let encode_msg_no_bindings = Message {
-            data: Trap {
-                variable_bindings: vec![],
-                ..encode_msg.data
+    #[test]
+    fn trap_with_every_application_syntax() {
+        #[rustfmt::skip]
+        let decode_data = [
+            // SEQUENCE -> Message
+            0x30, 0x81, 0x91,
+                // INTEGER -> Message::version
+                0x02, 0x01,
+                    0x00,
+                // OCTET STRING -> Message::community
+                0x04, 0x06,
+                    // "public"
+                    0x70, 0x75, 0x62, 0x6c, 0x69, 0x63,
+                // application constructed tag 4 -> Trap
+                0xa4, 0x81, 0x83,
+                    // OID -> Trap::enterprise
+                    0x06, 0x09,
+                        // 1.3.6.1.4.1.8072.2.3
+                        0x2b, 0x06, 0x01, 0x04, 0x01, 0xbf, 0x08, 0x02, 0x03,
+                    // OCTET STRING -> Trap::agent_addr
+                    0x40, 0x04,
+                        // NetworkAddress:Internet(IpAddress(192.168.1.10))
+                        0xc0, 0xa8, 0x01, 0x0a,
+                    // INTEGER -> Trap::generic_trap
+                    0x02, 0x01,
+                        0x06,
+                    // INTEGER -> Trap::specific_trap
+                    0x02, 0x01,
+                        0x01,
+                    // application tag 3 -> TimeTicks
+                    0x43, 0x03,
+                        // 360_000
+                        0x05, 0x7e, 0x40,
+                    // SEQUENCE -> VarBindList
+                    0x30, 0x65,
+                        // SEQUENCE -> VarBind
+                        0x30, 0x11,
+                            // OID -> VarBind::name
+                            0x06, 0x0a,
+                                // 1.3.6.1.2.1.2.2.1.10.1
+                                0x2b, 0x06, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01,
+                                0x0a, 0x01,
+                            // application tag 1 -> Counter
+                            0x41, 0x03,
+                                // 123_456
+                                0x01, 0xe2, 0x40,
+                        // SEQUENCE -> VarBind
+                        0x30, 0x12,
+                            // OID -> VarBind::name
+                            0x06, 0x0a,
+                                // 1.3.6.1.2.1.2.2.1.5.1
+                                0x2b, 0x06, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01,
+                                0x05, 0x01,
+                            // application tag 2 -> Gauge
+                            0x42, 0x04,
+                                // 100_000_000
+                                0x05, 0xf5, 0xe1, 0x00,
+                        // SEQUENCE -> VarBind
+                        0x30, 0x0f,
+                            // OID -> VarBind::name
+                            0x06, 0x08,
+                                // 1.3.6.1.2.1.1.3.0
+                                0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00,
+                            // application tag 3 -> TimeTicks
+                            0x43, 0x03,
+                                // 360_000
+                                0x05, 0x7e, 0x40,
+                        // SEQUENCE -> VarBind
+                        0x30, 0x17,
+                            // OID -> VarBind::name
+                            0x06, 0x0f,
+                                // 1.3.6.1.2.1.4.20.1.1.192.168.1.10
+                                0x2b, 0x06, 0x01, 0x02, 0x01, 0x04, 0x14, 0x01,
+                                0x01, 0x81, 0x40, 0x81, 0x28, 0x01, 0x0a,
+                            // application tag 0 -> IpAddress
+                            0x40, 0x04,
+                                // 192.168.1.10
+                                0xc0, 0xa8, 0x01, 0x0a,
+                        // SEQUENCE -> VarBind
+                        0x30, 0x12,
+                            // OID -> VarBind::name
+                            0x06, 0x0b,
+                                // 1.3.6.1.4.1.8072.2.3.2.1
+                                0x2b, 0x06, 0x01, 0x04, 0x01, 0xbf, 0x08, 0x02,
+                                0x03, 0x02, 0x01,
+                            // application tag 4 -> Opaque
+                            0x44, 0x03,
+                                // INTEGER 42
+                                0x02, 0x01, 0x2a,
+        ];
+
+        let address = NetworkAddress::Internet(IpAddress([192, 168, 1, 10].into()));
+        let variable_bindings = vec![
+            VarBind {
+                name: ObjectIdentifier::new_unchecked(
+                    vec![1, 3, 6, 1, 2, 1, 2, 2, 1, 10, 1].into(),
+                ),
+                value: Counter(123_456).into(),
+            },
+            VarBind {
+                name: ObjectIdentifier::new_unchecked(vec![1, 3, 6, 1, 2, 1, 2, 2, 1, 5, 1].into()),
+                value: Gauge(100_000_000).into(),
+            },
+            VarBind {
+                name: ObjectIdentifier::new_unchecked(vec![1, 3, 6, 1, 2, 1, 1, 3, 0].into()),
+                value: TimeTicks(360_000).into(),
             },
-            ..encode_msg
+            VarBind {
+                name: ObjectIdentifier::new_unchecked(
+                    vec![1, 3, 6, 1, 2, 1, 4, 20, 1, 1, 192, 168, 1, 10].into(),
+                ),
+                value: address.clone().into(),
+            },
+            VarBind {
+                name: ObjectIdentifier::new_unchecked(
+                    vec![1, 3, 6, 1, 4, 1, 8072, 2, 3, 2, 1].into(),
+                ),
+                value: Integer::from(42).to_opaque().unwrap().into(),
+            },
+        ];
+        let encode_msg = Message {
+            version: 0.into(),
+            community: "public".into(),
+            data: Pdus::Trap(Trap {
+                enterprise: ObjectIdentifier::new_unchecked(
+                    vec![1, 3, 6, 1, 4, 1, 8072, 2, 3].into(),
+                ),
+                agent_addr: address,
+                generic_trap: 6.into(),
+                specific_trap: 1.into(),
+                time_stamp: TimeTicks(360_000),
+                variable_bindings: variable_bindings.clone(),
+            }),
         };
-        assert!(rasn::ber::encode(&encode_msg_no_bindings).is_ok());
+
+        let decode_msg: Message<Pdus> = rasn::ber::decode(&decode_data).unwrap();
+        assert_eq!(decode_msg, encode_msg);
+        assert_eq!(rasn::ber::encode(&encode_msg).unwrap(), decode_data);
+
+        let response = Pdus::GetResponse(GetResponse(Pdu {
+            request_id: 1.into(),
+            error_status: Pdu::ERROR_STATUS_NO_ERROR.into(),
+            error_index: 0.into(),
+            variable_bindings,
+        }));
+
+        for pdus in [encode_msg.data, response] {
+            let ber = rasn::ber::encode(&pdus).unwrap();
+            assert_eq!(rasn::ber::decode::<Pdus>(&ber).unwrap(), pdus);
+            let uper = rasn::uper::encode(&pdus).unwrap();
+            assert_eq!(rasn::uper::decode::<Pdus>(&uper).unwrap(), pdus);
+            let aper = rasn::aper::encode(&pdus).unwrap();
+            assert_eq!(rasn::aper::decode::<Pdus>(&aper).unwrap(), pdus);
+            let jer = rasn::jer::encode(&pdus).unwrap();
+            assert_eq!(rasn::jer::decode::<Pdus>(&jer).unwrap(), pdus);
+        }
     }
 }
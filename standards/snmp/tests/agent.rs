@@ -0,0 +1,461 @@
+use mib::{interfaces, system};
+use rasn::types::{Integer, ObjectIdentifier, OctetString, Oid};
+use rasn::AsnType;
+use rasn_snmp::{
+    agent::{self, MibTree},
+    table::{Row, RowError, Table, Walk},
+    v2::{
+        BulkPdu, GetBulkRequest, GetNextRequest, GetRequest, ObjectSyntax, Pdu, Pdus, Response,
+        SetRequest, VarBind, VarBindValue,
+    },
+};
+use smi::v1::{Counter, Gauge, TimeTicks};
+
+use pretty_assertions::assert_eq;
+
+smi::object_type! {
+    /// A writable object whose values are limited to a byte.
+    #[derive(Debug, PartialEq)]
+    pub struct Volume(pub u8);
+    access: ReadWrite,
+    status: Current,
+    value = [1, 3, 6, 1, 4, 1, 55555, 1];
+}
+
+fn oid(arcs: &[u32]) -> ObjectIdentifier {
+    Oid::new(arcs).unwrap().into()
+}
+
+fn interface(index: u32, descr: &str) -> interfaces::Entry {
+    interfaces::Entry {
+        index: interfaces::Index(index.into()),
+        descr: interfaces::Descr(OctetString::from(descr.to_owned())),
+        r#type: interfaces::Type(6.into()),
+        mtu: interfaces::Mtu(1500.into()),
+        speed: interfaces::Speed(Gauge(100_000_000)),
+        phys_address: interfaces::PhysAddress(OctetString::from_static(&[0, 1, 2, 3, 4, 5])),
+        admin_status: interfaces::AdminStatus(1.into()),
+        oper_status: interfaces::OperStatus(1.into()),
+        last_change: interfaces::LastChange(TimeTicks(0)),
+        in_octets: interfaces::InOctets(Counter(10)),
+        in_ucast_pkts: interfaces::InUcastPkts(Counter(1)),
+        in_n_ucast_pkts: interfaces::InNUcastPkts(Counter(0)),
+        in_discards: interfaces::InDiscards(Counter(0)),
+        in_errors: interfaces::InErrors(Counter(0)),
+        in_unknown_protos: interfaces::InUnknownProtos(Counter(0)),
+        out_octets: interfaces::OutOctets(Counter(20)),
+        out_ucast_pkts: interfaces::OutUcastPkts(Counter(2)),
+        out_n_ucast_pkts: interfaces::OutNUcastPkts(Counter(0)),
+        out_discards: interfaces::OutDiscards(Counter(0)),
+        out_errors: interfaces::OutErrors(Counter(0)),
+        out_q_len: interfaces::OutQLen(Gauge(0)),
+        specific: interfaces::Specific(oid(&[0, 0])),
+    }
+}
+
+fn tree() -> MibTree {
+    let mut tree = MibTree::new();
+    tree.insert(&[0], system::Descr(OctetString::from_static(b"rasn agent")))
+        .unwrap();
+    tree.insert(&[0], system::Contact(OctetString::from_static(b"admin")))
+        .unwrap();
+    tree.insert(&[0], Volume(11)).unwrap();
+    tree.insert(&[1], system::OrIndex(1)).unwrap();
+    tree.register::<system::UpTime>();
+    tree.insert_row(interface(1, "lo")).unwrap();
+    tree.insert_row(interface(2, "eth0")).unwrap();
+    tree
+}
+
+fn binding(name: &[u32], value: VarBindValue) -> VarBind {
+    VarBind {
+        name: oid(name),
+        value,
+    }
+}
+
+fn unspecified(names: &[&[u32]]) -> Vec<VarBind> {
+    names
+        .iter()
+        .map(|name| binding(name, VarBindValue::Unspecified))
+        .collect()
+}
+
+fn string(value: &'static [u8]) -> VarBindValue {
+    VarBindValue::Value(OctetString::from_static(value).into())
+}
+
+fn integer(value: i32) -> VarBindValue {
+    VarBindValue::Value(Integer::from(value).into())
+}
+
+fn pdu(variable_bindings: Vec<VarBind>) -> Pdu {
+    Pdu {
+        request_id: 42,
+        error_status: Pdu::ERROR_STATUS_NO_ERROR,
+        error_index: 0,
+        variable_bindings,
+    }
+}
+
+fn response(error_status: u32, error_index: u32, variable_bindings: Vec<VarBind>) -> Response {
+    Response(Pdu {
+        error_status,
+        error_index,
+        ..pdu(variable_bindings)
+    })
+}
+
+const SYS_DESCR: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 1, 0];
+const SYS_UP_TIME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 3, 0];
+const SYS_CONTACT: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 4, 0];
+const VOLUME: &[u32] = &[1, 3, 6, 1, 4, 1, 55555, 1, 0];
+
+#[test]
+fn get() {
+    let tree = tree();
+    let request = GetRequest(pdu(unspecified(&[
+        SYS_DESCR,
+        SYS_UP_TIME,
+        &[1, 3, 6, 1, 2, 1, 1, 1, 1],
+        &[1, 3, 6, 1, 2, 1, 99, 0],
+        &[1, 3, 6, 1, 2, 1, 1, 9, 1, 1],
+        &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 2],
+    ])));
+
+    assert_eq!(
+        response(
+            0,
+            0,
+            vec![
+                binding(SYS_DESCR, string(b"rasn agent")),
+                binding(SYS_UP_TIME, VarBindValue::NoSuchInstance),
+                binding(&[1, 3, 6, 1, 2, 1, 1, 1, 1], VarBindValue::NoSuchInstance),
+                binding(&[1, 3, 6, 1, 2, 1, 99, 0], VarBindValue::NoSuchObject),
+                // sysORIndex isn't accessible.
+                binding(&[1, 3, 6, 1, 2, 1, 1, 9, 1, 1], VarBindValue::NoSuchObject),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 2], string(b"eth0")),
+            ]
+        ),
+        tree.get_request(&request)
+    );
+}
+
+#[test]
+fn get_next() {
+    let tree = tree();
+    let next = |name: &[u32]| {
+        let request = GetNextRequest(pdu(unspecified(&[name])));
+        tree.get_next_request(&request)
+            .0
+            .variable_bindings
+            .remove(0)
+    };
+
+    assert_eq!(
+        binding(SYS_DESCR, string(b"rasn agent")),
+        next(&[1, 3, 6, 1, 2, 1])
+    );
+    assert_eq!(binding(SYS_CONTACT, string(b"admin")), next(SYS_DESCR));
+    // sysORIndex is skipped.
+    assert_eq!(
+        binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 1], integer(1)),
+        next(SYS_CONTACT)
+    );
+    assert_eq!(
+        binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 2], integer(2)),
+        next(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 1])
+    );
+    assert_eq!(
+        binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 1], string(b"lo")),
+        next(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 2])
+    );
+    assert_eq!(
+        binding(VOLUME, integer(11)),
+        next(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 22, 2])
+    );
+    assert_eq!(binding(VOLUME, VarBindValue::EndOfMibView), next(VOLUME));
+
+    // Walking the tree visits every readable instance once.
+    let mut name = oid(&[0, 0]);
+    let mut count = 0;
+    loop {
+        let binding = next(&name);
+        if binding.value == VarBindValue::EndOfMibView {
+            break;
+        }
+        assert!(binding.name > name);
+        name = binding.name;
+        count += 1;
+    }
+    assert_eq!(2 + 2 * 22 + 1, count);
+}
+
+#[test]
+fn get_bulk() {
+    let tree = tree();
+    let request = GetBulkRequest(BulkPdu {
+        request_id: 42,
+        non_repeaters: 1,
+        max_repetitions: 3,
+        variable_bindings: unspecified(&[
+            &[1, 3, 6, 1, 2, 1, 1],
+            &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2],
+            &[1, 3, 6, 1, 2, 1, 2, 2, 1, 3],
+        ]),
+    });
+
+    assert_eq!(
+        response(
+            0,
+            0,
+            vec![
+                binding(SYS_DESCR, string(b"rasn agent")),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 1], string(b"lo")),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 3, 1], integer(6)),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 2], string(b"eth0")),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 3, 2], integer(6)),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 3, 1], integer(6)),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 4, 1], integer(1500)),
+            ]
+        ),
+        tree.get_bulk_request(&request)
+    );
+
+    // The repetitions stop once every variable has reached the end.
+    let request = GetBulkRequest(BulkPdu {
+        request_id: 42,
+        non_repeaters: 0,
+        max_repetitions: 10,
+        variable_bindings: unspecified(&[&[1, 3, 6, 1, 2, 1, 2, 2, 1, 22, 2]]),
+    });
+    assert_eq!(
+        response(
+            0,
+            0,
+            vec![
+                binding(VOLUME, integer(11)),
+                binding(VOLUME, VarBindValue::EndOfMibView),
+            ]
+        ),
+        tree.get_bulk_request(&request)
+    );
+}
+
+#[test]
+fn get_bulk_truncated() {
+    let mut tree = tree();
+    tree.set_max_bulk_bindings(4);
+    let request = GetBulkRequest(BulkPdu {
+        request_id: 42,
+        non_repeaters: 1,
+        max_repetitions: u32::MAX,
+        variable_bindings: unspecified(&[
+            &[1, 3, 6, 1, 2, 1, 1],
+            &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2],
+            &[1, 3, 6, 1, 2, 1, 2, 2, 1, 3],
+        ]),
+    });
+    assert_eq!(
+        response(
+            0,
+            0,
+            vec![
+                binding(SYS_DESCR, string(b"rasn agent")),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 1], string(b"lo")),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 3, 1], integer(6)),
+                binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 2], string(b"eth0")),
+            ]
+        ),
+        tree.get_bulk_request(&request)
+    );
+
+    // The cap also applies to the non-repeaters.
+    tree.set_max_bulk_bindings(0);
+    assert_eq!(response(0, 0, vec![]), tree.get_bulk_request(&request));
+
+    // Without a cap of its own, the tree answers with at most the default.
+    let mut tree = MibTree::new();
+    for index in 0..2000 {
+        tree.insert(&[index], Volume(1)).unwrap();
+    }
+    let request = GetBulkRequest(BulkPdu {
+        request_id: 42,
+        non_repeaters: 0,
+        max_repetitions: u32::MAX,
+        variable_bindings: unspecified(&[&[1, 3, 6, 1, 4, 1, 55555]]),
+    });
+    let Response(pdu) = tree.get_bulk_request(&request);
+    assert_eq!(
+        agent::DEFAULT_MAX_BULK_BINDINGS,
+        pdu.variable_bindings.len()
+    );
+}
+
+#[test]
+fn set() {
+    let mut tree = tree();
+    let mut set = |variable_bindings: Vec<VarBind>| {
+        let request = Pdus::SetRequest(SetRequest(pdu(variable_bindings.clone())));
+        let Some(Pdus::Response(response)) = tree.handle(&request) else {
+            panic!("expected a response");
+        };
+        (response.0.error_status, response.0.error_index)
+    };
+
+    let if_admin_status = &[1, 3, 6, 1, 2, 1, 2, 2, 1, 7, 2];
+    assert_eq!(
+        (Pdu::ERROR_STATUS_NO_ERROR, 0),
+        set(vec![
+            binding(SYS_CONTACT, string(b"root")),
+            binding(if_admin_status, integer(2)),
+            binding(VOLUME, integer(12)),
+        ])
+    );
+
+    // Nothing is set unless everything can be.
+    for (value, expected) in [
+        (
+            binding(SYS_DESCR, string(b"")),
+            Pdu::ERROR_STATUS_NOT_WRITABLE,
+        ),
+        (
+            binding(&[1, 3, 6, 1, 2, 1, 1, 9, 1, 1], integer(2)),
+            Pdu::ERROR_STATUS_NOT_WRITABLE,
+        ),
+        (
+            binding(&[1, 3, 6, 1, 2, 1, 99, 0], integer(2)),
+            Pdu::ERROR_STATUS_NOT_WRITABLE,
+        ),
+        (
+            binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 7, 3], integer(2)),
+            Pdu::ERROR_STATUS_NO_CREATION,
+        ),
+        (
+            binding(VOLUME, string(b"loud")),
+            Pdu::ERROR_STATUS_WRONG_TYPE,
+        ),
+        (
+            binding(VOLUME, VarBindValue::Unspecified),
+            Pdu::ERROR_STATUS_WRONG_TYPE,
+        ),
+        (binding(VOLUME, integer(256)), Pdu::ERROR_STATUS_WRONG_VALUE),
+    ] {
+        assert_eq!(
+            (expected, 2),
+            set(vec![binding(SYS_CONTACT, string(b"nobody")), value])
+        );
+    }
+
+    assert_eq!(
+        Some(system::Contact(OctetString::from_static(b"root"))),
+        tree.get::<system::Contact>(&[0])
+    );
+    assert_eq!(
+        Some(interfaces::AdminStatus(2.into())),
+        tree.get::<interfaces::AdminStatus>(&[2])
+    );
+    assert_eq!(Some(Volume(12)), tree.get::<Volume>(&[0]));
+
+    // Other PDUs aren't requests.
+    let response = Pdus::Response(response(0, 0, Vec::new()));
+    assert_eq!(None, tree.handle(&response));
+
+    assert!(tree.remove::<Volume>(&[0]));
+    assert!(!tree.remove::<Volume>(&[0]));
+    assert_eq!(
+        VarBindValue::NoSuchInstance,
+        tree.get_request(&GetRequest(pdu(unspecified(&[VOLUME]))))
+            .0
+            .variable_bindings[0]
+            .value
+    );
+}
+
+#[test]
+fn syntax() {
+    let tree = tree();
+    let request = GetRequest(pdu(unspecified(&[&[1, 3, 6, 1, 2, 1, 2, 2, 1, 5, 1]])));
+    assert_eq!(
+        VarBindValue::Value(ObjectSyntax::from(Gauge(100_000_000))),
+        tree.get_request(&request).0.variable_bindings[0].value
+    );
+}
+
+#[test]
+fn rows() {
+    let rows = vec![interface(1, "lo"), interface(2, "eth0")];
+    let variable_bindings = interfaces::Table(rows.clone()).to_var_binds().unwrap();
+    assert_eq!(variable_bindings.len(), 44);
+    assert_eq!(
+        variable_bindings[1],
+        binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 1], string(b"lo"))
+    );
+    assert_eq!(
+        variable_bindings[23],
+        binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 2], string(b"eth0"))
+    );
+
+    // Rows are collected by index, whatever the order of their bindings.
+    let mut shuffled = variable_bindings.clone();
+    shuffled.reverse();
+    shuffled.push(binding(SYS_DESCR, string(b"rasn agent")));
+    assert_eq!(
+        interfaces::Table::from_var_binds(&shuffled).unwrap().0,
+        rows
+    );
+
+    let tree = tree();
+    assert_eq!(tree.row::<interfaces::Entry>(&2.into()).unwrap(), rows[1]);
+    assert!(matches!(
+        tree.row::<interfaces::Entry>(&3.into()),
+        Err(RowError::Incomplete { index }) if index == [3]
+    ));
+    assert!(matches!(
+        interface(1, "lo").to_var_binds(),
+        Ok(bindings) if bindings.len() == 22
+    ));
+
+    let mut negative = interface(1, "lo");
+    negative.index = interfaces::Index((-1).into());
+    assert!(matches!(
+        MibTree::new().insert_row(negative),
+        Err(RowError::Index(_))
+    ));
+    assert!(matches!(
+        Vec::<interfaces::Entry>::from_var_binds(&variable_bindings[1..]),
+        Err(RowError::Incomplete { index }) if index == [1]
+    ));
+}
+
+#[test]
+fn walk() {
+    let tree = tree();
+    let mut walk = Walk::<interfaces::Entry>::new();
+    let mut requests = 0;
+    while let Some(request) = walk.request(requests) {
+        requests += 1;
+        walk.push(&tree.get_next_request(&request)).unwrap();
+    }
+
+    // Every column instance, then the binding that leaves the table.
+    assert_eq!(requests, 45);
+    assert!(walk.is_finished());
+    assert_eq!(
+        walk.finish().unwrap(),
+        [interface(1, "lo"), interface(2, "eth0")]
+    );
+
+    let mut walk = Walk::<interfaces::Entry>::new();
+    assert!(matches!(
+        walk.push(&response(Pdu::ERROR_STATUS_GEN_ERR, 1, Vec::new())),
+        Err(RowError::ErrorStatus(status)) if status == Pdu::ERROR_STATUS_GEN_ERR
+    ));
+    let descr = response(
+        0,
+        0,
+        vec![binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 1], string(b"lo"))],
+    );
+    walk.push(&descr).unwrap();
+    assert!(matches!(walk.push(&descr), Err(RowError::NotIncreasing)));
+}
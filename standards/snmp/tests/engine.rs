@@ -0,0 +1,476 @@
+use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+use rasn::types::{OctetString, Oid};
+use rasn_snmp::{
+    engine::*,
+    usm::{AuthProtocol, Digester, LocalizedKeys, PrivProtocol, PrivacyCipher},
+    v2::{GetRequest, Pdu, Response, VarBind, VarBindValue},
+    v3::{Message, Pdus, ScopedPduData},
+};
+use sha1::Digest;
+
+use pretty_assertions::assert_eq;
+
+const ENGINE_ID: &[u8] = b"\x80\x00\x1f\x88\x04rasn-agent";
+const SYS_DESCR: &Oid = Oid::const_new(&[1, 3, 6, 1, 2, 1, 1, 1, 0]);
+
+struct Crypto;
+
+impl Digester for Crypto {
+    fn digest(&self, protocol: AuthProtocol, data: &[u8]) -> Option<Vec<u8>> {
+        Some(match protocol {
+            AuthProtocol::HmacSha1 => sha1::Sha1::digest(data).to_vec(),
+            AuthProtocol::HmacSha256 => sha2::Sha256::digest(data).to_vec(),
+            _ => return None,
+        })
+    }
+}
+
+impl PrivacyCipher for Crypto {
+    fn encrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        plaintext: &[u8],
+    ) -> Option<Vec<u8>> {
+        if protocol != PrivProtocol::Aes128 {
+            return None;
+        }
+        let mut data = plaintext.to_vec();
+        cfb_mode::Encryptor::<aes::Aes128>::new_from_slices(key, iv)
+            .ok()?
+            .encrypt(&mut data);
+        Some(data)
+    }
+
+    fn decrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>> {
+        if protocol != PrivProtocol::Aes128 {
+            return None;
+        }
+        let mut data = ciphertext.to_vec();
+        cfb_mode::Decryptor::<aes::Aes128>::new_from_slices(key, iv)
+            .ok()?
+            .decrypt(&mut data);
+        Some(data)
+    }
+}
+
+fn user(engine_id: &[u8], privacy_password: &[u8]) -> User {
+    let keys = LocalizedKeys::from_passwords(
+        &Crypto,
+        engine_id,
+        AuthProtocol::HmacSha256,
+        b"maplesyrup",
+        Some((PrivProtocol::Aes128, privacy_password)),
+    )
+    .unwrap();
+    User::new("rasn", Some(keys))
+}
+
+/// An agent started at 1000 seconds, after booting seven times.
+fn agent(boots: u32) -> LocalEngine {
+    let mut agent = LocalEngine::new(ENGINE_ID, boots, 1000);
+    agent.insert_user(user(ENGINE_ID, b"privpassword"));
+    agent.insert_user(User::new("public", None));
+    agent
+}
+
+/// Sends a message over the wire.
+fn send(message: &Message) -> Vec<u8> {
+    rasn::ber::encode(message).unwrap()
+}
+
+fn get_request(request_id: i32) -> Pdus {
+    Pdus::GetRequest(GetRequest(Pdu {
+        request_id,
+        error_status: Pdu::ERROR_STATUS_NO_ERROR,
+        error_index: 0,
+        variable_bindings: vec![VarBind {
+            name: SYS_DESCR.into(),
+            value: VarBindValue::Unspecified,
+        }],
+    }))
+}
+
+fn response(request_id: i32) -> Pdus {
+    Pdus::Response(Response(Pdu {
+        request_id,
+        error_status: Pdu::ERROR_STATUS_NO_ERROR,
+        error_index: 0,
+        variable_bindings: vec![VarBind {
+            name: SYS_DESCR.into(),
+            value: VarBindValue::Value(OctetString::from("rasn").into()),
+        }],
+    }))
+}
+
+/// Returns the report of a rejected message.
+fn report(result: Result<Incoming, EngineError>, expected: ReportReason) -> Message {
+    match result {
+        Err(EngineError::Rejected {
+            reason,
+            report: Some(report),
+        }) if reason == expected => *report,
+        result => panic!("expected a {expected} report, got {result:?}"),
+    }
+}
+
+/// An agent and a manager synchronised with it, at the agent's time 10 and
+/// the manager's time 0.
+fn synchronized() -> (LocalEngine, RemoteEngine, User) {
+    let mut agent = agent(7);
+    let mut manager = RemoteEngine::with_engine_id(ENGINE_ID);
+    let user = user(ENGINE_ID, b"privpassword");
+
+    let request = manager
+        .request(
+            &Crypto,
+            &user,
+            SecurityLevel::AuthNoPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    let reply = report(
+        agent.receive(&Crypto, &send(&request), 1010),
+        ReportReason::NotInTimeWindow,
+    );
+    manager.receive(&Crypto, &send(&reply), 0).unwrap();
+    assert_eq!((7, 10), manager.boots_and_time(0));
+
+    (agent, manager, user)
+}
+
+#[test]
+fn discovery() {
+    let mut agent = agent(7);
+    let mut manager = RemoteEngine::new();
+    assert!(matches!(
+        manager.request(
+            &Crypto,
+            &user(b"", b"privpassword"),
+            SecurityLevel::AuthPriv,
+            b"",
+            get_request(1),
+            0
+        ),
+        Err(EngineError::NotDiscovered)
+    ));
+
+    // RFC 3414 § 4: an empty engine ID and user name get the engine ID.
+    let request = manager.discovery_request();
+    let reply = report(
+        agent.receive(&Crypto, &send(&request), 1010),
+        ReportReason::UnknownEngineId,
+    );
+    assert_eq!(1, agent.stats.unknown_engine_ids);
+    assert_eq!(
+        Some(SecurityLevel::NoAuthNoPriv),
+        SecurityLevel::from_flags(&reply.global_data.flags)
+    );
+    let incoming = manager.receive(&Crypto, &send(&reply), 0).unwrap();
+    assert_eq!(
+        Some(ReportReason::UnknownEngineId),
+        incoming.report_reason()
+    );
+    assert_eq!(Some(ENGINE_ID), manager.engine_id());
+    assert!(!manager.is_synchronized());
+
+    // The first authenticated request gets the engine's boots and time.
+    let user = user(manager.engine_id().unwrap(), b"privpassword");
+    let request = manager
+        .request(
+            &Crypto,
+            &user,
+            SecurityLevel::AuthPriv,
+            b"",
+            get_request(2),
+            0,
+        )
+        .unwrap();
+    let reply = report(
+        agent.receive(&Crypto, &send(&request), 1010),
+        ReportReason::NotInTimeWindow,
+    );
+    assert_eq!(
+        Some(SecurityLevel::AuthNoPriv),
+        SecurityLevel::from_flags(&reply.global_data.flags)
+    );
+    let incoming = manager.receive(&Crypto, &send(&reply), 0).unwrap();
+    assert_eq!(
+        Some(ReportReason::NotInTimeWindow),
+        incoming.report_reason()
+    );
+    assert!(manager.is_synchronized());
+    assert_eq!((7, 70), manager.boots_and_time(60));
+
+    // After which requests are accepted.
+    let request = manager
+        .request(
+            &Crypto,
+            &user,
+            SecurityLevel::AuthPriv,
+            b"ctx",
+            get_request(3),
+            60,
+        )
+        .unwrap();
+    assert!(matches!(
+        request.scoped_data,
+        ScopedPduData::EncryptedPdu(_)
+    ));
+    let incoming = agent.receive(&Crypto, &send(&request), 1070).unwrap();
+    assert_eq!(b"rasn", &*incoming.user_name);
+    assert_eq!(SecurityLevel::AuthPriv, incoming.security_level);
+    assert!(incoming.reportable);
+    assert_eq!(&b"ctx"[..], &*incoming.scoped_pdu.name);
+    assert_eq!(get_request(3), incoming.scoped_pdu.data);
+
+    let reply = agent
+        .respond(&Crypto, &incoming, response(3), 1071)
+        .unwrap();
+    let incoming = manager.receive(&Crypto, &send(&reply), 61).unwrap();
+    assert_eq!(SecurityLevel::AuthPriv, incoming.security_level);
+    assert!(!incoming.reportable);
+    assert_eq!(response(3), incoming.scoped_pdu.data);
+    assert_eq!(None, incoming.report_reason());
+
+    // Each response is only accepted once.
+    assert!(matches!(
+        manager.receive(&Crypto, &send(&reply), 61),
+        Err(EngineError::UnknownMessageId)
+    ));
+}
+
+#[test]
+fn time_window() {
+    let (mut agent, mut manager, user) = synchronized();
+    let request = |manager: &mut RemoteEngine, now| {
+        manager
+            .request(
+                &Crypto,
+                &user,
+                SecurityLevel::AuthNoPriv,
+                b"",
+                get_request(1),
+                now,
+            )
+            .unwrap()
+    };
+
+    // Within the window either side.
+    let message = request(&mut manager, 100);
+    assert!(agent.receive(&Crypto, &send(&message), 1260).is_ok());
+    let message = request(&mut manager, 300);
+    assert!(agent.receive(&Crypto, &send(&message), 1160).is_ok());
+
+    // The manager's clock has drifted 151 seconds behind.
+    let message = request(&mut manager, 1000);
+    let reply = report(
+        agent.receive(&Crypto, &send(&message), 2161),
+        ReportReason::NotInTimeWindow,
+    );
+    assert_eq!(2, agent.stats.not_in_time_windows);
+    manager.receive(&Crypto, &send(&reply), 1000).unwrap();
+    let message = request(&mut manager, 1000);
+    assert!(agent.receive(&Crypto, &send(&message), 2161).is_ok());
+
+    // The agent has restarted.
+    let mut agent = self::agent(8);
+    let message = request(&mut manager, 1000);
+    let reply = report(
+        agent.receive(&Crypto, &send(&message), 1000),
+        ReportReason::NotInTimeWindow,
+    );
+    manager.receive(&Crypto, &send(&reply), 1000).unwrap();
+    assert_eq!((8, 5), manager.boots_and_time(1005));
+    let message = request(&mut manager, 1005);
+    let incoming = agent.receive(&Crypto, &send(&message), 1005).unwrap();
+
+    // A response delayed past the window is rejected once a later one has
+    // moved the manager's clock on.
+    let late = agent
+        .respond(&Crypto, &incoming, response(1), 1005)
+        .unwrap();
+    let message = request(&mut manager, 1300);
+    let incoming = agent.receive(&Crypto, &send(&message), 1300).unwrap();
+    let reply = agent
+        .respond(&Crypto, &incoming, response(1), 1300)
+        .unwrap();
+    manager.receive(&Crypto, &send(&reply), 1300).unwrap();
+    assert!(matches!(
+        manager.receive(&Crypto, &send(&late), 1300),
+        Err(EngineError::Rejected {
+            reason: ReportReason::NotInTimeWindow,
+            report: None,
+        })
+    ));
+
+    // The engine's boots and time wrap together.
+    assert_eq!(
+        (9, 1),
+        agent.boots_and_time(1000 + u64::from(MAX_ENGINE_VALUE) + 2)
+    );
+    let agent = LocalEngine::new(ENGINE_ID, MAX_ENGINE_VALUE, 0);
+    assert_eq!(
+        (MAX_ENGINE_VALUE, 0),
+        agent.boots_and_time(u64::from(MAX_ENGINE_VALUE) + 1)
+    );
+}
+
+#[test]
+fn rejections() {
+    let (mut agent, mut manager, user) = synchronized();
+
+    let unknown = User::new("nobody", None);
+    let message = manager
+        .request(
+            &Crypto,
+            &unknown,
+            SecurityLevel::NoAuthNoPriv,
+            b"",
+            get_request(9),
+            0,
+        )
+        .unwrap();
+    let reply = report(
+        agent.receive(&Crypto, &send(&message), 1010),
+        ReportReason::UnknownUserName,
+    );
+    let incoming = manager.receive(&Crypto, &send(&reply), 0).unwrap();
+    assert_eq!(
+        Some(ReportReason::UnknownUserName),
+        incoming.report_reason()
+    );
+    let Pdus::Report(report_pdu) = incoming.scoped_pdu.data else {
+        panic!("expected a report");
+    };
+    assert_eq!(9, report_pdu.0.request_id);
+    assert_eq!(
+        VarBindValue::Value(smi::v1::Counter(1).into()),
+        report_pdu.0.variable_bindings[0].value
+    );
+
+    // The user only has noAuthNoPriv.
+    let public = User::new("public", user.keys.clone());
+    let message = manager
+        .request(
+            &Crypto,
+            &public,
+            SecurityLevel::AuthNoPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    report(
+        agent.receive(&Crypto, &send(&message), 1010),
+        ReportReason::UnsupportedSecLevel,
+    );
+    assert!(matches!(
+        manager.request(
+            &Crypto,
+            &unknown,
+            SecurityLevel::AuthNoPriv,
+            b"",
+            get_request(1),
+            0
+        ),
+        Err(EngineError::UnsupportedSecurityLevel)
+    ));
+
+    // The message was changed in transit.
+    let message = manager
+        .request(
+            &Crypto,
+            &user,
+            SecurityLevel::AuthNoPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    let mut encoded = rasn::ber::encode(&message).unwrap();
+    // The last arc of the variable's name.
+    let last = encoded.len() - 3;
+    encoded[last] ^= 1;
+    report(
+        agent.receive(&Crypto, &encoded, 1010),
+        ReportReason::WrongDigest,
+    );
+
+    // The privacy password is wrong.
+    let wrong = self::user(ENGINE_ID, b"wrongpassword");
+    let message = manager
+        .request(
+            &Crypto,
+            &wrong,
+            SecurityLevel::AuthPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    report(
+        agent.receive(&Crypto, &send(&message), 1010),
+        ReportReason::DecryptionError,
+    );
+
+    // Messages without the reportable flag are dropped silently.
+    let mut message = manager
+        .request(
+            &Crypto,
+            &unknown,
+            SecurityLevel::NoAuthNoPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    message.global_data.flags = SecurityLevel::NoAuthNoPriv.flags(false);
+    assert!(matches!(
+        agent.receive(&Crypto, &send(&message), 1010),
+        Err(EngineError::Rejected {
+            reason: ReportReason::UnknownUserName,
+            report: None,
+        })
+    ));
+
+    let stats = &agent.stats;
+    assert_eq!(2, stats.unknown_user_names);
+    assert_eq!(1, stats.unsupported_sec_levels);
+    assert_eq!(1, stats.wrong_digests);
+    assert_eq!(1, stats.decryption_errors);
+    assert_eq!(2, stats.get(ReportReason::UnknownUserName));
+
+    // An unauthenticated response to an authenticated request.
+    let message = manager
+        .request(
+            &Crypto,
+            &user,
+            SecurityLevel::AuthPriv,
+            b"",
+            get_request(1),
+            0,
+        )
+        .unwrap();
+    let mut incoming = agent.receive(&Crypto, &send(&message), 1010).unwrap();
+    incoming.security_level = SecurityLevel::NoAuthNoPriv;
+    let reply = agent
+        .respond(&Crypto, &incoming, response(1), 1010)
+        .unwrap();
+    assert!(matches!(
+        manager.receive(&Crypto, &send(&reply), 0),
+        Err(EngineError::Rejected {
+            reason: ReportReason::UnsupportedSecLevel,
+            report: None,
+        })
+    ));
+}
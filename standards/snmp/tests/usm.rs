@@ -0,0 +1,324 @@
+use aes::cipher::{
+    block_padding::NoPadding, AsyncStreamCipher, BlockDecryptMut, BlockEncryptMut, KeyIvInit,
+};
+use rasn_snmp::{
+    usm::*,
+    v3::{Message, ScopedPdu, ScopedPduData},
+};
+use sha1::Digest;
+
+use pretty_assertions::assert_eq;
+
+// The messages were built with Python's `hashlib`, `hmac` and `cryptography`,
+// for the user "rasn" with the authentication password "maplesyrup" and the
+// privacy password "privpassword", at engine boots 5 and time 1234.
+const ENGINE_ID: &str = "000000000000000000000002";
+const MD5_DES: &str = "307c020103300e020101020300ffe304010702010304353033040c000000000000000000000002020105020204d204047261736e040c3a40c166c82c13122967e5eb0408000000050102030404307856369cc3e237dc64ebb14e7ba79cf34b8b82f047c31a788cdb66e81516e973b96b1c221aada53d2cd2a7d63523b239";
+const SHA1_AES: &str = "3079020103300e020101020300ffe304010702010304353033040c000000000000000000000002020105020204d204047261736e040ce369e2e850596174fbf8cc8104080102030405060708042d44a3830aaaaffa13e0900e024bfd8d81a10346af2b2c8458ed5a404dde7e590e16764e98cc12e07318e23b4823";
+const SHA256: &str = "307b020103300e020101020300ffe304010502010304393037040c000000000000000000000002020105020204d204047261736e041856f215ff6f91e229ba1685a92f6cfd5c24a74aa63f7114240400302b040c0000000000000000000000020400a019020101020100020100300e300c06082b060102010101000500";
+/// The scoped PDU of each message, a GetRequest for sysDescr.0.
+const SCOPED_PDU: &str =
+    "302b040c0000000000000000000000020400a019020101020100020100300e300c06082b060102010101000500";
+
+fn hex(string: &str) -> Vec<u8> {
+    hex::decode(string).unwrap()
+}
+
+struct Crypto;
+
+impl Digester for Crypto {
+    fn digest(&self, protocol: AuthProtocol, data: &[u8]) -> Option<Vec<u8>> {
+        Some(match protocol {
+            AuthProtocol::HmacMd5 => md5::Md5::digest(data).to_vec(),
+            AuthProtocol::HmacSha1 => sha1::Sha1::digest(data).to_vec(),
+            AuthProtocol::HmacSha224 => sha2::Sha224::digest(data).to_vec(),
+            AuthProtocol::HmacSha256 => sha2::Sha256::digest(data).to_vec(),
+            AuthProtocol::HmacSha384 => sha2::Sha384::digest(data).to_vec(),
+            AuthProtocol::HmacSha512 => sha2::Sha512::digest(data).to_vec(),
+            _ => return None,
+        })
+    }
+}
+
+impl PrivacyCipher for Crypto {
+    fn encrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        plaintext: &[u8],
+    ) -> Option<Vec<u8>> {
+        match protocol {
+            PrivProtocol::Des if plaintext.len().is_multiple_of(8) => Some(
+                cbc::Encryptor::<des::Des>::new_from_slices(key, iv)
+                    .ok()?
+                    .encrypt_padded_vec_mut::<NoPadding>(plaintext),
+            ),
+            PrivProtocol::Aes128 => {
+                let mut data = plaintext.to_vec();
+                cfb_mode::Encryptor::<aes::Aes128>::new_from_slices(key, iv)
+                    .ok()?
+                    .encrypt(&mut data);
+                Some(data)
+            }
+            _ => None,
+        }
+    }
+
+    fn decrypt(
+        &self,
+        protocol: PrivProtocol,
+        key: &[u8],
+        iv: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>> {
+        match protocol {
+            PrivProtocol::Des => cbc::Decryptor::<des::Des>::new_from_slices(key, iv)
+                .ok()?
+                .decrypt_padded_vec_mut::<NoPadding>(ciphertext)
+                .ok(),
+            PrivProtocol::Aes128 => {
+                let mut data = ciphertext.to_vec();
+                cfb_mode::Decryptor::<aes::Aes128>::new_from_slices(key, iv)
+                    .ok()?
+                    .decrypt(&mut data);
+                Some(data)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn localized_keys(protocol: AuthProtocol, privacy: Option<PrivProtocol>) -> LocalizedKeys {
+    LocalizedKeys::from_passwords(
+        &Crypto,
+        &hex(ENGINE_ID),
+        protocol,
+        b"maplesyrup",
+        privacy.map(|privacy| (privacy, &b"privpassword"[..])),
+    )
+    .unwrap()
+}
+
+fn scoped_pdu() -> ScopedPdu {
+    rasn::ber::decode(&hex(SCOPED_PDU)).unwrap()
+}
+
+// RFC 3414 Appendix A.3, and the same for the RFC 7860 protocols.
+#[test]
+fn password_to_key() {
+    let engine_id = hex(ENGINE_ID);
+    for (protocol, key, localized) in [
+        (
+            AuthProtocol::HmacMd5,
+            "9faf3283884e92834ebc9847d8edd963",
+            "526f5eed9fcce26f8964c2930787d82b",
+        ),
+        (
+            AuthProtocol::HmacSha1,
+            "9fb5cc0381497b3793528939ff788d5d79145211",
+            "6695febc9288e36282235fc7151f128497b38f3f",
+        ),
+        (
+            AuthProtocol::HmacSha224,
+            "282a5867ee9aac639ad59df9572c7d3ac0fbc13a905b6df07dbbf00b",
+            "0bd8827c6e29f8065e08e09237f177e410f69b90e1782be682075674",
+        ),
+        (
+            AuthProtocol::HmacSha256,
+            "ab51014d1e077f6017df2b12bee5f5aa72993177e9bb569c4dff5a4ca0b4afac",
+            "8982e0e549e866db361a6b625d84cccc11162d453ee8ce3a6445c2d6776f0f8b",
+        ),
+        (
+            AuthProtocol::HmacSha384,
+            "e06eccdf2c68a06ed034723c9c26e0db3b669e1e2efed49150b55377a2e98f383c86fb836857444654b287c93f51ff64",
+            "3b298f16164a11184279d5432bf169e2d2a48307de02b3d3f7e2b4f36eb6f0455a53689a3937eea07319a633d2ccba78",
+        ),
+        (
+            AuthProtocol::HmacSha512,
+            "7e4396de5aadc77be853819b98c9406265b3a9c37cc3176569847a4e4f6fba63dd3a73d04924d31a63f95a601f9385af6be4ed1b37f87d040f7c6ed6f8d38a91",
+            "22a5a36cedfcc085807a128d7bc6c2382167ad6c0dbc5fdff856740f3d84c099ad1ea87a8db096714d9788bd544047c9021e4229ce27e4c0a69250adfcffbb0b",
+        ),
+    ] {
+        let ku = protocol.password_to_key(&Crypto, b"maplesyrup").unwrap();
+        assert_eq!(hex(key), ku);
+        assert_eq!(
+            hex(localized),
+            protocol.localize_key(&Crypto, &ku, &engine_id).unwrap()
+        );
+        assert_eq!(Some(protocol), AuthProtocol::from_oid(protocol.oid()));
+    }
+
+    assert!(matches!(
+        AuthProtocol::HmacMd5.password_to_key(&Crypto, b""),
+        Err(UsmError::EmptyPassword)
+    ));
+}
+
+fn round_trip(encoded: &str, protocol: AuthProtocol, privacy: Option<(PrivProtocol, u64)>) {
+    let keys = localized_keys(protocol, privacy.map(|(privacy, _)| privacy));
+    Message::verify_authentication(&hex(encoded), &Crypto, protocol, &keys.auth_key).unwrap();
+    let mut message: Message = rasn::ber::decode(&hex(encoded)).unwrap();
+    if let Some((privacy, _)) = keys.privacy.clone() {
+        message
+            .decrypt_scoped_pdu(&Crypto, privacy, &keys.privacy.as_ref().unwrap().1)
+            .unwrap();
+    }
+    assert_eq!(
+        ScopedPduData::CleartextPdu(scoped_pdu()),
+        message.scoped_data
+    );
+
+    // Securing the decrypted message again gives the same encoding.
+    if let (Some((privacy, salt)), Some((_, key))) = (privacy, &keys.privacy) {
+        message
+            .encrypt_scoped_pdu(&Crypto, privacy, key, salt)
+            .unwrap();
+    }
+    message
+        .authenticate(&Crypto, protocol, &keys.auth_key)
+        .unwrap();
+    assert_eq!(hex(encoded), rasn::ber::encode(&message).unwrap());
+}
+
+#[test]
+fn md5_des() {
+    round_trip(
+        MD5_DES,
+        AuthProtocol::HmacMd5,
+        Some((PrivProtocol::Des, 0x0102_0304)),
+    );
+}
+
+#[test]
+fn sha1_aes() {
+    round_trip(
+        SHA1_AES,
+        AuthProtocol::HmacSha1,
+        Some((PrivProtocol::Aes128, 0x0102_0304_0506_0708)),
+    );
+}
+
+#[test]
+fn sha256() {
+    round_trip(SHA256, AuthProtocol::HmacSha256, None);
+}
+
+#[test]
+fn authentication_failure() {
+    let keys = localized_keys(AuthProtocol::HmacSha256, None);
+    let verify = |encoded: &[u8]| {
+        Message::verify_authentication(encoded, &Crypto, AuthProtocol::HmacSha256, &keys.auth_key)
+    };
+
+    let mut encoded = hex(SHA256);
+    // The request ID.
+    let last = encoded.len() - 23;
+    encoded[last] ^= 1;
+    assert!(matches!(
+        verify(&encoded),
+        Err(UsmError::AuthenticationFailure)
+    ));
+
+    let encoded = hex(SHA256);
+    let mut wrong_key = keys.auth_key.clone();
+    wrong_key[0] ^= 1;
+    assert!(matches!(
+        Message::verify_authentication(&encoded, &Crypto, AuthProtocol::HmacSha256, &wrong_key),
+        Err(UsmError::AuthenticationFailure)
+    ));
+    assert!(matches!(
+        Message::verify_authentication(&encoded, &Crypto, AuthProtocol::HmacSha1, &keys.auth_key),
+        Err(UsmError::AuthenticationFailure)
+    ));
+    assert!(matches!(
+        Message::verify_authentication(
+            &encoded,
+            &Crypto,
+            AuthProtocol::HmacSha256,
+            &keys.auth_key[..16]
+        ),
+        Err(UsmError::InvalidKeyLength)
+    ));
+
+    let mut community: Message = rasn::ber::decode(&encoded).unwrap();
+    community.global_data.security_model = 2.into();
+    assert!(matches!(
+        verify(&rasn::ber::encode(&community).unwrap()),
+        Err(UsmError::WrongSecurityModel)
+    ));
+}
+
+#[test]
+fn non_minimal_lengths() {
+    let keys = localized_keys(AuthProtocol::HmacSha256, None);
+    let message: Message = rasn::ber::decode(&hex(SHA256)).unwrap();
+    let mac = message
+        .usm_security_parameters()
+        .unwrap()
+        .authentication_parameters;
+
+    // The message's length in two octets rather than one, signed as sent.
+    let mut encoded = hex(SHA256);
+    encoded.splice(1..2, [0x81, 0x7b]);
+    let position = encoded.windows(mac.len()).position(|w| *w == *mac).unwrap();
+    let range = position..position + mac.len();
+    encoded[range.clone()].fill(0);
+    let mac = AuthProtocol::HmacSha256
+        .mac(&Crypto, &keys.auth_key, &encoded)
+        .unwrap();
+    encoded[range].copy_from_slice(&mac);
+
+    let received: Message = rasn::ber::decode(&encoded).unwrap();
+    assert_eq!(message.scoped_data, received.scoped_data);
+    assert_ne!(encoded, rasn::ber::encode(&received).unwrap());
+    Message::verify_authentication(&encoded, &Crypto, AuthProtocol::HmacSha256, &keys.auth_key)
+        .unwrap();
+}
+
+#[test]
+fn decryption_error() {
+    let keys = localized_keys(AuthProtocol::HmacSha1, Some(PrivProtocol::Aes128));
+    let key = &keys.privacy.as_ref().unwrap().1;
+
+    let mut message: Message = rasn::ber::decode(&hex(SHA1_AES)).unwrap();
+    let mut wrong_key = key.clone();
+    wrong_key[0] ^= 1;
+    assert!(matches!(
+        message.decrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, &wrong_key),
+        Err(UsmError::DecryptionError)
+    ));
+
+    message
+        .decrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, key)
+        .unwrap();
+    assert!(matches!(
+        message.decrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, key),
+        Err(UsmError::DecryptionError)
+    ));
+
+    message
+        .encrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, key, 1)
+        .unwrap();
+    assert!(matches!(
+        message.encrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, key, 2),
+        Err(UsmError::AlreadyEncrypted)
+    ));
+    let mut parameters = message.usm_security_parameters().unwrap();
+    assert_eq!(&1u64.to_be_bytes()[..], &*parameters.privacy_parameters);
+
+    parameters.privacy_parameters = vec![0; 4].into();
+    message.security_parameters = rasn::ber::encode(&parameters).unwrap().into();
+    assert!(matches!(
+        message.decrypt_scoped_pdu(&Crypto, PrivProtocol::Aes128, key),
+        Err(UsmError::DecryptionError)
+    ));
+    assert!(matches!(
+        message.decrypt_scoped_pdu(&Crypto, PrivProtocol::Des, &key[..8]),
+        Err(UsmError::InvalidKeyLength)
+    ));
+    assert_eq!(
+        Some(PrivProtocol::Des),
+        PrivProtocol::from_oid(PrivProtocol::Des.oid())
+    );
+}
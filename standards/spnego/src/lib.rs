@@ -0,0 +1,317 @@
+#![doc = include_str!("../README.md")]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use rasn::{
+    error::{DecodeError, EncodeError},
+    prelude::*,
+};
+
+pub type MechType = ObjectIdentifier;
+pub type MechTypeList = SequenceOf<MechType>;
+
+/// OID of the SPNEGO pseudo-mechanism.
+pub const SPNEGO: &Oid = Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_SECURITY_MECHANISMS_SPNEGO;
+
+/// OID of the Kerberos V5 mechanism, from RFC 4121.
+pub const KERBEROS_V5: &Oid = Oid::ISO_MEMBER_BODY_US_MIT_KERBEROS_V5;
+
+/// OID of the Kerberos V5 mechanism as (mis)encoded by older versions of
+/// Windows, which still offer it alongside [KERBEROS_V5].
+pub const MS_KERBEROS_V5: &Oid = Oid::ISO_MEMBER_BODY_US_MICROSOFT_KERBEROS_V5;
+
+/// OID of the NTLM security support provider.
+pub const NTLM_SSP: &Oid =
+    Oid::ISO_IDENTIFIED_ORGANISATION_DOD_INTERNET_PRIVATE_ENTERPRISES_MICROSOFT_NTLM_SSP;
+
+/// The `[APPLICATION 0]` tag (constructed) which starts every initial context
+/// token.
+const INITIAL_CONTEXT_TOKEN_TAG: u8 = 0x60;
+
+/// An error returned when parsing a GSS-API or SPNEGO token.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TokenError {
+    /// The token doesn't have the `[APPLICATION 0]` framing, or its length
+    /// doesn't match the framing.
+    InvalidFraming,
+    /// The token is for a different mechanism.
+    UnexpectedMech(ObjectIdentifier),
+    /// The token couldn't be decoded.
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidFraming => write!(f, "invalid initial context token framing"),
+            Self::UnexpectedMech(mech) => write!(f, "unexpected mechanism {mech:?}"),
+            Self::Decode(error) => write!(f, "failed to decode token: {error}"),
+        }
+    }
+}
+
+impl From<DecodeError> for TokenError {
+    fn from(error: DecodeError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+/// The framing of the first token sent by a GSS-API context initiator, from
+/// [RFC 2743 Section 3.1].
+///
+/// ```asn1
+/// InitialContextToken ::= [APPLICATION 0] IMPLICIT SEQUENCE {
+///     thisMech MechType,
+///     innerContextToken ANY DEFINED BY thisMech
+/// }
+/// ```
+///
+/// The inner token is mechanism specific, and not necessarily ASN.1, so this
+/// is read and written with [InitialContextToken::parse] and
+/// [InitialContextToken::to_bytes] rather than an ASN.1 codec.
+///
+/// [RFC 2743 Section 3.1]: https://datatracker.ietf.org/doc/html/rfc2743#section-3.1
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InitialContextToken {
+    pub this_mech: MechType,
+    pub inner_context_token: Vec<u8>,
+}
+
+impl InitialContextToken {
+    pub fn new(this_mech: MechType, inner_context_token: Vec<u8>) -> Self {
+        Self {
+            this_mech,
+            inner_context_token,
+        }
+    }
+
+    /// Parses a framed token, which must take up all of `input`.
+    pub fn parse(input: &[u8]) -> Result<Self, TokenError> {
+        let Some((&INITIAL_CONTEXT_TOKEN_TAG, rest)) = input.split_first() else {
+            return Err(TokenError::InvalidFraming);
+        };
+        let (header, length) = read_length(rest)?;
+        let contents = &rest[header..];
+        if contents.len() != length {
+            return Err(TokenError::InvalidFraming);
+        }
+
+        let Some((0x06, rest)) = contents.split_first() else {
+            return Err(TokenError::InvalidFraming);
+        };
+        let (header, length) = read_length(rest)?;
+        let (oid, inner) = length
+            .checked_add(1 + header)
+            .and_then(|oid_length| contents.split_at_checked(oid_length))
+            .ok_or(TokenError::InvalidFraming)?;
+
+        Ok(Self {
+            this_mech: rasn::der::decode(oid)?,
+            inner_context_token: inner.to_vec(),
+        })
+    }
+
+    /// Returns the framed token.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut contents =
+            rasn::der::encode(&self.this_mech).expect("object identifiers always encode");
+        contents.extend_from_slice(&self.inner_context_token);
+
+        let mut output = alloc::vec![INITIAL_CONTEXT_TOKEN_TAG];
+        write_length(&mut output, contents.len());
+        output.append(&mut contents);
+        output
+    }
+}
+
+/// Reads a definite BER length from the start of `input`, returning the
+/// number of bytes it took up and the length itself.
+fn read_length(input: &[u8]) -> Result<(usize, usize), TokenError> {
+    match input.first() {
+        Some(&length @ 0..=0x7f) => Ok((1, length.into())),
+        Some(0x80) | None => Err(TokenError::InvalidFraming),
+        Some(first) => {
+            let header = 1 + usize::from(first & 0x7f);
+            input
+                .get(1..header)
+                .ok_or(TokenError::InvalidFraming)?
+                .iter()
+                .try_fold(0usize, |length, byte| {
+                    length
+                        .checked_mul(256)
+                        .map(|length| length + usize::from(*byte))
+                })
+                .map(|length| (header, length))
+                .ok_or(TokenError::InvalidFraming)
+        }
+    }
+}
+
+fn write_length(output: &mut Vec<u8>, length: usize) {
+    if length < 0x80 {
+        output.push(length as u8);
+    } else {
+        let bytes = length.to_be_bytes();
+        let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+        output.push(0x80 | (bytes.len() - skip) as u8);
+        output.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+/// A SPNEGO negotiation token, from [RFC 4178 Section 4.2].
+///
+/// The first token an initiator sends is a [NegTokenInit] inside an
+/// [InitialContextToken] for the [SPNEGO] mechanism, while every later token
+/// is sent as is. [NegotiationToken::parse] and [NegotiationToken::to_bytes]
+/// handle the difference.
+///
+/// [RFC 4178 Section 4.2]: https://datatracker.ietf.org/doc/html/rfc4178#section-4.2
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[rasn(choice)]
+pub enum NegotiationToken {
+    #[rasn(tag(explicit(0)))]
+    NegTokenInit(NegTokenInit),
+    #[rasn(tag(explicit(1)))]
+    NegTokenResp(NegTokenResp),
+}
+
+impl NegotiationToken {
+    /// Parses a token, with or without the initial context token framing.
+    pub fn parse(input: &[u8]) -> Result<Self, TokenError> {
+        if input.first() != Some(&INITIAL_CONTEXT_TOKEN_TAG) {
+            return Ok(rasn::der::decode(input)?);
+        }
+
+        let token = InitialContextToken::parse(input)?;
+        if token.this_mech != SPNEGO {
+            return Err(TokenError::UnexpectedMech(token.this_mech));
+        }
+
+        Ok(rasn::der::decode(&token.inner_context_token)?)
+    }
+
+    /// Encodes the token, adding the initial context token framing to a
+    /// [NegTokenInit].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let encoded = rasn::der::encode(self)?;
+        Ok(match self {
+            Self::NegTokenInit(_) => InitialContextToken::new(SPNEGO.into(), encoded).to_bytes(),
+            Self::NegTokenResp(_) => encoded,
+        })
+    }
+}
+
+/// The initiator's proposal of mechanisms, and optionally the first token of
+/// its preferred mechanism.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct NegTokenInit {
+    /// The mechanisms the initiator supports, most preferred first.
+    #[rasn(tag(explicit(0)))]
+    pub mech_types: MechTypeList,
+    /// Not used, and should be ignored by acceptors.
+    #[rasn(tag(explicit(1)))]
+    pub req_flags: Option<ContextFlags>,
+    /// The optimistic first token of the first mechanism in `mech_types`.
+    #[rasn(tag(explicit(2)))]
+    pub mech_token: Option<OctetString>,
+    /// A MIC over the DER encoding of `mech_types`, computed by the
+    /// negotiated mechanism.
+    #[rasn(tag(explicit(3)))]
+    pub mech_list_mic: Option<OctetString>,
+}
+
+impl NegTokenInit {
+    pub fn new(mech_types: MechTypeList, mech_token: Option<OctetString>) -> Self {
+        Self {
+            mech_types,
+            req_flags: None,
+            mech_token,
+            mech_list_mic: None,
+        }
+    }
+
+    /// Returns the DER encoding of `mech_types`, which is what the
+    /// `mech_list_mic` of both parties protects.
+    pub fn mech_list_bytes(&self) -> Vec<u8> {
+        rasn::der::encode(&self.mech_types).expect("object identifiers always encode")
+    }
+}
+
+/// The response to a [NegTokenInit], and all later tokens in the exchange.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct NegTokenResp {
+    /// The state of the negotiation. Only required in the acceptor's first
+    /// reply.
+    #[rasn(tag(explicit(0)))]
+    pub neg_state: Option<NegState>,
+    /// The mechanism chosen by the acceptor, only present in its first
+    /// reply.
+    #[rasn(tag(explicit(1)))]
+    pub supported_mech: Option<MechType>,
+    /// A token of the negotiated mechanism.
+    #[rasn(tag(explicit(2)))]
+    pub response_token: Option<OctetString>,
+    /// A MIC over the DER encoding of the initiator's `mech_types`.
+    #[rasn(tag(explicit(3)))]
+    pub mech_list_mic: Option<OctetString>,
+}
+
+impl NegTokenResp {
+    pub fn new(neg_state: Option<NegState>, response_token: Option<OctetString>) -> Self {
+        Self {
+            neg_state,
+            supported_mech: None,
+            response_token,
+            mech_list_mic: None,
+        }
+    }
+}
+
+/// The state of a SPNEGO negotiation.
+#[derive(AsnType, Clone, Copy, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[rasn(enumerated)]
+pub enum NegState {
+    /// No further negotiation tokens are expected.
+    AcceptCompleted = 0,
+    /// More tokens are expected from the peer.
+    AcceptIncomplete = 1,
+    /// The acceptor rejected all of the proposed mechanisms.
+    Reject = 2,
+    /// The acceptor requires the initiator's `mech_list_mic`.
+    RequestMic = 3,
+}
+
+/// The GSS-API context flags the initiator requested.
+#[derive(AsnType, Clone, Debug, Decode, Encode, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[rasn(delegate, size(32))]
+pub struct ContextFlags(pub BitString);
+
+impl ContextFlags {
+    pub const DELEG: usize = 0;
+    pub const MUTUAL: usize = 1;
+    pub const REPLAY: usize = 2;
+    pub const SEQUENCE: usize = 3;
+    pub const ANON: usize = 4;
+    pub const CONF: usize = 5;
+    pub const INTEG: usize = 6;
+
+    /// Creates flags with each bit in `bits` set.
+    pub fn new(bits: &[usize]) -> Self {
+        let mut flags = BitString::repeat(false, 32);
+        for bit in bits {
+            flags.set(*bit, true);
+        }
+        Self(flags)
+    }
+
+    /// Returns whether `bit` is set.
+    pub fn contains(&self, bit: usize) -> bool {
+        self.0.get(bit).is_some_and(|bit| *bit)
+    }
+}
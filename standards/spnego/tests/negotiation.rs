@@ -0,0 +1,131 @@
+use rasn::prelude::*;
+use rasn_spnego::*;
+
+use pretty_assertions::assert_eq;
+
+// A negotiation between MIT krb5's `gss_init_sec_context` and
+// `gss_accept_sec_context` for the SPNEGO mechanism, where the initiator
+// sends an optimistic Kerberos AP-REQ and the acceptor completes with an
+// AP-REP.
+const NEG_TOKEN_INIT: &[u8] = include_bytes!("data/neg-token-init");
+const NEG_TOKEN_RESP: &[u8] = include_bytes!("data/neg-token-resp");
+
+#[test]
+fn mit_negotiation() {
+    let NegotiationToken::NegTokenInit(init) = NegotiationToken::parse(NEG_TOKEN_INIT).unwrap()
+    else {
+        panic!("expected a NegTokenInit");
+    };
+    assert_eq!(mechs(&[KERBEROS_V5]), init.mech_types);
+    assert_eq!(None, init.mech_list_mic);
+    assert_eq!(
+        &[0x30, 0x0b, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x12, 0x01, 0x02, 0x02],
+        &init.mech_list_bytes()[..]
+    );
+
+    let mech_token = InitialContextToken::parse(init.mech_token.as_ref().unwrap()).unwrap();
+    assert_eq!(KERBEROS_V5, mech_token.this_mech);
+    // The Kerberos token identifier of an AP-REQ.
+    assert_eq!(&[0x01, 0x00], &mech_token.inner_context_token[..2]);
+
+    let token = NegotiationToken::NegTokenInit(init);
+    assert_eq!(NEG_TOKEN_INIT, &token.to_bytes().unwrap()[..]);
+
+    let NegotiationToken::NegTokenResp(resp) = NegotiationToken::parse(NEG_TOKEN_RESP).unwrap()
+    else {
+        panic!("expected a NegTokenResp");
+    };
+    assert_eq!(Some(NegState::AcceptCompleted), resp.neg_state);
+    assert_eq!(Some(KERBEROS_V5.into()), resp.supported_mech);
+    assert_eq!(
+        &[0x02, 0x00],
+        &InitialContextToken::parse(resp.response_token.as_ref().unwrap())
+            .unwrap()
+            .inner_context_token[..2]
+    );
+
+    let token = NegotiationToken::NegTokenResp(resp);
+    assert_eq!(NEG_TOKEN_RESP, &token.to_bytes().unwrap()[..]);
+}
+
+#[test]
+fn round_trip() {
+    let mut init = NegTokenInit::new(
+        mechs(&[MS_KERBEROS_V5, KERBEROS_V5, NTLM_SSP]),
+        Some(OctetString::from_static(b"token")),
+    );
+    init.req_flags = Some(ContextFlags::new(&[
+        ContextFlags::MUTUAL,
+        ContextFlags::INTEG,
+    ]));
+    let token = NegotiationToken::NegTokenInit(init);
+
+    let bytes = token.to_bytes().unwrap();
+    assert_eq!(&[0x60, 0x44, 0x06, 0x06], &bytes[..4]);
+    assert_eq!(token, NegotiationToken::parse(&bytes).unwrap());
+
+    let NegotiationToken::NegTokenInit(init) = token else {
+        unreachable!()
+    };
+    let flags = init.req_flags.unwrap();
+    assert!(flags.contains(ContextFlags::MUTUAL));
+    assert!(!flags.contains(ContextFlags::DELEG));
+
+    // Long form lengths in the framing.
+    let framed = InitialContextToken::new(KERBEROS_V5.into(), [0xaa; 300].to_vec());
+    let bytes = framed.to_bytes();
+    assert_eq!(&[0x60, 0x82, 0x01, 0x37, 0x06, 0x09], &bytes[..6]);
+    assert_eq!(framed, InitialContextToken::parse(&bytes).unwrap());
+}
+
+#[test]
+fn accept_incomplete() {
+    // The first reply of a Windows acceptor that has selected Kerberos, and
+    // is waiting for the initiator's mechListMIC.
+    let bytes = [
+        0xa1, 0x14, 0x30, 0x12, 0xa0, 0x03, 0x0a, 0x01, 0x01, 0xa1, 0x0b, 0x06, 0x09, 0x2a, 0x86,
+        0x48, 0x82, 0xf7, 0x12, 0x01, 0x02, 0x02,
+    ];
+    let mut resp = NegTokenResp::new(Some(NegState::AcceptIncomplete), None);
+    resp.supported_mech = Some(MS_KERBEROS_V5.into());
+    let token = NegotiationToken::NegTokenResp(resp);
+
+    assert_eq!(token, NegotiationToken::parse(&bytes).unwrap());
+    assert_eq!(&bytes[..], &token.to_bytes().unwrap()[..]);
+}
+
+#[test]
+fn errors() {
+    let mut truncated = NEG_TOKEN_INIT.to_vec();
+    truncated.pop();
+    assert!(matches!(
+        NegotiationToken::parse(&truncated),
+        Err(TokenError::InvalidFraming)
+    ));
+
+    let framed = InitialContextToken::new(KERBEROS_V5.into(), [0x30, 0x00].to_vec());
+    assert!(matches!(
+        NegotiationToken::parse(&framed.to_bytes()),
+        Err(TokenError::UnexpectedMech(mech)) if mech == KERBEROS_V5
+    ));
+
+    assert!(matches!(
+        InitialContextToken::parse(&[0x60, 0x02, 0x04, 0x00]),
+        Err(TokenError::InvalidFraming)
+    ));
+    // An OID length that overflows when added to its header.
+    assert!(matches!(
+        InitialContextToken::parse(&[
+            0x60, 0x0b, 0x06, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00
+        ]),
+        Err(TokenError::InvalidFraming)
+    ));
+    assert!(matches!(
+        NegotiationToken::parse(&[0xa2, 0x00]),
+        Err(TokenError::Decode(_))
+    ));
+}
+
+fn mechs(mechs: &[&Oid]) -> MechTypeList {
+    mechs.iter().map(|mech| (*mech).into()).collect()
+}
@@ -0,0 +1,75 @@
+use rasn::prelude::*;
+
+fn default_version() -> Integer {
+    Integer::from(1)
+}
+
+#[derive(AsnType, Debug, Decode, Encode, PartialEq)]
+struct Explicit {
+    #[rasn(tag(explicit(0)), default = "default_version")]
+    version: Integer,
+    #[rasn(tag(explicit(1)))]
+    value: bool,
+}
+
+#[derive(AsnType, Debug, Decode, Encode, PartialEq)]
+struct Implicit {
+    #[rasn(tag(0), default = "default_version")]
+    version: Integer,
+    #[rasn(tag(1))]
+    value: bool,
+}
+
+#[test]
+fn explicit_default_is_omitted() {
+    let default = Explicit {
+        version: default_version(),
+        value: true,
+    };
+    let encoded = &[0x30, 0x05, 0xa1, 0x03, 0x01, 0x01, 0xff];
+    assert_eq!(encoded, &*rasn::ber::encode(&default).unwrap());
+    assert_eq!(encoded, &*rasn::der::encode(&default).unwrap());
+    assert_eq!(default, rasn::ber::decode(encoded).unwrap());
+    assert_eq!(default, rasn::der::decode(encoded).unwrap());
+
+    let other = Explicit {
+        version: Integer::from(2),
+        value: true,
+    };
+    let encoded = &[
+        0x30, 0x0a, 0xa0, 0x03, 0x02, 0x01, 0x02, 0xa1, 0x03, 0x01, 0x01, 0xff,
+    ];
+    assert_eq!(encoded, &*rasn::ber::encode(&other).unwrap());
+    assert_eq!(encoded, &*rasn::der::encode(&other).unwrap());
+    assert_eq!(other, rasn::ber::decode(encoded).unwrap());
+    assert_eq!(other, rasn::der::decode(encoded).unwrap());
+
+    // BER senders may still include a value equal to the default.
+    let encoded = &[
+        0x30, 0x0a, 0xa0, 0x03, 0x02, 0x01, 0x01, 0xa1, 0x03, 0x01, 0x01, 0xff,
+    ];
+    assert_eq!(default, rasn::ber::decode(encoded).unwrap());
+}
+
+#[test]
+fn implicit_default_is_omitted() {
+    let default = Implicit {
+        version: default_version(),
+        value: true,
+    };
+    let encoded = &[0x30, 0x03, 0x81, 0x01, 0xff];
+    assert_eq!(encoded, &*rasn::ber::encode(&default).unwrap());
+    assert_eq!(encoded, &*rasn::der::encode(&default).unwrap());
+    assert_eq!(default, rasn::ber::decode(encoded).unwrap());
+    assert_eq!(default, rasn::der::decode(encoded).unwrap());
+
+    let other = Implicit {
+        version: Integer::from(2),
+        value: true,
+    };
+    let encoded = &[0x30, 0x06, 0x80, 0x01, 0x02, 0x81, 0x01, 0xff];
+    assert_eq!(encoded, &*rasn::ber::encode(&other).unwrap());
+    assert_eq!(encoded, &*rasn::der::encode(&other).unwrap());
+    assert_eq!(other, rasn::ber::decode(encoded).unwrap());
+    assert_eq!(other, rasn::der::decode(encoded).unwrap());
+}
@@ -54,3 +54,63 @@ fn it_works() {
     println!("{:?}", bin);
     assert_eq!(data, rasn::ber::decode(&bin).unwrap());
 }
+
+#[derive(AsnType, Decode, Encode, Debug, PartialEq, Clone)]
+#[rasn(choice)]
+enum Syntax {
+    Simple(SimpleSyntax),
+    ApplicationWide(ApplicationSyntax),
+}
+
+#[derive(AsnType, Decode, Encode, Debug, PartialEq, Clone)]
+#[rasn(choice)]
+enum SimpleSyntax {
+    Number(rasn::types::Integer),
+    Text(rasn::types::Utf8String),
+}
+
+#[derive(AsnType, Decode, Encode, Debug, PartialEq, Clone)]
+#[rasn(choice)]
+enum ApplicationSyntax {
+    #[rasn(tag(application, 1))]
+    Counter(u32),
+    #[rasn(tag(application, 2))]
+    Gauge(u32),
+}
+
+#[test]
+fn untagged_choice_in_choice() {
+    let number = Syntax::Simple(SimpleSyntax::Number(5.into()));
+    let gauge = Syntax::ApplicationWide(ApplicationSyntax::Gauge(5));
+
+    assert_eq!(&[0x02, 0x01, 0x05][..], rasn::ber::encode(&number).unwrap());
+    assert_eq!(&[0x42, 0x01, 0x05][..], rasn::ber::encode(&gauge).unwrap());
+    assert_eq!(
+        &[0x00, 0x41, 0x40][..],
+        rasn::uper::encode(&number).unwrap()
+    );
+    assert_eq!(
+        &[0xc0, 0x00, 0x00, 0x01, 0x40][..],
+        rasn::uper::encode(&gauge).unwrap()
+    );
+    assert_eq!(
+        &[0x00, 0x01, 0x05][..],
+        rasn::aper::encode(&number).unwrap()
+    );
+    assert_eq!(&[0xc0, 0x05][..], rasn::aper::encode(&gauge).unwrap());
+    assert_eq!(
+        r#"{"ApplicationWide":{"Gauge":5}}"#,
+        rasn::jer::encode(&gauge).unwrap()
+    );
+
+    for value in [number, gauge] {
+        let ber = rasn::ber::encode(&value).unwrap();
+        assert_eq!(value, rasn::ber::decode::<Syntax>(&ber).unwrap());
+        let uper = rasn::uper::encode(&value).unwrap();
+        assert_eq!(value, rasn::uper::decode::<Syntax>(&uper).unwrap());
+        let aper = rasn::aper::encode(&value).unwrap();
+        assert_eq!(value, rasn::aper::decode::<Syntax>(&aper).unwrap());
+        let jer = rasn::jer::encode(&value).unwrap();
+        assert_eq!(value, rasn::jer::decode::<Syntax>(&jer).unwrap());
+    }
+}